@@ -0,0 +1,67 @@
+//! Theme renderer benchmarks
+//!
+//! 每次刷新都会走一遍主题渲染,这里用一行典型的状态栏(含进度条这类需要
+//! 重新套用背景色的组件)衡量三个主题的耗时:
+//!
+//! ```text
+//! cargo bench --bench themes
+//! ```
+
+use std::hint::black_box;
+use std::sync::Arc;
+
+use claude_code_statusline_pro::components::{
+    ColorSupport, ComponentOutput, RenderContext, TerminalCapabilities,
+};
+use claude_code_statusline_pro::config::{AutoDetect, Config};
+use claude_code_statusline_pro::core::InputData;
+use claude_code_statusline_pro::themes::create_theme_renderer;
+use criterion::{criterion_group, criterion_main, Criterion};
+
+fn context() -> RenderContext {
+    let mut config = Config::default();
+    config.style.enable_colors = AutoDetect::Bool(true);
+    RenderContext {
+        input: Arc::new(InputData::default()),
+        config: Arc::new(config),
+        terminal: TerminalCapabilities {
+            color_support: ColorSupport::TrueColor,
+            supports_emoji: true,
+            supports_nerd_font: true,
+            ..TerminalCapabilities::default()
+        },
+        preview_mode: false,
+        transcript: None,
+        storage: None,
+    }
+}
+
+fn components() -> (Vec<ComponentOutput>, Vec<String>) {
+    let bar = "\x1b[32m██████\x1b[0m\x1b[2m░░░░\x1b[0m 62.4%";
+    let components = vec![
+        ComponentOutput::new("claude-code-statusline-pro").with_icon("\u{f07b}"),
+        ComponentOutput::new("Opus 4.1").with_icon("\u{f085}"),
+        ComponentOutput::new("main \x1b[33m✚2\x1b[0m \x1b[31m●1\x1b[0m").with_icon("\u{e0a0}"),
+        ComponentOutput::new(bar).with_icon("\u{f2db}"),
+        ComponentOutput::new("$1.84").with_icon("\u{f155}"),
+        ComponentOutput::new("Ready").with_icon("\u{f00c}"),
+    ];
+    let colors = ["blue", "cyan", "green", "yellow", "magenta", "bright_green"]
+        .map(str::to_string)
+        .to_vec();
+    (components, colors)
+}
+
+fn bench_themes(c: &mut Criterion) {
+    let ctx = context();
+    let (components, colors) = components();
+    for theme in ["classic", "powerline", "capsule"] {
+        let renderer = create_theme_renderer(theme);
+        c.bench_function(&format!("render_{theme}"), |b| {
+            b.iter(|| renderer.render(black_box(&components), black_box(&colors), &ctx));
+        });
+    }
+}
+
+criterion_group!(benches, bench_themes);
+criterion_main!(benches);
@@ -4,14 +4,22 @@
 //! components must implement, along with common structures used by components.
 
 use crate::{
-    config::{BaseComponentConfig, Config},
-    core::InputData,
+    config::{BaseComponentConfig, Config, Severity},
+    core::{InputData, TranscriptAnalysis},
+    storage::StorageHandle,
+    terminal::graphics::inline_image,
+    utils::sanitize::sanitize_terminal_text,
 };
 use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::path::Path;
 use std::sync::Arc;
 
 /// Terminal color support level
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
 pub enum ColorSupport {
     /// No color support
     None,
@@ -44,6 +52,17 @@ impl ColorSupport {
     }
 }
 
+/// Inline image protocol understood by the terminal
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum GraphicsProtocol {
+    #[default]
+    None,
+    /// kitty graphics protocol (kitty, Ghostty)
+    Kitty,
+    /// iTerm2 inline images (iTerm2, `WezTerm`)
+    Iterm2,
+}
+
 /// Terminal capabilities for rendering decisions
 #[derive(Debug, Clone)]
 pub struct TerminalCapabilities {
@@ -53,6 +72,10 @@ pub struct TerminalCapabilities {
     pub supports_emoji: bool,
     /// Whether terminal supports Nerd Font icons
     pub supports_nerd_font: bool,
+    /// Inline image protocol for `image_icon`
+    pub graphics: GraphicsProtocol,
+    /// Whether `OSC 9;4` progress reports are emitted
+    pub supports_progress: bool,
 }
 
 impl TerminalCapabilities {
@@ -69,6 +92,8 @@ impl Default for TerminalCapabilities {
             color_support: ColorSupport::TrueColor,
             supports_emoji: true,
             supports_nerd_font: false,
+            graphics: GraphicsProtocol::None,
+            supports_progress: false,
         }
     }
 }
@@ -93,10 +118,55 @@ pub struct RenderContext {
     /// "preview 无副作用"的契约。组件看到 `preview_mode = true` 时一律
     /// 返回占位输出。
     pub preview_mode: bool,
+    /// Transcript analysis shared by all components of this render; `None`
+    /// lets [`RenderContext::transcript`] analyze `input.transcript_path` itself
+    pub transcript: Option<Arc<TranscriptAnalysis>>,
+    /// Persisted session state; `None` uses the on-disk storage (an empty
+    /// in-memory one in builds without the `storage` feature)
+    pub storage: Option<Arc<dyn StorageHandle>>,
+}
+
+impl RenderContext {
+    /// Transcript analysis for this render, parsed on demand when the generator
+    /// did not provide one (e.g. components rendered directly in tests).
+    #[must_use]
+    pub fn transcript(&self) -> Option<Arc<TranscriptAnalysis>> {
+        if let Some(transcript) = &self.transcript {
+            return Some(Arc::clone(transcript));
+        }
+        let path = self.input.transcript_path.as_deref()?;
+        TranscriptAnalysis::load(Path::new(path)).map(Arc::new)
+    }
+
+    /// Storage the components of this render read and persist session state in
+    #[must_use]
+    #[cfg(feature = "storage")]
+    pub fn storage(&self) -> &dyn StorageHandle {
+        self.storage
+            .as_deref()
+            .unwrap_or(&crate::storage::DiskStorage)
+    }
+
+    /// Storage the components of this render read and persist session state in
+    #[must_use]
+    #[cfg(not(feature = "storage"))]
+    pub fn storage(&self) -> &dyn StorageHandle {
+        static EMPTY: std::sync::LazyLock<crate::storage::MemoryStorage> =
+            std::sync::LazyLock::new(crate::storage::MemoryStorage::new);
+        self.storage.as_deref().unwrap_or(&*EMPTY)
+    }
+}
+
+/// `text` with control characters neutralized, reusing the allocation when clean
+fn sanitized(text: String) -> String {
+    match sanitize_terminal_text(&text) {
+        Cow::Owned(clean) => clean,
+        Cow::Borrowed(_) => text,
+    }
 }
 
 /// Output from a component
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ComponentOutput {
     /// The rendered text
     pub text: String,
@@ -110,11 +180,35 @@ pub struct ComponentOutput {
     pub component_name: Option<String>,
     /// Whether to show this component (empty/disabled components return None)
     pub visible: bool,
+    /// Extra lines rendered below the main statusline (already wrapped, uncolored)
+    pub detail_lines: Vec<String>,
+    /// Separator drawn before this output in place of the theme's own, set by
+    /// `sep:…` / `space` items in `components.order`
+    pub separator: Option<String>,
+    /// Index into `components.groups`; consecutive outputs of one group share
+    /// a powerline segment / capsule
+    pub group: Option<usize>,
+    /// State severity feeding `style.severity_rules`
+    pub severity: Severity,
+    /// Percentage (0–100) reported to the terminal's progress indicator
+    pub progress: Option<f64>,
 }
 
 impl ComponentOutput {
-    /// Create a new visible component output
+    /// Create a new visible component output.
+    ///
+    /// Control characters in `text` are neutralized, see
+    /// [`sanitize_terminal_text`]; use [`Self::preformatted`] for text carrying
+    /// the component's own escape sequences.
     pub fn new(text: impl Into<String>) -> Self {
+        Self::preformatted(sanitized(text.into()))
+    }
+
+    /// Create a visible output whose text is written to the terminal as is.
+    ///
+    /// 只用于组件自己拼出的终端序列(渐变进度条、OSC 8 超链接),其中来自外部
+    /// 数据的部分必须先经过 [`sanitize_terminal_text`]。
+    pub fn preformatted(text: impl Into<String>) -> Self {
         Self {
             text: text.into(),
             icon: None,
@@ -122,6 +216,11 @@ impl ComponentOutput {
             text_color: None,
             component_name: None,
             visible: true,
+            detail_lines: Vec::new(),
+            separator: None,
+            group: None,
+            severity: Severity::Normal,
+            progress: None,
         }
     }
 
@@ -135,6 +234,11 @@ impl ComponentOutput {
             text_color: None,
             component_name: None,
             visible: false,
+            detail_lines: Vec::new(),
+            separator: None,
+            group: None,
+            severity: Severity::Normal,
+            progress: None,
         }
     }
 
@@ -159,6 +263,13 @@ impl ComponentOutput {
         self
     }
 
+    /// Attach lines rendered below the main statusline
+    #[must_use]
+    pub fn with_detail_lines(mut self, lines: Vec<String>) -> Self {
+        self.detail_lines = lines.into_iter().map(sanitized).collect();
+        self
+    }
+
     /// Attach the originating component name
     #[must_use]
     pub fn with_component_name(mut self, name: impl Into<String>) -> Self {
@@ -166,6 +277,20 @@ impl ComponentOutput {
         self
     }
 
+    /// Set the state severity
+    #[must_use]
+    pub const fn with_severity(mut self, severity: Severity) -> Self {
+        self.severity = severity;
+        self
+    }
+
+    /// Report a percentage to the terminal's progress indicator
+    #[must_use]
+    pub const fn with_progress(mut self, percent: f64) -> Self {
+        self.progress = Some(percent);
+        self
+    }
+
     /// Mutably set the component name
     pub fn set_component_name(&mut self, name: impl Into<String>) {
         self.component_name = Some(name.into());
@@ -187,6 +312,14 @@ pub trait Component: Send + Sync {
     /// Get the base configuration for this component
     fn base_config(&self, ctx: &RenderContext) -> Option<&BaseComponentConfig>;
 
+    /// Hash of everything the output depends on besides config and terminal
+    /// capabilities. When it matches the previous render the generator reuses
+    /// that output; `None` (the default) means "always render" and is the
+    /// right answer for anything reading git, storage or the transcript.
+    fn cache_key(&self, _ctx: &RenderContext) -> Option<u64> {
+        None
+    }
+
     /// Select the appropriate icon based on terminal capabilities
     fn select_icon(&self, ctx: &RenderContext) -> Option<String> {
         let config = self.base_config(ctx)?;
@@ -194,9 +327,16 @@ pub trait Component: Send + Sync {
         let style = &ctx.config.style;
 
         // Check forced modes first
-        if ctx.config.terminal.force_text {
+        if ctx.config.terminal.text_only() {
             return Some(config.text_icon.clone());
         }
+        if let Some(image) = config
+            .image_icon
+            .as_deref()
+            .and_then(|path| inline_image(path, terminal.graphics))
+        {
+            return Some(image);
+        }
         if ctx.config.terminal.force_nerd_font {
             return Some(config.nerd_icon.clone());
         }
@@ -215,6 +355,14 @@ pub trait Component: Send + Sync {
     }
 }
 
+/// Hash any tuple of inputs into a component cache key.
+#[must_use]
+pub fn cache_key_of(value: &impl Hash) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
 /// Factory trait for creating component instances
 pub trait ComponentFactory: Send + Sync {
     /// Create a new instance of the component
@@ -5,12 +5,19 @@
 use std::collections::HashMap;
 use std::fmt::Write as _;
 use std::path::{Path, PathBuf};
-use std::sync::Mutex;
-use std::time::{Duration, Instant};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime};
 
 use super::base::{Component, ComponentFactory, ComponentOutput, RenderContext};
+use super::ticket::TicketExtractor;
+use crate::config::schema::BranchPerformanceConfig;
 use crate::config::{BaseComponentConfig, BranchComponentConfig, Config};
-use crate::git::{GitCollectionOptions, GitInfo, GitService};
+use crate::git::{FetchState, GitInfo};
+#[cfg(feature = "git")]
+use crate::git::{GitCollectionOptions, GitService};
+use crate::utils::i18n::translate;
+use crate::utils::truncate_with_ellipsis;
+use crate::vcs::{builtin_provider, select_provider, VcsProvider, VcsStatus};
 use async_trait::async_trait;
 use tokio::task;
 
@@ -18,17 +25,52 @@ use tokio::task;
 pub struct BranchComponent {
     config: BranchComponentConfig,
     git_cache: Mutex<HashMap<PathBuf, CachedGitEntry>>,
+    /// Status of non-git working copies by root, under the same
+    /// `performance.cache_ttl` as `git_cache`
+    vcs_cache: Mutex<HashMap<PathBuf, CachedVcsEntry>>,
+    /// Set with `ticket.replace_branch`: hide the branch when it holds a ticket
+    ticket: Option<TicketExtractor>,
+    /// Non-git backends, tried before git
+    vcs: Vec<Arc<dyn VcsProvider>>,
 }
 
 impl BranchComponent {
     #[must_use]
     pub fn new(config: BranchComponentConfig) -> Self {
+        let timeout = Duration::from_millis(config.performance.git_timeout.into());
+        let vcs = config
+            .vcs
+            .iter()
+            .filter_map(|name| builtin_provider(name, timeout))
+            .collect();
         Self {
             config,
             git_cache: Mutex::new(HashMap::new()),
+            vcs_cache: Mutex::new(HashMap::new()),
+            ticket: None,
+            vcs,
         }
     }
 
+    /// Add backends for other version control systems next to the ones
+    /// enabled in `components.branch.vcs`
+    #[must_use]
+    pub fn with_vcs_providers(
+        mut self,
+        providers: impl IntoIterator<Item = Arc<dyn VcsProvider>>,
+    ) -> Self {
+        self.vcs.extend(providers);
+        self
+    }
+
+    /// Hide the branch whenever `extractor` finds a ticket id in it, leaving
+    /// the ticket component in its place
+    #[must_use]
+    pub fn with_ticket_replacement(mut self, extractor: TicketExtractor) -> Self {
+        self.ticket = Some(extractor);
+        self
+    }
+
     fn resolve_repo_path(ctx: &RenderContext) -> Option<PathBuf> {
         if let Some(current_dir) = ctx.input.current_dir() {
             return Some(PathBuf::from(current_dir));
@@ -55,24 +97,7 @@ impl BranchComponent {
         let path_for_store = repo_path.clone();
 
         let result = task::spawn_blocking(move || {
-            let service = GitService::discover(repo_path)?;
-
-            let mut options = GitCollectionOptions {
-                include_status,
-                include_stash,
-                include_operation: false,
-                include_version: false,
-            };
-
-            if performance.skip_on_large_repo {
-                let entry_count = service.estimate_workdir_entries() as u64;
-                if entry_count > performance.large_repo_threshold {
-                    options.include_status = false;
-                    options.include_stash = false;
-                }
-            }
-
-            Ok::<GitInfo, anyhow::Error>(service.collect_info_with_options(&options))
+            collect_git_info(&repo_path, include_status, include_stash, &performance)
         })
         .await;
 
@@ -109,19 +134,90 @@ impl BranchComponent {
         }
     }
 
-    fn prepare_branch_name(&self, raw: &str) -> String {
+    /// Status of the working copy at `root` through `provider`, cached like
+    /// [`Self::load_git_info`]
+    async fn load_vcs_status(
+        &self,
+        provider: Arc<dyn VcsProvider>,
+        root: PathBuf,
+    ) -> Option<VcsStatus> {
+        let performance = &self.config.performance;
+        if performance.enable_cache {
+            if let Some(status) = self.cached_vcs_status(&root) {
+                return Some(status);
+            }
+        }
+
+        let name = provider.name();
+        let path_for_store = root.clone();
+        match task::spawn_blocking(move || provider.status(&root)).await {
+            Ok(Ok(status)) => {
+                if performance.enable_cache {
+                    self.store_vcs_status(
+                        path_for_store,
+                        status.clone(),
+                        Duration::from_millis(performance.cache_ttl),
+                    );
+                }
+                Some(status)
+            }
+            Ok(Err(err)) => {
+                eprintln!("[statusline] branch: {name} status failed: {err:#}");
+                None
+            }
+            Err(_) => None,
+        }
+    }
+
+    fn cached_vcs_status(&self, root: &Path) -> Option<VcsStatus> {
+        let mut guard = self.vcs_cache.lock().ok()?;
+        if let Some(entry) = guard.get(root) {
+            if entry.expires_at > Instant::now() {
+                return Some(entry.status.clone());
+            }
+        }
+        guard.remove(root);
+        None
+    }
+
+    fn store_vcs_status(&self, root: PathBuf, status: VcsStatus, ttl: Duration) {
+        if ttl.is_zero() {
+            return;
+        }
+        let expires_at = Instant::now() + ttl;
+        if let Ok(mut guard) = self.vcs_cache.lock() {
+            guard.insert(root, CachedVcsEntry { expires_at, status });
+        }
+    }
+
+    /// Shown branch name and its text color: the first matching
+    /// `prefix_rules` entry colors it and may strip the prefix, then the name
+    /// is truncated to `max_length`
+    fn prepare_branch_name(&self, raw: &str) -> BranchName {
+        let replaced = self
+            .ticket
+            .as_ref()
+            .is_some_and(|ticket| ticket.extract(raw).is_some());
         let max_len = self.config.max_length.max(3) as usize;
-        if raw.len() > max_len {
-            let mut truncated = raw.chars().take(max_len - 3).collect::<String>();
-            truncated.push_str("...");
-            truncated
-        } else {
-            raw.to_string()
+        let matched = self
+            .config
+            .prefix_rules
+            .iter()
+            .find_map(|rule| rule.strip_from(raw).map(|rest| (rule, rest)));
+        let (name, color) = match matched {
+            Some((rule, rest)) if rule.strip && !rest.is_empty() => (rest, rule.color.clone()),
+            Some((rule, _)) => (raw, rule.color.clone()),
+            None => (raw, None),
+        };
+        BranchName {
+            text: truncate_with_ellipsis(name, max_len, "..."),
+            color,
+            replaced,
         }
     }
 
     /// Get the branch name and status from stdin fallback
-    fn get_branch_info(&self, ctx: &RenderContext) -> Option<(String, BranchStatus)> {
+    fn get_branch_info(&self, ctx: &RenderContext) -> Option<(BranchName, BranchStatus)> {
         let branch_name = ctx.input.branch()?;
 
         let mut status = BranchStatus::default();
@@ -141,13 +237,53 @@ impl BranchComponent {
             }
         }
 
+        status.fetch_stale = self.check_fetch(ctx);
         Some((self.prepare_branch_name(branch_name), status))
     }
 
+    /// Whether ahead/behind is older than `fetch_stale_minutes`; also starts
+    /// the rate-limited background fetch when `auto_fetch_minutes` is set
+    fn check_fetch(&self, ctx: &RenderContext) -> bool {
+        let status = &self.config.status;
+        let show_stale = status.show_ahead_behind && status.fetch_stale_minutes > 0;
+        let auto_fetch = status.auto_fetch_minutes > 0 && !ctx.preview_mode;
+        if !show_stale && !auto_fetch {
+            return false;
+        }
+        let Some(repo_path) = Self::resolve_repo_path(ctx) else {
+            return false;
+        };
+        let Some(state) = FetchState::load(&repo_path) else {
+            return false;
+        };
+
+        let now = SystemTime::now();
+        let interval = Duration::from_secs(status.auto_fetch_minutes.saturating_mul(60));
+        if auto_fetch && state.fetch_due(interval, now) {
+            if let Err(err) = state.spawn_fetch(&repo_path) {
+                eprintln!("[statusline] branch: background fetch failed: {err:#}");
+            }
+        }
+        show_stale
+            && state.is_stale(
+                Duration::from_secs(status.fetch_stale_minutes.saturating_mul(60)),
+                now,
+            )
+    }
+
     /// Format branch display with status indicators
     fn format_branch(&self, name: String, status: &BranchStatus, ctx: &RenderContext) -> String {
         let mut result = name;
 
+        if status.sparse {
+            result.push_str(Self::select_status_icon(
+                ctx,
+                &self.config.status_icons.sparse_emoji,
+                &self.config.status_icons.sparse_nerd,
+                &self.config.status_icons.sparse_text,
+            ));
+        }
+
         // Add status indicators
         if status.is_dirty {
             let icon = Self::select_status_icon(
@@ -179,6 +315,15 @@ impl BranchComponent {
             let _ = write!(&mut result, "{}{}", icon, status.behind);
         }
 
+        if status.fetch_stale {
+            result.push_str(Self::select_status_icon(
+                ctx,
+                &self.config.status_icons.stale_emoji,
+                &self.config.status_icons.stale_nerd,
+                &self.config.status_icons.stale_text,
+            ));
+        }
+
         if status.stash_count > 0 {
             let icon = Self::select_status_icon(
                 ctx,
@@ -211,7 +356,7 @@ impl BranchComponent {
         let terminal_cfg = &ctx.config.terminal;
         let style = &ctx.config.style;
 
-        if terminal_cfg.force_text {
+        if terminal_cfg.text_only() {
             return text_icon;
         }
         if terminal_cfg.force_nerd_font {
@@ -237,12 +382,26 @@ impl BranchComponent {
     }
 }
 
+/// Branch name after `prefix_rules` and truncation
+#[derive(Debug)]
+struct BranchName {
+    text: String,
+    /// Text color from the matching prefix rule
+    color: Option<String>,
+    /// The ticket component shows this branch instead
+    replaced: bool,
+}
+
 #[derive(Debug, Default)]
 struct BranchStatus {
     is_dirty: bool,
     ahead: i32,
     behind: i32,
     stash_count: i32,
+    /// Last fetch older than `fetch_stale_minutes`
+    fetch_stale: bool,
+    /// Sparse checkout or partial clone: counts cover only part of the repo
+    sparse: bool,
 }
 
 #[async_trait]
@@ -260,12 +419,20 @@ impl Component for BranchComponent {
             return ComponentOutput::hidden();
         }
 
+        // 非 git 后端(jj、hg…)管理的工作区优先;stdin 的 git 字段描述的是 git 视角
+        if let Some(output) = self.render_from_vcs(ctx).await {
+            return output;
+        }
+
         // 优先尝试从stdin输入获取分支信息(适用于有git字段的情况)
         if self.config.performance.lazy_load_status {
             if let Some((name, status)) = self.get_branch_info(ctx) {
-                let formatted = self.format_branch(name, &status, ctx);
-                let color = self.get_branch_color(&status).to_string();
-                return self.build_output(ctx, formatted, color);
+                if name.replaced {
+                    return ComponentOutput::hidden();
+                }
+                let formatted = self.format_branch(name.text, &status, ctx);
+                let color = self.get_branch_color(&status);
+                return self.build_output(ctx, formatted, color, name.color.as_deref());
             }
             // 如果stdin中没有git信息，继续往下通过libgit2获取
         }
@@ -285,15 +452,22 @@ impl Component for BranchComponent {
             return ComponentOutput::hidden();
         }
 
-        let (text, icon_color) = if let Some((name, status)) = branch_info {
-            let formatted = self.format_branch(name, &status, ctx);
+        let (text, icon_color, text_color) = if let Some((name, status)) = branch_info {
+            if name.replaced {
+                return ComponentOutput::hidden();
+            }
+            let formatted = self.format_branch(name.text, &status, ctx);
             let color = self.get_branch_color(&status).to_string();
-            (formatted, color)
+            (formatted, color, name.color)
         } else {
-            ("no-git".to_string(), self.config.base.icon_color.clone())
+            (
+                translate(&ctx.config, "no-git").to_string(),
+                self.config.base.icon_color.clone(),
+                None,
+            )
         };
 
-        self.build_output(ctx, text, icon_color)
+        self.build_output(ctx, text, &icon_color, text_color.as_deref())
     }
 
     fn base_config(&self, _ctx: &RenderContext) -> Option<&BaseComponentConfig> {
@@ -306,13 +480,18 @@ impl BranchComponent {
         &self,
         ctx: &RenderContext,
         text: String,
-        icon_color: String,
+        icon_color: &str,
+        text_color: Option<&str>,
     ) -> ComponentOutput {
         let icon = self.select_icon(ctx);
         ComponentOutput::new(text)
             .with_icon(icon.unwrap_or_default())
-            .with_icon_color(icon_color)
-            .with_text_color(&self.config.base.text_color)
+            .with_icon_color(ctx.config.palette.resolve(icon_color))
+            .with_text_color(
+                ctx.config
+                    .palette
+                    .resolve(text_color.unwrap_or(&self.config.base.text_color)),
+            )
     }
 
     fn render_no_git(&self, ctx: &RenderContext) -> ComponentOutput {
@@ -322,23 +501,61 @@ impl BranchComponent {
 
         self.build_output(
             ctx,
-            "no-git".to_string(),
-            self.config.base.icon_color.clone(),
+            translate(&ctx.config, "no-git").to_string(),
+            &self.config.base.icon_color,
+            None,
         )
     }
 
+    /// Render through the backend managing the working copy, when it is not
+    /// git; `None` falls back to git
+    async fn render_from_vcs(&self, ctx: &RenderContext) -> Option<ComponentOutput> {
+        if self.vcs.is_empty() {
+            return None;
+        }
+        let dir = Self::resolve_repo_path(ctx)?;
+        let (provider, root) = select_provider(&self.vcs, &dir)?;
+        let vcs_status = self.load_vcs_status(provider, root).await?;
+
+        let mut status = BranchStatus {
+            is_dirty: self.config.status.show_dirty && vcs_status.dirty == Some(true),
+            ..BranchStatus::default()
+        };
+        if self.config.status.show_ahead_behind {
+            status.ahead = Self::usize_to_i32(vcs_status.ahead);
+            status.behind = Self::usize_to_i32(vcs_status.behind);
+        }
+        let branch_name = self.prepare_branch_name(&vcs_status.branch);
+        if branch_name.replaced {
+            return Some(ComponentOutput::hidden());
+        }
+        let text = self.format_branch(branch_name.text, &status, ctx);
+        let icon_color = self.get_branch_color(&status);
+        Some(self.build_output(ctx, text, icon_color, branch_name.color.as_deref()))
+    }
+
     fn render_from_git_info(&self, ctx: &RenderContext, info: &GitInfo) -> ComponentOutput {
         let mut status = BranchStatus::default();
         status.is_dirty = !info.status.clean;
         status.ahead = Self::usize_to_i32(info.branch.ahead);
         status.behind = Self::usize_to_i32(info.branch.behind);
         status.stash_count = Self::usize_to_i32(info.stash.count);
+        status.fetch_stale = self.check_fetch(ctx);
+        status.sparse = info.sparse || info.partial_clone;
 
         let branch_name = self.prepare_branch_name(&info.branch.current);
-        let text = self.format_branch(branch_name, &status, ctx);
-        let icon_color = self.get_branch_color(&status).to_string();
+        if branch_name.replaced {
+            return ComponentOutput::hidden();
+        }
+        let name = if info.bare {
+            format!("bare:{}", branch_name.text)
+        } else {
+            branch_name.text
+        };
+        let text = self.format_branch(name, &status, ctx);
+        let icon_color = self.get_branch_color(&status);
 
-        self.build_output(ctx, text, icon_color)
+        self.build_output(ctx, text, icon_color, branch_name.color.as_deref())
     }
 }
 
@@ -357,7 +574,13 @@ pub struct BranchComponentFactory;
 
 impl ComponentFactory for BranchComponentFactory {
     fn create(&self, config: &Config) -> Box<dyn Component> {
-        Box::new(BranchComponent::new(config.components.branch.clone()))
+        let component = BranchComponent::new((*config.components.branch).clone());
+        let ticket = &config.components.ticket;
+        match TicketExtractor::new(ticket).filter(|_| ticket.base.enabled && ticket.replace_branch)
+        {
+            Some(extractor) => Box::new(component.with_ticket_replacement(extractor)),
+            None => Box::new(component),
+        }
     }
 
     fn name(&self) -> &'static str {
@@ -365,16 +588,59 @@ impl ComponentFactory for BranchComponentFactory {
     }
 }
 
+/// Read repository state through libgit2
+#[cfg(feature = "git")]
+fn collect_git_info(
+    repo_path: &Path,
+    include_status: bool,
+    include_stash: bool,
+    performance: &BranchPerformanceConfig,
+) -> anyhow::Result<GitInfo> {
+    let service = GitService::discover(repo_path)?;
+
+    let mut options = GitCollectionOptions::branch_only()
+        .with_status(include_status)
+        .with_stash(include_stash);
+
+    if performance.skip_on_large_repo {
+        let entry_count = service.estimate_workdir_entries() as u64;
+        if entry_count > performance.large_repo_threshold {
+            options.include_status = false;
+            options.include_stash = false;
+        }
+    }
+
+    Ok(service.collect_info_with_options(&options))
+}
+
+/// Without the `git` feature only the stdin `git` field is available
+#[cfg(not(feature = "git"))]
+fn collect_git_info(
+    _repo_path: &Path,
+    _include_status: bool,
+    _include_stash: bool,
+    _performance: &BranchPerformanceConfig,
+) -> anyhow::Result<GitInfo> {
+    anyhow::bail!("built without the `git` feature")
+}
+
 #[derive(Clone)]
 struct CachedGitEntry {
     expires_at: Instant,
     info: GitInfo,
 }
 
+#[derive(Clone)]
+struct CachedVcsEntry {
+    expires_at: Instant,
+    status: VcsStatus,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::components::TerminalCapabilities;
+    use crate::config::BranchPrefixRule;
     use crate::core::{GitInfo, InputData, WorkspaceInfo, WorktreeInfo};
     use std::sync::Arc;
 
@@ -412,6 +678,8 @@ mod tests {
             config: Arc::new(Config::default()),
             terminal: TerminalCapabilities::default(),
             preview_mode: false,
+            transcript: None,
+            storage: None,
         }
     }
 
@@ -466,6 +734,8 @@ mod tests {
             config: Arc::new(Config::default()),
             terminal: TerminalCapabilities::default(),
             preview_mode: false,
+            transcript: None,
+            storage: None,
         };
 
         let output = component.render(&ctx).await;
@@ -505,6 +775,8 @@ mod tests {
             config: Arc::new(Config::default()),
             terminal: TerminalCapabilities::default(),
             preview_mode: false,
+            transcript: None,
+            storage: None,
         };
 
         let component = BranchComponent::new(config);
@@ -512,6 +784,232 @@ mod tests {
         assert_eq!(output.text, "ver...");
     }
 
+    #[tokio::test]
+    async fn test_branch_prefix_rules_color_and_strip() {
+        let config = build_branch_config(|config| {
+            config.max_length = 20;
+            config.prefix_rules = vec![
+                BranchPrefixRule {
+                    prefix: "feature/*".to_string(),
+                    color: Some("green".to_string()),
+                    strip: true,
+                },
+                BranchPrefixRule {
+                    prefix: "hotfix/".to_string(),
+                    color: Some("red".to_string()),
+                    strip: false,
+                },
+            ];
+        });
+        let component = BranchComponent::new(config);
+
+        let output = component
+            .render(&create_test_context_with_git("feature/ABC-123-x", 0, 0))
+            .await;
+        assert_eq!(output.text, "ABC-123-x");
+        assert_eq!(output.text_color.as_deref(), Some("green"));
+
+        let output = component
+            .render(&create_test_context_with_git("hotfix/login", 0, 0))
+            .await;
+        assert_eq!(output.text, "hotfix/login");
+        assert_eq!(output.text_color.as_deref(), Some("red"));
+
+        let output = component
+            .render(&create_test_context_with_git("main", 0, 0))
+            .await;
+        assert_eq!(output.text, "main");
+        assert_eq!(output.text_color.as_deref(), Some("white"));
+    }
+
+    #[tokio::test]
+    async fn test_branch_replaced_by_ticket() {
+        let mut config = Config::default();
+        config.components.ticket.replace_branch = true;
+        let component = BranchComponentFactory.create(&config);
+
+        let output = component
+            .render(&create_test_context_with_git("feature/ENG-42-login", 0, 0))
+            .await;
+        assert!(!output.visible);
+        let output = component
+            .render(&create_test_context_with_git("main", 0, 0))
+            .await;
+        assert_eq!(output.text, "main");
+
+        config.components.ticket.base.enabled = false;
+        let output = BranchComponentFactory
+            .create(&config)
+            .render(&create_test_context_with_git("feature/ENG-42-login", 0, 0))
+            .await;
+        assert!(output.visible);
+    }
+
+    #[tokio::test]
+    async fn test_branch_marks_stale_fetch() -> anyhow::Result<()> {
+        let repo = tempfile::tempdir()?;
+        std::fs::create_dir(repo.path().join(".git"))?;
+        std::fs::write(
+            repo.path().join(".git/config"),
+            "[remote \"origin\"]\n\turl = git@github.com:org/repo.git\n",
+        )?;
+        let config = build_branch_config(|config| {
+            config.status.show_ahead_behind = true;
+            config.status.fetch_stale_minutes = 60;
+        });
+        let mut ctx = create_test_context_with_git("main", 1, 0);
+        ctx.input = Arc::new(InputData {
+            cwd: Some(repo.path().to_string_lossy().into_owned()),
+            ..(*ctx.input).clone()
+        });
+        let component = BranchComponent::new(config);
+
+        // 从未 fetch 过
+        let output = component.render(&ctx).await;
+        assert_eq!(output.text, "main🔼1⏳");
+
+        std::fs::write(repo.path().join(".git/FETCH_HEAD"), "")?;
+        let output = component.render(&ctx).await;
+        assert_eq!(output.text, "main🔼1");
+        Ok(())
+    }
+
+    #[cfg(feature = "git")]
+    #[tokio::test]
+    async fn test_branch_bare_repository() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        git2::Repository::init_bare(dir.path())?.set_head("refs/heads/main")?;
+        let config = build_branch_config(|config| {
+            config.status.show_dirty = true;
+            config.status.show_ahead_behind = true;
+        });
+        let ctx = RenderContext {
+            input: Arc::new(build_input(|input| {
+                input.cwd = Some(dir.path().to_string_lossy().into_owned());
+            })),
+            config: Arc::new(Config::default()),
+            terminal: TerminalCapabilities::default(),
+            preview_mode: false,
+            transcript: None,
+            storage: None,
+        };
+
+        let output = BranchComponent::new(config).render(&ctx).await;
+        assert_eq!(output.text, "bare:main");
+        Ok(())
+    }
+
+    struct FakeVcs;
+
+    impl VcsProvider for FakeVcs {
+        fn name(&self) -> &'static str {
+            "fake"
+        }
+
+        fn marker(&self) -> &'static str {
+            ".fake"
+        }
+
+        fn status(&self, _root: &Path) -> anyhow::Result<VcsStatus> {
+            Ok(VcsStatus {
+                branch: "trunk".to_string(),
+                dirty: Some(true),
+                ahead: 2,
+                behind: 0,
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_branch_uses_vcs_providers() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        std::fs::create_dir_all(dir.path().join("hg/.hg"))?;
+        std::fs::write(dir.path().join("hg/.hg/bookmarks.current"), "feature-x")?;
+        std::fs::create_dir_all(dir.path().join("fake/.fake"))?;
+        let context = |repo: &str| {
+            let mut ctx = create_test_context_with_git("main", 0, 0);
+            ctx.input = Arc::new(InputData {
+                cwd: Some(dir.path().join(repo).to_string_lossy().into_owned()),
+                ..(*ctx.input).clone()
+            });
+            ctx
+        };
+        let config = build_branch_config(|config| {
+            config.status.show_dirty = true;
+            config.status.show_ahead_behind = true;
+        });
+
+        let component = BranchComponent::new(config.clone());
+        assert_eq!(component.render(&context("hg")).await.text, "feature-x");
+        // 未注册的后端:回退到 stdin 的 git 信息
+        assert_eq!(component.render(&context("fake")).await.text, "main");
+
+        let component = component.with_vcs_providers([Arc::new(FakeVcs) as Arc<dyn VcsProvider>]);
+        assert_eq!(component.render(&context("fake")).await.text, "trunk⚡🔼2");
+
+        let git_only = BranchComponent::new(BranchComponentConfig {
+            vcs: Vec::new(),
+            ..config
+        });
+        assert_eq!(git_only.render(&context("hg")).await.text, "main");
+        Ok(())
+    }
+
+    /// Counts how often the working copy state is read
+    struct CountingVcs(Arc<std::sync::atomic::AtomicUsize>);
+
+    impl VcsProvider for CountingVcs {
+        fn name(&self) -> &'static str {
+            "counting"
+        }
+
+        fn marker(&self) -> &'static str {
+            ".counting"
+        }
+
+        fn status(&self, _root: &Path) -> anyhow::Result<VcsStatus> {
+            self.0.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            Ok(VcsStatus {
+                branch: "trunk".to_string(),
+                ..VcsStatus::default()
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_branch_caches_vcs_status() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        std::fs::create_dir_all(dir.path().join(".counting"))?;
+        let mut ctx = create_test_context_with_git("main", 0, 0);
+        ctx.input = Arc::new(InputData {
+            cwd: Some(dir.path().to_string_lossy().into_owned()),
+            ..(*ctx.input).clone()
+        });
+        let renders = |enable_cache: bool| {
+            let calls = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+            let config = build_branch_config(|config| {
+                config.vcs = Vec::new();
+                config.performance.enable_cache = enable_cache;
+            });
+            let component = BranchComponent::new(config)
+                .with_vcs_providers([Arc::new(CountingVcs(calls.clone())) as Arc<dyn VcsProvider>]);
+            (component, calls)
+        };
+
+        let (component, calls) = renders(true);
+        for _ in 0..3 {
+            assert_eq!(component.render(&ctx).await.text, "trunk");
+        }
+        assert_eq!(calls.load(std::sync::atomic::Ordering::SeqCst), 1);
+
+        let (component, calls) = renders(false);
+        for _ in 0..3 {
+            component.render(&ctx).await;
+        }
+        assert_eq!(calls.load(std::sync::atomic::Ordering::SeqCst), 3);
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_branch_show_when_no_git() {
         let config = build_branch_config(|config| {
@@ -527,6 +1025,8 @@ mod tests {
             config: Arc::new(Config::default()),
             terminal: TerminalCapabilities::default(),
             preview_mode: false,
+            transcript: None,
+            storage: None,
         };
 
         let component = BranchComponent::new(config);
@@ -571,6 +1071,8 @@ mod tests {
             config: Arc::new(Config::default()),
             terminal: TerminalCapabilities::default(),
             preview_mode: false,
+            transcript: None,
+            storage: None,
         };
 
         let resolved = BranchComponent::resolve_repo_path(&ctx);
@@ -0,0 +1,183 @@
+//! Generic input field component implementation.
+//!
+//! Displays any value of the stdin JSON addressed by a JSON pointer
+//! (`pointer = "/output_style/name"`), so fields newly sent by Claude Code can
+//! be shown before a dedicated component exists. Hidden when the pointer does
+//! not resolve or the value is empty.
+
+use super::base::{cache_key_of, Component, ComponentFactory, ComponentOutput, RenderContext};
+use crate::config::{BaseComponentConfig, Config, FieldComponentConfig};
+use async_trait::async_trait;
+use serde_json::Value;
+
+/// Generic input field component.
+pub struct FieldComponent {
+    config: FieldComponentConfig,
+}
+
+impl FieldComponent {
+    #[must_use]
+    pub const fn new(config: FieldComponentConfig) -> Self {
+        Self { config }
+    }
+
+    /// Display text of the addressed value
+    fn field_value(&self, ctx: &RenderContext) -> Option<String> {
+        let pointer = self.config.pointer.trim();
+        if pointer.is_empty() {
+            return None;
+        }
+
+        // 已知字段被解析进 InputData 的具名成员,只有未知字段留在 extra 里,
+        // 因此按完整的输入重新序列化后再查找
+        let raw = serde_json::to_value(ctx.input.as_ref()).ok()?;
+        let text = match raw.pointer(pointer)? {
+            Value::Null => return None,
+            Value::String(text) => text.trim().to_string(),
+            Value::Array(items) => items
+                .iter()
+                .map(|item| {
+                    item.as_str()
+                        .map_or_else(|| item.to_string(), str::to_string)
+                })
+                .collect::<Vec<_>>()
+                .join(","),
+            other => other.to_string(),
+        };
+        (!text.is_empty()).then_some(text)
+    }
+
+    #[allow(clippy::literal_string_with_formatting_args)] // placeholders are user template syntax
+    fn format_text(&self, value: &str) -> String {
+        self.config
+            .format
+            .replace("{label}", self.config.label.trim())
+            .replace("{value}", value)
+            .trim()
+            .to_string()
+    }
+}
+
+#[async_trait]
+impl Component for FieldComponent {
+    fn name(&self) -> &'static str {
+        "field"
+    }
+
+    fn is_enabled(&self, _ctx: &RenderContext) -> bool {
+        self.config.base.enabled
+    }
+
+    async fn render(&self, ctx: &RenderContext) -> ComponentOutput {
+        if !self.is_enabled(ctx) {
+            return ComponentOutput::hidden();
+        }
+
+        let Some(value) = self.field_value(ctx) else {
+            return ComponentOutput::hidden();
+        };
+
+        ComponentOutput::new(self.format_text(&value))
+            .with_icon(self.select_icon(ctx).unwrap_or_default())
+            .with_icon_color(&self.config.base.icon_color)
+            .with_text_color(&self.config.base.text_color)
+    }
+
+    fn base_config(&self, _ctx: &RenderContext) -> Option<&BaseComponentConfig> {
+        Some(&self.config.base)
+    }
+
+    fn cache_key(&self, ctx: &RenderContext) -> Option<u64> {
+        Some(cache_key_of(&self.field_value(ctx)))
+    }
+}
+
+/// Factory for creating input field components.
+pub struct FieldComponentFactory;
+
+impl ComponentFactory for FieldComponentFactory {
+    fn create(&self, config: &Config) -> Box<dyn Component> {
+        Box::new(FieldComponent::new((*config.components.field).clone()))
+    }
+
+    fn name(&self) -> &'static str {
+        "field"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::TerminalCapabilities;
+    use crate::core::input::InputData;
+    use anyhow::Result;
+    use std::sync::Arc;
+
+    fn context(raw: &Value) -> Result<RenderContext> {
+        Ok(RenderContext {
+            input: Arc::new(serde_json::from_value::<InputData>(raw.clone())?),
+            config: Arc::new(Config::default()),
+            terminal: TerminalCapabilities::default(),
+            preview_mode: false,
+            transcript: None,
+            storage: None,
+        })
+    }
+
+    fn component(pointer: &str, label: &str) -> FieldComponent {
+        FieldComponent::new(FieldComponentConfig {
+            pointer: pointer.to_string(),
+            label: label.to_string(),
+            ..FieldComponentConfig::default()
+        })
+    }
+
+    #[tokio::test]
+    async fn field_reads_unknown_and_known_input_fields() -> Result<()> {
+        let ctx = context(&serde_json::json!({
+            "model": {"id": "claude-opus-4-1-20250805"},
+            "output_style": {"name": "Explanatory"},
+            "tags": ["a", 1]
+        }))?;
+
+        let output = component("/output_style/name", "style").render(&ctx).await;
+        assert!(output.visible);
+        assert_eq!(output.text, "style Explanatory");
+
+        let output = component("/model/id", "").render(&ctx).await;
+        assert_eq!(output.text, "claude-opus-4-1-20250805");
+
+        let output = component("/tags", "").render(&ctx).await;
+        assert_eq!(output.text, "a,1");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn field_hidden_when_missing_or_empty() -> Result<()> {
+        let ctx = context(&serde_json::json!({"output_style": {"name": " "}}))?;
+
+        assert!(
+            !component("/output_style/name", "style")
+                .render(&ctx)
+                .await
+                .visible
+        );
+        assert!(!component("/missing", "").render(&ctx).await.visible);
+        assert!(!component("", "").render(&ctx).await.visible);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn field_format_template_places_label_and_value() -> Result<()> {
+        let ctx = context(&serde_json::json!({"version": "2.0.1"}))?;
+        let component = FieldComponent::new(FieldComponentConfig {
+            pointer: "/version".to_string(),
+            label: "cc".to_string(),
+            format: "{label}@{value}".to_string(),
+            ..FieldComponentConfig::default()
+        });
+
+        assert_eq!(component.render(&ctx).await.text, "cc@2.0.1");
+        Ok(())
+    }
+}
@@ -0,0 +1,141 @@
+//! Hook event component implementation.
+//!
+//! Displays the hook that triggered the statusline refresh
+//! (`InputData.hook_event_name`, e.g. `PreToolUse`, `Stop`). Useful when
+//! building hook-heavy workflows; hidden when the field is absent.
+
+use super::base::{cache_key_of, Component, ComponentFactory, ComponentOutput, RenderContext};
+use crate::config::{BaseComponentConfig, Config, HookComponentConfig};
+use async_trait::async_trait;
+
+/// Hook event component.
+pub struct HookComponent {
+    config: HookComponentConfig,
+}
+
+impl HookComponent {
+    #[must_use]
+    pub const fn new(config: HookComponentConfig) -> Self {
+        Self { config }
+    }
+
+    fn is_hidden_event(&self, event: &str) -> bool {
+        self.config
+            .hidden_events
+            .iter()
+            .any(|hidden| hidden.eq_ignore_ascii_case(event))
+    }
+
+    fn event_color(&self, event: &str) -> &str {
+        self.config
+            .event_colors
+            .get(event)
+            .unwrap_or(&self.config.base.text_color)
+    }
+}
+
+#[async_trait]
+impl Component for HookComponent {
+    fn name(&self) -> &'static str {
+        "hook"
+    }
+
+    fn is_enabled(&self, _ctx: &RenderContext) -> bool {
+        self.config.base.enabled
+    }
+
+    async fn render(&self, ctx: &RenderContext) -> ComponentOutput {
+        if !self.is_enabled(ctx) {
+            return ComponentOutput::hidden();
+        }
+
+        let Some(event) = ctx
+            .input
+            .hook_event_name
+            .as_deref()
+            .map(str::trim)
+            .filter(|event| !event.is_empty())
+        else {
+            return ComponentOutput::hidden();
+        };
+
+        if self.is_hidden_event(event) {
+            return ComponentOutput::hidden();
+        }
+
+        ComponentOutput::new(event)
+            .with_icon(self.select_icon(ctx).unwrap_or_default())
+            .with_icon_color(&self.config.base.icon_color)
+            .with_text_color(self.event_color(event))
+    }
+
+    fn base_config(&self, _ctx: &RenderContext) -> Option<&BaseComponentConfig> {
+        Some(&self.config.base)
+    }
+
+    fn cache_key(&self, ctx: &RenderContext) -> Option<u64> {
+        Some(cache_key_of(&ctx.input.hook_event_name))
+    }
+}
+
+/// Factory for creating hook event components.
+pub struct HookComponentFactory;
+
+impl ComponentFactory for HookComponentFactory {
+    fn create(&self, config: &Config) -> Box<dyn Component> {
+        Box::new(HookComponent::new((*config.components.hook).clone()))
+    }
+
+    fn name(&self) -> &'static str {
+        "hook"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::TerminalCapabilities;
+    use crate::core::input::InputData;
+    use std::sync::Arc;
+
+    fn context(hook_event_name: Option<&str>) -> RenderContext {
+        RenderContext {
+            input: Arc::new(InputData {
+                hook_event_name: hook_event_name.map(str::to_string),
+                ..InputData::default()
+            }),
+            config: Arc::new(Config::default()),
+            terminal: TerminalCapabilities::default(),
+            preview_mode: false,
+            transcript: None,
+            storage: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn hook_hidden_without_event_or_for_status_refresh() {
+        let component = HookComponent::new(HookComponentConfig::default());
+
+        assert!(!component.render(&context(None)).await.visible);
+        assert!(!component.render(&context(Some(""))).await.visible);
+        assert!(!component.render(&context(Some("Status"))).await.visible);
+    }
+
+    #[tokio::test]
+    async fn hook_renders_event_with_configured_color() {
+        let mut config = HookComponentConfig::default();
+        config
+            .event_colors
+            .insert("PreToolUse".to_string(), "yellow".to_string());
+        let component = HookComponent::new(config);
+
+        let output = component.render(&context(Some("PreToolUse"))).await;
+        assert!(output.visible);
+        assert_eq!(output.text, "PreToolUse");
+        assert_eq!(output.text_color.as_deref(), Some("yellow"));
+
+        let output = component.render(&context(Some("Stop"))).await;
+        assert_eq!(output.text, "Stop");
+        assert_eq!(output.text_color.as_deref(), Some("white"));
+    }
+}
@@ -4,21 +4,40 @@
 
 pub mod base;
 pub mod branch;
+pub mod field;
+pub mod hook;
 pub mod model;
 pub mod project;
+pub mod quota;
 pub mod rate_limit;
+pub mod registry;
+pub mod repo;
+pub mod session;
 pub mod status;
+pub mod text;
+pub mod ticket;
 pub mod tokens;
+pub mod trust;
 pub mod usage;
 
 // Re-export commonly used types
 pub use base::{
-    ColorSupport, Component, ComponentFactory, ComponentOutput, RenderContext, TerminalCapabilities,
+    cache_key_of, ColorSupport, Component, ComponentFactory, ComponentOutput, GraphicsProtocol,
+    RenderContext, TerminalCapabilities,
 };
 pub use branch::{BranchComponent, BranchComponentFactory};
+pub use field::{FieldComponent, FieldComponentFactory};
+pub use hook::{HookComponent, HookComponentFactory};
 pub use model::{ModelComponent, ModelComponentFactory};
 pub use project::{ProjectComponent, ProjectComponentFactory};
+pub use quota::{QuotaComponent, QuotaComponentFactory};
 pub use rate_limit::{RateLimitComponent, RateLimitComponentFactory};
+pub use registry::{ComponentRegistry, BUILTIN_COMPONENTS};
+pub use repo::{RepoComponent, RepoComponentFactory};
+pub use session::{SessionComponent, SessionComponentFactory};
 pub use status::{StatusComponent, StatusComponentFactory};
+pub use text::{TextComponent, TextComponentFactory};
+pub use ticket::{TicketComponent, TicketComponentFactory, TicketExtractor};
 pub use tokens::{TokensComponent, TokensComponentFactory};
-pub use usage::{UsageComponent, UsageComponentFactory};
+pub use trust::{TrustComponent, TrustComponentFactory};
+pub use usage::{ContextCostProvider, CostProvider, UsageComponent, UsageComponentFactory};
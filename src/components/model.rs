@@ -1,12 +1,16 @@
 //! Model component implementation
 //!
-//! Displays the AI model name with optional custom mappings.
+//! Displays the AI model name with optional custom mappings, colored by model
+//! series and optionally with a provider glyph.
 
-use super::base::{Component, ComponentFactory, ComponentOutput, RenderContext};
+use super::base::{cache_key_of, Component, ComponentFactory, ComponentOutput, RenderContext};
+use super::tokens::resolve_context_window;
 use crate::config::{BaseComponentConfig, Config, ModelComponentConfig};
-use crate::utils::effort::resolve_effort_level;
-use crate::utils::model_parser::parse_model_id;
+use crate::storage::ModelSwitchState;
+use crate::utils::effort::{resolve_effort_level, EffortLevel};
+use crate::utils::model_parser::{capitalize, parse_model_id};
 use async_trait::async_trait;
+use std::collections::HashMap;
 
 /// Model component
 pub struct ModelComponent {
@@ -53,6 +57,135 @@ impl ModelComponent {
         // No ID available, try display_name
         model.display_name.clone()
     }
+
+    /// Resolved context window when the badge is enabled
+    fn context_window(&self, ctx: &RenderContext) -> Option<u64> {
+        self.config
+            .show_context_window
+            .then(|| resolve_context_window(&ctx.config.components.tokens, ctx))
+    }
+
+    /// Session whose model switches are tracked, when the notice is enabled
+    fn tracks_switches<'a>(&self, ctx: &'a RenderContext) -> Option<&'a str> {
+        if self.config.switch_notice_renders == 0 || ctx.preview_mode {
+            return None;
+        }
+        ctx.input.session_id.as_deref()
+    }
+
+    /// `↻ Opus→Sonnet` while a mid-session model switch is being announced
+    async fn switch_notice(&self, ctx: &RenderContext) -> Option<String> {
+        let session_id = self.tracks_switches(ctx)?;
+        let current = ctx.input.model.as_ref()?.id.as_deref()?;
+
+        let mut state = ctx
+            .storage()
+            .session_history(session_id)
+            .await
+            .ok()
+            .flatten()
+            .map(|history| history.model_switch)
+            .unwrap_or_default();
+        let before = state.clone();
+        let notice = advance_switch(&mut state, current, self.config.switch_notice_renders);
+
+        if state != before {
+            if let Err(err) = ctx.storage().update_model_switch(session_id, state).await {
+                if ctx.config.debug {
+                    eprintln!("[statusline] failed to persist model switch: {err}");
+                }
+            }
+        }
+        notice.map(|(from, to)| format!("↻ {from}→{to}"))
+    }
+
+    /// Text the series / provider keys are matched against
+    fn match_source(ctx: &RenderContext) -> Option<String> {
+        let model = ctx.input.model.as_ref()?;
+        model
+            .id
+            .as_deref()
+            .or(model.display_name.as_deref())
+            .map(str::to_ascii_lowercase)
+    }
+
+    /// Whether the icon falls back to `text_icon` (mirrors `select_icon`)
+    fn uses_text_icon(ctx: &RenderContext) -> bool {
+        let terminal = &ctx.config.terminal;
+        if terminal.text_only() {
+            return true;
+        }
+        if terminal.force_nerd_font || terminal.force_emoji {
+            return false;
+        }
+        let style = &ctx.config.style;
+        !(ctx.terminal.supports_nerd_font && style.enable_nerd_font.is_enabled(true)
+            || ctx.terminal.supports_emoji && style.enable_emoji.is_enabled(true))
+    }
+}
+
+/// Record `current` as the latest model and consume one notice render.
+///
+/// 首次见到的模型只记录不提示;切换后重新计满 `renders` 次,期间再切换则以
+/// 新的前后模型重新计数。返回需要显示的 (旧, 新) 标签。
+fn advance_switch(
+    state: &mut ModelSwitchState,
+    current: &str,
+    renders: u32,
+) -> Option<(String, String)> {
+    if state.last_model_id.as_deref() != Some(current) {
+        if let Some(last) = state.last_model_id.replace(current.to_string()) {
+            state.previous_model_id = Some(last);
+            state.remaining_renders = renders;
+        }
+    }
+
+    if state.remaining_renders == 0 {
+        return None;
+    }
+    state.remaining_renders -= 1;
+    let previous = state.previous_model_id.as_deref()?;
+    Some(switch_labels(previous, current))
+}
+
+/// Series names (`Opus`, `Sonnet`) when the family changed, short names
+/// (`S4`, `S4.5`) for a version change within one family.
+fn switch_labels(from: &str, to: &str) -> (String, String) {
+    match (parse_model_id(from), parse_model_id(to)) {
+        (Some(from), Some(to)) if from.series != to.series => {
+            (capitalize(&from.series), capitalize(&to.series))
+        }
+        (Some(from), Some(to)) => (from.short_name(), to.short_name()),
+        _ => (from.to_string(), to.to_string()),
+    }
+}
+
+/// Compact window size badge: `1M`, `200K`, `1.5M`.
+#[allow(clippy::cast_precision_loss)]
+fn window_badge(window: u64) -> String {
+    let (value, unit) = if window >= 1_000_000 {
+        (window as f64 / 1_000_000.0, "M")
+    } else if window >= 1_000 {
+        (window as f64 / 1_000.0, "K")
+    } else {
+        return window.to_string();
+    };
+    let formatted = format!("{value:.1}");
+    format!("{}{unit}", formatted.trim_end_matches(".0"))
+}
+
+/// Value of the longest key contained in `source`.
+///
+/// `claude-3-5-haiku` 这类旧 ID 无法被 `parse_model_id` 解析,按子串匹配才能
+/// 覆盖新旧两种命名;取最长的键保证 `sonnet-4` 之类更具体的键优先。
+fn lookup_by_substring<'a>(map: &'a HashMap<String, String>, source: &str) -> Option<&'a str> {
+    map.iter()
+        .filter(|(key, _)| {
+            let key = key.trim();
+            !key.is_empty() && source.contains(&key.to_ascii_lowercase())
+        })
+        .max_by(|(a, _), (b, _)| a.len().cmp(&b.len()).then_with(|| b.cmp(a)))
+        .map(|(_, value)| value.as_str())
 }
 
 #[async_trait]
@@ -76,24 +209,66 @@ impl Component for ModelComponent {
             return ComponentOutput::hidden();
         };
 
+        if let Some(window) = self.context_window(ctx) {
+            text.push('·');
+            text.push_str(&window_badge(window));
+        }
+
         if let Some(level) = resolve_effort_level(ctx.input.as_ref()) {
             text.push(' ');
             text.push_str(level.symbol());
         }
 
+        if let Some(notice) = self.switch_notice(ctx).await {
+            text.push(' ');
+            text.push_str(&notice);
+        }
+
+        let source = Self::match_source(ctx).unwrap_or_default();
+
         // Select icon
-        let icon = self.select_icon(ctx);
+        let provider_icon = lookup_by_substring(&self.config.provider_icons, &source)
+            .filter(|_| !Self::uses_text_icon(ctx));
+        let icon = provider_icon
+            .map(str::to_string)
+            .or_else(|| self.select_icon(ctx));
+
+        let series_color = lookup_by_substring(&self.config.series_colors, &source);
+        let (icon_color, text_color) = series_color.map_or(
+            (
+                self.config.base.icon_color.as_str(),
+                self.config.base.text_color.as_str(),
+            ),
+            |color| (color, color),
+        );
 
         // Create output
         ComponentOutput::new(text)
             .with_icon(icon.unwrap_or_default())
-            .with_icon_color(&self.config.base.icon_color)
-            .with_text_color(&self.config.base.text_color)
+            .with_icon_color(icon_color)
+            .with_text_color(text_color)
     }
 
     fn base_config(&self, _ctx: &RenderContext) -> Option<&BaseComponentConfig> {
         Some(&self.config.base)
     }
+
+    fn cache_key(&self, ctx: &RenderContext) -> Option<u64> {
+        // 切换提示每次渲染都要读写 snapshot 并递减计数,不能复用上次输出
+        if self.tracks_switches(ctx).is_some() {
+            return None;
+        }
+        let model = ctx.input.model.as_ref();
+        // effort 来自环境变量或 settings 文件,解析成本低,但会随时变化,必须计入 key
+        let effort = resolve_effort_level(ctx.input.as_ref()).map(EffortLevel::symbol);
+        Some(cache_key_of(&(
+            model.and_then(|m| m.id.as_deref()),
+            model.and_then(|m| m.display_name.as_deref()),
+            effort,
+            // 窗口可能来自 stdin 的 context_window_size 或 ANTHROPIC_BASE_URL 对应的 provider
+            self.context_window(ctx),
+        )))
+    }
 }
 
 /// Factory for creating Model components
@@ -101,7 +276,7 @@ pub struct ModelComponentFactory;
 
 impl ComponentFactory for ModelComponentFactory {
     fn create(&self, config: &Config) -> Box<dyn Component> {
-        Box::new(ModelComponent::new(config.components.model.clone()))
+        Box::new(ModelComponent::new((*config.components.model).clone()))
     }
 
     fn name(&self) -> &'static str {
@@ -151,9 +326,149 @@ mod tests {
             config: Arc::new(Config::default()),
             terminal: TerminalCapabilities::default(),
             preview_mode: false,
+            transcript: None,
+            storage: None,
         }
     }
 
+    fn series_config() -> ModelComponentConfig {
+        build_model_config(|config| {
+            config.series_colors = HashMap::from([
+                ("opus".to_string(), "magenta".to_string()),
+                ("haiku".to_string(), "green".to_string()),
+            ]);
+            config.provider_icons = HashMap::from([
+                ("claude".to_string(), "✳".to_string()),
+                ("claude-opus".to_string(), "◆".to_string()),
+            ]);
+        })
+    }
+
+    // ==================== 系列着色与提供商图标 ====================
+
+    #[tokio::test]
+    async fn test_series_color_applies_to_text_and_icon() {
+        let component = ModelComponent::new(series_config());
+        let ctx =
+            create_test_context_with_model(Some("claude-opus-4-1-20250805".to_string()), None);
+
+        let output = component.render(&ctx).await;
+        assert_eq!(output.text_color.as_deref(), Some("magenta"));
+        assert_eq!(output.icon_color.as_deref(), Some("magenta"));
+
+        // 旧命名 claude-3-5-haiku 无法解析出系列,按子串仍能匹配
+        let ctx =
+            create_test_context_with_model(Some("claude-3-5-haiku-20241022".to_string()), None);
+        let output = component.render(&ctx).await;
+        assert_eq!(output.text_color.as_deref(), Some("green"));
+
+        let ctx =
+            create_test_context_with_model(Some("claude-sonnet-4-5-20250929".to_string()), None);
+        let output = component.render(&ctx).await;
+        assert_eq!(output.text_color.as_deref(), Some("white"));
+    }
+
+    #[tokio::test]
+    async fn test_provider_icon_prefers_longest_key_and_skips_text_mode() {
+        let component = ModelComponent::new(series_config());
+        let mut ctx =
+            create_test_context_with_model(Some("claude-opus-4-1-20250805".to_string()), None);
+
+        let output = component.render(&ctx).await;
+        assert_eq!(output.icon.as_deref(), Some("◆"));
+
+        ctx.terminal = TerminalCapabilities {
+            supports_emoji: false,
+            ..TerminalCapabilities::default()
+        };
+        let output = component.render(&ctx).await;
+        assert_eq!(output.icon.as_deref(), Some("[M]"));
+    }
+
+    // ==================== 模型切换提示 ====================
+
+    #[test]
+    fn test_switch_notice_counts_down_after_change() {
+        let mut state = ModelSwitchState::default();
+        assert_eq!(
+            advance_switch(&mut state, "claude-opus-4-1-20250805", 2),
+            None
+        );
+        assert_eq!(
+            advance_switch(&mut state, "claude-opus-4-1-20250805", 2),
+            None
+        );
+
+        let expected = Some(("Opus".to_string(), "Sonnet".to_string()));
+        assert_eq!(
+            advance_switch(&mut state, "claude-sonnet-4-5-20250929", 2),
+            expected
+        );
+        assert_eq!(
+            advance_switch(&mut state, "claude-sonnet-4-5-20250929", 2),
+            expected
+        );
+        assert_eq!(
+            advance_switch(&mut state, "claude-sonnet-4-5-20250929", 2),
+            None
+        );
+        assert_eq!(state.remaining_renders, 0);
+    }
+
+    #[test]
+    fn test_switch_labels_use_short_names_within_series() {
+        assert_eq!(
+            switch_labels("claude-sonnet-4-20250514", "claude-sonnet-4-5-20250929"),
+            ("S4".to_string(), "S4.5".to_string())
+        );
+        assert_eq!(
+            switch_labels("gpt-4o", "claude-3-5-haiku-20241022"),
+            (
+                "gpt-4o".to_string(),
+                "claude-3-5-haiku-20241022".to_string()
+            )
+        );
+    }
+
+    // ==================== 上下文窗口徽标 ====================
+
+    #[test]
+    fn test_window_badge_formatting() {
+        assert_eq!(window_badge(1_000_000), "1M");
+        assert_eq!(window_badge(200_000), "200K");
+        assert_eq!(window_badge(1_500_000), "1.5M");
+        assert_eq!(window_badge(128_000), "128K");
+        assert_eq!(window_badge(512), "512");
+    }
+
+    #[tokio::test]
+    async fn test_context_window_badge_follows_tokens_resolution() {
+        let component = ModelComponent::new(build_model_config(|config| {
+            config.show_context_window = true;
+        }));
+        let ctx = create_test_context_with_model(
+            Some("claude-sonnet-4-5-20250929[1m]".to_string()),
+            None,
+        );
+        let output = component.render(&ctx).await;
+        assert_eq!(output.text, "S4.5[1m]·1M");
+
+        // 官方 context_window_size 优先于默认窗口
+        let mut ctx =
+            create_test_context_with_model(Some("claude-opus-4-1-20250805".to_string()), None);
+        ctx.input = Arc::new(build_input(|input| {
+            input.model = ctx.input.model.clone();
+            input.extra = serde_json::json!({
+                "context_window": {"context_window_size": 400_000u64}
+            });
+        }));
+        let output = component.render(&ctx).await;
+        assert_eq!(output.text, "O4.1·400K");
+
+        let plain = ModelComponent::new(ModelComponentConfig::default());
+        assert_eq!(plain.render(&ctx).await.text, "O4.1");
+    }
+
     // ==================== 短名称生成测试 ====================
 
     #[tokio::test]
@@ -323,6 +638,8 @@ mod tests {
             config: Arc::new(Config::default()),
             terminal: TerminalCapabilities::default(),
             preview_mode: false,
+            transcript: None,
+            storage: None,
         };
 
         let component = ModelComponent::new(ModelComponentConfig::default());
@@ -373,6 +690,8 @@ mod tests {
             config: Arc::new(Config::default()),
             terminal: TerminalCapabilities::default(),
             preview_mode: false,
+            transcript: None,
+            storage: None,
         };
 
         let component = ModelComponent::new(ModelComponentConfig::default());
@@ -414,6 +733,8 @@ mod tests {
             config: Arc::new(Config::default()),
             terminal: TerminalCapabilities::default(),
             preview_mode: false,
+            transcript: None,
+            storage: None,
         };
 
         let output = component.render(&ctx).await;
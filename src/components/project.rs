@@ -1,11 +1,20 @@
 //! Project component implementation
 //!
 //! Displays the project name extracted from the current directory or workspace.
-
-use super::base::{Component, ComponentFactory, ComponentOutput, RenderContext};
-use crate::config::{BaseComponentConfig, Config, ProjectComponentConfig};
+//! Labels can also follow the git remote, so renamed or re-cloned folders keep
+//! the same name and color.
+
+use super::base::{cache_key_of, Component, ComponentFactory, ComponentOutput, RenderContext};
+use crate::config::project_patterns::pattern_matches;
+use crate::config::{BaseComponentConfig, Config, ProjectComponentConfig, ProjectMappingConfig};
+use crate::storage::ProjectId;
+use crate::utils::git_remote::{normalize_remote, remote_identity};
+use crate::utils::project_manifest::read_project_manifest;
+use crate::utils::{display_width, expand_home};
 use async_trait::async_trait;
-use std::path::Path;
+use std::path::{self, Path};
+
+const ELLIPSIS: &str = "…";
 
 /// Project component
 pub struct ProjectComponent {
@@ -58,6 +67,178 @@ impl ProjectComponent {
                     .map(std::string::ToString::to_string)
             })
     }
+
+    /// Directory whose name, manifest or remote identifies the project
+    fn identity_dir(ctx: &RenderContext) -> Option<&str> {
+        ctx.input
+            .worktree
+            .as_ref()
+            .and_then(|worktree| worktree.path.as_deref())
+            .or_else(|| ctx.input.project_dir())
+    }
+
+    /// Normalized git remote (`github.com/org/repo`), read only when the
+    /// name source or a mapping key needs it
+    fn remote(&self, ctx: &RenderContext) -> Option<String> {
+        let needed = self.config.source.eq_ignore_ascii_case("remote")
+            || self.config.mapping.keys().any(|key| is_remote_key(key));
+        if !needed {
+            return None;
+        }
+        remote_identity(Path::new(Self::identity_dir(ctx)?))
+    }
+
+    /// Entry of `mapping` for the current project: paths, then the remote
+    /// (exact before globs, longer globs first), then hashes
+    fn mapping_entry(
+        &self,
+        ctx: &RenderContext,
+        remote: Option<&str>,
+    ) -> Option<&ProjectMappingConfig> {
+        if self.config.mapping.is_empty() {
+            return None;
+        }
+
+        let dirs: Vec<&str> = [
+            ctx.input
+                .worktree
+                .as_ref()
+                .and_then(|worktree| worktree.path.as_deref()),
+            ctx.input.project_dir(),
+        ]
+        .into_iter()
+        .flatten()
+        .map(|dir| dir.trim_end_matches(['/', '\\']))
+        .filter(|dir| !dir.is_empty())
+        .collect();
+
+        let by_path = dirs.iter().find_map(|dir| {
+            self.config
+                .mapping
+                .iter()
+                .find(|(key, _)| expand_home(key).trim_end_matches(['/', '\\']) == *dir)
+                .map(|(_, entry)| entry)
+        });
+        by_path
+            .or_else(|| remote.and_then(|remote| self.remote_entry(remote)))
+            .or_else(|| {
+                dirs.iter()
+                    .find_map(|dir| self.config.mapping.get(ProjectId::from_path(dir).as_str()))
+            })
+    }
+
+    fn remote_entry(&self, remote: &str) -> Option<&ProjectMappingConfig> {
+        let mut candidates: Vec<(bool, usize, &ProjectMappingConfig)> = self
+            .config
+            .mapping
+            .iter()
+            .filter(|(key, _)| is_remote_key(key))
+            .filter_map(|(key, entry)| {
+                let key = normalize_remote(key).unwrap_or_else(|| key.trim().to_string());
+                if key == remote {
+                    Some((true, key.len(), entry))
+                } else {
+                    pattern_matches(&key, remote, None).then_some((false, key.len(), entry))
+                }
+            })
+            .collect();
+        candidates.sort_by_key(|(exact, len, _)| std::cmp::Reverse((*exact, *len)));
+        candidates.first().map(|(_, _, entry)| *entry)
+    }
+
+    /// Project text shown in the statusline
+    fn display_name(
+        &self,
+        ctx: &RenderContext,
+        entry: Option<&ProjectMappingConfig>,
+        remote: Option<&str>,
+    ) -> Option<String> {
+        let mapped_label = match entry {
+            Some(ProjectMappingConfig::Label(label)) => Some(label.clone()),
+            Some(ProjectMappingConfig::Detailed { label, .. }) => label.clone(),
+            None => None,
+        };
+        let name = mapped_label
+            .filter(|label| !label.is_empty())
+            .or_else(|| self.manifest_name(ctx))
+            .or_else(|| self.remote_name(remote))
+            .or_else(|| Self::extract_project_name(ctx))?;
+        let name = match self.relative_dir(ctx) {
+            Some(relative) => format!("{name}/{relative}"),
+            None => name,
+        };
+        Some(keep_tail(&name, self.config.max_length))
+    }
+
+    /// Package name (and version) from the project manifest in `manifest` mode
+    fn manifest_name(&self, ctx: &RenderContext) -> Option<String> {
+        if !self.config.source.eq_ignore_ascii_case("manifest") {
+            return None;
+        }
+
+        let manifest = read_project_manifest(Path::new(Self::identity_dir(ctx)?))?;
+        Some(match manifest.version {
+            Some(version) if self.config.show_version => format!("{} v{version}", manifest.name),
+            _ => manifest.name,
+        })
+    }
+
+    /// Repository name of the git remote in `remote` mode
+    fn remote_name(&self, remote: Option<&str>) -> Option<String> {
+        if !self.config.source.eq_ignore_ascii_case("remote") {
+            return None;
+        }
+        remote?
+            .rsplit('/')
+            .find(|segment| !segment.is_empty())
+            .map(str::to_string)
+    }
+
+    /// Leading segments of the current dir below the project root (`packages/web`)
+    fn relative_dir(&self, ctx: &RenderContext) -> Option<String> {
+        if !self.config.show_relative_dir || ctx.input.worktree.is_some() {
+            return None;
+        }
+
+        let workspace = ctx.input.workspace.as_ref()?;
+        if workspace.git_worktree.is_some() {
+            return None;
+        }
+        let root = ctx.input.project_dir()?.trim_end_matches(['/', '\\']);
+        let current = workspace.current_dir.as_deref()?;
+        let relative = Path::new(current).strip_prefix(root).ok()?;
+
+        let segments: Vec<&str> = relative
+            .components()
+            .filter_map(|component| match component {
+                path::Component::Normal(segment) => segment.to_str(),
+                _ => None,
+            })
+            .take(self.config.relative_depth)
+            .collect();
+        (!segments.is_empty()).then(|| segments.join("/"))
+    }
+}
+
+/// Whether a `mapping` key names a git remote rather than a path or hash
+fn is_remote_key(key: &str) -> bool {
+    let key = key.trim();
+    !key.is_empty() && !key.starts_with(['/', '\\', '~', '-', '.']) && key.get(1..2) != Some(":")
+}
+
+/// Cut `text` to `max_width` columns keeping its end; 0 disables the limit.
+fn keep_tail(text: &str, max_width: usize) -> String {
+    if max_width == 0 || display_width(text) <= max_width {
+        return text.to_string();
+    }
+
+    let budget = max_width.saturating_sub(display_width(ELLIPSIS));
+    let start = text
+        .char_indices()
+        .map(|(idx, _)| idx)
+        .find(|idx| display_width(&text[*idx..]) <= budget)
+        .unwrap_or(text.len());
+    format!("{ELLIPSIS}{}", &text[start..])
 }
 
 #[async_trait]
@@ -77,7 +258,9 @@ impl Component for ProjectComponent {
         }
 
         // Extract project name
-        let project_name = Self::extract_project_name(ctx);
+        let remote = self.remote(ctx);
+        let entry = self.mapping_entry(ctx, remote.as_deref());
+        let project_name = self.display_name(ctx, entry, remote.as_deref());
 
         // Check if we should show when empty
         if project_name.is_none() && !self.config.show_when_empty {
@@ -87,19 +270,39 @@ impl Component for ProjectComponent {
         // Get the project name or default
         let text = project_name.unwrap_or_else(|| "project".to_string());
 
-        // Select icon
-        let icon = self.select_icon(ctx);
+        // Select icon and color (mapped project values win)
+        let (mapped_icon, mapped_color) = match entry {
+            Some(ProjectMappingConfig::Detailed { icon, color, .. }) => {
+                (icon.clone(), color.as_deref())
+            }
+            _ => (None, None),
+        };
+        let icon = mapped_icon.or_else(|| self.select_icon(ctx));
 
         // Create output
         ComponentOutput::new(text)
             .with_icon(icon.unwrap_or_default())
             .with_icon_color(&self.config.base.icon_color)
-            .with_text_color(&self.config.base.text_color)
+            .with_text_color(mapped_color.unwrap_or(&self.config.base.text_color))
     }
 
     fn base_config(&self, _ctx: &RenderContext) -> Option<&BaseComponentConfig> {
         Some(&self.config.base)
     }
+
+    fn cache_key(&self, ctx: &RenderContext) -> Option<u64> {
+        let remote = self.remote(ctx);
+        let entry = self.mapping_entry(ctx, remote.as_deref());
+        Some(cache_key_of(&(
+            self.display_name(ctx, entry, remote.as_deref()),
+            entry,
+            ctx.input.project_dir(),
+            ctx.input
+                .worktree
+                .as_ref()
+                .and_then(|worktree| worktree.path.as_deref()),
+        )))
+    }
 }
 
 /// Factory for creating Project components
@@ -107,7 +310,7 @@ pub struct ProjectComponentFactory;
 
 impl ComponentFactory for ProjectComponentFactory {
     fn create(&self, config: &Config) -> Box<dyn Component> {
-        Box::new(ProjectComponent::new(config.components.project.clone()))
+        Box::new(ProjectComponent::new((*config.components.project).clone()))
     }
 
     fn name(&self) -> &'static str {
@@ -120,6 +323,7 @@ mod tests {
     use super::*;
     use crate::components::TerminalCapabilities;
     use crate::core::{InputData, WorkspaceInfo, WorktreeInfo};
+    use std::collections::HashMap;
     use std::sync::Arc;
 
     #[allow(clippy::field_reassign_with_default)]
@@ -153,6 +357,8 @@ mod tests {
             config: Arc::new(Config::default()),
             terminal: TerminalCapabilities::default(),
             preview_mode: false,
+            transcript: None,
+            storage: None,
         }
     }
 
@@ -192,6 +398,8 @@ mod tests {
             config: Arc::new(Config::default()),
             terminal: TerminalCapabilities::default(),
             preview_mode: false,
+            transcript: None,
+            storage: None,
         };
 
         let output = component.render(&ctx).await;
@@ -218,6 +426,8 @@ mod tests {
             config: Arc::new(Config::default()),
             terminal: TerminalCapabilities::default(),
             preview_mode: false,
+            transcript: None,
+            storage: None,
         };
 
         let component = ProjectComponent::new(ProjectComponentConfig::default());
@@ -243,6 +453,8 @@ mod tests {
             config: Arc::new(Config::default()),
             terminal: TerminalCapabilities::default(),
             preview_mode: false,
+            transcript: None,
+            storage: None,
         };
 
         let component = ProjectComponent::new(ProjectComponentConfig::default());
@@ -268,6 +480,8 @@ mod tests {
             config: Arc::new(Config::default()),
             terminal: TerminalCapabilities::default(),
             preview_mode: false,
+            transcript: None,
+            storage: None,
         };
 
         let component = ProjectComponent::new(ProjectComponentConfig::default());
@@ -276,4 +490,210 @@ mod tests {
         assert!(output.visible);
         assert_eq!(output.text, "my-project");
     }
+
+    #[tokio::test]
+    async fn test_project_shows_monorepo_package_dir() {
+        let input = build_input(|input| {
+            input.workspace = Some(WorkspaceInfo {
+                current_dir: Some("/home/user/monorepo/packages/web/src".to_string()),
+                project_dir: Some("/home/user/monorepo".to_string()),
+                added_dirs: None,
+                git_worktree: None,
+            });
+        });
+        let ctx = RenderContext {
+            input: Arc::new(input),
+            config: Arc::new(Config::default()),
+            terminal: TerminalCapabilities::default(),
+            preview_mode: false,
+            transcript: None,
+            storage: None,
+        };
+
+        let component = ProjectComponent::new(build_project_config(|config| {
+            config.show_relative_dir = true;
+        }));
+        assert_eq!(component.render(&ctx).await.text, "monorepo/packages/web");
+
+        let component = ProjectComponent::new(build_project_config(|config| {
+            config.show_relative_dir = true;
+            config.relative_depth = 1;
+            config.max_length = 12;
+        }));
+        assert_eq!(component.render(&ctx).await.text, "…po/packages");
+
+        let component = ProjectComponent::new(ProjectComponentConfig::default());
+        assert_eq!(component.render(&ctx).await.text, "monorepo");
+    }
+
+    #[test]
+    fn test_keep_tail_respects_width() {
+        assert_eq!(keep_tail("abcdef", 0), "abcdef");
+        assert_eq!(keep_tail("abcdef", 6), "abcdef");
+        assert_eq!(keep_tail("abcdef", 4), "…def");
+        assert_eq!(keep_tail("项目/web", 5), "…/web");
+    }
+
+    #[tokio::test]
+    async fn test_project_mapping_overrides_name_and_icon() {
+        let ctx = create_test_context();
+
+        let component = ProjectComponent::new(build_project_config(|config| {
+            config.mapping.insert(
+                "/home/user/my-project/".to_string(),
+                ProjectMappingConfig::Label("🚀 backend".to_string()),
+            );
+        }));
+        let output = component.render(&ctx).await;
+        assert_eq!(output.text, "🚀 backend");
+        assert_eq!(output.icon.as_deref(), Some("📁"));
+
+        let component = ProjectComponent::new(build_project_config(|config| {
+            config.mapping.insert(
+                ProjectId::from_path("/home/user/my-project").into_string(),
+                ProjectMappingConfig::Detailed {
+                    label: Some("api".to_string()),
+                    icon: Some("🛰️".to_string()),
+                    color: None,
+                },
+            );
+            config.mapping.insert(
+                "/home/user/other".to_string(),
+                ProjectMappingConfig::Label("other".to_string()),
+            );
+        }));
+        let output = component.render(&ctx).await;
+        assert_eq!(output.text, "api");
+        assert_eq!(output.icon.as_deref(), Some("🛰️"));
+    }
+
+    #[test]
+    fn test_project_mapping_accepts_string_or_table() -> anyhow::Result<()> {
+        let mapping: HashMap<String, ProjectMappingConfig> = toml_edit::de::from_str(
+            r#"
+            "~/work/site" = "🌐 site"
+            "-home-user-api" = { icon = "🛰️" }
+            "#,
+        )?;
+        assert_eq!(
+            mapping.get("~/work/site"),
+            Some(&ProjectMappingConfig::Label("🌐 site".to_string()))
+        );
+        assert_eq!(
+            mapping.get("-home-user-api"),
+            Some(&ProjectMappingConfig::Detailed {
+                label: None,
+                icon: Some("🛰️".to_string()),
+                color: None,
+            })
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_project_follows_git_remote() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let clone = dir.path().join("renamed-locally");
+        std::fs::create_dir_all(clone.join(".git"))?;
+        std::fs::write(
+            clone.join(".git/config"),
+            "[remote \"origin\"]\n\turl = git@github.com:acme/billing.git\n",
+        )?;
+        let project_dir = clone.to_string_lossy().to_string();
+        let ctx = RenderContext {
+            input: Arc::new(build_input(|input| {
+                input.workspace = Some(WorkspaceInfo {
+                    current_dir: Some(project_dir.clone()),
+                    project_dir: Some(project_dir.clone()),
+                    added_dirs: None,
+                    git_worktree: None,
+                });
+            })),
+            config: Arc::new(Config::default()),
+            terminal: TerminalCapabilities::default(),
+            preview_mode: false,
+            transcript: None,
+            storage: None,
+        };
+
+        let component = ProjectComponent::new(build_project_config(|config| {
+            config.source = "remote".to_string();
+        }));
+        assert_eq!(component.render(&ctx).await.text, "billing");
+
+        let component = ProjectComponent::new(build_project_config(|config| {
+            config.mapping.insert(
+                "github.com/acme/*".to_string(),
+                ProjectMappingConfig::Detailed {
+                    label: None,
+                    icon: Some("🏢".to_string()),
+                    color: Some("magenta".to_string()),
+                },
+            );
+            config.mapping.insert(
+                "https://github.com/acme/billing.git".to_string(),
+                ProjectMappingConfig::Label("💳 billing".to_string()),
+            );
+        }));
+        let output = component.render(&ctx).await;
+        assert_eq!(output.text, "💳 billing");
+        assert_eq!(output.icon.as_deref(), Some("📁"));
+
+        let component = ProjectComponent::new(build_project_config(|config| {
+            config.mapping.insert(
+                "github.com/acme/*".to_string(),
+                ProjectMappingConfig::Detailed {
+                    label: None,
+                    icon: Some("🏢".to_string()),
+                    color: Some("magenta".to_string()),
+                },
+            );
+        }));
+        let output = component.render(&ctx).await;
+        assert_eq!(output.text, "renamed-locally");
+        assert_eq!(output.icon.as_deref(), Some("🏢"));
+        assert_eq!(output.text_color.as_deref(), Some("magenta"));
+
+        assert!(is_remote_key("gitlab.com/team/app"));
+        assert!(!is_remote_key("~/work/app"));
+        assert!(!is_remote_key("-home-user-app"));
+        assert!(!is_remote_key("C:\\work\\app"));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_project_reads_name_from_manifest() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        std::fs::write(
+            dir.path().join("Cargo.toml"),
+            "[package]\nname = \"canonical-name\"\nversion = \"1.4.0\"\n",
+        )?;
+        let project_dir = dir.path().to_string_lossy().to_string();
+        let input = build_input(|input| {
+            input.workspace = Some(WorkspaceInfo {
+                current_dir: Some(project_dir.clone()),
+                project_dir: Some(project_dir.clone()),
+                added_dirs: None,
+                git_worktree: None,
+            });
+        });
+        let ctx = RenderContext {
+            input: Arc::new(input),
+            config: Arc::new(Config::default()),
+            terminal: TerminalCapabilities::default(),
+            preview_mode: false,
+            transcript: None,
+            storage: None,
+        };
+
+        let component = ProjectComponent::new(build_project_config(|config| {
+            config.source = "manifest".to_string();
+            config.show_version = true;
+        }));
+        assert_eq!(component.render(&ctx).await.text, "canonical-name v1.4.0");
+
+        let component = ProjectComponent::new(ProjectComponentConfig::default());
+        assert_ne!(component.render(&ctx).await.text, "canonical-name");
+        Ok(())
+    }
 }
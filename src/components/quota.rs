@@ -0,0 +1,346 @@
+//! Weekly quota component implementation.
+//!
+//! Tracks the weekly limits of a subscription plan (session hours and/or a
+//! token budget) against the usage stored across all sessions since the last
+//! weekly reset, and shows what is left plus the day the current pace runs
+//! out, when that is before the next reset.
+
+use chrono::{DateTime, Datelike, Duration, Local, TimeZone, Utc, Weekday};
+
+use super::base::{Component, ComponentFactory, ComponentOutput, RenderContext};
+use crate::config::{BaseComponentConfig, Config, QuotaComponentConfig, Severity};
+use crate::storage::usage_report::PeriodUsage;
+use crate::utils::number_format::{format_amount, AmountFormat};
+use async_trait::async_trait;
+
+/// Elapsed time before the pace is considered meaningful for a projection
+const MIN_PROJECTION_SECS: i64 = 3_600;
+
+/// One configured weekly limit and its usage so far
+#[derive(Debug, Clone, Copy)]
+struct Allowance {
+    used: f64,
+    limit: f64,
+    kind: AllowanceKind,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum AllowanceKind {
+    Hours,
+    Tokens,
+}
+
+impl Allowance {
+    fn used_percentage(&self) -> f64 {
+        self.used / self.limit * 100.0
+    }
+
+    fn remaining_text(&self) -> String {
+        let remaining = (self.limit - self.used).max(0.0);
+        match self.kind {
+            AllowanceKind::Hours => format!("{remaining:.1}h left"),
+            AllowanceKind::Tokens => {
+                let format = AmountFormat {
+                    compact: true,
+                    ..AmountFormat::default()
+                };
+                format!("{} tok left", format_amount(remaining, &format))
+            }
+        }
+    }
+
+    /// When the allowance runs out at the average pace since `start`, if that
+    /// is before `reset`
+    #[allow(clippy::cast_possible_truncation, clippy::cast_precision_loss)]
+    fn projected_exhaustion<Tz: TimeZone>(
+        &self,
+        start: &DateTime<Tz>,
+        now: &DateTime<Tz>,
+        reset: &DateTime<Tz>,
+    ) -> Option<DateTime<Tz>> {
+        let elapsed = (now.clone() - start.clone()).num_seconds();
+        if elapsed < MIN_PROJECTION_SECS || self.used <= 0.0 || self.used >= self.limit {
+            return None;
+        }
+        let rate = self.used / elapsed as f64;
+        let seconds_left = ((self.limit - self.used) / rate) as i64;
+        let exhausted_at = now.clone() + Duration::seconds(seconds_left);
+        (exhausted_at < *reset).then_some(exhausted_at)
+    }
+}
+
+/// Weekly quota component.
+pub struct QuotaComponent {
+    config: QuotaComponentConfig,
+}
+
+impl QuotaComponent {
+    #[must_use]
+    pub const fn new(config: QuotaComponentConfig) -> Self {
+        Self { config }
+    }
+
+    fn reset_day(&self) -> Weekday {
+        self.config.reset_day.parse().unwrap_or(Weekday::Mon)
+    }
+
+    fn allowances(&self, usage: PeriodUsage) -> Vec<Allowance> {
+        let mut allowances = Vec::new();
+        if self.config.weekly_hours > 0.0 {
+            allowances.push(Allowance {
+                used: usage.session_hours,
+                limit: self.config.weekly_hours,
+                kind: AllowanceKind::Hours,
+            });
+        }
+        if self.config.weekly_tokens > 0 {
+            #[allow(clippy::cast_precision_loss)]
+            allowances.push(Allowance {
+                used: usage.tokens as f64,
+                limit: self.config.weekly_tokens as f64,
+                kind: AllowanceKind::Tokens,
+            });
+        }
+        allowances
+    }
+
+    /// Text and severity of the quota at `now`
+    fn describe<Tz: TimeZone>(&self, usage: PeriodUsage, now: &DateTime<Tz>) -> (String, Severity)
+    where
+        Tz::Offset: std::fmt::Display,
+    {
+        let start = week_start(now, self.reset_day(), self.config.reset_hour);
+        let reset = start.clone() + Duration::days(7);
+        let allowances = self.allowances(usage);
+
+        let mut text = allowances
+            .iter()
+            .map(Allowance::remaining_text)
+            .collect::<Vec<_>>()
+            .join(" · ");
+
+        let exhaustion = allowances
+            .iter()
+            .filter_map(|allowance| allowance.projected_exhaustion(&start, now, &reset))
+            .min();
+        if self.config.show_projection {
+            if let Some(exhausted_at) = &exhaustion {
+                text = format!("{text} → {}", exhausted_at.format("%a"));
+            }
+        }
+
+        let worst = allowances
+            .iter()
+            .map(Allowance::used_percentage)
+            .fold(0.0, f64::max);
+        let severity = if worst >= self.config.critical_threshold {
+            Severity::Critical
+        } else if worst >= self.config.warning_threshold || exhaustion.is_some() {
+            Severity::Warning
+        } else {
+            Severity::Normal
+        };
+        (text, severity)
+    }
+}
+
+/// Start of the quota week containing `now`: the latest `reset_day` at
+/// `reset_hour` (local time of `now`) not after `now`.
+fn week_start<Tz: TimeZone>(
+    now: &DateTime<Tz>,
+    reset_day: Weekday,
+    reset_hour: u32,
+) -> DateTime<Tz> {
+    let days_back =
+        (7 + now.weekday().num_days_from_monday() - reset_day.num_days_from_monday()) % 7;
+    let date = now.date_naive() - Duration::days(i64::from(days_back));
+    let candidate = date
+        .and_hms_opt(reset_hour.min(23), 0, 0)
+        .and_then(|naive| now.timezone().from_local_datetime(&naive).earliest())
+        // 夏令时跳过的整点:退回到一周前,宁可多算一点用量
+        .unwrap_or_else(|| now.clone() - Duration::days(7));
+    if candidate > *now {
+        candidate - Duration::days(7)
+    } else {
+        candidate
+    }
+}
+
+#[async_trait]
+impl Component for QuotaComponent {
+    fn name(&self) -> &'static str {
+        "quota"
+    }
+
+    fn is_enabled(&self, _ctx: &RenderContext) -> bool {
+        self.config.base.enabled
+    }
+
+    async fn render(&self, ctx: &RenderContext) -> ComponentOutput {
+        if !self.is_enabled(ctx) {
+            return ComponentOutput::hidden();
+        }
+        if self.config.weekly_hours <= 0.0 && self.config.weekly_tokens == 0 {
+            return ComponentOutput::hidden();
+        }
+
+        let icon = self.select_icon(ctx).unwrap_or_default();
+        let now = Local::now();
+
+        // preview 不读取真实存储,按整周额度未使用展示
+        if ctx.preview_mode {
+            let (text, _) = self.describe(PeriodUsage::default(), &now);
+            return ComponentOutput::new(text)
+                .with_icon(icon)
+                .with_icon_color("gray")
+                .with_text_color("gray");
+        }
+
+        let start = week_start(&now, self.reset_day(), self.config.reset_hour);
+        let usage = ctx
+            .storage()
+            .usage_since(start.with_timezone(&Utc), self.config.weekly_tokens > 0)
+            .await;
+        let usage = match usage {
+            Ok(usage) => usage,
+            Err(err) => {
+                eprintln!("[statusline] quota: failed to read weekly usage: {err}");
+                return ComponentOutput::hidden();
+            }
+        };
+
+        let (text, severity) = self.describe(usage, &now);
+        ComponentOutput::new(text)
+            .with_icon(icon)
+            .with_icon_color(&self.config.base.icon_color)
+            .with_text_color(&self.config.base.text_color)
+            .with_severity(severity)
+    }
+
+    fn base_config(&self, _ctx: &RenderContext) -> Option<&BaseComponentConfig> {
+        Some(&self.config.base)
+    }
+}
+
+/// Factory for creating weekly quota components.
+pub struct QuotaComponentFactory;
+
+impl ComponentFactory for QuotaComponentFactory {
+    fn create(&self, config: &Config) -> Box<dyn Component> {
+        Box::new(QuotaComponent::new((*config.components.quota).clone()))
+    }
+
+    fn name(&self) -> &'static str {
+        "quota"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::TerminalCapabilities;
+    use crate::core::input::InputData;
+    use crate::storage::MemoryStorage;
+    use std::sync::Arc;
+
+    fn at(timestamp: &str) -> anyhow::Result<DateTime<Utc>> {
+        Ok(DateTime::parse_from_rfc3339(timestamp)?.with_timezone(&Utc))
+    }
+
+    fn component(weekly_hours: f64, weekly_tokens: u64) -> QuotaComponent {
+        QuotaComponent::new(QuotaComponentConfig {
+            weekly_hours,
+            weekly_tokens,
+            ..QuotaComponentConfig::default()
+        })
+    }
+
+    #[test]
+    fn week_starts_at_latest_reset() -> anyhow::Result<()> {
+        // 2025-03-05 是周三
+        let now = at("2025-03-05T10:00:00Z")?;
+        assert_eq!(
+            week_start(&now, Weekday::Mon, 0),
+            at("2025-03-03T00:00:00Z")?
+        );
+        assert_eq!(
+            week_start(&now, Weekday::Wed, 9),
+            at("2025-03-05T09:00:00Z")?
+        );
+        // 当天的重置时刻还没到,属于上一周
+        assert_eq!(
+            week_start(&now, Weekday::Wed, 12),
+            at("2025-02-26T12:00:00Z")?
+        );
+        assert_eq!(
+            week_start(&now, Weekday::Thu, 0),
+            at("2025-02-27T00:00:00Z")?
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn describes_remaining_quota_and_projection() -> anyhow::Result<()> {
+        let quota = component(40.0, 0);
+        let wednesday = at("2025-03-05T00:00:00Z")?;
+
+        // 两天用了 10h,按此速度周一重置前用不完
+        let usage = PeriodUsage {
+            session_hours: 10.0,
+            tokens: 0,
+        };
+        let (text, severity) = quota.describe(usage, &wednesday);
+        assert_eq!(text, "30.0h left");
+        assert_eq!(severity, Severity::Normal);
+
+        // 两天用了 20h,再过两天(周五)耗尽
+        let usage = PeriodUsage {
+            session_hours: 20.0,
+            tokens: 0,
+        };
+        let (text, severity) = quota.describe(usage, &wednesday);
+        assert_eq!(text, "20.0h left → Fri");
+        assert_eq!(severity, Severity::Warning);
+
+        let usage = PeriodUsage {
+            session_hours: 41.0,
+            tokens: 0,
+        };
+        let (text, severity) = quota.describe(usage, &wednesday);
+        assert_eq!(text, "0.0h left");
+        assert_eq!(severity, Severity::Critical);
+        Ok(())
+    }
+
+    #[test]
+    fn token_budget_uses_compact_amounts() -> anyhow::Result<()> {
+        let usage = PeriodUsage {
+            session_hours: 1.0,
+            tokens: 800_000,
+        };
+        let (text, _) = component(0.0, 5_000_000).describe(usage, &at("2025-03-09T23:00:00Z")?);
+        assert_eq!(text, "4.2M tok left");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn renders_usage_from_storage_handle() {
+        let storage = MemoryStorage::new().with_usage(PeriodUsage {
+            session_hours: 0.0,
+            tokens: 1_500,
+        });
+        let ctx = RenderContext {
+            input: Arc::new(InputData::default()),
+            config: Arc::new(Config::default()),
+            terminal: TerminalCapabilities::default(),
+            preview_mode: false,
+            transcript: None,
+            storage: Some(Arc::new(storage)),
+        };
+
+        assert!(!component(0.0, 0).render(&ctx).await.visible);
+        let output = component(0.0, 10_000).render(&ctx).await;
+        assert!(output.visible);
+        assert!(output.text.starts_with("8.5k tok left"), "{}", output.text);
+    }
+}
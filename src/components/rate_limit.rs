@@ -5,7 +5,7 @@
 
 use std::time::{SystemTime, UNIX_EPOCH};
 
-use super::base::{Component, ComponentFactory, ComponentOutput, RenderContext};
+use super::base::{cache_key_of, Component, ComponentFactory, ComponentOutput, RenderContext};
 use crate::config::{BaseComponentConfig, Config, RateLimitComponentConfig};
 use crate::core::input::RateLimitWindow;
 use async_trait::async_trait;
@@ -130,6 +130,19 @@ impl Component for RateLimitComponent {
     fn base_config(&self, _ctx: &RenderContext) -> Option<&BaseComponentConfig> {
         Some(&self.config.base)
     }
+
+    fn cache_key(&self, ctx: &RenderContext) -> Option<u64> {
+        let window_key = |window: Option<&RateLimitWindow>| {
+            window.map(|w| (w.used_percentage.map(f64::to_bits), w.resets_at))
+        };
+        let rate_limits = ctx.input.rate_limits.as_ref();
+        // 重置倒计时按分钟显示,分钟变化时也需要重新渲染
+        Some(cache_key_of(&(
+            window_key(rate_limits.and_then(|r| r.five_hour.as_ref())),
+            window_key(rate_limits.and_then(|r| r.seven_day.as_ref())),
+            Self::now_secs() / 60,
+        )))
+    }
 }
 
 /// Factory for creating rate limit components.
@@ -138,7 +151,7 @@ pub struct RateLimitComponentFactory;
 impl ComponentFactory for RateLimitComponentFactory {
     fn create(&self, config: &Config) -> Box<dyn Component> {
         Box::new(RateLimitComponent::new(
-            config.components.rate_limit.clone(),
+            (*config.components.rate_limit).clone(),
         ))
     }
 
@@ -160,6 +173,8 @@ mod tests {
             config: Arc::new(Config::default()),
             terminal: TerminalCapabilities::default(),
             preview_mode: false,
+            transcript: None,
+            storage: None,
         }
     }
 
@@ -0,0 +1,544 @@
+//! Component registry
+//!
+//! Maps component names to their `ComponentFactory`, and preset letters to
+//! component names. The generator resolves every name in `components.order`
+//! and every preset letter through the registry, so library consumers can add
+//! their own components next to the built-in ones:
+//!
+//! ```no_run
+//! use claude_code_statusline_pro::components::{
+//!     Component, ComponentFactory, ComponentRegistry, TextComponent,
+//! };
+//! use claude_code_statusline_pro::config::Config;
+//! use claude_code_statusline_pro::core::{GeneratorOptions, StatuslineGenerator};
+//!
+//! struct HostFactory;
+//!
+//! impl ComponentFactory for HostFactory {
+//!     fn create(&self, config: &Config) -> Box<dyn Component> {
+//!         Box::new(TextComponent::new((*config.components.text).clone()))
+//!     }
+//!
+//!     fn name(&self) -> &str {
+//!         "host"
+//!     }
+//! }
+//!
+//! let mut registry = ComponentRegistry::default();
+//! registry.register_with_letter(HostFactory, 'O')?;
+//!
+//! let mut config = Config::default();
+//! config.preset = Some("MO".to_string());
+//! let generator = StatuslineGenerator::with_registry(config, GeneratorOptions::default(), registry);
+//! # Ok::<(), anyhow::Error>(())
+//! ```
+
+use std::collections::{BTreeMap, HashMap};
+
+use anyhow::{bail, Result};
+
+use super::base::ComponentFactory;
+use super::{
+    BranchComponentFactory, FieldComponentFactory, HookComponentFactory, ModelComponentFactory,
+    ProjectComponentFactory, QuotaComponentFactory, RateLimitComponentFactory,
+    RepoComponentFactory, SessionComponentFactory, StatusComponentFactory, TextComponentFactory,
+    TicketComponentFactory, TokensComponentFactory, TrustComponentFactory, UsageComponentFactory,
+};
+use crate::core::generator::separator_item;
+
+/// Names of the components shipped with the crate
+pub const BUILTIN_COMPONENTS: [&str; 15] = [
+    "project",
+    "model",
+    "branch",
+    "tokens",
+    "usage",
+    "rate_limit",
+    "status",
+    "hook",
+    "field",
+    "text",
+    "trust",
+    "quota",
+    "session",
+    "ticket",
+    "repo",
+];
+
+/// Preset letters of the built-in components
+const BUILTIN_LETTERS: [(char, &str); 15] = [
+    ('P', "project"),
+    ('M', "model"),
+    ('B', "branch"),
+    ('T', "tokens"),
+    ('U', "usage"),
+    ('R', "rate_limit"),
+    ('S', "status"),
+    ('H', "hook"),
+    ('F', "field"),
+    ('L', "text"),
+    ('D', "trust"),
+    ('Q', "quota"),
+    ('N', "session"),
+    ('J', "ticket"),
+    ('G', "repo"),
+];
+
+/// Component factories available to a generator, keyed by component name
+///
+/// `Default` registers the built-in components.
+pub struct ComponentRegistry {
+    factories: HashMap<String, Box<dyn ComponentFactory>>,
+    /// Preset letter (uppercase) → component name
+    letters: BTreeMap<char, String>,
+}
+
+impl ComponentRegistry {
+    /// Registry without any component
+    #[must_use]
+    pub fn empty() -> Self {
+        Self {
+            factories: HashMap::new(),
+            letters: BTreeMap::new(),
+        }
+    }
+
+    /// Registry with all built-in components
+    #[must_use]
+    pub fn with_builtins() -> Self {
+        let mut registry = Self::empty();
+        registry.register(ProjectComponentFactory);
+        registry.register(ModelComponentFactory);
+        registry.register(BranchComponentFactory);
+        registry.register(TokensComponentFactory);
+        registry.register(UsageComponentFactory);
+        registry.register(RateLimitComponentFactory);
+        registry.register(StatusComponentFactory);
+        registry.register(HookComponentFactory);
+        registry.register(FieldComponentFactory);
+        registry.register(TextComponentFactory);
+        registry.register(TrustComponentFactory);
+        registry.register(QuotaComponentFactory);
+        registry.register(SessionComponentFactory);
+        registry.register(TicketComponentFactory);
+        registry.register(RepoComponentFactory);
+        for (letter, name) in BUILTIN_LETTERS {
+            registry.letters.insert(letter, name.to_string());
+        }
+        registry
+    }
+
+    /// Register `factory` under its `name()`.
+    ///
+    /// 同名的已有工厂(包括内置组件)会被替换并返回,可用于覆盖内置实现。
+    pub fn register<F>(&mut self, factory: F) -> Option<Box<dyn ComponentFactory>>
+    where
+        F: ComponentFactory + 'static,
+    {
+        self.register_boxed(Box::new(factory))
+    }
+
+    /// Register an already boxed factory under its `name()`.
+    pub fn register_boxed(
+        &mut self,
+        factory: Box<dyn ComponentFactory>,
+    ) -> Option<Box<dyn ComponentFactory>> {
+        self.factories.insert(factory.name().to_string(), factory)
+    }
+
+    /// Register `factory` and make it available in presets as `letter`.
+    ///
+    /// # Errors
+    ///
+    /// Fails without registering anything when `letter` is not an ASCII letter
+    /// or digit, or is already used by another component.
+    pub fn register_with_letter<F>(&mut self, factory: F, letter: char) -> Result<()>
+    where
+        F: ComponentFactory + 'static,
+    {
+        let letter = self.check_letter(letter, factory.name())?;
+        self.letters.insert(letter, factory.name().to_string());
+        self.register(factory);
+        Ok(())
+    }
+
+    /// Make the registered component `name` available in presets as `letter`.
+    ///
+    /// 一个组件可以有多个字母;字母大小写不敏感。
+    ///
+    /// # Errors
+    ///
+    /// Fails when `name` is not registered, `letter` is not an ASCII letter or
+    /// digit, or `letter` is already used by another component.
+    pub fn assign_letter(&mut self, letter: char, name: &str) -> Result<()> {
+        if !self.contains(name) {
+            bail!(
+                "无法为组件 '{name}' 设置预设字母 '{letter}': 组件未注册(已注册: {})",
+                self.names().join(", ")
+            );
+        }
+        let letter = self.check_letter(letter, name)?;
+        self.letters.insert(letter, name.to_string());
+        Ok(())
+    }
+
+    /// Normalized `letter`, if it can be used for `name`
+    fn check_letter(&self, letter: char, name: &str) -> Result<char> {
+        if !letter.is_ascii_alphanumeric() {
+            bail!("组件 '{name}' 的预设字母 '{letter}' 无效: 只能使用 A-Z 或 0-9");
+        }
+        let letter = letter.to_ascii_uppercase();
+        match self.letters.get(&letter) {
+            Some(owner) if owner != name => bail!(
+                "预设字母 '{letter}' 已被组件 '{owner}' 使用,无法分配给 '{name}'(可用字母: {})",
+                self.free_letters()
+            ),
+            _ => Ok(letter),
+        }
+    }
+
+    /// Unused preset letters, for error messages
+    fn free_letters(&self) -> String {
+        ('A'..='Z')
+            .chain('0'..='9')
+            .filter(|letter| !self.letters.contains_key(letter))
+            .collect()
+    }
+
+    /// Remove the factory registered as `name`, along with its preset letters
+    pub fn unregister(&mut self, name: &str) -> Option<Box<dyn ComponentFactory>> {
+        self.letters.retain(|_, owner| owner != name);
+        self.factories.remove(name)
+    }
+
+    /// Factory registered as `name`
+    #[must_use]
+    pub fn get(&self, name: &str) -> Option<&dyn ComponentFactory> {
+        self.factories.get(name).map(AsRef::as_ref)
+    }
+
+    #[must_use]
+    pub fn contains(&self, name: &str) -> bool {
+        self.factories.contains_key(name)
+    }
+
+    /// Registered component names, sorted
+    #[must_use]
+    pub fn names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.factories.keys().map(String::as_str).collect();
+        names.sort_unstable();
+        names
+    }
+
+    /// Component bound to preset `letter` (case-insensitive)
+    #[must_use]
+    pub fn component_for_letter(&self, letter: char) -> Option<&str> {
+        self.letters
+            .get(&letter.to_ascii_uppercase())
+            .map(String::as_str)
+    }
+
+    /// Preset letters and the components they stand for, sorted by letter
+    pub fn letters(&self) -> impl Iterator<Item = (char, &str)> {
+        self.letters
+            .iter()
+            .map(|(letter, name)| (*letter, name.as_str()))
+    }
+
+    /// Parse a preset into a component order.
+    ///
+    /// A preset is either a string of letters (`"PMBT"`) or a comma-separated
+    /// list of component names (`"project,model,tokens"`); single letters may
+    /// appear in the list too. Unknown letters and names are skipped; each
+    /// component appears once. Use [`Self::try_parse_preset`] to reject them.
+    #[must_use]
+    pub fn parse_preset(&self, preset: &str) -> Vec<String> {
+        self.scan_preset(preset).0
+    }
+
+    /// Parse a preset like [`Self::parse_preset`], rejecting unknown entries.
+    ///
+    /// 字母串中的非字母数字字符(如 `PM-BT` 中的 `-`)只作分隔,不算错误。
+    ///
+    /// # Errors
+    ///
+    /// Fails when the preset contains an unknown letter or component name, or
+    /// no component at all; the message lists the valid letters and names.
+    pub fn try_parse_preset(&self, preset: &str) -> Result<Vec<String>> {
+        let (order, unknown) = self.scan_preset(preset);
+        if !unknown.is_empty() {
+            bail!(
+                "预设 '{preset}' 包含未知的组件: {}\n{}",
+                unknown.join(", "),
+                self.preset_help()
+            );
+        }
+        if order.is_empty() {
+            bail!("预设 '{preset}' 不包含任何组件\n{}", self.preset_help());
+        }
+        Ok(order)
+    }
+
+    /// Check an explicit `components.order` list.
+    ///
+    /// Items are registered component names or separators (`space`, `sep:…`).
+    ///
+    /// # Errors
+    ///
+    /// Fails on the first unknown item, listing the registered names.
+    pub fn check_order(&self, order: &[String]) -> Result<()> {
+        for item in order {
+            if separator_item(item).is_none() && !self.contains(item) {
+                bail!(
+                    "未知的组件 '{item}'(可用: {};分隔符: space、sep:<文本>)",
+                    self.names().join(", ")
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// Component order of `preset` and the entries that matched nothing
+    fn scan_preset(&self, preset: &str) -> (Vec<String>, Vec<String>) {
+        let preset = preset.trim();
+        let mut order: Vec<String> = Vec::new();
+        let mut unknown = Vec::new();
+        let mut push = |name: &str| {
+            if !order.iter().any(|existing| existing == name) {
+                order.push(name.to_string());
+            }
+        };
+        if preset.contains(',') || self.resolve_name(preset).is_some() {
+            for item in preset
+                .split(',')
+                .map(str::trim)
+                .filter(|item| !item.is_empty())
+            {
+                let mut chars = item.chars();
+                let letter = match (chars.next(), chars.next()) {
+                    (Some(letter), None) => self.component_for_letter(letter),
+                    _ => None,
+                };
+                match self.resolve_name(item).or(letter) {
+                    Some(name) => push(name),
+                    None => unknown.push(item.to_string()),
+                }
+            }
+        } else {
+            for letter in preset.chars() {
+                match self.component_for_letter(letter) {
+                    Some(name) => push(name),
+                    None if letter.is_ascii_alphanumeric() => unknown.push(letter.to_string()),
+                    None => {}
+                }
+            }
+        }
+        (order, unknown)
+    }
+
+    /// Registered name matching `name`, ignoring ASCII case
+    fn resolve_name(&self, name: &str) -> Option<&str> {
+        self.factories
+            .get_key_value(name)
+            .or_else(|| self.factories.get_key_value(&name.to_ascii_lowercase()))
+            .map(|(name, _)| name.as_str())
+    }
+
+    /// Valid preset letters and names, for error messages
+    fn preset_help(&self) -> String {
+        let letters: Vec<String> = self
+            .letters()
+            .map(|(letter, name)| format!("{letter}={name}"))
+            .collect();
+        format!(
+            "  可用字母: {}\n  可用名称: {}",
+            letters.join(", "),
+            self.names().join(", ")
+        )
+    }
+
+    /// Bind the letters of a `preset_mapping` table (`"W" = "weather"`).
+    ///
+    /// Every entry is tried; the errors of the rejected ones are returned.
+    pub fn apply_preset_mapping(
+        &mut self,
+        mapping: &BTreeMap<String, String>,
+    ) -> Vec<anyhow::Error> {
+        let mut errors = Vec::new();
+        for (letter, name) in mapping {
+            let mut chars = letter.chars();
+            let result = match (chars.next(), chars.next()) {
+                (Some(letter), None) => self.assign_letter(letter, name),
+                _ => Err(anyhow::anyhow!("预设字母 '{letter}' 无效: 只能是单个字符")),
+            };
+            if let Err(err) = result {
+                errors.push(err);
+            }
+        }
+        errors
+    }
+}
+
+impl Default for ComponentRegistry {
+    fn default() -> Self {
+        Self::with_builtins()
+    }
+}
+
+impl std::fmt::Debug for ComponentRegistry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ComponentRegistry")
+            .field("components", &self.names())
+            .field("letters", &self.letters)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::{Component, TextComponent};
+    use crate::config::Config;
+
+    struct Renamed(&'static str);
+
+    impl ComponentFactory for Renamed {
+        fn create(&self, config: &Config) -> Box<dyn Component> {
+            Box::new(TextComponent::new((*config.components.text).clone()))
+        }
+
+        fn name(&self) -> &str {
+            self.0
+        }
+    }
+
+    #[test]
+    fn builtins_registered_by_name() {
+        let registry = ComponentRegistry::with_builtins();
+        let mut expected = BUILTIN_COMPONENTS.to_vec();
+        expected.sort_unstable();
+        assert_eq!(registry.names(), expected);
+        assert!(ComponentRegistry::empty().names().is_empty());
+    }
+
+    #[test]
+    fn register_replaces_and_unregister_removes() {
+        let mut registry = ComponentRegistry::with_builtins();
+
+        assert!(registry.register(Renamed("host")).is_none());
+        assert_eq!(
+            registry.get("host").map(ComponentFactory::name),
+            Some("host")
+        );
+
+        // 覆盖内置组件时返回被替换的工厂
+        assert!(registry.register(Renamed("text")).is_some());
+        assert!(registry.unregister("host").is_some());
+        assert!(!registry.contains("host"));
+    }
+
+    #[test]
+    fn custom_letters_participate_in_presets() -> Result<()> {
+        let mut registry = ComponentRegistry::with_builtins();
+        registry.register_with_letter(Renamed("host"), 'o')?;
+        registry.assign_letter('9', "host")?;
+
+        assert_eq!(registry.component_for_letter('O'), Some("host"));
+        assert_eq!(registry.parse_preset("Mo9P"), ["model", "host", "project"]);
+
+        registry.unregister("host");
+        assert_eq!(registry.parse_preset("MO"), ["model"]);
+        Ok(())
+    }
+
+    #[test]
+    fn presets_accept_component_names() -> Result<()> {
+        let registry = ComponentRegistry::with_builtins();
+        assert_eq!(
+            registry.try_parse_preset("project, model,tokens,model")?,
+            ["project", "model", "tokens"]
+        );
+        // 单个组件名优先于按字母拆分
+        assert_eq!(registry.try_parse_preset("model")?, ["model"]);
+        assert_eq!(registry.try_parse_preset("Branch,T")?, ["branch", "tokens"]);
+        assert_eq!(registry.try_parse_preset("PM-BT")?.len(), 4);
+        Ok(())
+    }
+
+    #[test]
+    fn unknown_preset_entries_list_valid_options() {
+        let registry = ComponentRegistry::with_builtins();
+        let err = registry
+            .try_parse_preset("PMXZ")
+            .map_err(|err| err.to_string());
+        assert!(
+            matches!(&err, Err(message) if message.contains("X, Z")
+                && message.contains("P=project")
+                && message.contains("rate_limit")),
+            "{err:?}"
+        );
+        let err = registry
+            .try_parse_preset("project,weather")
+            .map_err(|err| err.to_string());
+        assert!(
+            matches!(&err, Err(message) if message.contains("weather")),
+            "{err:?}"
+        );
+        assert!(registry.try_parse_preset(" , ").is_err());
+
+        // 宽松解析跳过未知项
+        assert_eq!(
+            registry.parse_preset("project,weather,model"),
+            ["project", "model"]
+        );
+        assert_eq!(registry.parse_preset("PXM"), ["project", "model"]);
+    }
+
+    #[test]
+    fn explicit_orders_accept_names_and_separators() {
+        let registry = ComponentRegistry::with_builtins();
+        let order = |items: &[&str]| items.iter().map(ToString::to_string).collect::<Vec<_>>();
+        assert!(registry
+            .check_order(&order(&["project", "space", "sep:|", "status"]))
+            .is_ok());
+        let err = registry
+            .check_order(&order(&["project", "P"]))
+            .map_err(|err| err.to_string());
+        assert!(
+            matches!(&err, Err(message) if message.contains("'P'") && message.contains("branch")),
+            "{err:?}"
+        );
+    }
+
+    #[test]
+    fn preset_mapping_reports_rejected_entries() {
+        let mut registry = ComponentRegistry::with_builtins();
+        registry.register(Renamed("host"));
+        let mapping = BTreeMap::from([
+            ("O".to_string(), "host".to_string()),
+            ("WX".to_string(), "host".to_string()),
+            ("P".to_string(), "host".to_string()),
+        ]);
+        assert_eq!(registry.apply_preset_mapping(&mapping).len(), 2);
+        assert_eq!(registry.component_for_letter('o'), Some("host"));
+    }
+
+    #[test]
+    fn conflicting_or_invalid_letters_rejected() {
+        let mut registry = ComponentRegistry::with_builtins();
+
+        let err = registry
+            .register_with_letter(Renamed("host"), 'p')
+            .map_err(|err| err.to_string());
+        assert!(
+            matches!(&err, Err(message) if message.contains("'project'") && message.contains('O')),
+            "{err:?}"
+        );
+        assert!(!registry.contains("host"));
+
+        assert!(registry.register_with_letter(Renamed("host"), '-').is_err());
+        assert!(registry.assign_letter('W', "missing").is_err());
+        // 同一组件重复声明同一字母不算冲突
+        assert!(registry.assign_letter('P', "project").is_ok());
+    }
+}
@@ -0,0 +1,251 @@
+//! Repository statistics component implementation.
+//!
+//! Shows the scale of the repository: tracked files (index entries) and the
+//! size of the pack files, e.g. `12k files · 1.2 GB`. The statistics are
+//! cached in `statusline-pro/cache/` until the pack directory or the index
+//! changes, or for at most [`STATS_TTL`]. Above
+//! `branch.performance.large_repo_threshold` the branch component skips status
+//! collection; the segment then switches to `large_color` and appends
+//! `skipped_text`, so users of huge monorepos can see why dirty counts are
+//! missing. Requires the `git` feature.
+
+use std::path::{Path, PathBuf};
+#[cfg(feature = "git")]
+use std::time::Duration;
+
+use super::base::{Component, ComponentFactory, ComponentOutput, RenderContext};
+use crate::config::{BaseComponentConfig, Config, RepoComponentConfig};
+use crate::git::GitRepoStats;
+#[cfg(feature = "git")]
+use crate::git::GitService;
+use async_trait::async_trait;
+use tokio::task;
+
+/// How long cached statistics are reused while the pack directory and the
+/// index stay unchanged; new loose objects do not touch either
+#[cfg(feature = "git")]
+const STATS_TTL: Duration = Duration::from_secs(10 * 60);
+
+/// Repository statistics component.
+pub struct RepoComponent {
+    config: RepoComponentConfig,
+}
+
+impl RepoComponent {
+    #[must_use]
+    pub const fn new(config: RepoComponentConfig) -> Self {
+        Self { config }
+    }
+
+    fn summary(&self, stats: GitRepoStats) -> Vec<String> {
+        let mut parts = Vec::new();
+        if self.config.show_files {
+            parts.push(format!("{} files", compact_count(stats.entries)));
+        }
+        if self.config.show_size {
+            parts.push(format_bytes(stats.pack_bytes));
+        }
+        parts
+    }
+}
+
+/// `850`, `12k`, `1.2M`
+#[allow(clippy::cast_precision_loss)]
+fn compact_count(count: usize) -> String {
+    match count {
+        0..1_000 => count.to_string(),
+        1_000..10_000 => format!("{:.1}k", count as f64 / 1_000.0),
+        10_000..1_000_000 => format!("{}k", count / 1_000),
+        _ => format!("{:.1}M", count as f64 / 1_000_000.0),
+    }
+}
+
+/// `512 B`, `340 MB`, `1.2 GB`
+#[allow(clippy::cast_precision_loss)]
+fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KB", "MB", "GB", "TB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{bytes} B")
+    } else if size >= 100.0 {
+        format!("{size:.0} {}", UNITS[unit])
+    } else {
+        format!("{size:.1} {}", UNITS[unit])
+    }
+}
+
+/// Statistics of the repository containing `dir`, reused from `cache_dir`
+/// when given
+#[cfg(feature = "git")]
+fn collect_stats(dir: &Path, cache_dir: Option<&Path>) -> Option<GitRepoStats> {
+    use crate::storage::{aggregate_cache, ProjectId};
+
+    let service = GitService::discover(dir).ok()?;
+    let Some(cache_dir) = cache_dir else {
+        return Some(service.repo_stats());
+    };
+    let name = format!(
+        "repo-{}",
+        ProjectId::from_path(&service.git_dir().to_string_lossy())
+    );
+    Some(aggregate_cache::cached(
+        &aggregate_cache::cache_path(cache_dir, &name),
+        &service.repo_stats_key(),
+        STATS_TTL,
+        chrono::Utc::now(),
+        || service.repo_stats(),
+    ))
+}
+
+#[cfg(not(feature = "git"))]
+const fn collect_stats(_dir: &Path, _cache_dir: Option<&Path>) -> Option<GitRepoStats> {
+    None
+}
+
+/// Where statistics are cached; previews, in-memory storage and builds
+/// without storage do not write to disk
+fn cache_dir(ctx: &RenderContext) -> Option<PathBuf> {
+    (cfg!(feature = "storage") && !ctx.preview_mode && ctx.storage.is_none())
+        .then(crate::storage::user_data_dir)
+}
+
+#[async_trait]
+impl Component for RepoComponent {
+    fn name(&self) -> &'static str {
+        "repo"
+    }
+
+    fn is_enabled(&self, _ctx: &RenderContext) -> bool {
+        self.config.base.enabled
+    }
+
+    async fn render(&self, ctx: &RenderContext) -> ComponentOutput {
+        if !self.is_enabled(ctx) {
+            return ComponentOutput::hidden();
+        }
+        let Some(dir) = ctx
+            .input
+            .current_dir()
+            .or_else(|| ctx.input.project_root_dir())
+            .map(PathBuf::from)
+        else {
+            return ComponentOutput::hidden();
+        };
+        let cache_dir = cache_dir(ctx);
+        let Ok(Some(stats)) =
+            task::spawn_blocking(move || collect_stats(&dir, cache_dir.as_deref())).await
+        else {
+            return ComponentOutput::hidden();
+        };
+
+        let mut parts = self.summary(stats);
+        let performance = &ctx.config.components.branch.performance;
+        let skipped = performance.skip_on_large_repo
+            && stats.entries as u64 > performance.large_repo_threshold;
+        if skipped && !self.config.skipped_text.is_empty() {
+            parts.push(self.config.skipped_text.clone());
+        }
+        if parts.is_empty() {
+            return ComponentOutput::hidden();
+        }
+        let text_color = if skipped {
+            &self.config.large_color
+        } else {
+            &self.config.base.text_color
+        };
+        ComponentOutput::new(parts.join(" · "))
+            .with_icon(self.select_icon(ctx).unwrap_or_default())
+            .with_icon_color(ctx.config.palette.resolve(&self.config.base.icon_color))
+            .with_text_color(ctx.config.palette.resolve(text_color))
+    }
+
+    fn base_config(&self, _ctx: &RenderContext) -> Option<&BaseComponentConfig> {
+        Some(&self.config.base)
+    }
+}
+
+/// Factory for creating repository statistics components.
+pub struct RepoComponentFactory;
+
+impl ComponentFactory for RepoComponentFactory {
+    fn create(&self, config: &Config) -> Box<dyn Component> {
+        Box::new(RepoComponent::new((*config.components.repo).clone()))
+    }
+
+    fn name(&self) -> &'static str {
+        "repo"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_and_sizes_are_compact() {
+        assert_eq!(compact_count(850), "850");
+        assert_eq!(compact_count(4_321), "4.3k");
+        assert_eq!(compact_count(12_345), "12k");
+        assert_eq!(compact_count(2_500_000), "2.5M");
+        assert_eq!(format_bytes(512), "512 B");
+        assert_eq!(format_bytes(356_515_840), "340 MB");
+        assert_eq!(format_bytes(1_288_490_189), "1.2 GB");
+    }
+
+    #[test]
+    fn summary_respects_toggles() {
+        let stats = GitRepoStats {
+            entries: 12_000,
+            pack_bytes: 2048,
+            loose_objects: 3,
+        };
+        let component = RepoComponent::new(RepoComponentConfig::default());
+        assert_eq!(component.summary(stats), ["12k files", "2.0 KB"]);
+        let component = RepoComponent::new(RepoComponentConfig {
+            show_size: false,
+            ..RepoComponentConfig::default()
+        });
+        assert_eq!(component.summary(stats), ["12k files"]);
+    }
+
+    #[cfg(feature = "git")]
+    #[tokio::test]
+    async fn large_repositories_explain_skipped_status() -> anyhow::Result<()> {
+        use crate::components::TerminalCapabilities;
+        use crate::core::input::InputData;
+        use std::sync::Arc;
+
+        let dir = tempfile::tempdir()?;
+        let repo = git2::Repository::init(dir.path())?;
+        std::fs::write(dir.path().join("a.txt"), "a")?;
+        std::fs::write(dir.path().join("b.txt"), "b")?;
+        let mut index = repo.index()?;
+        index.add_path(Path::new("a.txt"))?;
+        index.add_path(Path::new("b.txt"))?;
+        index.write()?;
+
+        let mut config = Config::default();
+        config.components.branch.performance.large_repo_threshold = 1;
+        config.components.repo.show_size = false;
+        let ctx = RenderContext {
+            input: Arc::new(InputData {
+                cwd: Some(dir.path().to_string_lossy().into_owned()),
+                ..InputData::default()
+            }),
+            config: Arc::new(config),
+            terminal: TerminalCapabilities::default(),
+            preview_mode: false,
+            transcript: None,
+            storage: Some(Arc::new(crate::storage::MemoryStorage::new())),
+        };
+        let component = RepoComponentFactory.create(&ctx.config);
+        let output = component.render(&ctx).await;
+        assert_eq!(output.text, "2 files · no status");
+        Ok(())
+    }
+}
@@ -0,0 +1,193 @@
+//! Session badge component implementation.
+//!
+//! When several Claude Code sessions work on the same project at once
+//! (counted from snapshots updated within `active_minutes`), shows a small
+//! badge like `②` so panes can be told apart. Sessions can also be given
+//! fixed labels in `labels`, which are shown regardless of the count.
+
+use std::time::Duration;
+
+use super::base::{Component, ComponentFactory, ComponentOutput, RenderContext};
+use crate::config::{BaseComponentConfig, Config, SessionComponentConfig};
+use async_trait::async_trait;
+
+/// Session badge component.
+pub struct SessionComponent {
+    config: SessionComponentConfig,
+}
+
+impl SessionComponent {
+    #[must_use]
+    pub const fn new(config: SessionComponentConfig) -> Self {
+        Self { config }
+    }
+
+    /// Custom label of `session_id`: an exact key, otherwise the longest key
+    /// the id starts with
+    fn label(&self, session_id: &str) -> Option<&str> {
+        self.config
+            .labels
+            .get(session_id)
+            .or_else(|| {
+                self.config
+                    .labels
+                    .iter()
+                    .filter(|(prefix, _)| {
+                        !prefix.is_empty() && session_id.starts_with(prefix.as_str())
+                    })
+                    .max_by_key(|(prefix, _)| prefix.len())
+                    .map(|(_, label)| label)
+            })
+            .map(String::as_str)
+            .filter(|label| !label.is_empty())
+    }
+
+    fn output(&self, text: String, ctx: &RenderContext) -> ComponentOutput {
+        let mut output = ComponentOutput::new(text)
+            .with_icon_color(&self.config.base.icon_color)
+            .with_text_color(&self.config.base.text_color);
+        // 图标默认留空;空图标不设置,避免经典主题在徽标前多出一个空格
+        if let Some(icon) = self.select_icon(ctx).filter(|icon| !icon.is_empty()) {
+            output = output.with_icon(icon);
+        }
+        output
+    }
+}
+
+/// `①` … `⑳`, then `#21`
+fn badge(number: usize) -> String {
+    u32::try_from(number)
+        .ok()
+        .filter(|number| (1..=20).contains(number))
+        .and_then(|number| char::from_u32(0x2460 + number - 1))
+        .map_or_else(|| format!("#{number}"), String::from)
+}
+
+#[async_trait]
+impl Component for SessionComponent {
+    fn name(&self) -> &'static str {
+        "session"
+    }
+
+    fn is_enabled(&self, _ctx: &RenderContext) -> bool {
+        self.config.base.enabled
+    }
+
+    async fn render(&self, ctx: &RenderContext) -> ComponentOutput {
+        if !self.is_enabled(ctx) {
+            return ComponentOutput::hidden();
+        }
+        let Some(session_id) = ctx.input.session_id.as_deref() else {
+            return ComponentOutput::hidden();
+        };
+        if let Some(label) = self.label(session_id) {
+            return self.output(label.to_string(), ctx);
+        }
+        // preview 不读取真实存储,只展示徽标的位置
+        if ctx.preview_mode {
+            return self.output(badge(1), ctx);
+        }
+
+        let window = Duration::from_secs(self.config.active_minutes.saturating_mul(60));
+        let sessions = match ctx.storage().active_sessions(window).await {
+            Ok(sessions) => sessions,
+            Err(err) => {
+                eprintln!("[statusline] session: failed to list active sessions: {err}");
+                return ComponentOutput::hidden();
+            }
+        };
+        if sessions.len() < 2 && !self.config.show_when_single {
+            return ComponentOutput::hidden();
+        }
+        sessions
+            .iter()
+            .position(|session| session.session_id == session_id)
+            .map_or_else(ComponentOutput::hidden, |index| {
+                self.output(badge(index + 1), ctx)
+            })
+    }
+
+    fn base_config(&self, _ctx: &RenderContext) -> Option<&BaseComponentConfig> {
+        Some(&self.config.base)
+    }
+}
+
+/// Factory for creating session badge components.
+pub struct SessionComponentFactory;
+
+impl ComponentFactory for SessionComponentFactory {
+    fn create(&self, config: &Config) -> Box<dyn Component> {
+        Box::new(SessionComponent::new((*config.components.session).clone()))
+    }
+
+    fn name(&self) -> &'static str {
+        "session"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::TerminalCapabilities;
+    use crate::core::input::InputData;
+    use crate::storage::{ActiveSession, MemoryStorage};
+    use std::sync::Arc;
+
+    fn context(session_id: &str, active: &[&str]) -> RenderContext {
+        let sessions = active
+            .iter()
+            .map(|id| ActiveSession {
+                session_id: (*id).to_string(),
+                created_at: None,
+            })
+            .collect();
+        RenderContext {
+            input: Arc::new(InputData {
+                session_id: Some(session_id.to_string()),
+                ..InputData::default()
+            }),
+            config: Arc::new(Config::default()),
+            terminal: TerminalCapabilities::default(),
+            preview_mode: false,
+            transcript: None,
+            storage: Some(Arc::new(
+                MemoryStorage::new().with_active_sessions(sessions),
+            )),
+        }
+    }
+
+    #[tokio::test]
+    async fn badge_numbers_concurrent_sessions() {
+        let component = SessionComponent::new(SessionComponentConfig::default());
+
+        let output = component.render(&context("b", &["a", "b", "c"])).await;
+        assert!(output.visible);
+        assert_eq!(output.text, "②");
+
+        // 只有一个活跃会话时不需要区分
+        assert!(!component.render(&context("a", &["a"])).await.visible);
+    }
+
+    #[tokio::test]
+    async fn custom_labels_match_ids_and_prefixes() {
+        let component = SessionComponent::new(SessionComponentConfig {
+            labels: [("3f2a", "api"), ("3f2a9", "web")]
+                .into_iter()
+                .map(|(id, label)| (id.to_string(), label.to_string()))
+                .collect(),
+            ..SessionComponentConfig::default()
+        });
+
+        let output = component.render(&context("3f2a9c1e", &[])).await;
+        assert_eq!(output.text, "web");
+        let output = component.render(&context("3f2a01", &[])).await;
+        assert_eq!(output.text, "api");
+    }
+
+    #[test]
+    fn badges_fall_back_to_numbers() {
+        assert_eq!(badge(1), "①");
+        assert_eq!(badge(20), "⑳");
+        assert_eq!(badge(21), "#21");
+    }
+}
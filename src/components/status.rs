@@ -1,17 +1,24 @@
 //! Status component implementation
 //!
-//! Displays the current status of Claude (ready, thinking, tool, error, etc.),
-//! falling back to STDIN metadata when transcript data is unavailable.
-
-use std::fs;
-use std::sync::Mutex;
-use std::time::SystemTime;
+//! Displays the current status of Claude (ready, thinking, tool, error, etc.)
+//! from the shared transcript analysis, falling back to STDIN metadata when
+//! transcript data is unavailable.
 
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use regex::Regex;
 use serde_json::Value;
 
 use super::base::{Component, ComponentFactory, ComponentOutput, RenderContext};
-use crate::config::{BaseComponentConfig, Config, StatusComponentConfig};
+use crate::config::{
+    BaseComponentConfig, Config, Severity, StatusComponentConfig, StatusErrorRule,
+    StatusErrorSeverity,
+};
+use crate::core::TranscriptAnalysis;
+use crate::storage::ErrorStreak;
+use crate::terminal::detector::TerminalDetector;
+use crate::utils::i18n::translate;
+use crate::utils::{display_width, truncate_with_ellipsis, wrap_to_width};
 
 /// Status types rendered by the component
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -21,6 +28,49 @@ enum StatusType {
     Tool,
     Error,
     Warning,
+    /// A tool call has gone unanswered long enough to suggest a permission prompt
+    Waiting,
+}
+
+/// Consecutive error entries at the tail of the transcript
+#[derive(Debug, Clone, Default)]
+struct ErrorRun {
+    /// UUIDs of the failing assistant entries, newest first
+    uuids: Vec<Option<String>>,
+    /// A successful assistant entry precedes the run, so the transcript alone
+    /// is authoritative and the persisted streak must not be carried over
+    terminated: bool,
+}
+
+impl ErrorRun {
+    /// Combine the transcript run with the streak persisted by a previous process.
+    fn merge(&self, persisted: Option<&ErrorStreak>) -> ErrorStreak {
+        let observed = u32::try_from(self.uuids.len()).unwrap_or(u32::MAX);
+        let newest_uuid = self.uuids.first().cloned().flatten();
+
+        let Some(previous) = persisted.filter(|_| !self.terminated) else {
+            return ErrorStreak {
+                count: observed,
+                last_error_uuid: newest_uuid,
+            };
+        };
+
+        // 之前已经计过的条目不能重复累加:只加比持久化 uuid 更新的那几条
+        let newer = previous
+            .last_error_uuid
+            .as_deref()
+            .and_then(|uuid| {
+                self.uuids
+                    .iter()
+                    .position(|candidate| candidate.as_deref() == Some(uuid))
+            })
+            .map_or(observed, |idx| u32::try_from(idx).unwrap_or(u32::MAX));
+
+        ErrorStreak {
+            count: previous.count.saturating_add(newer),
+            last_error_uuid: newest_uuid.or_else(|| previous.last_error_uuid.clone()),
+        }
+    }
 }
 
 /// Resolved status payload used for rendering
@@ -29,6 +79,10 @@ struct StatusInfo {
     status_type: StatusType,
     message: String,
     details: Option<String>,
+    /// Present only when the status came from the transcript
+    error_run: Option<ErrorRun>,
+    /// Timestamp of the `tool_use` entry while its result is still missing
+    tool_started_at: Option<DateTime<Utc>>,
 }
 
 impl StatusInfo {
@@ -37,6 +91,8 @@ impl StatusInfo {
             status_type: StatusType::Ready,
             message: "Ready".to_string(),
             details: None,
+            error_run: None,
+            tool_started_at: None,
         }
     }
 
@@ -45,6 +101,8 @@ impl StatusInfo {
             status_type: StatusType::Thinking,
             message: "Thinking".to_string(),
             details: None,
+            error_run: None,
+            tool_started_at: None,
         }
     }
 
@@ -53,6 +111,8 @@ impl StatusInfo {
             status_type: StatusType::Tool,
             message: "Tool".to_string(),
             details,
+            error_run: None,
+            tool_started_at: None,
         }
     }
 
@@ -61,6 +121,18 @@ impl StatusInfo {
             status_type: StatusType::Error,
             message: "Error".to_string(),
             details,
+            error_run: None,
+            tool_started_at: None,
+        }
+    }
+
+    fn waiting(details: Option<String>) -> Self {
+        Self {
+            status_type: StatusType::Waiting,
+            message: "Waiting".to_string(),
+            details,
+            error_run: None,
+            tool_started_at: None,
         }
     }
 
@@ -69,38 +141,83 @@ impl StatusInfo {
             status_type: StatusType::Warning,
             message: message.to_string(),
             details,
+            error_run: None,
+            tool_started_at: None,
         }
     }
 }
 
-#[derive(Clone)]
-struct TranscriptCache {
-    mtime: SystemTime,
-    info: StatusInfo,
+/// How a single assistant entry was classified
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum EntryClass {
+    Normal,
+    Error(Option<String>),
+    Warning(String),
+}
+
+/// `[[components.status.error_rules]]` with the pattern compiled
+struct CompiledErrorRule {
+    regex: Regex,
+    label: Option<String>,
+    severity: StatusErrorSeverity,
+}
+
+impl CompiledErrorRule {
+    /// Invalid patterns are skipped here; `validate` reports them.
+    fn compile_all(rules: &[StatusErrorRule]) -> Vec<Self> {
+        rules
+            .iter()
+            .filter_map(|rule| {
+                Regex::new(&rule.pattern).ok().map(|regex| Self {
+                    regex,
+                    label: rule.label.clone(),
+                    severity: rule.severity,
+                })
+            })
+            .collect()
+    }
+
+    fn classify(&self, text: &str) -> Option<EntryClass> {
+        let captures = self.regex.captures(text)?;
+        let label = self.label.as_ref().map(|template| {
+            let mut expanded = String::new();
+            captures.expand(template, &mut expanded);
+            expanded
+        });
+
+        Some(match self.severity {
+            StatusErrorSeverity::Error => {
+                EntryClass::Error(label.or_else(|| Some(text.to_string())))
+            }
+            StatusErrorSeverity::Warning => {
+                EntryClass::Warning(label.unwrap_or_else(|| "Warning".to_string()))
+            }
+            StatusErrorSeverity::Ignore => EntryClass::Normal,
+        })
+    }
 }
 
 /// Status component
 pub struct StatusComponent {
     config: StatusComponentConfig,
-    transcript_cache: Mutex<Option<TranscriptCache>>,
+    error_rules: Vec<CompiledErrorRule>,
 }
 
 impl StatusComponent {
     /// Create a new status component
     #[must_use]
-    pub const fn new(config: StatusComponentConfig) -> Self {
+    pub fn new(config: StatusComponentConfig) -> Self {
+        let error_rules = CompiledErrorRule::compile_all(&config.error_rules);
         Self {
             config,
-            transcript_cache: Mutex::new(None),
+            error_rules,
         }
     }
 
     /// Resolve status using transcript when available, otherwise fall back to stdin metadata.
     fn resolve_status(&self, ctx: &RenderContext) -> StatusInfo {
-        if let Some(path) = ctx.input.transcript_path.as_deref() {
-            if let Some(info) = self.parse_transcript_status(path, ctx) {
-                return info;
-            }
+        if let Some(transcript) = ctx.transcript() {
+            return self.parse_transcript_status(&transcript);
         }
 
         Self::parse_status_from_input(ctx)
@@ -127,51 +244,22 @@ impl StatusComponent {
         StatusInfo::ready()
     }
 
-    /// Parse transcript lines and infer status (mirrors TypeScript implementation)
-    fn parse_transcript_status(&self, path: &str, _ctx: &RenderContext) -> Option<StatusInfo> {
-        let metadata = fs::metadata(path).ok()?;
-        let modified = metadata.modified().ok()?;
-
-        if let Some(cache) = self
-            .transcript_cache
-            .lock()
-            .ok()
-            .and_then(|guard| guard.clone())
-        {
-            if cache.mtime == modified {
-                return Some(cache.info);
-            }
-        }
-
-        let content = fs::read_to_string(path).ok()?;
-        if content.trim().is_empty() {
-            let info = StatusInfo::ready();
-            self.memoize_transcript(modified, info.clone());
-            return Some(info);
-        }
-
-        let lines: Vec<&str> = content.lines().collect();
-        if lines.is_empty() {
-            let info = StatusInfo::ready();
-            self.memoize_transcript(modified, info.clone());
-            return Some(info);
+    /// Infer status from the shared transcript tail (mirrors TypeScript implementation)
+    fn parse_transcript_status(&self, transcript: &TranscriptAnalysis) -> StatusInfo {
+        let entries = transcript.entries();
+        if entries.is_empty() {
+            return StatusInfo::ready();
         }
 
         let mut last_entry_type: Option<String> = None;
         let mut last_stop_reason: Option<String> = None;
-        let mut assistant_error = false;
-        let mut assistant_error_detail: Option<String> = None;
-
-        for line in lines.iter().rev() {
-            let trimmed = line.trim();
-            if trimmed.is_empty() {
-                continue;
-            }
-
-            let Ok(value) = serde_json::from_str::<Value>(trimmed) else {
-                continue;
-            };
+        let mut latest_class = EntryClass::Normal;
+        let mut latest_timestamp: Option<DateTime<Utc>> = None;
+        let mut tool_result_seen = false;
+        let mut error_run = ErrorRun::default();
+        let mut seen_assistant = false;
 
+        for value in entries.iter().rev() {
             if last_entry_type.is_none() {
                 last_entry_type = value
                     .get("type")
@@ -180,6 +268,9 @@ impl StatusComponent {
             }
 
             if value.get("type").and_then(|v| v.as_str()) != Some("assistant") {
+                if !seen_assistant && Self::is_tool_result_entry(value) {
+                    tool_result_seen = true;
+                }
                 continue;
             }
 
@@ -191,23 +282,39 @@ impl StatusComponent {
                 continue;
             }
 
-            last_stop_reason = message
-                .get("stop_reason")
-                .and_then(|v| v.as_str())
-                .map(std::string::ToString::to_string);
+            let class = self.classify_entry(value);
+            let is_error = matches!(class, EntryClass::Error(_));
+
+            // 最新一条 assistant 决定当前状态;之后继续往前数连续失败的轮次
+            if !seen_assistant {
+                seen_assistant = true;
+                last_stop_reason = message
+                    .get("stop_reason")
+                    .and_then(|v| v.as_str())
+                    .map(std::string::ToString::to_string);
+                latest_timestamp = Self::entry_timestamp(value);
+                latest_class = class;
+            }
 
-            if Self::is_error_entry(&value) {
-                assistant_error = true;
-                assistant_error_detail = Self::get_error_details(&value);
+            if !is_error {
+                error_run.terminated = true;
+                break;
             }
 
-            break;
+            error_run.uuids.push(
+                value
+                    .get("uuid")
+                    .and_then(Value::as_str)
+                    .map(std::string::ToString::to_string),
+            );
         }
 
-        let tool_name = Self::collect_recent_tool_name(&lines).filter(|name| !name.is_empty());
+        let tool_name = Self::collect_recent_tool_name(entries).filter(|name| !name.is_empty());
 
-        let info = if assistant_error {
-            StatusInfo::error(assistant_error_detail)
+        let mut info = if let EntryClass::Error(details) = latest_class {
+            StatusInfo::error(details)
+        } else if let EntryClass::Warning(label) = latest_class {
+            StatusInfo::warning(&label, None)
         } else if let Some(reason) = last_stop_reason.as_deref() {
             Self::parse_stop_reason(reason, tool_name)
         } else if matches!(last_entry_type.as_deref(), Some("user")) {
@@ -215,18 +322,73 @@ impl StatusComponent {
         } else {
             StatusInfo::ready()
         };
+        info.error_run = Some(error_run);
+        if info.status_type == StatusType::Tool && !tool_result_seen {
+            info.tool_started_at = latest_timestamp;
+        }
+
+        info
+    }
 
-        self.memoize_transcript(modified, info.clone());
+    fn entry_timestamp(entry: &Value) -> Option<DateTime<Utc>> {
+        let raw = entry.get("timestamp").and_then(Value::as_str)?;
+        DateTime::parse_from_rfc3339(raw)
+            .ok()
+            .map(|timestamp| timestamp.with_timezone(&Utc))
+    }
 
-        Some(info)
+    /// Seconds the current tool has been running, measured from its `tool_use` entry.
+    fn tool_elapsed_secs(info: &StatusInfo, now: DateTime<Utc>) -> Option<u64> {
+        let started = info.tool_started_at?;
+        // 时钟回拨或时间戳来自未来时按 0 处理
+        Some(u64::try_from((now - started).num_seconds()).unwrap_or(0))
     }
 
-    fn memoize_transcript(&self, mtime: SystemTime, info: StatusInfo) {
-        if let Ok(mut guard) = self.transcript_cache.lock() {
-            *guard = Some(TranscriptCache { mtime, info });
+    fn format_elapsed(secs: u64) -> String {
+        match secs {
+            0..60 => format!("{secs}s"),
+            60..3600 => format!("{}m{:02}s", secs / 60, secs % 60),
+            _ => format!("{}h{:02}m", secs / 3600, secs % 3600 / 60),
         }
     }
 
+    /// Long error details go on their own lines when multiline output is on.
+    fn details_below(&self, info: &StatusInfo, details: &str, ctx: &RenderContext) -> bool {
+        let limit = self.config.details_inline_max_width;
+        info.status_type == StatusType::Error
+            && limit > 0
+            && ctx.config.multiline.as_ref().is_some_and(|m| m.enabled)
+            && display_width(details) > limit
+    }
+
+    /// Wrap details to the terminal width, eliding whatever exceeds `details_max_lines`.
+    fn wrap_details(&self, details: &str, columns: Option<usize>) -> Vec<String> {
+        const DEFAULT_COLUMNS: usize = 80;
+        const FIRST_PREFIX: &str = "  └ ";
+        const NEXT_PREFIX: &str = "    ";
+
+        let width = columns
+            .unwrap_or(DEFAULT_COLUMNS)
+            .saturating_sub(display_width(FIRST_PREFIX))
+            .max(10);
+        let max_lines = self.config.details_max_lines.max(1);
+
+        let mut wrapped = wrap_to_width(details, width);
+        if wrapped.len() > max_lines {
+            let rest = wrapped.split_off(max_lines - 1).join(" ");
+            wrapped.push(truncate_with_ellipsis(&rest, width, "…"));
+        }
+
+        wrapped
+            .into_iter()
+            .enumerate()
+            .map(|(idx, line)| {
+                let prefix = if idx == 0 { FIRST_PREFIX } else { NEXT_PREFIX };
+                format!("{prefix}{line}")
+            })
+            .collect()
+    }
+
     fn parse_status_string(status: &str) -> StatusInfo {
         let normalized = status.to_lowercase();
         match normalized.as_str() {
@@ -234,6 +396,7 @@ impl StatusComponent {
             "tool" | "tool_use" => StatusInfo::tool(None),
             "error" => StatusInfo::error(None),
             "warning" => StatusInfo::warning("Warning", None),
+            "waiting" | "permission" => StatusInfo::waiting(None),
             _ => StatusInfo::ready(),
         }
     }
@@ -253,7 +416,7 @@ impl StatusComponent {
         let style = &ctx.config.style;
         let terminal = &ctx.terminal;
 
-        if term_cfg.force_text {
+        if term_cfg.text_only() {
             return self.status_icon_text(status_type).to_string();
         }
         if term_cfg.force_nerd_font {
@@ -285,6 +448,7 @@ impl StatusComponent {
             StatusType::Tool => &self.config.icons.emoji.tool,
             StatusType::Error => &self.config.icons.emoji.error,
             StatusType::Warning => &self.config.icons.emoji.warning,
+            StatusType::Waiting => &self.config.icons.emoji.waiting,
         }
     }
 
@@ -295,6 +459,7 @@ impl StatusComponent {
             StatusType::Tool => &self.config.icons.nerd.tool,
             StatusType::Error => &self.config.icons.nerd.error,
             StatusType::Warning => &self.config.icons.nerd.warning,
+            StatusType::Waiting => &self.config.icons.nerd.waiting,
         }
     }
 
@@ -305,9 +470,51 @@ impl StatusComponent {
             StatusType::Tool => &self.config.icons.text.tool,
             StatusType::Error => &self.config.icons.text.error,
             StatusType::Warning => &self.config.icons.text.warning,
+            StatusType::Waiting => &self.config.icons.text.waiting,
         }
     }
 
+    /// Resolve the consecutive error count, syncing it with the session snapshot.
+    ///
+    /// The statusline runs as a fresh process on every refresh, so the counter
+    /// lives in the snapshot; preview mode and inputs without a session only
+    /// use what the transcript shows.
+    async fn consecutive_errors(&self, info: &StatusInfo, ctx: &RenderContext) -> u32 {
+        let Some(run) = info.error_run.as_ref() else {
+            return u32::from(info.status_type == StatusType::Error);
+        };
+
+        let session_id = ctx
+            .input
+            .session_id
+            .as_deref()
+            .filter(|_| !ctx.preview_mode);
+        let Some(session_id) = session_id else {
+            return run.merge(None).count;
+        };
+
+        let persisted = ctx
+            .storage()
+            .session_history(session_id)
+            .await
+            .ok()
+            .flatten()
+            .map(|history| history.errors);
+        let streak = run.merge(persisted.as_ref());
+        if persisted.as_ref() != Some(&streak) {
+            if let Err(err) = ctx
+                .storage()
+                .update_error_streak(session_id, streak.clone())
+                .await
+            {
+                if ctx.config.debug {
+                    eprintln!("[statusline] failed to persist error streak: {err}");
+                }
+            }
+        }
+        streak.count
+    }
+
     fn get_status_color(&self, status_type: &StatusType) -> String {
         match status_type {
             StatusType::Ready => self.config.colors.ready.clone(),
@@ -315,7 +522,41 @@ impl StatusComponent {
             StatusType::Tool => self.config.colors.tool.clone(),
             StatusType::Error => self.config.colors.error.clone(),
             StatusType::Warning => self.config.colors.warning.clone(),
+            StatusType::Waiting => self.config.colors.waiting.clone(),
+        }
+    }
+
+    /// Classify an assistant entry: user rules first, then the built-in heuristics.
+    fn classify_entry(&self, entry: &Value) -> EntryClass {
+        if !self.error_rules.is_empty() {
+            let tool_error = entry
+                .get("toolUseResult")
+                .and_then(|result| result.get("error"))
+                .and_then(Value::as_str);
+            let message_texts = entry
+                .get("message")
+                .filter(|message| Self::is_stop_sequence_message(message))
+                .and_then(|message| message.get("content"))
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+                .filter(|item| item.get("type").and_then(Value::as_str) == Some("text"))
+                .filter_map(|item| item.get("text").and_then(Value::as_str));
+
+            let matched = tool_error
+                .into_iter()
+                .chain(message_texts)
+                .find_map(|text| self.error_rules.iter().find_map(|rule| rule.classify(text)));
+            if let Some(class) = matched {
+                return class;
+            }
+        }
+
+        if self.config.builtin_error_rules && Self::is_error_entry(entry) {
+            return EntryClass::Error(Self::get_error_details(entry));
         }
+
+        EntryClass::Normal
     }
 
     fn is_error_entry(entry: &Value) -> bool {
@@ -353,17 +594,14 @@ impl StatusComponent {
         })
     }
 
-    fn collect_recent_tool_name(lines: &[&str]) -> Option<String> {
+    fn collect_recent_tool_name(entries: &[Value]) -> Option<String> {
         const RECENT_WINDOW: usize = 5;
 
-        lines
+        entries
             .iter()
             .rev()
             .take(RECENT_WINDOW)
-            .map(|line| line.trim())
-            .filter(|trimmed| !trimmed.is_empty())
-            .filter_map(|trimmed| serde_json::from_str::<Value>(trimmed).ok())
-            .find_map(|value| Self::extract_tool_use_name(&value))
+            .find_map(Self::extract_tool_use_name)
     }
 
     fn tool_use_indicates_error(tool_use_result: &Value) -> bool {
@@ -435,6 +673,18 @@ impl StatusComponent {
         })
     }
 
+    fn is_tool_result_entry(entry: &Value) -> bool {
+        entry
+            .get("message")
+            .and_then(|message| message.get("content"))
+            .and_then(Value::as_array)
+            .is_some_and(|items| {
+                items
+                    .iter()
+                    .any(|item| item.get("type").and_then(Value::as_str) == Some("tool_result"))
+            })
+    }
+
     fn extract_tool_use_name(value: &Value) -> Option<String> {
         let content = value
             .get("message")
@@ -472,34 +722,84 @@ impl Component for StatusComponent {
             return ComponentOutput::hidden();
         }
 
-        let status_info = self.resolve_status(ctx);
+        let mut status_info = self.resolve_status(ctx);
+        let tool_elapsed = Self::tool_elapsed_secs(&status_info, Utc::now());
+        let waiting_after = self.config.waiting_after_seconds;
+        if waiting_after > 0 && tool_elapsed.is_some_and(|secs| secs >= waiting_after) {
+            status_info = StatusInfo::waiting(status_info.details);
+        }
 
         if status_info.status_type == StatusType::Ready && !self.config.show_when_idle {
             return ComponentOutput::hidden();
         }
 
+        let error_count = self.consecutive_errors(&status_info, ctx).await;
         let icon = self.get_status_icon(&status_info.status_type, ctx);
-        let mut text = status_info.message.clone();
+        let mut text = translate(&ctx.config, &status_info.message).to_string();
+
+        let tool_elapsed = tool_elapsed.filter(|_| self.config.show_tool_elapsed);
+        let mut details = status_info
+            .details
+            .as_deref()
+            .map(|details| translate(&ctx.config, details).to_string());
+        if let Some(elapsed) = tool_elapsed {
+            let elapsed = Self::format_elapsed(elapsed);
+            details = Some(match details.filter(|d| !d.is_empty()) {
+                Some(name) => format!("{name} · {elapsed}"),
+                None => elapsed,
+            });
+        }
+
+        if status_info.status_type == StatusType::Error
+            && self.config.show_error_count
+            && error_count > 1
+        {
+            text.push_str(" ×");
+            text.push_str(&error_count.to_string());
+        }
 
-        if let Some(details) = status_info.details.as_ref().filter(|d| !d.is_empty()) {
+        let mut detail_lines = Vec::new();
+        if let Some(details) = details.as_ref().filter(|d| !d.is_empty()) {
             let should_show_details = match status_info.status_type {
                 StatusType::Ready => self.config.show_recent_errors,
                 _ => true,
             };
 
-            if should_show_details {
+            if should_show_details && self.details_below(&status_info, details, ctx) {
+                detail_lines = self.wrap_details(details, TerminalDetector::detect_columns());
+            } else if should_show_details {
                 text.push_str(" (");
                 text.push_str(details);
                 text.push(')');
             }
         }
 
-        let color = self.get_status_color(&status_info.status_type);
+        let tool_stuck = status_info.status_type == StatusType::Tool
+            && self.config.tool_warning_seconds > 0
+            && tool_elapsed.is_some_and(|secs| secs >= self.config.tool_warning_seconds);
+        let color = if status_info.status_type == StatusType::Error
+            && error_count >= self.config.error_escalation_threshold
+        {
+            self.config.colors.error_critical.clone()
+        } else if tool_stuck {
+            self.config.colors.warning.clone()
+        } else {
+            self.get_status_color(&status_info.status_type)
+        };
+        let color = ctx.config.palette.resolve(&color);
+        let severity = match status_info.status_type {
+            StatusType::Error => Severity::Critical,
+            StatusType::Warning | StatusType::Waiting => Severity::Warning,
+            _ if tool_stuck => Severity::Warning,
+            _ => Severity::Normal,
+        };
 
         ComponentOutput::new(text)
             .with_icon(icon)
             .with_icon_color(color.clone())
             .with_text_color(color)
+            .with_detail_lines(detail_lines)
+            .with_severity(severity)
     }
 
     fn base_config(&self, _ctx: &RenderContext) -> Option<&BaseComponentConfig> {
@@ -512,7 +812,7 @@ pub struct StatusComponentFactory;
 
 impl ComponentFactory for StatusComponentFactory {
     fn create(&self, config: &Config) -> Box<dyn Component> {
-        Box::new(StatusComponent::new(config.components.status.clone()))
+        Box::new(StatusComponent::new((*config.components.status).clone()))
     }
 
     fn name(&self) -> &'static str {
@@ -525,6 +825,7 @@ mod tests {
     use super::*;
     use crate::components::TerminalCapabilities;
     use crate::core::InputData;
+    use crate::storage::{MemoryStorage, SessionHistory};
     use anyhow::{Context, Result};
     use serde_json::json;
     use std::io::Write;
@@ -559,6 +860,8 @@ mod tests {
             config: Arc::new(Config::default()),
             terminal: TerminalCapabilities::default(),
             preview_mode: false,
+            transcript: None,
+            storage: None,
         }
     }
 
@@ -594,6 +897,22 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_status_text_follows_language() -> TestResult {
+        let component = StatusComponent::new(StatusComponentConfig::default());
+        let mut ctx = create_test_context(json!({
+            "stop_reason": "max_tokens"
+        }));
+        ctx.config = Arc::new(Config {
+            language: "zh".to_string(),
+            ..Config::default()
+        });
+
+        let output = component.render(&ctx).await;
+        assert_eq!(output.text, "输出上限 (已达到 token 上限)");
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_status_hide_when_idle() -> TestResult {
         let component = StatusComponent::new(StatusComponentConfig::default());
@@ -630,6 +949,8 @@ mod tests {
             config: Arc::new(Config::default()),
             terminal: TerminalCapabilities::default(),
             preview_mode: false,
+            transcript: None,
+            storage: None,
         };
 
         let component = StatusComponent::new(StatusComponentConfig::default());
@@ -640,6 +961,143 @@ mod tests {
         Ok(())
     }
 
+    async fn render_running_tool(
+        started_secs_ago: i64,
+        configure: impl FnOnce(&mut StatusComponentConfig),
+    ) -> Result<ComponentOutput> {
+        let started = Utc::now() - chrono::Duration::seconds(started_secs_ago);
+        let mut file = NamedTempFile::new()?;
+        writeln!(
+            file,
+            "{}",
+            json!({
+                "type": "assistant",
+                "timestamp": started.to_rfc3339(),
+                "message": {
+                    "usage": {"input_tokens": 10},
+                    "stop_reason": "tool_use",
+                    "content": [{"type": "tool_use", "name": "Bash"}]
+                }
+            })
+        )
+        .context("failed to write running tool transcript")?;
+
+        let input = build_input(|input| {
+            input.transcript_path = Some(file.path().to_string_lossy().to_string());
+        });
+        let ctx = RenderContext {
+            input: Arc::new(input),
+            config: Arc::new(Config::default()),
+            terminal: TerminalCapabilities::default(),
+            preview_mode: false,
+            transcript: None,
+            storage: None,
+        };
+
+        let component = StatusComponent::new(build_status_config(configure));
+        Ok(component.render(&ctx).await)
+    }
+
+    #[tokio::test]
+    async fn test_running_tool_shows_elapsed_time() -> TestResult {
+        let output = render_running_tool(5, |_| {}).await?;
+        assert!(
+            output.text.starts_with("Tool (Bash · ") && output.text.ends_with("s)"),
+            "text={}",
+            output.text
+        );
+        assert_eq!(output.text_color, Some("blue".to_string()));
+
+        let stuck = render_running_tool(600, |_| {}).await?;
+        assert!(stuck.text.contains("Bash · 10m"), "text={}", stuck.text);
+        assert_eq!(stuck.text_color, Some("yellow".to_string()));
+
+        let disabled = render_running_tool(600, |config| {
+            config.show_tool_elapsed = false;
+        })
+        .await?;
+        assert_eq!(disabled.text, "Tool (Bash)");
+        assert_eq!(disabled.text_color, Some("blue".to_string()));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_unanswered_tool_becomes_waiting() -> TestResult {
+        let output = render_running_tool(30, |config| {
+            config.waiting_after_seconds = 20;
+        })
+        .await?;
+        assert!(
+            output.text.starts_with("Waiting (Bash · "),
+            "text={}",
+            output.text
+        );
+        assert_eq!(output.icon, Some("⏳".to_string()));
+        assert_eq!(output.text_color, Some("bright_magenta".to_string()));
+
+        let recent = render_running_tool(5, |config| {
+            config.waiting_after_seconds = 20;
+        })
+        .await?;
+        assert!(
+            recent.text.starts_with("Tool (Bash · "),
+            "text={}",
+            recent.text
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_answered_tool_is_not_waiting() -> TestResult {
+        let started = Utc::now() - chrono::Duration::seconds(120);
+        let mut file = NamedTempFile::new()?;
+        for entry in [
+            json!({
+                "type": "assistant",
+                "timestamp": started.to_rfc3339(),
+                "message": {
+                    "usage": {"input_tokens": 10},
+                    "stop_reason": "tool_use",
+                    "content": [{"type": "tool_use", "name": "Bash"}]
+                }
+            }),
+            json!({
+                "type": "user",
+                "message": {"content": [{"type": "tool_result", "content": "ok"}]}
+            }),
+        ] {
+            writeln!(file, "{entry}").context("failed to write transcript entry")?;
+        }
+
+        let input = build_input(|input| {
+            input.transcript_path = Some(file.path().to_string_lossy().to_string());
+        });
+        let ctx = RenderContext {
+            input: Arc::new(input),
+            config: Arc::new(Config::default()),
+            terminal: TerminalCapabilities::default(),
+            preview_mode: false,
+            transcript: None,
+            storage: None,
+        };
+        let component = StatusComponent::new(build_status_config(|config| {
+            config.waiting_after_seconds = 20;
+        }));
+        let output = component.render(&ctx).await;
+
+        assert_eq!(output.text, "Tool (Bash)");
+        assert_eq!(output.text_color, Some("blue".to_string()));
+        Ok(())
+    }
+
+    #[test]
+    fn test_format_elapsed() {
+        assert_eq!(StatusComponent::format_elapsed(0), "0s");
+        assert_eq!(StatusComponent::format_elapsed(59), "59s");
+        assert_eq!(StatusComponent::format_elapsed(125), "2m05s");
+        assert_eq!(StatusComponent::format_elapsed(3_720), "1h02m");
+    }
+
     #[tokio::test]
     async fn test_status_from_transcript_error_details() -> TestResult {
         let mut file = NamedTempFile::new()?;
@@ -669,6 +1127,8 @@ mod tests {
             config: Arc::new(Config::default()),
             terminal: TerminalCapabilities::default(),
             preview_mode: false,
+            transcript: None,
+            storage: None,
         };
 
         let component = StatusComponent::new(StatusComponentConfig::default());
@@ -683,6 +1143,65 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_long_error_details_move_to_detail_lines() -> TestResult {
+        let details =
+            "Bash failed: connection reset by peer while fetching https://example.com/api";
+        let mut file = NamedTempFile::new()?;
+        writeln!(
+            file,
+            "{}",
+            json!({
+                "type": "assistant",
+                "message": {
+                    "usage": {"input_tokens": 42},
+                    "stop_reason": "end_turn",
+                    "content": []
+                },
+                "toolUseResult": {"error": details}
+            })
+        )
+        .context("failed to write error transcript")?;
+
+        let input = build_input(|input| {
+            input.transcript_path = Some(file.path().to_string_lossy().to_string());
+        });
+        let mut ctx = RenderContext {
+            input: Arc::new(input),
+            config: Arc::new(Config::default()),
+            terminal: TerminalCapabilities::default(),
+            preview_mode: false,
+            transcript: None,
+            storage: None,
+        };
+
+        let component = StatusComponent::new(StatusComponentConfig::default());
+        let output = component.render(&ctx).await;
+        assert_eq!(output.text, "Error");
+        assert!(!output.detail_lines.is_empty());
+        assert!(output.detail_lines[0].starts_with("  └ Bash failed"));
+
+        // multiline 关闭时保持内联
+        ctx.config = Arc::new(Config {
+            multiline: None,
+            ..Config::default()
+        });
+        let component = StatusComponent::new(StatusComponentConfig::default());
+        let output = component.render(&ctx).await;
+        assert_eq!(output.text, format!("Error ({details})"));
+        assert!(output.detail_lines.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_wrap_details_respects_width_and_max_lines() {
+        let component = StatusComponent::new(build_status_config(|config| {
+            config.details_max_lines = 2;
+        }));
+        let lines = component.wrap_details("one two three four five six seven eight", Some(20));
+        assert_eq!(lines, ["  └ one two three", "    four five six s…"]);
+    }
+
     #[tokio::test]
     async fn test_status_stop_sequence_is_ready() -> TestResult {
         let mut file = NamedTempFile::new()?;
@@ -713,6 +1232,8 @@ mod tests {
             config: Arc::new(Config::default()),
             terminal: TerminalCapabilities::default(),
             preview_mode: false,
+            transcript: None,
+            storage: None,
         };
 
         let component = StatusComponent::new(config);
@@ -723,4 +1244,227 @@ mod tests {
         assert_eq!(output.icon_color, Some("green".to_string()));
         Ok(())
     }
+
+    fn error_entry(uuid: &str) -> serde_json::Value {
+        json!({
+            "type": "assistant",
+            "uuid": uuid,
+            "message": {
+                "usage": {"input_tokens": 1},
+                "stop_reason": "stop_sequence",
+                "content": [{"type": "text", "text": "API Error: 403 user quota is not enough"}]
+            }
+        })
+    }
+
+    #[tokio::test]
+    async fn test_status_counts_consecutive_errors() -> TestResult {
+        let mut file = NamedTempFile::new()?;
+        let success = json!({
+            "type": "assistant",
+            "uuid": "ok-1",
+            "message": {"usage": {"input_tokens": 1}, "stop_reason": "end_turn"}
+        });
+        writeln!(file, "{}", error_entry("err-0"))?;
+        writeln!(file, "{success}")?;
+        for idx in 1..=3 {
+            writeln!(
+                file,
+                "{}",
+                json!({"type": "user", "message": {"content": "retry"}})
+            )?;
+            writeln!(file, "{}", error_entry(&format!("err-{idx}")))?;
+        }
+
+        let input = build_input(|input| {
+            input.transcript_path = Some(file.path().to_string_lossy().to_string());
+        });
+        let ctx = RenderContext {
+            input: Arc::new(input),
+            config: Arc::new(Config::default()),
+            terminal: TerminalCapabilities::default(),
+            preview_mode: false,
+            transcript: None,
+            storage: None,
+        };
+
+        let component = StatusComponent::new(StatusComponentConfig::default());
+        let output = component.render(&ctx).await;
+        assert_eq!(output.text, "Error ×3 (403 quota insufficient)");
+        assert_eq!(output.icon_color, Some("bright_red".to_string()));
+
+        let quiet = StatusComponent::new(build_status_config(|config| {
+            config.show_error_count = false;
+            config.error_escalation_threshold = 5;
+        }));
+        let output = quiet.render(&ctx).await;
+        assert_eq!(output.text, "Error (403 quota insufficient)");
+        assert_eq!(output.icon_color, Some("red".to_string()));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_error_streak_persisted_through_storage_handle() -> TestResult {
+        let mut file = NamedTempFile::new()?;
+        writeln!(file, "{}", error_entry("err-5"))?;
+        let history = SessionHistory {
+            errors: ErrorStreak {
+                count: 4,
+                last_error_uuid: Some("err-4".to_string()),
+            },
+            ..SessionHistory::default()
+        };
+        let storage = Arc::new(MemoryStorage::new().with_history("s", history));
+
+        let input = build_input(|input| {
+            input.session_id = Some("s".to_string());
+            input.transcript_path = Some(file.path().to_string_lossy().to_string());
+        });
+        let ctx = RenderContext {
+            input: Arc::new(input),
+            storage: Some(storage.clone()),
+            ..create_test_context(json!({}))
+        };
+
+        let output = StatusComponent::new(StatusComponentConfig::default())
+            .render(&ctx)
+            .await;
+        assert_eq!(output.text, "Error ×5 (403 quota insufficient)");
+        let persisted = storage.history("s").map(|history| history.errors);
+        assert_eq!(
+            persisted.and_then(|streak| streak.last_error_uuid),
+            Some("err-5".to_string())
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_error_run_merge_with_persisted_streak() {
+        let uuids = |ids: &[&str]| ids.iter().map(|id| Some((*id).to_string())).collect();
+        let persisted = ErrorStreak {
+            count: 4,
+            last_error_uuid: Some("b".to_string()),
+        };
+
+        // 同一份 transcript:只累加比持久化 uuid 更新的条目
+        let run = ErrorRun {
+            uuids: uuids(&["c", "b", "a"]),
+            terminated: false,
+        };
+        assert_eq!(run.merge(Some(&persisted)).count, 5);
+
+        // 新 transcript(进程重启后)里的错误接着之前的计数
+        let run = ErrorRun {
+            uuids: uuids(&["x"]),
+            terminated: false,
+        };
+        let merged = run.merge(Some(&persisted));
+        assert_eq!(merged.count, 5);
+        assert_eq!(merged.last_error_uuid.as_deref(), Some("x"));
+
+        // 成功的回合之后重新计数
+        let run = ErrorRun {
+            uuids: uuids(&["y"]),
+            terminated: true,
+        };
+        assert_eq!(run.merge(Some(&persisted)).count, 1);
+        let run = ErrorRun {
+            uuids: Vec::new(),
+            terminated: true,
+        };
+        assert_eq!(run.merge(Some(&persisted)), ErrorStreak::default());
+    }
+
+    async fn render_single_entry(
+        config: StatusComponentConfig,
+        entry: &serde_json::Value,
+    ) -> Result<ComponentOutput> {
+        let mut file = NamedTempFile::new()?;
+        writeln!(file, "{entry}")?;
+        let input = build_input(|input| {
+            input.transcript_path = Some(file.path().to_string_lossy().to_string());
+        });
+        let ctx = RenderContext {
+            input: Arc::new(input),
+            config: Arc::new(Config::default()),
+            terminal: TerminalCapabilities::default(),
+            preview_mode: false,
+            transcript: None,
+            storage: None,
+        };
+        Ok(StatusComponent::new(config).render(&ctx).await)
+    }
+
+    fn synthetic_entry(text: &str) -> serde_json::Value {
+        json!({
+            "type": "assistant",
+            "message": {
+                "usage": {"input_tokens": 1},
+                "stop_reason": "stop_sequence",
+                "content": [{"type": "text", "text": text}]
+            }
+        })
+    }
+
+    fn rule(pattern: &str, label: Option<&str>, severity: StatusErrorSeverity) -> StatusErrorRule {
+        StatusErrorRule {
+            pattern: pattern.to_string(),
+            label: label.map(str::to_string),
+            severity,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_custom_error_rules_map_proxy_errors() -> TestResult {
+        let config = build_status_config(|config| {
+            config.show_when_idle = true;
+            config.error_rules = vec![
+                rule(
+                    r"upstream error (\d+)",
+                    Some("HTTP $1"),
+                    StatusErrorSeverity::Error,
+                ),
+                rule(
+                    "rate limited",
+                    Some("Rate Limited"),
+                    StatusErrorSeverity::Warning,
+                ),
+                rule("content filter notice", None, StatusErrorSeverity::Ignore),
+                rule("(unclosed", None, StatusErrorSeverity::Error),
+            ];
+        });
+
+        let output = render_single_entry(
+            config.clone(),
+            &synthetic_entry("proxy: upstream error 502 bad gateway"),
+        )
+        .await?;
+        assert_eq!(output.text, "Error (HTTP 502)");
+
+        let output =
+            render_single_entry(config.clone(), &synthetic_entry("you are rate limited")).await?;
+        assert_eq!(output.text, "Rate Limited");
+        assert_eq!(output.icon_color, Some("yellow".to_string()));
+
+        // ignore 规则压过内置的 "filter" 判定
+        let output = render_single_entry(config, &synthetic_entry("content filter notice")).await?;
+        assert_eq!(output.text, "Ready");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_builtin_error_rules_can_be_disabled() -> TestResult {
+        let entry = synthetic_entry("API Error: 403 user quota is not enough");
+
+        let output = render_single_entry(StatusComponentConfig::default(), &entry).await?;
+        assert_eq!(output.text, "Error (403 quota insufficient)");
+
+        let config = build_status_config(|config| {
+            config.show_when_idle = true;
+            config.builtin_error_rules = false;
+        });
+        let output = render_single_entry(config, &entry).await?;
+        assert_eq!(output.text, "Ready");
+        Ok(())
+    }
 }
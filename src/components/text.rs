@@ -0,0 +1,117 @@
+//! Static text component implementation.
+//!
+//! Displays a fixed configurable string (`content`), useful as a decorative
+//! label or machine identifier in presets and multiline rows. Hidden while
+//! `content` is empty.
+
+use super::base::{cache_key_of, Component, ComponentFactory, ComponentOutput, RenderContext};
+use crate::config::{BaseComponentConfig, Config, TextComponentConfig};
+use async_trait::async_trait;
+
+/// Static text component.
+pub struct TextComponent {
+    config: TextComponentConfig,
+}
+
+impl TextComponent {
+    #[must_use]
+    pub const fn new(config: TextComponentConfig) -> Self {
+        Self { config }
+    }
+}
+
+#[async_trait]
+impl Component for TextComponent {
+    fn name(&self) -> &'static str {
+        "text"
+    }
+
+    fn is_enabled(&self, _ctx: &RenderContext) -> bool {
+        self.config.base.enabled
+    }
+
+    async fn render(&self, ctx: &RenderContext) -> ComponentOutput {
+        if !self.is_enabled(ctx) || self.config.content.trim().is_empty() {
+            return ComponentOutput::hidden();
+        }
+
+        let mut output = ComponentOutput::new(self.config.content.clone())
+            .with_icon_color(&self.config.base.icon_color)
+            .with_text_color(&self.config.base.text_color);
+        // 图标默认留空;空图标不设置,避免经典主题在文字前多出一个空格
+        if let Some(icon) = self.select_icon(ctx).filter(|icon| !icon.is_empty()) {
+            output = output.with_icon(icon);
+        }
+        output
+    }
+
+    fn base_config(&self, _ctx: &RenderContext) -> Option<&BaseComponentConfig> {
+        Some(&self.config.base)
+    }
+
+    fn cache_key(&self, _ctx: &RenderContext) -> Option<u64> {
+        // 输出只取决于配置
+        Some(cache_key_of(&()))
+    }
+}
+
+/// Factory for creating static text components.
+pub struct TextComponentFactory;
+
+impl ComponentFactory for TextComponentFactory {
+    fn create(&self, config: &Config) -> Box<dyn Component> {
+        Box::new(TextComponent::new((*config.components.text).clone()))
+    }
+
+    fn name(&self) -> &'static str {
+        "text"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::TerminalCapabilities;
+    use crate::core::input::InputData;
+    use std::sync::Arc;
+
+    fn context() -> RenderContext {
+        RenderContext {
+            input: Arc::new(InputData::default()),
+            config: Arc::new(Config::default()),
+            terminal: TerminalCapabilities::default(),
+            preview_mode: false,
+            transcript: None,
+            storage: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn text_hidden_without_content() {
+        let component = TextComponent::new(TextComponentConfig::default());
+        assert!(!component.render(&context()).await.visible);
+
+        let component = TextComponent::new(TextComponentConfig {
+            content: "dev".to_string(),
+            ..TextComponentConfig::default()
+        });
+        assert_eq!(component.render(&context()).await.icon, None);
+    }
+
+    #[tokio::test]
+    async fn text_renders_content_with_icon_and_colors() {
+        let mut config = TextComponentConfig {
+            content: "prod-box".to_string(),
+            ..TextComponentConfig::default()
+        };
+        config.base.emoji_icon = "🖥".to_string();
+        config.base.text_color = "yellow".to_string();
+        let component = TextComponent::new(config);
+
+        let output = component.render(&context()).await;
+        assert!(output.visible);
+        assert_eq!(output.text, "prod-box");
+        assert_eq!(output.icon.as_deref(), Some("🖥"));
+        assert_eq!(output.text_color.as_deref(), Some("yellow"));
+    }
+}
@@ -0,0 +1,204 @@
+//! Ticket component implementation.
+//!
+//! Extracts a Jira / Linear style ticket id (`ENG-42`) from the branch name
+//! with `pattern` and shows it as its own short segment. With `url_template`
+//! set the id becomes an OSC 8 hyperlink to the tracker, and
+//! `replace_branch` hides the branch component while an id is found.
+
+use std::path::Path;
+
+use super::base::{Component, ComponentFactory, ComponentOutput, RenderContext};
+use crate::config::{BaseComponentConfig, Config, TicketComponentConfig};
+use crate::utils::git_remote::head_branch;
+use crate::utils::sanitize::sanitize_terminal_text;
+use async_trait::async_trait;
+use regex::Regex;
+
+/// Ticket id extraction compiled from `[components.ticket]`
+pub struct TicketExtractor {
+    regex: Regex,
+    uppercase: bool,
+}
+
+impl TicketExtractor {
+    /// `None` when `pattern` is not a valid regex; `validate` reports it.
+    #[must_use]
+    pub fn new(config: &TicketComponentConfig) -> Option<Self> {
+        Regex::new(&config.pattern).ok().map(|regex| Self {
+            regex,
+            uppercase: config.uppercase,
+        })
+    }
+
+    /// Ticket id in `branch`: the first capture group, or the whole match
+    #[must_use]
+    pub fn extract(&self, branch: &str) -> Option<String> {
+        let captures = self.regex.captures(branch)?;
+        let ticket = captures.get(1).or_else(|| captures.get(0))?.as_str();
+        if ticket.is_empty() {
+            return None;
+        }
+        Some(if self.uppercase {
+            ticket.to_uppercase()
+        } else {
+            ticket.to_string()
+        })
+    }
+}
+
+/// `text` as an OSC 8 hyperlink to `url`
+fn hyperlink(url: &str, text: &str) -> String {
+    let url: String = url.chars().filter(|ch| !ch.is_control()).collect();
+    format!("\x1b]8;;{url}\x1b\\{text}\x1b]8;;\x1b\\")
+}
+
+/// Ticket component.
+pub struct TicketComponent {
+    config: TicketComponentConfig,
+    extractor: Option<TicketExtractor>,
+}
+
+impl TicketComponent {
+    #[must_use]
+    pub fn new(config: TicketComponentConfig) -> Self {
+        let extractor = TicketExtractor::new(&config);
+        Self { config, extractor }
+    }
+
+    /// Branch from the input, falling back to the repository `HEAD`
+    fn branch(ctx: &RenderContext) -> Option<String> {
+        if let Some(branch) = ctx.input.branch() {
+            return Some(branch.to_string());
+        }
+        ctx.input
+            .current_dir()
+            .or_else(|| ctx.input.project_root_dir())
+            .and_then(|dir| head_branch(Path::new(dir)))
+    }
+}
+
+#[async_trait]
+impl Component for TicketComponent {
+    fn name(&self) -> &'static str {
+        "ticket"
+    }
+
+    fn is_enabled(&self, _ctx: &RenderContext) -> bool {
+        self.config.base.enabled
+    }
+
+    async fn render(&self, ctx: &RenderContext) -> ComponentOutput {
+        if !self.is_enabled(ctx) {
+            return ComponentOutput::hidden();
+        }
+        let Some(id) = self
+            .extractor
+            .as_ref()
+            .zip(Self::branch(ctx))
+            .and_then(|(extractor, branch)| extractor.extract(&branch))
+        else {
+            return ComponentOutput::hidden();
+        };
+
+        let template = self.config.url_template.trim();
+        let id = sanitize_terminal_text(&id).into_owned();
+        let text = if template.is_empty() {
+            id
+        } else {
+            hyperlink(&template.replace("{ticket}", &id), &id)
+        };
+        ComponentOutput::preformatted(text)
+            .with_icon(self.select_icon(ctx).unwrap_or_default())
+            .with_icon_color(ctx.config.palette.resolve(&self.config.base.icon_color))
+            .with_text_color(ctx.config.palette.resolve(&self.config.base.text_color))
+    }
+
+    fn base_config(&self, _ctx: &RenderContext) -> Option<&BaseComponentConfig> {
+        Some(&self.config.base)
+    }
+}
+
+/// Factory for creating ticket components.
+pub struct TicketComponentFactory;
+
+impl ComponentFactory for TicketComponentFactory {
+    fn create(&self, config: &Config) -> Box<dyn Component> {
+        Box::new(TicketComponent::new((*config.components.ticket).clone()))
+    }
+
+    fn name(&self) -> &'static str {
+        "ticket"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::TerminalCapabilities;
+    use crate::core::input::InputData;
+    use crate::utils::visible_width;
+    use std::sync::Arc;
+
+    fn context(branch: &str) -> RenderContext {
+        RenderContext {
+            input: Arc::new(InputData {
+                git_branch: Some(branch.to_string()),
+                ..InputData::default()
+            }),
+            config: Arc::new(Config::default()),
+            terminal: TerminalCapabilities::default(),
+            preview_mode: false,
+            transcript: None,
+            storage: None,
+        }
+    }
+
+    fn extractor(configure: impl FnOnce(&mut TicketComponentConfig)) -> Option<TicketExtractor> {
+        let mut config = TicketComponentConfig::default();
+        configure(&mut config);
+        TicketExtractor::new(&config)
+    }
+
+    #[test]
+    fn tickets_are_extracted_from_branch_names() -> anyhow::Result<()> {
+        let default = extractor(|_| {}).ok_or_else(|| anyhow::anyhow!("default pattern"))?;
+        assert_eq!(
+            default.extract("feature/ENG-42-login").as_deref(),
+            Some("ENG-42")
+        );
+        assert_eq!(default.extract("eng-7_fix").as_deref(), Some("ENG-7"));
+        assert_eq!(default.extract("main"), None);
+
+        let grouped = extractor(|config| {
+            config.pattern = r"^[a-z]+/(\d+)-".to_string();
+            config.uppercase = false;
+        })
+        .ok_or_else(|| anyhow::anyhow!("group pattern"))?;
+        assert_eq!(
+            grouped.extract("bugfix/1234-crash").as_deref(),
+            Some("1234")
+        );
+
+        assert!(extractor(|config| config.pattern = "(".to_string()).is_none());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn ticket_links_to_the_tracker() {
+        let component = TicketComponent::new(TicketComponentConfig {
+            url_template: "https://linear.app/acme/issue/{ticket}".to_string(),
+            ..TicketComponentConfig::default()
+        });
+        let output = component.render(&context("eng-42-login")).await;
+        assert!(output.visible);
+        assert_eq!(
+            output.text,
+            "\x1b]8;;https://linear.app/acme/issue/ENG-42\x1b\\ENG-42\x1b]8;;\x1b\\"
+        );
+        assert_eq!(visible_width(&output.text), 6);
+
+        let plain = TicketComponent::new(TicketComponentConfig::default());
+        assert_eq!(plain.render(&context("ENG-42")).await.text, "ENG-42");
+        assert!(!plain.render(&context("main")).await.visible);
+    }
+}
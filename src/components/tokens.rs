@@ -7,12 +7,14 @@ use std::fmt::Write;
 use async_trait::async_trait;
 
 use super::base::{Component, ComponentFactory, ComponentOutput, RenderContext};
-use crate::config::{BaseComponentConfig, Config, TokensComponentConfig};
-use crate::storage;
+use crate::config::{BaseComponentConfig, Config, SegmentAlign, Severity, TokensComponentConfig};
+use crate::themes::ANSI_RESET;
 use crate::utils::model_parser::parse_model_id;
+use crate::utils::number_format::PERCENT_WIDTH;
 use crate::utils::provider_profiles::{
     context_window_from_model_map, context_window_from_providers, DEFAULT_CONTEXT_WINDOW,
 };
+use crate::utils::{ansi, display_width, pad_to_width};
 
 #[derive(Clone, Debug)]
 struct TokenUsageInfo {
@@ -136,14 +138,15 @@ impl TokensComponent {
             return Some(usage);
         }
 
-        // preview 模式跳过 storage:`storage::get_session_tokens` 底层
+        // preview 模式跳过 storage:磁盘存储底层
         // `StorageManager::new()` 会 `ensure_directories()`,在用户真实
         // `~/.claude/statusline-pro/...` 下建目录,违反"preview 无副作用"
         // 契约。preview 场景下直接落到下面的 show_zero / None 分支即可,
         // 预览里 token 用量的位置和图标仍然可见,具体数字不需要真实。
         if !ctx.preview_mode {
             if let Some(session_id) = ctx.input.session_id.as_deref() {
-                if let Ok(Some(tokens)) = storage::get_session_tokens(session_id).await {
+                let history = ctx.storage().session_history(session_id).await;
+                if let Ok(Some(tokens)) = history.map(|history| history.and_then(|h| h.tokens)) {
                     let used = tokens.input + tokens.cache_creation_input + tokens.cache_read_input;
                     if used == 0 && !self.config.show_zero {
                         return None;
@@ -170,43 +173,11 @@ impl TokensComponent {
 
     fn context_window_for_model(&self, ctx: &RenderContext) -> u64 {
         self.model_specific_context_window(ctx)
-            .unwrap_or_else(|| self.default_context_window())
-    }
-
-    fn default_context_window(&self) -> u64 {
-        self.config
-            .context_windows
-            .get("default")
-            .copied()
-            .unwrap_or(DEFAULT_CONTEXT_WINDOW)
+            .unwrap_or_else(|| default_context_window(&self.config))
     }
 
     fn model_specific_context_window(&self, ctx: &RenderContext) -> Option<u64> {
-        let model = ctx.input.model.as_ref()?;
-
-        if let Some(id) = model.id.as_ref() {
-            // Priority 1: Exact match from config
-            if let Some(value) = context_window_from_model_map(&self.config.context_windows, id) {
-                return Some(value);
-            }
-
-            // Priority 2: Shared model provider profiles
-            let endpoint = std::env::var("ANTHROPIC_BASE_URL").ok();
-            if let Some(value) =
-                context_window_from_providers(&ctx.config.model_providers, id, endpoint.as_deref())
-            {
-                return Some(value);
-            }
-
-            // Priority 3: Infer from model ID params (e.g., [1m])
-            if let Some(parsed) = parse_model_id(id) {
-                if let Some(window) = parsed.infer_context_window() {
-                    return Some(window);
-                }
-            }
-        }
-
-        None
+        model_context_window(&self.config, ctx)
     }
 
     fn build_progress_bar(&self, ctx: &RenderContext, percentage: f64) -> Option<String> {
@@ -215,41 +186,23 @@ impl TokensComponent {
         }
 
         let width = self.config.progress_width.max(1) as usize;
-        let width_f64 = to_f64(width);
-        let filled_len = clamp_round_to_usize((percentage / 100.0) * width_f64, width);
-        let capped_filled = filled_len.min(width);
+        let glyphs = self.bar_glyphs(ctx);
+        // 有半格字符的样式每格拆成两步,分辨率翻倍
+        let steps = if glyphs.half.is_some() { 2 } else { 1 };
+        let filled_units =
+            clamp_round_to_usize((percentage / 100.0) * to_f64(width * steps), width * steps);
+        let capped_filled = filled_units.div_ceil(steps).min(width);
 
         let gradient_enabled = self.config.show_gradient
             || matches!(ctx.config.theme.as_str(), "powerline" | "capsule");
         let supports_colors = ctx.terminal.supports_colors();
 
-        let filled_char = self
-            .config
-            .progress_bar_chars
-            .filled
-            .chars()
-            .next()
-            .unwrap_or('█');
-        let empty_char = self
-            .config
-            .progress_bar_chars
-            .empty
-            .chars()
-            .next()
-            .unwrap_or('░');
-        let backup_char = self
-            .config
-            .progress_bar_chars
-            .backup
-            .chars()
-            .next()
-            .unwrap_or('▓');
-
         let mut bar = String::with_capacity(width * 16);
         let mut color_active = false;
 
         for idx in 0..width {
-            if idx < capped_filled {
+            let cell_units = filled_units.saturating_sub(idx * steps).min(steps);
+            if cell_units > 0 {
                 let gradient_percentage = if capped_filled == 0 {
                     0.0
                 } else {
@@ -260,7 +213,11 @@ impl TokensComponent {
                 }
                 .clamp(0.0, 100.0);
                 let is_backup = gradient_percentage >= self.config.thresholds.backup;
-                let symbol = if is_backup { backup_char } else { filled_char };
+                let symbol = match glyphs.half {
+                    Some(half) if cell_units < steps => half,
+                    _ if is_backup => glyphs.backup,
+                    _ => glyphs.filled,
+                };
 
                 if gradient_enabled && supports_colors {
                     let (r, g, b) = rainbow_gradient_color(gradient_percentage);
@@ -271,20 +228,111 @@ impl TokensComponent {
                 }
             } else if gradient_enabled && supports_colors {
                 bar.push_str("\x1b[38;2;120;120;120m");
-                bar.push(empty_char);
+                bar.push(glyphs.empty);
                 color_active = true;
             } else {
-                bar.push(empty_char);
+                bar.push(glyphs.empty);
             }
         }
 
         if color_active {
-            bar.push_str("\x1b[0m");
+            bar.push_str(ANSI_RESET);
         }
 
         Some(bar)
     }
 
+    /// 按 `progress_style` 选择进度条字符;纯文本终端回退到 ASCII
+    fn bar_glyphs(&self, ctx: &RenderContext) -> BarGlyphs {
+        let text_only = ctx.config.terminal.text_only()
+            || (!ctx.terminal.supports_emoji && !ctx.terminal.supports_nerd_font);
+        if text_only {
+            return BarGlyphs::uniform('#', '-', None);
+        }
+
+        match self
+            .config
+            .progress_style
+            .trim()
+            .to_ascii_lowercase()
+            .as_str()
+        {
+            "braille" => BarGlyphs::uniform('⣿', '⣀', Some('⡇')),
+            "dots" => BarGlyphs::uniform('●', '○', None),
+            "line" => BarGlyphs::uniform('━', '─', Some('╸')),
+            _ => {
+                let chars = &self.config.progress_bar_chars;
+                let first = |value: &str, fallback| value.chars().next().unwrap_or(fallback);
+                BarGlyphs {
+                    filled: first(&chars.filled, '█'),
+                    backup: first(&chars.backup, '▓'),
+                    empty: first(&chars.empty, '░'),
+                    half: None,
+                }
+            }
+        }
+    }
+
+    /// 临界强调方式;关闭颜色时不输出任何样式 | Emphasis at the critical threshold
+    fn critical_emphasis(&self, ctx: &RenderContext) -> Option<Emphasis> {
+        let supports_colors = ctx.terminal.supports_colors()
+            && ctx
+                .config
+                .style
+                .enable_colors
+                .is_enabled(ctx.terminal.supports_colors());
+        if !supports_colors {
+            return None;
+        }
+
+        match self
+            .config
+            .critical_emphasis
+            .trim()
+            .to_ascii_lowercase()
+            .as_str()
+        {
+            "reverse" => Some(Emphasis::Reverse),
+            "bold" | "bold-red" => Some(Emphasis::Bold),
+            _ => None,
+        }
+    }
+
+    /// 每个会话进入临界区时只响铃一次,回落到临界以下后重新生效
+    async fn should_ring_bell(&self, ctx: &RenderContext, critical: bool) -> bool {
+        let session_id = ctx
+            .input
+            .session_id
+            .as_deref()
+            .filter(|_| !ctx.preview_mode);
+        let Some(session_id) = session_id else {
+            return false;
+        };
+
+        let alerted = ctx
+            .storage()
+            .session_history(session_id)
+            .await
+            .ok()
+            .flatten()
+            .is_some_and(|history| history.critical_alerted);
+        if alerted == critical {
+            return false;
+        }
+
+        if let Err(err) = ctx
+            .storage()
+            .update_critical_alert(session_id, critical)
+            .await
+        {
+            if ctx.config.debug {
+                eprintln!("[statusline] failed to persist critical alert: {err}");
+            }
+            return false;
+        }
+        critical
+    }
+
     fn select_status_icon(&self, ctx: &RenderContext, percentage: f64) -> Option<String> {
         let thresholds = &self.config.thresholds;
         let status = if percentage >= thresholds.critical {
@@ -300,7 +348,7 @@ impl TokensComponent {
         let terminal = &ctx.terminal;
         let style = &ctx.config.style;
 
-        if terminal_cfg.force_text {
+        if terminal_cfg.text_only() {
             return icon_for_kind(&icons.text, status).map(std::string::ToString::to_string);
         }
         if terminal_cfg.force_nerd_font {
@@ -345,13 +393,152 @@ impl TokensComponent {
         }
     }
 
-    fn format_usage(&self, info: &TokenUsageInfo) -> String {
+    fn token_unit(&self) -> TokenUnit {
         if self.config.show_raw_numbers {
-            format!("({}/{})", info.used, info.total)
+            TokenUnit::Raw
         } else {
-            let used_k = to_f64(info.used) / 1_000.0;
-            let total_k = to_f64(info.total) / 1_000.0;
-            format!("({used_k:.1}k/{total_k:.0}k)")
+            TokenUnit::from_config(&self.config.unit)
+        }
+    }
+
+    fn format_usage(&self, info: &TokenUsageInfo, fixed_width: bool) -> String {
+        let unit = self.token_unit();
+        let used = unit.format(info.used, false);
+        // 定宽时按窗口大小的写法占位,如 ` 90.0k/200k`
+        let width = if fixed_width {
+            display_width(&unit.format(info.total, false))
+        } else {
+            0
+        };
+        format!(
+            "({}/{})",
+            pad_to_width(&used, width, SegmentAlign::Right),
+            unit.format(info.total, true)
+        )
+    }
+
+    /// Percentage without the `%` sign, padded when `fixed_width`
+    fn format_percent(percentage: f64, fixed_width: bool) -> String {
+        if fixed_width {
+            format!("{percentage:>PERCENT_WIDTH$.1}")
+        } else {
+            format!("{percentage:.1}")
+        }
+    }
+
+    /// 自定义模板:`format` 含占位符时生效 | Template text when `format` has placeholders
+    fn template(&self) -> Option<&str> {
+        Some(self.config.format.as_str()).filter(|format| format.contains('{'))
+    }
+
+    #[allow(clippy::literal_string_with_formatting_args)] // placeholders are user template syntax
+    fn render_template(
+        &self,
+        template: &str,
+        usage: &TokenUsageInfo,
+        percentage: f64,
+        bar: Option<&str>,
+        status_icon: Option<&str>,
+        fixed_width: bool,
+    ) -> String {
+        let unit = self.token_unit();
+        template
+            .replace("{bar}", bar.unwrap_or_default())
+            .replace("{percent}", &Self::format_percent(percentage, fixed_width))
+            .replace("{used}", &unit.format(usage.used, false))
+            .replace("{window}", &unit.format(usage.total, true))
+            .replace(
+                "{remaining}",
+                &unit.format(usage.total.saturating_sub(usage.used), false),
+            )
+            .replace("{status}", status_icon.unwrap_or_default())
+            .trim()
+            .to_string()
+    }
+}
+
+/// Emphasis of the whole segment at the critical threshold
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Emphasis {
+    Reverse,
+    Bold,
+}
+
+impl Emphasis {
+    /// Wrap `text`, re-enabling the style after embedded resets (gradient bar).
+    fn apply(self, text: &str) -> String {
+        let (on, off) = match self {
+            Self::Reverse => ("\x1b[7m", "\x1b[27m"),
+            Self::Bold => ("\x1b[1m", "\x1b[22m"),
+        };
+        let body = ansi::reapply_after_resets(text, on);
+        format!("{on}{body}{off}")
+    }
+}
+
+/// Glyphs of a progress bar style
+#[derive(Clone, Copy, Debug)]
+struct BarGlyphs {
+    filled: char,
+    /// Filled cell beyond the backup threshold
+    backup: char,
+    empty: char,
+    /// Half-filled cell, doubling the resolution
+    half: Option<char>,
+}
+
+impl BarGlyphs {
+    const fn uniform(filled: char, empty: char, half: Option<char>) -> Self {
+        Self {
+            filled,
+            backup: filled,
+            empty,
+            half,
+        }
+    }
+}
+
+/// Unit of displayed token counts
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum TokenUnit {
+    Raw,
+    Thousands,
+    Millions,
+    Auto,
+}
+
+impl TokenUnit {
+    fn from_config(value: &str) -> Self {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "raw" => Self::Raw,
+            "m" => Self::Millions,
+            "auto" => Self::Auto,
+            _ => Self::Thousands,
+        }
+    }
+
+    /// Format a token count; `window` drops insignificant decimals (`200k`, `1M`).
+    fn format(self, value: u64, window: bool) -> String {
+        let unit = match self {
+            Self::Auto if value < 1_000 => Self::Raw,
+            Self::Auto if value < 1_000_000 => Self::Thousands,
+            Self::Auto => Self::Millions,
+            unit => unit,
+        };
+        let value = to_f64(value);
+        match unit {
+            Self::Thousands if window => format!("{:.0}k", value / 1_000.0),
+            Self::Thousands => format!("{:.1}k", value / 1_000.0),
+            Self::Millions => {
+                let text = format!("{:.2}", value / 1_000_000.0);
+                let text = if window {
+                    text.trim_end_matches('0').trim_end_matches('.')
+                } else {
+                    &text
+                };
+                format!("{text}M")
+            }
+            _ => format!("{value}"),
         }
     }
 }
@@ -381,9 +568,7 @@ impl Component for TokensComponent {
             .unwrap_or_else(|| (to_f64(usage.used) / to_f64(total)) * 100.0);
         let clamped_percentage = percentage.clamp(0.0, 999.9);
 
-        let mut parts = Vec::new();
-
-        if let Some(bar) = self.build_progress_bar(ctx, clamped_percentage) {
+        let bar = self.build_progress_bar(ctx, clamped_percentage).map(|bar| {
             let left = self
                 .config
                 .progress_bar_chars
@@ -398,27 +583,80 @@ impl Component for TokensComponent {
                 .chars()
                 .next()
                 .unwrap_or(']');
-            parts.push(format!("{left}{bar}{right}"));
-        }
+            format!("{left}{bar}{right}")
+        });
+        let status_icon = self.select_status_icon(ctx, clamped_percentage);
+
+        let fixed_width = ctx.config.style.fixed_width_numbers;
+        let mut text = if let Some(template) = self.template() {
+            self.render_template(
+                template,
+                &usage,
+                clamped_percentage,
+                bar.as_deref(),
+                status_icon.as_deref(),
+                fixed_width,
+            )
+        } else {
+            let mut parts = Vec::new();
+            parts.extend(bar);
+
+            if self.config.show_percentage {
+                parts.push(format!(
+                    "{}%",
+                    Self::format_percent(clamped_percentage, fixed_width)
+                ));
+            }
 
-        if self.config.show_percentage {
-            parts.push(format!("{clamped_percentage:.1}%"));
-        }
+            if self.config.show_numbers {
+                parts.push(self.format_usage(&usage, fixed_width));
+            }
 
-        parts.push(self.format_usage(&usage));
+            parts.extend(status_icon);
+            parts.join(" ")
+        };
 
-        if let Some(status_icon) = self.select_status_icon(ctx, clamped_percentage) {
-            parts.push(status_icon);
+        let mut color = ctx
+            .config
+            .palette
+            .resolve(&self.select_color(clamped_percentage));
+        let mut icon = self.select_icon(ctx).unwrap_or_default();
+
+        let critical = clamped_percentage >= self.config.thresholds.critical;
+        if critical {
+            if let Some(emphasis) = self.critical_emphasis(ctx) {
+                if emphasis == Emphasis::Bold {
+                    color = ctx.config.palette.resolve(&self.config.colors.danger);
+                }
+                // 图标并入文本,整段一起强调
+                let segment = if icon.is_empty() {
+                    text
+                } else {
+                    format!("{} {text}", std::mem::take(&mut icon))
+                };
+                text = emphasis.apply(&segment);
+            }
         }
 
-        let text = parts.join(" ");
-        let color = self.select_color(clamped_percentage);
-        let icon = self.select_icon(ctx);
+        if self.config.critical_bell && self.should_ring_bell(ctx, critical).await {
+            text.insert(0, '\x07');
+        }
 
-        ComponentOutput::new(text)
-            .with_icon(icon.unwrap_or_default())
+        let severity = if critical {
+            Severity::Critical
+        } else if clamped_percentage >= self.config.thresholds.backup {
+            Severity::Warning
+        } else {
+            Severity::Normal
+        };
+
+        // 文本只含数字与组件自己生成的颜色、强调和响铃序列
+        ComponentOutput::preformatted(text)
+            .with_icon(icon)
             .with_icon_color(color.clone())
             .with_text_color(color)
+            .with_severity(severity)
+            .with_progress(clamped_percentage.min(100.0))
     }
 
     fn base_config(&self, _ctx: &RenderContext) -> Option<&BaseComponentConfig> {
@@ -513,12 +751,73 @@ impl IntoF64 for u64 {
     }
 }
 
+/// Context window of the current session, resolved like the tokens component.
+///
+/// 官方输入的 `context_window_size` 优先;官方只报 200K 而模型配置/参数给出其他
+/// 窗口时(`[1m]` 变体)以模型为准,与用量计算使用的分母保持一致。
+pub(crate) fn resolve_context_window(config: &TokensComponentConfig, ctx: &RenderContext) -> u64 {
+    let official = ctx
+        .input
+        .extra
+        .get("context_window")
+        .or_else(|| ctx.input.extra.get("contextWindow"))
+        .and_then(|window| {
+            window
+                .get("context_window_size")
+                .or_else(|| window.get("contextWindowSize"))
+        })
+        .and_then(serde_json::Value::as_u64);
+    let model = model_context_window(config, ctx);
+    match (official, model) {
+        (Some(200_000), Some(model_window)) => model_window,
+        (official, model) => official
+            .or(model)
+            .unwrap_or_else(|| default_context_window(config)),
+    }
+}
+
+fn default_context_window(config: &TokensComponentConfig) -> u64 {
+    config
+        .context_windows
+        .get("default")
+        .copied()
+        .unwrap_or(DEFAULT_CONTEXT_WINDOW)
+}
+
+fn model_context_window(config: &TokensComponentConfig, ctx: &RenderContext) -> Option<u64> {
+    let model = ctx.input.model.as_ref()?;
+
+    if let Some(id) = model.id.as_ref() {
+        // Priority 1: Exact match from config
+        if let Some(value) = context_window_from_model_map(&config.context_windows, id) {
+            return Some(value);
+        }
+
+        // Priority 2: Shared model provider profiles
+        let endpoint = std::env::var("ANTHROPIC_BASE_URL").ok();
+        if let Some(value) =
+            context_window_from_providers(&ctx.config.model_providers, id, endpoint.as_deref())
+        {
+            return Some(value);
+        }
+
+        // Priority 3: Infer from model ID params (e.g., [1m])
+        if let Some(parsed) = parse_model_id(id) {
+            if let Some(window) = parsed.infer_context_window() {
+                return Some(window);
+            }
+        }
+    }
+
+    None
+}
+
 /// Factory for creating Tokens components
 pub struct TokensComponentFactory;
 
 impl ComponentFactory for TokensComponentFactory {
     fn create(&self, config: &Config) -> Box<dyn Component> {
-        Box::new(TokensComponent::new(config.components.tokens.clone()))
+        Box::new(TokensComponent::new((*config.components.tokens).clone()))
     }
 
     fn name(&self) -> &'static str {
@@ -532,6 +831,7 @@ mod tests {
     use crate::components::{ColorSupport, TerminalCapabilities};
     use crate::config::AutoDetect;
     use crate::core::InputData;
+    use crate::storage::{MemoryStorage, SessionHistory, TokenHistory};
     use serde_json::json;
     use std::sync::Arc;
 
@@ -570,6 +870,8 @@ mod tests {
             config: Arc::new(Config::default()),
             terminal: TerminalCapabilities::default(),
             preview_mode: false,
+            transcript: None,
+            storage: None,
         }
     }
 
@@ -599,6 +901,125 @@ mod tests {
         assert!(output.text.contains("(1500/200000)"));
     }
 
+    #[tokio::test]
+    async fn test_tokens_units_and_percentage_only() {
+        let ctx = create_test_context_with_tokens(45_200);
+        let render_with = |configure: fn(&mut TokensComponentConfig)| {
+            let config = build_tokens_config(|config| {
+                config.show_progress_bar = false;
+                configure(config);
+            });
+            TokensComponent::new(config)
+        };
+
+        let output = render_with(|config| config.unit = "m".to_string())
+            .render(&ctx)
+            .await;
+        assert!(output.text.contains("(0.05M/0.2M)"), "{}", output.text);
+
+        let output = render_with(|config| config.unit = "auto".to_string())
+            .render(&ctx)
+            .await;
+        assert!(output.text.contains("(45.2k/200k)"), "{}", output.text);
+
+        let output = render_with(|config| config.show_numbers = false)
+            .render(&ctx)
+            .await;
+        assert_eq!(output.text, "22.6%");
+    }
+
+    #[tokio::test]
+    async fn test_tokens_fixed_width_numbers() {
+        let mut ctx = create_test_context_with_tokens(8_600);
+        let mut config = Config::default();
+        config.style.fixed_width_numbers = true;
+        ctx.config = Arc::new(config);
+        let component = TokensComponent::new(build_tokens_config(|config| {
+            config.show_progress_bar = false;
+        }));
+
+        let output = component.render(&ctx).await;
+        assert_eq!(output.text, "  4.3% (  8.6k/200k)");
+    }
+
+    #[tokio::test]
+    async fn test_tokens_format_template() {
+        let config = build_tokens_config(|config| {
+            config.format = "{percent}% of {window} ({remaining} left)".to_string();
+        });
+        let component = TokensComponent::new(config);
+        let ctx = create_test_context_with_tokens(50_000);
+
+        let output = component.render(&ctx).await;
+        assert_eq!(output.text, "25.0% of 200k (150.0k left)");
+    }
+
+    #[tokio::test]
+    async fn test_tokens_progress_styles_and_ascii_fallback() {
+        let config = build_tokens_config(|config| {
+            config.show_percentage = false;
+            config.show_numbers = false;
+            config.show_gradient = false;
+            config.progress_width = 4;
+            config.progress_style = "braille".to_string();
+        });
+        let component = TokensComponent::new(config);
+        let mut ctx = create_test_context_with_tokens(75_000);
+        ctx.config = Arc::new(Config {
+            theme: "classic".to_string(),
+            ..Config::default()
+        });
+
+        let output = component.render(&ctx).await;
+        assert_eq!(output.text, "[⣿⡇⣀⣀]");
+
+        ctx.terminal.supports_emoji = false;
+        ctx.terminal.supports_nerd_font = false;
+        let output = component.render(&ctx).await;
+        assert_eq!(output.text, "[##--]");
+    }
+
+    #[tokio::test]
+    async fn test_tokens_critical_emphasis_wraps_whole_segment() {
+        let config = build_tokens_config(|config| {
+            config.show_progress_bar = false;
+            config.critical_emphasis = "reverse".to_string();
+        });
+        let component = TokensComponent::new(config);
+        let mut ctx = create_test_context_with_tokens(195_000);
+
+        let output = component.render(&ctx).await;
+        assert!(
+            output.text.starts_with("\x1b[7m📊 97.5%"),
+            "{:?}",
+            output.text
+        );
+        assert!(output.text.ends_with("\x1b[27m"));
+        assert_eq!(output.icon.as_deref(), Some(""));
+
+        ctx.config = Arc::new(Config {
+            style: crate::config::StyleConfig {
+                enable_colors: AutoDetect::Bool(false),
+                ..Config::default().style
+            },
+            ..Config::default()
+        });
+        let output = component.render(&ctx).await;
+        assert!(!output.text.contains('\x1b'));
+
+        let below = create_test_context_with_tokens(100_000);
+        let output = component.render(&below).await;
+        assert!(!output.text.contains('\x1b'));
+    }
+
+    #[test]
+    fn test_emphasis_survives_embedded_resets() {
+        assert_eq!(
+            Emphasis::Bold.apply("a\x1b[0mb"),
+            "\x1b[1ma\x1b[0m\x1b[1mb\x1b[22m"
+        );
+    }
+
     #[tokio::test]
     async fn test_tokens_progress_bar_enabled() {
         let config = build_tokens_config(|config| {
@@ -639,6 +1060,32 @@ mod tests {
         assert!(output.text.contains("\x1b[38;2"));
     }
 
+    #[tokio::test]
+    async fn test_tokens_fall_back_to_stored_history() {
+        let history = SessionHistory {
+            tokens: Some(TokenHistory {
+                input: 50_000,
+                cache_read_input: 10_000,
+                ..TokenHistory::default()
+            }),
+            ..SessionHistory::default()
+        };
+        let storage = MemoryStorage::new().with_history("stored", history);
+        let ctx = RenderContext {
+            input: Arc::new(build_input(|input| {
+                input.session_id = Some("stored".to_string());
+            })),
+            storage: Some(Arc::new(storage)),
+            ..create_test_context_with_tokens(0)
+        };
+
+        let output = TokensComponent::new(TokensComponentConfig::default())
+            .render(&ctx)
+            .await;
+        assert!(output.visible);
+        assert!(output.text.contains("30.0%"), "text={}", output.text);
+    }
+
     #[tokio::test]
     async fn test_tokens_zero_hidden() {
         let config = build_tokens_config(|config| {
@@ -697,6 +1144,8 @@ mod tests {
             config: Arc::new(Config::default()),
             terminal: TerminalCapabilities::default(),
             preview_mode: false,
+            transcript: None,
+            storage: None,
         };
 
         let config = build_tokens_config(|config| {
@@ -735,6 +1184,8 @@ mod tests {
             config: Arc::new(Config::default()),
             terminal: TerminalCapabilities::default(),
             preview_mode: false,
+            transcript: None,
+            storage: None,
         };
 
         let config = build_tokens_config(|config| {
@@ -778,6 +1229,8 @@ mod tests {
             config: Arc::new(Config::default()),
             terminal: TerminalCapabilities::default(),
             preview_mode: false,
+            transcript: None,
+            storage: None,
         };
 
         let config = build_tokens_config(|config| {
@@ -822,6 +1275,8 @@ mod tests {
             config: Arc::new(Config::default()),
             terminal: TerminalCapabilities::default(),
             preview_mode: false,
+            transcript: None,
+            storage: None,
         };
 
         let config = build_tokens_config(|config| {
@@ -864,6 +1319,8 @@ mod tests {
             config: Arc::new(Config::default()),
             terminal: TerminalCapabilities::default(),
             preview_mode: false,
+            transcript: None,
+            storage: None,
         };
 
         let config = build_tokens_config(|config| {
@@ -909,6 +1366,8 @@ mod tests {
             config: Arc::new(Config::default()),
             terminal: TerminalCapabilities::default(),
             preview_mode: false,
+            transcript: None,
+            storage: None,
         };
 
         let config = build_tokens_config(|config| {
@@ -951,6 +1410,8 @@ mod tests {
             config: Arc::new(Config::default()),
             terminal: TerminalCapabilities::default(),
             preview_mode: false,
+            transcript: None,
+            storage: None,
         };
 
         let config = build_tokens_config(|config| {
@@ -990,6 +1451,8 @@ mod tests {
             config: Arc::new(Config::default()),
             terminal: TerminalCapabilities::default(),
             preview_mode: false,
+            transcript: None,
+            storage: None,
         };
 
         let config = build_tokens_config(|config| {
@@ -1021,6 +1484,8 @@ mod tests {
             config: Arc::new(Config::default()),
             terminal: TerminalCapabilities::default(),
             preview_mode: false,
+            transcript: None,
+            storage: None,
         };
 
         let config = build_tokens_config(|config| {
@@ -1061,6 +1526,8 @@ mod tests {
             config: Arc::new(Config::default()),
             terminal: TerminalCapabilities::default(),
             preview_mode: false,
+            transcript: None,
+            storage: None,
         };
 
         let config = build_tokens_config(|config| {
@@ -1101,6 +1568,8 @@ mod tests {
             config: Arc::new(Config::default()),
             terminal: TerminalCapabilities::default(),
             preview_mode: false,
+            transcript: None,
+            storage: None,
         };
 
         let config = build_tokens_config(|config| {
@@ -1145,6 +1614,8 @@ mod tests {
             config: Arc::new(Config::default()),
             terminal: TerminalCapabilities::default(),
             preview_mode: false,
+            transcript: None,
+            storage: None,
         };
 
         let config = build_tokens_config(|config| {
@@ -0,0 +1,273 @@
+//! Workspace trust component implementation.
+//!
+//! Warns with a shield when the project directory is outside every
+//! `security.trusted_paths` entry, or lives on a network filesystem (NFS, SMB,
+//! SSHFS, ...), before the agent is let loose there. Hidden in trusted, local
+//! directories unless `show_when_trusted` is set.
+
+use std::path::{Path, PathBuf};
+
+use super::base::{cache_key_of, Component, ComponentFactory, ComponentOutput, RenderContext};
+use crate::config::{BaseComponentConfig, Config, Severity, TrustComponentConfig};
+use crate::utils::expand_home;
+use async_trait::async_trait;
+
+/// Filesystem types treated as network mounts
+const NETWORK_FILESYSTEMS: &[&str] = &[
+    "nfs",
+    "nfs4",
+    "cifs",
+    "smbfs",
+    "smb3",
+    "afs",
+    "9p",
+    "ncpfs",
+    "davfs",
+    "glusterfs",
+    "ceph",
+    "fuse.sshfs",
+    "fuse.rclone",
+];
+
+/// Workspace trust component.
+pub struct TrustComponent {
+    config: TrustComponentConfig,
+}
+
+impl TrustComponent {
+    #[must_use]
+    pub const fn new(config: TrustComponentConfig) -> Self {
+        Self { config }
+    }
+
+    /// Warning labels for `dir`; empty when it is trusted and local
+    fn warnings(&self, dir: &Path, trusted_paths: &[String]) -> Vec<String> {
+        let mut warnings = Vec::new();
+        if !trusted_paths.is_empty() && !is_trusted(dir, trusted_paths) {
+            warnings.push(self.config.untrusted_text.clone());
+        }
+        if self.config.warn_network {
+            if let Some(fs_type) = network_filesystem(dir) {
+                warnings.push(format!("{} ({fs_type})", self.config.network_text));
+            }
+        }
+        warnings
+    }
+}
+
+#[async_trait]
+impl Component for TrustComponent {
+    fn name(&self) -> &'static str {
+        "trust"
+    }
+
+    fn is_enabled(&self, _ctx: &RenderContext) -> bool {
+        self.config.base.enabled
+    }
+
+    async fn render(&self, ctx: &RenderContext) -> ComponentOutput {
+        if !self.is_enabled(ctx) {
+            return ComponentOutput::hidden();
+        }
+        let Some(dir) = ctx.input.project_dir() else {
+            return ComponentOutput::hidden();
+        };
+
+        let trusted_paths = &ctx.config.security.trusted_paths;
+        let warnings = self.warnings(Path::new(dir), trusted_paths);
+        let icon = self.select_icon(ctx).unwrap_or_default();
+
+        if warnings.is_empty() {
+            // 未配置受信任目录时没有"已信任"可言,只在出现网络挂载时提示
+            if !self.config.show_when_trusted || trusted_paths.is_empty() {
+                return ComponentOutput::hidden();
+            }
+            return ComponentOutput::new(self.config.trusted_text.clone())
+                .with_icon(icon)
+                .with_icon_color(ctx.config.palette.resolve(&self.config.trusted_color))
+                .with_text_color(ctx.config.palette.resolve(&self.config.trusted_color));
+        }
+
+        ComponentOutput::new(warnings.join(" "))
+            .with_icon(icon)
+            .with_icon_color(&self.config.base.icon_color)
+            .with_text_color(&self.config.base.text_color)
+            .with_severity(Severity::Warning)
+    }
+
+    fn base_config(&self, _ctx: &RenderContext) -> Option<&BaseComponentConfig> {
+        Some(&self.config.base)
+    }
+
+    fn cache_key(&self, ctx: &RenderContext) -> Option<u64> {
+        Some(cache_key_of(&(
+            ctx.input.project_dir(),
+            &ctx.config.security.trusted_paths,
+        )))
+    }
+}
+
+/// Whether `dir` is one of `trusted_paths` or below one of them
+fn is_trusted(dir: &Path, trusted_paths: &[String]) -> bool {
+    let dir = canonical(dir);
+    trusted_paths
+        .iter()
+        .map(|path| path.trim())
+        .filter(|path| !path.is_empty())
+        .any(|path| dir.starts_with(canonical(Path::new(&expand_home(path)))))
+}
+
+fn canonical(path: &Path) -> PathBuf {
+    path.canonicalize().unwrap_or_else(|_| path.to_path_buf())
+}
+
+#[cfg(target_os = "linux")]
+fn network_filesystem(dir: &Path) -> Option<String> {
+    let mounts = std::fs::read_to_string("/proc/mounts").ok()?;
+    network_mount(&mounts, &canonical(dir))
+}
+
+#[cfg(windows)]
+fn network_filesystem(dir: &Path) -> Option<String> {
+    // UNC 路径(\\server\share)即网络共享;\\?\ 前缀的本地长路径除外
+    let path = dir.to_string_lossy();
+    (path.starts_with(r"\\") && !path.starts_with(r"\\?\")).then(|| "smb".to_string())
+}
+
+#[cfg(not(any(target_os = "linux", windows)))]
+const fn network_filesystem(_dir: &Path) -> Option<String> {
+    None
+}
+
+/// Filesystem type of the network mount containing `path`, from a
+/// `/proc/mounts` listing; `None` when the innermost mount is local.
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn network_mount(mounts: &str, path: &Path) -> Option<String> {
+    mounts
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let _device = fields.next()?;
+            let mount_point = unescape_mount_field(fields.next()?);
+            let fs_type = fields.next()?;
+            Some((PathBuf::from(mount_point), fs_type))
+        })
+        .filter(|(mount_point, _)| path.starts_with(mount_point))
+        .max_by_key(|(mount_point, _)| mount_point.components().count())
+        .map(|(_, fs_type)| fs_type)
+        .filter(|fs_type| NETWORK_FILESYSTEMS.contains(fs_type))
+        .map(str::to_string)
+}
+
+/// `/proc/mounts` escapes whitespace and backslashes as octal
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn unescape_mount_field(field: &str) -> String {
+    field
+        .replace("\\040", " ")
+        .replace("\\011", "\t")
+        .replace("\\012", "\n")
+        .replace("\\134", "\\")
+}
+
+/// Factory for creating workspace trust components.
+pub struct TrustComponentFactory;
+
+impl ComponentFactory for TrustComponentFactory {
+    fn create(&self, config: &Config) -> Box<dyn Component> {
+        Box::new(TrustComponent::new((*config.components.trust).clone()))
+    }
+
+    fn name(&self) -> &'static str {
+        "trust"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::TerminalCapabilities;
+    use crate::core::input::InputData;
+    use std::sync::Arc;
+    use tempfile::tempdir;
+
+    fn context(cwd: &Path, trusted_paths: Vec<String>) -> RenderContext {
+        let mut config = Config::default();
+        config.security.trusted_paths = trusted_paths;
+        RenderContext {
+            input: Arc::new(InputData {
+                cwd: Some(cwd.to_string_lossy().into_owned()),
+                ..InputData::default()
+            }),
+            config: Arc::new(config),
+            terminal: TerminalCapabilities::default(),
+            preview_mode: false,
+            transcript: None,
+            storage: None,
+        }
+    }
+
+    fn local_only() -> TrustComponent {
+        TrustComponent::new(TrustComponentConfig {
+            warn_network: false,
+            ..TrustComponentConfig::default()
+        })
+    }
+
+    #[tokio::test]
+    async fn untrusted_directory_shows_warning() -> anyhow::Result<()> {
+        let trusted = tempdir()?;
+        let elsewhere = tempdir()?;
+        let trusted_paths = vec![trusted.path().to_string_lossy().into_owned()];
+
+        let output = local_only()
+            .render(&context(elsewhere.path(), trusted_paths.clone()))
+            .await;
+        assert!(output.visible);
+        assert_eq!(output.text, "untrusted");
+        assert_eq!(output.severity, Severity::Warning);
+
+        let nested = trusted.path().join("repo");
+        std::fs::create_dir(&nested)?;
+        let output = local_only().render(&context(&nested, trusted_paths)).await;
+        assert!(!output.visible);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn trusted_state_shown_on_request() -> anyhow::Result<()> {
+        let trusted = tempdir()?;
+        let component = TrustComponent::new(TrustComponentConfig {
+            show_when_trusted: true,
+            warn_network: false,
+            ..TrustComponentConfig::default()
+        });
+
+        let ctx = context(
+            trusted.path(),
+            vec![trusted.path().to_string_lossy().into_owned()],
+        );
+        let output = component.render(&ctx).await;
+        assert_eq!(output.text, "trusted");
+        assert_eq!(output.text_color.as_deref(), Some("green"));
+
+        // 没有配置受信任目录时不判定信任状态
+        let output = component.render(&context(trusted.path(), Vec::new())).await;
+        assert!(!output.visible);
+        Ok(())
+    }
+
+    #[test]
+    fn network_mount_uses_innermost_mount_point() {
+        let mounts = "\
+/dev/sda1 / ext4 rw 0 0
+server:/export /mnt/nfs nfs4 rw 0 0
+/dev/sdb1 /mnt/nfs/local ext4 rw 0 0
+//nas/share /mnt/my\\040share cifs rw 0 0
+";
+        let mount = |path: &str| network_mount(mounts, Path::new(path));
+        assert_eq!(mount("/mnt/nfs/project"), Some("nfs4".to_string()));
+        assert_eq!(mount("/mnt/nfs/local/project"), None);
+        assert_eq!(mount("/mnt/my share/x"), Some("cifs".to_string()));
+        assert_eq!(mount("/home/user"), None);
+    }
+}
@@ -6,17 +6,28 @@
 //! this component integrates seamlessly with the storage system.
 
 use std::fmt::Write;
+use std::sync::Arc;
 
 use crate::components::base::{Component, ComponentFactory, ComponentOutput, RenderContext};
-use crate::config::{BaseComponentConfig, Config, ModelPricingConfig, UsageComponentConfig};
-use crate::storage;
+use crate::config::{
+    BaseComponentConfig, Config, ModelPricingConfig, SegmentAlign, Severity, UsageComponentConfig,
+};
+use crate::storage::usage_report::burn_rate;
+use crate::utils::number_format::{
+    amount_width, format_amount, AmountFormat, NumberLocale, RoundingMode,
+};
+use crate::utils::pad_to_width;
 use crate::utils::provider_profiles::{
-    builtin_endpoint_currency, builtin_model_currency, match_endpoint_currency_rules,
-    match_model_currency_rules, model_names_from_value, provider_currency, provider_pricing,
-    provider_pricing_currency, AUTO_CURRENCY, DEFAULT_CURRENCY,
+    builtin_endpoint_currency, builtin_model_currency, convert_currency,
+    match_endpoint_currency_rules, match_model_currency_rules, model_names_from_value,
+    provider_currency, provider_pricing, provider_pricing_currency, AUTO_CURRENCY,
+    DEFAULT_CURRENCY,
 };
 use async_trait::async_trait;
 
+/// Share of `budget` at which the cost is reported as a warning
+const BUDGET_WARNING_RATIO: f64 = 0.8;
+
 /// Official Session data interface from Claude Code stdin JSON format
 #[derive(Debug, Clone, serde::Deserialize)]
 pub struct OfficialSessionData {
@@ -58,17 +69,88 @@ pub struct OfficialCostData {
     pub total_lines_removed: u64,
 }
 
+/// Where the usage component reads costs from
+///
+/// 默认的 [`ContextCostProvider`] 读取 stdin 负载与渲染上下文的存储句柄;
+/// 测试可以换成固定数据,覆盖序列化失败、存储出错等难以构造的分支。
+#[async_trait]
+pub trait CostProvider: Send + Sync {
+    /// The stdin payload as JSON: upstream cost, token breakdown and line counts
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the payload cannot be serialized.
+    fn session_payload(&self, ctx: &RenderContext) -> anyhow::Result<serde_json::Value>;
+
+    /// Total USD cost of a conversation, including the sessions it resumed
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the stored session history cannot be read.
+    async fn conversation_cost(&self, ctx: &RenderContext, session_id: &str)
+        -> anyhow::Result<f64>;
+}
+
+/// Costs from the render context's input and storage handle
+pub struct ContextCostProvider;
+
+#[async_trait]
+impl CostProvider for ContextCostProvider {
+    fn session_payload(&self, ctx: &RenderContext) -> anyhow::Result<serde_json::Value> {
+        Ok(serde_json::to_value(&*ctx.input)?)
+    }
+
+    async fn conversation_cost(
+        &self,
+        ctx: &RenderContext,
+        session_id: &str,
+    ) -> anyhow::Result<f64> {
+        Ok(ctx
+            .storage()
+            .session_history(session_id)
+            .await?
+            .map_or(0.0, |history| history.cost.total.total_cost_usd))
+    }
+}
+
 /// Usage组件 - 显示Session成本统计
 pub struct UsageComponent {
     name: String,
     config: UsageComponentConfig,
+    /// `style.fixed_width_numbers`:金额按固定宽度右对齐
+    fixed_width: bool,
+    /// `None` 时使用 [`ContextCostProvider`]
+    costs: Option<Arc<dyn CostProvider>>,
 }
 
 impl UsageComponent {
     /// Create new usage component
     #[must_use]
     pub const fn new(name: String, config: UsageComponentConfig) -> Self {
-        Self { name, config }
+        Self {
+            name,
+            config,
+            fixed_width: false,
+            costs: None,
+        }
+    }
+
+    /// Read costs from `provider` instead of the render context
+    #[must_use]
+    pub fn with_cost_provider(mut self, provider: Arc<dyn CostProvider>) -> Self {
+        self.costs = Some(provider);
+        self
+    }
+
+    fn costs(&self) -> &dyn CostProvider {
+        self.costs.as_deref().unwrap_or(&ContextCostProvider)
+    }
+
+    /// Pad displayed costs to a fixed width (`$ 0.32`)
+    #[must_use]
+    pub const fn with_fixed_width(mut self, fixed_width: bool) -> Self {
+        self.fixed_width = fixed_width;
+        self
     }
 
     /// 渲染Mock数据 | Render mock data
@@ -121,14 +203,10 @@ impl UsageComponent {
     fn render_no_data(&self, ctx: &RenderContext) -> ComponentOutput {
         let icon = self.select_icon(ctx);
         let currency_prefix = self.resolve_currency_prefix(None);
-        ComponentOutput::new(Self::format_cost(
-            0.0,
-            self.config.precision,
-            &currency_prefix,
-        ))
-        .with_icon_color("gray".to_string())
-        .with_text_color("gray".to_string())
-        .with_icon(icon.unwrap_or_default())
+        ComponentOutput::new(self.format_cost(0.0, &currency_prefix))
+            .with_icon_color("gray".to_string())
+            .with_text_color("gray".to_string())
+            .with_icon(icon.unwrap_or_default())
     }
 
     /// 格式化官方使用信息显示 | Format official usage info display
@@ -140,15 +218,21 @@ impl UsageComponent {
         let icon = self.select_icon(ctx);
         let endpoint = std::env::var("ANTHROPIC_BASE_URL").ok();
         let cost = Self::resolve_display_cost(data, endpoint.as_deref(), ctx);
+        let source_currency = self.resolve_source_currency(data, endpoint.as_deref(), ctx);
         let currency_code = self.resolve_currency_code(data, endpoint.as_deref(), ctx);
-        let currency_prefix = Self::currency_prefix_for_code(&currency_code);
-        let display_text = self.build_official_display_text(data, &currency_prefix, cost);
-        let color = Self::get_usage_color(cost);
+        let (display_cost, currency_prefix) = self.display_cost(
+            cost,
+            &source_currency,
+            &Self::currency_prefix_for_code(&currency_code),
+        );
+        let display_text = self.build_official_display_text(data, &currency_prefix, display_cost);
+        let color = ctx.config.palette.resolve(Self::get_usage_color(cost));
 
         ComponentOutput::new(display_text)
             .with_icon_color(color.clone())
             .with_text_color(color)
             .with_icon(icon.unwrap_or_default())
+            .with_severity(self.budget_severity(cost, &source_currency))
     }
 
     /// 构建官方数据显示文本 | Build official data display text
@@ -170,7 +254,7 @@ impl UsageComponent {
             .and_then(serde_json::Value::as_u64)
             .unwrap_or(0);
 
-        let mut text = Self::format_cost(cost, self.config.precision, currency_prefix);
+        let mut text = self.format_cost(cost, currency_prefix);
 
         // 根据显示模式和配置添加代码行数 | Add code lines based on display mode and config
         if self.config.display_mode == "conversation"
@@ -195,9 +279,62 @@ impl UsageComponent {
     }
 
     /// 格式化成本显示 | Format cost display
-    fn format_cost(cost: f64, precision: u32, currency_prefix: &str) -> String {
-        let precision = precision as usize;
-        format!("{currency_prefix}{cost:.precision$}")
+    fn format_cost(&self, cost: f64, currency_prefix: &str) -> String {
+        let format = AmountFormat {
+            precision: self.config.precision as usize,
+            rounding: RoundingMode::from_config(&self.config.rounding),
+            compact: self.config.compact,
+            locale: NumberLocale::from_tag(&self.config.locale),
+        };
+        let amount = format_amount(cost, &format);
+        let width = if self.fixed_width {
+            amount_width(format.precision)
+        } else {
+            0
+        };
+        format!(
+            "{currency_prefix}{}",
+            pad_to_width(&amount, width, SegmentAlign::Right)
+        )
+    }
+
+    /// 按汇率换算到固定货币 | Convert `cost` from `source_currency` to the configured currency
+    ///
+    /// auto 模式原样返回;配置了固定货币却缺少汇率时返回 `None`。
+    fn convert_cost(&self, cost: f64, source_currency: &str) -> Option<f64> {
+        let configured_currency = self.config.currency.trim();
+        if configured_currency.eq_ignore_ascii_case(AUTO_CURRENCY) {
+            return Some(cost);
+        }
+        convert_currency(
+            cost,
+            source_currency,
+            configured_currency,
+            &self.config.exchange_rates,
+        )
+    }
+
+    /// 换算后的金额与货币前缀 | Converted `cost` with the prefix to show it under
+    ///
+    /// 无法换算时保留原币种金额,并改用原币种符号,避免把美元金额标成 `£`。
+    fn display_cost(&self, cost: f64, source_currency: &str, prefix: &str) -> (f64, String) {
+        self.convert_cost(cost, source_currency).map_or_else(
+            || (cost, Self::currency_prefix_for_code(source_currency)),
+            |converted| (converted, prefix.to_string()),
+        )
+    }
+
+    /// 格式化以美元计的聚合成本 | Format a USD total (stored aggregates, usage reports)
+    ///
+    /// Uses the configured currency, exchange rates, precision and locale.
+    #[must_use]
+    pub fn format_usd_total(&self, cost_usd: f64) -> String {
+        let (cost, prefix) = self.display_cost(
+            cost_usd,
+            DEFAULT_CURRENCY,
+            &self.resolve_conversation_currency_prefix(),
+        );
+        self.format_cost(cost, &prefix)
     }
 
     fn resolve_currency_prefix(&self, data: Option<&serde_json::Value>) -> String {
@@ -229,6 +366,16 @@ impl UsageComponent {
             return configured_currency.to_string();
         }
 
+        self.resolve_source_currency(data, endpoint, ctx)
+    }
+
+    /// 成本数值本身的货币(忽略固定的 currency 配置) | Currency the cost amount is denominated in
+    fn resolve_source_currency(
+        &self,
+        data: &serde_json::Value,
+        endpoint: Option<&str>,
+        ctx: &RenderContext,
+    ) -> String {
         let model_names = model_names_from_value(data);
         let pricing_currency =
             provider_pricing_currency(&ctx.config.model_providers, &model_names, endpoint);
@@ -437,16 +584,41 @@ impl UsageComponent {
         Some(raw / pricing.unit_tokens)
     }
 
+    /// 按预算判断成本状态 | Severity of a cost against the USD budget
+    ///
+    /// 无汇率可用时按原币种金额直接比较。
+    fn budget_severity(&self, cost: f64, source_currency: &str) -> Severity {
+        let budget = self.config.budget;
+        if budget <= 0.0 {
+            return Severity::Normal;
+        }
+
+        let cost_usd = convert_currency(
+            cost,
+            source_currency,
+            DEFAULT_CURRENCY,
+            &self.config.exchange_rates,
+        )
+        .unwrap_or(cost);
+        if cost_usd >= budget {
+            Severity::Critical
+        } else if cost_usd >= budget * BUDGET_WARNING_RATIO {
+            Severity::Warning
+        } else {
+            Severity::Normal
+        }
+    }
+
     /// 获取使用信息的颜色 | Get usage info color based on cost amount
-    fn get_usage_color(cost: f64) -> String {
+    const fn get_usage_color(cost: f64) -> &'static str {
         if cost > 1.0 {
-            "red".to_string() // 高成本 | High cost
+            "danger" // 高成本 | High cost
         } else if cost > 0.1 {
-            "yellow".to_string() // 中等成本 | Medium cost
+            "warn" // 中等成本 | Medium cost
         } else if cost > 0.0 {
-            "green".to_string() // 低成本 | Low cost
+            "ok" // 低成本 | Low cost
         } else {
-            "gray".to_string() // 无成本 | No cost
+            "gray" // 无成本 | No cost
         }
     }
 
@@ -459,80 +631,96 @@ impl UsageComponent {
     ) -> ComponentOutput {
         let icon = self.select_icon(ctx);
 
-        // preview 模式下绝对不能走真实 storage:`storage::get_conversation_cost_display`
+        // preview 模式下绝对不能走真实 storage:磁盘存储读取会话历史时
         // 内部会调 `StorageManager::new()`,其构造会 `ensure_directories()`,
         // 在用户真实的 `~/.claude/statusline-pro/...` 下建目录,违反"preview
         // 无副作用"的契约。返回一个稳定的 $0.00 占位,预览里只是让用户能看到
         // 这个组件会出现在状态行的哪个位置,数字不需要是真实的。
         if ctx.preview_mode {
-            return ComponentOutput::new(Self::format_cost(
-                0.0,
-                self.config.precision,
-                currency_prefix,
-            ))
-            .with_icon_color("gray".to_string())
-            .with_text_color("gray".to_string())
-            .with_icon(icon.unwrap_or_default());
+            return ComponentOutput::new(self.format_cost(0.0, currency_prefix))
+                .with_icon_color("gray".to_string())
+                .with_text_color("gray".to_string())
+                .with_icon(icon.unwrap_or_default());
         }
 
-        // 使用新的conversation cost API
-        match storage::get_conversation_cost_display(session_id).await {
+        match self.costs().conversation_cost(ctx, session_id).await {
             Ok(cost) => {
                 if cost > 0.0 {
-                    let formatted_cost =
-                        Self::format_cost(cost, self.config.precision, currency_prefix);
+                    let (display_cost, currency_prefix) =
+                        self.display_cost(cost, DEFAULT_CURRENCY, currency_prefix);
+                    let formatted_cost = self.format_cost(display_cost, &currency_prefix);
 
                     ComponentOutput::new(formatted_cost)
                         .with_icon_color("cyan".to_string())
                         .with_text_color("cyan".to_string())
                         .with_icon(icon.unwrap_or_default())
+                        .with_severity(self.budget_severity(cost, DEFAULT_CURRENCY))
                 } else {
-                    ComponentOutput::new(Self::format_cost(
-                        0.0,
-                        self.config.precision,
-                        currency_prefix,
-                    ))
-                    .with_icon_color("gray".to_string())
-                    .with_text_color("gray".to_string())
-                    .with_icon(icon.unwrap_or_default())
+                    ComponentOutput::new(self.format_cost(0.0, currency_prefix))
+                        .with_icon_color("gray".to_string())
+                        .with_text_color("gray".to_string())
+                        .with_icon(icon.unwrap_or_default())
                 }
             }
             Err(e) => {
                 eprintln!("Failed to load conversation cost: {e}");
-                ComponentOutput::new(Self::format_cost(
-                    0.0,
-                    self.config.precision,
-                    currency_prefix,
-                ))
-                .with_icon_color("gray".to_string())
-                .with_text_color("gray".to_string())
-                .with_icon(icon.unwrap_or_default())
+                ComponentOutput::new(self.format_cost(0.0, currency_prefix))
+                    .with_icon_color("gray".to_string())
+                    .with_text_color("gray".to_string())
+                    .with_icon(icon.unwrap_or_default())
             }
         }
     }
 }
 
-fn token_field(value: &serde_json::Value, names: &[&str]) -> Option<f64> {
-    names
-        .iter()
-        .find_map(|name| value.get(*name).and_then(serde_json::Value::as_f64))
-}
-
-#[async_trait]
-impl Component for UsageComponent {
-    fn name(&self) -> &str {
-        &self.name
-    }
-
-    fn is_enabled(&self, _ctx: &RenderContext) -> bool {
-        self.config.base.enabled
-    }
+impl UsageComponent {
+    /// 成本速率异常时在成本旁加标记 | Mark the cost when the session burns
+    /// money far faster than the stored sessions usually do
+    async fn flag_anomaly(
+        &self,
+        ctx: &RenderContext,
+        mut output: ComponentOutput,
+    ) -> ComponentOutput {
+        if !output.visible
+            || ctx.preview_mode
+            || self.config.anomaly_std_devs <= 0.0
+            || ctx.input.extra.get("__mock__").is_some()
+        {
+            return output;
+        }
+        let (Some(session_id), Some(cost)) =
+            (ctx.input.session_id.as_deref(), ctx.input.cost.as_ref())
+        else {
+            return output;
+        };
+        let duration_ms = cost
+            .total_duration_ms
+            .and_then(|ms| u64::try_from(ms).ok())
+            .unwrap_or(0);
+        let Some(rate) = burn_rate(cost.total_cost_usd.unwrap_or(0.0), duration_ms) else {
+            return output;
+        };
 
-    fn base_config(&self, _ctx: &RenderContext) -> Option<&BaseComponentConfig> {
-        Some(&self.config.base)
+        let baseline = match ctx.storage().burn_rate_baseline(session_id).await {
+            Ok(Some(baseline)) => baseline,
+            Ok(None) => return output,
+            Err(err) => {
+                eprintln!("Failed to load cost burn rate baseline: {err}");
+                return output;
+            }
+        };
+        let anomalous = baseline.sessions >= self.config.anomaly_min_sessions
+            && baseline
+                .z_score(rate)
+                .is_some_and(|z| z >= self.config.anomaly_std_devs);
+        if anomalous {
+            output.text = format!("{} {}", output.text, self.config.anomaly_icon);
+            output.severity = output.severity.max(Severity::Warning);
+        }
+        output
     }
 
-    async fn render(&self, ctx: &RenderContext) -> ComponentOutput {
+    async fn render_cost(&self, ctx: &RenderContext) -> ComponentOutput {
         let input_data = &ctx.input;
 
         // 检查是否有Mock数据 | Check for mock data
@@ -542,14 +730,6 @@ impl Component for UsageComponent {
             }
         }
 
-        let serialized_input = match serde_json::to_value(&**input_data) {
-            Ok(value) => Some(value),
-            Err(err) => {
-                eprintln!("Failed to serialize usage input: {err}");
-                None
-            }
-        };
-
         if let Some(session_id) = input_data.session_id.as_deref() {
             if self.config.display_mode == "conversation" {
                 let currency_prefix = self.resolve_conversation_currency_prefix();
@@ -559,16 +739,40 @@ impl Component for UsageComponent {
             }
         }
 
-        if let Some(ref value) = serialized_input {
-            return self.format_official_usage_display(value, ctx);
+        match self.costs().session_payload(ctx) {
+            Ok(value) => self.format_official_usage_display(&value, ctx),
+            Err(err) => {
+                // 序列化失败时的降级处理 | Graceful fallback when serialization fails
+                eprintln!("Failed to serialize usage input: {err}");
+                self.render_no_data(ctx)
+            }
         }
+    }
+}
 
-        if input_data.cost.is_some() {
-            // 存在官方数据但序列化失败时的降级处理 | Graceful fallback when serialization fails
-            return self.render_no_data(ctx);
-        }
+fn token_field(value: &serde_json::Value, names: &[&str]) -> Option<f64> {
+    names
+        .iter()
+        .find_map(|name| value.get(*name).and_then(serde_json::Value::as_f64))
+}
 
-        self.render_no_data(ctx)
+#[async_trait]
+impl Component for UsageComponent {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn is_enabled(&self, _ctx: &RenderContext) -> bool {
+        self.config.base.enabled
+    }
+
+    fn base_config(&self, _ctx: &RenderContext) -> Option<&BaseComponentConfig> {
+        Some(&self.config.base)
+    }
+
+    async fn render(&self, ctx: &RenderContext) -> ComponentOutput {
+        let output = self.render_cost(ctx).await;
+        self.flag_anomaly(ctx, output).await
     }
 }
 
@@ -590,10 +794,10 @@ impl UsageComponentFactory {
 
 impl ComponentFactory for UsageComponentFactory {
     fn create(&self, config: &Config) -> Box<dyn Component> {
-        Box::new(UsageComponent::new(
-            "usage".to_string(),
-            config.components.usage.clone(),
-        ))
+        Box::new(
+            UsageComponent::new("usage".to_string(), (*config.components.usage).clone())
+                .with_fixed_width(config.style.fixed_width_numbers),
+        )
     }
 
     fn name(&self) -> &'static str {
@@ -606,6 +810,7 @@ mod tests {
     use super::*;
     use crate::components::TerminalCapabilities;
     use crate::core::InputData;
+    use crate::storage::{MemoryStorage, SessionHistory};
 
     fn component_with_config(config: UsageComponentConfig) -> UsageComponent {
         UsageComponent::new("usage".to_string(), config)
@@ -662,6 +867,8 @@ mod tests {
             config: std::sync::Arc::new(Config::default()),
             terminal: TerminalCapabilities::default(),
             preview_mode: false,
+            transcript: None,
+            storage: None,
         };
 
         assert_eq!(
@@ -717,7 +924,95 @@ mod tests {
     #[test]
     fn formats_custom_currency_codes_with_separator() {
         assert_eq!(UsageComponent::currency_prefix_for_code("AUD"), "AUD ");
-        assert_eq!(UsageComponent::format_cost(1.234, 2, "AUD "), "AUD 1.23");
+        let component = component_with_config(UsageComponentConfig {
+            precision: 2,
+            ..UsageComponentConfig::default()
+        });
+        assert_eq!(component.format_cost(1.234, "AUD "), "AUD 1.23");
+    }
+
+    #[test]
+    fn fixed_width_costs_keep_their_width() {
+        let component = component_with_config(UsageComponentConfig {
+            precision: 2,
+            ..UsageComponentConfig::default()
+        })
+        .with_fixed_width(true);
+        assert_eq!(component.format_cost(0.32, "$"), "$ 0.32");
+        assert_eq!(component.format_cost(12.5, "$"), "$12.50");
+        // 超出宽度的金额不截断
+        assert_eq!(component.format_cost(123.0, "$"), "$123.00");
+    }
+
+    #[test]
+    fn fixed_currency_converts_with_exchange_rates_and_locale() {
+        let mut config = UsageComponentConfig {
+            currency: "EUR".to_string(),
+            locale: "de-DE".to_string(),
+            ..UsageComponentConfig::default()
+        };
+        config.exchange_rates.insert("eur".to_string(), 0.5);
+        let component = component_with_config(config);
+        let ctx = RenderContext {
+            input: std::sync::Arc::new(InputData::default()),
+            config: std::sync::Arc::new(Config::default()),
+            terminal: TerminalCapabilities::default(),
+            preview_mode: false,
+            transcript: None,
+            storage: None,
+        };
+        let data = serde_json::json!({
+            "model": { "id": "claude-sonnet-4" },
+            "cost": { "total_cost_usd": 2468.0 }
+        });
+
+        assert_eq!(
+            component.format_official_usage_display(&data, &ctx).text,
+            "€1.234,00"
+        );
+        assert_eq!(component.format_usd_total(2.5), "€1,25");
+    }
+
+    #[test]
+    fn rounding_and_compact_apply_to_costs_and_totals() {
+        let component = component_with_config(UsageComponentConfig {
+            rounding: "ceil".to_string(),
+            ..UsageComponentConfig::default()
+        });
+        assert_eq!(component.format_cost(0.001, "$"), "$0.01");
+
+        let component = component_with_config(UsageComponentConfig {
+            compact: true,
+            ..UsageComponentConfig::default()
+        });
+        assert_eq!(component.format_cost(0.05, "$"), "$0.05");
+        assert_eq!(component.format_usd_total(1234.5), "$1.2k");
+    }
+
+    #[test]
+    fn missing_exchange_rate_keeps_the_source_currency_symbol() {
+        let component = component_with_config(UsageComponentConfig {
+            currency: "GBP".to_string(),
+            ..UsageComponentConfig::default()
+        });
+        let ctx = RenderContext {
+            input: std::sync::Arc::new(InputData::default()),
+            config: std::sync::Arc::new(Config::default()),
+            terminal: TerminalCapabilities::default(),
+            preview_mode: false,
+            transcript: None,
+            storage: None,
+        };
+        let data = serde_json::json!({
+            "model": { "id": "claude-sonnet-4" },
+            "cost": { "total_cost_usd": 12.5 }
+        });
+
+        assert_eq!(component.format_usd_total(1234.5), "$1234.50");
+        assert_eq!(
+            component.format_official_usage_display(&data, &ctx).text,
+            "$12.50"
+        );
     }
 
     #[test]
@@ -736,6 +1031,8 @@ mod tests {
             config: std::sync::Arc::new(Config::default()),
             terminal: TerminalCapabilities::default(),
             preview_mode: false,
+            transcript: None,
+            storage: None,
         };
 
         let cost = UsageComponent::resolve_display_cost(&data, None, &ctx);
@@ -758,6 +1055,8 @@ mod tests {
             config: std::sync::Arc::new(Config::default()),
             terminal: TerminalCapabilities::default(),
             preview_mode: false,
+            transcript: None,
+            storage: None,
         };
 
         let cn_cost = UsageComponent::resolve_display_cost(
@@ -785,6 +1084,8 @@ mod tests {
             config: std::sync::Arc::new(Config::default()),
             terminal: TerminalCapabilities::default(),
             preview_mode: false,
+            transcript: None,
+            storage: None,
         };
 
         let cost = UsageComponent::resolve_display_cost(&data, None, &ctx);
@@ -810,6 +1111,8 @@ mod tests {
             config: std::sync::Arc::new(Config::default()),
             terminal: TerminalCapabilities::default(),
             preview_mode: false,
+            transcript: None,
+            storage: None,
         };
 
         let cost = UsageComponent::resolve_display_cost(&data, None, &ctx);
@@ -817,4 +1120,262 @@ mod tests {
         // 793.9k×3 + 129.6k×6 + 18.7M×0.025 = 3,626,800 / 1_000_000 = 3.6268
         assert!((cost - 3.6268).abs() < 1e-6, "expected ~3.6268, got {cost}");
     }
+
+    #[tokio::test]
+    async fn conversation_mode_reads_cost_from_storage_handle() {
+        let mut history = SessionHistory::default();
+        history.cost.total.total_cost_usd = 6.96;
+        let storage = MemoryStorage::new().with_history("conv", history);
+        let component = component_with_config(UsageComponentConfig {
+            display_mode: "conversation".to_string(),
+            ..UsageComponentConfig::default()
+        });
+        let ctx = RenderContext {
+            input: std::sync::Arc::new(InputData {
+                session_id: Some("conv".to_string()),
+                ..InputData::default()
+            }),
+            config: std::sync::Arc::new(Config::default()),
+            terminal: TerminalCapabilities::default(),
+            preview_mode: false,
+            transcript: None,
+            storage: Some(std::sync::Arc::new(storage)),
+        };
+
+        let output = component.render(&ctx).await;
+        assert_eq!(output.text, "$6.96");
+        assert_eq!(output.text_color.as_deref(), Some("cyan"));
+    }
+
+    #[tokio::test]
+    async fn runaway_burn_rate_is_flagged() {
+        // 历史会话每小时 $1.0 ~ $1.4
+        let storage = (0..5).fold(MemoryStorage::new(), |storage, index| {
+            let mut history = SessionHistory::default();
+            history.cost.total.total_cost_usd = 0.1f64.mul_add(f64::from(index), 1.0);
+            history.cost.total.total_duration_ms = 3_600_000;
+            storage.with_history(&format!("past-{index}"), history)
+        });
+        let storage: std::sync::Arc<MemoryStorage> = std::sync::Arc::new(storage);
+        let ctx_with_cost = |cost: f64, min_sessions: usize| {
+            let mut config = Config::default();
+            config.components.usage.anomaly_min_sessions = min_sessions;
+            let input: InputData = serde_json::from_value(serde_json::json!({
+                "session_id": "current",
+                "cost": {"total_cost_usd": cost, "total_duration_ms": 3_600_000}
+            }))
+            .unwrap_or_default();
+            RenderContext {
+                input: std::sync::Arc::new(input),
+                config: std::sync::Arc::new(config),
+                terminal: TerminalCapabilities::default(),
+                preview_mode: false,
+                transcript: None,
+                storage: Some(storage.clone()),
+            }
+        };
+        let render = |ctx: RenderContext| async move {
+            UsageComponentFactory.create(&ctx.config).render(&ctx).await
+        };
+
+        let output = render(ctx_with_cost(1.3, 5)).await;
+        assert_eq!(output.text, "$1.30");
+        assert_eq!(output.severity, Severity::Normal);
+
+        let output = render(ctx_with_cost(12.0, 5)).await;
+        assert_eq!(output.text, "$12.00 ⚠");
+        assert_eq!(output.severity, Severity::Warning);
+
+        // 历史会话不够时不判断
+        let output = render(ctx_with_cost(12.0, 6)).await;
+        assert_eq!(output.text, "$12.00");
+    }
+
+    /// Canned costs; `None` makes the corresponding call fail
+    #[derive(Default)]
+    struct FixedCosts {
+        payload: Option<serde_json::Value>,
+        conversation: Option<f64>,
+        conversation_calls: std::sync::atomic::AtomicUsize,
+    }
+
+    #[async_trait]
+    impl CostProvider for FixedCosts {
+        fn session_payload(&self, _ctx: &RenderContext) -> anyhow::Result<serde_json::Value> {
+            self.payload
+                .clone()
+                .ok_or_else(|| anyhow::anyhow!("payload is not serializable"))
+        }
+
+        async fn conversation_cost(
+            &self,
+            _ctx: &RenderContext,
+            _session_id: &str,
+        ) -> anyhow::Result<f64> {
+            self.conversation_calls
+                .fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            self.conversation
+                .ok_or_else(|| anyhow::anyhow!("storage is unavailable"))
+        }
+    }
+
+    fn ctx_for(input: serde_json::Value) -> RenderContext {
+        RenderContext {
+            input: std::sync::Arc::new(serde_json::from_value(input).unwrap_or_default()),
+            config: std::sync::Arc::new(Config::default()),
+            terminal: TerminalCapabilities::default(),
+            preview_mode: false,
+            transcript: None,
+            storage: None,
+        }
+    }
+
+    fn conversation_config() -> UsageComponentConfig {
+        UsageComponentConfig {
+            display_mode: "conversation".to_string(),
+            ..UsageComponentConfig::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn conversation_mode_converts_the_stored_total_and_checks_the_budget() {
+        let costs = std::sync::Arc::new(FixedCosts {
+            conversation: Some(6.0),
+            ..FixedCosts::default()
+        });
+        let component = component_with_config(UsageComponentConfig {
+            currency: "EUR".to_string(),
+            exchange_rates: [("EUR".to_string(), 0.5)].into(),
+            budget: 5.0,
+            ..conversation_config()
+        })
+        .with_cost_provider(costs.clone());
+
+        let output = component
+            .render(&ctx_for(serde_json::json!({"session_id": "conv"})))
+            .await;
+        assert_eq!(output.text, "€3.00");
+        // 预算按美元比较
+        assert_eq!(output.severity, Severity::Critical);
+        assert_eq!(
+            costs
+                .conversation_calls
+                .load(std::sync::atomic::Ordering::SeqCst),
+            1
+        );
+    }
+
+    #[tokio::test]
+    async fn conversation_mode_falls_back_to_zero() {
+        for conversation in [Some(0.0), None] {
+            let component = component_with_config(conversation_config()).with_cost_provider(
+                std::sync::Arc::new(FixedCosts {
+                    conversation,
+                    ..FixedCosts::default()
+                }),
+            );
+            let output = component
+                .render(&ctx_for(serde_json::json!({"session_id": "conv"})))
+                .await;
+            assert_eq!(output.text, "$0.00", "conversation cost {conversation:?}");
+            assert_eq!(output.text_color.as_deref(), Some("gray"));
+            assert_eq!(output.severity, Severity::Normal);
+        }
+    }
+
+    #[tokio::test]
+    async fn preview_mode_never_reads_the_conversation_cost() {
+        let costs = std::sync::Arc::new(FixedCosts {
+            conversation: Some(6.0),
+            ..FixedCosts::default()
+        });
+        let component =
+            component_with_config(conversation_config()).with_cost_provider(costs.clone());
+        let mut ctx = ctx_for(serde_json::json!({"session_id": "conv"}));
+        ctx.preview_mode = true;
+
+        let output = component.render(&ctx).await;
+        assert_eq!(output.text, "$0.00");
+        assert_eq!(
+            costs
+                .conversation_calls
+                .load(std::sync::atomic::Ordering::SeqCst),
+            0
+        );
+    }
+
+    #[tokio::test]
+    async fn unserializable_input_renders_zero_cost() {
+        let component = component_with_config(UsageComponentConfig::default())
+            .with_cost_provider(std::sync::Arc::new(FixedCosts::default()));
+        let output = component
+            .render(&ctx_for(serde_json::json!({
+                "session_id": "s1",
+                "cost": {"total_cost_usd": 3.5}
+            })))
+            .await;
+        assert_eq!(output.text, "$0.00");
+        assert_eq!(output.text_color.as_deref(), Some("gray"));
+    }
+
+    #[tokio::test]
+    async fn precision_controls_decimal_places() {
+        let ctx = ctx_for(serde_json::json!({"cost": {"total_cost_usd": 0.123_45}}));
+        for (precision, expected) in [(0, "$0"), (2, "$0.12"), (4, "$0.1235")] {
+            let component = component_with_config(UsageComponentConfig {
+                precision,
+                ..UsageComponentConfig::default()
+            });
+            assert_eq!(component.render(&ctx).await.text, expected);
+        }
+    }
+
+    #[tokio::test]
+    async fn line_counts_follow_the_toggles() {
+        let payload = serde_json::json!({
+            "cost": {
+                "total_cost_usd": 0.5,
+                "total_lines_added": 25,
+                "total_lines_removed": 0
+            }
+        });
+        let render = |show_lines_added, show_lines_removed, payload: &serde_json::Value| {
+            let component = component_with_config(UsageComponentConfig {
+                show_lines_added,
+                show_lines_removed,
+                ..conversation_config()
+            })
+            .with_cost_provider(std::sync::Arc::new(FixedCosts {
+                payload: Some(payload.clone()),
+                ..FixedCosts::default()
+            }));
+            async move { component.render(&ctx_for(serde_json::json!({}))).await.text }
+        };
+
+        assert_eq!(render(true, true, &payload).await, "$0.50 +25");
+        assert_eq!(render(false, true, &payload).await, "$0.50");
+        let mut both = payload.clone();
+        both["cost"]["total_lines_removed"] = serde_json::json!(8);
+        assert_eq!(render(true, true, &both).await, "$0.50 +25 -8");
+        assert_eq!(render(false, true, &both).await, "$0.50 -8");
+        assert_eq!(render(false, false, &both).await, "$0.50");
+    }
+
+    #[tokio::test]
+    async fn mock_usage_data_overrides_the_default_scenario() {
+        let component = component_with_config(UsageComponentConfig {
+            show_lines_added: true,
+            ..conversation_config()
+        });
+        let ctx = ctx_for(serde_json::json!({
+            "session_id": "mock",
+            "__mock__": {
+                "usageData": {"cost": {"total_cost_usd": 2.5, "total_lines_added": 3}}
+            }
+        }));
+        assert_eq!(component.render(&ctx).await.text, "$2.50 +3");
+
+        let ctx = ctx_for(serde_json::json!({"__mock__": {"usageData": {}}}));
+        assert_eq!(component.render(&ctx).await.text, "$0.12 +25");
+    }
 }
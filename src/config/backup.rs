@@ -0,0 +1,178 @@
+//! Config file backups
+//!
+//! 每次改写配置文件(`persist` / `apply_theme` / `config init` / `config set` /
+//! `config upgrade` / TUI 保存)之前,把原文件复制到同级的 `backups/<文件名>-<时间戳>.toml`,
+//! 只保留最近 [`MAX_CONFIG_BACKUPS`] 份。`config rollback [n]` 恢复倒数第 n 份。
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context, Result};
+
+/// Number of backups kept per config file
+pub const MAX_CONFIG_BACKUPS: usize = 10;
+
+/// Directory holding the backups of `config_path`
+#[must_use]
+pub fn backup_dir(config_path: &Path) -> PathBuf {
+    config_path
+        .parent()
+        .unwrap_or_else(|| Path::new("."))
+        .join("backups")
+}
+
+/// File name prefix shared by the backups of `config_path`
+fn backup_prefix(config_path: &Path) -> String {
+    let stem = config_path
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or("config");
+    format!("{stem}-")
+}
+
+/// Backups of `config_path`, newest first
+///
+/// # Errors
+///
+/// Returns an error when the backup directory exists but cannot be read.
+pub fn list_backups(config_path: &Path) -> Result<Vec<PathBuf>> {
+    let dir = backup_dir(config_path);
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+    let prefix = backup_prefix(config_path);
+    let mut backups: Vec<PathBuf> = fs::read_dir(&dir)
+        .with_context(|| format!("Failed to read backup directory: {}", dir.display()))?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            path.extension().is_some_and(|ext| ext == "toml")
+                && path
+                    .file_name()
+                    .and_then(|name| name.to_str())
+                    .is_some_and(|name| name.starts_with(&prefix))
+        })
+        .collect();
+    // 时间戳定长,按文件名倒序即按时间倒序
+    backups.sort_by(|a, b| b.cmp(a));
+    Ok(backups)
+}
+
+/// Copy `config_path` into its backup directory and prune old backups.
+///
+/// Returns `None` when there is nothing to back up: the file does not exist
+/// or matches the newest backup.
+///
+/// # Errors
+///
+/// Returns an error when the config cannot be read or the backup cannot be
+/// written.
+pub fn backup_config(config_path: &Path) -> Result<Option<PathBuf>> {
+    if !config_path.exists() {
+        return Ok(None);
+    }
+    let content = fs::read(config_path)
+        .with_context(|| format!("Failed to read config file: {}", config_path.display()))?;
+    let backups = list_backups(config_path)?;
+    if backups
+        .first()
+        .and_then(|newest| fs::read(newest).ok())
+        .is_some_and(|newest| newest == content)
+    {
+        return Ok(None);
+    }
+
+    let dir = backup_dir(config_path);
+    fs::create_dir_all(&dir)
+        .with_context(|| format!("Failed to create backup directory: {}", dir.display()))?;
+    let timestamp = chrono::Local::now().format("%Y%m%d-%H%M%S-%3f");
+    let backup_path = dir.join(format!("{}{timestamp}.toml", backup_prefix(config_path)));
+    fs::write(&backup_path, &content)
+        .with_context(|| format!("Failed to write backup: {}", backup_path.display()))?;
+
+    for stale in list_backups(config_path)?.iter().skip(MAX_CONFIG_BACKUPS) {
+        let _ = fs::remove_file(stale);
+    }
+    Ok(Some(backup_path))
+}
+
+/// Restore the `n`-th newest backup (1 = newest) over `config_path`.
+///
+/// 恢复前会先备份当前文件,再次 `rollback 1` 即可撤销本次恢复。
+///
+/// # Errors
+///
+/// Returns an error when `n` is out of range or the files cannot be copied.
+pub fn rollback_config(config_path: &Path, n: usize) -> Result<PathBuf> {
+    let backups = list_backups(config_path)?;
+    if backups.is_empty() {
+        bail!("No backups found for {}", config_path.display());
+    }
+    let Some(source) = n
+        .checked_sub(1)
+        .and_then(|index| backups.get(index))
+        .cloned()
+    else {
+        bail!(
+            "Backup #{n} does not exist, {} backup(s) available",
+            backups.len()
+        );
+    };
+
+    backup_config(config_path)?;
+    fs::copy(&source, config_path).with_context(|| {
+        format!(
+            "Failed to restore {} from {}",
+            config_path.display(),
+            source.display()
+        )
+    })?;
+    Ok(source)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn backups_are_bounded_and_deduplicated() -> Result<()> {
+        let temp_dir = tempdir()?;
+        let config = temp_dir.path().join("config.toml");
+        assert!(backup_config(&config)?.is_none());
+
+        for index in 0..MAX_CONFIG_BACKUPS + 3 {
+            fs::write(&config, format!("max_width = {index}\n"))?;
+            assert!(backup_config(&config)?.is_some());
+            std::thread::sleep(std::time::Duration::from_millis(2));
+        }
+        // 内容未变时不重复备份
+        assert!(backup_config(&config)?.is_none());
+
+        let backups = list_backups(&config)?;
+        assert_eq!(backups.len(), MAX_CONFIG_BACKUPS);
+        let newest = fs::read_to_string(&backups[0])?;
+        assert_eq!(newest, format!("max_width = {}\n", MAX_CONFIG_BACKUPS + 2));
+        Ok(())
+    }
+
+    #[test]
+    fn rollback_restores_and_can_be_undone() -> Result<()> {
+        let temp_dir = tempdir()?;
+        let config = temp_dir.path().join("config.toml");
+        fs::write(&config, "theme = \"classic\"\n")?;
+        backup_config(&config)?;
+        std::thread::sleep(std::time::Duration::from_millis(2));
+        fs::write(&config, "theme = \"broken\n")?;
+
+        rollback_config(&config, 1)?;
+        assert_eq!(fs::read_to_string(&config)?, "theme = \"classic\"\n");
+
+        std::thread::sleep(std::time::Duration::from_millis(2));
+        rollback_config(&config, 1)?;
+        assert_eq!(fs::read_to_string(&config)?, "theme = \"broken\n");
+
+        assert!(rollback_config(&config, 0).is_err());
+        assert!(rollback_config(&config, 9).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,119 @@
+//! Compact rendering profile
+//!
+//! 终端宽度低于 `compact.width_threshold`,或设置了 `COMPACT=1` 时,把 `[compact]`
+//! 下的覆盖项(与主配置同样的键,如 `[compact.components.tokens]`)合并到配置上,
+//! 得到更短的模型名、不带进度条的 token 和缩写的成本。`COMPACT=0` 强制关闭。
+
+use anyhow::{Context, Result};
+use serde_json::Value;
+
+use super::loader::ConfigLoader;
+use super::Config;
+use crate::terminal::detector::TerminalDetector;
+
+/// Environment variable forcing the compact profile on (`1`) or off (`0`)
+pub const COMPACT_ENV: &str = "COMPACT";
+
+impl Config {
+    /// Whether the compact profile applies, given the value of `COMPACT` and
+    /// the terminal width.
+    #[must_use]
+    pub fn compact_wanted(&self, flag: Option<&str>, columns: Option<usize>) -> bool {
+        if let Some(flag) = flag.map(str::trim).filter(|flag| !flag.is_empty()) {
+            return flag != "0";
+        }
+        let threshold = self.compact.width_threshold;
+        threshold > 0 && columns.is_some_and(|columns| columns < threshold)
+    }
+
+    /// This configuration with the `[compact]` overrides merged on top.
+    ///
+    /// # Errors
+    ///
+    /// Fails when the overrides do not fit the configuration schema.
+    pub fn with_compact_overrides(&self) -> Result<Self> {
+        let mut value = serde_json::to_value(self).context("Failed to serialize config")?;
+        let mut overrides = self.compact.overrides.clone();
+        // 覆盖项不能再修改 [compact] 自身
+        overrides.remove("compact");
+        ConfigLoader::merge_value(&mut value, Value::Object(overrides));
+        serde_json::from_value(value).context("[compact] 覆盖项与配置结构不匹配")
+    }
+
+    /// Switch to the compact profile when `COMPACT` or the terminal width ask
+    /// for it; returns whether it was applied.
+    ///
+    /// # Errors
+    ///
+    /// Fails when the overrides do not fit the configuration schema.
+    pub fn apply_compact_profile(&mut self) -> Result<bool> {
+        let flag = std::env::var(COMPACT_ENV).ok();
+        // 只在配置了阈值时才探测宽度
+        let columns = if self.compact.width_threshold > 0 {
+            TerminalDetector::detect_columns()
+        } else {
+            None
+        };
+        if !self.compact_wanted(flag.as_deref(), columns) {
+            return Ok(false);
+        }
+        *self = self.with_compact_overrides()?;
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compact_selected_by_flag_or_width() {
+        let mut config = Config::default();
+        assert!(!config.compact_wanted(None, Some(60)));
+        assert!(config.compact_wanted(Some("1"), None));
+
+        config.compact.width_threshold = 100;
+        assert!(config.compact_wanted(None, Some(99)));
+        assert!(!config.compact_wanted(None, Some(100)));
+        assert!(!config.compact_wanted(None, None));
+        // COMPACT=0 在窄终端上也保持完整显示
+        assert!(!config.compact_wanted(Some("0"), Some(40)));
+    }
+
+    #[test]
+    fn overrides_merge_onto_config() -> Result<()> {
+        let mut config = Config::default();
+        config.components.model.show_full_name = true;
+        config
+            .compact
+            .overrides
+            .insert("preset".to_string(), Value::String("PMT".to_string()));
+
+        let compact = config.with_compact_overrides()?;
+        assert!(!compact.components.model.show_full_name);
+        assert!(!compact.components.tokens.show_progress_bar);
+        assert!(compact.components.usage.compact);
+        assert_eq!(compact.preset.as_deref(), Some("PMT"));
+        // 未覆盖的值保持不变
+        assert_eq!(compact.theme, config.theme);
+        Ok(())
+    }
+
+    #[test]
+    fn compact_section_parses_from_toml() -> Result<()> {
+        let config: Config = toml_edit::de::from_str(
+            r"
+[compact]
+width_threshold = 90
+
+[compact.components.tokens]
+show_percentage = false
+",
+        )?;
+        assert_eq!(config.compact.width_threshold, 90);
+
+        let compact = config.with_compact_overrides()?;
+        assert!(!compact.components.tokens.show_percentage);
+        Ok(())
+    }
+}
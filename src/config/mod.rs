@@ -3,24 +3,38 @@
 //! This module handles all configuration-related functionality,
 //! including schema definitions, loading, and validation.
 
+pub mod backup;
+pub mod compact;
 pub mod component_widgets;
+pub mod lazy;
 pub mod loader;
+pub mod profiles;
+pub mod project_patterns;
 pub mod schema;
+pub mod upgrade;
 
 // Re-export commonly used types
 pub use component_widgets::{
     ComponentMultilineConfig, ComponentMultilineMeta, WidgetApiConfig, WidgetApiMethod,
     WidgetConfig, WidgetDetectionConfig, WidgetFilterConfig, WidgetFilterMode, WidgetType,
 };
+pub use lazy::LazySection;
 pub use loader::{
-    ComponentCopyStats, ConfigLoader, ConfigSource, ConfigSourceType, CreateConfigOptions,
-    CreateConfigResult, MergeLayer, MergeReport, TerminalCapabilityHint,
+    ComponentConflict, ComponentCopyStats, ConfigLoader, ConfigSource, ConfigSourceType,
+    ConflictAction, CopyComponentOptions, CreateConfigOptions, CreateConfigResult, MergeLayer,
+    MergeReport, TerminalCapabilityHint,
 };
 pub use schema::{
-    AutoDetect, BaseComponentConfig, BranchComponentConfig, ComponentsConfig, Config,
-    ModelComponentConfig, ModelPricingConfig, ModelProviderConfig, MultilineConfig,
-    MultilineRowConfig, ProjectComponentConfig, RateLimitComponentConfig, StatusComponentConfig,
-    StorageConfig, StyleConfig, TerminalConfig, TokenIconSetConfig, TokensColorConfig,
-    TokensComponentConfig, TokensProgressBarCharsConfig, TokensStatusIconsConfig,
-    TokensThresholdsConfig, UsageComponentConfig,
+    AutoDetect, BackgroundGradientConfig, BaseComponentConfig, BranchComponentConfig,
+    BranchPrefixRule, CompactConfig, ComponentsConfig, Config, FieldComponentConfig, FrameConfig,
+    HookComponentConfig, HooksConfig, IdleConfig, ModelComponentConfig, ModelPricingConfig,
+    ModelProviderConfig, MultilineConfig, MultilineRowConfig, PaletteConfig, PerformanceConfig,
+    PricingConfig, ProjectComponentConfig, ProjectMappingConfig, QuotaComponentConfig,
+    RateLimitComponentConfig, RepoComponentConfig, SecurityConfig, SegmentAlign,
+    SessionComponentConfig, Severity, SeverityRuleConfig, StatusComponentConfig, StatusErrorRule,
+    StatusErrorSeverity, StorageConfig, StyleConfig, TerminalConfig, TextComponentConfig,
+    TicketComponentConfig, TokenIconSetConfig, TokensColorConfig, TokensComponentConfig,
+    TokensProgressBarCharsConfig, TokensStatusIconsConfig, TokensThresholdsConfig,
+    TrustComponentConfig, UsageComponentConfig,
 };
+pub use upgrade::UpgradeReport;
@@ -0,0 +1,130 @@
+//! Named statusline profiles
+//!
+//! 同一份配置文件可以定义多个 `[profiles.<name>]` 段,键与主配置相同。
+//! `ccsp render --profile minimal` 把对应段合并到配置上,不同项目的 Claude Code
+//! 设置可以注册不同的状态栏命令,却共用一份配置。合并顺序:配置文件 → 配置档 →
+//! `[compact]` → 命令行参数。
+
+use anyhow::{bail, Context, Result};
+use serde_json::Value;
+
+use super::loader::ConfigLoader;
+use super::Config;
+
+impl Config {
+    /// Names of the profiles defined in `[profiles.*]`, sorted
+    #[must_use]
+    pub fn profile_names(&self) -> Vec<&str> {
+        self.profiles.keys().map(String::as_str).collect()
+    }
+
+    /// This configuration with profile `name` merged on top.
+    ///
+    /// A profile that sets `preset` without `components.order` replaces the
+    /// inherited order, so the preset takes effect.
+    ///
+    /// # Errors
+    ///
+    /// Fails when the profile is not defined, or its keys do not fit the
+    /// configuration schema.
+    pub fn with_profile(&self, name: &str) -> Result<Self> {
+        let Some(profile) = self.profiles.get(name) else {
+            if self.profiles.is_empty() {
+                bail!("未找到配置档 '{name}': 配置中没有 [profiles.*] 段");
+            }
+            bail!(
+                "未找到配置档 '{name}'(已定义: {})",
+                self.profile_names().join(", ")
+            );
+        };
+        let mut value = serde_json::to_value(self).context("Failed to serialize config")?;
+        let mut overrides = profile.clone();
+        // 配置档之间不能互相改写
+        overrides.remove("profiles");
+        // 只换 preset 的配置档:主配置里的 components.order 会优先于 preset,需一并清空
+        let sets_order = overrides
+            .get("components")
+            .and_then(|components| components.get("order"))
+            .is_some();
+        if overrides.contains_key("preset") && !sets_order {
+            value["components"]["order"] = Value::Array(Vec::new());
+        }
+        ConfigLoader::merge_value(&mut value, Value::Object(overrides));
+        serde_json::from_value(value).with_context(|| format!("[profiles.{name}] 与配置结构不匹配"))
+    }
+
+    /// Switch to profile `name` in place.
+    ///
+    /// # Errors
+    ///
+    /// See [`Self::with_profile`].
+    pub fn apply_profile(&mut self, name: &str) -> Result<()> {
+        *self = self.with_profile(name)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = r#"
+preset = "PMBTURS"
+theme = "powerline"
+
+[components]
+order = ["project", "model", "branch", "tokens", "usage"]
+
+[profiles.minimal]
+preset = "PM"
+theme = "classic"
+
+[profiles.minimal.components.model]
+show_full_name = true
+
+[profiles.focus.components]
+order = ["branch", "status"]
+"#;
+
+    #[test]
+    fn profiles_merge_onto_config() -> Result<()> {
+        let config: Config = toml_edit::de::from_str(CONFIG)?;
+        assert_eq!(config.profile_names(), ["focus", "minimal"]);
+
+        let minimal = config.with_profile("minimal")?;
+        assert_eq!(minimal.preset.as_deref(), Some("PM"));
+        assert!(minimal.components.order.is_empty());
+        assert_eq!(minimal.theme, "classic");
+        assert!(minimal.components.model.show_full_name);
+        // 未覆盖的值保持不变
+        assert_eq!(minimal.language, config.language);
+
+        let mut focus = config;
+        focus.apply_profile("focus")?;
+        assert_eq!(focus.components.order, ["branch", "status"]);
+        assert_eq!(focus.theme, "powerline");
+        Ok(())
+    }
+
+    #[test]
+    fn unknown_or_invalid_profiles_are_errors() -> Result<()> {
+        let err = Config::default()
+            .with_profile("minimal")
+            .map_err(|err| err.to_string());
+        assert!(
+            matches!(&err, Err(message) if message.contains("[profiles.*]")),
+            "{err:?}"
+        );
+
+        let config: Config = toml_edit::de::from_str(CONFIG)?;
+        let err = config.with_profile("wide").map_err(|err| err.to_string());
+        assert!(
+            matches!(&err, Err(message) if message.contains("focus, minimal")),
+            "{err:?}"
+        );
+
+        let broken: Config = toml_edit::de::from_str("[profiles.broken]\ntheme = 3\n")?;
+        assert!(broken.with_profile("broken").is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,136 @@
+//! Directory-pattern project settings
+//!
+//! `~/.claude/statusline-pro/projects.toml` 以 glob 为表名,当前项目路径匹配时
+//! 把该表合并到配置上,整棵目录树共享设置,不必为每个仓库单独建项目级配置:
+//!
+//! ```toml
+//! ["~/work/**"]
+//! theme = "powerline"
+//!
+//! ["~/oss/*"]
+//! preset = "PMB"
+//! ```
+//!
+//! `*` 不跨越 `/`,`**` 匹配任意层目录;多个表匹配时按文件中的顺序合并,后面的覆盖前面的。
+
+use std::path::Path;
+
+use glob::{MatchOptions, Pattern};
+use serde_json::Value;
+use toml_edit::DocumentMut;
+
+/// File name of the pattern table, next to the user config
+pub const PROJECT_PATTERNS_FILE: &str = "projects.toml";
+
+const MATCH_OPTIONS: MatchOptions = MatchOptions {
+    case_sensitive: !cfg!(windows),
+    require_literal_separator: true,
+    require_literal_leading_dot: false,
+};
+
+/// `path` with `\` separators replaced by `/`
+fn normalize(path: &str) -> String {
+    path.replace('\\', "/")
+}
+
+/// `pattern` with a leading `~` expanded to `home`
+fn expand_home(pattern: &str, home: Option<&Path>) -> String {
+    match (pattern.strip_prefix('~'), home) {
+        (Some(rest), Some(home)) if rest.is_empty() || rest.starts_with(['/', '\\']) => {
+            format!("{}{rest}", home.display())
+        }
+        _ => pattern.to_string(),
+    }
+}
+
+/// Whether `project_path` matches `pattern`
+#[must_use]
+pub fn pattern_matches(pattern: &str, project_path: &str, home: Option<&Path>) -> bool {
+    let pattern = normalize(&expand_home(pattern.trim(), home));
+    let pattern = pattern.trim_end_matches('/');
+    let path = normalize(project_path);
+    let path = path.trim_end_matches('/');
+    Pattern::new(pattern).is_ok_and(|pattern| pattern.matches_with(path, MATCH_OPTIONS))
+}
+
+/// Tables of `document` whose pattern matches `project_path`, in file order
+#[must_use]
+pub fn matching_sections(
+    document: &DocumentMut,
+    project_path: &str,
+    home: Option<&Path>,
+) -> Vec<(String, Value)> {
+    document
+        .iter()
+        .filter(|(pattern, item)| item.is_table() && pattern_matches(pattern, project_path, home))
+        .filter_map(|(pattern, item)| {
+            let mut section = DocumentMut::new();
+            section.insert("section", item.clone());
+            let mut value: Value = toml_edit::de::from_str(&section.to_string()).ok()?;
+            Some((pattern.to_string(), value.get_mut("section")?.take()))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn globs_match_directory_trees() {
+        let home = Some(Path::new("/home/dev"));
+        assert!(pattern_matches("~/work/**", "/home/dev/work/api", home));
+        assert!(pattern_matches(
+            "~/work/**",
+            "/home/dev/work/team/api/",
+            home
+        ));
+        assert!(!pattern_matches(
+            "~/work/**",
+            "/home/dev/personal/api",
+            home
+        ));
+        // `*` 只匹配一层
+        assert!(pattern_matches("~/oss/*", "/home/dev/oss/ripgrep", home));
+        assert!(!pattern_matches(
+            "~/oss/*",
+            "/home/dev/oss/rust/compiler",
+            home
+        ));
+        assert!(pattern_matches(
+            "/srv/*-service",
+            "/srv/billing-service",
+            home
+        ));
+        assert!(pattern_matches(
+            "C:/Users/dev/**",
+            "C:\\Users\\dev\\repo",
+            home
+        ));
+    }
+
+    #[test]
+    fn matching_sections_keep_file_order() -> anyhow::Result<()> {
+        let document = concat!(
+            "[\"/work/**\"]\n",
+            "theme = \"powerline\"\n\n",
+            "[\"/work/api\"]\n",
+            "theme = \"capsule\"\n",
+            "[\"/work/api\".components.tokens]\n",
+            "progress_width = 8\n\n",
+            "[\"/other/**\"]\n",
+            "theme = \"classic\"\n",
+        )
+        .parse::<DocumentMut>()?;
+
+        let sections = matching_sections(&document, "/work/api", None);
+        let patterns: Vec<&str> = sections
+            .iter()
+            .map(|(pattern, _)| pattern.as_str())
+            .collect();
+        assert_eq!(patterns, ["/work/**", "/work/api"]);
+        assert_eq!(sections[1].1["theme"], "capsule");
+        assert_eq!(sections[1].1["components"]["tokens"]["progress_width"], 8);
+        Ok(())
+    }
+}
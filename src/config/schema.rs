@@ -3,16 +3,21 @@
 //! This module defines all configuration structures for the statusline,
 //! compatible with the TypeScript version's TOML config files.
 
+use super::lazy::LazySection;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 /// Main configuration structure
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct Config {
-    /// Component preset string (e.g., "PMBTURS")
+    /// Component preset string (e.g., "PMBTURS", "PMBTUSH")
     #[serde(default)]
     pub preset: Option<String>,
 
+    /// Extra preset letters (`"W" = "weather"`), for components registered at runtime
+    #[serde(default)]
+    pub preset_mapping: BTreeMap<String, String>,
+
     /// Theme name (classic, powerline, capsule)
     #[serde(default = "default_theme")]
     pub theme: String,
@@ -21,10 +26,20 @@ pub struct Config {
     #[serde(default = "default_language")]
     pub language: String,
 
+    /// User translations of built-in strings per language
+    /// (`[translations.zh] Ready = "就绪"`), taking precedence over the
+    /// built-in tables
+    #[serde(default)]
+    pub translations: HashMap<String, HashMap<String, String>>,
+
     /// Debug mode
     #[serde(default)]
     pub debug: bool,
 
+    /// Disable all network access (API widgets, pricing updates)
+    #[serde(default)]
+    pub offline: bool,
+
     /// Terminal capabilities override
     #[serde(default)]
     pub terminal: TerminalConfig,
@@ -37,10 +52,39 @@ pub struct Config {
     #[serde(default)]
     pub style: StyleConfig,
 
+    /// Colors of the semantic roles (`ok`, `warn`, `danger`, `info`)
+    #[serde(default)]
+    pub palette: PaletteConfig,
+
+    /// Overrides applied on narrow terminals or with `COMPACT=1`
+    #[serde(default)]
+    pub compact: CompactConfig,
+
+    /// Named overrides selected with `ccsp render --profile <name>`
+    /// (`[profiles.minimal] preset = "PM"`), same keys as the main configuration
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub profiles: BTreeMap<String, serde_json::Map<String, serde_json::Value>>,
+
+    /// Workspace trust settings
+    #[serde(default)]
+    pub security: SecurityConfig,
+
     /// Shared model/provider profiles consumed by multiple components
     #[serde(default)]
     pub model_providers: HashMap<String, ModelProviderConfig>,
 
+    /// Model price catalog used by usage reports
+    #[serde(default)]
+    pub pricing: PricingConfig,
+
+    /// Shell commands run on session events
+    #[serde(default)]
+    pub hooks: HooksConfig,
+
+    /// Whole-render limits
+    #[serde(default)]
+    pub performance: PerformanceConfig,
+
     /// Component configurations
     #[serde(default)]
     pub components: ComponentsConfig,
@@ -58,13 +102,23 @@ impl Default for Config {
     fn default() -> Self {
         Self {
             preset: Some("PMBTURS".to_string()),
+            preset_mapping: BTreeMap::new(),
             theme: default_theme(),
             language: default_language(),
+            translations: HashMap::new(),
             debug: false,
+            offline: false,
             terminal: TerminalConfig::default(),
             storage: StorageConfig::default(),
             style: StyleConfig::default(),
+            palette: PaletteConfig::default(),
+            compact: CompactConfig::default(),
+            profiles: BTreeMap::new(),
+            security: SecurityConfig::default(),
             model_providers: default_model_providers(),
+            pricing: PricingConfig::default(),
+            hooks: HooksConfig::default(),
+            performance: PerformanceConfig::default(),
             components: ComponentsConfig::default(),
             multiline: Some(MultilineConfig::default()),
             themes: ThemesConfig::default(),
@@ -72,6 +126,216 @@ impl Default for Config {
     }
 }
 
+/// Semantic color roles
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct PaletteConfig {
+    /// Built-in role colors: `none`, `deuteranopia` or `protanopia`
+    #[serde(default = "default_palette_accessibility")]
+    pub accessibility: String,
+
+    /// Per-role overrides of the preset
+    #[serde(default)]
+    pub ok: Option<String>,
+    #[serde(default)]
+    pub warn: Option<String>,
+    #[serde(default)]
+    pub danger: Option<String>,
+    #[serde(default)]
+    pub info: Option<String>,
+}
+
+impl Default for PaletteConfig {
+    fn default() -> Self {
+        Self {
+            accessibility: default_palette_accessibility(),
+            ok: None,
+            warn: None,
+            danger: None,
+            info: None,
+        }
+    }
+}
+
+fn default_palette_accessibility() -> String {
+    "none".to_string()
+}
+
+/// Compact rendering profile
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct CompactConfig {
+    /// Terminal width (columns) below which the profile is used; 0 = only
+    /// with `COMPACT=1`
+    #[serde(default)]
+    pub width_threshold: usize,
+
+    /// Any configuration keys (`components.tokens.show_progress_bar = false`)
+    /// merged over the configuration in compact mode
+    #[serde(flatten)]
+    pub overrides: serde_json::Map<String, serde_json::Value>,
+}
+
+impl Default for CompactConfig {
+    fn default() -> Self {
+        let overrides = serde_json::json!({
+            "components": {
+                "model": { "show_full_name": false, "show_context_window": false },
+                "tokens": { "show_progress_bar": false, "show_numbers": false },
+                "usage": { "compact": true, "precision": 1 },
+            }
+        });
+        Self {
+            width_threshold: 0,
+            overrides: match overrides {
+                serde_json::Value::Object(map) => map,
+                _ => serde_json::Map::new(),
+            },
+        }
+    }
+}
+
+/// Workspace trust settings
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct SecurityConfig {
+    /// Directories (and everything below them) the agent may work in;
+    /// `~` expands to the home directory. Empty disables the trust check.
+    #[serde(default)]
+    pub trusted_paths: Vec<String>,
+}
+
+/// Model price catalog source
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct PricingConfig {
+    /// LiteLLM-style price list fetched by `ccsp pricing update`
+    #[serde(default = "default_pricing_source_url")]
+    pub source_url: String,
+
+    /// Request timeout of the update in milliseconds
+    #[serde(default = "default_pricing_timeout")]
+    pub timeout_ms: u64,
+}
+
+impl Default for PricingConfig {
+    fn default() -> Self {
+        Self {
+            source_url: default_pricing_source_url(),
+            timeout_ms: default_pricing_timeout(),
+        }
+    }
+}
+
+fn default_pricing_source_url() -> String {
+    "https://raw.githubusercontent.com/BerriAI/litellm/main/model_prices_and_context_window.json"
+        .to_string()
+}
+
+const fn default_pricing_timeout() -> u64 {
+    10_000
+}
+
+/// Whole-render limits.
+///
+/// Claude Code 每次刷新都等待状态栏输出。超过 `max_total_ms` 后生成器不再等待
+/// 仍在渲染的组件:有上一次输出的沿用旧结果,没有的本次隐藏;启用 `warm_start`
+/// 时,上一次输出也可以来自该会话之前的调用。
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct PerformanceConfig {
+    /// Budget of one invocation in milliseconds (0 = unlimited)
+    #[serde(default = "default_max_total_ms")]
+    pub max_total_ms: u64,
+
+    /// Persist each component's last output per session, shown in place of a
+    /// component that runs past the budget in a later invocation
+    #[serde(default = "default_true")]
+    pub warm_start: bool,
+
+    /// Oldest warm-start output still shown, in seconds
+    #[serde(default = "default_warm_start_max_age")]
+    pub warm_start_max_age_secs: u64,
+}
+
+impl Default for PerformanceConfig {
+    fn default() -> Self {
+        Self {
+            max_total_ms: default_max_total_ms(),
+            warm_start: true,
+            warm_start_max_age_secs: default_warm_start_max_age(),
+        }
+    }
+}
+
+const fn default_max_total_ms() -> u64 {
+    300
+}
+
+const fn default_warm_start_max_age() -> u64 {
+    600
+}
+
+/// Shell commands run when a session enters a state.
+///
+/// 每个命令在条件由假变真时触发一次,事件 JSON 写入其 stdin,不等待命令结束;
+/// 同一事件在 `debounce_seconds` 内不会重复触发。状态记录在会话快照中。
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct HooksConfig {
+    /// Minimum seconds between two runs of the same event
+    #[serde(default = "default_hooks_debounce")]
+    pub debounce_seconds: u64,
+
+    /// Session cost (USD) at which `cost_threshold` fires; 0 disables it
+    #[serde(default)]
+    pub cost_threshold_usd: f64,
+
+    /// First render of a session
+    #[serde(default)]
+    pub session_start: Option<String>,
+    /// Session cost reached `cost_threshold_usd`
+    #[serde(default)]
+    pub cost_threshold: Option<String>,
+    /// Context usage reached the tokens component's critical threshold
+    #[serde(default)]
+    pub context_critical: Option<String>,
+    /// The status component reports an error
+    #[serde(default)]
+    pub error_detected: Option<String>,
+    /// No transcript activity for `style.idle.threshold_minutes`
+    #[serde(default)]
+    pub session_idle: Option<String>,
+}
+
+impl HooksConfig {
+    /// Command configured for `event`
+    #[must_use]
+    pub fn command(&self, event: &str) -> Option<&str> {
+        match event {
+            "session_start" => self.session_start.as_deref(),
+            "cost_threshold" => self.cost_threshold.as_deref(),
+            "context_critical" => self.context_critical.as_deref(),
+            "error_detected" => self.error_detected.as_deref(),
+            "session_idle" => self.session_idle.as_deref(),
+            _ => None,
+        }
+        .filter(|command| !command.trim().is_empty())
+    }
+}
+
+impl Default for HooksConfig {
+    fn default() -> Self {
+        Self {
+            debounce_seconds: default_hooks_debounce(),
+            cost_threshold_usd: 0.0,
+            session_start: None,
+            cost_threshold: None,
+            context_critical: None,
+            error_detected: None,
+            session_idle: None,
+        }
+    }
+}
+
+const fn default_hooks_debounce() -> u64 {
+    300
+}
+
 /// Shared model/provider profile.
 #[derive(Debug, Clone, Deserialize, Serialize, Default)]
 pub struct ModelProviderConfig {
@@ -137,7 +401,8 @@ impl Default for ModelPricingConfig {
 }
 
 /// Terminal capabilities configuration
-#[derive(Debug, Clone, Deserialize, Serialize, Default)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[allow(clippy::struct_excessive_bools)]
 pub struct TerminalConfig {
     /// Force enable Nerd Font icons
     #[serde(default)]
@@ -150,10 +415,72 @@ pub struct TerminalConfig {
     /// Force enable text-only mode
     #[serde(default)]
     pub force_text: bool,
+
+    /// Strict ASCII output: text icons plus ASCII bars, separators and
+    /// symbols (implies `force_text`)
+    #[serde(default)]
+    pub ascii_only: bool,
+
+    /// Also write a plain-text summary to the window / tab title (OSC 2)
+    #[serde(default)]
+    pub set_title: bool,
+
+    /// Components summarized in the title, in order
+    #[serde(default = "default_title_components")]
+    pub title_components: Vec<String>,
+
+    /// Inline image protocol for `image_icon`: `auto`, `kitty`, `iterm2` or
+    /// `none`
+    #[serde(default = "default_graphics_protocol")]
+    pub graphics_protocol: String,
+
+    /// Report context usage through `OSC 9;4` progress sequences (taskbar /
+    /// tab progress in Windows Terminal, `ConEmu`, Ghostty); `auto` detects
+    #[serde(default = "default_progress_osc")]
+    pub progress_osc: AutoDetect,
+}
+
+impl Default for TerminalConfig {
+    fn default() -> Self {
+        Self {
+            force_nerd_font: false,
+            force_emoji: false,
+            force_text: false,
+            ascii_only: false,
+            set_title: false,
+            title_components: default_title_components(),
+            graphics_protocol: default_graphics_protocol(),
+            progress_osc: default_progress_osc(),
+        }
+    }
+}
+
+const fn default_progress_osc() -> AutoDetect {
+    AutoDetect::Bool(false)
+}
+
+fn default_graphics_protocol() -> String {
+    "auto".to_string()
+}
+
+fn default_title_components() -> Vec<String> {
+    ["project", "model", "tokens"]
+        .into_iter()
+        .map(String::from)
+        .collect()
+}
+
+impl TerminalConfig {
+    /// Whether icons fall back to their text variants
+    #[must_use]
+    pub const fn text_only(&self) -> bool {
+        self.force_text || self.ascii_only
+    }
 }
 
 /// Storage system configuration
 #[derive(Debug, Clone, Deserialize, Serialize)]
+#[allow(clippy::struct_excessive_bools)]
 pub struct StorageConfig {
     /// Enable conversation-level cost tracking
     #[serde(default = "default_true", rename = "enableConversationTracking")]
@@ -174,6 +501,27 @@ pub struct StorageConfig {
     /// Enable cleanup on startup
     #[serde(default = "default_true", rename = "enableStartupCleanup")]
     pub enable_startup_cleanup: bool,
+
+    /// Keys stripped from the stored `latest` payload.
+    /// 含 `.` 的按根路径匹配(如 `cost.input_tokens`),否则匹配任意层级的同名键
+    #[serde(default = "default_redact_keys", rename = "redactKeys")]
+    pub redact_keys: Vec<String>,
+
+    /// Store the sanitized stdin payload as `latest` in session snapshots
+    #[serde(default = "default_true", rename = "storeLatest")]
+    pub store_latest: bool,
+
+    /// Default data source of `ccsp usage`: `snapshots`, `metrics` or `transcripts`
+    #[serde(default = "default_usage_source", rename = "usageSource")]
+    pub usage_source: String,
+
+    /// Append every rendered statusline to a per-session history log
+    #[serde(default, rename = "historyLog")]
+    pub history_log: bool,
+
+    /// Entries kept per session history log
+    #[serde(default = "default_history_max_entries", rename = "historyMaxEntries")]
+    pub history_max_entries: usize,
 }
 
 impl Default for StorageConfig {
@@ -183,12 +531,18 @@ impl Default for StorageConfig {
             enable_cost_persistence: true,
             session_expiry_days: default_expiry(),
             enable_startup_cleanup: true,
+            redact_keys: default_redact_keys(),
+            store_latest: true,
+            usage_source: default_usage_source(),
+            history_log: false,
+            history_max_entries: default_history_max_entries(),
         }
     }
 }
 
 /// Style configuration
 #[derive(Debug, Clone, Deserialize, Serialize)]
+#[allow(clippy::struct_excessive_bools)]
 pub struct StyleConfig {
     /// Component separator
     #[serde(default = "default_separator")]
@@ -217,6 +571,218 @@ pub struct StyleConfig {
     /// Space after separator
     #[serde(default = "default_space")]
     pub separator_after: String,
+
+    /// Whole-line styling applied while a component reports a severe state
+    #[serde(default)]
+    pub severity_rules: Vec<SeverityRuleConfig>,
+
+    /// Styling of sessions whose transcript has not changed for a while
+    #[serde(default)]
+    pub idle: IdleConfig,
+
+    /// Wrap segments with right-to-left text in bidi isolates and drop
+    /// directional control characters
+    #[serde(default = "default_true")]
+    pub bidi_isolation: bool,
+
+    /// Pad segments ending in a double-width character before powerline /
+    /// capsule separators
+    #[serde(default)]
+    pub wide_char_padding: bool,
+
+    /// Lighten or darken the powerline / capsule foreground when it is
+    /// unreadable on a segment background; off by default so configured
+    /// colors render as written
+    #[serde(default)]
+    pub auto_contrast: bool,
+
+    /// Contrast ratio (1–21) below which a foreground counts as unreadable
+    #[serde(default = "default_min_contrast")]
+    pub min_contrast: f64,
+
+    /// Powerline / capsule backgrounds interpolated across the whole line
+    #[serde(default)]
+    pub background_gradient: BackgroundGradientConfig,
+
+    /// Pad percentages, token counts and costs to fixed widths so the line
+    /// does not shift as digits change
+    #[serde(default)]
+    pub fixed_width_numbers: bool,
+
+    /// Box-drawing border around the statusline or each of its rows
+    #[serde(default)]
+    pub frame: FrameConfig,
+
+    /// Cut every output line to this many columns (0 = unlimited)
+    #[serde(default)]
+    pub max_output_width: usize,
+
+    /// Cut every output line to this many bytes, escape sequences included
+    /// (0 = unlimited)
+    #[serde(default)]
+    pub max_output_bytes: usize,
+}
+
+/// Border drawn around the rendered statusline
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct FrameConfig {
+    /// `none`, `line` (one box around all rows) or `rows` (a box per row)
+    #[serde(default = "default_frame_mode")]
+    pub mode: String,
+
+    /// `rounded`, `square`, `double` or `heavy`
+    #[serde(default = "default_frame_border")]
+    pub border: String,
+
+    /// Border color
+    #[serde(default = "default_frame_color")]
+    pub color: String,
+
+    /// Border color per row in `rows` mode; rows past the end use `color`
+    #[serde(default)]
+    pub row_colors: Vec<String>,
+}
+
+impl Default for FrameConfig {
+    fn default() -> Self {
+        Self {
+            mode: default_frame_mode(),
+            border: default_frame_border(),
+            color: default_frame_color(),
+            row_colors: Vec::new(),
+        }
+    }
+}
+
+fn default_frame_mode() -> String {
+    "none".to_string()
+}
+
+fn default_frame_border() -> String {
+    "rounded".to_string()
+}
+
+fn default_frame_color() -> String {
+    "gray".to_string()
+}
+
+/// Cross-segment background gradient
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct BackgroundGradientConfig {
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// Background at the left edge of the line
+    #[serde(default = "default_gradient_start")]
+    pub start: String,
+
+    /// Background at the right edge of the line
+    #[serde(default = "default_gradient_end")]
+    pub end: String,
+}
+
+impl Default for BackgroundGradientConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            start: default_gradient_start(),
+            end: default_gradient_end(),
+        }
+    }
+}
+
+fn default_gradient_start() -> String {
+    "#5e81ac".to_string()
+}
+
+fn default_gradient_end() -> String {
+    "#b48ead".to_string()
+}
+
+/// Idle session detection
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct IdleConfig {
+    /// Minutes without transcript writes before the session counts as idle
+    /// (0 = off)
+    #[serde(default)]
+    pub threshold_minutes: u64,
+
+    /// Dim the whole statusline while idle
+    #[serde(default = "default_true")]
+    pub dim: bool,
+
+    /// Show an idle segment (`⏸ idle 12m`) at the start of the line
+    #[serde(default)]
+    pub show_segment: bool,
+
+    #[serde(default = "default_idle_icon")]
+    pub icon: String,
+
+    #[serde(default = "default_idle_text")]
+    pub text: String,
+
+    /// Color of the idle segment
+    #[serde(default = "default_idle_color")]
+    pub color: String,
+}
+
+impl Default for IdleConfig {
+    fn default() -> Self {
+        Self {
+            threshold_minutes: 0,
+            dim: true,
+            show_segment: false,
+            icon: default_idle_icon(),
+            text: default_idle_text(),
+            color: default_idle_color(),
+        }
+    }
+}
+
+fn default_idle_icon() -> String {
+    "⏸".to_string()
+}
+
+fn default_idle_text() -> String {
+    "idle".to_string()
+}
+
+fn default_idle_color() -> String {
+    "gray".to_string()
+}
+
+/// How alarming a component's current state is
+#[derive(
+    Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq, PartialOrd, Ord, Hash, Default,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    #[default]
+    Normal,
+    Warning,
+    Critical,
+}
+
+/// Line styling triggered by the most severe component state
+///
+/// 多条规则同时满足时取 `level` 最高的一条,同级取先声明的。
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SeverityRuleConfig {
+    /// Minimum severity that triggers the rule
+    pub level: Severity,
+
+    /// Components whose state counts (empty = all)
+    #[serde(default)]
+    pub components: Vec<String>,
+
+    /// Separator color while the rule applies
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub separator_color: Option<String>,
+
+    /// Color blended into segment backgrounds (powerline / capsule) or used
+    /// as the line background (classic)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tint: Option<String>,
 }
 
 impl Default for StyleConfig {
@@ -229,10 +795,25 @@ impl Default for StyleConfig {
             separator_color: default_white(),
             separator_before: default_space(),
             separator_after: default_space(),
+            severity_rules: Vec::new(),
+            idle: IdleConfig::default(),
+            bidi_isolation: true,
+            wide_char_padding: false,
+            auto_contrast: false,
+            min_contrast: default_min_contrast(),
+            background_gradient: BackgroundGradientConfig::default(),
+            fixed_width_numbers: false,
+            frame: FrameConfig::default(),
+            max_output_width: 0,
+            max_output_bytes: 0,
         }
     }
 }
 
+const fn default_min_contrast() -> f64 {
+    1.5
+}
+
 /// Auto-detection option
 #[derive(Debug, Clone, Deserialize, Serialize)]
 #[serde(untagged)]
@@ -259,57 +840,174 @@ impl AutoDetect {
 }
 
 /// All component configurations
-#[derive(Debug, Clone, Deserialize, Serialize, Default)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct ComponentsConfig {
-    /// Component display order (e.g., `["project", "model", "branch", "tokens"]`)
+    /// Component display order (e.g., `["project", "model", "branch", "tokens"]`).
+    /// `"sep:│"` and `"space"` items replace the theme separator at that position.
     #[serde(default)]
     pub order: Vec<String>,
 
+    /// Components drawn together in one powerline segment / capsule when
+    /// adjacent in the order (e.g., `[["tokens", "usage"]]`)
     #[serde(default)]
-    pub project: ProjectComponentConfig,
+    pub groups: Vec<Vec<String>>,
+
+    /// Per-component render timeout in milliseconds; a component that misses
+    /// it is left out of this refresh (0 = no timeout)
+    #[serde(default = "default_component_render_timeout")]
+    pub render_timeout_ms: u64,
 
     #[serde(default)]
-    pub model: ModelComponentConfig,
+    pub project: LazySection<ProjectComponentConfig>,
 
     #[serde(default)]
-    pub branch: BranchComponentConfig,
+    pub model: LazySection<ModelComponentConfig>,
 
     #[serde(default)]
-    pub tokens: TokensComponentConfig,
+    pub branch: LazySection<BranchComponentConfig>,
 
     #[serde(default)]
-    pub usage: UsageComponentConfig,
+    pub tokens: LazySection<TokensComponentConfig>,
 
     #[serde(default)]
-    pub rate_limit: RateLimitComponentConfig,
+    pub usage: LazySection<UsageComponentConfig>,
 
     #[serde(default)]
-    pub status: StatusComponentConfig,
-}
+    pub rate_limit: LazySection<RateLimitComponentConfig>,
 
-/// Base component configuration
-#[derive(Debug, Clone, Deserialize, Serialize)]
-pub struct BaseComponentConfig {
-    /// Whether to enable this component
-    #[serde(default = "default_true")]
-    pub enabled: bool,
+    #[serde(default)]
+    pub status: LazySection<StatusComponentConfig>,
 
-    /// Icon color
-    #[serde(default = "default_white")]
-    pub icon_color: String,
+    #[serde(default)]
+    pub hook: LazySection<HookComponentConfig>,
 
-    /// Text color
-    #[serde(default = "default_white")]
-    pub text_color: String,
+    #[serde(default)]
+    pub field: LazySection<FieldComponentConfig>,
 
-    /// Emoji icon
-    pub emoji_icon: String,
+    #[serde(default)]
+    pub text: LazySection<TextComponentConfig>,
 
-    /// Nerd Font icon
+    #[serde(default)]
+    pub trust: LazySection<TrustComponentConfig>,
+
+    #[serde(default)]
+    pub quota: LazySection<QuotaComponentConfig>,
+
+    #[serde(default)]
+    pub session: LazySection<SessionComponentConfig>,
+
+    #[serde(default)]
+    pub ticket: LazySection<TicketComponentConfig>,
+
+    #[serde(default)]
+    pub repo: LazySection<RepoComponentConfig>,
+}
+
+impl ComponentsConfig {
+    /// Component tables that fail to deserialize, by component name.
+    ///
+    /// 组件表按需反序列化,无效的表在渲染时只会回退到默认值;`validate` 用它
+    /// 提前报告。
+    #[must_use]
+    pub fn section_errors(&self) -> Vec<(&'static str, serde_json::Error)> {
+        [
+            ("project", self.project.check()),
+            ("model", self.model.check()),
+            ("branch", self.branch.check()),
+            ("tokens", self.tokens.check()),
+            ("usage", self.usage.check()),
+            ("rate_limit", self.rate_limit.check()),
+            ("status", self.status.check()),
+            ("hook", self.hook.check()),
+            ("field", self.field.check()),
+            ("text", self.text.check()),
+            ("trust", self.trust.check()),
+            ("quota", self.quota.check()),
+            ("session", self.session.check()),
+            ("ticket", self.ticket.check()),
+            ("repo", self.repo.check()),
+        ]
+        .into_iter()
+        .filter_map(|(name, result)| result.err().map(|err| (name, err)))
+        .collect()
+    }
+}
+
+impl Default for ComponentsConfig {
+    fn default() -> Self {
+        Self {
+            order: Vec::new(),
+            groups: Vec::new(),
+            render_timeout_ms: default_component_render_timeout(),
+            project: LazySection::default(),
+            model: LazySection::default(),
+            branch: LazySection::default(),
+            tokens: LazySection::default(),
+            usage: LazySection::default(),
+            rate_limit: LazySection::default(),
+            status: LazySection::default(),
+            hook: LazySection::default(),
+            field: LazySection::default(),
+            text: LazySection::default(),
+            trust: LazySection::default(),
+            quota: LazySection::default(),
+            session: LazySection::default(),
+            ticket: LazySection::default(),
+            repo: LazySection::default(),
+        }
+    }
+}
+
+/// Base component configuration
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct BaseComponentConfig {
+    /// Whether to enable this component
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+
+    /// Icon color
+    #[serde(default = "default_white")]
+    pub icon_color: String,
+
+    /// Text color
+    #[serde(default = "default_white")]
+    pub text_color: String,
+
+    /// Emoji icon
+    pub emoji_icon: String,
+
+    /// Nerd Font icon
     pub nerd_icon: String,
 
     /// Text icon
     pub text_icon: String,
+
+    /// Render timeout in milliseconds, overriding `components.render_timeout_ms`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_ms: Option<u64>,
+
+    /// Minimum display width of the text, padded per `align` (0 = off)
+    #[serde(default)]
+    pub min_width: usize,
+
+    /// Placement of the text inside `min_width`
+    #[serde(default)]
+    pub align: SegmentAlign,
+
+    /// PNG shown instead of the glyph icon on terminals with an inline image
+    /// protocol (kitty, iTerm2, `WezTerm`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub image_icon: Option<String>,
+}
+
+/// Alignment of a component's text inside its minimum width
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum SegmentAlign {
+    #[default]
+    Left,
+    Center,
+    Right,
 }
 
 /// Project component configuration
@@ -321,6 +1019,55 @@ pub struct ProjectComponentConfig {
     /// Show when project name is empty
     #[serde(default)]
     pub show_when_empty: bool,
+
+    /// Append the current package dir when working below the project root
+    /// (`repo/packages/web`), for monorepos
+    #[serde(default)]
+    pub show_relative_dir: bool,
+
+    /// Number of path segments below the project root to show
+    #[serde(default = "default_relative_depth")]
+    pub relative_depth: usize,
+
+    /// Maximum display width of the project text (0 = unlimited); longer
+    /// names keep their tail (`…es/web`)
+    #[serde(default)]
+    pub max_length: usize,
+
+    /// Name source: `directory` (folder name), `manifest` (package name
+    /// from `Cargo.toml` / `package.json` / `pyproject.toml`) or `remote`
+    /// (repository name of the git remote); the latter two fall back to the
+    /// folder name
+    #[serde(default = "default_project_source")]
+    pub source: String,
+
+    /// Append the manifest version (`ccsp v4.1.1`) in `manifest` mode
+    #[serde(default)]
+    pub show_version: bool,
+
+    /// Custom labels keyed by project path (`~` allowed), git remote
+    /// (`github.com/org/repo`, globs like `github.com/org/*`, or the remote
+    /// URL) or project hash (`-home-user-backend`), tried in that order
+    /// before the directory name
+    #[serde(default)]
+    pub mapping: HashMap<String, ProjectMappingConfig>,
+}
+
+/// Custom project label: `"🚀 backend"` or `{ label = "backend", icon = "🚀" }`
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq, Hash)]
+#[serde(untagged)]
+pub enum ProjectMappingConfig {
+    Label(String),
+    Detailed {
+        #[serde(default)]
+        label: Option<String>,
+        /// Replaces the component icon for this project
+        #[serde(default)]
+        icon: Option<String>,
+        /// Replaces the component text color for this project
+        #[serde(default)]
+        color: Option<String>,
+    },
 }
 
 impl Default for ProjectComponentConfig {
@@ -333,8 +1080,18 @@ impl Default for ProjectComponentConfig {
                 emoji_icon: "📁".to_string(),
                 nerd_icon: "\u{f07c}".to_string(),
                 text_icon: "[P]".to_string(),
+                timeout_ms: None,
+                min_width: 0,
+                align: SegmentAlign::Left,
+                image_icon: None,
             },
             show_when_empty: false,
+            show_relative_dir: false,
+            relative_depth: default_relative_depth(),
+            max_length: 0,
+            source: default_project_source(),
+            show_version: false,
+            mapping: HashMap::new(),
         }
     }
 }
@@ -349,6 +1106,14 @@ pub struct ModelComponentConfig {
     #[serde(default)]
     pub show_full_name: bool,
 
+    /// Append the context window size (`·1M`, `·200K`) to the model name
+    #[serde(default)]
+    pub show_context_window: bool,
+
+    /// Renders to show `↻ Opus→Sonnet` after the model changes mid-session (0 = off)
+    #[serde(default)]
+    pub switch_notice_renders: u32,
+
     /// Custom model short name mapping
     #[serde(default)]
     pub mapping: HashMap<String, String>,
@@ -356,6 +1121,16 @@ pub struct ModelComponentConfig {
     /// Custom model long name mapping
     #[serde(default)]
     pub long_name_mapping: HashMap<String, String>,
+
+    /// Text and icon color per model series (`opus = "magenta"`), matched
+    /// case-insensitively against the model id
+    #[serde(default)]
+    pub series_colors: HashMap<String, String>,
+
+    /// Icon per provider or series (`claude = "✳"`), replacing the component
+    /// icon on emoji / Nerd Font terminals
+    #[serde(default)]
+    pub provider_icons: HashMap<String, String>,
 }
 
 impl Default for ModelComponentConfig {
@@ -368,10 +1143,18 @@ impl Default for ModelComponentConfig {
                 emoji_icon: "🤖".to_string(),
                 nerd_icon: "\u{f09d1}".to_string(),
                 text_icon: "[M]".to_string(),
+                timeout_ms: None,
+                min_width: 0,
+                align: SegmentAlign::Left,
+                image_icon: None,
             },
             show_full_name: false,
+            show_context_window: false,
+            switch_notice_renders: 0,
             mapping: HashMap::new(),
             long_name_mapping: HashMap::new(),
+            series_colors: HashMap::new(),
+            provider_icons: HashMap::new(),
         }
     }
 }
@@ -406,9 +1189,19 @@ pub struct BranchComponentConfig {
     #[serde(default)]
     pub status_colors: BranchStatusColors,
 
+    /// Branch name rules by prefix (`feature/`, `hotfix/`); the first
+    /// matching rule applies
+    #[serde(default)]
+    pub prefix_rules: Vec<BranchPrefixRule>,
+
     /// Performance tuning options
     #[serde(default)]
     pub performance: BranchPerformanceConfig,
+
+    /// Non-git backends detected per repository (`jj`, `hg`, `sl`); the
+    /// working copy root nearest to the current directory wins
+    #[serde(default = "default_branch_vcs")]
+    pub vcs: Vec<String>,
 }
 
 impl Default for BranchComponentConfig {
@@ -421,6 +1214,10 @@ impl Default for BranchComponentConfig {
                 emoji_icon: "🌿".to_string(),
                 nerd_icon: "\u{e0a0}".to_string(),
                 text_icon: "[B]".to_string(),
+                timeout_ms: None,
+                min_width: 0,
+                align: SegmentAlign::Left,
+                image_icon: None,
             },
             show_when_empty: false,
             show_when_no_git: false,
@@ -428,7 +1225,9 @@ impl Default for BranchComponentConfig {
             status: BranchStatusConfig::default(),
             status_icons: BranchStatusIcons::default(),
             status_colors: BranchStatusColors::default(),
+            prefix_rules: Vec::new(),
             performance: BranchPerformanceConfig::default(),
+            vcs: default_branch_vcs(),
         }
     }
 }
@@ -486,6 +1285,16 @@ pub struct BranchStatusConfig {
     /// Show stash count
     #[serde(default)]
     pub show_stash_count: bool,
+
+    /// Mark ahead/behind as stale when the last fetch is older than this
+    /// many minutes (0 = off)
+    #[serde(default)]
+    pub fetch_stale_minutes: u64,
+
+    /// Run `git fetch --quiet` in the background at most once per this many
+    /// minutes (0 = off)
+    #[serde(default)]
+    pub auto_fetch_minutes: u64,
 }
 
 /// Branch status icons
@@ -503,6 +1312,18 @@ pub struct BranchStatusIcons {
     pub ahead_text: String,
     pub behind_text: String,
     pub stash_text: String,
+    #[serde(default = "default_branch_stale_emoji")]
+    pub stale_emoji: String,
+    #[serde(default = "default_branch_stale_nerd")]
+    pub stale_nerd: String,
+    #[serde(default = "default_branch_stale_text")]
+    pub stale_text: String,
+    #[serde(default = "default_branch_sparse_emoji")]
+    pub sparse_emoji: String,
+    #[serde(default = "default_branch_sparse_nerd")]
+    pub sparse_nerd: String,
+    #[serde(default = "default_branch_sparse_text")]
+    pub sparse_text: String,
 }
 
 impl Default for BranchStatusIcons {
@@ -520,10 +1341,44 @@ impl Default for BranchStatusIcons {
             ahead_text: "[↑]".to_string(),
             behind_text: "[↓]".to_string(),
             stash_text: "[S]".to_string(),
+            stale_emoji: default_branch_stale_emoji(),
+            stale_nerd: default_branch_stale_nerd(),
+            stale_text: default_branch_stale_text(),
+            sparse_emoji: default_branch_sparse_emoji(),
+            sparse_nerd: default_branch_sparse_nerd(),
+            sparse_text: default_branch_sparse_text(),
         }
     }
 }
 
+fn default_branch_vcs() -> Vec<String> {
+    vec!["jj".to_string(), "hg".to_string(), "sl".to_string()]
+}
+
+fn default_branch_stale_emoji() -> String {
+    "⏳".to_string()
+}
+
+fn default_branch_stale_nerd() -> String {
+    "\u{f017}".to_string()
+}
+
+fn default_branch_stale_text() -> String {
+    "[~]".to_string()
+}
+
+fn default_branch_sparse_emoji() -> String {
+    "🧩".to_string()
+}
+
+fn default_branch_sparse_nerd() -> String {
+    "\u{f0c4}".to_string()
+}
+
+fn default_branch_sparse_text() -> String {
+    "[sp]".to_string()
+}
+
 /// Branch status colors
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct BranchStatusColors {
@@ -540,8 +1395,8 @@ pub struct BranchStatusColors {
 impl Default for BranchStatusColors {
     fn default() -> Self {
         Self {
-            clean: "green".to_string(),
-            dirty: "yellow".to_string(),
+            clean: "ok".to_string(),
+            dirty: "warn".to_string(),
             ahead: default_branch_ahead_color(),
             behind: default_branch_behind_color(),
             operation: default_branch_operation_color(),
@@ -549,6 +1404,34 @@ impl Default for BranchStatusColors {
     }
 }
 
+/// Branch name rule: `{ prefix = "feature/", color = "green", strip = true }`
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
+pub struct BranchPrefixRule {
+    /// Branch name prefix; a trailing `*` is ignored (`feature/*`)
+    pub prefix: String,
+
+    /// Text color of matching branch names
+    #[serde(default)]
+    pub color: Option<String>,
+
+    /// Remove the prefix from the shown name
+    /// (`feature/ABC-123-x` → `ABC-123-x`)
+    #[serde(default)]
+    pub strip: bool,
+}
+
+impl BranchPrefixRule {
+    /// `branch` without the rule's prefix, or `None` when it does not match
+    #[must_use]
+    pub fn strip_from<'a>(&self, branch: &'a str) -> Option<&'a str> {
+        let prefix = self.prefix.trim().trim_end_matches('*');
+        if prefix.is_empty() {
+            return None;
+        }
+        branch.strip_prefix(prefix)
+    }
+}
+
 /// Tokens component configuration
 #[derive(Debug, Clone, Deserialize, Serialize)]
 #[allow(clippy::struct_excessive_bools)]
@@ -560,261 +1443,765 @@ pub struct TokensComponentConfig {
     #[serde(default)]
     pub show_zero: bool,
 
-    /// Number formatting
+    /// Text template, e.g. `"{percent}% of {window}"`
+    ///
+    /// Placeholders: `{bar}` `{percent}` `{used}` `{window}` `{remaining}` `{status}`.
+    /// Values without placeholders (the legacy `"compact"`) keep the default layout.
     #[serde(default = "default_compact")]
     pub format: String,
 
-    #[serde(default)]
-    pub show_progress_bar: bool,
+    /// Unit of token counts: `k`, `m`, `raw` or `auto`
+    #[serde(default = "default_token_unit")]
+    pub unit: String,
+
+    #[serde(default)]
+    pub show_progress_bar: bool,
+
+    #[serde(default)]
+    pub show_percentage: bool,
+
+    /// Show the `(used/window)` part; `false` gives a percentage-only display
+    #[serde(default = "default_true")]
+    pub show_numbers: bool,
+
+    /// Legacy switch, same as `unit = "raw"`
+    #[serde(default)]
+    pub show_raw_numbers: bool,
+
+    #[serde(default = "default_progress_width")]
+    pub progress_width: u32,
+
+    /// Progress bar glyphs: `blocks` (uses `progress_bar_chars`), `braille`,
+    /// `dots` or `line`; text-only terminals always fall back to `#`/`-`
+    #[serde(default = "default_progress_style")]
+    pub progress_style: String,
+
+    #[serde(default)]
+    pub show_gradient: bool,
+
+    /// Emphasis of the whole segment at the critical threshold: `none`,
+    /// `reverse` (reverse video) or `bold` (bold in the danger color)
+    #[serde(default = "default_critical_emphasis")]
+    pub critical_emphasis: String,
+
+    /// Ring the terminal bell once per session when usage turns critical
+    #[serde(default)]
+    pub critical_bell: bool,
+
+    #[serde(default)]
+    pub progress_bar_chars: TokensProgressBarCharsConfig,
+
+    #[serde(default)]
+    pub colors: TokensColorConfig,
+
+    #[serde(default)]
+    pub thresholds: TokensThresholdsConfig,
+
+    #[serde(default)]
+    pub status_icons: TokensStatusIconsConfig,
+
+    #[serde(default)]
+    pub context_windows: HashMap<String, u64>,
+}
+
+impl Default for TokensComponentConfig {
+    fn default() -> Self {
+        Self {
+            base: BaseComponentConfig {
+                enabled: true,
+                icon_color: "cyan".to_string(),
+                text_color: "white".to_string(),
+                emoji_icon: "📊".to_string(),
+                nerd_icon: "\u{f201}".to_string(),
+                text_icon: "[T]".to_string(),
+                timeout_ms: None,
+                min_width: 0,
+                align: SegmentAlign::Left,
+                image_icon: None,
+            },
+            show_zero: false,
+            format: default_compact(),
+            unit: default_token_unit(),
+            show_progress_bar: true,
+            show_percentage: true,
+            show_numbers: true,
+            show_raw_numbers: false,
+            progress_width: default_progress_width(),
+            progress_style: default_progress_style(),
+            show_gradient: false,
+            critical_emphasis: default_critical_emphasis(),
+            critical_bell: false,
+            progress_bar_chars: TokensProgressBarCharsConfig::default(),
+            colors: TokensColorConfig::default(),
+            thresholds: TokensThresholdsConfig::default(),
+            status_icons: TokensStatusIconsConfig::default(),
+            context_windows: default_context_windows(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct TokensProgressBarCharsConfig {
+    #[serde(default = "default_filled_char")]
+    pub filled: String,
+    #[serde(default = "default_empty_char")]
+    pub empty: String,
+    #[serde(default = "default_backup_char")]
+    pub backup: String,
+    #[serde(default = "default_left_bracket")]
+    pub left_bracket: String,
+    #[serde(default = "default_right_bracket")]
+    pub right_bracket: String,
+}
+
+impl Default for TokensProgressBarCharsConfig {
+    fn default() -> Self {
+        Self {
+            filled: default_filled_char(),
+            empty: default_empty_char(),
+            backup: default_backup_char(),
+            left_bracket: default_left_bracket(),
+            right_bracket: default_right_bracket(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct TokensColorConfig {
+    #[serde(default = "default_safe_color")]
+    pub safe: String,
+    #[serde(default = "default_warning_color")]
+    pub warning: String,
+    #[serde(default = "default_danger_color")]
+    pub danger: String,
+}
+
+impl Default for TokensColorConfig {
+    fn default() -> Self {
+        Self {
+            safe: default_safe_color(),
+            warning: default_warning_color(),
+            danger: default_danger_color(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct TokensThresholdsConfig {
+    #[serde(default = "default_warning_threshold")]
+    pub warning: f64,
+    #[serde(default = "default_danger_threshold")]
+    pub danger: f64,
+    #[serde(default = "default_backup_threshold")]
+    pub backup: f64,
+    #[serde(default = "default_critical_threshold")]
+    pub critical: f64,
+}
+
+impl Default for TokensThresholdsConfig {
+    fn default() -> Self {
+        Self {
+            warning: default_warning_threshold(),
+            danger: default_danger_threshold(),
+            backup: default_backup_threshold(),
+            critical: default_critical_threshold(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, Default)]
+pub struct TokenIconSetConfig {
+    #[serde(default)]
+    pub backup: String,
+    #[serde(default)]
+    pub critical: String,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct TokensStatusIconsConfig {
+    #[serde(default = "default_emoji_icon_set")]
+    pub emoji: TokenIconSetConfig,
+    #[serde(default = "default_nerd_icon_set")]
+    pub nerd: TokenIconSetConfig,
+    #[serde(default = "default_text_icon_set")]
+    pub text: TokenIconSetConfig,
+}
+
+impl Default for TokensStatusIconsConfig {
+    fn default() -> Self {
+        Self {
+            emoji: default_emoji_icon_set(),
+            nerd: default_nerd_icon_set(),
+            text: default_text_icon_set(),
+        }
+    }
+}
+
+/// Usage component configuration
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct UsageComponentConfig {
+    #[serde(flatten)]
+    pub base: BaseComponentConfig,
+
+    /// Display mode
+    #[serde(default = "default_smart")]
+    pub display_mode: String,
+
+    /// Precision for cost display
+    #[serde(default = "default_precision")]
+    pub precision: u32,
+
+    /// Rounding to `precision`: `nearest`, `ceil` or `floor`
+    #[serde(default = "default_rounding")]
+    pub rounding: String,
+
+    /// Abbreviate large amounts (`$1.2k`)
+    #[serde(default)]
+    pub compact: bool,
+
+    /// Currency display mode or fixed currency code
+    #[serde(default = "default_auto_string")]
+    pub currency: String,
+
+    /// User-provided endpoint to currency mappings
+    #[serde(default)]
+    pub currency_endpoint_rules: HashMap<String, String>,
+
+    /// User-provided model name to currency mappings
+    #[serde(default)]
+    pub currency_model_rules: HashMap<String, String>,
+
+    /// Exchange rates as units per 1 USD (e.g. `EUR = 0.92`), used when
+    /// `currency` is fixed and differs from the cost's source currency
+    #[serde(default)]
+    pub exchange_rates: HashMap<String, f64>,
+
+    /// Number formatting locale (e.g. `de-DE`); empty keeps plain `1234.56`
+    #[serde(default)]
+    pub locale: String,
+
+    /// Show lines added
+    #[serde(default)]
+    pub show_lines_added: bool,
+
+    /// Show lines removed
+    #[serde(default)]
+    pub show_lines_removed: bool,
+
+    /// Session budget in USD; reaching it reports a critical state, 80% of it
+    /// a warning (0 = no budget)
+    #[serde(default)]
+    pub budget: f64,
+
+    /// Flag sessions whose cost burn rate (USD/hour) is this many standard
+    /// deviations above the stored sessions' norm (0 = off)
+    #[serde(default = "default_anomaly_std_devs")]
+    pub anomaly_std_devs: f64,
+
+    /// Stored sessions needed before anomalies are flagged
+    #[serde(default = "default_anomaly_min_sessions")]
+    pub anomaly_min_sessions: usize,
+
+    /// Marker appended to an anomalous cost
+    #[serde(default = "default_anomaly_icon")]
+    pub anomaly_icon: String,
+}
+
+impl Default for UsageComponentConfig {
+    fn default() -> Self {
+        Self {
+            base: BaseComponentConfig {
+                enabled: true,
+                icon_color: "yellow".to_string(),
+                text_color: "white".to_string(),
+                emoji_icon: "💰".to_string(),
+                nerd_icon: "\u{f155}".to_string(),
+                text_icon: "[U]".to_string(),
+                timeout_ms: None,
+                min_width: 0,
+                align: SegmentAlign::Left,
+                image_icon: None,
+            },
+            display_mode: default_smart(),
+            precision: default_precision(),
+            rounding: default_rounding(),
+            compact: false,
+            currency: default_auto_string(),
+            currency_endpoint_rules: HashMap::new(),
+            currency_model_rules: HashMap::new(),
+            exchange_rates: HashMap::new(),
+            locale: String::new(),
+            show_lines_added: false,
+            show_lines_removed: false,
+            budget: 0.0,
+            anomaly_std_devs: default_anomaly_std_devs(),
+            anomaly_min_sessions: default_anomaly_min_sessions(),
+            anomaly_icon: default_anomaly_icon(),
+        }
+    }
+}
+
+const fn default_anomaly_std_devs() -> f64 {
+    3.0
+}
+
+const fn default_anomaly_min_sessions() -> usize {
+    5
+}
+
+fn default_anomaly_icon() -> String {
+    "⚠".to_string()
+}
+
+/// Rate limit component configuration
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct RateLimitComponentConfig {
+    #[serde(flatten)]
+    pub base: BaseComponentConfig,
+
+    /// Show the 5-hour rolling window
+    #[serde(default = "default_true")]
+    pub show_five_hour: bool,
+
+    /// Show the 7-day weekly window
+    #[serde(default = "default_true")]
+    pub show_seven_day: bool,
+
+    /// Show reset countdowns when `resets_at` is available
+    #[serde(default = "default_true")]
+    pub show_reset: bool,
+}
+
+impl Default for RateLimitComponentConfig {
+    fn default() -> Self {
+        Self {
+            base: BaseComponentConfig {
+                enabled: true,
+                icon_color: "magenta".to_string(),
+                text_color: "white".to_string(),
+                emoji_icon: "⏱️".to_string(),
+                nerd_icon: "\u{f017}".to_string(),
+                text_icon: "[R]".to_string(),
+                timeout_ms: None,
+                min_width: 0,
+                align: SegmentAlign::Left,
+                image_icon: None,
+            },
+            show_five_hour: true,
+            show_seven_day: true,
+            show_reset: true,
+        }
+    }
+}
+
+/// Hook event component configuration
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct HookComponentConfig {
+    #[serde(flatten)]
+    pub base: BaseComponentConfig,
+
+    /// Events never displayed (case-insensitive). `Status` is the plain
+    /// statusline refresh, so it is hidden by default.
+    #[serde(default = "default_hook_hidden_events")]
+    pub hidden_events: Vec<String>,
+
+    /// Text color per event name, falling back to `text_color`
+    #[serde(default)]
+    pub event_colors: HashMap<String, String>,
+}
+
+impl Default for HookComponentConfig {
+    fn default() -> Self {
+        Self {
+            base: BaseComponentConfig {
+                enabled: true,
+                icon_color: "cyan".to_string(),
+                text_color: "white".to_string(),
+                emoji_icon: "🪝".to_string(),
+                nerd_icon: "\u{f0c1}".to_string(),
+                text_icon: "[H]".to_string(),
+                timeout_ms: None,
+                min_width: 0,
+                align: SegmentAlign::Left,
+                image_icon: None,
+            },
+            hidden_events: default_hook_hidden_events(),
+            event_colors: HashMap::new(),
+        }
+    }
+}
+
+/// Generic stdin field component configuration
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct FieldComponentConfig {
+    #[serde(flatten)]
+    pub base: BaseComponentConfig,
+
+    /// JSON pointer into the raw input, e.g. `/output_style/name`
+    #[serde(default)]
+    pub pointer: String,
+
+    /// Label shown before the value
+    #[serde(default)]
+    pub label: String,
+
+    /// Display template with `{label}` and `{value}` placeholders
+    #[serde(default = "default_field_format")]
+    pub format: String,
+}
+
+impl Default for FieldComponentConfig {
+    fn default() -> Self {
+        Self {
+            base: BaseComponentConfig {
+                enabled: true,
+                icon_color: "bright_blue".to_string(),
+                text_color: "white".to_string(),
+                emoji_icon: "🏷️".to_string(),
+                nerd_icon: "\u{f02b}".to_string(),
+                text_icon: "[F]".to_string(),
+                timeout_ms: None,
+                min_width: 0,
+                align: SegmentAlign::Left,
+                image_icon: None,
+            },
+            pointer: String::new(),
+            label: String::new(),
+            format: default_field_format(),
+        }
+    }
+}
+
+/// Static text component configuration
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct TextComponentConfig {
+    #[serde(flatten)]
+    pub base: BaseComponentConfig,
+
+    /// Fixed text to display; the component is hidden while empty
+    #[serde(default)]
+    pub content: String,
+}
+
+impl Default for TextComponentConfig {
+    fn default() -> Self {
+        Self {
+            base: BaseComponentConfig {
+                enabled: true,
+                icon_color: "white".to_string(),
+                text_color: "white".to_string(),
+                emoji_icon: String::new(),
+                nerd_icon: String::new(),
+                text_icon: String::new(),
+                timeout_ms: None,
+                min_width: 0,
+                align: SegmentAlign::Left,
+                image_icon: None,
+            },
+            content: String::new(),
+        }
+    }
+}
+
+/// Workspace trust component configuration
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct TrustComponentConfig {
+    #[serde(flatten)]
+    pub base: BaseComponentConfig,
+
+    /// Keep showing the component in trusted, local directories
+    #[serde(default)]
+    pub show_when_trusted: bool,
+
+    /// Warn about directories on network filesystems (NFS, SMB, SSHFS, ...)
+    #[serde(default = "default_true")]
+    pub warn_network: bool,
+
+    #[serde(default = "default_trust_untrusted_text")]
+    pub untrusted_text: String,
+
+    #[serde(default = "default_trust_network_text")]
+    pub network_text: String,
+
+    #[serde(default = "default_trust_trusted_text")]
+    pub trusted_text: String,
+
+    /// Color of the trusted state
+    #[serde(default = "default_trust_trusted_color")]
+    pub trusted_color: String,
+}
+
+impl Default for TrustComponentConfig {
+    fn default() -> Self {
+        Self {
+            base: BaseComponentConfig {
+                enabled: true,
+                icon_color: "yellow".to_string(),
+                text_color: "yellow".to_string(),
+                emoji_icon: "🛡️".to_string(),
+                nerd_icon: "\u{f132}".to_string(),
+                text_icon: "[!]".to_string(),
+                timeout_ms: None,
+                min_width: 0,
+                align: SegmentAlign::Left,
+                image_icon: None,
+            },
+            show_when_trusted: false,
+            warn_network: true,
+            untrusted_text: default_trust_untrusted_text(),
+            network_text: default_trust_network_text(),
+            trusted_text: default_trust_trusted_text(),
+            trusted_color: default_trust_trusted_color(),
+        }
+    }
+}
+
+fn default_trust_untrusted_text() -> String {
+    "untrusted".to_string()
+}
+
+fn default_trust_network_text() -> String {
+    "network".to_string()
+}
 
-    #[serde(default)]
-    pub show_percentage: bool,
+fn default_trust_trusted_text() -> String {
+    "trusted".to_string()
+}
 
-    #[serde(default)]
-    pub show_raw_numbers: bool,
+fn default_trust_trusted_color() -> String {
+    "ok".to_string()
+}
 
-    #[serde(default = "default_progress_width")]
-    pub progress_width: u32,
+/// Weekly quota component configuration
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct QuotaComponentConfig {
+    #[serde(flatten)]
+    pub base: BaseComponentConfig,
 
+    /// Weekly session-time limit in hours (0 = not tracked)
     #[serde(default)]
-    pub show_gradient: bool,
+    pub weekly_hours: f64,
 
+    /// Weekly token budget (0 = not tracked). Counting tokens scans the
+    /// transcripts written since the last reset.
     #[serde(default)]
-    pub progress_bar_chars: TokensProgressBarCharsConfig,
+    pub weekly_tokens: u64,
 
-    #[serde(default)]
-    pub colors: TokensColorConfig,
+    /// Day the quota resets (`monday` ... `sunday`)
+    #[serde(default = "default_quota_reset_day")]
+    pub reset_day: String,
 
+    /// Local hour (0-23) the quota resets on `reset_day`
     #[serde(default)]
-    pub thresholds: TokensThresholdsConfig,
+    pub reset_hour: u32,
 
-    #[serde(default)]
-    pub status_icons: TokensStatusIconsConfig,
+    /// Show the day the current pace runs out, when that is before the reset
+    #[serde(default = "default_true")]
+    pub show_projection: bool,
 
-    #[serde(default)]
-    pub context_windows: HashMap<String, u64>,
+    /// Used share (%) reported as a warning
+    #[serde(default = "default_quota_warning_threshold")]
+    pub warning_threshold: f64,
+
+    /// Used share (%) reported as critical
+    #[serde(default = "default_quota_critical_threshold")]
+    pub critical_threshold: f64,
 }
 
-impl Default for TokensComponentConfig {
+impl Default for QuotaComponentConfig {
     fn default() -> Self {
         Self {
             base: BaseComponentConfig {
                 enabled: true,
                 icon_color: "cyan".to_string(),
                 text_color: "white".to_string(),
-                emoji_icon: "📊".to_string(),
-                nerd_icon: "\u{f201}".to_string(),
-                text_icon: "[T]".to_string(),
+                emoji_icon: "📅".to_string(),
+                nerd_icon: "\u{f073}".to_string(),
+                text_icon: "[Q]".to_string(),
+                timeout_ms: None,
+                min_width: 0,
+                align: SegmentAlign::Left,
+                image_icon: None,
             },
-            show_zero: false,
-            format: default_compact(),
-            show_progress_bar: true,
-            show_percentage: true,
-            show_raw_numbers: false,
-            progress_width: default_progress_width(),
-            show_gradient: false,
-            progress_bar_chars: TokensProgressBarCharsConfig::default(),
-            colors: TokensColorConfig::default(),
-            thresholds: TokensThresholdsConfig::default(),
-            status_icons: TokensStatusIconsConfig::default(),
-            context_windows: default_context_windows(),
+            weekly_hours: 0.0,
+            weekly_tokens: 0,
+            reset_day: default_quota_reset_day(),
+            reset_hour: 0,
+            show_projection: true,
+            warning_threshold: default_quota_warning_threshold(),
+            critical_threshold: default_quota_critical_threshold(),
         }
     }
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize)]
-pub struct TokensProgressBarCharsConfig {
-    #[serde(default = "default_filled_char")]
-    pub filled: String,
-    #[serde(default = "default_empty_char")]
-    pub empty: String,
-    #[serde(default = "default_backup_char")]
-    pub backup: String,
-    #[serde(default = "default_left_bracket")]
-    pub left_bracket: String,
-    #[serde(default = "default_right_bracket")]
-    pub right_bracket: String,
-}
-
-impl Default for TokensProgressBarCharsConfig {
-    fn default() -> Self {
-        Self {
-            filled: default_filled_char(),
-            empty: default_empty_char(),
-            backup: default_backup_char(),
-            left_bracket: default_left_bracket(),
-            right_bracket: default_right_bracket(),
-        }
-    }
+fn default_quota_reset_day() -> String {
+    "monday".to_string()
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize)]
-pub struct TokensColorConfig {
-    #[serde(default = "default_safe_color")]
-    pub safe: String,
-    #[serde(default = "default_warning_color")]
-    pub warning: String,
-    #[serde(default = "default_danger_color")]
-    pub danger: String,
+const fn default_quota_warning_threshold() -> f64 {
+    80.0
 }
 
-impl Default for TokensColorConfig {
-    fn default() -> Self {
-        Self {
-            safe: default_safe_color(),
-            warning: default_warning_color(),
-            danger: default_danger_color(),
-        }
-    }
+const fn default_quota_critical_threshold() -> f64 {
+    95.0
 }
 
+/// Session badge component configuration
 #[derive(Debug, Clone, Deserialize, Serialize)]
-pub struct TokensThresholdsConfig {
-    #[serde(default = "default_warning_threshold")]
-    pub warning: f64,
-    #[serde(default = "default_danger_threshold")]
-    pub danger: f64,
-    #[serde(default = "default_backup_threshold")]
-    pub backup: f64,
-    #[serde(default = "default_critical_threshold")]
-    pub critical: f64,
-}
+pub struct SessionComponentConfig {
+    #[serde(flatten)]
+    pub base: BaseComponentConfig,
 
-impl Default for TokensThresholdsConfig {
-    fn default() -> Self {
-        Self {
-            warning: default_warning_threshold(),
-            danger: default_danger_threshold(),
-            backup: default_backup_threshold(),
-            critical: default_critical_threshold(),
-        }
-    }
-}
+    /// Minutes since its last snapshot update for a session to count as active
+    #[serde(default = "default_session_active_minutes")]
+    pub active_minutes: u64,
 
-#[derive(Debug, Clone, Deserialize, Serialize, Default)]
-pub struct TokenIconSetConfig {
-    #[serde(default)]
-    pub backup: String,
+    /// Show the badge even when this is the only active session
     #[serde(default)]
-    pub critical: String,
-}
+    pub show_when_single: bool,
 
-#[derive(Debug, Clone, Deserialize, Serialize)]
-pub struct TokensStatusIconsConfig {
-    #[serde(default = "default_emoji_icon_set")]
-    pub emoji: TokenIconSetConfig,
-    #[serde(default = "default_nerd_icon_set")]
-    pub nerd: TokenIconSetConfig,
-    #[serde(default = "default_text_icon_set")]
-    pub text: TokenIconSetConfig,
+    /// Fixed labels by session id; a key matching the start of the id also
+    /// applies (longest key wins)
+    #[serde(default)]
+    pub labels: HashMap<String, String>,
 }
 
-impl Default for TokensStatusIconsConfig {
+impl Default for SessionComponentConfig {
     fn default() -> Self {
         Self {
-            emoji: default_emoji_icon_set(),
-            nerd: default_nerd_icon_set(),
-            text: default_text_icon_set(),
+            base: BaseComponentConfig {
+                enabled: true,
+                icon_color: "cyan".to_string(),
+                text_color: "cyan".to_string(),
+                emoji_icon: String::new(),
+                nerd_icon: String::new(),
+                text_icon: String::new(),
+                timeout_ms: None,
+                min_width: 0,
+                align: SegmentAlign::Left,
+                image_icon: None,
+            },
+            active_minutes: default_session_active_minutes(),
+            show_when_single: false,
+            labels: HashMap::new(),
         }
     }
 }
 
-/// Usage component configuration
+const fn default_session_active_minutes() -> u64 {
+    10
+}
+
+/// Ticket component configuration
 #[derive(Debug, Clone, Deserialize, Serialize)]
-pub struct UsageComponentConfig {
+pub struct TicketComponentConfig {
     #[serde(flatten)]
     pub base: BaseComponentConfig,
 
-    /// Display mode
-    #[serde(default = "default_smart")]
-    pub display_mode: String,
-
-    /// Precision for cost display
-    #[serde(default = "default_precision")]
-    pub precision: u32,
-
-    /// Currency display mode or fixed currency code
-    #[serde(default = "default_auto_string")]
-    pub currency: String,
-
-    /// User-provided endpoint to currency mappings
-    #[serde(default)]
-    pub currency_endpoint_rules: HashMap<String, String>,
+    /// Regex finding the ticket id in the branch name; the first capture
+    /// group is used when present, otherwise the whole match
+    #[serde(default = "default_ticket_pattern")]
+    pub pattern: String,
 
-    /// User-provided model name to currency mappings
-    #[serde(default)]
-    pub currency_model_rules: HashMap<String, String>,
+    /// Upper-case the extracted id (`eng-42` → `ENG-42`)
+    #[serde(default = "default_true")]
+    pub uppercase: bool,
 
-    /// Show lines added
+    /// Tracker URL with a `{ticket}` placeholder; when set the id is rendered
+    /// as an OSC 8 hyperlink
     #[serde(default)]
-    pub show_lines_added: bool,
+    pub url_template: String,
 
-    /// Show lines removed
+    /// Hide the branch component while a ticket id is found in the branch
     #[serde(default)]
-    pub show_lines_removed: bool,
+    pub replace_branch: bool,
 }
 
-impl Default for UsageComponentConfig {
+impl Default for TicketComponentConfig {
     fn default() -> Self {
         Self {
             base: BaseComponentConfig {
                 enabled: true,
-                icon_color: "yellow".to_string(),
+                icon_color: "magenta".to_string(),
                 text_color: "white".to_string(),
-                emoji_icon: "💰".to_string(),
-                nerd_icon: "\u{f155}".to_string(),
-                text_icon: "[U]".to_string(),
+                emoji_icon: "🎫".to_string(),
+                nerd_icon: "\u{f02b}".to_string(),
+                text_icon: "[#]".to_string(),
+                timeout_ms: None,
+                min_width: 0,
+                align: SegmentAlign::Left,
+                image_icon: None,
             },
-            display_mode: default_smart(),
-            precision: default_precision(),
-            currency: default_auto_string(),
-            currency_endpoint_rules: HashMap::new(),
-            currency_model_rules: HashMap::new(),
-            show_lines_added: false,
-            show_lines_removed: false,
+            pattern: default_ticket_pattern(),
+            uppercase: true,
+            url_template: String::new(),
+            replace_branch: false,
         }
     }
 }
 
-/// Rate limit component configuration
+/// Repository statistics component configuration
 #[derive(Debug, Clone, Deserialize, Serialize)]
-pub struct RateLimitComponentConfig {
+pub struct RepoComponentConfig {
     #[serde(flatten)]
     pub base: BaseComponentConfig,
 
-    /// Show the 5-hour rolling window
+    /// Show the number of tracked files (`12k files`)
     #[serde(default = "default_true")]
-    pub show_five_hour: bool,
+    pub show_files: bool,
 
-    /// Show the 7-day weekly window
+    /// Show the size of the pack files (`1.2 GB`)
     #[serde(default = "default_true")]
-    pub show_seven_day: bool,
+    pub show_size: bool,
 
-    /// Show reset countdowns when `resets_at` is available
-    #[serde(default = "default_true")]
-    pub show_reset: bool,
+    /// Appended when the branch component skips status collection because
+    /// the repository exceeds `branch.performance.large_repo_threshold`
+    #[serde(default = "default_repo_skipped_text")]
+    pub skipped_text: String,
+
+    /// Text color while status collection is skipped
+    #[serde(default = "default_repo_large_color")]
+    pub large_color: String,
 }
 
-impl Default for RateLimitComponentConfig {
+impl Default for RepoComponentConfig {
     fn default() -> Self {
         Self {
             base: BaseComponentConfig {
                 enabled: true,
-                icon_color: "magenta".to_string(),
+                icon_color: "blue".to_string(),
                 text_color: "white".to_string(),
-                emoji_icon: "⏱️".to_string(),
-                nerd_icon: "\u{f017}".to_string(),
-                text_icon: "[R]".to_string(),
+                emoji_icon: "🗃️".to_string(),
+                nerd_icon: "\u{f1c0}".to_string(),
+                text_icon: "[G]".to_string(),
+                timeout_ms: None,
+                min_width: 0,
+                align: SegmentAlign::Left,
+                image_icon: None,
             },
-            show_five_hour: true,
-            show_seven_day: true,
-            show_reset: true,
+            show_files: true,
+            show_size: true,
+            skipped_text: default_repo_skipped_text(),
+            large_color: default_repo_large_color(),
         }
     }
 }
 
+fn default_repo_skipped_text() -> String {
+    "no status".to_string()
+}
+
+fn default_repo_large_color() -> String {
+    "warn".to_string()
+}
+
+fn default_ticket_pattern() -> String {
+    r"[A-Za-z][A-Za-z0-9]+-\d+".to_string()
+}
+
 /// Status component configuration
 #[derive(Debug, Clone, Deserialize, Serialize)]
+#[allow(clippy::struct_excessive_bools)]
 pub struct StatusComponentConfig {
     #[serde(flatten)]
     pub base: BaseComponentConfig,
@@ -827,6 +2214,45 @@ pub struct StatusComponentConfig {
     #[serde(default = "default_true")]
     pub show_recent_errors: bool,
 
+    /// Show `Error ×N` when several assistant turns failed in a row
+    #[serde(default = "default_true")]
+    pub show_error_count: bool,
+
+    /// Consecutive error count at which `colors.error_critical` is used
+    #[serde(default = "default_status_error_escalation")]
+    pub error_escalation_threshold: u32,
+
+    /// Keep the built-in error heuristics (403 quota, filter, blocked tools)
+    #[serde(default = "default_true")]
+    pub builtin_error_rules: bool,
+
+    /// User-defined error patterns, checked before the built-in heuristics
+    #[serde(default)]
+    pub error_rules: Vec<StatusErrorRule>,
+
+    /// Append how long the running tool has been going, e.g. `Tool (Bash · 12s)`
+    #[serde(default = "default_true")]
+    pub show_tool_elapsed: bool,
+
+    /// Seconds after which a running tool switches to `colors.warning` (0 = never)
+    #[serde(default = "default_status_tool_warning_seconds")]
+    pub tool_warning_seconds: u64,
+
+    /// Error details wider than this many columns move to a wrapped line below
+    /// the statusline (needs `multiline.enabled`; 0 keeps them inline)
+    #[serde(default = "default_status_details_inline_width")]
+    pub details_inline_max_width: usize,
+
+    /// Maximum number of wrapped detail lines
+    #[serde(default = "default_status_details_max_lines")]
+    pub details_max_lines: usize,
+
+    /// Seconds a `tool_use` may go without a result before it is shown as
+    /// `Waiting` (likely a permission prompt). 0 disables the detection, since a
+    /// slow tool and a pending approval look the same in the transcript.
+    #[serde(default)]
+    pub waiting_after_seconds: u64,
+
     /// Status icon overrides grouped by output type
     #[serde(default)]
     pub icons: StatusIconsConfig,
@@ -846,15 +2272,54 @@ impl Default for StatusComponentConfig {
                 emoji_icon: "✨".to_string(),
                 nerd_icon: "\u{f00c}".to_string(),
                 text_icon: "[S]".to_string(),
+                timeout_ms: None,
+                min_width: 0,
+                align: SegmentAlign::Left,
+                image_icon: None,
             },
             show_when_idle: false,
             show_recent_errors: default_true(),
+            show_error_count: default_true(),
+            error_escalation_threshold: default_status_error_escalation(),
+            builtin_error_rules: default_true(),
+            error_rules: Vec::new(),
+            show_tool_elapsed: default_true(),
+            tool_warning_seconds: default_status_tool_warning_seconds(),
+            waiting_after_seconds: 0,
+            details_inline_max_width: default_status_details_inline_width(),
+            details_max_lines: default_status_details_max_lines(),
             icons: StatusIconsConfig::default(),
             colors: StatusColorConfig::default(),
         }
     }
 }
 
+/// Regex rule mapping transcript error text to a status
+///
+/// Applied to tool errors and synthetic (`stop_sequence`) assistant messages.
+/// `label` may reference capture groups (`$1`, `${name}`).
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
+pub struct StatusErrorRule {
+    pub pattern: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    #[serde(default)]
+    pub severity: StatusErrorSeverity,
+}
+
+/// How a matched error rule is displayed
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum StatusErrorSeverity {
+    /// `Error (label)`, counted towards the consecutive error streak
+    #[default]
+    Error,
+    /// `label` shown as a warning
+    Warning,
+    /// Not an error, even if a built-in heuristic would match
+    Ignore,
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize, Default)]
 pub struct StatusIconsConfig {
     #[serde(default)]
@@ -879,6 +2344,8 @@ pub struct StatusEmojiIcons {
     pub error: String,
     #[serde(default = "default_status_warning_emoji")]
     pub warning: String,
+    #[serde(default = "default_status_waiting_emoji")]
+    pub waiting: String,
 }
 
 impl Default for StatusEmojiIcons {
@@ -889,6 +2356,7 @@ impl Default for StatusEmojiIcons {
             tool: default_status_tool_emoji(),
             error: default_status_error_emoji(),
             warning: default_status_warning_emoji(),
+            waiting: default_status_waiting_emoji(),
         }
     }
 }
@@ -905,6 +2373,8 @@ pub struct StatusNerdIcons {
     pub error: String,
     #[serde(default = "default_status_warning_nerd")]
     pub warning: String,
+    #[serde(default = "default_status_waiting_nerd")]
+    pub waiting: String,
 }
 
 impl Default for StatusNerdIcons {
@@ -915,6 +2385,7 @@ impl Default for StatusNerdIcons {
             tool: default_status_tool_nerd(),
             error: default_status_error_nerd(),
             warning: default_status_warning_nerd(),
+            waiting: default_status_waiting_nerd(),
         }
     }
 }
@@ -931,6 +2402,8 @@ pub struct StatusTextIcons {
     pub error: String,
     #[serde(default = "default_status_warning_text")]
     pub warning: String,
+    #[serde(default = "default_status_waiting_text")]
+    pub waiting: String,
 }
 
 impl Default for StatusTextIcons {
@@ -941,6 +2414,7 @@ impl Default for StatusTextIcons {
             tool: default_status_tool_text(),
             error: default_status_error_text(),
             warning: default_status_warning_text(),
+            waiting: default_status_waiting_text(),
         }
     }
 }
@@ -955,8 +2429,12 @@ pub struct StatusColorConfig {
     pub tool: String,
     #[serde(default = "default_status_error_color")]
     pub error: String,
+    #[serde(default = "default_status_error_critical_color")]
+    pub error_critical: String,
     #[serde(default = "default_status_warning_color")]
     pub warning: String,
+    #[serde(default = "default_status_waiting_color")]
+    pub waiting: String,
 }
 
 impl Default for StatusColorConfig {
@@ -966,7 +2444,9 @@ impl Default for StatusColorConfig {
             thinking: default_status_thinking_color(),
             tool: default_status_tool_color(),
             error: default_status_error_color(),
+            error_critical: default_status_error_critical_color(),
             warning: default_status_warning_color(),
+            waiting: default_status_waiting_color(),
         }
     }
 }
@@ -1167,6 +2647,29 @@ const fn default_expiry() -> u32 {
     30
 }
 
+fn default_usage_source() -> String {
+    "snapshots".to_string()
+}
+
+const fn default_history_max_entries() -> usize {
+    500
+}
+
+/// Token counters duplicated from the transcript; kept out of snapshots by default
+#[must_use]
+pub fn default_redact_keys() -> Vec<String> {
+    [
+        "cost.input_tokens",
+        "cost.output_tokens",
+        "cost.total_tokens",
+        "cost.cache_read_tokens",
+        "cost.cache_write_tokens",
+    ]
+    .into_iter()
+    .map(str::to_string)
+    .collect()
+}
+
 fn default_separator() -> String {
     "|".to_string()
 }
@@ -1187,6 +2690,26 @@ fn default_compact() -> String {
     "compact".to_string()
 }
 
+fn default_project_source() -> String {
+    "directory".to_string()
+}
+
+const fn default_relative_depth() -> usize {
+    2
+}
+
+fn default_token_unit() -> String {
+    "k".to_string()
+}
+
+fn default_progress_style() -> String {
+    "blocks".to_string()
+}
+
+fn default_critical_emphasis() -> String {
+    "none".to_string()
+}
+
 fn default_smart() -> String {
     "smart".to_string()
 }
@@ -1199,6 +2722,10 @@ const fn default_precision() -> u32 {
     2
 }
 
+fn default_rounding() -> String {
+    "nearest".to_string()
+}
+
 const fn default_max_rows() -> u32 {
     5
 }
@@ -1216,7 +2743,7 @@ fn default_branch_behind_color() -> String {
 }
 
 fn default_branch_operation_color() -> String {
-    "red".to_string()
+    "danger".to_string()
 }
 
 const fn default_branch_max_length() -> u32 {
@@ -1227,6 +2754,10 @@ const fn default_branch_cache_ttl() -> u64 {
     5_000
 }
 
+const fn default_component_render_timeout() -> u64 {
+    2000
+}
+
 const fn default_branch_git_timeout() -> u32 {
     1_000
 }
@@ -1260,15 +2791,15 @@ fn default_right_bracket() -> String {
 }
 
 fn default_safe_color() -> String {
-    "green".to_string()
+    "ok".to_string()
 }
 
 fn default_warning_color() -> String {
-    "yellow".to_string()
+    "warn".to_string()
 }
 
 fn default_danger_color() -> String {
-    "red".to_string()
+    "danger".to_string()
 }
 
 const fn default_warning_threshold() -> f64 {
@@ -1321,11 +2852,11 @@ fn default_text_icon_set() -> TokenIconSetConfig {
 }
 
 fn default_status_ready_color() -> String {
-    "green".to_string()
+    "ok".to_string()
 }
 
 fn default_status_thinking_color() -> String {
-    "yellow".to_string()
+    "warn".to_string()
 }
 
 fn default_status_tool_color() -> String {
@@ -1333,11 +2864,44 @@ fn default_status_tool_color() -> String {
 }
 
 fn default_status_error_color() -> String {
-    "red".to_string()
+    "danger".to_string()
+}
+
+fn default_status_error_critical_color() -> String {
+    "bright_red".to_string()
+}
+
+#[allow(clippy::literal_string_with_formatting_args)] // placeholders are user template syntax
+fn default_field_format() -> String {
+    "{label} {value}".to_string()
+}
+
+fn default_hook_hidden_events() -> Vec<String> {
+    vec!["Status".to_string()]
+}
+
+const fn default_status_error_escalation() -> u32 {
+    3
+}
+
+const fn default_status_details_inline_width() -> usize {
+    40
+}
+
+const fn default_status_details_max_lines() -> usize {
+    3
+}
+
+const fn default_status_tool_warning_seconds() -> u64 {
+    60
 }
 
 fn default_status_warning_color() -> String {
-    "yellow".to_string()
+    "warn".to_string()
+}
+
+fn default_status_waiting_color() -> String {
+    "bright_magenta".to_string()
 }
 
 fn default_status_ready_emoji() -> String {
@@ -1360,6 +2924,10 @@ fn default_status_warning_emoji() -> String {
     "⚠️".to_string()
 }
 
+fn default_status_waiting_emoji() -> String {
+    "⏳".to_string()
+}
+
 fn default_status_ready_nerd() -> String {
     "\u{f00c}".to_string()
 }
@@ -1380,6 +2948,10 @@ fn default_status_warning_nerd() -> String {
     "\u{f071}".to_string()
 }
 
+fn default_status_waiting_nerd() -> String {
+    "\u{f252}".to_string()
+}
+
 fn default_status_ready_text() -> String {
     "[OK]".to_string()
 }
@@ -1399,3 +2971,7 @@ fn default_status_error_text() -> String {
 fn default_status_warning_text() -> String {
     "[WARN]".to_string()
 }
+
+fn default_status_waiting_text() -> String {
+    "[WAIT]".to_string()
+}
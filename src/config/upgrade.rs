@@ -0,0 +1,267 @@
+//! Three-way configuration upgrade
+//!
+//! `config init` 在配置旁保存一份生成时使用的模板快照(`.config.defaults.toml`)。
+//! `config upgrade` 以快照为共同祖先,把新版模板与用户文件做三方合并:
+//! 用户改过的值保留,用户没动过的值跟随新默认值,新增的键连同注释一起出现,
+//! 新模板删掉且用户没改过的键被移除。没有快照时退化为两方合并,用户文件中的值全部保留。
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use toml_edit::{DocumentMut, Item, Table};
+
+use super::backup::backup_config;
+use super::loader::ConfigLoader;
+
+/// Outcome of a configuration upgrade, as dotted key paths
+#[derive(Debug, Clone, Default)]
+pub struct UpgradeReport {
+    pub path: PathBuf,
+    /// Whether a defaults snapshot was available as the merge base
+    pub had_base: bool,
+    /// Keys new in the defaults
+    pub added: Vec<String>,
+    /// Untouched keys moved to their new default value
+    pub updated: Vec<String>,
+    /// User edits kept over the defaults
+    pub kept: Vec<String>,
+    /// Untouched keys no longer in the defaults
+    pub removed: Vec<String>,
+}
+
+impl UpgradeReport {
+    /// Whether the upgrade changes anything
+    #[must_use]
+    pub fn is_noop(&self) -> bool {
+        self.added.is_empty() && self.updated.is_empty() && self.removed.is_empty()
+    }
+}
+
+/// Path of the defaults snapshot kept next to `config_path`
+#[must_use]
+pub fn defaults_snapshot_path(config_path: &Path) -> PathBuf {
+    let stem = config_path
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or("config");
+    config_path.with_file_name(format!(".{stem}.defaults.toml"))
+}
+
+/// Merge `user` onto `defaults`, using `base` (the defaults the user file
+/// was generated from) to tell user edits from stale defaults.
+///
+/// 结果以 `defaults` 为骨架,保留新模板的注释和键顺序。
+#[must_use]
+pub fn three_way_merge(
+    base: Option<&DocumentMut>,
+    defaults: &DocumentMut,
+    user: &DocumentMut,
+    report: &mut UpgradeReport,
+) -> DocumentMut {
+    let mut merged = defaults.clone();
+    report.had_base = base.is_some();
+    merge_table(
+        base.map(DocumentMut::as_table),
+        defaults.as_table(),
+        user.as_table(),
+        merged.as_table_mut(),
+        &mut Vec::new(),
+        report,
+    );
+    merged
+}
+
+fn merge_table(
+    base: Option<&Table>,
+    defaults: &Table,
+    user: &Table,
+    merged: &mut Table,
+    path: &mut Vec<String>,
+    report: &mut UpgradeReport,
+) {
+    let mut keys: Vec<String> = defaults.iter().map(|(key, _)| key.to_string()).collect();
+    keys.extend(
+        user.iter()
+            .map(|(key, _)| key.to_string())
+            .filter(|key| !defaults.contains_key(key)),
+    );
+
+    for key in keys {
+        path.push(key.clone());
+        let base_item = base.and_then(|table| table.get(&key));
+        match (defaults.get(&key), user.get(&key)) {
+            (Some(Item::Table(default_table)), Some(Item::Table(user_table))) => {
+                if let Some(Item::Table(merged_table)) = merged.get_mut(&key) {
+                    merge_table(
+                        base_item.and_then(Item::as_table),
+                        default_table,
+                        user_table,
+                        merged_table,
+                        path,
+                        report,
+                    );
+                }
+            }
+            (Some(default_item), Some(user_item)) => {
+                if same_value(user_item, default_item) {
+                    // 与新默认值一致,无需处理
+                } else if base_item.is_some_and(|base_item| same_value(user_item, base_item)) {
+                    report.updated.push(path.join("."));
+                } else {
+                    replace_keeping_decor(merged, &key, user_item);
+                    report.kept.push(path.join("."));
+                }
+            }
+            (Some(_), None) => {
+                if base.is_none() || base_item.is_none() {
+                    report.added.push(path.join("."));
+                } else {
+                    // 用户删掉了这个键,尊重删除
+                    merged.remove(&key);
+                }
+            }
+            (None, Some(user_item)) => {
+                if base_item.is_some_and(|base_item| same_value(user_item, base_item)) {
+                    report.removed.push(path.join("."));
+                } else {
+                    merged.insert(&key, user_item.clone());
+                    report.kept.push(path.join("."));
+                }
+            }
+            (None, None) => {}
+        }
+        path.pop();
+    }
+}
+
+/// Put `user_item` under `key`, keeping the comments attached to the
+/// default value.
+fn replace_keeping_decor(merged: &mut Table, key: &str, user_item: &Item) {
+    match (merged.get_mut(key), user_item) {
+        (Some(Item::Value(slot)), Item::Value(user_value)) => {
+            let decor = slot.decor().clone();
+            *slot = user_value.clone();
+            *slot.decor_mut() = decor;
+        }
+        _ => {
+            merged.insert(key, user_item.clone());
+        }
+    }
+}
+
+/// Whether two items hold the same data, ignoring formatting and comments
+fn same_value(a: &Item, b: &Item) -> bool {
+    match (normalized(a), normalized(b)) {
+        (Some(a), Some(b)) => a == b,
+        _ => false,
+    }
+}
+
+fn normalized(item: &Item) -> Option<serde_json::Value> {
+    let mut document = DocumentMut::new();
+    document.insert("value", item.clone());
+    toml_edit::de::from_str(&document.to_string()).ok()
+}
+
+fn read_document(path: &Path) -> Result<DocumentMut> {
+    fs::read_to_string(path)
+        .with_context(|| format!("Failed to read config file: {}", path.display()))?
+        .parse::<DocumentMut>()
+        .with_context(|| format!("Failed to parse TOML config: {}", path.display()))
+}
+
+impl ConfigLoader {
+    /// Three-way merge the current template into the config at `path`.
+    ///
+    /// 合并后刷新默认值快照,下次升级以本次模板为基准。`dry_run` 时只返回报告。
+    /// # Errors
+    ///
+    /// Returns an error when the config file cannot be read or parsed, or
+    /// when writing the upgraded file fails.
+    pub fn upgrade_config(path: &Path, dry_run: bool) -> Result<UpgradeReport> {
+        let user = read_document(path)?;
+        let defaults = Self::template_document();
+        let snapshot_path = defaults_snapshot_path(path);
+        let base = snapshot_path
+            .exists()
+            .then(|| read_document(&snapshot_path))
+            .transpose()?;
+
+        let mut report = UpgradeReport {
+            path: path.to_path_buf(),
+            ..UpgradeReport::default()
+        };
+        let merged = three_way_merge(base.as_ref(), &defaults, &user, &mut report);
+
+        if !dry_run {
+            backup_config(path)?;
+            fs::write(path, merged.to_string())
+                .with_context(|| format!("Failed to write config file: {}", path.display()))?;
+            fs::write(&snapshot_path, defaults.to_string()).with_context(|| {
+                format!(
+                    "Failed to write defaults snapshot: {}",
+                    snapshot_path.display()
+                )
+            })?;
+        }
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn doc(text: &str) -> Result<DocumentMut> {
+        Ok(text.parse::<DocumentMut>()?)
+    }
+
+    #[test]
+    fn user_edits_survive_and_new_defaults_apply() -> Result<()> {
+        let base = doc("theme = \"classic\"\nmax = 10\nold = 1\n\n[style]\nseparator = \"|\"\n")?;
+        let defaults = doc(concat!(
+            "theme = \"classic\"\n",
+            "# 新的上限\n",
+            "max = 20\n\n",
+            "[style]\n",
+            "separator = \"|\"\n",
+            "# 新增的键\n",
+            "compact = false\n",
+        ))?;
+        let user = doc("theme = \"powerline\"\nmax = 10\nold = 1\n\n[style]\nseparator = \"/\"\n\n[translations.zh]\nReady = \"好\"\n")?;
+
+        let mut report = UpgradeReport::default();
+        let merged = three_way_merge(Some(&base), &defaults, &user, &mut report);
+
+        assert_eq!(merged["theme"].as_str(), Some("powerline"));
+        assert_eq!(merged["max"].as_integer(), Some(20));
+        assert!(merged.get("old").is_none());
+        assert_eq!(merged["style"]["separator"].as_str(), Some("/"));
+        assert_eq!(merged["style"]["compact"].as_bool(), Some(false));
+        assert_eq!(merged["translations"]["zh"]["Ready"].as_str(), Some("好"));
+        // 新键的注释随模板一起出现
+        assert!(merged.to_string().contains("# 新增的键\ncompact = false"));
+
+        assert_eq!(report.added, ["style.compact"]);
+        assert_eq!(report.updated, ["max"]);
+        assert_eq!(report.removed, ["old"]);
+        assert_eq!(report.kept, ["theme", "style.separator", "translations"]);
+        Ok(())
+    }
+
+    #[test]
+    fn without_base_user_values_are_kept() -> Result<()> {
+        let defaults = doc("max = 20\nnew = true\n")?;
+        let user = doc("max = 10\n")?;
+
+        let mut report = UpgradeReport::default();
+        let merged = three_way_merge(None, &defaults, &user, &mut report);
+
+        assert!(!report.had_base);
+        assert_eq!(merged["max"].as_integer(), Some(10));
+        assert_eq!(merged["new"].as_bool(), Some(true));
+        assert_eq!(report.added, ["new"]);
+        Ok(())
+    }
+}
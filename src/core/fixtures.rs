@@ -0,0 +1,217 @@
+//! Render fixtures (golden outputs)
+//!
+//! Each fixture is a directory holding:
+//! - `input.json`: the stdin payload fed to the generator
+//! - `config.toml` (optional): config layered only on top of the defaults
+//! - `expected.ansi` / `expected.txt`: golden output with and without colors
+//!
+//! `tests/render_fixtures.rs` renders every fixture and compares it against the
+//! goldens; `--render-fixtures` regenerates them after an intentional change.
+//! Rendering is pinned so the output does not depend on the host terminal:
+//! auto-detected emoji / Nerd Font support resolves to "off", storage is never
+//! touched, and colors are forced on or off per golden.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+
+use super::{GeneratorOptions, InputData, StatuslineGenerator};
+use crate::config::{AutoDetect, Config, ConfigLoader};
+
+/// Fixture directory used by the test-suite and `--render-fixtures`
+pub const DEFAULT_FIXTURES_DIR: &str = "tests/fixtures/render";
+
+const INPUT_FILE: &str = "input.json";
+const CONFIG_FILE: &str = "config.toml";
+const ANSI_GOLDEN: &str = "expected.ansi";
+const PLAIN_GOLDEN: &str = "expected.txt";
+
+/// A single fixture loaded from disk
+#[derive(Debug, Clone)]
+pub struct RenderFixture {
+    pub name: String,
+    pub dir: PathBuf,
+    pub input: InputData,
+    pub config: Config,
+}
+
+/// Rendered output of a fixture
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FixtureOutput {
+    pub ansi: String,
+    pub plain: String,
+}
+
+/// List fixture directories (those containing `input.json`) under `root`, sorted by name.
+///
+/// # Errors
+///
+/// Returns an error if `root` cannot be read.
+pub fn discover(root: &Path) -> Result<Vec<PathBuf>> {
+    let entries = fs::read_dir(root)
+        .with_context(|| format!("Failed to read fixture directory: {}", root.display()))?;
+
+    let mut dirs: Vec<PathBuf> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.join(INPUT_FILE).is_file())
+        .collect();
+    dirs.sort();
+    Ok(dirs)
+}
+
+/// Load a fixture directory.
+///
+/// # Errors
+///
+/// Returns an error if `input.json` or `config.toml` is missing or invalid.
+pub fn load(dir: &Path) -> Result<RenderFixture> {
+    let input_path = dir.join(INPUT_FILE);
+    let raw = fs::read_to_string(&input_path)
+        .with_context(|| format!("Failed to read fixture input: {}", input_path.display()))?;
+    let input = InputData::from_json(&raw)
+        .with_context(|| format!("Invalid fixture input: {}", input_path.display()))?;
+
+    let config_path = dir.join(CONFIG_FILE);
+    let mut config = if config_path.is_file() {
+        ConfigLoader::load_standalone(&config_path)?
+    } else {
+        Config::default()
+    };
+    pin_detection(&mut config);
+
+    let name = dir.file_name().map_or_else(
+        || dir.display().to_string(),
+        |name| name.to_string_lossy().into_owned(),
+    );
+
+    Ok(RenderFixture {
+        name,
+        dir: dir.to_path_buf(),
+        input,
+        config,
+    })
+}
+
+/// Render a fixture with colors on (ANSI golden) and off (plain golden).
+///
+/// # Errors
+///
+/// Returns an error if the generator fails to render the fixture.
+pub async fn render(fixture: &RenderFixture) -> Result<FixtureOutput> {
+    let ansi = Box::pin(render_with_colors(fixture, true)).await?;
+    let plain = Box::pin(render_with_colors(fixture, false)).await?;
+    Ok(FixtureOutput { ansi, plain })
+}
+
+async fn render_with_colors(fixture: &RenderFixture, colors: bool) -> Result<String> {
+    let mut config = fixture.config.clone();
+    config.style.enable_colors = AutoDetect::Bool(colors);
+    if !colors {
+        // force_nerd_font / force_emoji 隐含全彩输出,会压过 enable_colors=false;
+        // 换成等价的 enable_* 开关,图标不变但真正关闭颜色。
+        if config.terminal.force_nerd_font {
+            config.terminal.force_nerd_font = false;
+            config.style.enable_nerd_font = AutoDetect::Bool(true);
+        }
+        if config.terminal.force_emoji {
+            config.terminal.force_emoji = false;
+            config.style.enable_emoji = AutoDetect::Bool(true);
+        }
+    }
+
+    let options = GeneratorOptions {
+        update_throttling: false,
+        disable_cache: true,
+        config_base_dir: Some(fixture.dir.to_string_lossy().into_owned()),
+        preview_mode: true,
+        ..GeneratorOptions::default()
+    };
+    let mut generator = StatuslineGenerator::new(config, options);
+    generator
+        .generate(fixture.input.clone())
+        .await
+        .with_context(|| format!("Failed to render fixture '{}'", fixture.name))
+}
+
+/// Read the golden outputs of a fixture, if both exist.
+#[must_use]
+pub fn read_goldens(dir: &Path) -> Option<FixtureOutput> {
+    let ansi = fs::read_to_string(dir.join(ANSI_GOLDEN)).ok()?;
+    let plain = fs::read_to_string(dir.join(PLAIN_GOLDEN)).ok()?;
+    Some(FixtureOutput { ansi, plain })
+}
+
+/// Overwrite the golden outputs of a fixture.
+///
+/// # Errors
+///
+/// Returns an error if a golden file cannot be written.
+pub fn write_goldens(dir: &Path, output: &FixtureOutput) -> Result<()> {
+    for (file, content) in [(ANSI_GOLDEN, &output.ansi), (PLAIN_GOLDEN, &output.plain)] {
+        let path = dir.join(file);
+        fs::write(&path, content)
+            .with_context(|| format!("Failed to write golden: {}", path.display()))?;
+    }
+    Ok(())
+}
+
+/// Auto-detection reads the host terminal; fixtures must not.
+fn pin_detection(config: &mut Config) {
+    for setting in [
+        &mut config.style.enable_emoji,
+        &mut config.style.enable_nerd_font,
+    ] {
+        if matches!(setting, AutoDetect::Auto(_)) {
+            *setting = AutoDetect::Bool(false);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn discover_only_returns_directories_with_input() -> Result<()> {
+        let root = tempdir()?;
+        fs::create_dir_all(root.path().join("b-fixture"))?;
+        fs::write(root.path().join("b-fixture").join(INPUT_FILE), "{}")?;
+        fs::create_dir_all(root.path().join("a-fixture"))?;
+        fs::write(root.path().join("a-fixture").join(INPUT_FILE), "{}")?;
+        fs::create_dir_all(root.path().join("empty"))?;
+
+        let names: Vec<_> = discover(root.path())?
+            .iter()
+            .filter_map(|dir| dir.file_name().map(|n| n.to_string_lossy().into_owned()))
+            .collect();
+        assert_eq!(names, ["a-fixture", "b-fixture"]);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn goldens_round_trip() -> Result<()> {
+        let dir = tempdir()?;
+        fs::write(
+            dir.path().join(INPUT_FILE),
+            r#"{"model":{"id":"claude-sonnet-4"},"cwd":"/nonexistent/app"}"#,
+        )?;
+        fs::write(dir.path().join(CONFIG_FILE), "preset = \"PM\"\n")?;
+
+        let fixture = load(dir.path())?;
+        assert!(matches!(
+            fixture.config.style.enable_emoji,
+            AutoDetect::Bool(false)
+        ));
+
+        let output = render(&fixture).await?;
+        assert!(output.plain.contains("app"), "plain={}", output.plain);
+        assert!(!output.plain.contains('\x1b'));
+
+        write_goldens(dir.path(), &output)?;
+        assert_eq!(read_goldens(dir.path()), Some(output));
+        Ok(())
+    }
+}
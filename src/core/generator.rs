@@ -2,19 +2,44 @@
 //!
 //! The main orchestrator that coordinates components, themes, and terminal rendering.
 
-use std::collections::{HashMap, HashSet};
-use std::path::PathBuf;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
-use std::time::{Duration, Instant};
+use std::time::{Duration, Instant, SystemTime};
 
 use anyhow::Result;
-
-use crate::components::{ComponentFactory, ComponentOutput, RenderContext, TerminalCapabilities};
-use crate::config::Config;
-use crate::core::{InputData, MultiLineRenderer};
-use crate::storage::{self, ProjectResolver};
+use futures::future::join_all;
+
+use crate::components::{
+    cache_key_of, ColorSupport, Component, ComponentOutput, ComponentRegistry, GraphicsProtocol,
+    RenderContext, TerminalCapabilities, BUILTIN_COMPONENTS,
+};
+use crate::config::{Config, Severity};
+use crate::core::hooks::{self, HookObservation};
+use crate::core::{InputData, MultiLineRenderer, TranscriptAnalysis};
+use crate::error::{StatuslineError, StatuslineResult};
+use crate::storage::warm_start::WarmOutput;
+#[cfg(not(feature = "storage"))]
+use crate::storage::MemoryStorage;
+use crate::storage::StorageHandle;
+#[cfg(feature = "storage")]
+use crate::storage::{ProjectId, StorageContext};
 use crate::terminal::detector::TerminalDetector;
-use crate::themes::{create_theme_renderer, ThemeRenderer};
+use crate::terminal::graphics::detect_protocol;
+use crate::terminal::progress::{detect_progress_support, osc_progress};
+use crate::terminal::title::{osc_title, title_summary};
+use crate::themes::frame::frame_lines;
+use crate::themes::gradient::apply_gradient;
+use crate::themes::idle::{dim_line, idle_duration, idle_segment};
+use crate::themes::severity::{select_rule, tint_color, tint_line};
+use crate::themes::text_safety::secure_segments;
+use crate::themes::{
+    ansi_fg_with_support, create_theme_renderer, Theme, ThemeRenderer, ANSI_RESET,
+};
+use crate::utils::ascii::to_ascii;
+#[cfg(feature = "storage")]
+use crate::utils::strip_sgr;
+use crate::utils::{pad_to_width, truncate_rendered};
 
 const POWERLINE_PALETTE: &[(&str, &str)] = &[
     ("project", "blue"),
@@ -24,6 +49,14 @@ const POWERLINE_PALETTE: &[(&str, &str)] = &[
     ("usage", "orange"),
     ("rate_limit", "magenta"),
     ("status", "magenta"),
+    ("hook", "cyan"),
+    ("field", "blue"),
+    ("text", "gray"),
+    ("trust", "yellow"),
+    ("quota", "cyan"),
+    ("session", "cyan"),
+    ("ticket", "magenta"),
+    ("repo", "blue"),
 ];
 
 const CAPSULE_PALETTE: &[(&str, &str)] = &[
@@ -34,6 +67,14 @@ const CAPSULE_PALETTE: &[(&str, &str)] = &[
     ("usage", "bright_orange"),
     ("rate_limit", "bright_magenta"),
     ("status", "bright_magenta"),
+    ("hook", "bright_cyan"),
+    ("field", "bright_blue"),
+    ("text", "gray"),
+    ("trust", "bright_yellow"),
+    ("quota", "bright_cyan"),
+    ("session", "bright_cyan"),
+    ("ticket", "bright_magenta"),
+    ("repo", "bright_blue"),
 ];
 
 /// Generator options
@@ -82,30 +123,97 @@ impl GeneratorOptions {
     }
 }
 
+/// A component output reused while its inputs are unchanged
+struct CachedOutput {
+    /// `None` for components without a cache key: kept only as the fallback
+    /// of a render cut off by `performance.max_total_ms`
+    key: Option<u64>,
+    output: ComponentOutput,
+}
+
+/// How a slot's render ended
+enum SlotOutcome {
+    /// Rendered in time
+    Fresh(ComponentOutput),
+    /// Served from the component cache
+    Cached(ComponentOutput),
+    /// Missed its own timeout and is left out
+    TimedOut,
+    /// Still rendering when the budget ran out; previous output, if any
+    OverBudget(Option<ComponentOutput>),
+}
+
+/// A component resolved for the current render
+struct RenderSlot {
+    name: String,
+    component: Box<dyn Component>,
+    key: Option<u64>,
+    cached: Option<ComponentOutput>,
+    /// Previous output, shown when the render budget runs out
+    stale: Option<ComponentOutput>,
+    /// Explicit separator placed before this component in the order
+    separator: Option<String>,
+}
+
+/// Separator text of a `sep:…` or `space` item in `components.order`.
+pub(crate) fn separator_item(item: &str) -> Option<&str> {
+    if item == "space" {
+        return Some(" ");
+    }
+    item.strip_prefix("sep:")
+}
+
 /// Core statusline generator
 ///
 /// Integrates all components to generate the final statusline
+#[allow(clippy::struct_excessive_bools)]
 pub struct StatuslineGenerator {
     config: Arc<Config>,
-    component_registry: HashMap<String, Box<dyn ComponentFactory>>,
+    component_registry: ComponentRegistry,
     terminal_detector: TerminalDetector,
     theme_renderer: Box<dyn ThemeRenderer>,
     multi_line_renderer: MultiLineRenderer,
     last_update: Option<Instant>,
     last_result: Option<String>,
+    /// Outputs of cacheable components from the previous render, keyed by name
+    component_cache: HashMap<String, CachedOutput>,
+    /// Transcript analysis of the previous render, reused while the file is unchanged
+    transcript_cache: Option<Arc<TranscriptAnalysis>>,
     update_interval: Duration,
     disable_cache: bool,
+    /// Storage settings and project of this generator, handed to components
+    #[cfg(feature = "storage")]
+    storage_context: StorageContext,
+    #[cfg(feature = "storage")]
     storage_initialized: bool,
-    active_project_id: Option<String>,
+    #[cfg(feature = "storage")]
+    active_project_id: Option<ProjectId>,
     config_base_dir: Option<PathBuf>,
     /// See `GeneratorOptions::preview_mode`: when true, `generate` is
     /// side-effect free (no storage init, no snapshot persistence).
     preview_mode: bool,
+    /// Storage handed to components instead of `storage_context`
+    storage: Option<Arc<dyn StorageHandle>>,
+    /// Last outputs of the current session's components from earlier
+    /// invocations (`performance.warm_start`), refreshed by every render
+    warm_outputs: BTreeMap<String, WarmOutput>,
 }
 
 impl StatuslineGenerator {
     /// Create a new generator with the given configuration and options
+    #[must_use]
     pub fn new(config: Config, options: GeneratorOptions) -> Self {
+        Self::with_registry(config, options, ComponentRegistry::default())
+    }
+
+    /// Create a generator rendering the components of `registry`.
+    ///
+    /// 注册表中的自定义组件与内置组件一样按 `components.order` 中的名字参与渲染。
+    pub fn with_registry(
+        config: Config,
+        options: GeneratorOptions,
+        component_registry: ComponentRegistry,
+    ) -> Self {
         let config_arc = Arc::new(config);
         let terminal_detector = TerminalDetector::new();
 
@@ -125,61 +233,54 @@ impl StatuslineGenerator {
 
         let mut generator = Self {
             config: Arc::clone(&config_arc),
-            component_registry: HashMap::new(),
+            component_registry,
             terminal_detector,
             theme_renderer,
             multi_line_renderer,
             last_update: None,
             last_result: None,
+            component_cache: HashMap::new(),
+            transcript_cache: None,
             update_interval,
             disable_cache: options.disable_cache,
+            #[cfg(feature = "storage")]
+            storage_context: StorageContext::new(&config_arc.storage),
+            #[cfg(feature = "storage")]
             storage_initialized: false,
+            #[cfg(feature = "storage")]
             active_project_id: None,
             config_base_dir,
             preview_mode: options.preview_mode,
+            storage: None,
+            warm_outputs: BTreeMap::new(),
         };
         drop(config_arc);
 
+        generator.apply_preset_mapping();
+
         // Apply preset if specified
         generator.apply_config_preset();
         if let Some(preset) = options.preset {
             generator.apply_preset(&preset);
         }
 
-        // Initialize components
-        generator.initialize_components();
-
         // Ensure multiline renderer holds latest config state
         generator.refresh_multiline_renderer();
 
         generator
     }
 
-    /// Initialize component registry
-    fn initialize_components(&mut self) {
-        use crate::components::{
-            BranchComponentFactory, ModelComponentFactory, ProjectComponentFactory,
-            RateLimitComponentFactory, StatusComponentFactory, TokensComponentFactory,
-            UsageComponentFactory,
-        };
+    /// Give components `storage` instead of the on-disk session storage.
+    #[must_use]
+    pub fn with_storage(mut self, storage: Arc<dyn StorageHandle>) -> Self {
+        self.storage = Some(storage);
+        self
+    }
 
-        // Register all component factories
-        self.component_registry
-            .insert("project".to_string(), Box::new(ProjectComponentFactory));
-        self.component_registry
-            .insert("model".to_string(), Box::new(ModelComponentFactory));
-        self.component_registry
-            .insert("branch".to_string(), Box::new(BranchComponentFactory));
-        self.component_registry
-            .insert("tokens".to_string(), Box::new(TokensComponentFactory));
-        self.component_registry
-            .insert("status".to_string(), Box::new(StatusComponentFactory));
-        self.component_registry
-            .insert("usage".to_string(), Box::new(UsageComponentFactory));
-        self.component_registry.insert(
-            "rate_limit".to_string(),
-            Box::new(RateLimitComponentFactory),
-        );
+    /// Component factories used by this generator
+    #[must_use]
+    pub const fn registry(&self) -> &ComponentRegistry {
+        &self.component_registry
     }
 
     fn refresh_multiline_renderer(&mut self) {
@@ -190,17 +291,34 @@ impl StatuslineGenerator {
 
     /// Apply a preset configuration
     fn apply_preset(&mut self, preset: &str) {
-        // Parse preset string (e.g., "PMBTURS" -> ["P", "M", "B", "T", "U", "R", "S"])
-        let component_map = Self::parse_preset(preset);
+        // Parse preset string (e.g., "PMBTURS" or "project,model,tokens")
+        let component_map = self
+            .component_registry
+            .try_parse_preset(preset)
+            .unwrap_or_else(|err| {
+                eprintln!("[statusline] preset: {err}");
+                self.component_registry.parse_preset(preset)
+            });
 
         // Update config.components.order based on preset
         if let Some(ref mut config) = Arc::get_mut(&mut self.config) {
             config.components.order = component_map;
         }
+        self.component_cache.clear();
 
         self.refresh_multiline_renderer();
     }
 
+    /// Bind the letters of `preset_mapping` to registered components
+    fn apply_preset_mapping(&mut self) {
+        let errors = self
+            .component_registry
+            .apply_preset_mapping(&self.config.preset_mapping);
+        for err in errors {
+            eprintln!("[statusline] preset_mapping: {err}");
+        }
+    }
+
     /// Apply preset defined in configuration if present
     fn apply_config_preset(&mut self) {
         if self.config.components.order.is_empty() {
@@ -212,25 +330,32 @@ impl StatuslineGenerator {
         self.refresh_multiline_renderer();
     }
 
-    /// Parse preset string into component order
-    fn parse_preset(preset: &str) -> Vec<String> {
-        let mut seen = HashSet::new();
+    /// Parse the transcript once for all components of this render.
+    fn analyze_transcript(&mut self, input: &InputData) -> Option<Arc<TranscriptAnalysis>> {
+        let path = Path::new(input.transcript_path.as_deref()?);
+        if let Some(cached) = self
+            .transcript_cache
+            .as_ref()
+            .filter(|cached| cached.is_fresh(path))
+        {
+            return Some(Arc::clone(cached));
+        }
 
-        preset
-            .chars()
-            .filter_map(|c| match c.to_ascii_uppercase() {
-                'P' => Some("project"),
-                'M' => Some("model"),
-                'B' => Some("branch"),
-                'T' => Some("tokens"),
-                'U' => Some("usage"),
-                'R' => Some("rate_limit"),
-                'S' => Some("status"),
-                _ => None,
-            })
-            .filter(|name| seen.insert(*name))
-            .map(std::string::ToString::to_string)
-            .collect()
+        let analysis = TranscriptAnalysis::load(path).map(Arc::new);
+        self.transcript_cache.clone_from(&analysis);
+        analysis
+    }
+
+    const fn capabilities_key(
+        terminal: &TerminalCapabilities,
+    ) -> (ColorSupport, bool, bool, GraphicsProtocol) {
+        // supports_progress 只影响整行输出,不影响组件渲染
+        (
+            terminal.color_support,
+            terminal.supports_emoji,
+            terminal.supports_nerd_font,
+            terminal.graphics,
+        )
     }
 
     /// Check if update should be performed based on throttling
@@ -256,31 +381,29 @@ impl StatuslineGenerator {
         }
     }
 
+    /// Storage used when none was given via [`Self::with_storage`]: the
+    /// on-disk session storage
+    #[cfg(feature = "storage")]
+    fn default_storage(&self) -> Arc<dyn StorageHandle> {
+        Arc::new(self.storage_context.clone())
+    }
+
+    /// Without the `storage` feature components get an empty in-memory storage
+    #[cfg(not(feature = "storage"))]
+    #[allow(clippy::unused_self)]
+    fn default_storage(&self) -> Arc<dyn StorageHandle> {
+        Arc::new(MemoryStorage::new())
+    }
+
     /// Generate the statusline
     /// # Errors
     ///
-    /// Returns an error if component rendering fails or if required
-    /// configuration initialization steps cannot complete successfully.
-    pub async fn generate(&mut self, input_data: InputData) -> Result<String> {
-        // Preview mode(TUI 编辑器)完全跳过任何持久化副作用:
-        // 1. `ensure_storage_ready` 会把 mock 的 project_id 注册成全局状态,
-        //    再初始化 storage 子系统,会在 `~/.claude/.../sessions/` 下建目录;
-        // 2. `update_session_snapshot` 会把合成的 mock InputData 落盘成真正的
-        //    session snapshot,污染用户真实的 conversation 使用量/成本数据。
-        // 两者都不是渲染本身必须的,preview 只需要纯粹的 "这份 config 渲染出来
-        // 长什么样",所以直接短路。
-        if !self.preview_mode {
-            self.ensure_storage_ready(&input_data).await?;
-
-            if let Ok(snapshot_value) = serde_json::to_value(&input_data) {
-                if let Err(err) = storage::update_session_snapshot(&snapshot_value).await {
-                    // Only log unexpected errors; missing session ID is expected in some scenarios
-                    if !err.to_string().contains("No session ID found") {
-                        eprintln!("[statusline] failed to update session snapshot: {err}");
-                    }
-                }
-            }
-        }
+    /// Returns [`StatuslineError::Storage`] when session storage cannot be
+    /// initialized and [`StatuslineError::Render`] when component or theme
+    /// rendering fails.
+    pub async fn generate(&mut self, input_data: InputData) -> StatuslineResult<String> {
+        let started = tokio::time::Instant::now();
+        self.persist_session(&input_data).await?;
 
         if !self.should_update() {
             if let Some(ref last_result) = self.last_result {
@@ -296,21 +419,54 @@ impl StatuslineGenerator {
         // 否则就算 generator 这层已经不写 session snapshot,组件里
         // `storage::get_*` 的 `StorageManager::new()` 仍会在用户真实目录
         // 下 `ensure_directories()`,一样算副作用。
+        let transcript = self.analyze_transcript(&input_data);
         let context = RenderContext {
             input: Arc::new(input_data),
             config: self.config.clone(),
             terminal: capabilities,
             preview_mode: self.preview_mode,
+            transcript,
+            storage: Some(
+                self.storage
+                    .clone()
+                    .unwrap_or_else(|| self.default_storage()),
+            ),
         };
 
         // Render components
-        let component_results = self.render_components(&context).await?;
+        self.load_warm_start(&context).await;
+        let mut component_results = self
+            .render_components(&context, started)
+            .await
+            .map_err(StatuslineError::Render)?;
+        self.save_warm_start(&context).await;
+        self.resolve_color_roles(&mut component_results);
+
+        let idle_config = &self.config.style.idle;
+        let idle = idle_duration(
+            idle_config,
+            context
+                .transcript
+                .as_ref()
+                .and_then(|transcript| transcript.modified),
+            SystemTime::now(),
+        );
+        if !self.preview_mode {
+            self.run_hooks(&context, &component_results, idle).await;
+        }
+        if let Some(idle) = idle.filter(|_| idle_config.show_segment) {
+            component_results.insert(0, idle_segment(idle_config, idle));
+        }
+        secure_segments(
+            &mut component_results,
+            &self.config.style,
+            &self.config.theme,
+        );
 
         // Apply theme rendering
-        let colors = self.extract_component_colors(&component_results);
         let main_line = self
-            .theme_renderer
-            .render(&component_results, &colors, &context)?;
+            .render_main_line(&component_results, &context)
+            .map_err(StatuslineError::Render)?;
 
         // Render multiline extensions
         let extension_result = self
@@ -322,6 +478,7 @@ impl StatuslineGenerator {
         if !main_line.is_empty() {
             lines.push(main_line);
         }
+        lines.extend(Self::render_detail_lines(&component_results, &context));
 
         if extension_result.success {
             lines.extend(extension_result.lines);
@@ -329,8 +486,26 @@ impl StatuslineGenerator {
             eprintln!("[statusline] multiline render failed: {err}");
         }
 
-        let result = lines.join("\n");
+        lines = frame_lines(
+            lines,
+            &self.config.style.frame,
+            Self::colors_enabled(&context).then_some(context.terminal.color_support),
+        );
 
+        if idle.is_some() && self.config.style.idle.dim && Self::colors_enabled(&context) {
+            lines = lines.iter().map(|line| dim_line(line)).collect();
+        }
+        lines = self.limit_output(lines);
+
+        let mut result = lines.join("\n");
+        self.record_history(&context, &result).await;
+        result = format!(
+            "{}{result}",
+            self.terminal_sequences(&component_results, &context)
+        );
+        if self.config.terminal.ascii_only {
+            result = to_ascii(&result);
+        }
         // Cache result
         if !self.disable_cache {
             self.last_result = Some(result.clone());
@@ -339,6 +514,311 @@ impl StatuslineGenerator {
         Ok(result)
     }
 
+    /// Initialize session storage and record the input as the session
+    /// snapshot
+    #[cfg(feature = "storage")]
+    async fn persist_session(&mut self, input_data: &InputData) -> StatuslineResult<()> {
+        // Preview mode(TUI 编辑器)完全跳过任何持久化副作用:
+        // 1. `ensure_storage_ready` 会把 mock 的 project_id 注册成全局状态,
+        //    再初始化 storage 子系统,会在 `~/.claude/.../sessions/` 下建目录;
+        // 2. `update_session_snapshot` 会把合成的 mock InputData 落盘成真正的
+        //    session snapshot,污染用户真实的 conversation 使用量/成本数据。
+        // 两者都不是渲染本身必须的,preview 只需要纯粹的 "这份 config 渲染出来
+        // 长什么样",所以直接短路。
+        if self.preview_mode {
+            return Ok(());
+        }
+        self.ensure_storage_ready(input_data)
+            .await
+            .map_err(StatuslineError::Storage)?;
+
+        if let Ok(snapshot_value) = serde_json::to_value(input_data) {
+            if let Err(err) = self
+                .storage_context
+                .update_session_snapshot(&snapshot_value)
+                .await
+            {
+                // Only log unexpected errors; missing session ID is expected in some scenarios
+                if !err.to_string().contains("No session ID found") {
+                    eprintln!("[statusline] failed to update session snapshot: {err}");
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Builds without the `storage` feature persist nothing
+    #[cfg(not(feature = "storage"))]
+    #[allow(clippy::unused_async)]
+    async fn persist_session(&self, _input_data: &InputData) -> StatuslineResult<()> {
+        Ok(())
+    }
+
+    /// Whether `performance.warm_start` applies to this render
+    fn warm_start_enabled(&self, context: &RenderContext) -> bool {
+        cfg!(feature = "storage")
+            && !self.preview_mode
+            && self.config.performance.warm_start
+            && context.input.session_id.is_some()
+    }
+
+    /// Load the session's warm-start outputs (`performance.warm_start`), the
+    /// fallback of components without previous output in this process
+    #[cfg(feature = "storage")]
+    async fn load_warm_start(&mut self, context: &RenderContext) {
+        self.warm_outputs.clear();
+        if !self.warm_start_enabled(context) {
+            return;
+        }
+        let Some(session_id) = context.input.session_id.as_deref() else {
+            return;
+        };
+        let max_age = Duration::from_secs(self.config.performance.warm_start_max_age_secs);
+        match self
+            .storage_context
+            .warm_start_outputs(session_id, max_age)
+            .await
+        {
+            Ok(outputs) => self.warm_outputs = outputs,
+            Err(err) if self.config.debug => {
+                eprintln!("[statusline] failed to load warm-start outputs: {err}");
+            }
+            Err(_) => {}
+        }
+    }
+
+    /// Persist the outputs refreshed by this render for the next invocation
+    #[cfg(feature = "storage")]
+    async fn save_warm_start(&mut self, context: &RenderContext) {
+        if !self.warm_start_enabled(context) {
+            return;
+        }
+        let Some(session_id) = context.input.session_id.as_deref() else {
+            return;
+        };
+        let outputs = std::mem::take(&mut self.warm_outputs);
+        if let Err(err) = self
+            .storage_context
+            .save_warm_start(session_id, outputs)
+            .await
+        {
+            if self.config.debug {
+                eprintln!("[statusline] failed to save warm-start outputs: {err}");
+            }
+        }
+    }
+
+    /// Warm-start outputs live in the session directory; builds without the
+    /// `storage` feature have none
+    #[cfg(not(feature = "storage"))]
+    #[allow(clippy::unused_async)]
+    async fn load_warm_start(&mut self, _context: &RenderContext) {
+        self.warm_outputs.clear();
+    }
+
+    #[cfg(not(feature = "storage"))]
+    #[allow(clippy::unused_async)]
+    async fn save_warm_start(&mut self, _context: &RenderContext) {
+        self.warm_outputs.clear();
+    }
+
+    /// Cut lines to `style.max_output_width` / `style.max_output_bytes`
+    fn limit_output(&self, lines: Vec<String>) -> Vec<String> {
+        let style = &self.config.style;
+        if style.max_output_width == 0 && style.max_output_bytes == 0 {
+            return lines;
+        }
+        lines
+            .into_iter()
+            .map(|line| {
+                // 先转写再截断:ASCII 替换可能比原字符更宽
+                let line = if self.config.terminal.ascii_only {
+                    to_ascii(&line)
+                } else {
+                    line
+                };
+                truncate_rendered(&line, style.max_output_width, style.max_output_bytes)
+            })
+            .collect()
+    }
+
+    /// Append the rendered lines to the session's history log
+    /// (`storage.historyLog`)
+    #[cfg(feature = "storage")]
+    async fn record_history(&self, context: &RenderContext, rendered: &str) {
+        if self.preview_mode {
+            return;
+        }
+        let Some(session_id) = context.input.session_id.as_deref() else {
+            return;
+        };
+        if let Err(err) = self
+            .storage_context
+            .append_history(session_id, &strip_sgr(rendered))
+            .await
+        {
+            eprintln!("[statusline] failed to append history log: {err}");
+        }
+    }
+
+    #[cfg(not(feature = "storage"))]
+    #[allow(clippy::unused_async)]
+    async fn record_history(&self, _context: &RenderContext, _rendered: &str) {}
+
+    /// Record the session status transitions and fire the `[hooks]` commands
+    /// whose condition just became true
+    async fn run_hooks(
+        &self,
+        context: &RenderContext,
+        components: &[ComponentOutput],
+        idle: Option<Duration>,
+    ) {
+        let config = &self.config.hooks;
+        // 没有持久化存储时无法去重,session_start 等事件会在每次渲染重复触发
+        if !cfg!(feature = "storage") && self.storage.is_none() {
+            return;
+        }
+        let Some(session_id) = context.input.session_id.clone() else {
+            return;
+        };
+        let component = |name: &str| {
+            components
+                .iter()
+                .find(|component| component.component_name.as_deref() == Some(name))
+        };
+        let tokens = component("tokens");
+        let observation = HookObservation {
+            session_id,
+            project_dir: context.input.project_dir().map(str::to_string),
+            model: context
+                .input
+                .model
+                .as_ref()
+                .and_then(|model| model.display_name.clone().or_else(|| model.id.clone())),
+            cost_usd: context
+                .input
+                .cost
+                .as_ref()
+                .and_then(|cost| cost.total_cost_usd),
+            context_percent: tokens.and_then(|tokens| tokens.progress),
+            context_critical: tokens.is_some_and(|tokens| tokens.severity == Severity::Critical),
+            error_detected: component("status")
+                .is_some_and(|status| status.severity == Severity::Critical),
+            idle,
+        };
+        if let Err(err) =
+            hooks::dispatch(config, context.storage(), &observation, chrono::Utc::now()).await
+        {
+            eprintln!("[statusline] failed to run hooks: {err:#}");
+        }
+    }
+
+    /// Escape sequences written ahead of the statusline: the window title
+    /// (`terminal.set_title`) and the `OSC 9;4` progress report
+    fn terminal_sequences(
+        &self,
+        components: &[ComponentOutput],
+        context: &RenderContext,
+    ) -> String {
+        let mut sequences = String::new();
+        if self.config.terminal.set_title {
+            let title = title_summary(components, &self.config.terminal.title_components);
+            if !title.is_empty() {
+                sequences.push_str(&osc_title(&title));
+            }
+        }
+        if context.terminal.supports_progress {
+            let progress = components
+                .iter()
+                .find(|component| component.progress.is_some());
+            sequences.push_str(&osc_progress(
+                progress.and_then(|component| component.progress),
+                progress
+                    .map(|component| component.severity)
+                    .unwrap_or_default(),
+            ));
+        }
+        sequences
+    }
+
+    /// Theme-render the main line, applying the `style.severity_rules` entry
+    /// matching the most severe component state.
+    fn render_main_line(
+        &self,
+        components: &[ComponentOutput],
+        context: &RenderContext,
+    ) -> Result<String> {
+        let mut colors = self.extract_component_colors(components);
+        if Theme::from_name(&self.config.theme) != Theme::Classic {
+            apply_gradient(
+                &mut colors,
+                components,
+                &self.config.style.background_gradient,
+            );
+        }
+        let Some(rule) = select_rule(&self.config.style.severity_rules, components) else {
+            return self.theme_renderer.render(components, &colors, context);
+        };
+
+        let mut themed = context.clone();
+        if let Some(separator_color) = &rule.separator_color {
+            let mut config = (*context.config).clone();
+            config.style.separator_color.clone_from(separator_color);
+            themed.config = Arc::new(config);
+        }
+
+        let Some(tint) = rule.tint.as_deref() else {
+            return self.theme_renderer.render(components, &colors, &themed);
+        };
+        if Theme::from_name(&self.config.theme) != Theme::Classic {
+            // powerline / capsule:色调混入每段背景
+            for color in &mut colors {
+                *color = tint_color(color, tint);
+            }
+            return self.theme_renderer.render(components, &colors, &themed);
+        }
+
+        let line = self.theme_renderer.render(components, &colors, &themed)?;
+        Ok(if Self::colors_enabled(context) && !line.is_empty() {
+            tint_line(&line, tint, context.terminal.color_support)
+        } else {
+            line
+        })
+    }
+
+    /// Whether the terminal and `style.enable_colors` allow ANSI styling
+    fn colors_enabled(context: &RenderContext) -> bool {
+        context.terminal.supports_colors()
+            && context
+                .config
+                .style
+                .enable_colors
+                .is_enabled(context.terminal.supports_colors())
+    }
+
+    /// Secondary lines attached by components (e.g. long status error details),
+    /// placed between the main line and the multiline widget rows.
+    fn render_detail_lines(components: &[ComponentOutput], context: &RenderContext) -> Vec<String> {
+        let supports_colors = Self::colors_enabled(context);
+
+        components
+            .iter()
+            .filter(|component| component.visible)
+            .flat_map(|component| {
+                let color_seq = component
+                    .text_color
+                    .as_deref()
+                    .filter(|_| supports_colors)
+                    .and_then(|color| ansi_fg_with_support(color, context.terminal.color_support));
+                component.detail_lines.iter().map(move |line| {
+                    color_seq
+                        .as_ref()
+                        .map_or_else(|| line.clone(), |seq| format!("{seq}{line}{ANSI_RESET}"))
+                })
+            })
+            .collect()
+    }
+
     fn extract_component_colors(&self, components: &[ComponentOutput]) -> Vec<String> {
         let mut colors = Vec::with_capacity(components.len());
         let theme_palette = match self.config.theme.as_str() {
@@ -359,14 +839,38 @@ impl StatuslineGenerator {
                         .find(|(component_name, _)| *component_name == name)
                         .map(|(_, color)| (*color).to_string())
                 })
-                .unwrap_or_else(|| self.component_config_color(name));
-
-            colors.push(color);
+                .unwrap_or_else(|| {
+                    if BUILTIN_COMPONENTS.contains(&name) {
+                        return self.component_config_color(name);
+                    }
+                    // 注册表中的自定义组件没有配置段,使用组件自身的图标颜色
+                    component
+                        .icon_color
+                        .clone()
+                        .filter(|color| !color.is_empty())
+                        .unwrap_or_else(|| "blue".to_string())
+                });
+
+            colors.push(self.config.palette.resolve(&color));
         }
 
         colors
     }
 
+    /// Replace semantic role names (`ok`, `danger`, ...) left in component
+    /// colors by their palette colors
+    fn resolve_color_roles(&self, components: &mut [ComponentOutput]) {
+        let palette = &self.config.palette;
+        for component in components {
+            for color in [&mut component.icon_color, &mut component.text_color]
+                .into_iter()
+                .flatten()
+            {
+                *color = palette.resolve(color);
+            }
+        }
+    }
+
     fn component_config_color(&self, name: &str) -> String {
         match name {
             "project" => self.config.components.project.base.icon_color.clone(),
@@ -376,6 +880,14 @@ impl StatuslineGenerator {
             "usage" => self.config.components.usage.base.icon_color.clone(),
             "rate_limit" => self.config.components.rate_limit.base.icon_color.clone(),
             "status" => self.config.components.status.base.icon_color.clone(),
+            "hook" => self.config.components.hook.base.icon_color.clone(),
+            "field" => self.config.components.field.base.icon_color.clone(),
+            "text" => self.config.components.text.base.icon_color.clone(),
+            "trust" => self.config.components.trust.base.icon_color.clone(),
+            "quota" => self.config.components.quota.base.icon_color.clone(),
+            "session" => self.config.components.session.base.icon_color.clone(),
+            "ticket" => self.config.components.ticket.base.icon_color.clone(),
+            "repo" => self.config.components.repo.base.icon_color.clone(),
             other => {
                 eprintln!(
                     "[statusline] unknown component '{other}' when resolving theme colors, fallback to blue"
@@ -387,50 +899,100 @@ impl StatuslineGenerator {
 
     /// Detect terminal capabilities
     fn detect_terminal_capabilities(&self) -> TerminalCapabilities {
-        let caps = self.terminal_detector.detect(
+        let mut caps = self.terminal_detector.detect(
             &self.config.style.enable_colors,
             &self.config.style.enable_emoji,
             &self.config.style.enable_nerd_font,
             self.config.terminal.force_nerd_font,
             self.config.terminal.force_emoji,
-            self.config.terminal.force_text,
+            self.config.terminal.text_only(),
         );
+        if !self.config.terminal.text_only() {
+            caps.graphics = detect_protocol(&self.config.terminal.graphics_protocol);
+            caps.supports_progress = detect_progress_support(&self.config.terminal.progress_osc);
+        }
 
         if self.config.debug {
             eprintln!("[调试] 终端能力检测结果:");
             eprintln!("  - color_support: {:?}", caps.color_support);
             eprintln!("  - supports_emoji: {}", caps.supports_emoji);
             eprintln!("  - supports_nerd_font: {}", caps.supports_nerd_font);
+            eprintln!("  - graphics: {:?}", caps.graphics);
+            eprintln!("  - supports_progress: {}", caps.supports_progress);
             eprintln!("  - TERM_PROGRAM: {:?}", std::env::var("TERM_PROGRAM"));
         }
 
         caps
     }
 
-    /// Render all enabled components
-    async fn render_components(&self, context: &RenderContext) -> Result<Vec<ComponentOutput>> {
-        let mut results = Vec::new();
+    /// Pad the text to the component's `min_width`, so changing values do not
+    /// shift the rest of the line
+    fn apply_min_width(
+        component: &dyn Component,
+        context: &RenderContext,
+        output: &mut ComponentOutput,
+    ) {
+        if let Some(base) = component
+            .base_config(context)
+            .filter(|base| base.min_width > 0)
+        {
+            output.text = pad_to_width(&output.text, base.min_width, base.align);
+        }
+    }
 
-        // Get component order from configuration or use default
-        let default_order = vec![
-            "project".to_string(),
-            "model".to_string(),
-            "branch".to_string(),
-            "tokens".to_string(),
-            "usage".to_string(),
-            "rate_limit".to_string(),
-            "status".to_string(),
-        ];
-
-        let component_order = if self.config.components.order.is_empty() {
-            default_order
-        } else {
-            self.config.components.order.clone()
-        };
+    /// Index of the `components.groups` entry containing `name`
+    fn group_of(&self, name: &str) -> Option<usize> {
+        self.config
+            .components
+            .groups
+            .iter()
+            .position(|group| group.iter().any(|member| member == name))
+    }
+
+    /// Component order from configuration, or the default order
+    fn component_order(&self) -> Vec<String> {
+        if !self.config.components.order.is_empty() {
+            return self.config.components.order.clone();
+        }
+        [
+            "project",
+            "model",
+            "branch",
+            "tokens",
+            "usage",
+            "rate_limit",
+            "status",
+        ]
+        .map(str::to_string)
+        .to_vec()
+    }
 
-        // Render each component in order
+    /// Render all enabled components
+    ///
+    /// Components are independent (git, storage, transcript), so every one
+    /// that is not served from the cache renders concurrently; outputs are then
+    /// assembled in the configured order. A component exceeding its timeout is
+    /// left out of this refresh; one still running when `performance.max_total_ms`
+    /// (counted from `started`) runs out shows its previous output instead, if
+    /// any. Futures share one task, so synchronous work inside `render` only
+    /// overlaps (and times out) if it goes through `spawn_blocking` like the
+    /// storage helpers do.
+    async fn render_components(
+        &mut self,
+        context: &RenderContext,
+        started: tokio::time::Instant,
+    ) -> Result<Vec<ComponentOutput>> {
+        let component_order = self.component_order();
+
+        // Resolve components in order, serving unchanged ones from the cache
+        let mut slots = Vec::new();
         let mut seen = HashSet::new();
-        for component_name in &component_order {
+        let mut pending_separator = None;
+        for component_name in component_order {
+            if let Some(separator) = separator_item(&component_name) {
+                pending_separator = Some(separator.to_string());
+                continue;
+            }
             if !seen.insert(component_name.clone()) {
                 continue;
             }
@@ -444,36 +1006,157 @@ impl StatuslineGenerator {
                 continue;
             }
 
-            let mut output = component.render(context).await;
+            let key = (!self.disable_cache)
+                .then(|| component.cache_key(context))
+                .flatten()
+                .map(|key| cache_key_of(&(key, Self::capabilities_key(&context.terminal))));
+
+            let previous = self.component_cache.get(&component_name);
+            let cached = key.and_then(|key| {
+                previous
+                    .filter(|cached| cached.key == Some(key))
+                    .map(|cached| cached.output.clone())
+            });
+            let stale = previous
+                .map(|cached| &cached.output)
+                .or_else(|| {
+                    self.warm_outputs
+                        .get(&component_name)
+                        .map(|warm| &warm.output)
+                })
+                .filter(|_| cached.is_none())
+                .cloned();
+
+            slots.push(RenderSlot {
+                name: component_name,
+                component,
+                key,
+                cached,
+                stale,
+                separator: pending_separator.take(),
+            });
+        }
+
+        let default_timeout = self.config.components.render_timeout_ms;
+        let budget_ms = self.config.performance.max_total_ms;
+        let deadline = (budget_ms > 0).then(|| started + Duration::from_millis(budget_ms));
+        let debug = self.config.debug;
+        let rendered = join_all(
+            slots
+                .iter()
+                .map(|slot| Self::render_slot(slot, context, default_timeout, deadline, debug)),
+        )
+        .await;
+
+        let mut results = Vec::new();
+        let rendered_at = chrono::Utc::now();
+        // 分隔符跟随下一个可见组件:分组的首个组件隐藏时,由组内下一个组件承接
+        let mut pending_separator = None;
+        for (slot, outcome) in slots.into_iter().zip(rendered) {
+            if slot.separator.is_some() {
+                pending_separator = slot.separator;
+            }
+            let (mut output, fresh, current) = match outcome {
+                SlotOutcome::Fresh(output) => (output, true, true),
+                SlotOutcome::Cached(output) => (output, false, true),
+                SlotOutcome::OverBudget(Some(output)) => (output, false, false),
+                SlotOutcome::TimedOut | SlotOutcome::OverBudget(None) => continue,
+            };
+            if current && self.warm_start_enabled(context) {
+                self.warm_outputs.insert(
+                    slot.name.clone(),
+                    WarmOutput {
+                        rendered_at,
+                        output: output.clone(),
+                    },
+                );
+            }
+
+            if fresh && !self.disable_cache {
+                self.component_cache.insert(
+                    slot.name.clone(),
+                    CachedOutput {
+                        key: slot.key,
+                        output: output.clone(),
+                    },
+                );
+            }
+
             if !output.visible {
                 continue;
             }
 
-            output.set_component_name(component_name.clone());
+            Self::apply_min_width(slot.component.as_ref(), context, &mut output);
+            output.group = self.group_of(&slot.name);
+            output.set_component_name(slot.name);
+            output.separator = pending_separator.take().filter(|_| !results.is_empty());
             results.push(output);
         }
 
         Ok(results)
     }
 
-    async fn ensure_storage_ready(&mut self, input_data: &InputData) -> Result<()> {
-        if let Some(transcript) = input_data.transcript_path.as_deref() {
-            ProjectResolver::set_global_project_id_from_transcript(Some(transcript));
+    /// Render one slot within its timeout and the render budget
+    async fn render_slot(
+        slot: &RenderSlot,
+        context: &RenderContext,
+        default_timeout: u64,
+        deadline: Option<tokio::time::Instant>,
+        debug: bool,
+    ) -> SlotOutcome {
+        if let Some(output) = slot.cached.clone() {
+            return SlotOutcome::Cached(output);
         }
 
-        let fallback_path = input_data.project_dir().or(input_data.cwd.as_deref());
+        let timeout_ms = slot
+            .component
+            .base_config(context)
+            .and_then(|base| base.timeout_ms)
+            .unwrap_or(default_timeout);
+        let timeout = (timeout_ms > 0)
+            .then(|| tokio::time::Instant::now() + Duration::from_millis(timeout_ms));
+        // 先到期的一方决定结果:组件超时直接跳过,总预算耗尽则沿用上一次输出
+        let (limit, over_budget) = match (timeout, deadline) {
+            (Some(timeout), Some(deadline)) if deadline < timeout => (Some(deadline), true),
+            (Some(timeout), _) => (Some(timeout), false),
+            (None, deadline) => (deadline, deadline.is_some()),
+        };
+        let Some(limit) = limit else {
+            return SlotOutcome::Fresh(slot.component.render(context).await);
+        };
 
-        let project_id = ProjectResolver::get_global_project_id(fallback_path);
-        ProjectResolver::set_global_project_id(Some(&project_id));
+        if let Ok(output) = tokio::time::timeout_at(limit, slot.component.render(context)).await {
+            return SlotOutcome::Fresh(output);
+        }
+        if over_budget {
+            if debug {
+                eprintln!(
+                    "[statusline] component '{}' ran past the render budget",
+                    slot.name
+                );
+            }
+            return SlotOutcome::OverBudget(slot.stale.clone());
+        }
+        if debug {
+            eprintln!(
+                "[statusline] component '{}' timed out after {timeout_ms}ms",
+                slot.name
+            );
+        }
+        SlotOutcome::TimedOut
+    }
 
-        if !self.storage_initialized
-            || self.active_project_id.as_deref() != Some(project_id.as_str())
-        {
-            storage::initialize_storage_with_settings(
-                Some(project_id.clone()),
-                &self.config.storage,
-            )
-            .await?;
+    #[cfg(feature = "storage")]
+    async fn ensure_storage_ready(&mut self, input_data: &InputData) -> Result<()> {
+        let fallback_path = input_data.project_dir().or(input_data.cwd.as_deref());
+        let project_id = self
+            .storage_context
+            .resolve_project_id(input_data.transcript_path.as_deref(), fallback_path);
+
+        if !self.storage_initialized || self.active_project_id.as_ref() != Some(&project_id) {
+            self.storage_context
+                .set_project_id(Some(project_id.to_string()));
+            self.storage_context.initialize().await?;
             self.storage_initialized = true;
             self.active_project_id = Some(project_id);
         }
@@ -490,36 +1173,55 @@ impl StatuslineGenerator {
     /// Update configuration
     pub fn update_config(&mut self, config: Config) {
         self.config = Arc::new(config);
+        #[cfg(feature = "storage")]
+        {
+            self.storage_context = StorageContext::new(&self.config.storage);
+            self.storage_initialized = false;
+        }
         self.apply_config_preset();
         self.theme_renderer = create_theme_renderer(&self.config.theme);
         self.refresh_multiline_renderer();
         // Clear cache to force re-render
         self.last_result = None;
+        self.component_cache.clear();
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::components::ComponentFactory;
+    use crate::config::BaseComponentConfig;
+    use std::sync::atomic::{AtomicUsize, Ordering};
 
     #[test]
     fn test_parse_preset() {
-        let order = StatuslineGenerator::parse_preset("PMBT");
+        let registry = ComponentRegistry::default();
+        let order = registry.parse_preset("PMBT");
         assert_eq!(order, vec!["project", "model", "branch", "tokens"]);
 
-        let order = StatuslineGenerator::parse_preset("TBMP");
+        let order = registry.parse_preset("TBMP");
         assert_eq!(order, vec!["tokens", "branch", "model", "project"]);
 
         // Test with lowercase and mixed case
-        let order = StatuslineGenerator::parse_preset("pmBT");
+        let order = registry.parse_preset("pmBT");
         assert_eq!(order, vec!["project", "model", "branch", "tokens"]);
 
         // Test with invalid characters
-        let order = StatuslineGenerator::parse_preset("PM-BT");
+        let order = registry.parse_preset("PM-BT");
         assert_eq!(order, vec!["project", "model", "branch", "tokens"]);
 
-        let order = StatuslineGenerator::parse_preset("UR");
+        let order = registry.parse_preset("UR");
         assert_eq!(order, vec!["usage", "rate_limit"]);
+
+        let order = registry.parse_preset("SH");
+        assert_eq!(order, vec!["status", "hook"]);
+
+        let order = registry.parse_preset("MFL");
+        assert_eq!(order, vec!["model", "field", "text"]);
+
+        let order = registry.parse_preset("PD");
+        assert_eq!(order, vec!["project", "trust"]);
     }
 
     #[test]
@@ -560,4 +1262,449 @@ mod tests {
         assert_eq!(generator.update_interval, Duration::from_millis(300));
         assert!(!generator.disable_cache);
     }
+
+    struct CountingFactory(Arc<AtomicUsize>);
+
+    struct CountingComponent(Arc<AtomicUsize>);
+
+    #[async_trait::async_trait]
+    impl Component for CountingComponent {
+        fn name(&self) -> &'static str {
+            "counting"
+        }
+
+        fn is_enabled(&self, _ctx: &RenderContext) -> bool {
+            true
+        }
+
+        async fn render(&self, ctx: &RenderContext) -> ComponentOutput {
+            self.0.fetch_add(1, Ordering::SeqCst);
+            ComponentOutput::new(ctx.input.session_id.clone().unwrap_or_default())
+        }
+
+        fn base_config(&self, _ctx: &RenderContext) -> Option<&BaseComponentConfig> {
+            None
+        }
+
+        fn cache_key(&self, ctx: &RenderContext) -> Option<u64> {
+            Some(cache_key_of(&ctx.input.session_id))
+        }
+    }
+
+    impl ComponentFactory for CountingFactory {
+        fn create(&self, _config: &Config) -> Box<dyn Component> {
+            Box::new(CountingComponent(Arc::clone(&self.0)))
+        }
+
+        fn name(&self) -> &'static str {
+            "counting"
+        }
+    }
+
+    fn counting_generator(disable_cache: bool) -> (StatuslineGenerator, Arc<AtomicUsize>) {
+        let mut config = Config::default();
+        config.components.order = vec!["counting".to_string()];
+        let options = GeneratorOptions {
+            update_throttling: false,
+            disable_cache,
+            preview_mode: true,
+            ..GeneratorOptions::default()
+        };
+        let mut generator = StatuslineGenerator::new(config, options);
+        let renders = Arc::new(AtomicUsize::new(0));
+        generator
+            .component_registry
+            .register(CountingFactory(Arc::clone(&renders)));
+        (generator, renders)
+    }
+
+    fn session_input(session_id: &str) -> InputData {
+        InputData {
+            session_id: Some(session_id.to_string()),
+            ..InputData::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_component_output_reused_while_inputs_unchanged() -> Result<()> {
+        let (mut generator, renders) = counting_generator(false);
+
+        let first = generator.generate(session_input("a")).await?;
+        let second = generator.generate(session_input("a")).await?;
+        assert_eq!(first, second);
+        assert_eq!(renders.load(Ordering::SeqCst), 1);
+
+        let third = generator.generate(session_input("b")).await?;
+        assert!(third.contains('b'), "third={third}");
+        assert_eq!(renders.load(Ordering::SeqCst), 2);
+        Ok(())
+    }
+
+    struct SleepyFactory {
+        name: &'static str,
+        delay_ms: u64,
+    }
+
+    struct SleepyComponent {
+        name: &'static str,
+        delay_ms: u64,
+    }
+
+    #[async_trait::async_trait]
+    impl Component for SleepyComponent {
+        fn name(&self) -> &'static str {
+            self.name
+        }
+
+        fn is_enabled(&self, _ctx: &RenderContext) -> bool {
+            true
+        }
+
+        async fn render(&self, _ctx: &RenderContext) -> ComponentOutput {
+            tokio::time::sleep(Duration::from_millis(self.delay_ms)).await;
+            ComponentOutput::new(self.name)
+        }
+
+        fn base_config(&self, _ctx: &RenderContext) -> Option<&BaseComponentConfig> {
+            None
+        }
+    }
+
+    impl ComponentFactory for SleepyFactory {
+        fn create(&self, _config: &Config) -> Box<dyn Component> {
+            Box::new(SleepyComponent {
+                name: self.name,
+                delay_ms: self.delay_ms,
+            })
+        }
+
+        fn name(&self) -> &'static str {
+            self.name
+        }
+    }
+
+    fn sleepy_generator(
+        components: &[(&'static str, u64)],
+        timeout_ms: u64,
+    ) -> StatuslineGenerator {
+        let mut config = Config::default();
+        config.components.order = components
+            .iter()
+            .map(|(name, _)| (*name).to_string())
+            .collect();
+        config.components.render_timeout_ms = timeout_ms;
+        config.performance.max_total_ms = 0;
+        let mut generator = StatuslineGenerator::new(config, GeneratorOptions::default());
+        for &(name, delay_ms) in components {
+            generator
+                .component_registry
+                .register(SleepyFactory { name, delay_ms });
+        }
+        generator
+    }
+
+    #[test]
+    fn test_preset_mapping_binds_registered_components() {
+        let mut registry = ComponentRegistry::default();
+        registry.register(SleepyFactory {
+            name: "weather",
+            delay_ms: 0,
+        });
+        let mut config = Config {
+            preset: Some("MW".to_string()),
+            ..Config::default()
+        };
+        config.components.order.clear();
+        config
+            .preset_mapping
+            .insert("w".to_string(), "weather".to_string());
+        // 与内置字母冲突的映射被忽略
+        config
+            .preset_mapping
+            .insert("P".to_string(), "weather".to_string());
+
+        let generator =
+            StatuslineGenerator::with_registry(config, GeneratorOptions::default(), registry);
+        assert_eq!(generator.config.components.order, ["model", "weather"]);
+        assert_eq!(
+            generator.registry().component_for_letter('P'),
+            Some("project")
+        );
+    }
+
+    fn plain_context(generator: &StatuslineGenerator) -> RenderContext {
+        RenderContext {
+            input: Arc::new(InputData::default()),
+            config: Arc::clone(&generator.config),
+            terminal: TerminalCapabilities::default(),
+            preview_mode: true,
+            transcript: None,
+            storage: None,
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_components_render_concurrently_in_configured_order() -> Result<()> {
+        let mut generator = sleepy_generator(&[("slow", 300), ("fast", 10), ("medium", 200)], 0);
+        let context = plain_context(&generator);
+
+        let started = tokio::time::Instant::now();
+        let outputs = generator
+            .render_components(&context, tokio::time::Instant::now())
+            .await?;
+        let elapsed = started.elapsed();
+
+        let texts: Vec<_> = outputs.iter().map(|output| output.text.as_str()).collect();
+        assert_eq!(texts, ["slow", "fast", "medium"]);
+        assert!(elapsed < Duration::from_millis(500), "elapsed={elapsed:?}");
+        Ok(())
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_component_exceeding_timeout_is_skipped() -> Result<()> {
+        let mut generator = sleepy_generator(&[("stuck", 5_000), ("quick", 10)], 100);
+        let context = plain_context(&generator);
+
+        let outputs = generator
+            .render_components(&context, tokio::time::Instant::now())
+            .await?;
+        let texts: Vec<_> = outputs.iter().map(|output| output.text.as_str()).collect();
+        assert_eq!(texts, ["quick"]);
+        Ok(())
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_render_budget_falls_back_to_previous_output() -> Result<()> {
+        let mut generator = sleepy_generator(&[("slow", 500), ("quick", 10)], 2_000);
+        Arc::make_mut(&mut generator.config)
+            .performance
+            .max_total_ms = 300;
+        let context = plain_context(&generator);
+
+        let started = tokio::time::Instant::now();
+        let outputs = generator.render_components(&context, started).await?;
+        let texts: Vec<_> = outputs.iter().map(|output| output.text.as_str()).collect();
+        assert_eq!(texts, ["quick"]);
+        assert!(started.elapsed() < Duration::from_millis(400));
+
+        // 上一次完整渲染的输出在预算耗尽时沿用
+        generator.component_cache.insert(
+            "slow".to_string(),
+            CachedOutput {
+                key: None,
+                output: ComponentOutput::new("slow (previous)"),
+            },
+        );
+        let outputs = generator
+            .render_components(&context, tokio::time::Instant::now())
+            .await?;
+        let texts: Vec<_> = outputs.iter().map(|output| output.text.as_str()).collect();
+        assert_eq!(texts, ["slow (previous)", "quick"]);
+        Ok(())
+    }
+
+    #[cfg(feature = "storage")]
+    #[tokio::test]
+    async fn test_warm_start_fills_in_only_missing_segments() -> Result<()> {
+        use crate::storage::StorageConfig;
+
+        let dir = tempfile::tempdir()?;
+        let storage = || {
+            StorageContext::with_config(StorageConfig {
+                storage_path: Some(dir.path().to_path_buf()),
+                ..StorageConfig::default()
+            })
+        };
+        let warm_generator = |components: &[(&'static str, u64)]| {
+            let mut generator = sleepy_generator(components, 0);
+            Arc::make_mut(&mut generator.config)
+                .performance
+                .max_total_ms = 100;
+            generator.storage_context = storage();
+            generator
+        };
+        let max_age = Duration::from_secs(600);
+
+        let complete = warm_generator(&[("quick", 0), ("slow", 0)])
+            .generate(session_input("s1"))
+            .await?;
+        assert!(complete.contains("slow"), "{complete}");
+        let slow_rendered_at = |outputs: &BTreeMap<String, WarmOutput>| {
+            outputs.get("slow").map(|warm| warm.rendered_at)
+        };
+        let first = slow_rendered_at(&storage().warm_start_outputs("s1", max_age).await?);
+        assert!(first.is_some());
+
+        // 新进程里 slow 超出预算:只有这一段用上一次的输出补上,其余组件是新结果
+        let mut cold = warm_generator(&[("quick", 0), ("slow", 5_000), ("added", 0)]);
+        let line = cold.generate(session_input("s1")).await?;
+        assert!(line.contains("slow"), "{line}");
+        assert!(line.contains("added"), "{line}");
+        // 补上的输出不刷新渲染时间,一直超时的组件到期后不再显示
+        let stored = storage().warm_start_outputs("s1", max_age).await?;
+        assert_eq!(slow_rendered_at(&stored), first);
+        assert!(stored.contains_key("added"));
+
+        let other = warm_generator(&[("quick", 0), ("slow", 5_000)])
+            .generate(session_input("s2"))
+            .await?;
+        assert!(!other.contains("slow"), "{other}");
+        Ok(())
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_order_separators_attach_to_next_visible_component() -> Result<()> {
+        let mut generator =
+            sleepy_generator(&[("a", 0), ("stuck", 5_000), ("b", 0), ("c", 0)], 100);
+        Arc::make_mut(&mut generator.config).components.order =
+            ["sep:X", "a", "space", "stuck", "b", "sep:│", "c", "sep:Y"]
+                .map(str::to_string)
+                .to_vec();
+        let context = plain_context(&generator);
+
+        let outputs = generator
+            .render_components(&context, tokio::time::Instant::now())
+            .await?;
+        let separators: Vec<_> = outputs
+            .iter()
+            .map(|output| (output.text.as_str(), output.separator.as_deref()))
+            .collect();
+        assert_eq!(
+            separators,
+            [("a", None), ("b", Some(" ")), ("c", Some("│"))]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_transcript_analysis_shared_until_file_changes() -> Result<()> {
+        use std::io::Write;
+
+        let mut file = tempfile::NamedTempFile::new()?;
+        writeln!(file, r#"{{"type":"user","message":{{"content":"hi"}}}}"#)?;
+        let input = InputData {
+            transcript_path: Some(file.path().to_string_lossy().into_owned()),
+            ..InputData::default()
+        };
+        let mut generator =
+            StatuslineGenerator::new(Config::default(), GeneratorOptions::default());
+
+        let first = generator.analyze_transcript(&input);
+        let second = generator.analyze_transcript(&input);
+        assert!(matches!((&first, &second), (Some(a), Some(b)) if Arc::ptr_eq(a, b)));
+
+        writeln!(file, r#"{{"type":"assistant","message":{{"content":[]}}}}"#)?;
+        let third = generator.analyze_transcript(&input);
+        assert_eq!(third.map(|analysis| analysis.entries().len()), Some(2));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_idle_session_shows_segment_and_dims_line() -> Result<()> {
+        let file = tempfile::NamedTempFile::new()?;
+        file.as_file()
+            .set_modified(SystemTime::now() - Duration::from_secs(15 * 60))?;
+
+        let (mut generator, _) = counting_generator(true);
+        let config = Arc::make_mut(&mut generator.config);
+        config.style.enable_colors = crate::config::AutoDetect::Bool(false);
+        config.style.idle.threshold_minutes = 10;
+        config.style.idle.show_segment = true;
+        let input = InputData {
+            transcript_path: Some(file.path().to_string_lossy().into_owned()),
+            ..session_input("a")
+        };
+
+        let line = generator.generate(input.clone()).await?;
+        assert!(line.starts_with("⏸ idle 15m"), "{line}");
+        assert!(!line.contains("\x1b[2m"));
+
+        Arc::make_mut(&mut generator.config)
+            .style
+            .idle
+            .threshold_minutes = 30;
+        let line = generator.generate(input).await?;
+        assert!(!line.contains("idle"), "{line}");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_output_limits_cut_long_lines() -> Result<()> {
+        let mut config = Config {
+            preset: Some("PB".to_string()),
+            theme: "powerline".to_string(),
+            ..Config::default()
+        };
+        config.style.enable_colors = crate::config::AutoDetect::Bool(true);
+        config.style.max_output_width = 40;
+        config.style.max_output_bytes = 300;
+        config.components.branch.max_length = 0;
+        let mut generator = StatuslineGenerator::new(
+            config,
+            GeneratorOptions {
+                disable_cache: true,
+                preview_mode: true,
+                ..GeneratorOptions::default()
+            },
+        );
+        let input = InputData {
+            cwd: Some(format!("/tmp/{}", "p".repeat(5_000))),
+            git_branch: Some("b".repeat(5_000)),
+            ..InputData::default()
+        };
+
+        let line = generator.generate(input).await?;
+        assert!(crate::utils::visible_width(&line) <= 40, "{line:?}");
+        assert!(line.len() <= 300, "{}", line.len());
+        assert!(line.ends_with("\x1b[0m"), "{line:?}");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_min_width_pads_component_text() -> Result<()> {
+        let mut config = Config {
+            preset: Some("P".to_string()),
+            ..Config::default()
+        };
+        config.style.enable_colors = crate::config::AutoDetect::Bool(false);
+        config.components.project.base.min_width = 8;
+        config.components.project.base.align = crate::config::SegmentAlign::Right;
+        let mut generator = StatuslineGenerator::new(
+            config,
+            GeneratorOptions {
+                disable_cache: true,
+                ..GeneratorOptions::default()
+            },
+        );
+        let input = InputData {
+            cwd: Some("/tmp/abc".to_string()),
+            ..InputData::default()
+        };
+
+        let line = generator.generate(input).await?;
+        assert!(line.ends_with("     abc"), "{line:?}");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_update_config_drops_cached_outputs() -> Result<()> {
+        let (mut generator, renders) = counting_generator(false);
+
+        generator.generate(session_input("a")).await?;
+        let config = generator.config().clone();
+        generator.update_config(config);
+        generator.generate(session_input("a")).await?;
+        assert_eq!(renders.load(Ordering::SeqCst), 2);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_component_cache_respects_disable_cache() -> Result<()> {
+        let (mut generator, renders) = counting_generator(true);
+
+        generator.generate(session_input("a")).await?;
+        generator.generate(session_input("a")).await?;
+        assert_eq!(renders.load(Ordering::SeqCst), 2);
+        Ok(())
+    }
 }
@@ -0,0 +1,361 @@
+//! Session event hooks
+//!
+//! `[hooks]` 把会话状态的变化映射到用户的 shell 命令:会话开始、成本越过阈值、
+//! 上下文进入 critical、状态组件报错、会话空闲。每次渲染后比较本次成立的条件与
+//! 会话快照中记录的上一次状态,条件的成立与失效不论是否配置了命令都会记录
+//! (并写入指标日志),命令只在条件由假变真时触发;同一事件在
+//! `hooks.debounce_seconds` 内不会重复触发。命令通过 `sh -c`(Windows 为 `cmd /C`)
+//! 异步启动,事件 JSON 写入其 stdin,渲染不等待命令结束。
+
+use std::io::Write;
+use std::process::{Command, Stdio};
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde_json::{json, Value};
+
+use crate::config::HooksConfig;
+use crate::storage::{HookState, StorageHandle};
+
+/// Session state transitions a hook can be attached to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HookEvent {
+    SessionStart,
+    CostThreshold,
+    ContextCritical,
+    ErrorDetected,
+    SessionIdle,
+}
+
+impl HookEvent {
+    pub const ALL: [Self; 5] = [
+        Self::SessionStart,
+        Self::CostThreshold,
+        Self::ContextCritical,
+        Self::ErrorDetected,
+        Self::SessionIdle,
+    ];
+
+    /// Key of the event in `[hooks]` and in the event JSON
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::SessionStart => "session_start",
+            Self::CostThreshold => "cost_threshold",
+            Self::ContextCritical => "context_critical",
+            Self::ErrorDetected => "error_detected",
+            Self::SessionIdle => "session_idle",
+        }
+    }
+}
+
+/// Session state observed at one render
+#[derive(Debug, Clone, Default)]
+pub struct HookObservation {
+    pub session_id: String,
+    pub project_dir: Option<String>,
+    pub model: Option<String>,
+    pub cost_usd: Option<f64>,
+    /// Context usage reported by the tokens component (0–100)
+    pub context_percent: Option<f64>,
+    pub context_critical: bool,
+    pub error_detected: bool,
+    pub idle: Option<Duration>,
+}
+
+impl HookObservation {
+    /// Whether the condition of `event` holds
+    #[must_use]
+    pub fn holds(&self, event: HookEvent, config: &HooksConfig) -> bool {
+        match event {
+            HookEvent::SessionStart => true,
+            HookEvent::CostThreshold => {
+                config.cost_threshold_usd > 0.0
+                    && self
+                        .cost_usd
+                        .is_some_and(|cost| cost >= config.cost_threshold_usd)
+            }
+            HookEvent::ContextCritical => self.context_critical,
+            HookEvent::ErrorDetected => self.error_detected,
+            HookEvent::SessionIdle => self.idle.is_some(),
+        }
+    }
+
+    /// JSON written to the hook command's stdin
+    #[must_use]
+    pub fn event_json(&self, event: HookEvent, now: DateTime<Utc>) -> Value {
+        json!({
+            "event": event.name(),
+            "timestamp": now.to_rfc3339(),
+            "session_id": self.session_id,
+            "project_dir": self.project_dir,
+            "model": self.model,
+            "cost_usd": self.cost_usd,
+            "context_percent": self.context_percent,
+            "idle_seconds": self.idle.map(|idle| idle.as_secs()),
+        })
+    }
+}
+
+/// Advance `state` to the conditions in `observation` and return the events
+/// that fire.
+///
+/// 所有条件都记入 `state`;条件由假变真即消耗这次边沿,没有配置命令或处于防抖
+/// 窗口内时不触发,直到条件再次失效后重新成立。
+pub fn transition(
+    state: &mut HookState,
+    config: &HooksConfig,
+    observation: &HookObservation,
+    now: DateTime<Utc>,
+) -> Vec<HookEvent> {
+    let debounce = chrono::Duration::seconds(
+        i64::try_from(config.debounce_seconds).unwrap_or(i64::MAX / 1000),
+    );
+    let mut fired = Vec::new();
+    for event in HookEvent::ALL {
+        let name = event.name();
+        if !observation.holds(event, config) {
+            state.active.remove(name);
+            continue;
+        }
+        if !state.active.insert(name.to_string()) || config.command(name).is_none() {
+            continue;
+        }
+        let debounced = state
+            .last_fired
+            .get(name)
+            .is_some_and(|last| now.signed_duration_since(*last) < debounce);
+        if !debounced {
+            state.last_fired.insert(name.to_string(), now);
+            fired.push(event);
+        }
+    }
+    fired
+}
+
+/// Run the hooks whose condition became true for the observed session,
+/// persisting the new hook state through `storage`.
+///
+/// # Errors
+///
+/// Returns an error when the hook state cannot be read or written; a command
+/// that fails to start is reported on stderr and does not abort the others.
+pub async fn dispatch(
+    config: &HooksConfig,
+    storage: &dyn StorageHandle,
+    observation: &HookObservation,
+    now: DateTime<Utc>,
+) -> Result<Vec<HookEvent>> {
+    let session_id = observation.session_id.as_str();
+    let previous = storage
+        .session_history(session_id)
+        .await?
+        .map(|history| history.hooks)
+        .unwrap_or_default();
+    let mut state = previous.clone();
+    let fired = transition(&mut state, config, observation, now);
+    if state != previous {
+        storage.update_hook_state(session_id, state).await?;
+    }
+
+    for event in &fired {
+        let Some(command) = config.command(event.name()) else {
+            continue;
+        };
+        let payload = observation.event_json(*event, now);
+        if let Err(err) = spawn_hook(
+            command,
+            *event,
+            &payload,
+            observation.project_dir.as_deref(),
+        ) {
+            eprintln!("[statusline] hook {} failed: {err:#}", event.name());
+        }
+    }
+    Ok(fired)
+}
+
+/// Start `command` with the event JSON on stdin without waiting for it
+fn spawn_hook(
+    command: &str,
+    event: HookEvent,
+    payload: &Value,
+    working_dir: Option<&str>,
+) -> Result<()> {
+    let mut process = if cfg!(windows) {
+        let mut process = Command::new("cmd");
+        process.args(["/C", command]);
+        process
+    } else {
+        let mut process = Command::new("sh");
+        process.args(["-c", command]);
+        process
+    };
+    if let Some(dir) = working_dir.filter(|dir| std::path::Path::new(dir).is_dir()) {
+        process.current_dir(dir);
+    }
+    let mut child = process
+        .env("CCSP_HOOK_EVENT", event.name())
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .with_context(|| format!("Failed to start `{command}`"))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        // 命令可能不读 stdin,写入失败(管道已关闭)不算错误
+        let _ = writeln!(stdin, "{payload}");
+    }
+    // 回收子进程;statusline 进程先退出时子进程由 init 接管
+    std::thread::spawn(move || child.wait());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::MemoryStorage;
+
+    fn config_with(event: &str) -> HooksConfig {
+        let mut config = HooksConfig {
+            debounce_seconds: 60,
+            cost_threshold_usd: 5.0,
+            ..HooksConfig::default()
+        };
+        let command = Some("true".to_string());
+        match event {
+            "session_start" => config.session_start = command,
+            "cost_threshold" => config.cost_threshold = command,
+            "context_critical" => config.context_critical = command,
+            "error_detected" => config.error_detected = command,
+            _ => config.session_idle = command,
+        }
+        config
+    }
+
+    #[test]
+    fn events_fire_on_rising_edges_only() {
+        let config = config_with("context_critical");
+        let mut state = HookState::default();
+        let now = Utc::now();
+        let mut observation = HookObservation {
+            context_critical: true,
+            ..HookObservation::default()
+        };
+
+        assert_eq!(
+            transition(&mut state, &config, &observation, now),
+            [HookEvent::ContextCritical]
+        );
+        // 条件持续成立不重复触发
+        assert!(transition(&mut state, &config, &observation, now).is_empty());
+
+        // 失效后在防抖窗口内重新成立:不触发
+        observation.context_critical = false;
+        assert!(transition(&mut state, &config, &observation, now).is_empty());
+        observation.context_critical = true;
+        let soon = now + chrono::Duration::seconds(30);
+        assert!(transition(&mut state, &config, &observation, soon).is_empty());
+
+        // 窗口过后的下一次边沿触发
+        observation.context_critical = false;
+        transition(&mut state, &config, &observation, soon);
+        observation.context_critical = true;
+        let later = now + chrono::Duration::seconds(120);
+        assert_eq!(
+            transition(&mut state, &config, &observation, later),
+            [HookEvent::ContextCritical]
+        );
+    }
+
+    #[test]
+    fn unconfigured_and_disabled_events_never_fire() {
+        let mut state = HookState::default();
+        let observation = HookObservation {
+            cost_usd: Some(10.0),
+            error_detected: true,
+            ..HookObservation::default()
+        };
+        let config = config_with("session_idle");
+        assert!(transition(&mut state, &config, &observation, Utc::now()).is_empty());
+
+        let config = HooksConfig {
+            cost_threshold_usd: 0.0,
+            ..config_with("cost_threshold")
+        };
+        assert!(transition(&mut state, &config, &observation, Utc::now()).is_empty());
+        assert_eq!(
+            transition(
+                &mut state,
+                &config_with("cost_threshold"),
+                &observation,
+                Utc::now()
+            ),
+            [HookEvent::CostThreshold]
+        );
+    }
+
+    #[test]
+    fn conditions_are_recorded_without_commands() {
+        let mut state = HookState::default();
+        let mut observation = HookObservation {
+            context_critical: true,
+            ..HookObservation::default()
+        };
+        let config = HooksConfig::default();
+        assert!(transition(&mut state, &config, &observation, Utc::now()).is_empty());
+        assert!(state.active.contains("context_critical"));
+
+        observation.context_critical = false;
+        transition(&mut state, &config, &observation, Utc::now());
+        assert!(!state.active.contains("context_critical"));
+    }
+
+    #[tokio::test]
+    async fn session_start_fires_once_per_session() -> Result<()> {
+        let storage = MemoryStorage::new();
+        let config = config_with("session_start");
+        let observation = HookObservation {
+            session_id: "s1".to_string(),
+            ..HookObservation::default()
+        };
+
+        let fired = dispatch(&config, &storage, &observation, Utc::now()).await?;
+        assert_eq!(fired, [HookEvent::SessionStart]);
+        let fired = dispatch(&config, &storage, &observation, Utc::now()).await?;
+        assert!(fired.is_empty());
+        let history = storage
+            .history("s1")
+            .ok_or_else(|| anyhow::anyhow!("hook state not stored"))?;
+        assert!(history.hooks.active.contains("session_start"));
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn commands_receive_the_event_json() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let output = dir.path().join("event.json");
+        let config = HooksConfig {
+            error_detected: Some(format!("cat > '{}'", output.display())),
+            ..HooksConfig::default()
+        };
+        let observation = HookObservation {
+            session_id: "s2".to_string(),
+            error_detected: true,
+            ..HookObservation::default()
+        };
+        dispatch(&config, &MemoryStorage::new(), &observation, Utc::now()).await?;
+
+        for _ in 0..100 {
+            if std::fs::read_to_string(&output).is_ok_and(|text| text.ends_with('\n')) {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(20));
+        }
+        let event: Value = serde_json::from_str(&std::fs::read_to_string(&output)?)?;
+        assert_eq!(event["event"], "error_detected");
+        assert_eq!(event["session_id"], "s2");
+        Ok(())
+    }
+}
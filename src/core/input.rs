@@ -6,6 +6,11 @@
 
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use std::sync::mpsc;
+use std::time::Duration;
+
+use super::input_version::{normalize_payload, PayloadVersion};
+use crate::error::{StatuslineError, StatuslineResult};
 
 /// Main input data structure from Claude Code
 ///
@@ -70,6 +75,10 @@ pub struct InputData {
     /// Additional fields for future expansion
     #[serde(flatten)]
     pub extra: Value,
+
+    /// Payload generation recognized while parsing, see [`super::input_version`]
+    #[serde(skip)]
+    pub payload_version: PayloadVersion,
 }
 
 /// Claude.ai subscription rate limits (Pro/Max only).
@@ -266,8 +275,22 @@ impl InputData {
     ///
     /// Returns an error when the provided JSON payload cannot be parsed into
     /// the expected input schema.
-    pub fn from_json(json: &str) -> anyhow::Result<Self> {
-        let data: Self = serde_json::from_str(json)?;
+    pub fn from_json(json: &str) -> StatuslineResult<Self> {
+        let payload: Value =
+            serde_json::from_str(json).map_err(|err| StatuslineError::Input(err.into()))?;
+        Self::from_value(payload)
+    }
+
+    /// Parse `InputData` from a JSON value, normalizing older payload
+    /// generations to the current field names
+    /// # Errors
+    ///
+    /// Returns an error when the payload does not match the input schema.
+    pub fn from_value(mut payload: Value) -> StatuslineResult<Self> {
+        let payload_version = normalize_payload(&mut payload);
+        let mut data: Self =
+            serde_json::from_value(payload).map_err(|err| StatuslineError::Input(err.into()))?;
+        data.payload_version = payload_version;
         Ok(data)
     }
 
@@ -276,10 +299,12 @@ impl InputData {
     ///
     /// Returns an error when stdin cannot be read or the streamed data fails
     /// to deserialize into structured input metadata.
-    pub fn from_stdin() -> anyhow::Result<Self> {
+    pub fn from_stdin() -> StatuslineResult<Self> {
         use std::io::{self, Read};
         let mut buffer = String::new();
-        io::stdin().read_to_string(&mut buffer)?;
+        io::stdin()
+            .read_to_string(&mut buffer)
+            .map_err(|err| StatuslineError::Input(err.into()))?;
 
         // Handle empty input by returning default
         if buffer.trim().is_empty() {
@@ -289,6 +314,48 @@ impl InputData {
         Self::from_json(&buffer)
     }
 
+    /// Read all of stdin, giving up after `timeout`; `None` on timeout.
+    ///
+    /// 读取在后台线程进行;超时后直接返回,线程随进程退出,不会让状态行一直挂起。
+    ///
+    /// # Errors
+    ///
+    /// Returns [`StatuslineError::Input`] when stdin cannot be read.
+    pub fn read_stdin(timeout: Duration) -> StatuslineResult<Option<String>> {
+        use std::io::Read;
+
+        let (sender, receiver) = mpsc::channel();
+        std::thread::spawn(move || {
+            let mut buffer = String::new();
+            let result = std::io::stdin().read_to_string(&mut buffer).map(|_| buffer);
+            let _ = sender.send(result);
+        });
+
+        receiver
+            .recv_timeout(timeout)
+            .ok()
+            .map_or(Ok(None), |result| {
+                result
+                    .map(Some)
+                    .map_err(|err| StatuslineError::Input(err.into()))
+            })
+    }
+
+    /// Parse `InputData` from stdin, giving up after `timeout`; `None` on
+    /// timeout
+    /// # Errors
+    ///
+    /// Returns an error when stdin cannot be read or fails to deserialize.
+    pub fn from_stdin_timeout(timeout: Duration) -> StatuslineResult<Option<Self>> {
+        let Some(buffer) = Self::read_stdin(timeout)? else {
+            return Ok(None);
+        };
+        if buffer.trim().is_empty() {
+            return Ok(Some(Self::default()));
+        }
+        Self::from_json(&buffer).map(Some)
+    }
+
     /// Get the effective project directory
     ///
     /// Returns the original project root directory for the session.
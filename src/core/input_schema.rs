@@ -0,0 +1,454 @@
+//! Documented stdin input schema
+//!
+//! 列出解析器认识的全部 stdin 字段(`ccsp schema input` 输出),以及
+//! `--print-input` 回显负载前使用的脱敏处理。Claude Code 调整负载格式时,
+//! 对照这张表和实际回显即可定位哪些字段缺失或改名。
+
+use std::path::Path;
+
+use serde_json::Value;
+
+use crate::utils::secrets::looks_like_raw_token;
+
+/// Placeholder written over redacted values
+pub const REDACTED: &str = "<redacted>";
+
+/// JSON type of an input field
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FieldKind {
+    String,
+    Integer,
+    Number,
+    Boolean,
+    StringArray,
+    /// Free-form value, only presence or a loose shape is checked
+    Any,
+}
+
+impl FieldKind {
+    #[must_use]
+    pub const fn label(self) -> &'static str {
+        match self {
+            Self::String => "string",
+            Self::Integer => "integer",
+            Self::Number => "number",
+            Self::Boolean => "boolean",
+            Self::StringArray => "string[]",
+            Self::Any => "any",
+        }
+    }
+}
+
+/// One field the parser understands
+#[derive(Debug, Clone, Copy)]
+pub struct InputField {
+    /// Dotted path in the payload
+    pub path: &'static str,
+    /// Accepted alternative spellings of the full path
+    pub aliases: &'static [&'static str],
+    pub kind: FieldKind,
+    /// Whether the field is deserialized into [`super::InputData`]; other
+    /// fields are read from `InputData::extra` by the components using them
+    pub typed: bool,
+    pub description: &'static str,
+}
+
+const fn typed(
+    path: &'static str,
+    aliases: &'static [&'static str],
+    kind: FieldKind,
+    description: &'static str,
+) -> InputField {
+    InputField {
+        path,
+        aliases,
+        kind,
+        typed: true,
+        description,
+    }
+}
+
+const fn extra(
+    path: &'static str,
+    aliases: &'static [&'static str],
+    kind: FieldKind,
+    description: &'static str,
+) -> InputField {
+    InputField {
+        path,
+        aliases,
+        kind,
+        typed: false,
+        description,
+    }
+}
+
+/// Every stdin field read by the parser or a component
+pub const INPUT_FIELDS: &[InputField] = &[
+    typed(
+        "hook_event_name",
+        &[],
+        FieldKind::String,
+        "触发状态行的 hook 事件名",
+    ),
+    typed("session_id", &["sessionId"], FieldKind::String, "会话 ID"),
+    typed(
+        "transcript_path",
+        &["transcriptPath"],
+        FieldKind::String,
+        "会话 transcript JSONL 路径",
+    ),
+    typed("cwd", &[], FieldKind::String, "当前工作目录"),
+    typed("model.id", &[], FieldKind::String, "模型 ID"),
+    typed(
+        "model.display_name",
+        &["model.displayName"],
+        FieldKind::String,
+        "模型显示名",
+    ),
+    typed(
+        "workspace.current_dir",
+        &["workspace.currentDir"],
+        FieldKind::String,
+        "当前目录",
+    ),
+    typed(
+        "workspace.project_dir",
+        &["workspace.projectDir"],
+        FieldKind::String,
+        "项目根目录",
+    ),
+    typed(
+        "workspace.added_dirs",
+        &["workspace.addedDirs"],
+        FieldKind::StringArray,
+        "会话中追加的目录",
+    ),
+    typed(
+        "workspace.git_worktree",
+        &["workspace.gitWorktree"],
+        FieldKind::String,
+        "关联的 git worktree 名称",
+    ),
+    typed(
+        "worktree.name",
+        &["worktree.worktreeName"],
+        FieldKind::String,
+        "--worktree 会话的 worktree 名称",
+    ),
+    typed(
+        "worktree.path",
+        &["worktree.worktreePath"],
+        FieldKind::String,
+        "worktree 目录",
+    ),
+    typed(
+        "worktree.branch",
+        &["worktree.worktreeBranch"],
+        FieldKind::String,
+        "worktree 分支",
+    ),
+    typed(
+        "worktree.original_cwd",
+        &["worktree.originalCwd"],
+        FieldKind::String,
+        "进入 worktree 前的目录",
+    ),
+    typed(
+        "worktree.original_branch",
+        &["worktree.originalBranch"],
+        FieldKind::String,
+        "进入 worktree 前的分支",
+    ),
+    typed(
+        "git_branch",
+        &[],
+        FieldKind::String,
+        "分支名(旧字段,优先 git.branch)",
+    ),
+    typed("git.branch", &[], FieldKind::String, "分支名"),
+    typed(
+        "git.status",
+        &[],
+        FieldKind::String,
+        "工作区状态(clean / dirty)",
+    ),
+    typed("git.ahead", &[], FieldKind::Integer, "领先远端的提交数"),
+    typed("git.behind", &[], FieldKind::Integer, "落后远端的提交数"),
+    typed("git.staged", &[], FieldKind::Integer, "已暂存文件数"),
+    typed("git.unstaged", &[], FieldKind::Integer, "未暂存文件数"),
+    typed("git.untracked", &[], FieldKind::Integer, "未跟踪文件数"),
+    typed(
+        "cost.total_cost_usd",
+        &[],
+        FieldKind::Number,
+        "会话累计成本(USD)",
+    ),
+    typed("cost.currency", &[], FieldKind::String, "成本币种"),
+    typed(
+        "cost.total_duration_ms",
+        &[],
+        FieldKind::Integer,
+        "会话总时长(毫秒)",
+    ),
+    typed(
+        "cost.total_api_duration_ms",
+        &[],
+        FieldKind::Integer,
+        "API 调用总时长(毫秒)",
+    ),
+    typed(
+        "cost.total_lines_added",
+        &[],
+        FieldKind::Integer,
+        "新增行数",
+    ),
+    typed(
+        "cost.total_lines_removed",
+        &[],
+        FieldKind::Integer,
+        "删除行数",
+    ),
+    typed("cost.input_tokens", &[], FieldKind::Integer, "输入 token"),
+    typed("cost.output_tokens", &[], FieldKind::Integer, "输出 token"),
+    typed("cost.total_tokens", &[], FieldKind::Integer, "总 token"),
+    typed(
+        "cost.cache_read_tokens",
+        &[],
+        FieldKind::Integer,
+        "缓存读取 token",
+    ),
+    typed(
+        "cost.cache_write_tokens",
+        &[],
+        FieldKind::Integer,
+        "缓存写入 token",
+    ),
+    typed(
+        "rate_limits.five_hour.used_percentage",
+        &[],
+        FieldKind::Number,
+        "5 小时窗口已用百分比",
+    ),
+    typed(
+        "rate_limits.five_hour.resets_at",
+        &[],
+        FieldKind::Integer,
+        "5 小时窗口重置时间(Unix 秒)",
+    ),
+    typed(
+        "rate_limits.seven_day.used_percentage",
+        &[],
+        FieldKind::Number,
+        "7 天窗口已用百分比",
+    ),
+    typed(
+        "rate_limits.seven_day.resets_at",
+        &[],
+        FieldKind::Integer,
+        "7 天窗口重置时间(Unix 秒)",
+    ),
+    extra("version", &[], FieldKind::String, "Claude Code 版本"),
+    extra(
+        "context_window.context_window_size",
+        &["contextWindow.contextWindowSize"],
+        FieldKind::Integer,
+        "上下文窗口大小",
+    ),
+    extra(
+        "context_window.used_percentage",
+        &["contextWindow.usedPercentage"],
+        FieldKind::Number,
+        "上下文已用百分比",
+    ),
+    extra(
+        "context_window.current_usage.input_tokens",
+        &["contextWindow.currentUsage.inputTokens"],
+        FieldKind::Integer,
+        "当前上下文的输入 token",
+    ),
+    extra(
+        "context_window.current_usage.cache_creation_input_tokens",
+        &["contextWindow.currentUsage.cacheCreationInputTokens"],
+        FieldKind::Integer,
+        "当前上下文的缓存写入 token",
+    ),
+    extra(
+        "context_window.current_usage.cache_read_input_tokens",
+        &["contextWindow.currentUsage.cacheReadInputTokens"],
+        FieldKind::Integer,
+        "当前上下文的缓存读取 token",
+    ),
+    extra("status", &[], FieldKind::String, "会话状态(旧字段)"),
+    extra(
+        "stop_reason",
+        &[],
+        FieldKind::String,
+        "最近一次回复的停止原因(旧字段)",
+    ),
+    extra("error", &[], FieldKind::Any, "错误标记或错误信息(旧字段)"),
+    extra(
+        "output_style.name",
+        &[],
+        FieldKind::String,
+        "输出风格(field 组件示例)",
+    ),
+];
+
+/// `ccsp schema input` 的文本输出
+#[must_use]
+pub fn describe_input_fields() -> String {
+    let width = INPUT_FIELDS
+        .iter()
+        .map(|field| field.path.len())
+        .max()
+        .unwrap_or(0);
+    let mut lines = Vec::with_capacity(INPUT_FIELDS.len() + 2);
+    for (typed, title) in [(true, "结构化字段"), (false, "组件从 extra 读取的字段")] {
+        lines.push(format!("{title}:"));
+        for field in INPUT_FIELDS.iter().filter(|field| field.typed == typed) {
+            let aliases = if field.aliases.is_empty() {
+                String::new()
+            } else {
+                format!(" (别名: {})", field.aliases.join(", "))
+            };
+            lines.push(format!(
+                "  {:<width$}  {:<8}  {}{aliases}",
+                field.path,
+                field.kind.label(),
+                field.description
+            ));
+        }
+    }
+    lines.join("\n")
+}
+
+/// `payload` with credentials replaced by [`REDACTED`] and the home
+/// directory shortened to `~`, for `--print-input`.
+#[must_use]
+pub fn redact_input(payload: &Value, home: Option<&Path>) -> Value {
+    let home = home
+        .map(|home| {
+            home.to_string_lossy()
+                .trim_end_matches(['/', '\\'])
+                .to_string()
+        })
+        .filter(|home| !home.is_empty());
+    let mut redacted = payload.clone();
+    redact_value(&mut redacted, "", home.as_deref());
+    redacted
+}
+
+fn redact_value(value: &mut Value, key: &str, home: Option<&str>) {
+    match value {
+        Value::Object(map) => {
+            for (child_key, child) in map.iter_mut() {
+                redact_value(child, child_key, home);
+            }
+        }
+        Value::Array(items) => {
+            for item in items {
+                redact_value(item, key, home);
+            }
+        }
+        Value::String(text) => {
+            if looks_like_raw_token(key, text) {
+                *text = REDACTED.to_string();
+            } else if let Some(rest) = home.and_then(|home| text.strip_prefix(home)) {
+                if rest.is_empty() || rest.starts_with(['/', '\\']) {
+                    *text = format!("~{rest}");
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::InputData;
+    use serde_json::json;
+
+    fn sample(kind: FieldKind) -> Value {
+        match kind {
+            FieldKind::String | FieldKind::Any => json!("x"),
+            FieldKind::Integer => json!(1),
+            FieldKind::Number => json!(1.5),
+            FieldKind::Boolean => json!(true),
+            FieldKind::StringArray => json!(["x"]),
+        }
+    }
+
+    fn insert_path(root: &mut Value, path: &str, leaf: Value) {
+        let mut current = root;
+        let segments: Vec<&str> = path.split('.').collect();
+        for (index, segment) in segments.iter().enumerate() {
+            if !current.is_object() {
+                *current = json!({});
+            }
+            let Value::Object(map) = current else {
+                return;
+            };
+            if index + 1 == segments.len() {
+                map.insert((*segment).to_string(), leaf);
+                return;
+            }
+            current = map
+                .entry((*segment).to_string())
+                .or_insert_with(|| json!({}));
+        }
+    }
+
+    #[test]
+    fn typed_fields_round_trip_through_input_data() -> anyhow::Result<()> {
+        let mut payload = json!({});
+        for field in INPUT_FIELDS.iter().filter(|field| field.typed) {
+            insert_path(&mut payload, field.path, sample(field.kind));
+        }
+        let input = InputData::from_json(&payload.to_string())?;
+        let serialized = serde_json::to_value(&input)?;
+        for field in INPUT_FIELDS.iter().filter(|field| field.typed) {
+            let pointer = format!("/{}", field.path.replace('.', "/"));
+            assert_eq!(
+                serialized.pointer(&pointer),
+                Some(&sample(field.kind)),
+                "{} is not a typed InputData field",
+                field.path
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn schema_lists_every_field() {
+        let text = describe_input_fields();
+        for field in INPUT_FIELDS {
+            assert!(text.contains(field.path));
+        }
+    }
+
+    #[test]
+    fn redaction_hides_tokens_and_home() {
+        let payload = json!({
+            "cwd": "/home/dev/work/api",
+            "transcript_path": "/home/dev/.claude/projects/x/session.jsonl",
+            "session_id": "abc",
+            "auth_token": "sk-ant-REDACTED",
+            "workspace": {"added_dirs": ["/home/developer/other"]}
+        });
+        let redacted = redact_input(&payload, Some(Path::new("/home/dev/")));
+        assert_eq!(redacted["cwd"], "~/work/api");
+        assert_eq!(
+            redacted["transcript_path"],
+            "~/.claude/projects/x/session.jsonl"
+        );
+        assert_eq!(redacted["session_id"], "abc");
+        assert_eq!(redacted["auth_token"], REDACTED);
+        // 只替换完整的路径前缀
+        assert_eq!(
+            redacted["workspace"]["added_dirs"][0],
+            "/home/developer/other"
+        );
+    }
+}
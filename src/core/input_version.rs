@@ -0,0 +1,264 @@
+//! Input payload generations
+//!
+//! Claude Code 的 stdin 负载格式在演进中多次增改字段:早期(以及 TypeScript 版)
+//! 使用 `camelCase`,随后统一为 `snake_case`,之后又加入 `context_window`、
+//! `rate_limits`、`worktree` 等字段。解析前先识别负载属于哪一代,再按该代的
+//! 别名表把字段改写成当前的规范名称;识别结果记录在
+//! [`InputData::payload_version`](super::InputData::payload_version) 中,
+//! 由 `--debug` 与 `--print-input` 输出,字段改名导致的显示异常可以直接定位。
+
+use serde_json::Value;
+
+/// Payload generation recognized on stdin
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PayloadVersion {
+    /// No known marker field present (empty or foreign payload)
+    #[default]
+    Unknown,
+    /// `camelCase` fields (`sessionId`, `workspace.currentDir`)
+    Legacy,
+    /// `snake_case` session, workspace and cost fields
+    V1,
+    /// Adds `context_window`, `rate_limits`, `worktree` and `version`
+    V2,
+}
+
+impl PayloadVersion {
+    #[must_use]
+    pub const fn label(self) -> &'static str {
+        match self {
+            Self::Unknown => "unknown",
+            Self::Legacy => "legacy",
+            Self::V1 => "v1",
+            Self::V2 => "v2",
+        }
+    }
+
+    #[must_use]
+    pub const fn description(self) -> &'static str {
+        match self {
+            Self::Unknown => "未识别的负载",
+            Self::Legacy => "camelCase 字段(早期 Claude Code / TypeScript 版)",
+            Self::V1 => "snake_case 基础字段",
+            Self::V2 => "含 context_window / rate_limits / worktree",
+        }
+    }
+}
+
+/// Marker fields and field renames of one payload generation
+#[derive(Debug, Clone, Copy)]
+pub struct PayloadGeneration {
+    pub version: PayloadVersion,
+    /// Dotted paths only this generation (or later ones) sends
+    pub markers: &'static [&'static str],
+    /// `(generation path, canonical path)` pairs, applied in order; parent
+    /// objects are renamed before their children
+    pub renames: &'static [(&'static str, &'static str)],
+}
+
+/// Known generations, newest first
+pub const PAYLOAD_GENERATIONS: &[PayloadGeneration] = &[
+    PayloadGeneration {
+        version: PayloadVersion::V2,
+        markers: &[
+            "context_window",
+            "rate_limits",
+            "worktree",
+            "version",
+            "workspace.added_dirs",
+        ],
+        renames: &[],
+    },
+    PayloadGeneration {
+        version: PayloadVersion::V1,
+        markers: &[
+            "session_id",
+            "transcript_path",
+            "workspace.current_dir",
+            "workspace.project_dir",
+            "cost.total_cost_usd",
+        ],
+        renames: &[],
+    },
+    PayloadGeneration {
+        version: PayloadVersion::Legacy,
+        markers: &[
+            "sessionId",
+            "transcriptPath",
+            "workspace.currentDir",
+            "workspace.projectDir",
+            "model.displayName",
+            "contextWindow",
+        ],
+        renames: &[
+            ("sessionId", "session_id"),
+            ("transcriptPath", "transcript_path"),
+            ("model.displayName", "model.display_name"),
+            ("workspace.currentDir", "workspace.current_dir"),
+            ("workspace.projectDir", "workspace.project_dir"),
+            ("workspace.addedDirs", "workspace.added_dirs"),
+            ("workspace.gitWorktree", "workspace.git_worktree"),
+            ("worktree.worktreeName", "worktree.name"),
+            ("worktree.worktreePath", "worktree.path"),
+            ("worktree.worktreeBranch", "worktree.branch"),
+            ("worktree.originalCwd", "worktree.original_cwd"),
+            ("worktree.originalBranch", "worktree.original_branch"),
+            ("contextWindow", "context_window"),
+            (
+                "context_window.contextWindowSize",
+                "context_window.context_window_size",
+            ),
+            (
+                "context_window.usedPercentage",
+                "context_window.used_percentage",
+            ),
+            (
+                "context_window.currentUsage",
+                "context_window.current_usage",
+            ),
+            (
+                "context_window.current_usage.inputTokens",
+                "context_window.current_usage.input_tokens",
+            ),
+            (
+                "context_window.current_usage.cacheCreationInputTokens",
+                "context_window.current_usage.cache_creation_input_tokens",
+            ),
+            (
+                "context_window.current_usage.cacheReadInputTokens",
+                "context_window.current_usage.cache_read_input_tokens",
+            ),
+        ],
+    },
+];
+
+fn get_path<'a>(value: &'a Value, path: &str) -> Option<&'a Value> {
+    path.split('.')
+        .try_fold(value, |current, segment| current.get(segment))
+}
+
+/// Generation of `payload`: the newest one with a marker field present
+#[must_use]
+pub fn detect_version(payload: &Value) -> PayloadVersion {
+    PAYLOAD_GENERATIONS
+        .iter()
+        .find(|generation| {
+            generation
+                .markers
+                .iter()
+                .any(|marker| get_path(payload, marker).is_some())
+        })
+        .map_or(PayloadVersion::Unknown, |generation| generation.version)
+}
+
+/// Move the value at `from` to `to` unless `to` is already set
+fn rename_path(payload: &mut Value, from: &str, to: &str) {
+    let (from_parent, from_key) = from.rsplit_once('.').unwrap_or(("", from));
+    let (to_parent, to_key) = to.rsplit_once('.').unwrap_or(("", to));
+    if from_parent != to_parent {
+        return;
+    }
+    let parent = if from_parent.is_empty() {
+        Some(&mut *payload)
+    } else {
+        from_parent
+            .split('.')
+            .try_fold(&mut *payload, |current, segment| current.get_mut(segment))
+    };
+    let Some(Value::Object(map)) = parent else {
+        return;
+    };
+    if map.contains_key(to_key) {
+        return;
+    }
+    if let Some(value) = map.remove(from_key) {
+        map.insert(to_key.to_string(), value);
+    }
+}
+
+/// Detect the generation of `payload` and rewrite its fields to the
+/// canonical names.
+///
+/// 所有已知代的改名都会应用(同一负载可能混用新旧字段),规范名已存在时保留规范值。
+pub fn normalize_payload(payload: &mut Value) -> PayloadVersion {
+    let version = detect_version(payload);
+    for generation in PAYLOAD_GENERATIONS {
+        for (from, to) in generation.renames {
+            rename_path(payload, from, to);
+        }
+    }
+    version
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::input_schema::INPUT_FIELDS;
+    use crate::core::InputData;
+    use serde_json::json;
+
+    #[test]
+    fn generations_are_detected() {
+        assert_eq!(detect_version(&json!({})), PayloadVersion::Unknown);
+        assert_eq!(
+            detect_version(&json!({"sessionId": "a", "workspace": {"currentDir": "/w"}})),
+            PayloadVersion::Legacy
+        );
+        assert_eq!(
+            detect_version(&json!({"session_id": "a", "cost": {"total_cost_usd": 1.0}})),
+            PayloadVersion::V1
+        );
+        assert_eq!(
+            detect_version(&json!({"session_id": "a", "context_window": {}})),
+            PayloadVersion::V2
+        );
+    }
+
+    #[test]
+    fn legacy_payloads_are_normalized() -> anyhow::Result<()> {
+        let input = InputData::from_json(
+            r#"{
+                "sessionId": "legacy",
+                "workspace": {"currentDir": "/w", "projectDir": "/p"},
+                "contextWindow": {
+                    "contextWindowSize": 200000,
+                    "currentUsage": {"inputTokens": 1200}
+                }
+            }"#,
+        )?;
+        assert_eq!(input.payload_version, PayloadVersion::Legacy);
+        assert_eq!(input.session_id.as_deref(), Some("legacy"));
+        assert_eq!(input.project_dir(), Some("/p"));
+        assert_eq!(
+            input.extra["context_window"]["current_usage"]["input_tokens"],
+            1200
+        );
+        assert!(input.extra.get("contextWindow").is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn canonical_values_win_over_aliases() {
+        let mut payload = json!({"sessionId": "old", "session_id": "new"});
+        assert_eq!(normalize_payload(&mut payload), PayloadVersion::V1);
+        assert_eq!(payload, json!({"sessionId": "old", "session_id": "new"}));
+    }
+
+    #[test]
+    fn every_documented_alias_has_a_rename() {
+        let renames: Vec<&str> = PAYLOAD_GENERATIONS
+            .iter()
+            .flat_map(|generation| generation.renames.iter().map(|(_, to)| *to))
+            .collect();
+        for field in INPUT_FIELDS {
+            if field.aliases.is_empty() {
+                continue;
+            }
+            assert!(
+                renames.contains(&field.path),
+                "no generation renames an alias of {}",
+                field.path
+            );
+        }
+    }
+}
@@ -4,11 +4,17 @@
 //! including input data parsing, configuration management, and the main
 //! generator logic.
 
+pub mod fixtures;
 pub mod generator;
+pub mod hooks;
 pub mod input;
+pub mod input_schema;
+pub mod input_version;
 pub mod multiline;
+pub mod transcript;
 
 // Re-export commonly used types
 pub use generator::{GeneratorOptions, StatuslineGenerator};
 pub use input::{CostInfo, GitInfo, InputData, ModelInfo, WorkspaceInfo, WorktreeInfo};
 pub use multiline::{MultiLineRenderResult, MultiLineRenderer};
+pub use transcript::{CompactionInfo, TodoItem, TranscriptAnalysis, TranscriptUsage};
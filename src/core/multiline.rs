@@ -21,7 +21,8 @@ use crate::config::component_widgets::{
     WidgetFilterMode, WidgetType,
 };
 use crate::config::{Config, MultilineConfig, MultilineRowConfig};
-use crate::utils;
+use crate::core::generator::separator_item;
+use crate::utils::{self, truncate_to_width};
 
 static ENV_PATTERN: OnceLock<Result<Regex, regex::Error>> = OnceLock::new();
 static PLACEHOLDER_PATTERN: OnceLock<Result<Regex, regex::Error>> = OnceLock::new();
@@ -44,6 +45,30 @@ pub struct MultiLineRenderResult {
     pub error: Option<String>,
 }
 
+/// Where a component widget file was found
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum WidgetOrigin {
+    /// Under the user config dir (`~/.claude`)
+    UserConfig,
+    /// Next to a config file outside the user config dir (`--config`)
+    ConfigDir,
+    /// Relative `components/` in the working directory, i.e. whatever repo
+    /// the statusline happens to run in
+    WorkingDir,
+}
+
+impl WidgetOrigin {
+    /// 工作目录里的组件文件来自任意仓库,不允许发起 API 请求
+    const fn allows_api(self) -> bool {
+        matches!(self, Self::UserConfig | Self::ConfigDir)
+    }
+
+    /// 只有用户配置目录里的组件文件可以读取环境变量与钥匙串中的密钥
+    const fn allows_secrets(self) -> bool {
+        matches!(self, Self::UserConfig)
+    }
+}
+
 /// Renderer responsible for multi-line widgets
 pub struct MultiLineRenderer {
     config: Config,
@@ -56,8 +81,7 @@ pub struct MultiLineRenderer {
 impl MultiLineRenderer {
     #[must_use]
     pub fn new(config: Config, base_dir: Option<PathBuf>) -> Self {
-        let log_file = utils::home_dir()
-            .unwrap_or_else(|| PathBuf::from("."))
+        let log_file = utils::home_dir_or_temp()
             .join(".claude")
             .join("statusline-pro")
             .join("multiline.log");
@@ -124,28 +148,32 @@ impl MultiLineRenderer {
             .collect::<Vec<_>>();
 
         for component_name in component_order {
-            if !self.is_component_enabled(&component_name) {
+            if separator_item(&component_name).is_some()
+                || !self.is_component_enabled(&component_name)
+            {
                 continue;
             }
 
-            let component_config = match self.load_component_config(component_name.as_str()).await {
-                Ok(Some(config)) => config,
-                Ok(None) => {
-                    continue;
-                }
-                Err(err) => {
-                    return MultiLineRenderResult {
-                        success: false,
-                        lines: Vec::new(),
-                        error: Some(err.to_string()),
-                    };
-                }
-            };
+            let (component_config, origin) =
+                match self.load_component_config(component_name.as_str()).await {
+                    Ok(Some(found)) => found,
+                    Ok(None) => {
+                        continue;
+                    }
+                    Err(err) => {
+                        return MultiLineRenderResult {
+                            success: false,
+                            lines: Vec::new(),
+                            error: Some(err.to_string()),
+                        };
+                    }
+                };
 
             if let Err(err) = self
                 .render_component_widgets(
                     &component_name,
                     &component_config,
+                    origin,
                     context,
                     &multiline_config,
                 )
@@ -176,36 +204,58 @@ impl MultiLineRenderer {
             "usage" => self.config.components.usage.base.enabled,
             "rate_limit" => self.config.components.rate_limit.base.enabled,
             "status" => self.config.components.status.base.enabled,
+            "hook" => self.config.components.hook.base.enabled,
+            "field" => self.config.components.field.base.enabled,
+            "text" => self.config.components.text.base.enabled,
+            "trust" => self.config.components.trust.base.enabled,
+            "quota" => self.config.components.quota.base.enabled,
+            "session" => self.config.components.session.base.enabled,
+            "ticket" => self.config.components.ticket.base.enabled,
+            "repo" => self.config.components.repo.base.enabled,
             _ => true,
         }
     }
 
-    async fn load_component_config(
-        &self,
-        component_name: &str,
-    ) -> Result<Option<ComponentMultilineConfig>> {
+    fn component_file_candidates(&self, component_name: &str) -> Vec<(PathBuf, WidgetOrigin)> {
+        let file_name = format!("{component_name}.toml");
+        let user_config_dir = utils::home_dir().map(|home| home.join(".claude"));
         let mut candidate_paths = Vec::new();
 
         if let Some(base) = &self.config_base_dir {
-            candidate_paths.push(
-                base.join("components")
-                    .join(format!("{component_name}.toml")),
-            );
+            let origin = if user_config_dir
+                .as_ref()
+                .is_some_and(|dir| base.starts_with(dir))
+            {
+                WidgetOrigin::UserConfig
+            } else {
+                WidgetOrigin::ConfigDir
+            };
+            candidate_paths.push((base.join("components").join(&file_name), origin));
         }
 
-        if let Some(user_dir) = utils::home_dir() {
-            candidate_paths.push(
-                user_dir
-                    .join(".claude")
+        if let Some(user_config_dir) = user_config_dir {
+            candidate_paths.push((
+                user_config_dir
                     .join("statusline-pro")
                     .join("components")
-                    .join(format!("{component_name}.toml")),
-            );
+                    .join(&file_name),
+                WidgetOrigin::UserConfig,
+            ));
         }
 
-        candidate_paths.push(PathBuf::from("components").join(format!("{component_name}.toml")));
+        candidate_paths.push((
+            PathBuf::from("components").join(file_name),
+            WidgetOrigin::WorkingDir,
+        ));
+
+        candidate_paths
+    }
 
-        for path in candidate_paths {
+    async fn load_component_config(
+        &self,
+        component_name: &str,
+    ) -> Result<Option<(ComponentMultilineConfig, WidgetOrigin)>> {
+        for (path, origin) in self.component_file_candidates(component_name) {
             if path.exists() {
                 let contents = fs::read_to_string(&path).await.with_context(|| {
                     format!("Failed to read component configuration: {}", path.display())
@@ -214,7 +264,7 @@ impl MultiLineRenderer {
                     .with_context(|| {
                         format!("Failed to parse component configuration {}", path.display())
                     })?;
-                return Ok(Some(config));
+                return Ok(Some((config, origin)));
             }
         }
 
@@ -225,6 +275,7 @@ impl MultiLineRenderer {
         &mut self,
         component_name: &str,
         component_config: &ComponentMultilineConfig,
+        origin: WidgetOrigin,
         context: &RenderContext,
         multiline_config: &MultilineConfig,
     ) -> Result<()> {
@@ -246,13 +297,21 @@ impl MultiLineRenderer {
             let allow_stale_cache = matches!(widget_config.kind, WidgetType::Api);
             let widget_output = match widget_config.kind {
                 WidgetType::Static => Some(self.render_static_widget(widget_config, context)),
-                WidgetType::Api => match self.render_api_widget(widget_config, context).await {
-                    Ok(value) => value,
-                    Err(err) => {
-                        let err_str = err.to_string();
-
-                        // 记录完整错误到日志文件
-                        let log_msg = format!(
+                WidgetType::Api if !origin.allows_api() => {
+                    self.log_error(&format!(
+                        "Widget {component_name}.{widget_name} skipped: API widgets are not loaded from the working directory"
+                    ))
+                    .await;
+                    None
+                }
+                WidgetType::Api => {
+                    match self.render_api_widget(widget_config, origin, context).await {
+                        Ok(value) => value,
+                        Err(err) => {
+                            let err_str = err.to_string();
+
+                            // 记录完整错误到日志文件
+                            let log_msg = format!(
                             "Widget {}.{} API request failed:\n  Error: {}\n  Config: base_url={:?}, endpoint={:?}, method={:?}",
                             component_name,
                             widget_name,
@@ -261,12 +320,13 @@ impl MultiLineRenderer {
                             widget_config.api.as_ref().map(|a| &a.endpoint),
                             widget_config.api.as_ref().map(|a| &a.method)
                         );
-                        self.log_error(&log_msg).await;
+                            self.log_error(&log_msg).await;
 
-                        // API失败时不显示widget
-                        None
+                            // API失败时不显示widget
+                            None
+                        }
                     }
-                },
+                }
                 WidgetType::Input => self.render_input_widget(widget_config, context),
             };
 
@@ -344,13 +404,17 @@ impl MultiLineRenderer {
     async fn render_api_widget(
         &self,
         widget: &WidgetConfig,
+        origin: WidgetOrigin,
         context: &RenderContext,
     ) -> Result<Option<String>> {
         let Some(api_config) = widget.api.as_ref() else {
             return Ok(None);
         };
+        if self.config.offline {
+            return Ok(None);
+        }
 
-        let api_data = self.fetch_api_data(api_config).await?;
+        let api_data = self.fetch_api_data(api_config, origin).await?;
 
         if !Self::passes_filter(widget, &api_data.root) {
             return Ok(None);
@@ -433,12 +497,31 @@ impl MultiLineRenderer {
         ))
     }
 
-    async fn fetch_api_data(&self, config: &WidgetApiConfig) -> Result<ApiData> {
+    async fn fetch_api_data(
+        &self,
+        config: &WidgetApiConfig,
+        origin: WidgetOrigin,
+    ) -> Result<ApiData> {
         let endpoint = config
             .endpoint
             .as_ref()
             .ok_or_else(|| anyhow!("API widget missing endpoint"))?;
 
+        if !origin.allows_secrets() {
+            let referenced = config
+                .headers
+                .iter()
+                .map(|(key, value)| (key.as_str(), value))
+                .chain([("endpoint", endpoint)])
+                .chain(config.base_url.as_ref().map(|base| ("base_url", base)))
+                .find(|(_, value)| utils::secrets::has_secret_reference(value));
+            if let Some((key, _)) = referenced {
+                anyhow::bail!(
+                    "{key} references a secret, which is only allowed for widgets in ~/.claude"
+                );
+            }
+        }
+
         // 替换endpoint中的环境变量
         let endpoint = substitute_env(endpoint);
 
@@ -460,37 +543,11 @@ impl MultiLineRenderer {
         };
 
         // 使用ureq同步客户端（在tokio::task::spawn_blocking中运行）
-        let url_clone = url.clone();
-        let timeout_ms = config.timeout;
+        let timeout = Duration::from_millis(config.timeout);
         let headers = config.headers.clone();
-        let method_str = method_str.to_string();
-
-        let json_result = tokio::task::spawn_blocking(move || -> Result<Value> {
-            let mut request =
-                ureq::request(&method_str, &url_clone).timeout(Duration::from_millis(timeout_ms));
-
-            // 添加headers
-            for (key, value) in &headers {
-                let substituted_value = substitute_env(value);
-                request = request.set(key, &substituted_value);
-            }
-
-            // 添加User-Agent
-            request = request.set("User-Agent", "claude-code-statusline/3.0");
-
-            // 发送请求
-            let response = request.call().context("ureq request failed")?;
-
-            // 解析JSON
-            let json: Value = response
-                .into_json()
-                .context("Failed to parse JSON response")?;
-
-            Ok(json)
-        })
-        .await??;
-
-        let json = json_result;
+        let json =
+            tokio::task::spawn_blocking(move || request_json(method_str, &url, timeout, &headers))
+                .await??;
 
         if let Some(path) = &config.data_path {
             let selected = {
@@ -535,6 +592,47 @@ impl MultiLineRenderer {
     }
 }
 
+/// Send an API widget request and parse the JSON response
+#[cfg(feature = "http")]
+fn request_json(
+    method: &str,
+    url: &str,
+    timeout: Duration,
+    headers: &HashMap<String, String>,
+) -> Result<Value> {
+    let mut request = ureq::request(method, url).timeout(timeout);
+
+    // 添加headers
+    // 先解析 env: / keyring: 密钥引用,再替换 ${VAR}
+    for (key, value) in headers {
+        let resolved = utils::secrets::resolve_secrets(value)
+            .with_context(|| format!("Failed to resolve header {key}"))?;
+        request = request.set(key, &substitute_env(&resolved));
+    }
+
+    // 添加User-Agent
+    request = request.set("User-Agent", "claude-code-statusline/3.0");
+
+    // 发送请求
+    let response = request.call().context("ureq request failed")?;
+
+    // 解析JSON
+    response
+        .into_json()
+        .context("Failed to parse JSON response")
+}
+
+/// API widgets are unavailable without the `http` feature
+#[cfg(not(feature = "http"))]
+fn request_json(
+    _method: &str,
+    _url: &str,
+    _timeout: Duration,
+    _headers: &HashMap<String, String>,
+) -> Result<Value> {
+    anyhow::bail!("API widgets require the `http` feature")
+}
+
 fn value_matches_filter(filter: &WidgetFilterConfig, data: &Value) -> bool {
     let Some(keyword) = filter.keyword.as_deref() else {
         return true;
@@ -640,19 +738,12 @@ impl MultiLineGrid {
     }
 }
 
-fn truncate_to_width(text: &str, max_width: usize) -> String {
-    if text.chars().count() <= max_width {
-        return text.to_string();
-    }
-    text.chars().take(max_width).collect()
-}
-
 fn select_widget_icon(
     widget: &WidgetConfig,
     terminal: &TerminalCapabilities,
     config: &Config,
 ) -> String {
-    if config.terminal.force_text {
+    if config.terminal.text_only() {
         return widget.text_icon.clone();
     }
     if config.terminal.force_nerd_font {
@@ -1403,8 +1494,11 @@ content = "Hello"
                 color_support: ColorSupport::TrueColor,
                 supports_emoji: true,
                 supports_nerd_font: false,
+                ..TerminalCapabilities::default()
             },
             preview_mode: false,
+            transcript: None,
+            storage: None,
         };
 
         let result = renderer.render_extension_lines(&context).await;
@@ -1458,6 +1552,8 @@ method = "GET"
             config: Arc::new(config),
             terminal: TerminalCapabilities::default(),
             preview_mode: false,
+            transcript: None,
+            storage: None,
         };
 
         let result = renderer.render_extension_lines(&context).await;
@@ -1466,6 +1562,110 @@ method = "GET"
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_working_dir_api_widget_is_refused() -> TestResult {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0")?;
+        listener.set_nonblocking(true)?;
+        let port = listener.local_addr()?.port();
+
+        let config = Config {
+            multiline: Some(MultilineConfig {
+                enabled: true,
+                max_rows: 5,
+                rows: HashMap::new(),
+            }),
+            ..Config::default()
+        };
+        let component_config: ComponentMultilineConfig = toml_edit::de::from_str(&format!(
+            r#"
+[widgets.sample]
+enabled = true
+type = "api"
+row = 1
+col = 0
+nerd_icon = ""
+emoji_icon = ""
+text_icon = ""
+
+[widgets.sample.api]
+endpoint = "http://127.0.0.1:{port}/quota"
+method = "GET"
+timeout = 500
+"#
+        ))?;
+        let mut renderer = MultiLineRenderer::new(config.clone(), None);
+        let candidates = renderer.component_file_candidates("usage");
+        assert_eq!(
+            candidates.last().map(|(_, origin)| *origin),
+            Some(WidgetOrigin::WorkingDir)
+        );
+
+        let context = RenderContext {
+            input: Arc::new(InputData::default()),
+            config: Arc::new(config),
+            terminal: TerminalCapabilities::default(),
+            preview_mode: false,
+            transcript: None,
+            storage: None,
+        };
+        let multiline_config = context
+            .config
+            .multiline
+            .clone()
+            .context("multiline config missing")?;
+        renderer
+            .render_component_widgets(
+                "usage",
+                &component_config,
+                WidgetOrigin::WorkingDir,
+                &context,
+                &multiline_config,
+            )
+            .await?;
+
+        assert!(renderer.grid.render(&multiline_config).is_empty());
+        assert!(matches!(
+            listener.accept(),
+            Err(err) if err.kind() == std::io::ErrorKind::WouldBlock
+        ));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_repo_widget_secret_headers_are_rejected() -> TestResult {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0")?;
+        listener.set_nonblocking(true)?;
+        let port = listener.local_addr()?.port();
+
+        let api_config: WidgetApiConfig = toml_edit::de::from_str(&format!(
+            r#"
+endpoint = "http://127.0.0.1:{port}/quota"
+method = "GET"
+timeout = 500
+
+[headers]
+Authorization = "Bearer ${{env:HOME}}"
+"#
+        ))?;
+        let renderer = MultiLineRenderer::new(Config::default(), None);
+
+        for origin in [WidgetOrigin::ConfigDir, WidgetOrigin::WorkingDir] {
+            let err = renderer
+                .fetch_api_data(&api_config, origin)
+                .await
+                .err()
+                .context("secret header was resolved")?;
+            assert!(err
+                .to_string()
+                .contains("Authorization references a secret"));
+        }
+        assert!(matches!(
+            listener.accept(),
+            Err(err) if err.kind() == std::io::ErrorKind::WouldBlock
+        ));
+        Ok(())
+    }
+
     #[test]
     fn test_expression_template_rendering() {
         let data = serde_json::json!({
@@ -1518,8 +1718,11 @@ method = "GET"
                 color_support: ColorSupport::TrueColor,
                 supports_emoji: false,
                 supports_nerd_font: false,
+                ..TerminalCapabilities::default()
             },
             preview_mode: false,
+            transcript: None,
+            storage: None,
         };
         Ok((renderer, context, temp_dir))
     }
@@ -1650,6 +1853,8 @@ data_path = "$.rate_limits.five_hour"
             config: first_context.config.clone(),
             terminal: first_context.terminal,
             preview_mode: first_context.preview_mode,
+            transcript: None,
+            storage: None,
         };
 
         let second_result = renderer.render_extension_lines(&second_context).await;
@@ -0,0 +1,346 @@
+//! Shared transcript analysis
+//!
+//! The transcript is parsed once per render and handed to every component via
+//! [`RenderContext`](crate::components::RenderContext), instead of each
+//! component opening and parsing the JSONL file on its own. Only the tail is
+//! kept: everything the statusline shows (current status, latest usage,
+//! recent tools, todos, compaction) lives in the last few hundred entries,
+//! which [`load`](TranscriptAnalysis::load) reads backwards from the end of the
+//! file via [`RevLines`] so large transcripts are never read in full.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use serde_json::Value;
+
+use crate::error::{StatuslineError, StatuslineResult};
+use crate::utils::RevLines;
+
+/// Number of trailing entries kept from the transcript
+pub const TAIL_ENTRIES: usize = 500;
+
+/// Token usage reported by the latest assistant entry
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TranscriptUsage {
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cache_creation_input_tokens: u64,
+    pub cache_read_input_tokens: u64,
+}
+
+impl TranscriptUsage {
+    /// Tokens occupying the context window
+    #[must_use]
+    pub const fn context_tokens(&self) -> u64 {
+        self.input_tokens + self.cache_creation_input_tokens + self.cache_read_input_tokens
+    }
+}
+
+/// Item of the latest `TodoWrite` call
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TodoItem {
+    pub content: String,
+    /// `pending` / `in_progress` / `completed`
+    pub status: String,
+}
+
+/// Latest compaction (`isCompactSummary`) found in the tail
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CompactionInfo {
+    pub uuid: Option<String>,
+    pub timestamp: Option<String>,
+}
+
+/// Parsed tail of a transcript
+#[derive(Debug, Clone, Default)]
+pub struct TranscriptAnalysis {
+    pub path: PathBuf,
+    pub modified: Option<SystemTime>,
+    pub len: u64,
+    /// Parsed entries, oldest first
+    entries: Vec<Value>,
+    pub last_usage: Option<TranscriptUsage>,
+    /// Tool names from `tool_use` items, newest first
+    pub recent_tool_names: Vec<String>,
+    pub todos: Vec<TodoItem>,
+    pub last_compaction: Option<CompactionInfo>,
+}
+
+impl TranscriptAnalysis {
+    /// Read and analyze the tail of the transcript at `path`.
+    ///
+    /// Only the last [`TAIL_ENTRIES`] entries are read, newest first.
+    /// Returns `None` when the file cannot be read.
+    #[must_use]
+    pub fn load(path: &Path) -> Option<Self> {
+        Self::read(path).ok()
+    }
+
+    /// Like [`Self::load`], reporting why the file cannot be read.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`StatuslineError::Transcript`] when the transcript cannot be
+    /// opened or read.
+    pub fn read(path: &Path) -> StatuslineResult<Self> {
+        let transcript_error = |err: std::io::Error| {
+            StatuslineError::Transcript(
+                anyhow::Error::new(err)
+                    .context(format!("Failed to read transcript: {}", path.display())),
+            )
+        };
+        let metadata = fs::metadata(path).map_err(transcript_error)?;
+        let lines = RevLines::open(path).map_err(transcript_error)?;
+        let mut entries = Vec::new();
+        for line in lines {
+            let line = line.map_err(transcript_error)?;
+            if let Some(entry) = parse_line(&line) {
+                entries.push(entry);
+                if entries.len() == TAIL_ENTRIES {
+                    break;
+                }
+            }
+        }
+        entries.reverse();
+
+        let mut analysis = Self::from_entries(entries);
+        analysis.path = path.to_path_buf();
+        analysis.modified = metadata.modified().ok();
+        analysis.len = metadata.len();
+        Ok(analysis)
+    }
+
+    /// Analyze transcript content (JSONL).
+    #[must_use]
+    pub fn from_jsonl(content: &str) -> Self {
+        let mut entries: Vec<Value> = content
+            .lines()
+            .rev()
+            .filter_map(parse_line)
+            .take(TAIL_ENTRIES)
+            .collect();
+        entries.reverse();
+        Self::from_entries(entries)
+    }
+
+    fn from_entries(entries: Vec<Value>) -> Self {
+        let mut analysis = Self::default();
+
+        for entry in entries.iter().rev() {
+            if analysis.last_usage.is_none() {
+                analysis.last_usage = usage_of(entry);
+            }
+            if analysis.last_compaction.is_none() && is_compact_summary(entry) {
+                analysis.last_compaction = Some(CompactionInfo {
+                    uuid: string_field(entry, "uuid"),
+                    timestamp: string_field(entry, "timestamp"),
+                });
+            }
+            for item in tool_use_items(entry) {
+                let Some(name) = item.get("name").and_then(Value::as_str) else {
+                    continue;
+                };
+                if analysis.todos.is_empty() && name == "TodoWrite" {
+                    analysis.todos = todos_of(item);
+                }
+                analysis.recent_tool_names.push(name.to_string());
+            }
+        }
+
+        analysis.entries = entries;
+        analysis
+    }
+
+    /// Whether this analysis still describes the file on disk
+    #[must_use]
+    pub fn is_fresh(&self, path: &Path) -> bool {
+        self.path == path
+            && fs::metadata(path).is_ok_and(|metadata| {
+                metadata.len() == self.len && metadata.modified().ok() == self.modified
+            })
+    }
+
+    /// Parsed entries of the tail, oldest first
+    #[must_use]
+    pub fn entries(&self) -> &[Value] {
+        &self.entries
+    }
+
+    /// Whether the tail holds no entries
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+fn parse_line(line: &str) -> Option<Value> {
+    let line = line.trim();
+    if line.is_empty() {
+        return None;
+    }
+    serde_json::from_str(line).ok()
+}
+
+fn string_field(entry: &Value, key: &str) -> Option<String> {
+    entry
+        .get(key)
+        .and_then(Value::as_str)
+        .map(std::string::ToString::to_string)
+}
+
+fn is_compact_summary(entry: &Value) -> bool {
+    entry
+        .get("isCompactSummary")
+        .and_then(Value::as_bool)
+        .unwrap_or(false)
+}
+
+fn usage_of(entry: &Value) -> Option<TranscriptUsage> {
+    if entry.get("type").and_then(Value::as_str) != Some("assistant") {
+        return None;
+    }
+    let usage = entry.get("message")?.get("usage")?;
+    let field = |key: &str| usage.get(key).and_then(Value::as_u64).unwrap_or(0);
+    Some(TranscriptUsage {
+        input_tokens: field("input_tokens"),
+        output_tokens: field("output_tokens"),
+        cache_creation_input_tokens: field("cache_creation_input_tokens"),
+        cache_read_input_tokens: field("cache_read_input_tokens"),
+    })
+}
+
+fn tool_use_items(entry: &Value) -> impl Iterator<Item = &Value> {
+    entry
+        .get("message")
+        .and_then(|message| message.get("content"))
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter(|item| item.get("type").and_then(Value::as_str) == Some("tool_use"))
+}
+
+fn todos_of(tool_use: &Value) -> Vec<TodoItem> {
+    tool_use
+        .get("input")
+        .and_then(|input| input.get("todos"))
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(|todo| {
+            Some(TodoItem {
+                content: string_field(todo, "content")?,
+                status: string_field(todo, "status").unwrap_or_else(|| "pending".to_string()),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn jsonl(entries: &[Value]) -> String {
+        entries
+            .iter()
+            .map(Value::to_string)
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn analysis_extracts_usage_tools_todos_and_compaction() {
+        let content = jsonl(&[
+            json!({"type": "user", "isCompactSummary": true, "uuid": "c1", "timestamp": "t0"}),
+            json!({
+                "type": "assistant",
+                "message": {
+                    "usage": {"input_tokens": 5, "output_tokens": 1},
+                    "content": [{
+                        "type": "tool_use",
+                        "name": "TodoWrite",
+                        "input": {"todos": [
+                            {"content": "write tests", "status": "in_progress"},
+                            {"content": "ship"}
+                        ]}
+                    }]
+                }
+            }),
+            json!({
+                "type": "assistant",
+                "message": {
+                    "usage": {"input_tokens": 10, "output_tokens": 2, "cache_read_input_tokens": 100},
+                    "content": [{"type": "tool_use", "name": "Bash"}]
+                }
+            }),
+        ]);
+
+        let analysis = TranscriptAnalysis::from_jsonl(&format!("{content}\nnot json\n\n"));
+
+        assert_eq!(analysis.entries().len(), 3);
+        assert_eq!(
+            analysis
+                .last_usage
+                .as_ref()
+                .map(TranscriptUsage::context_tokens),
+            Some(110)
+        );
+        assert_eq!(analysis.recent_tool_names, ["Bash", "TodoWrite"]);
+        assert_eq!(
+            analysis.todos,
+            [
+                TodoItem {
+                    content: "write tests".to_string(),
+                    status: "in_progress".to_string(),
+                },
+                TodoItem {
+                    content: "ship".to_string(),
+                    status: "pending".to_string(),
+                },
+            ]
+        );
+        assert_eq!(
+            analysis.last_compaction,
+            Some(CompactionInfo {
+                uuid: Some("c1".to_string()),
+                timestamp: Some("t0".to_string()),
+            })
+        );
+    }
+
+    #[test]
+    fn analysis_keeps_only_the_tail() {
+        let content = (0..TAIL_ENTRIES + 10)
+            .map(|idx| json!({"type": "user", "idx": idx}).to_string())
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        let analysis = TranscriptAnalysis::from_jsonl(&content);
+
+        assert_eq!(analysis.entries().len(), TAIL_ENTRIES);
+        assert_eq!(analysis.entries()[0]["idx"], json!(10));
+    }
+
+    #[test]
+    fn load_reads_tail_from_end_of_file() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("transcript.jsonl");
+        let content = (0..TAIL_ENTRIES + 10)
+            .map(|idx| json!({"type": "user", "idx": idx}).to_string())
+            .collect::<Vec<_>>()
+            .join("\n");
+        fs::write(&path, format!("{content}\n"))?;
+
+        let analysis = TranscriptAnalysis::load(&path).ok_or_else(|| anyhow::anyhow!("load"))?;
+
+        assert_eq!(analysis.entries().len(), TAIL_ENTRIES);
+        assert_eq!(analysis.entries()[0]["idx"], json!(10));
+        assert_eq!(
+            analysis.entries()[TAIL_ENTRIES - 1]["idx"],
+            json!(TAIL_ENTRIES + 9)
+        );
+        assert!(analysis.is_fresh(&path));
+        Ok(())
+    }
+}
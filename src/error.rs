@@ -0,0 +1,86 @@
+//! Library error type
+//!
+//! 公共 API(配置加载、输入解析、存储初始化、Git 探测、Transcript 读取、渲染)返回
+//! [`StatuslineError`],让嵌入方(npm 包装、未来的 GUI)按类别区分可恢复的环境问题
+//! 与程序缺陷。各变体透明包装内部的 [`anyhow::Error`],`Display` 与错误链保持不变;
+//! CLI 内部仍使用 anyhow,`?` 可直接转换。
+
+use thiserror::Error;
+
+/// Errors returned from the public library API
+#[derive(Debug, Error)]
+pub enum StatuslineError {
+    /// Configuration files cannot be read, parsed or validated
+    #[error(transparent)]
+    Config(anyhow::Error),
+    /// Claude Code input JSON cannot be read or parsed
+    #[error(transparent)]
+    Input(anyhow::Error),
+    /// Session storage cannot be initialized or written
+    #[error(transparent)]
+    Storage(anyhow::Error),
+    /// No usable git repository
+    #[error(transparent)]
+    Git(anyhow::Error),
+    /// The transcript file cannot be read
+    #[error(transparent)]
+    Transcript(anyhow::Error),
+    /// Rendering failed; indicates a bug rather than an environment problem
+    #[error(transparent)]
+    Render(anyhow::Error),
+}
+
+impl StatuslineError {
+    /// Whether the caller can recover, e.g. by fixing the config, retrying
+    /// or rendering without the failed part.
+    ///
+    /// 只有渲染错误视为缺陷,其余均源于外部环境。
+    #[must_use]
+    pub const fn is_recoverable(&self) -> bool {
+        !matches!(self, Self::Render(_))
+    }
+
+    /// The wrapped error chain
+    #[must_use]
+    pub const fn inner(&self) -> &anyhow::Error {
+        match self {
+            Self::Config(err)
+            | Self::Input(err)
+            | Self::Storage(err)
+            | Self::Git(err)
+            | Self::Transcript(err)
+            | Self::Render(err) => err,
+        }
+    }
+}
+
+/// Result alias used by the public library API
+pub type StatuslineResult<T> = Result<T, StatuslineError>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Context;
+
+    #[test]
+    fn errors_keep_message_and_chain() -> anyhow::Result<()> {
+        let err = StatuslineError::Config(
+            std::fs::read_to_string("/nonexistent/statusline/config.toml")
+                .context("Failed to read config file")
+                .err()
+                .ok_or_else(|| anyhow::anyhow!("read unexpectedly succeeded"))?,
+        );
+        assert!(err.is_recoverable());
+        assert_eq!(err.to_string(), "Failed to read config file");
+        assert!(std::error::Error::source(&err).is_some());
+
+        // CLI 侧转回 anyhow 后仍可按类别 downcast
+        let cli_err = anyhow::Error::from(err);
+        assert!(matches!(
+            cli_err.downcast_ref::<StatuslineError>(),
+            Some(StatuslineError::Config(_))
+        ));
+        assert!(!StatuslineError::Render(anyhow::anyhow!("bug")).is_recoverable());
+        Ok(())
+    }
+}
@@ -0,0 +1,163 @@
+//! Fetch freshness
+//!
+//! ahead/behind 计数只反映上一次 fetch 时远程分支的位置。这里读取
+//! `FETCH_HEAD` 的修改时间判断距上次 fetch 过了多久,供分支组件在超过
+//! `status.fetch_stale_minutes` 时附加过期标记;`status.auto_fetch_minutes`
+//! 大于 0 时在后台执行 `git fetch --quiet`,同一仓库在该间隔内最多启动一次。
+//! 只读取文件元数据并调用 `git` 可执行文件,不依赖 `git` 特性。
+
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::{Duration, SystemTime};
+
+use anyhow::{Context, Result};
+
+use crate::utils::git_remote::{common_dir, find_git_dir, remote_url_from_config};
+
+/// Log of the last background fetch; its mtime rate-limits new fetches
+const FETCH_LOG: &str = "statusline-fetch.log";
+
+/// When the repository containing a directory last fetched
+#[derive(Debug, Clone)]
+pub struct FetchState {
+    /// Repository directory shared by all worktrees
+    common_dir: PathBuf,
+    /// Whether any remote is configured
+    pub has_remote: bool,
+    /// Modification time of `FETCH_HEAD`; `None` if it never fetched
+    pub fetched_at: Option<SystemTime>,
+}
+
+impl FetchState {
+    /// Read the fetch state of the repository containing `dir`
+    #[must_use]
+    pub fn load(dir: &Path) -> Option<Self> {
+        let git_dir = find_git_dir(dir)?;
+        let common_dir = common_dir(&git_dir);
+        let has_remote = fs::read_to_string(common_dir.join("config"))
+            .ok()
+            .and_then(|config| remote_url_from_config(&config))
+            .is_some();
+        // FETCH_HEAD 可能写在工作树目录或共享目录,取较新的一个
+        let fetched_at = [&git_dir, &common_dir]
+            .iter()
+            .filter_map(|dir| modified(&dir.join("FETCH_HEAD")))
+            .max();
+        Some(Self {
+            common_dir,
+            has_remote,
+            fetched_at,
+        })
+    }
+
+    /// Whether the last fetch is older than `max_age`; a repository with a
+    /// remote that never fetched is stale, one without remotes never is
+    #[must_use]
+    pub fn is_stale(&self, max_age: Duration, now: SystemTime) -> bool {
+        self.has_remote
+            && self
+                .fetched_at
+                .is_none_or(|fetched| age(fetched, now) > max_age)
+    }
+
+    /// Whether a background fetch may start: neither a fetch nor a fetch
+    /// attempt happened within `interval`
+    #[must_use]
+    pub fn fetch_due(&self, interval: Duration, now: SystemTime) -> bool {
+        let last_attempt = modified(&self.common_dir.join(FETCH_LOG));
+        self.has_remote
+            && [self.fetched_at, last_attempt]
+                .into_iter()
+                .flatten()
+                .all(|at| age(at, now) >= interval)
+    }
+
+    /// Start `git fetch --quiet` in `dir` without waiting for it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the fetch log cannot be created or `git` cannot
+    /// be started.
+    pub fn spawn_fetch(&self, dir: &Path) -> Result<()> {
+        // 先写日志文件:它的修改时间即本次尝试时间,fetch 失败时同样限流
+        let log_path = self.common_dir.join(FETCH_LOG);
+        let log = File::create(&log_path)
+            .with_context(|| format!("Failed to create {}", log_path.display()))?;
+        let mut child = Command::new("git")
+            .args(["fetch", "--quiet"])
+            .current_dir(dir)
+            .env("GIT_TERMINAL_PROMPT", "0")
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(log)
+            .spawn()
+            .context("Failed to start `git fetch`")?;
+        std::thread::spawn(move || child.wait());
+        Ok(())
+    }
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|meta| meta.modified()).ok()
+}
+
+fn age(at: SystemTime, now: SystemTime) -> Duration {
+    now.duration_since(at).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MINUTE: Duration = Duration::from_secs(60);
+
+    fn repo(remote: bool) -> Result<tempfile::TempDir> {
+        let dir = tempfile::tempdir()?;
+        fs::create_dir(dir.path().join(".git"))?;
+        let config = if remote {
+            "[remote \"origin\"]\n\turl = git@github.com:org/repo.git\n"
+        } else {
+            "[core]\n\tbare = false\n"
+        };
+        fs::write(dir.path().join(".git/config"), config)?;
+        Ok(dir)
+    }
+
+    #[test]
+    fn staleness_follows_fetch_head() -> Result<()> {
+        let dir = repo(true)?;
+        let now = SystemTime::now();
+        let state = FetchState::load(dir.path()).context("not a repository")?;
+        assert!(state.fetched_at.is_none());
+        assert!(state.is_stale(60 * MINUTE, now));
+
+        fs::write(dir.path().join(".git/FETCH_HEAD"), "")?;
+        let state = FetchState::load(dir.path()).context("not a repository")?;
+        assert!(!state.is_stale(60 * MINUTE, now));
+        assert!(state.is_stale(60 * MINUTE, now + 61 * MINUTE));
+
+        let local = repo(false)?;
+        let state = FetchState::load(local.path()).context("not a repository")?;
+        assert!(!state.is_stale(60 * MINUTE, now));
+        Ok(())
+    }
+
+    #[test]
+    fn background_fetch_is_rate_limited() -> Result<()> {
+        let dir = repo(true)?;
+        let now = SystemTime::now();
+        let state = FetchState::load(dir.path()).context("not a repository")?;
+        assert!(state.fetch_due(15 * MINUTE, now));
+
+        // 上一次尝试(即使失败)同样计入间隔
+        fs::write(dir.path().join(".git").join(FETCH_LOG), "")?;
+        assert!(!state.fetch_due(15 * MINUTE, now));
+        assert!(state.fetch_due(15 * MINUTE, now + 16 * MINUTE));
+
+        let local = repo(false)?;
+        let state = FetchState::load(local.path()).context("not a repository")?;
+        assert!(!state.fetch_due(15 * MINUTE, now + 16 * MINUTE));
+        Ok(())
+    }
+}
@@ -1,5 +1,31 @@
+//! Git repository inspection
+//!
+//! [`GitService`](crate::git::GitService)(需要 `git` 特性)基于 libgit2 收集分支、
+//! 工作区状态、stash、进行中的操作与版本信息,结果是可序列化的自有类型
+//! [`GitInfo`]。它是公开 API,下游工具可以直接复用:
+//!
+//! ```no_run
+//! # #[cfg(feature = "git")]
+//! # async fn demo() -> claude_code_statusline_pro::StatuslineResult<()> {
+//! use claude_code_statusline_pro::git::{GitCollectionOptions, GitService};
+//!
+//! let options = GitCollectionOptions::branch_only().with_status(true);
+//! let info = GitService::collect_info_async(".", options).await?;
+//! println!("{} dirty={}", info.branch.current, !info.status.clean);
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`fetch`] 与 [`sparse`] 只读取 `.git` 下的文件,不依赖 `git` 特性。
+
+pub mod fetch;
+#[cfg(feature = "git")]
 mod service;
+pub mod sparse;
 mod types;
 
+pub use fetch::FetchState;
+#[cfg(feature = "git")]
 pub use service::{GitCollectionOptions, GitService};
+pub use sparse::SparseCone;
 pub use types::*;
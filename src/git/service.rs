@@ -1,23 +1,48 @@
 use std::path::{Path, PathBuf};
 
 use anyhow::{Context, Result};
-use git2::{BranchType, DescribeOptions, Repository, Status, StatusOptions};
+use git2::{
+    BranchType, DescribeOptions, ErrorCode, IndexEntryExtendedFlag, Repository, Status,
+    StatusOptions,
+};
+
+use crate::error::{StatuslineError, StatuslineResult};
 
+use super::sparse::SparseCone;
 use super::types::{
-    GitBranchInfo, GitInfo, GitOperationStatus, GitStashInfo, GitVersionInfo, GitWorkingStatus,
+    GitBranchInfo, GitInfo, GitOperationStatus, GitRepoStats, GitStashInfo, GitVersionInfo,
+    GitWorkingStatus,
 };
 
-#[derive(Debug, Clone, Copy)]
+/// What [`GitService::collect_info_with_options`] collects besides the
+/// branch.
+///
+/// Built with [`all`](Self::all) (the default) or
+/// [`branch_only`](Self::branch_only) and the `with_*` methods:
+///
+/// ```
+/// use claude_code_statusline_pro::git::GitCollectionOptions;
+///
+/// let options = GitCollectionOptions::branch_only().with_status(true);
+/// assert!(options.include_status && !options.include_stash);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
 #[allow(clippy::struct_excessive_bools)]
 pub struct GitCollectionOptions {
+    /// Working tree status counts (the most expensive part)
     pub include_status: bool,
     pub include_stash: bool,
+    /// In-progress rebase / merge / cherry-pick / bisect
     pub include_operation: bool,
+    /// `HEAD` commit and nearest tag
     pub include_version: bool,
 }
 
-impl Default for GitCollectionOptions {
-    fn default() -> Self {
+impl GitCollectionOptions {
+    /// Collect everything
+    #[must_use]
+    pub const fn all() -> Self {
         Self {
             include_status: true,
             include_stash: true,
@@ -25,13 +50,61 @@ impl Default for GitCollectionOptions {
             include_version: true,
         }
     }
+
+    /// Collect the branch and its ahead/behind counts only
+    #[must_use]
+    pub const fn branch_only() -> Self {
+        Self {
+            include_status: false,
+            include_stash: false,
+            include_operation: false,
+            include_version: false,
+        }
+    }
+
+    #[must_use]
+    pub const fn with_status(mut self, include: bool) -> Self {
+        self.include_status = include;
+        self
+    }
+
+    #[must_use]
+    pub const fn with_stash(mut self, include: bool) -> Self {
+        self.include_stash = include;
+        self
+    }
+
+    #[must_use]
+    pub const fn with_operation(mut self, include: bool) -> Self {
+        self.include_operation = include;
+        self
+    }
+
+    #[must_use]
+    pub const fn with_version(mut self, include: bool) -> Self {
+        self.include_version = include;
+        self
+    }
+}
+
+impl Default for GitCollectionOptions {
+    fn default() -> Self {
+        Self::all()
+    }
 }
 
 /// High level helper around git repositories.
+///
+/// libgit2 调用是阻塞的;在异步上下文中使用
+/// [`collect_info_async`](Self::collect_info_async) /
+/// [`repo_stats_async`](Self::repo_stats_async),它们在阻塞线程池里完成探测与收集。
 pub struct GitService {
     repo: Repository,
-    workdir: PathBuf,
+    /// Working tree; `None` for bare repositories
+    workdir: Option<PathBuf>,
     git_dir: PathBuf,
+    /// Discovered from a path inside `git_dir`
+    inside_git_dir: bool,
 }
 
 impl GitService {
@@ -41,31 +114,81 @@ impl GitService {
     ///
     /// Returns an error if Git repository discovery fails or if the working
     /// directory cannot be determined.
-    pub fn discover<P: AsRef<Path>>(path: P) -> Result<Self> {
-        let repo = Repository::discover(path.as_ref()).with_context(|| {
-            format!(
-                "Failed to locate git repository from {}",
-                path.as_ref().display()
-            )
-        })?;
-
-        let workdir = repo.workdir().map_or_else(
-            || {
-                repo.path()
-                    .parent()
-                    .map_or_else(|| PathBuf::from("."), Path::to_path_buf)
-            },
-            Path::to_path_buf,
-        );
+    pub fn discover<P: AsRef<Path>>(path: P) -> StatuslineResult<Self> {
+        let repo = Repository::discover(path.as_ref())
+            .with_context(|| {
+                format!(
+                    "Failed to locate git repository from {}",
+                    path.as_ref().display()
+                )
+            })
+            .map_err(StatuslineError::Git)?;
+
+        let workdir = repo.workdir().map(Path::to_path_buf);
         let git_dir = repo.path().to_path_buf();
+        let inside_git_dir = workdir.is_some() && is_within(path.as_ref(), &git_dir);
 
         Ok(Self {
             repo,
             workdir,
             git_dir,
+            inside_git_dir,
         })
     }
 
+    /// Discover the repository containing `path` and collect `options` on the
+    /// blocking thread pool.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`StatuslineError::Git`] if no repository contains `path` or
+    /// the collection task is cancelled.
+    pub async fn collect_info_async(
+        path: impl Into<PathBuf>,
+        options: GitCollectionOptions,
+    ) -> StatuslineResult<GitInfo> {
+        let path = path.into();
+        Self::blocking(move || {
+            Self::discover(&path).map(|service| service.collect_info_with_options(&options))
+        })
+        .await
+    }
+
+    /// Discover the repository containing `path` and measure it on the
+    /// blocking thread pool.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`StatuslineError::Git`] if no repository contains `path` or
+    /// the task is cancelled.
+    pub async fn repo_stats_async(path: impl Into<PathBuf>) -> StatuslineResult<GitRepoStats> {
+        let path = path.into();
+        Self::blocking(move || Self::discover(&path).map(|service| service.repo_stats())).await
+    }
+
+    async fn blocking<T, F>(job: F) -> StatuslineResult<T>
+    where
+        F: FnOnce() -> StatuslineResult<T> + Send + 'static,
+        T: Send + 'static,
+    {
+        tokio::task::spawn_blocking(job).await.map_err(|err| {
+            StatuslineError::Git(anyhow::Error::new(err).context("git collection task failed"))
+        })?
+    }
+
+    /// Whether the repository is bare (has no working tree).
+    #[must_use]
+    pub const fn is_bare(&self) -> bool {
+        self.workdir.is_none()
+    }
+
+    /// Whether working tree status can be collected: not bare, and not
+    /// invoked from inside the git directory.
+    #[must_use]
+    pub const fn has_worktree(&self) -> bool {
+        !self.is_bare() && !self.inside_git_dir
+    }
+
     /// Collect a snapshot of repository state.
     #[must_use]
     pub fn collect_info(&self) -> GitInfo {
@@ -76,12 +199,20 @@ impl GitService {
     #[must_use]
     pub fn collect_info_with_options(&self, options: &GitCollectionOptions) -> GitInfo {
         let branch = self.branch_info().unwrap_or_default();
-        let status = if options.include_status {
-            self.working_status().unwrap_or_default()
+        let worktree = self.has_worktree();
+        let sparse = self.config_flag("core.sparseCheckout");
+        let status = if !worktree {
+            // 没有工作树可比较,视为干净而不是"未知"
+            GitWorkingStatus {
+                clean: true,
+                ..GitWorkingStatus::default()
+            }
+        } else if options.include_status {
+            self.working_status(sparse).unwrap_or_default()
         } else {
             GitWorkingStatus::default()
         };
-        let stash = if options.include_stash {
+        let stash = if options.include_stash && worktree {
             self.stash_info().unwrap_or_default()
         } else {
             GitStashInfo::default()
@@ -99,6 +230,10 @@ impl GitService {
 
         GitInfo {
             is_repo: true,
+            bare: self.is_bare(),
+            inside_git_dir: self.inside_git_dir,
+            sparse,
+            partial_clone: self.is_partial_clone(),
             branch,
             status,
             stash,
@@ -113,8 +248,57 @@ impl GitService {
         self.repo.index().map_or(0, |index| index.len())
     }
 
+    /// Tracked entries, size of the pack files and number of loose objects.
+    ///
+    /// 只看对象库:遍历整个 `.git`(LFS 缓存、reflog、worktrees)在大仓库上要
+    /// 几秒,而包文件才是体积的主体。
+    #[must_use]
+    pub fn repo_stats(&self) -> GitRepoStats {
+        let objects = self.repo.commondir().join("objects");
+        GitRepoStats {
+            entries: self.estimate_workdir_entries(),
+            pack_bytes: files_size(&objects.join("pack")),
+            loose_objects: loose_object_count(&objects),
+        }
+    }
+
+    /// Changes whenever [`Self::repo_stats`] may: the modification times of
+    /// the pack directory and the index
+    #[must_use]
+    pub fn repo_stats_key(&self) -> String {
+        let pack_dir = self.repo.commondir().join("objects").join("pack");
+        format!(
+            "{}:{}",
+            modified_nanos(&pack_dir),
+            modified_nanos(&self.git_dir.join("index"))
+        )
+    }
+
+    /// The git directory (`.git`, or `.git/worktrees/<name>` of a linked
+    /// worktree)
+    #[must_use]
+    pub fn git_dir(&self) -> &Path {
+        &self.git_dir
+    }
+
     fn branch_info(&self) -> Result<GitBranchInfo> {
-        let head = self.repo.head()?;
+        let head = match self.repo.head() {
+            Ok(head) => head,
+            // 新建仓库还没有提交:分支名取自 HEAD 的符号引用
+            Err(err) if err.code() == ErrorCode::UnbornBranch => {
+                let reference = self.repo.find_reference("HEAD")?;
+                let current = reference
+                    .symbolic_target()?
+                    .and_then(|target| target.strip_prefix("refs/heads/"))
+                    .unwrap_or("HEAD")
+                    .to_string();
+                return Ok(GitBranchInfo {
+                    current,
+                    ..GitBranchInfo::default()
+                });
+            }
+            Err(err) => return Err(err.into()),
+        };
         let detached = !head.is_branch();
 
         let current = if detached {
@@ -155,7 +339,60 @@ impl GitService {
         Ok(info)
     }
 
-    fn working_status(&self) -> Result<GitWorkingStatus> {
+    fn config_flag(&self, name: &str) -> bool {
+        self.repo
+            .config()
+            .and_then(|config| config.get_bool(name))
+            .unwrap_or(false)
+    }
+
+    /// `extensions.partialClone` or any `remote.<name>.promisor = true`
+    fn is_partial_clone(&self) -> bool {
+        let Ok(config) = self.repo.config() else {
+            return false;
+        };
+        if config.get_string("extensions.partialClone").is_ok() {
+            return true;
+        }
+        let mut promisor = false;
+        if let Ok(entries) = config.entries(Some(r"remote\..*\.promisor")) {
+            let _ = entries.for_each(|entry| {
+                promisor |= entry
+                    .value()
+                    .is_ok_and(|value| value.eq_ignore_ascii_case("true"));
+            });
+        }
+        promisor
+    }
+
+    /// Cone of a cone-mode sparse checkout; `None` in non-cone mode
+    fn sparse_cone(&self) -> Option<SparseCone> {
+        if !self.config_flag("core.sparseCheckoutCone") {
+            return None;
+        }
+        std::fs::read_to_string(self.git_dir.join("info/sparse-checkout"))
+            .ok()
+            .map(|content| SparseCone::parse(&content))
+    }
+
+    fn working_status(&self, sparse: bool) -> Result<GitWorkingStatus> {
+        // 稀疏检出时只统计 cone 内的路径,并忽略带 skip-worktree 标记(未检出)的条目
+        let (index, cone) = if sparse {
+            (Some(self.repo.index()?), self.sparse_cone())
+        } else {
+            (None, None)
+        };
+        let outside_checkout = |path: &str| {
+            cone.as_ref().is_some_and(|cone| !cone.contains(path))
+                || index
+                    .as_ref()
+                    .and_then(|index| index.get_path(Path::new(path), 0))
+                    .is_some_and(|entry| {
+                        IndexEntryExtendedFlag::from_bits_truncate(entry.flags_extended)
+                            .is_skip_worktree()
+                    })
+        };
+
         let mut opts = StatusOptions::new();
         opts.include_untracked(true)
             .recurse_untracked_dirs(true)
@@ -168,6 +405,9 @@ impl GitService {
 
         for entry in statuses.iter() {
             let status = entry.status();
+            if sparse && entry.path().is_ok_and(outside_checkout) {
+                continue;
+            }
 
             if status.intersects(Status::CONFLICTED) {
                 result.conflicted += 1;
@@ -207,7 +447,7 @@ impl GitService {
 
     fn stash_info(&self) -> Result<GitStashInfo> {
         let mut count = 0usize;
-        let mut repo = Repository::open(&self.workdir)
+        let mut repo = Repository::open(&self.git_dir)
             .with_context(|| "Failed to open repository for stash inspection")?;
         repo.stash_foreach(|_, _, _| {
             count += 1;
@@ -263,9 +503,196 @@ impl GitService {
         })
     }
 
-    /// Expose repository workdir for callers that need it.
+    /// Expose repository workdir for callers that need it; `None` for bare
+    /// repositories.
     #[must_use]
-    pub fn workdir(&self) -> &Path {
-        &self.workdir
+    pub fn workdir(&self) -> Option<&Path> {
+        self.workdir.as_deref()
+    }
+}
+
+/// Whether `path` is `dir` or below it, comparing canonical paths
+fn is_within(path: &Path, dir: &Path) -> bool {
+    match (path.canonicalize(), dir.canonicalize()) {
+        (Ok(path), Ok(dir)) => path.starts_with(dir),
+        _ => false,
+    }
+}
+
+/// Total size of the regular files directly inside `dir`
+fn files_size(dir: &Path) -> u64 {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return 0;
+    };
+    entries
+        .flatten()
+        .filter_map(|entry| entry.metadata().ok())
+        .filter(std::fs::Metadata::is_file)
+        .map(|meta| meta.len())
+        .sum()
+}
+
+/// Files in the `objects/xx/` fan-out directories
+fn loose_object_count(objects: &Path) -> usize {
+    let Ok(entries) = std::fs::read_dir(objects) else {
+        return 0;
+    };
+    entries
+        .flatten()
+        .filter(|entry| {
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            name.len() == 2 && name.chars().all(|ch| ch.is_ascii_hexdigit())
+        })
+        .filter_map(|entry| std::fs::read_dir(entry.path()).ok())
+        .map(Iterator::count)
+        .sum()
+}
+
+/// Modification time of `path` in nanoseconds since the epoch; 0 when missing
+fn modified_nanos(path: &Path) -> u128 {
+    std::fs::metadata(path)
+        .and_then(|meta| meta.modified())
+        .ok()
+        .and_then(|modified| modified.duration_since(std::time::UNIX_EPOCH).ok())
+        .map_or(0, |age| age.as_nanos())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn async_collection_returns_serializable_snapshots() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        Repository::init(dir.path())?.set_head("refs/heads/trunk")?;
+
+        let info = GitService::collect_info_async(
+            dir.path(),
+            GitCollectionOptions::branch_only().with_status(true),
+        )
+        .await?;
+        assert_eq!(info.branch.current, "trunk");
+        assert!(info.status.clean);
+
+        let json = serde_json::to_string(&info)?;
+        assert_eq!(serde_json::from_str::<GitInfo>(&json)?, info);
+        // 缺失字段取默认值
+        let partial: GitInfo = serde_json::from_str(r#"{"branch": {"current": "main"}}"#)?;
+        assert_eq!(partial.branch.current, "main");
+
+        let missing = tempfile::tempdir()?;
+        let err = GitService::collect_info_async(missing.path(), GitCollectionOptions::all())
+            .await
+            .err()
+            .context("collected outside a repository")?;
+        assert!(matches!(err, StatuslineError::Git(_)));
+        Ok(())
+    }
+
+    #[test]
+    fn bare_repositories_skip_status() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let repo = Repository::init_bare(dir.path())?;
+        repo.set_head("refs/heads/main")?;
+
+        let service = GitService::discover(dir.path())?;
+        assert!(service.is_bare());
+        assert!(!service.has_worktree());
+        assert_eq!(service.workdir(), None);
+
+        let info = service.collect_info();
+        assert!(info.bare);
+        assert_eq!(info.branch.current, "main");
+        assert!(info.status.clean);
+        Ok(())
+    }
+
+    #[test]
+    fn repo_stats_measure_only_the_object_store() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let repo = Repository::init(dir.path())?;
+        repo.blob(b"loose")?;
+        let git_dir = dir.path().join(".git");
+        std::fs::write(git_dir.join("objects/pack/pack-1.pack"), [0_u8; 100])?;
+        std::fs::create_dir_all(git_dir.join("lfs/objects"))?;
+        std::fs::write(git_dir.join("lfs/objects/big"), [0_u8; 4096])?;
+
+        let service = GitService::discover(dir.path())?;
+        let key = service.repo_stats_key();
+        let stats = service.repo_stats();
+        assert_eq!(stats.pack_bytes, 100);
+        assert_eq!(stats.loose_objects, 1);
+        assert_eq!(service.repo_stats_key(), key);
+        Ok(())
+    }
+
+    #[test]
+    fn git_directory_invocations_skip_status() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        Repository::init(dir.path())?;
+        std::fs::write(dir.path().join("untracked.txt"), "x")?;
+
+        let service = GitService::discover(dir.path())?;
+        assert!(service.has_worktree());
+        assert_eq!(service.collect_info().status.untracked, 1);
+
+        let service = GitService::discover(dir.path().join(".git/refs"))?;
+        assert!(!service.is_bare());
+        assert!(!service.has_worktree());
+        let info = service.collect_info();
+        assert!(info.inside_git_dir);
+        assert!(info.status.clean);
+        assert_eq!(info.status.untracked, 0);
+        Ok(())
+    }
+
+    #[test]
+    fn sparse_checkouts_only_count_the_cone() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let repo = Repository::init(dir.path())?;
+        for file in ["README.md", "services/api/main.rs", "vendor/lib.c"] {
+            let path = dir.path().join(file);
+            std::fs::create_dir_all(path.parent().context("no parent")?)?;
+            std::fs::write(path, "v1")?;
+        }
+        let mut index = repo.index()?;
+        index.add_all(["*"], git2::IndexAddOption::DEFAULT, None)?;
+        let tree = repo.find_tree(index.write_tree()?)?;
+        let signature = git2::Signature::now("dev", "dev@example.com")?;
+        repo.commit(Some("HEAD"), &signature, &signature, "init", &tree, &[])?;
+
+        // vendor/ 不在 cone 内:标记 skip-worktree 并从工作树删除
+        let mut entry = index
+            .get_path(Path::new("vendor/lib.c"), 0)
+            .context("missing entry")?;
+        entry.flags_extended |= IndexEntryExtendedFlag::SKIP_WORKTREE.bits();
+        index.add(&entry)?;
+        index.write()?;
+        std::fs::remove_dir_all(dir.path().join("vendor"))?;
+
+        let mut config = repo.config()?;
+        config.set_bool("core.sparseCheckout", true)?;
+        config.set_bool("core.sparseCheckoutCone", true)?;
+        std::fs::write(
+            dir.path().join(".git/info/sparse-checkout"),
+            "/*\n!/*/\n/services/\n",
+        )?;
+        std::fs::write(dir.path().join("README.md"), "v2")?;
+        std::fs::write(dir.path().join("services/api/new.rs"), "")?;
+
+        let info = GitService::discover(dir.path())?.collect_info();
+        assert!(info.sparse);
+        assert!(!info.partial_clone);
+        assert_eq!(info.status.unstaged, 1);
+        assert_eq!(info.status.untracked, 1);
+
+        config.set_str("remote.origin.promisor", "true")?;
+        assert!(
+            GitService::discover(dir.path())?
+                .collect_info()
+                .partial_clone
+        );
+        Ok(())
     }
 }
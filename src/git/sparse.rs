@@ -0,0 +1,84 @@
+//! Sparse checkout cone
+//!
+//! 稀疏检出(`core.sparseCheckout`)只把仓库的一部分放进工作树。cone 模式下
+//! `info/sparse-checkout` 的格式固定:
+//!
+//! ```text
+//! /*
+//! !/*/
+//! /services/
+//! !/services/*/
+//! /services/api/
+//! ```
+//!
+//! 根目录文件总是检出;`/services/` 后紧跟 `!/services/*/` 表示只检出该目录下的
+//! 文件,`/services/api/` 则整个目录递归检出。状态收集只统计 cone 内的路径。
+
+/// Directories checked out by a cone-mode sparse checkout
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SparseCone {
+    /// Directories checked out recursively (`services/api/`)
+    recursive: Vec<String>,
+    /// Directories whose direct files only are checked out (`services/`)
+    parents: Vec<String>,
+}
+
+impl SparseCone {
+    /// Parse a cone-mode `info/sparse-checkout` file
+    #[must_use]
+    pub fn parse(content: &str) -> Self {
+        let lines: Vec<&str> = content
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .collect();
+        let mut cone = Self::default();
+        for line in &lines {
+            let Some(dir) = line.strip_prefix('/').filter(|dir| dir.ends_with('/')) else {
+                continue;
+            };
+            if dir == "*/" {
+                continue;
+            }
+            let excluded = format!("!/{dir}*/");
+            if lines.contains(&excluded.as_str()) {
+                cone.parents.push(dir.to_string());
+            } else {
+                cone.recursive.push(dir.to_string());
+            }
+        }
+        cone
+    }
+
+    /// Whether the repository-relative `path` lies inside the cone
+    #[must_use]
+    pub fn contains(&self, path: &str) -> bool {
+        if !path.contains('/') {
+            return true;
+        }
+        self.recursive
+            .iter()
+            .any(|dir| path.starts_with(dir.as_str()))
+            || self.parents.iter().any(|dir| {
+                path.strip_prefix(dir.as_str())
+                    .is_some_and(|rest| !rest.contains('/'))
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cone_includes_root_parents_and_recursive_dirs() {
+        let cone =
+            SparseCone::parse("/*\n!/*/\n/services/\n!/services/*/\n/services/api/\n/docs/\n");
+        assert!(cone.contains("README.md"));
+        assert!(cone.contains("services/Makefile"));
+        assert!(cone.contains("services/api/src/main.rs"));
+        assert!(cone.contains("docs/guide/intro.md"));
+        assert!(!cone.contains("services/web/index.ts"));
+        assert!(!cone.contains("vendor/lib.c"));
+    }
+}
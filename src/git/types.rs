@@ -1,16 +1,31 @@
-use std::default::Default;
+//! Owned repository snapshot types
+//!
+//! [`GitService`](super::GitService) 返回的数据全部为自有类型,可跨线程传递,
+//! 并实现 serde 序列化,便于下游工具缓存或通过 JSON 转发。反序列化时缺失的字段
+//! 取默认值,旧版本写出的快照仍可读取。
 
-#[derive(Debug, Clone, PartialEq, Eq, Default)]
+use serde::{Deserialize, Serialize};
+
+/// Current branch and its distance to the upstream
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(default)]
 pub struct GitBranchInfo {
+    /// Branch name, or `HEAD@<short id>` when detached
     pub current: String,
+    /// Upstream branch (`origin/main`)
     pub upstream: Option<String>,
     pub detached: bool,
+    /// Commits not yet on the upstream
     pub ahead: usize,
+    /// Upstream commits not yet merged
     pub behind: usize,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Default)]
+/// Working tree status counts
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(default)]
 pub struct GitWorkingStatus {
+    /// No staged, unstaged, untracked or conflicted entries
     pub clean: bool,
     pub staged: usize,
     pub unstaged: usize,
@@ -18,12 +33,16 @@ pub struct GitWorkingStatus {
     pub conflicted: usize,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Default)]
+/// Stash entries
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(default)]
 pub struct GitStashInfo {
     pub count: usize,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Default)]
+/// In-progress operations (rebase, merge, cherry-pick / revert, bisect)
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(default)]
 #[allow(clippy::struct_excessive_bools)]
 pub struct GitOperationStatus {
     pub rebasing: bool,
@@ -32,7 +51,9 @@ pub struct GitOperationStatus {
     pub bisecting: bool,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Default)]
+/// `HEAD` commit and the nearest tag
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(default)]
 pub struct GitVersionInfo {
     pub commit_id: String,
     pub short_commit_id: String,
@@ -42,9 +63,34 @@ pub struct GitVersionInfo {
     pub tag: Option<String>,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Default)]
+/// Repository scale
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GitRepoStats {
+    /// Entries in the index (tracked files)
+    pub entries: usize,
+    /// Total size of the pack files in bytes
+    pub pack_bytes: u64,
+    /// Objects not packed yet (`objects/xx/*`)
+    pub loose_objects: usize,
+}
+
+/// Snapshot of a repository, as collected by
+/// [`GitService::collect_info_with_options`](super::GitService::collect_info_with_options)
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(default)]
+#[allow(clippy::struct_excessive_bools)]
 pub struct GitInfo {
+    /// `false` only for the default value; collected snapshots are repositories
     pub is_repo: bool,
+    /// Bare repository: no working tree, status is not collected
+    pub bare: bool,
+    /// Invoked from inside the git directory of a non-bare repository
+    pub inside_git_dir: bool,
+    /// `core.sparseCheckout`: status only covers the checked-out subset
+    pub sparse: bool,
+    /// Partial clone (promisor remote): objects are fetched on demand
+    pub partial_clone: bool,
     pub branch: GitBranchInfo,
     pub status: GitWorkingStatus,
     pub stash: GitStashInfo,
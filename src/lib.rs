@@ -15,11 +15,15 @@
 pub mod components;
 pub mod config;
 pub mod core;
+pub mod error;
 pub mod git;
 pub mod storage;
 pub mod terminal;
 pub mod themes;
 pub mod utils;
+pub mod vcs;
+
+pub use error::{StatuslineError, StatuslineResult};
 
 /// 库版本
 pub const VERSION: &str = env!("CARGO_PKG_VERSION");
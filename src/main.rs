@@ -6,20 +6,38 @@
 //! multi-line widgets, and statusline generation.
 
 use std::fs;
+use std::io::{IsTerminal, Write};
 use std::path::{Path, PathBuf};
+use std::time::Duration;
 
 use anyhow::{anyhow, bail, Context, Result};
-use clap::{Args as ClapArgs, Parser, Subcommand};
+use clap::{Args as ClapArgs, CommandFactory, Parser, Subcommand};
+use clap_complete::Shell;
 use claude_code_statusline_pro::{
+    components::{ComponentRegistry, UsageComponent},
     config::{
-        AutoDetect, ConfigLoader, ConfigSourceType, CreateConfigOptions, TerminalCapabilityHint,
+        backup, AutoDetect, ComponentConflict, ComponentCopyStats, Config, ConfigLoader,
+        ConfigSourceType, ConflictAction, CopyComponentOptions, CreateConfigOptions,
+        TerminalCapabilityHint,
     },
-    core::{GeneratorOptions, InputData, StatuslineGenerator},
+    core::{
+        fixtures, input_schema, input_version, GeneratorOptions, InputData, StatuslineGenerator,
+    },
+    storage,
+    utils::secrets,
+    vcs,
 };
-use dialoguer::Confirm;
+use dialoguer::{Confirm, Select};
 use toml_edit::{Array, DocumentMut, Item, Table, Value as TomlEditValue};
 
+/// 默认的 stdin 等待时间(毫秒)
+const DEFAULT_STDIN_TIMEOUT_MS: u64 = 5000;
+
+/// stdin 是终端时用于预览的 mock 场景
+const TTY_MOCK_SCENARIO: &str = "dev";
+
 mod mock_data;
+mod preview;
 mod tui;
 use mock_data::MockDataGenerator;
 
@@ -27,7 +45,7 @@ use mock_data::MockDataGenerator;
 #[command(name = "claude-code-statusline-pro")]
 #[command(author, version, about = "Claude Code Statusline Pro - Rust Edition", long_about = None)]
 struct Cli {
-    /// 预设字符串（例如 PMBT、PMBTURS）
+    /// 预设字符串（例如 PMBT、PMBTURS，或逗号分隔的组件名 project,model,tokens）
     #[arg(value_name = "PRESET")]
     preset: Option<String>,
 
@@ -43,6 +61,14 @@ struct Cli {
     #[arg(short = 't', long = "theme")]
     theme: Option<String>,
 
+    /// 仅本次调用覆盖 components.order（逗号分隔，例如 project,branch,status），不修改配置文件
+    #[arg(
+        long = "components",
+        value_delimiter = ',',
+        conflicts_with_all = ["preset", "preset_override"]
+    )]
+    components: Option<Vec<String>>,
+
     /// 禁用颜色输出
     #[arg(long = "no-colors", action = clap::ArgAction::SetTrue)]
     no_colors: bool,
@@ -67,14 +93,36 @@ struct Cli {
     #[arg(long = "force-text", action = clap::ArgAction::SetTrue)]
     force_text: bool,
 
+    /// 严格 ASCII 模式:在纯文本模式基础上,进度条、分隔符和符号也全部换成 ASCII
+    #[arg(long = "ascii", action = clap::ArgAction::SetTrue)]
+    ascii: bool,
+
     /// 启用调试输出
     #[arg(short, long, action = clap::ArgAction::SetTrue)]
     debug: bool,
 
-    /// 使用预置的 Mock 场景生成状态行
+    /// 使用 Mock 场景生成状态行(场景名,或 JSON payload 文件路径)
     #[arg(long = "mock")]
     mock: Option<String>,
 
+    /// 等待 stdin 输入的最长时间(毫秒),超时后按空输入渲染
+    #[arg(long = "stdin-timeout", value_name = "MS", default_value_t = DEFAULT_STDIN_TIMEOUT_MS)]
+    stdin_timeout: u64,
+
+    /// 渲染前回显收到的 stdin JSON(格式化并脱敏);默认写到 stderr,可指定文件
+    #[arg(long = "print-input", value_name = "FILE", num_args = 0..=1, default_missing_value = "-")]
+    print_input: Option<PathBuf>,
+
+    /// 重新生成渲染 fixture 的 golden 输出(开发用,默认 tests/fixtures/render)
+    #[arg(
+        long = "render-fixtures",
+        value_name = "DIR",
+        num_args = 0..=1,
+        default_missing_value = fixtures::DEFAULT_FIXTURES_DIR,
+        hide = true
+    )]
+    render_fixtures: Option<PathBuf>,
+
     /// 子命令
     #[command(subcommand)]
     command: Option<Commands>,
@@ -82,6 +130,8 @@ struct Cli {
 
 #[derive(Subcommand, Debug)]
 enum Commands {
+    /// 渲染状态行(与不带子命令相同),可用 --profile 选择配置中的 [profiles.*] 段
+    Render(RenderArgs),
     /// 配置文件管理（初始化 / 重置 / 路径查看）
     Config(ConfigArgs),
     /// 主题管理
@@ -90,6 +140,181 @@ enum Commands {
     Validate { file: Option<String> },
     /// 环境诊断
     Doctor,
+    /// 使用 mock 场景预览状态行(可配合 --watch 实时重绘)
+    Preview(PreviewArgs),
+    /// 录制 Claude Code 发送的 stdin 负载,并照常输出状态行
+    Record(RecordArgs),
+    /// 回放录制的负载,逐条重新渲染
+    Replay(ReplayArgs),
+    /// 生成 shell 补全脚本(bash / zsh / fish / powershell / elvish)
+    Completions(CompletionsArgs),
+    /// 会话快照管理
+    #[cfg(feature = "storage")]
+    Sessions(SessionsArgs),
+    /// 存储统计与修复
+    #[cfg(feature = "storage")]
+    Storage(StorageArgs),
+    /// 按日期 / 模型汇总历史 token 与成本
+    Usage(UsageArgs),
+    /// 回看会话渲染过的状态行(需启用 storage.historyLog)
+    #[cfg(feature = "storage")]
+    History(HistoryArgs),
+    /// 模型价格表管理
+    Pricing(PricingArgs),
+    /// 输出解析器支持的数据格式说明
+    Schema(SchemaArgs),
+    /// 输出路径对应的项目 ID(~/.claude/projects 下的目录名)
+    Hash(HashArgs),
+}
+
+#[derive(ClapArgs, Debug)]
+struct RenderArgs {
+    /// 配置档名称,对应配置文件中的 [profiles.<name>]
+    #[arg(long = "profile", value_name = "NAME")]
+    profile: Option<String>,
+}
+
+#[derive(ClapArgs, Debug)]
+struct HashArgs {
+    /// 项目路径(默认当前目录)
+    path: Option<String>,
+
+    /// 不解析符号链接,按输入的路径计算
+    #[arg(long = "no-resolve", action = clap::ArgAction::SetTrue)]
+    no_resolve: bool,
+}
+
+#[derive(ClapArgs, Debug)]
+struct SchemaArgs {
+    #[command(subcommand)]
+    target: SchemaTarget,
+}
+
+#[derive(Subcommand, Debug)]
+enum SchemaTarget {
+    /// 列出 stdin 负载中解析器认识的全部字段
+    Input,
+}
+
+#[derive(ClapArgs, Debug)]
+struct PricingArgs {
+    #[command(subcommand)]
+    action: PricingAction,
+}
+
+#[derive(Subcommand, Debug)]
+enum PricingAction {
+    /// 从 pricing.source_url 下载最新价格表并缓存
+    Update,
+}
+
+#[derive(ClapArgs, Debug)]
+struct UsageArgs {
+    /// 数据源: snapshots(statusline-pro 快照)、metrics(增量指标日志)或 transcripts(直接扫描 Claude Code JSONL)
+    #[arg(short = 's', long = "source")]
+    source: Option<String>,
+
+    /// 只显示最近 N 天的明细
+    #[arg(short = 'd', long = "days", default_value_t = 7)]
+    days: usize,
+}
+
+#[cfg(feature = "storage")]
+#[derive(ClapArgs, Debug)]
+struct HistoryArgs {
+    /// 会话 ID(可只写开头几位)
+    session: String,
+
+    /// 只显示最近 N 条
+    #[arg(short = 'n', long = "tail")]
+    tail: Option<usize>,
+}
+
+#[cfg(feature = "storage")]
+#[derive(ClapArgs, Debug)]
+struct StorageArgs {
+    #[command(subcommand)]
+    action: StorageAction,
+}
+
+#[cfg(feature = "storage")]
+#[derive(Subcommand, Debug)]
+enum StorageAction {
+    /// 按项目统计会话数量、磁盘占用、最早/最新快照与解析失败数
+    Stats(StorageStatsArgs),
+}
+
+#[cfg(feature = "storage")]
+#[derive(ClapArgs, Debug)]
+struct StorageStatsArgs {
+    /// 重写无法解析的快照，保留仍能反序列化的字段
+    #[arg(long = "repair", action = clap::ArgAction::SetTrue)]
+    repair: bool,
+}
+
+#[cfg(feature = "storage")]
+#[derive(ClapArgs, Debug)]
+struct SessionsArgs {
+    #[command(subcommand)]
+    action: SessionsAction,
+}
+
+#[cfg(feature = "storage")]
+#[derive(Subcommand, Debug)]
+enum SessionsAction {
+    /// 按当前 storage.redactKeys / storage.storeLatest 重新清洗已保存的快照
+    Scrub(SessionsScrubArgs),
+}
+
+#[cfg(feature = "storage")]
+#[derive(ClapArgs, Debug)]
+struct SessionsScrubArgs {
+    /// 仅列出需要清洗的文件，不写入
+    #[arg(short = 'n', long = "dry-run", action = clap::ArgAction::SetTrue)]
+    dry_run: bool,
+}
+
+#[derive(ClapArgs, Debug)]
+struct CompletionsArgs {
+    /// 目标 shell
+    #[arg(value_enum)]
+    shell: Shell,
+
+    /// 补全脚本绑定的命令名(npm 安装时为 ccsp)
+    #[arg(long = "bin-name", default_value = "ccsp")]
+    bin_name: String,
+}
+
+#[derive(ClapArgs, Debug)]
+struct RecordArgs {
+    /// 录制文件路径(默认 ~/.claude/statusline-pro/recordings/<日期>.jsonl)
+    #[arg(short = 'o', long = "output")]
+    output: Option<PathBuf>,
+}
+
+#[derive(ClapArgs, Debug)]
+struct ReplayArgs {
+    /// `ccsp record` 生成的 JSONL 文件
+    file: PathBuf,
+
+    /// 只回放第 N 条记录(从 1 开始)
+    #[arg(short = 'n', long = "index")]
+    index: Option<usize>,
+}
+
+#[derive(ClapArgs, Debug)]
+struct PreviewArgs {
+    /// 预览使用的 mock 场景(内置场景、~/.claude/statusline-pro/mocks/ 下的文件名,或 JSON 文件路径)
+    #[arg(short = 's', long = "scenario", default_value = "dev")]
+    scenario: String,
+
+    /// 监听配置文件与 transcript,变化时自动重新渲染
+    #[arg(short = 'w', long = "watch", action = clap::ArgAction::SetTrue)]
+    watch: bool,
+
+    /// 指定 transcript 文件(作为输入的 transcript_path,--watch 时一并监听)
+    #[arg(long = "transcript")]
+    transcript: Option<PathBuf>,
 }
 
 #[derive(ClapArgs, Debug, Default)]
@@ -127,6 +352,32 @@ enum ConfigAction {
     Init(ConfigInitArgs),
     /// 启动 TUI 配置编辑器
     Edit(ConfigEditArgs),
+    /// 按新版模板升级配置文件，保留自定义修改
+    Upgrade(ConfigUpgradeArgs),
+    /// 从备份恢复配置文件（默认恢复最近一份）
+    Rollback(ConfigRollbackArgs),
+}
+
+#[derive(ClapArgs, Debug, Default)]
+struct ConfigRollbackArgs {
+    /// 恢复倒数第 N 份备份(1 为最近一份)
+    #[arg(value_name = "N", default_value_t = 1)]
+    n: usize,
+
+    /// 恢复用户级配置(默认优先项目级,无项目级回退到用户级)
+    #[arg(short = 'g', long = "global", action = clap::ArgAction::SetTrue)]
+    global: bool,
+
+    /// 列出可用备份，不恢复
+    #[arg(short = 'l', long = "list", action = clap::ArgAction::SetTrue)]
+    list: bool,
+}
+
+#[derive(ClapArgs, Debug, Default)]
+struct ConfigUpgradeArgs {
+    /// 升级用户级配置(默认优先项目级,无项目级回退到用户级)
+    #[arg(short = 'g', long = "global", action = clap::ArgAction::SetTrue)]
+    global: bool,
 }
 
 #[derive(ClapArgs, Debug, Default)]
@@ -139,7 +390,7 @@ struct ConfigEditArgs {
     #[arg(short = 'f', long = "file")]
     file: Option<String>,
 
-    /// 预览使用的 mock 场景(dev / critical / thinking / complete / error)
+    /// 预览使用的 mock 场景(内置场景、~/.claude/statusline-pro/mocks/ 下的文件名,或 JSON 文件路径)
     #[arg(long = "mock", default_value = "dev")]
     mock: String,
 }
@@ -178,31 +429,164 @@ struct ConfigInitArgs {
     #[arg(short = 'w', long = "with-components", action = clap::ArgAction::SetTrue)]
     with_components: bool,
 
+    /// 只显示组件模板与已有组件文件的差异，不写入任何文件（需配合 --with-components）
+    #[arg(long = "diff", action = clap::ArgAction::SetTrue, requires = "with_components")]
+    diff: bool,
+
     /// 初始化时指定主题
     #[arg(short = 't', long = "theme")]
     theme: Option<String>,
 
+    /// 初始化时指定预设（例如 PMBT 或 project,model,branch），同步生成 components.order 与各组件开关
+    #[arg(short = 'p', long = "preset")]
+    preset: Option<String>,
+
+    /// 初始化时指定组件顺序（逗号分隔，例如 project,model,branch），优先于 --preset
+    #[arg(long = "components", value_delimiter = ',')]
+    components: Option<Vec<String>>,
+
     /// 覆盖已有配置文件时跳过确认
     #[arg(short = 'y', long = "force", alias = "yes", action = clap::ArgAction::SetTrue)]
     force: bool,
 }
 
-#[tokio::main]
-async fn main() -> Result<()> {
+fn main() -> Result<()> {
     let cli = Cli::parse();
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()
+        .context("创建异步运行时失败")?;
+    let result = runtime.block_on(run(&cli));
+    // 超出 performance.max_total_ms 被放弃的组件可能仍在 spawn_blocking 里运行
+    // (libgit2、jj、transcript 扫描);输出已经写完,不等它们结束
+    runtime.shutdown_timeout(Duration::ZERO);
+    result
+}
+
+async fn run(cli: &Cli) -> Result<()> {
+    if let Some(dir) = &cli.render_fixtures {
+        return handle_render_fixtures(dir).await;
+    }
 
     match &cli.command {
         Some(Commands::Config(args)) => handle_config(args).await?,
         Some(Commands::Theme(args)) => handle_theme(args).await?,
         Some(Commands::Validate { file }) => handle_validate(file.as_deref()).await?,
         Some(Commands::Doctor) => handle_doctor().await?,
-        None => handle_run(&cli).await?,
+        Some(Commands::Preview(args)) => handle_preview(cli, args).await?,
+        Some(Commands::Record(args)) => handle_record(cli, args).await?,
+        Some(Commands::Replay(args)) => handle_replay(cli, args).await?,
+        Some(Commands::Completions(args)) => handle_completions(args),
+        #[cfg(feature = "storage")]
+        Some(Commands::Sessions(args)) => handle_sessions(cli, args).await?,
+        #[cfg(feature = "storage")]
+        Some(Commands::Storage(args)) => handle_storage(cli, args).await?,
+        Some(Commands::Usage(args)) => handle_usage(cli, args).await?,
+        #[cfg(feature = "storage")]
+        Some(Commands::History(args)) => handle_history(cli, args).await?,
+        Some(Commands::Pricing(args)) => handle_pricing(cli, args).await?,
+        Some(Commands::Schema(args)) => match args.target {
+            SchemaTarget::Input => {
+                println!("{}", input_schema::describe_input_fields());
+                println!("负载版本(按从新到旧识别):");
+                for generation in input_version::PAYLOAD_GENERATIONS {
+                    println!(
+                        "  {:<7} {}",
+                        generation.version.label(),
+                        generation.version.description()
+                    );
+                }
+            }
+        },
+        Some(Commands::Hash(args)) => {
+            let policy = if args.no_resolve {
+                storage::SymlinkPolicy::Preserve
+            } else {
+                storage::SymlinkPolicy::Resolve
+            };
+            let path = args.path.as_deref().unwrap_or(".");
+            println!("{}", storage::ProjectId::from_path_with(path, policy));
+        }
+        Some(Commands::Render(args)) => handle_run(cli, args.profile.as_deref()).await?,
+        None => handle_run(cli, None).await?,
+    }
+
+    Ok(())
+}
+
+async fn handle_run(cli: &Cli, profile: Option<&str>) -> Result<()> {
+    let (config, options) = load_run_config(cli, profile).await?;
+    let mut generator = StatuslineGenerator::new(config, options);
+
+    let input = if let Some(mock_name) = &cli.mock {
+        MockDataGenerator::new().resolve(mock_name)?
+    } else if std::io::stdin().is_terminal() {
+        // 手动在终端里运行时没有管道输入,直接读 stdin 会一直等待
+        eprintln!(
+            "[statusline] stdin 是终端,未收到 Claude Code 输入,使用 mock 场景 `{TTY_MOCK_SCENARIO}` 预览。"
+        );
+        eprintln!("[statusline] 用法: echo '<JSON>' | claude-code-statusline-pro,或 --mock <场景>");
+        MockDataGenerator::new().resolve(TTY_MOCK_SCENARIO)?
+    } else {
+        read_stdin_input(cli)?
+    };
+
+    let statusline = generator.generate(input).await?;
+    let mut stdout = std::io::stdout().lock();
+    writeln!(stdout, "{statusline}")?;
+    stdout.flush()?;
+    Ok(())
+}
+
+/// 从 stdin 读取输入;超时视为空输入,避免状态行挂起
+fn read_stdin_input(cli: &Cli) -> Result<InputData> {
+    let Some(raw) = InputData::read_stdin(Duration::from_millis(cli.stdin_timeout))? else {
+        eprintln!(
+            "[statusline] {} ms 内未从 stdin 收到输入,按空输入渲染",
+            cli.stdin_timeout
+        );
+        return Ok(InputData::default());
+    };
+    if let Some(target) = &cli.print_input {
+        // 回显失败不影响渲染
+        if let Err(err) = print_input(&raw, target) {
+            eprintln!("[statusline] 回显输入失败: {err}");
+        }
+    }
+    if raw.trim().is_empty() {
+        return Ok(InputData::default());
+    }
+    let input = InputData::from_json(&raw)?;
+    if cli.debug || cli.print_input.is_some() {
+        eprintln!(
+            "[调试] 输入负载版本: {} ({})",
+            input.payload_version.label(),
+            input.payload_version.description()
+        );
     }
+    Ok(input)
+}
 
+/// `--print-input`:把 stdin 负载格式化、脱敏后写到 stderr(`-`)或文件
+fn print_input(raw: &str, target: &Path) -> Result<()> {
+    let text = match serde_json::from_str::<serde_json::Value>(raw) {
+        Ok(payload) => serde_json::to_string_pretty(&input_schema::redact_input(
+            &payload,
+            claude_code_statusline_pro::utils::home_dir().as_deref(),
+        ))?,
+        Err(err) => format!("// 不是合法的 JSON: {err}\n{raw}"),
+    };
+    if target == Path::new("-") {
+        eprintln!("{text}");
+    } else {
+        fs::write(target, format!("{text}\n"))
+            .with_context(|| format!("写入 {} 失败", target.display()))?;
+    }
     Ok(())
 }
 
-async fn handle_run(cli: &Cli) -> Result<()> {
+/// 加载配置并套用配置档与 CLI 覆盖项,返回生成器所需的配置与选项
+async fn load_run_config(cli: &Cli, profile: Option<&str>) -> Result<(Config, GeneratorOptions)> {
     // Debug: 输出所有CLI参数
     if cli.debug {
         eprintln!("[调试] CLI参数:");
@@ -216,10 +600,18 @@ async fn handle_run(cli: &Cli) -> Result<()> {
     let mut loader = ConfigLoader::new();
     let mut config = loader.load(cli.config.as_deref()).await?;
 
+    if let Some(profile) = profile {
+        config.apply_profile(profile)?;
+    }
     if cli.debug {
         config.debug = true;
     }
 
+    // 窄终端或 COMPACT=1 时使用 [compact] 覆盖项,命令行参数仍然优先
+    if config.apply_compact_profile()? && config.debug {
+        eprintln!("[调试] 已启用紧凑模式");
+    }
+
     // CLI参数覆盖配置文件 - 确保命令行参数优先级最高
     if let Some(theme) = &cli.theme {
         if config.debug {
@@ -244,15 +636,16 @@ async fn handle_run(cli: &Cli) -> Result<()> {
         .cloned();
 
     if let Some(ref preset) = preset_override {
+        check_preset(&config, preset)?;
         config.preset = Some(preset.clone());
     }
+    if let Some(components) = &cli.components {
+        ComponentRegistry::with_builtins().check_order(components)?;
+    }
 
     apply_runtime_overrides(cli, &mut config);
 
-    let base_dir = loader
-        .get_config_source()
-        .and_then(|source| source.path.as_ref())
-        .and_then(|path| path.parent().map(|p| p.to_path_buf()));
+    let base_dir = loader.config_base_dir();
 
     let mut options = GeneratorOptions {
         config_base_dir: base_dir.as_ref().map(|p| p.to_string_lossy().to_string()),
@@ -262,21 +655,6 @@ async fn handle_run(cli: &Cli) -> Result<()> {
         options = options.with_preset(preset);
     }
 
-    let mut generator = StatuslineGenerator::new(config.clone(), options);
-
-    let input = if let Some(mock_name) = &cli.mock {
-        let generator = MockDataGenerator::new();
-        generator.generate(mock_name).ok_or_else(|| {
-            anyhow!(format!(
-                "未找到 Mock 场景: {}。可用场景: {}",
-                mock_name,
-                generator.available().collect::<Vec<_>>().join(", ")
-            ))
-        })?
-    } else {
-        InputData::from_stdin()?
-    };
-
     if config.debug {
         if let Some(source) = loader.get_config_source() {
             eprintln!("[调试] 配置来源: {:?}", source.source_type);
@@ -286,11 +664,408 @@ async fn handle_run(cli: &Cli) -> Result<()> {
         }
     }
 
+    Ok((config, options))
+}
+
+async fn handle_record(cli: &Cli, args: &RecordArgs) -> Result<()> {
+    // 与 render 相同:终端上不读 stdin,管道按 --stdin-timeout 限时读取
+    let raw = if std::io::stdin().is_terminal() {
+        eprintln!("[statusline] stdin 是终端,没有可录制的输入;用法: echo '<JSON>' | claude-code-statusline-pro record");
+        String::new()
+    } else if let Some(raw) = InputData::read_stdin(Duration::from_millis(cli.stdin_timeout))? {
+        raw
+    } else {
+        eprintln!(
+            "[statusline] {} ms 内未从 stdin 收到输入,按空输入渲染",
+            cli.stdin_timeout
+        );
+        String::new()
+    };
+
+    // 录制失败不能影响状态行输出,只在 stderr 提示
+    if !raw.trim().is_empty() {
+        let record = storage::recorder::RecordedPayload::capture(raw.trim());
+        if let Err(err) = storage::recorder::append_recording(&record, args.output.as_deref()) {
+            eprintln!("[statusline] 录制 stdin 失败: {err}");
+        }
+    }
+
+    let input = if raw.trim().is_empty() {
+        InputData::default()
+    } else {
+        InputData::from_json(&raw)?
+    };
+
+    let (config, options) = load_run_config(cli, None).await?;
+    let mut generator = StatuslineGenerator::new(config, options);
     let statusline = generator.generate(input).await?;
-    println!("{statusline}");
+    let mut stdout = std::io::stdout().lock();
+    writeln!(stdout, "{statusline}")?;
+    stdout.flush()?;
+    Ok(())
+}
+
+async fn handle_replay(cli: &Cli, args: &ReplayArgs) -> Result<()> {
+    let records = storage::recorder::read_recording(&args.file)?;
+    if records.is_empty() {
+        bail!("录制文件中没有任何记录: {}", args.file.display());
+    }
+
+    let selected: Vec<(usize, &storage::recorder::RecordedPayload)> = match args.index {
+        Some(index) => {
+            let record = index
+                .checked_sub(1)
+                .and_then(|idx| records.get(idx))
+                .ok_or_else(|| anyhow!("记录序号超出范围: {index} (共 {} 条)", records.len()))?;
+            vec![(index, record)]
+        }
+        None => records
+            .iter()
+            .enumerate()
+            .map(|(idx, r)| (idx + 1, r))
+            .collect(),
+    };
+
+    let (config, options) = load_run_config(cli, None).await?;
+    // 回放不能把录制里的真实 session 写回 storage,也不能被节流吞掉后续记录
+    let options = GeneratorOptions {
+        update_throttling: false,
+        disable_cache: true,
+        preview_mode: true,
+        ..options
+    };
+
+    for (index, record) in selected {
+        let recorded_at = record.recorded_at.as_deref().unwrap_or("-");
+        println!("#{index} · {recorded_at}");
+
+        let Some(payload) = record.payload.clone() else {
+            println!(
+                "❌ 非 JSON 负载: {}",
+                record.raw.as_deref().unwrap_or_default()
+            );
+            continue;
+        };
+
+        match InputData::from_value(payload) {
+            Ok(input) => {
+                println!("负载版本: {}", input.payload_version.label());
+                let mut generator = StatuslineGenerator::new(config.clone(), options.clone());
+                match generator.generate(input).await {
+                    Ok(statusline) => println!("{statusline}"),
+                    Err(err) => println!("❌ 渲染失败: {err:#}"),
+                }
+            }
+            Err(err) => println!("❌ 负载解析失败: {err}"),
+        }
+    }
+
+    Ok(())
+}
+
+async fn handle_preview(cli: &Cli, args: &PreviewArgs) -> Result<()> {
+    let options = preview::PreviewOptions {
+        config_path: cli.config.as_deref(),
+        scenario: &args.scenario,
+        transcript: args.transcript.clone(),
+        watch: args.watch,
+    };
+
+    preview::run(&options, |config| {
+        if let Some(theme) = &cli.theme {
+            config.theme = theme.clone();
+        }
+        if let Some(preset) = cli.preset_override.as_ref().or(cli.preset.as_ref()) {
+            config.preset = Some(preset.clone());
+        }
+        apply_runtime_overrides(cli, config);
+    })
+    .await
+}
+
+/// 重新渲染所有 fixture 并覆盖 golden 文件
+async fn handle_render_fixtures(root: &Path) -> Result<()> {
+    let dirs = fixtures::discover(root)?;
+    if dirs.is_empty() {
+        bail!("{} 下没有找到包含 input.json 的 fixture", root.display());
+    }
+
+    let mut updated = 0usize;
+    for dir in &dirs {
+        let fixture = fixtures::load(dir)?;
+        let output = fixtures::render(&fixture).await?;
+        if fixtures::read_goldens(dir).as_ref() == Some(&output) {
+            println!("✅ {}", fixture.name);
+        } else {
+            fixtures::write_goldens(dir, &output)?;
+            updated += 1;
+            println!("📝 {} (已更新)", fixture.name);
+        }
+    }
+
+    println!("\n共 {} 个 fixture,更新 {updated} 个", dirs.len());
+    Ok(())
+}
+
+fn handle_completions(args: &CompletionsArgs) {
+    let mut command = Cli::command();
+    clap_complete::generate(
+        args.shell,
+        &mut command,
+        args.bin_name.clone(),
+        &mut std::io::stdout(),
+    );
+}
+
+#[cfg(feature = "storage")]
+async fn handle_sessions(cli: &Cli, args: &SessionsArgs) -> Result<()> {
+    match &args.action {
+        SessionsAction::Scrub(scrub_args) => {
+            let config = ConfigLoader::new().load(cli.config.as_deref()).await?;
+            let report = storage::scrub_sessions(&config.storage, scrub_args.dry_run).await?;
+
+            let verb = if scrub_args.dry_run {
+                "需要清洗"
+            } else {
+                "已清洗"
+            };
+            for path in &report.rewritten {
+                println!("{verb}: {}", path.display());
+            }
+            for path in &report.unreadable {
+                println!("⚠️  无法解析，已跳过: {}", path.display());
+            }
+            println!(
+                "共检查 {} 个快照，{verb} {} 个，跳过 {} 个",
+                report.scanned,
+                report.rewritten.len(),
+                report.unreadable.len()
+            );
+        }
+    }
     Ok(())
 }
 
+#[cfg(feature = "storage")]
+async fn handle_storage(cli: &Cli, args: &StorageArgs) -> Result<()> {
+    match &args.action {
+        StorageAction::Stats(stats_args) => {
+            let config = ConfigLoader::new().load(cli.config.as_deref()).await?;
+            let stats = storage::storage_stats(&config.storage, stats_args.repair).await?;
+            print_storage_stats(&stats, stats_args.repair);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(feature = "storage")]
+fn print_storage_stats(stats: &storage::StorageStats, repair: bool) {
+    println!("📦 存储目录: {}", stats.root.display());
+    if stats.projects.is_empty() {
+        println!("  (暂无会话快照)");
+        return;
+    }
+
+    let format_age = |age: &storage::SnapshotAge| {
+        format!(
+            "{} ({})",
+            age.session_id,
+            age.modified
+                .with_timezone(&chrono::Local)
+                .format("%Y-%m-%d %H:%M")
+        )
+    };
+
+    for project in &stats.projects {
+        println!(
+            "\n📁 {}: {} 个会话, {}, 解析失败 {}",
+            project.project_id,
+            project.sessions,
+            format_size(project.bytes),
+            project.parse_failures.len()
+        );
+        if project.metrics_events > 0 {
+            println!(
+                "  指标日志: {} 条事件, {}",
+                project.metrics_events,
+                format_size(project.metrics_bytes)
+            );
+        }
+        if let Some(oldest) = &project.oldest {
+            println!("  最早: {}", format_age(oldest));
+        }
+        if let Some(newest) = &project.newest {
+            println!("  最新: {}", format_age(newest));
+        }
+        for path in &project.repaired {
+            println!("  🔧 已修复: {}", path.display());
+        }
+        for path in &project.unrecoverable {
+            println!("  ❌ 不是合法 JSON，无法修复: {}", path.display());
+        }
+        if !repair {
+            for path in &project.parse_failures {
+                println!("  ⚠️  解析失败: {}", path.display());
+            }
+        }
+    }
+
+    println!(
+        "\n合计: {} 个项目, {} 个会话, {}, 指标事件 {}, 解析失败 {}",
+        stats.projects.len(),
+        stats.total_sessions(),
+        format_size(stats.total_bytes()),
+        stats.total_metrics_events(),
+        stats.total_parse_failures()
+    );
+    if !repair && stats.total_parse_failures() > 0 {
+        println!("提示: 使用 `storage stats --repair` 重写可抢救的快照");
+    }
+}
+
+async fn handle_usage(cli: &Cli, args: &UsageArgs) -> Result<()> {
+    use storage::usage_report::{UsageSource, UsageTotals};
+
+    let config = ConfigLoader::new().load(cli.config.as_deref()).await?;
+    let source: UsageSource = args
+        .source
+        .as_deref()
+        .unwrap_or(&config.storage.usage_source)
+        .parse()?;
+    let report = storage::usage_report(&config.storage, &config.model_providers, source).await?;
+
+    let source_label = match source {
+        UsageSource::Snapshots => "会话快照",
+        UsageSource::Metrics => "增量指标",
+        UsageSource::Transcripts => "Claude Code transcripts",
+    };
+    println!(
+        "📊 用量统计 · 数据源: {source_label} · 文件 {}",
+        report.files
+    );
+    if report.total.entries == 0 {
+        println!("  (没有可统计的数据)");
+        return Ok(());
+    }
+
+    let with_tokens = source != UsageSource::Snapshots;
+    let money = UsageComponent::new("usage".to_string(), (*config.components.usage).clone());
+    let format_row = |label: &str, totals: &UsageTotals| {
+        if with_tokens {
+            format!(
+                "  {label:<28} 输入 {:>10}  输出 {:>9}  缓存 {:>11}  {}",
+                totals.input_tokens,
+                totals.output_tokens,
+                totals.cache_creation_tokens + totals.cache_read_tokens,
+                money.format_usd_total(totals.cost_usd)
+            )
+        } else {
+            format!(
+                "  {label:<28} 会话 {:>4}  {}",
+                totals.entries,
+                money.format_usd_total(totals.cost_usd)
+            )
+        }
+    };
+
+    println!("\n按日期(最近 {} 天):", args.days);
+    let skip = report.by_day.len().saturating_sub(args.days);
+    for (day, totals) in report.by_day.iter().skip(skip) {
+        println!("{}", format_row(&day.to_string(), totals));
+    }
+
+    if !report.by_model.is_empty() {
+        println!("\n按模型:");
+        for (model, totals) in &report.by_model {
+            println!("{}", format_row(model, totals));
+        }
+    }
+
+    println!("\n{}", format_row("合计", &report.total));
+    if report.duplicates > 0 {
+        println!("已去重 {} 条重复响应", report.duplicates);
+    }
+    if report.total.unpriced > 0 {
+        println!(
+            "⚠️  {} 条消息缺少美元定价,未计入成本(可运行 `pricing update` 或在 model_providers 中配置 pricing)",
+            report.total.unpriced
+        );
+    }
+    Ok(())
+}
+
+#[cfg(feature = "storage")]
+async fn handle_history(cli: &Cli, args: &HistoryArgs) -> Result<()> {
+    let config = ConfigLoader::new().load(cli.config.as_deref()).await?;
+    let Some(entries) = storage::session_history_log(&config.storage, &args.session).await? else {
+        println!("未找到会话 {} 的状态行历史", args.session);
+        if !config.storage.history_log {
+            println!("💡 在 [storage] 中设置 historyLog = true 后开始记录");
+        }
+        return Ok(());
+    };
+
+    let skip = args
+        .tail
+        .map_or(0, |tail| entries.len().saturating_sub(tail));
+    let session_id = entries
+        .first()
+        .map_or(args.session.as_str(), |entry| entry.session_id.as_str());
+    println!("📜 会话 {session_id} 的状态行历史 ({} 条)", entries.len());
+    for entry in entries.iter().skip(skip) {
+        let timestamp = entry
+            .timestamp
+            .with_timezone(&chrono::Local)
+            .format("%Y-%m-%d %H:%M:%S")
+            .to_string();
+        let indent = " ".repeat(claude_code_statusline_pro::utils::display_width(&timestamp));
+        for (index, line) in entry.line.lines().enumerate() {
+            let prefix = if index == 0 { &timestamp } else { &indent };
+            println!("{prefix}  {line}");
+        }
+    }
+    Ok(())
+}
+
+async fn handle_pricing(cli: &Cli, args: &PricingArgs) -> Result<()> {
+    use storage::pricing::{self, CatalogSource, PricingCatalog};
+
+    match args.action {
+        PricingAction::Update => {
+            let current = PricingCatalog::load();
+            let current_label = match &current.source {
+                CatalogSource::Bundled => "内置离线价格表".to_string(),
+                CatalogSource::Cached(path) => path.display().to_string(),
+            };
+            println!("当前价格表: {current_label} ({} 个模型)", current.len());
+
+            let config = ConfigLoader::new().load(cli.config.as_deref()).await?;
+            println!("⬇️  正在下载: {}", config.pricing.source_url);
+            let (count, path) =
+                tokio::task::spawn_blocking(move || pricing::update(&config)).await??;
+            println!("✅ 已更新 {count} 个模型价格: {}", path.display());
+        }
+    }
+    Ok(())
+}
+
+#[cfg(feature = "storage")]
+#[allow(clippy::cast_precision_loss)]
+fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KB", "MB", "GB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{bytes} B")
+    } else {
+        format!("{size:.1} {}", UNITS[unit])
+    }
+}
+
 async fn handle_config(args: &ConfigArgs) -> Result<()> {
     let mut loader = ConfigLoader::new();
 
@@ -308,6 +1083,14 @@ async fn handle_config(args: &ConfigArgs) -> Result<()> {
                 handle_config_edit(&mut loader, args, edit_args).await?;
                 return Ok(());
             }
+            ConfigAction::Upgrade(upgrade_args) => {
+                handle_config_upgrade(&loader, args, upgrade_args)?;
+                return Ok(());
+            }
+            ConfigAction::Rollback(rollback_args) => {
+                handle_config_rollback(&loader, args, rollback_args)?;
+                return Ok(());
+            }
         }
     }
 
@@ -346,7 +1129,14 @@ async fn handle_config(args: &ConfigArgs) -> Result<()> {
                     println!("项目级配置: {}", path.display());
                 }
             }
-            ConfigSourceType::Custom => {
+            ConfigSourceType::Repository => {
+                if let Some(path) = &source.path {
+                    println!("仓库级配置: {}", path.display());
+                }
+            }
+            ConfigSourceType::Custom
+            | ConfigSourceType::Component
+            | ConfigSourceType::ProjectPattern => {
                 if let Some(path) = &source.path {
                     println!("自定义配置: {}", path.display());
                 }
@@ -366,6 +1156,8 @@ fn handle_config_init(
     parent_args: &ConfigArgs,
     init_args: &ConfigInitArgs,
 ) -> Result<()> {
+    // --diff 只查看差异,等同 dry-run
+    let dry_run = parent_args.dry_run || init_args.diff;
     let target_path = if init_args.global {
         loader
             .user_config_path()
@@ -385,7 +1177,7 @@ fn handle_config_init(
             _ => std::env::current_dir().context("无法获取当前工作目录")?,
         };
 
-        if !project_path.exists() && !parent_args.dry_run {
+        if !project_path.exists() && !dry_run {
             bail!("项目路径不存在: {}", project_path.display());
         }
 
@@ -396,7 +1188,7 @@ fn handle_config_init(
         )
     };
 
-    if target_path.exists() && !init_args.force && !parent_args.dry_run {
+    if target_path.exists() && !init_args.force && !dry_run {
         match Confirm::new()
             .with_prompt("配置文件已存在，是否覆盖?")
             .default(false)
@@ -433,11 +1225,14 @@ fn handle_config_init(
             emoji: capabilities.supports_emoji,
             nerd_font: capabilities.supports_nerd_font,
         }),
-        copy_components: init_args.with_components,
+        preset: init_args.preset.as_deref(),
+        components: init_args.components.as_deref(),
+        // 组件模板在下面单独复制,以便交互式处理冲突
+        copy_components: false,
         force: init_args.force,
     };
 
-    if parent_args.dry_run {
+    if dry_run {
         println!("🔍 (dry-run) 将生成配置文件: {}", target_path.display());
         if target_path.exists() {
             println!("  - 现有文件将被覆盖 (可使用 --force/-y 跳过确认)");
@@ -448,13 +1243,22 @@ fn handle_config_init(
             "  - 主题: {}",
             init_args.theme.as_deref().unwrap_or("保持模板中的默认主题")
         );
+        if let Some(components) = &init_args.components {
+            println!("  - 组件顺序: {}", components.join(", "));
+        } else if let Some(preset) = &init_args.preset {
+            println!("  - 预设: {preset}");
+        }
         println!(
             "  - 终端能力检测: colors={} emoji={} nerd_font={}",
             capabilities.supports_colors(),
             capabilities.supports_emoji,
             capabilities.supports_nerd_font
         );
-        if init_args.with_components {
+        if init_args.with_components && init_args.diff {
+            if let Some(dir) = target_path.parent() {
+                copy_component_templates(dir, init_args)?;
+            }
+        } else if init_args.with_components {
             println!("  - 将复制组件模板 (dry-run 未执行)");
         }
         if init_args.global {
@@ -465,15 +1269,9 @@ fn handle_config_init(
     } else {
         let result = ConfigLoader::create_default_config(options)?;
         println!("✅ 已生成配置文件: {}", result.path.display());
-        if let Some(stats) = result.copy_stats {
-            if stats.copied > 0 {
-                println!("✅ 已复制 {} 个组件模板", stats.copied);
-            }
-            if stats.skipped > 0 {
-                println!("⏭️  跳过 {} 个已存在的组件文件", stats.skipped);
-                if !init_args.force {
-                    println!("💡 提示: 使用 --force/-y 可以覆盖已存在的文件");
-                }
+        if init_args.with_components {
+            if let Some(dir) = result.path.parent() {
+                copy_component_templates(dir, init_args)?;
             }
         }
 
@@ -487,6 +1285,175 @@ fn handle_config_init(
     Ok(())
 }
 
+/// 复制组件模板:`--diff` 只显示差异;交互终端下逐个确认与模板不同的已有文件
+fn copy_component_templates(config_dir: &Path, init_args: &ConfigInitArgs) -> Result<()> {
+    let options = CopyComponentOptions {
+        force: init_args.force,
+        interactive: !init_args.force && std::io::stdin().is_terminal(),
+        diff: init_args.diff,
+    };
+    let stats =
+        ConfigLoader::copy_component_configs_with(config_dir, options, resolve_component_conflict)?;
+
+    if init_args.diff {
+        print_component_diff(&stats);
+        return Ok(());
+    }
+    if stats.copied > 0 {
+        println!("✅ 已复制 {} 个组件模板", stats.copied);
+    }
+    if stats.unchanged > 0 {
+        println!("⏭️  {} 个组件文件与模板一致", stats.unchanged);
+    }
+    if stats.skipped > 0 {
+        println!("⏭️  跳过 {} 个已存在的组件文件", stats.skipped);
+        if !init_args.force {
+            println!("💡 提示: 使用 --force/-y 可以覆盖已存在的文件，--diff 查看差异");
+        }
+    }
+    Ok(())
+}
+
+/// 询问如何处理与模板不同的组件文件,选择"查看差异"后重新询问
+fn resolve_component_conflict(conflict: &ComponentConflict) -> Result<ConflictAction> {
+    loop {
+        let choice = Select::new()
+            .with_prompt(format!(
+                "组件文件 components/{} 已存在且与模板不同",
+                conflict.name
+            ))
+            .items(["跳过", "覆盖", "查看差异"])
+            .default(0)
+            .interact_opt();
+        match choice {
+            Ok(Some(1)) => return Ok(ConflictAction::Overwrite),
+            Ok(Some(2)) => print!("{}", conflict.diff),
+            Ok(_) => return Ok(ConflictAction::Skip),
+            Err(err) => {
+                eprintln!("无法获取选择输入: {err}，已跳过 {}", conflict.name);
+                return Ok(ConflictAction::Skip);
+            }
+        }
+    }
+}
+
+fn print_component_diff(stats: &ComponentCopyStats) {
+    for name in &stats.created {
+        println!("  + components/{name} (新文件)");
+    }
+    for conflict in &stats.conflicts {
+        println!("📝 components/{} 与模板不同:", conflict.name);
+        print!("{}", conflict.diff);
+    }
+    if stats.created.is_empty() && stats.conflicts.is_empty() {
+        println!("✅ 组件文件与模板一致，无需更新");
+    } else {
+        println!("💡 提示: 去掉 --diff 并使用 --force/-y 可按模板覆盖");
+    }
+}
+
+/// `--file` 优先;`--global` 为用户级;否则优先已存在的项目级配置,回退到用户级
+fn existing_config_target(
+    loader: &ConfigLoader,
+    parent_args: &ConfigArgs,
+    global: bool,
+) -> Result<PathBuf> {
+    if let Some(custom) = parent_args.file.as_deref() {
+        return Ok(PathBuf::from(custom));
+    }
+    let project = loader.project_config_path().ok().filter(|p| p.exists());
+    match project {
+        Some(project) if !global && !parent_args.global => Ok(project),
+        _ => loader
+            .user_config_path()
+            .ok_or_else(|| anyhow!("无法确定用户级配置路径")),
+    }
+}
+
+fn handle_config_rollback(
+    loader: &ConfigLoader,
+    parent_args: &ConfigArgs,
+    rollback_args: &ConfigRollbackArgs,
+) -> Result<()> {
+    let path = existing_config_target(loader, parent_args, rollback_args.global)?;
+    let backups = backup::list_backups(&path)?;
+    if backups.is_empty() {
+        bail!("没有找到 {} 的备份", path.display());
+    }
+
+    if rollback_args.list {
+        println!("📦 {} 的备份 (最新在前):", path.display());
+        for (index, backup) in backups.iter().enumerate() {
+            println!("  {}. {}", index + 1, backup.display());
+        }
+        return Ok(());
+    }
+
+    if parent_args.dry_run {
+        let source = rollback_args
+            .n
+            .checked_sub(1)
+            .and_then(|index| backups.get(index))
+            .ok_or_else(|| {
+                anyhow!(
+                    "备份 #{} 不存在，共有 {} 份",
+                    rollback_args.n,
+                    backups.len()
+                )
+            })?;
+        println!(
+            "🔍 (dry-run) 将用 {} 恢复 {}",
+            source.display(),
+            path.display()
+        );
+        return Ok(());
+    }
+
+    let source = backup::rollback_config(&path, rollback_args.n)?;
+    println!(
+        "✅ 已从 {} 恢复配置文件: {}",
+        source.display(),
+        path.display()
+    );
+    println!("💡 恢复前的内容也已备份，再次运行 `config rollback` 可撤销");
+    Ok(())
+}
+
+fn handle_config_upgrade(
+    loader: &ConfigLoader,
+    parent_args: &ConfigArgs,
+    upgrade_args: &ConfigUpgradeArgs,
+) -> Result<()> {
+    let path = existing_config_target(loader, parent_args, upgrade_args.global)?;
+    if !path.exists() {
+        bail!("配置文件不存在: {}，请先运行 `config init`", path.display());
+    }
+
+    let report = ConfigLoader::upgrade_config(&path, parent_args.dry_run)?;
+    if parent_args.dry_run {
+        println!("🔍 (dry-run) 将升级配置文件: {}", report.path.display());
+    } else {
+        println!("✅ 已升级配置文件: {}", report.path.display());
+    }
+    if !report.had_base {
+        println!("💡 未找到生成时的默认值快照，已保留文件中的全部现有值");
+    }
+    for (label, keys) in [
+        ("新增", &report.added),
+        ("更新为新默认值", &report.updated),
+        ("移除", &report.removed),
+        ("保留自定义", &report.kept),
+    ] {
+        if !keys.is_empty() {
+            println!("  - {label} ({}): {}", keys.len(), keys.join(", "));
+        }
+    }
+    if report.is_noop() {
+        println!("配置已是最新，无需变更");
+    }
+    Ok(())
+}
+
 async fn handle_config_edit(
     loader: &mut ConfigLoader,
     parent_args: &ConfigArgs,
@@ -589,6 +1556,7 @@ fn handle_config_set(
     let mut document = load_document(&target_path)?;
     set_document_value(&mut document, &path_tokens, parsed_value)?;
 
+    backup::backup_config(&target_path)?;
     fs::write(&target_path, document.to_string())
         .with_context(|| format!("无法写入配置文件: {}", target_path.display()))?;
 
@@ -631,7 +1599,50 @@ async fn handle_theme(args: &ThemeArgs) -> Result<()> {
 
 async fn handle_validate(file: Option<&str>) -> Result<()> {
     let mut loader = ConfigLoader::new();
-    loader.load(file).await?;
+    let config = loader.load(file).await?;
+    let section_errors = config.components.section_errors();
+    if !section_errors.is_empty() {
+        let details: Vec<String> = section_errors
+            .iter()
+            .map(|(name, err)| format!("  [components.{name}] {err}"))
+            .collect();
+        bail!("组件配置无效:\n{}", details.join("\n"));
+    }
+    for rule in &config.components.status.error_rules {
+        regex::Regex::new(&rule.pattern).with_context(|| {
+            format!(
+                "components.status.error_rules 中的正则无效: {}",
+                rule.pattern
+            )
+        })?;
+    }
+    for name in &config.components.branch.vcs {
+        if vcs::builtin_provider(name, Duration::ZERO).is_none() {
+            println!(
+                "⚠️  components.branch.vcs 中的后端未知: {name}(可用: {})",
+                vcs::BUILTIN_PROVIDERS.join(", ")
+            );
+        }
+    }
+    let ticket_pattern = &config.components.ticket.pattern;
+    regex::Regex::new(ticket_pattern)
+        .with_context(|| format!("components.ticket.pattern 中的正则无效: {ticket_pattern}"))?;
+    if let Some(preset) = &config.preset {
+        check_preset(&config, preset).context("preset 无效")?;
+    }
+    for name in config.profile_names() {
+        let profile = config.with_profile(name)?;
+        if let Some(preset) = &profile.preset {
+            check_preset(&profile, preset)
+                .with_context(|| format!("[profiles.{name}] 的 preset 无效"))?;
+        }
+    }
+    for (path, key) in raw_token_findings(&loader) {
+        println!(
+            "⚠️  {}: {key} 疑似明文令牌，建议改用 env:VAR 或 keyring:service/account 引用",
+            path.display()
+        );
+    }
     if let Some(source) = loader.get_config_source() {
         println!(
             "✅ 配置有效: {}",
@@ -645,8 +1656,62 @@ async fn handle_validate(file: Option<&str>) -> Result<()> {
     Ok(())
 }
 
+/// 校验预设中的字母与组件名,配置里的 preset_mapping 一并生效
+fn check_preset(config: &Config, preset: &str) -> Result<()> {
+    let mut registry = ComponentRegistry::with_builtins();
+    // 映射本身的错误在渲染时警告,这里只校验预设
+    let _ = registry.apply_preset_mapping(&config.preset_mapping);
+    registry.try_parse_preset(preset).map(drop)
+}
+
+/// 扫描已加载的配置文件及其旁边的 components/*.toml,找出疑似明文令牌
+fn raw_token_findings(loader: &ConfigLoader) -> Vec<(PathBuf, String)> {
+    let mut config_files: Vec<PathBuf> = loader
+        .merge_report()
+        .map(|report| {
+            report
+                .layers
+                .iter()
+                .filter_map(|layer| layer.path.clone())
+                .collect()
+        })
+        .unwrap_or_default();
+    config_files.extend(loader.user_config_path());
+
+    let mut files = Vec::new();
+    for config_file in config_files {
+        if let Some(dir) = config_file.parent().map(|dir| dir.join("components")) {
+            if let Ok(entries) = fs::read_dir(dir) {
+                files.extend(
+                    entries
+                        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                        .filter(|path| path.extension().is_some_and(|ext| ext == "toml")),
+                );
+            }
+        }
+        files.push(config_file);
+    }
+    files.sort();
+    files.dedup();
+
+    files
+        .into_iter()
+        .filter_map(|path| {
+            let content = fs::read_to_string(&path).ok()?;
+            let value: serde_json::Value = toml_edit::de::from_str(&content).ok()?;
+            Some((path, value))
+        })
+        .flat_map(|(path, value)| {
+            secrets::find_raw_tokens(&value)
+                .into_iter()
+                .map(move |key| (path.clone(), key))
+        })
+        .collect()
+}
+
 async fn handle_doctor() -> Result<()> {
     use claude_code_statusline_pro::terminal::detector::TerminalDetector;
+    use claude_code_statusline_pro::utils::platform;
 
     let detector = TerminalDetector::new();
     let capabilities = detector.detect(
@@ -660,6 +1725,31 @@ async fn handle_doctor() -> Result<()> {
 
     println!("🔍 环境诊断结果");
     println!("操作系统: {}", std::env::consts::OS);
+    println!(
+        "构建: {}{}",
+        platform::target_description(),
+        if platform::is_static_build() {
+            ",静态链接"
+        } else {
+            ""
+        }
+    );
+    let features: Vec<String> = platform::FEATURES
+        .iter()
+        .map(|(name, enabled)| format!("{name} {}", bool_icon(*enabled)))
+        .collect();
+    println!("可选特性: {}", features.join("  "));
+    match claude_code_statusline_pro::utils::home_dir() {
+        Some(home) => println!("主目录: {}", home.display()),
+        None => println!(
+            "主目录: ⚠️ 无法确定,数据写入 {}",
+            claude_code_statusline_pro::utils::home_dir_or_temp().display()
+        ),
+    }
+    match platform::keyring_status() {
+        Ok(()) => println!("系统钥匙串: ✅ 可用"),
+        Err(reason) => println!("系统钥匙串: ⚪ 不可用 ({reason}),keyring: 引用将无法解析"),
+    }
     println!(
         "终端: {}",
         std::env::var("TERM").unwrap_or_else(|_| "未知".to_string())
@@ -681,6 +1771,9 @@ async fn handle_doctor() -> Result<()> {
 }
 
 fn apply_runtime_overrides(cli: &Cli, config: &mut claude_code_statusline_pro::config::Config) {
+    if let Some(components) = &cli.components {
+        config.components.order.clone_from(components);
+    }
     if cli.no_colors {
         config.style.enable_colors = AutoDetect::Bool(false);
     }
@@ -704,6 +1797,11 @@ fn apply_runtime_overrides(cli: &Cli, config: &mut claude_code_statusline_pro::c
         config.terminal.force_emoji = false;
         config.terminal.force_nerd_font = false;
     }
+    if cli.ascii {
+        config.terminal.ascii_only = true;
+        config.terminal.force_emoji = false;
+        config.terminal.force_nerd_font = false;
+    }
 }
 
 fn bool_icon(value: bool) -> &'static str {
@@ -761,6 +1859,9 @@ fn source_type_label(source_type: &ConfigSourceType) -> &'static str {
         ConfigSourceType::User => "用户级",
         ConfigSourceType::Project => "项目级",
         ConfigSourceType::Custom => "自定义",
+        ConfigSourceType::Component => "组件文件",
+        ConfigSourceType::ProjectPattern => "目录规则",
+        ConfigSourceType::Repository => "仓库级",
     }
 }
 
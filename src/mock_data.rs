@@ -1,35 +1,97 @@
 use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
 
+use anyhow::{anyhow, Context, Result};
 use claude_code_statusline_pro::core::{CostInfo, InputData, ModelInfo, WorkspaceInfo};
+use claude_code_statusline_pro::storage;
 use serde_json::json;
 
-/// Predefined mock scenarios for CLI preview mode
+/// Mock scenarios for CLI preview mode
+///
+/// 内置五个场景;`~/.claude/statusline-pro/mocks/*.json` 里的文件会按文件名
+/// (去掉 `.json`)作为额外场景加载,同名时覆盖内置场景。文件内容就是
+/// Claude Code 通过 stdin 发送的 JSON。
 pub struct MockDataGenerator {
-    scenarios: HashMap<&'static str, InputData>,
+    scenarios: HashMap<String, InputData>,
 }
 
 impl MockDataGenerator {
     pub fn new() -> Self {
+        let mut generator = Self::builtin();
+        generator.load_dir(&user_mocks_dir());
+        generator
+    }
+
+    fn builtin() -> Self {
         let mut scenarios = HashMap::new();
 
-        scenarios.insert("dev", build_dev_scenario());
-        scenarios.insert("critical", build_critical_scenario());
-        scenarios.insert("thinking", build_thinking_scenario());
-        scenarios.insert("complete", build_complete_scenario());
-        scenarios.insert("error", build_error_scenario());
+        scenarios.insert("dev".to_string(), build_dev_scenario());
+        scenarios.insert("critical".to_string(), build_critical_scenario());
+        scenarios.insert("thinking".to_string(), build_thinking_scenario());
+        scenarios.insert("complete".to_string(), build_complete_scenario());
+        scenarios.insert("error".to_string(), build_error_scenario());
 
         Self { scenarios }
     }
 
-    pub fn generate(&self, name: &str) -> Option<InputData> {
-        self.scenarios.get(name).cloned()
+    /// 加载目录下的 `*.json` 场景;无法解析的文件直接跳过,不影响内置场景。
+    fn load_dir(&mut self, dir: &Path) {
+        let Ok(entries) = fs::read_dir(dir) else {
+            return;
+        };
+
+        for path in entries.flatten().map(|entry| entry.path()) {
+            if path.extension().and_then(|ext| ext.to_str()) != Some("json") {
+                continue;
+            }
+            let Some(name) = path.file_stem().and_then(|stem| stem.to_str()) else {
+                continue;
+            };
+            if let Ok(input) = load_mock_file(&path) {
+                self.scenarios.insert(name.to_string(), input);
+            }
+        }
+    }
+
+    /// 按场景名查找;不是已知场景但指向一个存在的文件时,按 payload 文件加载。
+    pub fn resolve(&self, name: &str) -> Result<InputData> {
+        if let Some(input) = self.scenarios.get(name) {
+            return Ok(input.clone());
+        }
+
+        let path = Path::new(name);
+        if path.is_file() {
+            return load_mock_file(path);
+        }
+
+        let mut available: Vec<&str> = self.available().collect();
+        available.sort_unstable();
+        Err(anyhow!(
+            "未找到 Mock 场景: {}。可用场景: {}(也可以传入 JSON 文件路径)",
+            name,
+            available.join(", ")
+        ))
     }
 
-    pub fn available(&self) -> impl Iterator<Item = &'static str> + '_ {
-        self.scenarios.keys().copied()
+    pub fn available(&self) -> impl Iterator<Item = &str> + '_ {
+        self.scenarios.keys().map(String::as_str)
     }
 }
 
+/// 用户自定义 mock 场景目录
+pub fn user_mocks_dir() -> PathBuf {
+    storage::user_data_dir().join("mocks")
+}
+
+/// 读取一个 mock payload 文件(与 stdin 输入格式相同)
+pub fn load_mock_file(path: &Path) -> Result<InputData> {
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("无法读取 Mock 文件: {}", path.display()))?;
+    InputData::from_json(&content)
+        .with_context(|| format!("Mock 文件不是有效的输入 JSON: {}", path.display()))
+}
+
 fn build_dev_scenario() -> InputData {
     let current_dir = std::env::current_dir()
         .unwrap_or_default()
@@ -206,3 +268,59 @@ fn build_error_scenario() -> InputData {
         ..Default::default()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn user_directory_scenarios_are_listed_and_override_builtins() -> Result<()> {
+        let dir = tempdir()?;
+        fs::write(
+            dir.path().join("ci.json"),
+            r#"{"session_id":"from-file","model":{"id":"claude-opus-4-1"}}"#,
+        )?;
+        fs::write(
+            dir.path().join("dev.json"),
+            r#"{"session_id":"custom-dev"}"#,
+        )?;
+        fs::write(dir.path().join("broken.json"), "not json")?;
+        fs::write(dir.path().join("notes.txt"), "{}")?;
+
+        let mut generator = MockDataGenerator::builtin();
+        generator.load_dir(dir.path());
+
+        let mut names: Vec<&str> = generator.available().collect();
+        names.sort_unstable();
+        assert_eq!(
+            names,
+            ["ci", "complete", "critical", "dev", "error", "thinking"]
+        );
+
+        let ci = generator.resolve("ci")?;
+        assert_eq!(ci.session_id.as_deref(), Some("from-file"));
+        let dev = generator.resolve("dev")?;
+        assert_eq!(dev.session_id.as_deref(), Some("custom-dev"));
+        Ok(())
+    }
+
+    #[test]
+    fn resolve_accepts_payload_paths() -> Result<()> {
+        let dir = tempdir()?;
+        let path = dir.path().join("payload.json");
+        fs::write(&path, r#"{"session_id":"by-path","cwd":"/tmp"}"#)?;
+
+        let generator = MockDataGenerator::builtin();
+        let input = generator.resolve(&path.to_string_lossy())?;
+        assert_eq!(input.session_id.as_deref(), Some("by-path"));
+
+        let message = generator
+            .resolve("missing")
+            .err()
+            .map(|err| err.to_string())
+            .unwrap_or_default();
+        assert!(message.contains("dev"), "message={message}");
+        Ok(())
+    }
+}
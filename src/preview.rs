@@ -0,0 +1,294 @@
+//! `ccsp preview`:用 mock 场景渲染一次状态行;`--watch` 时监听配置文件与
+//! transcript,任何变化都会清屏重绘,方便边改主题/配置边看效果。
+//!
+//! 和 `config edit` 的 TUI 预览一样走 `preview_mode`,不会写入任何 session
+//! snapshot。
+
+use std::collections::HashSet;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use claude_code_statusline_pro::config::{Config, ConfigLoader};
+use claude_code_statusline_pro::core::{GeneratorOptions, StatuslineGenerator};
+use crossterm::{cursor::MoveTo, execute, terminal::Clear, terminal::ClearType};
+use notify::{Event, RecommendedWatcher, RecursiveMode, Watcher};
+use tokio::sync::mpsc;
+
+use crate::mock_data::MockDataGenerator;
+
+/// 连续保存(编辑器写临时文件再 rename)会触发一串事件,攒一小段时间再重绘。
+const DEBOUNCE: Duration = Duration::from_millis(120);
+
+/// `ccsp preview` 的运行参数
+pub struct PreviewOptions<'a> {
+    pub config_path: Option<&'a str>,
+    pub scenario: &'a str,
+    pub transcript: Option<PathBuf>,
+    pub watch: bool,
+}
+
+/// 一次渲染的结果以及本轮需要监听的文件
+struct PreviewFrame {
+    output: Result<String>,
+    watch_files: Vec<PathBuf>,
+    watch_dirs: Vec<PathBuf>,
+}
+
+/// 执行预览。`prepare` 用来在每次重新加载配置后套用 CLI 覆盖项(主题、预设等)。
+pub async fn run(options: &PreviewOptions<'_>, prepare: impl Fn(&mut Config)) -> Result<()> {
+    let frame = render_frame(options, &prepare).await;
+
+    if !options.watch {
+        println!("{}", frame.output?);
+        return Ok(());
+    }
+
+    let (tx, mut rx) = mpsc::unbounded_channel::<notify::Result<Event>>();
+    let mut frame = frame;
+
+    loop {
+        draw(options, &frame)?;
+
+        // 每一轮都重建 watcher:项目级配置可能刚被创建,需要加入监听列表
+        let watcher = build_watcher(tx.clone(), &frame)?;
+
+        let mut changed = false;
+        while !changed {
+            let Some(event) = rx.recv().await else {
+                return Ok(());
+            };
+            changed = is_relevant(&event, &frame);
+        }
+
+        tokio::time::sleep(DEBOUNCE).await;
+        while rx.try_recv().is_ok() {}
+
+        drop(watcher);
+        frame = render_frame(options, &prepare).await;
+    }
+}
+
+async fn render_frame(
+    options: &PreviewOptions<'_>,
+    prepare: &impl Fn(&mut Config),
+) -> PreviewFrame {
+    let mut loader = ConfigLoader::new();
+    let loaded = loader.load(options.config_path).await;
+
+    let mut watch_files: Vec<PathBuf> = Vec::new();
+    if let Some(path) = options.config_path {
+        watch_files.push(PathBuf::from(path));
+    }
+    watch_files.extend(loader.user_config_path());
+    watch_files.extend(loader.project_config_path().ok());
+    if let Some(transcript) = &options.transcript {
+        watch_files.push(transcript.clone());
+    }
+
+    let base_dir = loader.config_base_dir();
+    let watch_dirs = base_dir
+        .iter()
+        .map(|dir| dir.join("components"))
+        .filter(|dir| dir.is_dir())
+        .map(|dir| watch_path(&dir))
+        .collect();
+    // 事件里是绝对路径;相对的 `--config` 或经过符号链接的目录要先规范化才能比较
+    let watch_files = watch_files.iter().map(|file| watch_path(file)).collect();
+
+    let output = match loaded {
+        Ok(mut config) => {
+            prepare(&mut config);
+            render_once(config, options, base_dir.as_deref()).await
+        }
+        Err(err) => Err(err.into()),
+    };
+
+    PreviewFrame {
+        output,
+        watch_files,
+        watch_dirs,
+    }
+}
+
+async fn render_once(
+    config: Config,
+    options: &PreviewOptions<'_>,
+    base_dir: Option<&Path>,
+) -> Result<String> {
+    let mut input = MockDataGenerator::new().resolve(options.scenario)?;
+    if let Some(transcript) = &options.transcript {
+        input.transcript_path = Some(transcript.to_string_lossy().into_owned());
+    }
+
+    let generator_options = GeneratorOptions {
+        update_throttling: false,
+        disable_cache: true,
+        config_base_dir: base_dir.map(|p| p.to_string_lossy().into_owned()),
+        preview_mode: true,
+        ..GeneratorOptions::default()
+    };
+    let mut generator = StatuslineGenerator::new(config, generator_options);
+    Ok(generator.generate(input).await?)
+}
+
+fn draw(options: &PreviewOptions<'_>, frame: &PreviewFrame) -> Result<()> {
+    let mut stdout = std::io::stdout();
+    execute!(stdout, Clear(ClearType::All), MoveTo(0, 0))?;
+
+    println!(
+        "👀 预览 · 场景: {} · 监听 {} 个文件 (Ctrl+C 退出)\n",
+        options.scenario,
+        frame
+            .watch_files
+            .iter()
+            .filter(|path| path.exists())
+            .count()
+            + frame.watch_dirs.len()
+    );
+    match &frame.output {
+        Ok(statusline) => println!("{statusline}"),
+        Err(err) => println!("❌ 渲染失败: {err:#}"),
+    }
+    stdout.flush()?;
+    Ok(())
+}
+
+/// `path` made absolute with symlinks resolved; a file that does not exist
+/// yet is resolved through its parent directory
+fn watch_path(path: &Path) -> PathBuf {
+    if let Ok(canonical) = path.canonicalize() {
+        return canonical;
+    }
+    let absolute = std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf());
+    match (absolute.parent(), absolute.file_name()) {
+        (Some(parent), Some(name)) => parent
+            .canonicalize()
+            .map_or_else(|_| absolute.clone(), |parent| parent.join(name)),
+        _ => absolute,
+    }
+}
+
+fn build_watcher(
+    tx: mpsc::UnboundedSender<notify::Result<Event>>,
+    frame: &PreviewFrame,
+) -> Result<RecommendedWatcher> {
+    let mut watcher = notify::recommended_watcher(move |event| {
+        let _ = tx.send(event);
+    })
+    .context("无法创建文件监听器")?;
+
+    // 监听父目录而不是文件本身:编辑器常用"写临时文件 + rename"保存,
+    // 直接监听文件会在第一次保存后丢失 inode。
+    let mut watched = HashSet::new();
+    for file in &frame.watch_files {
+        if let Some(parent) = file.parent().filter(|dir| dir.is_dir()) {
+            if watched.insert(parent.to_path_buf()) {
+                watcher.watch(parent, RecursiveMode::NonRecursive)?;
+            }
+        }
+    }
+    for dir in &frame.watch_dirs {
+        if watched.insert(dir.clone()) {
+            watcher.watch(dir, RecursiveMode::NonRecursive)?;
+        }
+    }
+
+    Ok(watcher)
+}
+
+fn is_relevant(event: &notify::Result<Event>, frame: &PreviewFrame) -> bool {
+    let Ok(event) = event else {
+        return false;
+    };
+    if event.kind.is_access() {
+        return false;
+    }
+
+    event.paths.iter().any(|path| {
+        frame.watch_files.iter().any(|file| file == path)
+            || frame.watch_dirs.iter().any(|dir| path.starts_with(dir))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use notify::event::{AccessKind, CreateKind, EventKind};
+
+    fn frame_with(files: Vec<PathBuf>, dirs: Vec<PathBuf>) -> PreviewFrame {
+        PreviewFrame {
+            output: Ok(String::new()),
+            watch_files: files,
+            watch_dirs: dirs,
+        }
+    }
+
+    #[test]
+    fn relevant_when_watched_file_changes() {
+        let frame = frame_with(vec![PathBuf::from("/tmp/cfg/config.toml")], Vec::new());
+        let event = Event::new(EventKind::Create(CreateKind::File))
+            .add_path(PathBuf::from("/tmp/cfg/config.toml"));
+        assert!(is_relevant(&Ok(event), &frame));
+
+        let unrelated = Event::new(EventKind::Create(CreateKind::File))
+            .add_path(PathBuf::from("/tmp/cfg/other.toml"));
+        assert!(!is_relevant(&Ok(unrelated), &frame));
+    }
+
+    #[test]
+    fn relevant_for_component_directory_entries() {
+        let frame = frame_with(Vec::new(), vec![PathBuf::from("/tmp/cfg/components")]);
+        let event = Event::new(EventKind::Create(CreateKind::File))
+            .add_path(PathBuf::from("/tmp/cfg/components/usage.toml"));
+        assert!(is_relevant(&Ok(event), &frame));
+    }
+
+    #[test]
+    fn watch_paths_are_absolute() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let canonical = dir.path().canonicalize()?;
+        std::fs::write(dir.path().join("config.toml"), "")?;
+        assert_eq!(
+            watch_path(&dir.path().join("config.toml")),
+            canonical.join("config.toml")
+        );
+        // 尚未创建的项目级配置:经由父目录解析
+        assert_eq!(
+            watch_path(&dir.path().join("new.toml")),
+            canonical.join("new.toml")
+        );
+
+        let relative = watch_path(Path::new("preview-relative.toml"));
+        assert!(relative.is_absolute());
+        assert_eq!(
+            relative,
+            std::env::current_dir()?
+                .canonicalize()?
+                .join("preview-relative.toml")
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn access_events_are_ignored() {
+        let frame = frame_with(vec![PathBuf::from("/tmp/cfg/config.toml")], Vec::new());
+        let event = Event::new(EventKind::Access(AccessKind::Any))
+            .add_path(PathBuf::from("/tmp/cfg/config.toml"));
+        assert!(!is_relevant(&Ok(event), &frame));
+    }
+
+    #[tokio::test]
+    async fn unknown_scenario_reports_available_ones() {
+        let options = PreviewOptions {
+            config_path: None,
+            scenario: "missing",
+            transcript: None,
+            watch: false,
+        };
+        let result = render_once(Config::default(), &options, None).await;
+        let message = result.err().map(|err| err.to_string()).unwrap_or_default();
+        assert!(message.contains("dev"), "message={message}");
+    }
+}
@@ -0,0 +1,108 @@
+//! Cached aggregates over all sessions
+//!
+//! 成本速率基线这类统计要读取所有项目的会话快照,每次渲染都重新计算,代价随历史
+//! 线性增长。结果连同计算时间与键写入 `statusline-pro/cache/<name>.json`,有效期内
+//! 直接复用;键(排除的会话、统计起点)变化或过期后重新计算。缓存读写失败时
+//! 退回直接计算,不影响渲染。
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+/// Directory of the cache files below the statusline-pro data directory
+const CACHE_DIR: &str = "cache";
+
+#[derive(Serialize, Deserialize)]
+struct Entry<T> {
+    key: String,
+    computed_at: DateTime<Utc>,
+    value: T,
+}
+
+/// Cache file `name` below `data_dir`
+#[must_use]
+pub fn cache_path(data_dir: &Path, name: &str) -> PathBuf {
+    data_dir.join(CACHE_DIR).join(format!("{name}.json"))
+}
+
+/// Value stored at `path` for `key` within `ttl` of `now`, otherwise
+/// `compute()`, stored for the next call.
+pub fn cached<T, F>(path: &Path, key: &str, ttl: Duration, now: DateTime<Utc>, compute: F) -> T
+where
+    T: Serialize + DeserializeOwned,
+    F: FnOnce() -> T,
+{
+    if let Some(value) = read(path, key, ttl, now) {
+        return value;
+    }
+    let value = compute();
+    let entry = Entry {
+        key: key.to_string(),
+        computed_at: now,
+        value,
+    };
+    // 写入失败只意味着下一次重新计算
+    let _ = write(path, &entry);
+    entry.value
+}
+
+fn read<T: DeserializeOwned>(
+    path: &Path,
+    key: &str,
+    ttl: Duration,
+    now: DateTime<Utc>,
+) -> Option<T> {
+    let content = fs::read_to_string(path).ok()?;
+    let entry: Entry<T> = serde_json::from_str(&content).ok()?;
+    let age = (now - entry.computed_at).to_std().ok()?;
+    (entry.key == key && age <= ttl).then_some(entry.value)
+}
+
+fn write<T: Serialize>(path: &Path, entry: &Entry<T>) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create {}", parent.display()))?;
+    }
+    let content = serde_json::to_string(entry).context("Failed to serialize cache entry")?;
+    let tmp_path = path.with_extension(format!("json.{}.tmp", std::process::id()));
+    fs::write(&tmp_path, content)
+        .and_then(|()| fs::rename(&tmp_path, path))
+        .with_context(|| format!("Failed to write cache file: {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    #[test]
+    fn values_are_reused_until_the_key_changes_or_they_expire() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = cache_path(dir.path(), "baseline");
+        let ttl = Duration::from_secs(600);
+        let now = Utc::now();
+        let computed = Cell::new(0);
+        let compute = |value: u32| {
+            computed.set(computed.get() + 1);
+            value
+        };
+
+        assert_eq!(cached(&path, "s1", ttl, now, || compute(1)), 1);
+        assert_eq!(cached(&path, "s1", ttl, now, || compute(2)), 1);
+        assert_eq!(computed.get(), 1);
+
+        assert_eq!(cached(&path, "s2", ttl, now, || compute(3)), 3);
+        let later = now + chrono::Duration::seconds(601);
+        assert_eq!(cached(&path, "s2", ttl, later, || compute(4)), 4);
+        assert_eq!(computed.get(), 3);
+
+        fs::write(&path, "garbage")?;
+        assert_eq!(cached(&path, "s2", ttl, later, || compute(5)), 5);
+        Ok(())
+    }
+}
@@ -0,0 +1,379 @@
+//! Explicit storage context
+//!
+//! 每个生成器持有自己的 `StorageContext`(存储设置 + 项目 ID 解析),并作为
+//! `StorageHandle` 显式传给组件,不再读写进程级的全局状态:同一进程里的多个
+//! 项目、多个测试互不干扰。模块级的 `storage::get_*` / `update_*` 函数保留为
+//! 兼容层,作用于一个全局上下文。
+
+use std::collections::BTreeMap;
+use std::sync::{LazyLock, RwLock};
+use std::time::Duration;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use tokio::task;
+
+use super::aggregate_cache;
+use super::handle::StorageHandle;
+use super::manager::StorageManager;
+use super::project_id::ProjectId;
+use super::project_resolver::ProjectResolver;
+use super::types::{
+    ActiveSession, ErrorStreak, HookState, ModelSwitchState, SessionHistory, StorageConfig,
+};
+use super::usage_report::{self, BurnRateBaseline, PeriodUsage};
+use super::warm_start::WarmOutput;
+use crate::config::StorageConfig as SettingsConfig;
+
+/// How long the burn rate baseline of all stored sessions is reused; it
+/// moves slowly, while computing it parses every snapshot
+const BASELINE_TTL: Duration = Duration::from_secs(10 * 60);
+
+/// How long the usage of the current period is reused; computing it reads
+/// every snapshot and the transcripts written in the period
+const USAGE_TTL: Duration = Duration::from_secs(60);
+
+/// Context behind the module-level compatibility functions
+static GLOBAL: LazyLock<RwLock<StorageContext>> =
+    LazyLock::new(|| RwLock::new(StorageContext::default()));
+
+/// Storage settings and project of one statusline generator
+#[derive(Debug, Clone, Default)]
+pub struct StorageContext {
+    config: StorageConfig,
+    resolver: ProjectResolver,
+    project_id: Option<String>,
+}
+
+impl StorageContext {
+    /// Context for the `[storage]` settings (honours `STATUSLINE_STORAGE_PATH`)
+    #[must_use]
+    pub fn new(settings: &SettingsConfig) -> Self {
+        Self::with_config(super::convert_settings(settings))
+    }
+
+    #[must_use]
+    pub fn with_config(config: StorageConfig) -> Self {
+        Self {
+            config,
+            ..Self::default()
+        }
+    }
+
+    /// Context storing sessions under `project_id`
+    #[must_use]
+    pub fn with_project_id(mut self, project_id: impl Into<String>) -> Self {
+        self.project_id = Some(project_id.into());
+        self
+    }
+
+    #[must_use]
+    pub const fn config(&self) -> &StorageConfig {
+        &self.config
+    }
+
+    #[must_use]
+    pub fn project_id(&self) -> Option<&str> {
+        self.project_id.as_deref()
+    }
+
+    pub fn set_project_id(&mut self, project_id: Option<String>) {
+        self.project_id = project_id;
+    }
+
+    /// Project ID of an input: the transcript's project folder, otherwise the
+    /// project ID resolved earlier, otherwise the hashed `fallback_path`.
+    pub fn resolve_project_id(
+        &mut self,
+        transcript_path: Option<&str>,
+        fallback_path: Option<&str>,
+    ) -> ProjectId {
+        self.resolver
+            .set_project_id_from_transcript(transcript_path);
+        let project_id = self.resolver.get_project_id(fallback_path);
+        self.resolver.set_project_id(Some(project_id.as_str()));
+        project_id
+    }
+
+    /// Storage manager for this context's project
+    ///
+    /// Without a project ID the manager falls back to the global
+    /// `ProjectResolver`, as the module-level functions always did.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the storage directories cannot be created.
+    pub fn manager(&self) -> Result<StorageManager> {
+        StorageManager::with_config(self.config.clone(), self.project_id.clone())
+    }
+
+    /// Create the directories, finish interrupted writes and clean up expired
+    /// sessions.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if storage directories cannot be created or if cleanup
+    /// of existing sessions fails.
+    pub async fn initialize(&self) -> Result<()> {
+        let manager = self.manager()?;
+
+        // 上次写入被中断(崩溃/断电)时留下的临时文件
+        if let Err(err) = manager.recover_temp_files() {
+            eprintln!("[storage] Failed to recover snapshot temp files: {err}");
+        }
+
+        if self.config.enable_startup_cleanup {
+            manager.cleanup_old_sessions().await?;
+        }
+        Ok(())
+    }
+
+    /// Update the session snapshot from Claude Code input data.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error when snapshot persistence fails.
+    pub async fn update_session_snapshot(&self, input_data: &serde_json::Value) -> Result<()> {
+        let payload = input_data.clone();
+        self.blocking(move |manager| manager.update_snapshot_from_value(&payload).map(drop))
+            .await
+    }
+
+    /// Append a rendered statusline to the session's history log.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error when the log cannot be written.
+    pub async fn append_history(&self, session_id: &str, line: &str) -> Result<()> {
+        if self.config.history_max_entries == 0 {
+            return Ok(());
+        }
+        let session_id = session_id.to_string();
+        let line = line.to_string();
+        self.blocking(move |manager| manager.append_history(&session_id, &line))
+            .await
+    }
+
+    /// Warm-start outputs of a session rendered within `max_age`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error when the storage directories cannot be resolved.
+    pub async fn warm_start_outputs(
+        &self,
+        session_id: &str,
+        max_age: std::time::Duration,
+    ) -> Result<BTreeMap<String, WarmOutput>> {
+        let session_id = session_id.to_string();
+        self.blocking(move |manager| Ok(manager.warm_start_outputs(&session_id, max_age)))
+            .await
+    }
+
+    /// Store the warm-start outputs of a session.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error when the outputs cannot be written.
+    pub async fn save_warm_start(
+        &self,
+        session_id: &str,
+        outputs: BTreeMap<String, WarmOutput>,
+    ) -> Result<()> {
+        let session_id = session_id.to_string();
+        self.blocking(move |manager| manager.save_warm_start(&session_id, &outputs))
+            .await
+    }
+
+    /// Run `operation` on a storage manager off the async runtime
+    async fn blocking<R, F>(&self, operation: F) -> Result<R>
+    where
+        R: Send + 'static,
+        F: FnOnce(StorageManager) -> Result<R> + Send + 'static,
+    {
+        let context = self.clone();
+        task::spawn_blocking(move || operation(context.manager()?)).await?
+    }
+
+    /// Snapshot of the process-wide context used by the module-level functions
+    pub(crate) fn global() -> Self {
+        GLOBAL
+            .read()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .clone()
+    }
+
+    /// Replace the process-wide context
+    pub(crate) fn set_global(context: Self) {
+        *GLOBAL
+            .write()
+            .unwrap_or_else(std::sync::PoisonError::into_inner) = context;
+    }
+}
+
+#[async_trait]
+impl StorageHandle for StorageContext {
+    async fn session_history(&self, session_id: &str) -> Result<Option<SessionHistory>> {
+        let session_id = session_id.to_string();
+        let snapshot = self
+            .blocking(move |manager| manager.get_snapshot(&session_id))
+            .await?;
+        Ok(snapshot.map(|snap| snap.history))
+    }
+
+    async fn update_critical_alert(&self, session_id: &str, alerted: bool) -> Result<()> {
+        let session_id = session_id.to_string();
+        self.blocking(move |manager| manager.update_critical_alert(&session_id, alerted))
+            .await
+    }
+
+    async fn update_error_streak(&self, session_id: &str, streak: ErrorStreak) -> Result<()> {
+        let session_id = session_id.to_string();
+        self.blocking(move |manager| manager.update_error_streak(&session_id, &streak))
+            .await
+    }
+
+    async fn update_model_switch(&self, session_id: &str, state: ModelSwitchState) -> Result<()> {
+        let session_id = session_id.to_string();
+        self.blocking(move |manager| manager.update_model_switch(&session_id, &state))
+            .await
+    }
+
+    async fn update_hook_state(&self, session_id: &str, state: HookState) -> Result<()> {
+        let session_id = session_id.to_string();
+        self.blocking(move |manager| manager.update_hook_state(&session_id, &state))
+            .await
+    }
+
+    async fn usage_since(&self, since: DateTime<Utc>, include_tokens: bool) -> Result<PeriodUsage> {
+        let config = self.config.clone();
+        let usage = task::spawn_blocking(move || {
+            let path =
+                aggregate_cache::cache_path(&StorageManager::user_data_dir(&config), "usage_since");
+            let key = format!("{}:{include_tokens}", since.to_rfc3339());
+            aggregate_cache::cached(&path, &key, USAGE_TTL, Utc::now(), || {
+                let snapshots = StorageManager::all_snapshot_files(&config);
+                let mut usage = usage_report::period_from_snapshots(&snapshots, since);
+                if include_tokens {
+                    usage.tokens =
+                        usage_report::transcript_tokens_since(&super::claude_projects_dir(), since);
+                }
+                usage
+            })
+        })
+        .await?;
+        Ok(usage)
+    }
+
+    async fn burn_rate_baseline(&self, exclude_session: &str) -> Result<Option<BurnRateBaseline>> {
+        let config = self.config.clone();
+        let exclude_session = exclude_session.to_string();
+        let baseline = task::spawn_blocking(move || {
+            let path = aggregate_cache::cache_path(
+                &StorageManager::user_data_dir(&config),
+                "burn_rate_baseline",
+            );
+            aggregate_cache::cached(&path, &exclude_session, BASELINE_TTL, Utc::now(), || {
+                let snapshots = StorageManager::all_snapshot_files(&config);
+                usage_report::baseline_from_snapshots(&snapshots, &exclude_session)
+            })
+        })
+        .await?;
+        Ok(baseline)
+    }
+
+    async fn active_sessions(&self, window: Duration) -> Result<Vec<ActiveSession>> {
+        self.blocking(move |manager| Ok(manager.active_sessions(window)))
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn context_in(dir: &std::path::Path, project_id: &str) -> StorageContext {
+        StorageContext::with_config(StorageConfig {
+            storage_path: Some(dir.to_path_buf()),
+            ..StorageConfig::default()
+        })
+        .with_project_id(project_id)
+    }
+
+    #[tokio::test]
+    async fn contexts_do_not_share_state() -> Result<()> {
+        let dir = tempdir()?;
+        let first = context_in(dir.path(), "project-a");
+        let second = context_in(dir.path(), "project-b");
+
+        first.update_critical_alert("session", true).await?;
+
+        let alerted = |history: Option<SessionHistory>| history.map(|h| h.critical_alerted);
+        assert_eq!(alerted(first.session_history("session").await?), Some(true));
+        assert_eq!(alerted(second.session_history("session").await?), None);
+        assert!(dir
+            .path()
+            .join("projects/project-a/statusline-pro/sessions/session.json")
+            .exists());
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[serial_test::serial]
+    async fn usage_reads_claude_transcripts_and_is_reused() -> Result<()> {
+        let storage_dir = tempdir()?;
+        let claude_dir = tempdir()?;
+        std::env::set_var("CLAUDE_CONFIG_DIR", claude_dir.path());
+        let project = claude_dir.path().join("projects").join("-home-me-app");
+        std::fs::create_dir_all(&project)?;
+        let since = Utc::now() - chrono::Duration::hours(1);
+        let response = |message_id: &str| {
+            serde_json::json!({
+                "type": "assistant",
+                "requestId": message_id,
+                "timestamp": Utc::now().to_rfc3339(),
+                "message": {"id": message_id, "usage": {"input_tokens": 100, "output_tokens": 10}}
+            })
+            .to_string()
+        };
+        let transcript = project.join("a.jsonl");
+        std::fs::write(&transcript, response("first"))?;
+
+        // 自定义的存储目录下没有 transcript,仍按 Claude Code 的目录统计
+        let context = context_in(storage_dir.path(), "project");
+        assert_eq!(context.usage_since(since, true).await?.tokens, 110);
+
+        // 有效期内复用上一次的结果,统计起点变化时重新计算
+        std::fs::write(
+            &transcript,
+            [response("first"), response("second")].join("\n"),
+        )?;
+        assert_eq!(context.usage_since(since, true).await?.tokens, 110);
+        let earlier = since - chrono::Duration::minutes(1);
+        assert_eq!(context.usage_since(earlier, true).await?.tokens, 220);
+
+        std::env::remove_var("CLAUDE_CONFIG_DIR");
+        Ok(())
+    }
+
+    #[test]
+    fn resolved_project_sticks_until_transcript_changes() {
+        let mut context = StorageContext::default();
+
+        let transcript = "/home/u/.claude/projects/-home-u-app/abc.jsonl";
+        assert_eq!(
+            context.resolve_project_id(Some(transcript), Some("/tmp")),
+            "-home-u-app"
+        );
+        assert_eq!(
+            context.resolve_project_id(None, Some("/tmp")),
+            "-home-u-app"
+        );
+        // 新的上下文不受影响
+        assert_ne!(
+            StorageContext::default().resolve_project_id(None, Some("/tmp")),
+            "-home-u-app"
+        );
+    }
+}
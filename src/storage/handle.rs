@@ -0,0 +1,218 @@
+//! Storage access for components
+//!
+//! Components reach persisted session state through a [`StorageHandle`] on
+//! the `RenderContext` instead of calling the module-level functions directly,
+//! so tests can hand them a [`MemoryStorage`] instead of pointing
+//! `STATUSLINE_STORAGE_PATH` at a temp dir.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+
+use super::types::{ActiveSession, ErrorStreak, HookState, ModelSwitchState, SessionHistory};
+use super::usage_report::{self, BurnRateBaseline, PeriodUsage};
+
+/// Per-session state read and written while rendering
+#[async_trait]
+pub trait StorageHandle: Send + Sync {
+    /// Aggregated history of `session_id`, `None` when nothing was stored yet
+    async fn session_history(&self, session_id: &str) -> Result<Option<SessionHistory>>;
+
+    /// Record whether the critical context bell rang for `session_id`
+    async fn update_critical_alert(&self, session_id: &str, alerted: bool) -> Result<()>;
+
+    /// Persist the consecutive error streak of `session_id`
+    async fn update_error_streak(&self, session_id: &str, streak: ErrorStreak) -> Result<()>;
+
+    /// Persist the model switch tracking state of `session_id`
+    async fn update_model_switch(&self, session_id: &str, state: ModelSwitchState) -> Result<()>;
+
+    /// Persist the event hook state of `session_id`
+    async fn update_hook_state(&self, session_id: &str, state: HookState) -> Result<()>;
+
+    /// Usage across all sessions since `since`; transcript tokens are only
+    /// counted when `include_tokens` is set, since that scans transcripts
+    async fn usage_since(&self, since: DateTime<Utc>, include_tokens: bool) -> Result<PeriodUsage>;
+
+    /// Cost burn rate baseline of all stored sessions except `exclude_session`
+    async fn burn_rate_baseline(&self, exclude_session: &str) -> Result<Option<BurnRateBaseline>>;
+
+    /// Sessions of the current project updated within `window`, oldest first
+    async fn active_sessions(&self, window: Duration) -> Result<Vec<ActiveSession>>;
+}
+
+/// Session snapshots on disk, via `StorageManager`
+#[cfg(feature = "storage")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DiskStorage;
+
+#[cfg(feature = "storage")]
+#[async_trait]
+impl StorageHandle for DiskStorage {
+    async fn session_history(&self, session_id: &str) -> Result<Option<SessionHistory>> {
+        super::get_session_history(session_id).await
+    }
+
+    async fn update_critical_alert(&self, session_id: &str, alerted: bool) -> Result<()> {
+        super::update_session_critical_alert(session_id, alerted).await
+    }
+
+    async fn update_error_streak(&self, session_id: &str, streak: ErrorStreak) -> Result<()> {
+        super::update_session_error_streak(session_id, streak).await
+    }
+
+    async fn update_model_switch(&self, session_id: &str, state: ModelSwitchState) -> Result<()> {
+        super::update_session_model_switch(session_id, state).await
+    }
+
+    async fn update_hook_state(&self, session_id: &str, state: HookState) -> Result<()> {
+        super::update_session_hook_state(session_id, state).await
+    }
+
+    async fn usage_since(&self, since: DateTime<Utc>, include_tokens: bool) -> Result<PeriodUsage> {
+        super::usage_since(since, include_tokens).await
+    }
+
+    async fn burn_rate_baseline(&self, exclude_session: &str) -> Result<Option<BurnRateBaseline>> {
+        super::burn_rate_baseline(exclude_session).await
+    }
+
+    async fn active_sessions(&self, window: Duration) -> Result<Vec<ActiveSession>> {
+        super::get_active_sessions(window).await
+    }
+}
+
+/// Session histories kept in memory, for tests and previews
+#[derive(Debug, Default)]
+pub struct MemoryStorage {
+    sessions: Mutex<HashMap<String, SessionHistory>>,
+    usage: Mutex<PeriodUsage>,
+    active: Mutex<Vec<ActiveSession>>,
+}
+
+impl MemoryStorage {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Storage already holding `history` for `session_id`
+    #[must_use]
+    pub fn with_history(self, session_id: &str, history: SessionHistory) -> Self {
+        self.set_history(session_id, history);
+        self
+    }
+
+    /// Replace the stored history of `session_id`
+    pub fn set_history(&self, session_id: &str, history: SessionHistory) {
+        self.sessions
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .insert(session_id.to_string(), history);
+    }
+
+    /// Storage reporting `usage` for any period
+    #[must_use]
+    pub fn with_usage(self, usage: PeriodUsage) -> Self {
+        *self
+            .usage
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner) = usage;
+        self
+    }
+
+    /// Storage reporting `sessions` as the active sessions of the project
+    #[must_use]
+    pub fn with_active_sessions(self, sessions: Vec<ActiveSession>) -> Self {
+        *self
+            .active
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner) = sessions;
+        self
+    }
+
+    /// Stored history of `session_id`
+    #[must_use]
+    pub fn history(&self, session_id: &str) -> Option<SessionHistory> {
+        self.sessions
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .get(session_id)
+            .cloned()
+    }
+
+    fn update(&self, session_id: &str, apply: impl FnOnce(&mut SessionHistory)) {
+        let mut sessions = self
+            .sessions
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        apply(sessions.entry(session_id.to_string()).or_default());
+    }
+}
+
+#[async_trait]
+impl StorageHandle for MemoryStorage {
+    async fn session_history(&self, session_id: &str) -> Result<Option<SessionHistory>> {
+        Ok(self.history(session_id))
+    }
+
+    async fn update_critical_alert(&self, session_id: &str, alerted: bool) -> Result<()> {
+        self.update(session_id, |history| history.critical_alerted = alerted);
+        Ok(())
+    }
+
+    async fn update_error_streak(&self, session_id: &str, streak: ErrorStreak) -> Result<()> {
+        self.update(session_id, |history| history.errors = streak);
+        Ok(())
+    }
+
+    async fn update_model_switch(&self, session_id: &str, state: ModelSwitchState) -> Result<()> {
+        self.update(session_id, |history| history.model_switch = state);
+        Ok(())
+    }
+
+    async fn update_hook_state(&self, session_id: &str, state: HookState) -> Result<()> {
+        self.update(session_id, |history| history.hooks = state);
+        Ok(())
+    }
+
+    async fn usage_since(
+        &self,
+        _since: DateTime<Utc>,
+        include_tokens: bool,
+    ) -> Result<PeriodUsage> {
+        let usage = *self
+            .usage
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        Ok(PeriodUsage {
+            tokens: if include_tokens { usage.tokens } else { 0 },
+            ..usage
+        })
+    }
+
+    async fn burn_rate_baseline(&self, exclude_session: &str) -> Result<Option<BurnRateBaseline>> {
+        let sessions = self
+            .sessions
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        Ok(usage_report::burn_rate_baseline(
+            sessions
+                .iter()
+                .filter(|(session_id, _)| session_id.as_str() != exclude_session)
+                .map(|(_, history)| &history.cost.total),
+        ))
+    }
+
+    async fn active_sessions(&self, _window: Duration) -> Result<Vec<ActiveSession>> {
+        Ok(self
+            .active
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .clone())
+    }
+}
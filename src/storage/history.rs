@@ -0,0 +1,134 @@
+//! Rendered statusline history
+//!
+//! 启用 `storage.historyLog` 后,每次渲染的状态行(去掉颜色的纯文本)连同时间戳、
+//! 会话 ID 追加到会话目录下的 `<session>.history.jsonl`,超过
+//! `storage.historyMaxEntries` 条时丢弃最早的记录。与上一条内容相同的渲染不重复
+//! 记录,日志只保留状态变化的时刻。`ccsp history <session>` 按时间顺序回看,
+//! 用于排查"从什么时候开始报错"。
+
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// File name suffix of per-session history logs
+pub const HISTORY_SUFFIX: &str = ".history.jsonl";
+
+/// One rendered statusline
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    pub timestamp: DateTime<Utc>,
+    pub session_id: String,
+    /// Plain text of the rendered lines, joined with `\n`
+    pub line: String,
+}
+
+/// History log of `session_id` inside `sessions_dir`
+#[must_use]
+pub fn history_path(sessions_dir: &Path, session_id: &str) -> PathBuf {
+    sessions_dir.join(format!("{session_id}{HISTORY_SUFFIX}"))
+}
+
+/// Read every entry of a history log; unparsable lines are skipped.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be read.
+pub fn read_history(path: &Path) -> Result<Vec<HistoryEntry>> {
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read history log: {}", path.display()))?;
+    Ok(content
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect())
+}
+
+/// Append `entry` to the log at `path`, keeping at most `max_entries`.
+///
+/// Returns `false` without writing when the last entry has the same text.
+///
+/// # Errors
+///
+/// Returns an error if the log cannot be read or written.
+pub fn append_entry(path: &Path, entry: &HistoryEntry, max_entries: usize) -> Result<bool> {
+    let existing = if path.exists() {
+        read_history(path)?
+    } else {
+        Vec::new()
+    };
+    if existing.last().is_some_and(|last| last.line == entry.line) {
+        return Ok(false);
+    }
+
+    if existing.len() < max_entries {
+        let mut line = serde_json::to_string(entry)?;
+        line.push('\n');
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .and_then(|mut file| file.write_all(line.as_bytes()))
+            .with_context(|| format!("Failed to append history log: {}", path.display()))?;
+        return Ok(true);
+    }
+
+    // 达到上限:重写为最近的 max_entries 条
+    let keep = existing.len() + 1 - max_entries.max(1);
+    let mut content = String::new();
+    for kept in existing.iter().skip(keep).chain(std::iter::once(entry)) {
+        content.push_str(&serde_json::to_string(kept)?);
+        content.push('\n');
+    }
+    fs::write(path, content)
+        .with_context(|| format!("Failed to rewrite history log: {}", path.display()))?;
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn entry(line: &str) -> HistoryEntry {
+        HistoryEntry {
+            timestamp: Utc::now(),
+            session_id: "s1".to_string(),
+            line: line.to_string(),
+        }
+    }
+
+    #[test]
+    fn unchanged_lines_are_not_repeated() -> Result<()> {
+        let dir = tempdir()?;
+        let path = history_path(dir.path(), "s1");
+        assert!(append_entry(&path, &entry("ok"), 10)?);
+        assert!(!append_entry(&path, &entry("ok"), 10)?);
+        assert!(append_entry(&path, &entry("error"), 10)?);
+        assert!(append_entry(&path, &entry("ok"), 10)?);
+
+        let lines: Vec<String> = read_history(&path)?
+            .into_iter()
+            .map(|entry| entry.line)
+            .collect();
+        assert_eq!(lines, ["ok", "error", "ok"]);
+        Ok(())
+    }
+
+    #[test]
+    fn log_is_capped_to_the_newest_entries() -> Result<()> {
+        let dir = tempdir()?;
+        let path = history_path(dir.path(), "s1");
+        for index in 0..8 {
+            append_entry(&path, &entry(&format!("line {index}")), 5)?;
+        }
+
+        let history = read_history(&path)?;
+        assert_eq!(history.len(), 5);
+        assert_eq!(history[0].line, "line 3");
+        assert_eq!(history[4].line, "line 7");
+        Ok(())
+    }
+}
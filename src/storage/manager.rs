@@ -2,24 +2,142 @@
 //!
 //! 存储管理器 - 负责会话快照与增量指标的持久化。
 
+use std::collections::{BTreeMap, HashMap};
 use std::fs::{self, File};
-use std::io::{BufRead, BufReader, Seek, SeekFrom};
+use std::io::{BufRead, BufReader, Seek, SeekFrom, Write};
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, LazyLock, Mutex, PoisonError};
+use std::time::{Duration, SystemTime};
 
 use anyhow::{anyhow, Context, Result};
-use chrono::Utc;
+use chrono::{DateTime, Utc};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
 use serde_json::Map;
 use serde_json::Value;
 use tokio::fs as async_fs;
 
+use super::context::StorageContext;
+use super::history::{self, HistoryEntry};
+use super::metrics::{self, MetricEvent, MetricKind};
 use super::project_resolver::ProjectResolver;
 use super::types::{
-    CostMetrics, ModelUsageEntry, SessionHistory, SessionSnapshot, StorageConfig, StoragePaths,
-    TokenHistory,
+    ActiveSession, CostMetrics, ErrorStreak, HookState, ModelSwitchState, ModelUsageEntry,
+    SessionCounters, SessionHistory, SessionSnapshot, StorageConfig, StoragePaths, TokenHistory,
 };
-use super::{current_runtime_config, current_runtime_project_id, set_runtime_project_id};
+use super::warm_start::{self, WarmOutput};
 use crate::utils;
 
+/// Result of [`StorageManager::scrub_sessions`]
+#[derive(Debug, Clone, Default)]
+pub struct ScrubReport {
+    /// Snapshot files inspected
+    pub scanned: usize,
+    /// Files whose `latest` payload changed (rewritten unless dry-run)
+    pub rewritten: Vec<PathBuf>,
+    /// Files that could not be read or parsed
+    pub unreadable: Vec<PathBuf>,
+}
+
+/// Result of [`StorageManager::recover_temp_files`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TempRecovery {
+    /// Temp files renamed over their snapshot
+    pub completed: usize,
+    /// Stale or corrupt temp files deleted
+    pub removed: usize,
+}
+
+/// Result of [`StorageManager::storage_stats`]
+#[derive(Debug, Clone, Default)]
+pub struct StorageStats {
+    /// Directory holding the per-project folders
+    pub root: PathBuf,
+    pub projects: Vec<ProjectStats>,
+}
+
+impl StorageStats {
+    #[must_use]
+    pub fn total_sessions(&self) -> usize {
+        self.projects.iter().map(|project| project.sessions).sum()
+    }
+
+    #[must_use]
+    pub fn total_bytes(&self) -> u64 {
+        self.projects.iter().map(|project| project.bytes).sum()
+    }
+
+    #[must_use]
+    pub fn total_metrics_events(&self) -> usize {
+        self.projects
+            .iter()
+            .map(|project| project.metrics_events)
+            .sum()
+    }
+
+    #[must_use]
+    pub fn total_parse_failures(&self) -> usize {
+        self.projects
+            .iter()
+            .map(|project| project.parse_failures.len())
+            .sum()
+    }
+}
+
+/// Snapshot statistics of a single project
+#[derive(Debug, Clone, Default)]
+pub struct ProjectStats {
+    pub project_id: String,
+    pub sessions: usize,
+    /// Disk usage of the snapshot files
+    pub bytes: u64,
+    /// Events in the incremental metrics logs
+    pub metrics_events: usize,
+    /// Disk usage of the metrics logs
+    pub metrics_bytes: u64,
+    pub oldest: Option<SnapshotAge>,
+    pub newest: Option<SnapshotAge>,
+    /// Snapshots that failed to deserialize
+    pub parse_failures: Vec<PathBuf>,
+    /// Snapshots rewritten from their salvageable fields (`--repair`)
+    pub repaired: Vec<PathBuf>,
+    /// Snapshots that are not valid JSON and were left untouched
+    pub unrecoverable: Vec<PathBuf>,
+}
+
+/// Session id and modification time of a snapshot file
+#[derive(Debug, Clone)]
+pub struct SnapshotAge {
+    pub session_id: String,
+    pub modified: DateTime<Utc>,
+}
+
+/// Extension of in-flight snapshot writes
+const TEMP_EXTENSION: &str = "tmp";
+
+/// Age below which a temp file may still belong to a write in progress in
+/// another process; recovery leaves it alone
+const TEMP_RECOVERY_AGE: Duration = Duration::from_secs(60);
+
+/// Interval after which a render rewrites the snapshot even without a state
+/// change; counters in between only go to the metrics log
+const SNAPSHOT_WRITE_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Sequence number making the temp file of every write unique
+static TEMP_SEQUENCE: AtomicU64 = AtomicU64::new(0);
+
+/// One lock per snapshot file.
+///
+/// 组件并发渲染,各自对同一快照做「读取 → 改一个字段 → 写回」;不串行化时
+/// 后写入的一方会覆盖前者的改动。
+static SNAPSHOT_LOCKS: LazyLock<Mutex<HashMap<PathBuf, Arc<Mutex<()>>>>> =
+    LazyLock::new(Mutex::default);
+
+/// Lines scanned at the head of a new transcript when looking for the
+/// `parentUuid` that links it to the session it was resumed from
+const RESUME_SCAN_LINES: usize = 50;
+
 /// Storage Manager responsible for persisting session snapshots.
 pub struct StorageManager {
     config: StorageConfig,
@@ -28,15 +146,18 @@ pub struct StorageManager {
 }
 
 impl StorageManager {
-    /// Create new `StorageManager` using runtime configuration
+    /// Create new `StorageManager` from the process-wide storage context set up
+    /// by `storage::initialize_storage_with_settings`
     ///
     /// # Errors
     ///
     /// Returns an error if required storage directories cannot be created.
     pub fn new() -> Result<Self> {
-        let config = current_runtime_config();
-        let project_id = current_runtime_project_id();
-        Self::with_config(config, project_id)
+        let context = StorageContext::global();
+        Self::with_config(
+            context.config().clone(),
+            context.project_id().map(str::to_string),
+        )
     }
 
     /// Create new `StorageManager` with custom configuration and project context
@@ -59,21 +180,17 @@ impl StorageManager {
 
     /// Initialize storage paths based on current project
     fn initialize_paths(config: &StorageConfig, project_id: Option<&str>) -> StoragePaths {
-        let base_path = config.storage_path.clone().unwrap_or_else(|| {
-            utils::home_dir()
-                .unwrap_or_else(|| PathBuf::from("."))
-                .join(".claude")
-        });
+        let base_path = Self::base_path(config);
 
         let project_hash = project_id.map_or_else(
-            || ProjectResolver::get_global_project_id(None),
+            || ProjectResolver::get_global_project_id(None).into_string(),
             str::to_string,
         );
 
         let project_dir = base_path.join("projects").join(&project_hash);
 
         StoragePaths {
-            user_config_dir: base_path.join("statusline-pro"),
+            user_config_dir: Self::user_data_dir(config),
             project_config_dir: project_dir.join("statusline-pro"),
             sessions_dir: project_dir.join("statusline-pro").join("sessions"),
             user_config_path: base_path.join("statusline-pro").join("config.toml"),
@@ -81,6 +198,18 @@ impl StorageManager {
         }
     }
 
+    /// Directory of the statusline-pro data shared by all projects
+    pub(crate) fn user_data_dir(config: &StorageConfig) -> PathBuf {
+        Self::base_path(config).join("statusline-pro")
+    }
+
+    pub(crate) fn base_path(config: &StorageConfig) -> PathBuf {
+        config
+            .storage_path
+            .clone()
+            .unwrap_or_else(|| utils::home_dir_or_temp().join(".claude"))
+    }
+
     /// Ensure all required directories exist
     ///
     /// # Errors
@@ -106,10 +235,6 @@ impl StorageManager {
     /// Set project ID and reinitialize paths
     pub fn set_project_id(&mut self, project_id: &str) {
         self.project_id = Some(project_id.to_string());
-
-        set_runtime_project_id(self.project_id.clone());
-        ProjectResolver::set_global_project_id(Some(project_id));
-
         self.paths = Self::initialize_paths(&self.config, Some(project_id));
         let _ = self.ensure_directories();
     }
@@ -118,7 +243,18 @@ impl StorageManager {
         self.paths.sessions_dir.join(format!("{session_id}.json"))
     }
 
+    /// Load `session_id`'s snapshot with the counters checkpointed since it
+    /// was written.
     fn load_snapshot(&self, session_id: &str) -> Result<Option<SessionSnapshot>> {
+        let mut snapshot = self.read_stored_snapshot(session_id)?;
+        if let Some(snapshot) = &mut snapshot {
+            replay_checkpoint(snapshot, &self.paths.sessions_dir);
+        }
+        Ok(snapshot)
+    }
+
+    /// The snapshot as last written to disk
+    fn read_stored_snapshot(&self, session_id: &str) -> Result<Option<SessionSnapshot>> {
         let path = self.session_file_path(session_id);
         if !path.exists() {
             return Ok(None);
@@ -140,6 +276,33 @@ impl StorageManager {
         }
     }
 
+    /// Lock serializing the read-modify-write cycles on `session_id`'s snapshot
+    fn snapshot_lock(&self, session_id: &str) -> Arc<Mutex<()>> {
+        SNAPSHOT_LOCKS
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .entry(self.session_file_path(session_id))
+            .or_default()
+            .clone()
+    }
+
+    /// Load `session_id`'s snapshot (a new one when missing), apply `modify`
+    /// and save it, holding the snapshot's lock throughout.
+    fn modify_snapshot<T>(
+        &self,
+        session_id: &str,
+        modify: impl FnOnce(&mut SessionSnapshot) -> T,
+    ) -> Result<T> {
+        let lock = self.snapshot_lock(session_id);
+        let _guard = lock.lock().unwrap_or_else(PoisonError::into_inner);
+        let mut snapshot = self
+            .load_snapshot(session_id)?
+            .unwrap_or_else(|| SessionSnapshot::new(session_id));
+        let result = modify(&mut snapshot);
+        self.save_snapshot(&snapshot)?;
+        Ok(result)
+    }
+
     fn save_snapshot(&self, snapshot: &SessionSnapshot) -> Result<()> {
         if !self.config.enable_cost_persistence {
             return Ok(());
@@ -155,18 +318,98 @@ impl StorageManager {
             })?;
         }
 
-        let tmp_path = path.with_extension("json.tmp");
+        Self::write_snapshot_file(&path, snapshot)
+    }
+
+    /// Write a snapshot via temp file + rename.
+    ///
+    /// 临时文件在 rename 前 fsync,rename 后再 fsync 目录,断电后要么是旧快照,
+    /// 要么是完整的新快照;残留的临时文件由 [`Self::recover_temp_files`] 处理。
+    /// 临时文件名带进程号与序号(`<session>.json.<pid>-<n>.tmp`),并发的写入
+    /// 不会互相覆盖或 rename 走对方的文件。
+    fn write_snapshot_file(path: &Path, snapshot: &SessionSnapshot) -> Result<()> {
+        let tmp_path = temp_path_for(path);
         let json_content = serde_json::to_string_pretty(snapshot)
             .with_context(|| "Failed to serialize session snapshot")?;
-        fs::write(&tmp_path, json_content).with_context(|| {
-            format!("Failed to write snapshot temp file: {}", tmp_path.display())
+
+        let mut file = File::create(&tmp_path).with_context(|| {
+            format!(
+                "Failed to create snapshot temp file: {}",
+                tmp_path.display()
+            )
         })?;
-        fs::rename(&tmp_path, &path).with_context(|| {
+        file.write_all(json_content.as_bytes())
+            .and_then(|()| file.sync_all())
+            .with_context(|| {
+                format!("Failed to write snapshot temp file: {}", tmp_path.display())
+            })?;
+        drop(file);
+
+        fs::rename(&tmp_path, path).with_context(|| {
             format!("Failed to atomically persist snapshot: {}", path.display())
         })?;
+        if let Some(parent) = path.parent() {
+            sync_dir(parent);
+        }
         Ok(())
     }
 
+    /// Complete or discard `*.json.tmp` files left behind by an interrupted write.
+    ///
+    /// A temp file that parses is newer than (or replaces a broken) snapshot, so
+    /// it is renamed into place; anything else is removed. Files younger than
+    /// a minute are skipped: another statusline process may be about to
+    /// rename them.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the sessions directory cannot be read.
+    pub fn recover_temp_files(&self) -> Result<TempRecovery> {
+        let mut recovery = TempRecovery::default();
+        if !self.paths.sessions_dir.exists() {
+            return Ok(recovery);
+        }
+
+        let entries = fs::read_dir(&self.paths.sessions_dir)
+            .with_context(|| "Failed to read sessions directory")?;
+        for tmp_path in entries.flatten().map(|entry| entry.path()) {
+            let Some(target) = temp_target(&tmp_path) else {
+                continue;
+            };
+            // 并发的渲染进程正在写入,交给它自己 rename
+            let in_flight = fs::metadata(&tmp_path)
+                .and_then(|metadata| metadata.modified())
+                .map_or(true, |modified| {
+                    SystemTime::now()
+                        .duration_since(modified)
+                        .map_or(true, |age| age < TEMP_RECOVERY_AGE)
+                });
+            if in_flight {
+                continue;
+            }
+            let pending = read_snapshot_file(&tmp_path);
+            let current = read_snapshot_file(&target);
+            let complete = match (&pending, &current) {
+                (Some(_), None) => true,
+                (Some(pending), Some(current)) => {
+                    pending.meta.last_update_time > current.meta.last_update_time
+                }
+                (None, _) => false,
+            };
+
+            if complete && fs::rename(&tmp_path, &target).is_ok() {
+                recovery.completed += 1;
+            } else if fs::remove_file(&tmp_path).is_ok() {
+                recovery.removed += 1;
+            }
+        }
+
+        if recovery.completed > 0 {
+            sync_dir(&self.paths.sessions_dir);
+        }
+        Ok(recovery)
+    }
+
     fn determine_project_path(input: &Value, existing: Option<&str>) -> Option<String> {
         if let Some(workspace) = input
             .get("workspace")
@@ -237,14 +480,16 @@ impl StorageManager {
         }
     }
 
+    /// Read the transcript past the processed offset and return the usage of
+    /// the assistant messages found there.
     fn read_tokens_from_transcript(
         snapshot: &mut SessionSnapshot,
         transcript_path: &str,
-    ) -> Result<()> {
+    ) -> Result<Vec<TokenHistory>> {
         let path = Path::new(transcript_path);
         if !path.exists() {
             snapshot.transcript_state.transcript_path = Some(transcript_path.to_string());
-            return Ok(());
+            return Ok(Vec::new());
         }
 
         let metadata = fs::metadata(path)
@@ -275,6 +520,7 @@ impl StorageManager {
         let mut buffer = String::new();
         let mut current_offset = offset;
         let mut latest_tokens = snapshot.history.tokens.clone();
+        let mut messages = Vec::new();
         Self::process_transcript_stream(
             &mut reader,
             transcript_path,
@@ -282,6 +528,7 @@ impl StorageManager {
             &mut current_offset,
             &mut processed_messages,
             &mut latest_tokens,
+            &mut messages,
         )?;
 
         snapshot.transcript_state.transcript_path = Some(transcript_path.to_string());
@@ -300,7 +547,7 @@ impl StorageManager {
             snapshot.history.tokens = Some(tokens);
         }
 
-        Ok(())
+        Ok(messages)
     }
 
     fn process_transcript_stream(
@@ -310,6 +557,7 @@ impl StorageManager {
         current_offset: &mut u64,
         processed_messages: &mut u64,
         latest_tokens: &mut Option<TokenHistory>,
+        messages: &mut Vec<TokenHistory>,
     ) -> Result<()> {
         loop {
             buffer.clear();
@@ -340,6 +588,7 @@ impl StorageManager {
             }
 
             if let Some(entry) = Self::token_entry_from_message(&value) {
+                messages.push(entry.clone());
                 *latest_tokens = Some(entry);
             }
         }
@@ -459,9 +708,19 @@ impl StorageManager {
         let session_id = Self::extract_session_id(input_data)
             .ok_or_else(|| anyhow!("No session ID found in input data"))?;
 
-        let mut snapshot = self
-            .load_snapshot(session_id)?
-            .unwrap_or_else(|| SessionSnapshot::new(session_id));
+        let lock = self.snapshot_lock(session_id);
+        let _guard = lock.lock().unwrap_or_else(PoisonError::into_inner);
+        let transcript_path = Self::extract_transcript_path(input_data);
+        let stored = self.read_stored_snapshot(session_id)?;
+        let mut snapshot = stored.clone().unwrap_or_else(|| {
+            self.find_resumed_snapshot(session_id, transcript_path)
+                .map_or_else(
+                    || SessionSnapshot::new(session_id),
+                    |previous| Self::continue_snapshot(session_id, previous),
+                )
+        });
+        replay_checkpoint(&mut snapshot, &self.paths.sessions_dir);
+        let previous_counters = snapshot.counters();
 
         snapshot.meta.session_id = session_id.to_string();
         snapshot.meta.project_path =
@@ -471,18 +730,21 @@ impl StorageManager {
             snapshot.meta.created_at = Some(Utc::now());
         }
 
-        let mut latest = input_data.clone();
-        sanitize_latest_value(&mut latest);
-        snapshot.latest = latest;
+        snapshot.latest = Self::sanitized_latest(&self.config, input_data.clone());
 
+        let previous_cost = snapshot.history.cost.total.total_cost_usd;
         if let Some(cost_value) = Self::extract_cost_value(input_data) {
             let metrics = CostMetrics::from_cost_value(cost_value);
             snapshot.history.cost.apply(&metrics);
         }
 
-        if let Some(transcript_path) = Self::extract_transcript_path(input_data) {
-            if let Err(err) = Self::read_tokens_from_transcript(&mut snapshot, transcript_path) {
-                eprintln!("[storage] Failed to update token usage for session {session_id}: {err}");
+        let mut messages = Vec::new();
+        if let Some(transcript_path) = transcript_path {
+            match Self::read_tokens_from_transcript(&mut snapshot, transcript_path) {
+                Ok(read) => messages = read,
+                Err(err) => eprintln!(
+                    "[storage] Failed to update token usage for session {session_id}: {err}"
+                ),
             }
         }
 
@@ -496,10 +758,484 @@ impl StorageManager {
         let effective_timestamp = input_timestamp.or(token_timestamp_owned.as_deref());
         Self::update_model_usage(&mut snapshot.history, model_value, effective_timestamp);
 
+        let mut events =
+            Self::snapshot_metric_events(session_id, previous_cost, &snapshot, &messages);
+        if !Self::needs_write(stored.as_ref(), &snapshot) {
+            // 状态未变且未到重写间隔:计数以检查点追加到日志,不重写快照
+            if counters_changed(&previous_counters, &snapshot.counters()) {
+                events.push(MetricEvent {
+                    timestamp: Utc::now(),
+                    session_id: session_id.to_string(),
+                    kind: MetricKind::Checkpoint(Box::new(snapshot.counters())),
+                });
+            }
+            match self.append_metrics(session_id, &events) {
+                Ok(()) => return Ok(snapshot),
+                Err(err) => {
+                    eprintln!("[storage] Failed to append metrics for session {session_id}: {err}");
+                }
+            }
+        } else if let Err(err) = self.append_metrics(session_id, &events) {
+            eprintln!("[storage] Failed to append metrics for session {session_id}: {err}");
+        }
+
         self.save_snapshot(&snapshot)?;
         Ok(snapshot)
     }
 
+    /// Whether an update has to rewrite the snapshot file: it does not exist
+    /// yet, [`SNAPSHOT_WRITE_INTERVAL`] passed since it was written, or the
+    /// project, transcript or set of models changed.
+    fn needs_write(stored: Option<&SessionSnapshot>, updated: &SessionSnapshot) -> bool {
+        let Some(stored) = stored else {
+            return true;
+        };
+        let due = stored.meta.last_update_time.is_none_or(|written| {
+            (Utc::now() - written)
+                .to_std()
+                .map_or(true, |age| age >= SNAPSHOT_WRITE_INTERVAL)
+        });
+        let models = |snapshot: &SessionSnapshot| {
+            snapshot
+                .history
+                .model_usage
+                .iter()
+                .map(|entry| entry.id.clone())
+                .collect::<Vec<_>>()
+        };
+        due || stored.meta.project_path != updated.meta.project_path
+            || stored.transcript_state.transcript_path != updated.transcript_state.transcript_path
+            || models(stored) != models(updated)
+    }
+
+    /// Token and cost deltas of one snapshot update
+    fn snapshot_metric_events(
+        session_id: &str,
+        previous_cost: f64,
+        snapshot: &SessionSnapshot,
+        messages: &[TokenHistory],
+    ) -> Vec<MetricEvent> {
+        let timestamp = Utc::now();
+        let event = |kind| MetricEvent {
+            timestamp,
+            session_id: session_id.to_string(),
+            kind,
+        };
+        let mut events = Vec::new();
+        if !messages.is_empty() {
+            events.push(event(MetricKind::Tokens {
+                input: messages.iter().map(|message| message.input).sum(),
+                output: messages.iter().map(|message| message.output).sum(),
+                cache_creation_input: messages
+                    .iter()
+                    .map(|message| message.cache_creation_input)
+                    .sum(),
+                cache_read_input: messages
+                    .iter()
+                    .map(|message| message.cache_read_input)
+                    .sum(),
+                messages: messages.len() as u64,
+            }));
+        }
+        let total_cost = snapshot.history.cost.total.total_cost_usd;
+        if total_cost > previous_cost {
+            events.push(event(MetricKind::Cost {
+                delta_usd: total_cost - previous_cost,
+                total_usd: total_cost,
+            }));
+        }
+        events
+    }
+
+    /// Append events to the session's metrics log.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the log cannot be written.
+    pub fn append_metrics(&self, session_id: &str, events: &[MetricEvent]) -> Result<()> {
+        metrics::append_events(
+            &metrics::metrics_path(&self.paths.sessions_dir, session_id),
+            events,
+        )
+    }
+
+    /// Apply the configured sanitization policy to a stdin payload.
+    fn sanitized_latest(config: &StorageConfig, mut latest: Value) -> Value {
+        if !config.store_latest {
+            return Value::Null;
+        }
+        sanitize_latest_value(&mut latest, &config.redact_keys);
+        latest
+    }
+
+    /// Re-apply the sanitization policy to every stored snapshot, across all projects.
+    ///
+    /// With `dry_run` files are only inspected, never rewritten.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a changed snapshot cannot be written back.
+    pub fn scrub_sessions(config: &StorageConfig, dry_run: bool) -> Result<ScrubReport> {
+        let mut report = ScrubReport::default();
+
+        for path in Self::all_snapshot_files(config) {
+            report.scanned += 1;
+            let Some(mut snapshot) = fs::read_to_string(&path)
+                .ok()
+                .and_then(|content| serde_json::from_str::<SessionSnapshot>(&content).ok())
+            else {
+                report.unreadable.push(path);
+                continue;
+            };
+
+            let scrubbed = Self::sanitized_latest(config, snapshot.latest.clone());
+            if scrubbed == snapshot.latest {
+                continue;
+            }
+
+            snapshot.latest = scrubbed;
+            if !dry_run {
+                Self::write_snapshot_file(&path, &snapshot)?;
+            }
+            report.rewritten.push(path);
+        }
+
+        Ok(report)
+    }
+
+    /// Snapshot files of every project under the storage root
+    pub(crate) fn all_snapshot_files(config: &StorageConfig) -> Vec<PathBuf> {
+        Self::project_sessions_dirs(config)
+            .into_iter()
+            .flat_map(|(_, dir)| snapshot_files_in(&dir))
+            .collect()
+    }
+
+    /// Metrics logs of every project under the storage root
+    pub(crate) fn all_metrics_files(config: &StorageConfig) -> Vec<PathBuf> {
+        Self::project_sessions_dirs(config)
+            .into_iter()
+            .flat_map(|(_, dir)| metrics_files_in(&dir))
+            .collect()
+    }
+
+    /// `(project id, sessions dir)` of every project under the storage root
+    fn project_sessions_dirs(config: &StorageConfig) -> Vec<(String, PathBuf)> {
+        let projects_dir = Self::base_path(config).join("projects");
+        let Ok(projects) = fs::read_dir(projects_dir) else {
+            return Vec::new();
+        };
+
+        let mut dirs: Vec<(String, PathBuf)> = projects
+            .flatten()
+            .map(|project| {
+                (
+                    project.file_name().to_string_lossy().into_owned(),
+                    project.path().join("statusline-pro").join("sessions"),
+                )
+            })
+            .filter(|(_, dir)| dir.is_dir())
+            .collect();
+        dirs.sort();
+        dirs
+    }
+
+    /// Collect per-project storage statistics, optionally repairing snapshots
+    /// that no longer deserialize.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a repaired snapshot cannot be written back.
+    pub fn storage_stats(config: &StorageConfig, repair: bool) -> Result<StorageStats> {
+        let mut stats = StorageStats {
+            root: Self::base_path(config).join("projects"),
+            projects: Vec::new(),
+        };
+
+        for (project_id, dir) in Self::project_sessions_dirs(config) {
+            let mut project = ProjectStats {
+                project_id,
+                ..ProjectStats::default()
+            };
+
+            for path in metrics_files_in(&dir) {
+                project.metrics_bytes += fs::metadata(&path).map_or(0, |meta| meta.len());
+                project.metrics_events +=
+                    metrics::read_metrics(&path).map_or(0, |events| events.len());
+            }
+
+            for path in snapshot_files_in(&dir) {
+                let Ok(metadata) = fs::metadata(&path) else {
+                    continue;
+                };
+                project.sessions += 1;
+                project.bytes += metadata.len();
+
+                if let Ok(modified) = metadata.modified() {
+                    let entry = SnapshotAge {
+                        session_id: file_stem(&path),
+                        modified: modified.into(),
+                    };
+                    if project
+                        .oldest
+                        .as_ref()
+                        .is_none_or(|oldest| entry.modified < oldest.modified)
+                    {
+                        project.oldest = Some(entry.clone());
+                    }
+                    if project
+                        .newest
+                        .as_ref()
+                        .is_none_or(|newest| entry.modified > newest.modified)
+                    {
+                        project.newest = Some(entry);
+                    }
+                }
+
+                if read_snapshot_file(&path).is_some() {
+                    continue;
+                }
+                project.parse_failures.push(path.clone());
+
+                if repair {
+                    match salvage_snapshot_file(&path) {
+                        Some(snapshot) => {
+                            Self::write_snapshot_file(&path, &snapshot)?;
+                            project.repaired.push(path);
+                        }
+                        None => project.unrecoverable.push(path),
+                    }
+                }
+            }
+
+            stats.projects.push(project);
+        }
+
+        Ok(stats)
+    }
+
+    /// Find the snapshot of the session that `session_id` resumes.
+    ///
+    /// Claude Code 恢复会话时会换一个新的 session id:要么继续写同一个
+    /// transcript,要么新 transcript 的 `parentUuid` 指向旧会话最后一条消息。
+    /// 两种情况都取最近更新的匹配快照。
+    fn find_resumed_snapshot(
+        &self,
+        session_id: &str,
+        transcript_path: Option<&str>,
+    ) -> Option<SessionSnapshot> {
+        let transcript_path = transcript_path?;
+        let parent_uuids = Self::transcript_parent_uuids(Path::new(transcript_path));
+
+        fs::read_dir(&self.paths.sessions_dir)
+            .ok()?
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+            .filter_map(|path| read_current_snapshot(&path))
+            .filter(|snapshot| snapshot.meta.session_id != session_id)
+            .filter(|snapshot| {
+                let state = &snapshot.transcript_state;
+                state.transcript_path.as_deref() == Some(transcript_path)
+                    || state
+                        .last_message_uuid
+                        .as_ref()
+                        .is_some_and(|uuid| parent_uuids.contains(uuid))
+            })
+            .max_by_key(|snapshot| snapshot.meta.last_update_time)
+    }
+
+    /// `parentUuid` values found at the head of a transcript
+    fn transcript_parent_uuids(path: &Path) -> Vec<String> {
+        let Ok(file) = File::open(path) else {
+            return Vec::new();
+        };
+
+        BufReader::new(file)
+            .lines()
+            .take(RESUME_SCAN_LINES)
+            .map_while(std::result::Result::ok)
+            .filter_map(|line| serde_json::from_str::<Value>(line.trim()).ok())
+            .filter_map(|value| {
+                value
+                    .get("parentUuid")
+                    .and_then(Value::as_str)
+                    .map(std::string::ToString::to_string)
+            })
+            .collect()
+    }
+
+    /// Start a new session's snapshot from the one it resumes.
+    ///
+    /// The new session reports its cost from zero again, so the previous
+    /// total moves into the accumulated bucket; tokens, models, error streak
+    /// and transcript progress carry over unchanged.
+    fn continue_snapshot(session_id: &str, previous: SessionSnapshot) -> SessionSnapshot {
+        let mut snapshot = SessionSnapshot::new(session_id);
+        snapshot.meta.created_at = previous.meta.created_at.or(snapshot.meta.created_at);
+        snapshot.meta.project_path = previous.meta.project_path;
+        snapshot.meta.resumed_from = Some(previous.meta.session_id);
+
+        snapshot.history = previous.history;
+        snapshot.history.cost.accumulated = snapshot.history.cost.total.clone();
+        snapshot.history.cost.current = CostMetrics::default();
+        snapshot.transcript_state = previous.transcript_state;
+        snapshot
+    }
+
+    /// Persist the consecutive error streak for a session.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error when the snapshot cannot be loaded or saved.
+    pub fn update_error_streak(&self, session_id: &str, streak: &ErrorStreak) -> Result<()> {
+        self.modify_snapshot(session_id, |snapshot| {
+            snapshot.history.errors = streak.clone();
+        })
+    }
+
+    /// # Errors
+    ///
+    /// Returns an error if the snapshot cannot be loaded or written.
+    pub fn update_critical_alert(&self, session_id: &str, alerted: bool) -> Result<()> {
+        self.modify_snapshot(session_id, |snapshot| {
+            snapshot.history.critical_alerted = alerted;
+        })
+    }
+
+    /// Persist the model switch tracking state for a session.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the snapshot cannot be loaded or written.
+    pub fn update_model_switch(&self, session_id: &str, state: &ModelSwitchState) -> Result<()> {
+        self.modify_snapshot(session_id, |snapshot| {
+            snapshot.history.model_switch = state.clone();
+        })
+    }
+
+    /// Persist the event hook state for a session.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the snapshot cannot be loaded or written.
+    pub fn update_hook_state(&self, session_id: &str, state: &HookState) -> Result<()> {
+        let previous = self.modify_snapshot(session_id, |snapshot| {
+            std::mem::replace(&mut snapshot.history.hooks, state.clone())
+        })?;
+
+        // 条件的成立与失效记入指标日志
+        let timestamp = Utc::now();
+        let mut changes: Vec<(&String, bool)> = state
+            .active
+            .difference(&previous.active)
+            .map(|condition| (condition, true))
+            .chain(
+                previous
+                    .active
+                    .difference(&state.active)
+                    .map(|condition| (condition, false)),
+            )
+            .collect();
+        changes.sort();
+        let events: Vec<MetricEvent> = changes
+            .into_iter()
+            .map(|(condition, active)| MetricEvent {
+                timestamp,
+                session_id: session_id.to_string(),
+                kind: MetricKind::Status {
+                    condition: condition.clone(),
+                    active,
+                },
+            })
+            .collect();
+        self.append_metrics(session_id, &events)
+    }
+
+    /// Append a rendered statusline to the session's history log when
+    /// `storage.historyLog` is enabled.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the log cannot be read or written.
+    pub fn append_history(&self, session_id: &str, line: &str) -> Result<()> {
+        if self.config.history_max_entries == 0 {
+            return Ok(());
+        }
+        let entry = HistoryEntry {
+            timestamp: Utc::now(),
+            session_id: session_id.to_string(),
+            line: line.to_string(),
+        };
+        history::append_entry(
+            &history::history_path(&self.paths.sessions_dir, session_id),
+            &entry,
+            self.config.history_max_entries,
+        )
+        .map(drop)
+    }
+
+    /// Warm-start outputs of a session rendered within `max_age`
+    #[must_use]
+    pub fn warm_start_outputs(
+        &self,
+        session_id: &str,
+        max_age: std::time::Duration,
+    ) -> BTreeMap<String, WarmOutput> {
+        warm_start::read_outputs(
+            &warm_start::warm_start_path(&self.paths.sessions_dir, session_id),
+            max_age,
+            Utc::now(),
+        )
+    }
+
+    /// Store the warm-start outputs of a session.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be written.
+    pub fn save_warm_start(
+        &self,
+        session_id: &str,
+        outputs: &BTreeMap<String, WarmOutput>,
+    ) -> Result<()> {
+        warm_start::write_outputs(
+            &warm_start::warm_start_path(&self.paths.sessions_dir, session_id),
+            outputs,
+        )
+    }
+
+    /// History log of the session whose ID is or starts with `session`, in
+    /// any project.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error when the prefix matches logs of several sessions.
+    pub fn find_history(config: &StorageConfig, session: &str) -> Result<Option<PathBuf>> {
+        let mut matches: Vec<(String, PathBuf)> = Self::project_sessions_dirs(config)
+            .into_iter()
+            .filter_map(|(_, dir)| fs::read_dir(dir).ok())
+            .flat_map(|entries| entries.flatten().map(|entry| entry.path()))
+            .filter_map(|path| {
+                let id = path
+                    .file_name()?
+                    .to_str()?
+                    .strip_suffix(history::HISTORY_SUFFIX)?
+                    .to_string();
+                id.starts_with(session).then_some((id, path))
+            })
+            .collect();
+        if let Some(index) = matches.iter().position(|(id, _)| id == session) {
+            return Ok(Some(matches.swap_remove(index).1));
+        }
+        match matches.len() {
+            0 | 1 => Ok(matches.pop().map(|(_, path)| path)),
+            count => Err(anyhow!(
+                "{count} sessions start with `{session}`, use a longer prefix"
+            )),
+        }
+    }
+
     /// # Errors
     ///
     /// Returns an error if the snapshot cannot be loaded from disk or the
@@ -508,6 +1244,30 @@ impl StorageManager {
         self.load_snapshot(session_id)
     }
 
+    /// Sessions of this project whose snapshot was updated within `window`,
+    /// oldest first.
+    #[must_use]
+    pub fn active_sessions(&self, window: std::time::Duration) -> Vec<ActiveSession> {
+        let cutoff =
+            Utc::now() - chrono::Duration::from_std(window).unwrap_or(chrono::Duration::MAX);
+        let mut sessions: Vec<ActiveSession> = snapshot_files_in(&self.paths.sessions_dir)
+            .iter()
+            .filter_map(|path| read_current_snapshot(path))
+            .filter(|snapshot| {
+                snapshot
+                    .meta
+                    .last_update_time
+                    .is_some_and(|updated| updated >= cutoff)
+            })
+            .map(|snapshot| ActiveSession {
+                session_id: snapshot.meta.session_id,
+                created_at: snapshot.meta.created_at,
+            })
+            .collect();
+        sessions.sort_by(|a, b| (a.created_at, &a.session_id).cmp(&(b.created_at, &b.session_id)));
+        sessions
+    }
+
     /// Clean up old session snapshots based on retention configuration.
     ///
     /// # Errors
@@ -542,7 +1302,10 @@ impl StorageManager {
         while let Some(entry) = entries.next_entry().await? {
             let path = entry.path();
 
-            if path.extension().is_none_or(|ext| ext != "json") {
+            // 历史日志与 warm-start 行按与快照相同的期限清理
+            if path.extension().is_none_or(|ext| {
+                ext != "json" && ext != "jsonl" && ext != warm_start::WARM_START_EXTENSION
+            }) {
                 continue;
             }
 
@@ -561,62 +1324,365 @@ impl StorageManager {
     }
 }
 
-fn sanitize_latest_value(value: &mut Value) {
+/// Unique temp file for a write of the snapshot at `path`
+fn temp_path_for(path: &Path) -> PathBuf {
+    let sequence = TEMP_SEQUENCE.fetch_add(1, Ordering::Relaxed);
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(format!(
+        ".{}-{sequence}.{TEMP_EXTENSION}",
+        std::process::id()
+    ));
+    path.with_file_name(name)
+}
+
+/// Snapshot a temp file was written for: `<session>.json` for
+/// `<session>.json.<pid>-<n>.tmp` and the older `<session>.json.tmp`
+fn temp_target(tmp_path: &Path) -> Option<PathBuf> {
+    let name = tmp_path.file_name()?.to_str()?;
+    let mut target = Path::new(name.strip_suffix(&format!(".{TEMP_EXTENSION}"))?);
+    if target.extension()? != "json" {
+        target = Path::new(target.file_stem()?);
+    }
+    (target.extension()? == "json").then(|| tmp_path.with_file_name(target))
+}
+
+fn read_snapshot_file(path: &Path) -> Option<SessionSnapshot> {
+    let content = fs::read_to_string(path).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+/// A snapshot file with the newest counter checkpoint of its metrics log applied
+pub fn read_current_snapshot(path: &Path) -> Option<SessionSnapshot> {
+    let mut snapshot = read_snapshot_file(path)?;
+    if let Some(sessions_dir) = path.parent() {
+        replay_checkpoint(&mut snapshot, sessions_dir);
+    }
+    Some(snapshot)
+}
+
+/// Apply the latest checkpoint logged after the snapshot was written and
+/// move `metrics_offset` to the end of the log.
+///
+/// 偏移取自读取检查点之前的日志长度;期间其他进程追加的检查点在下次读取时
+/// 仍会被应用。
+fn replay_checkpoint(snapshot: &mut SessionSnapshot, sessions_dir: &Path) {
+    let log = metrics::metrics_path(sessions_dir, &snapshot.meta.session_id);
+    let len = metrics::log_len(&log);
+    if let Some(counters) = metrics::latest_checkpoint(&log, snapshot.meta.metrics_offset) {
+        snapshot.apply_counters(counters);
+    }
+    snapshot.meta.metrics_offset = len;
+}
+
+/// Whether the counters differ in more than their timestamp
+fn counters_changed(before: &SessionCounters, after: &SessionCounters) -> bool {
+    SessionCounters {
+        updated_at: after.updated_at,
+        ..before.clone()
+    } != *after
+}
+
+fn snapshot_files_in(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut files: Vec<PathBuf> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .collect();
+    files.sort();
+    files
+}
+
+fn metrics_files_in(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut files: Vec<PathBuf> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.ends_with(metrics::METRICS_SUFFIX))
+        })
+        .collect();
+    files.sort();
+    files
+}
+
+fn file_stem(path: &Path) -> String {
+    path.file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+/// Rebuild a snapshot from the fields of a damaged file that still deserialize.
+///
+/// 逐个字段(history 下再逐个子字段)尝试反序列化,失败的字段回退为默认值;
+/// 文件本身不是合法 JSON 时无从抢救,返回 `None`。
+fn salvage_snapshot_file(path: &Path) -> Option<SessionSnapshot> {
+    let content = fs::read_to_string(path).ok()?;
+    let value: Value = serde_json::from_str(&content).ok()?;
+
+    let mut snapshot = SessionSnapshot::new(&file_stem(path));
+    let section = |key: &str| value.get(key).and_then(Value::as_object);
+    if let Some(meta) = section("meta") {
+        snapshot.meta = salvage_fields(snapshot.meta, meta);
+    }
+    if let Some(latest) = value.get("latest") {
+        snapshot.latest = latest.clone();
+    }
+    if let Some(state) = section("transcript_state") {
+        snapshot.transcript_state = salvage_fields(snapshot.transcript_state, state);
+    }
+    if let Some(history) = section("history") {
+        snapshot.history = salvage_fields(snapshot.history, history);
+    }
+    Some(snapshot)
+}
+
+/// `base` with every field of `damaged` that still parses taken over;
+/// covers new fields of the section without listing them here
+fn salvage_fields<T: Serialize + DeserializeOwned>(base: T, damaged: &Map<String, Value>) -> T {
+    let Ok(Value::Object(mut fields)) = serde_json::to_value(&base) else {
+        return base;
+    };
+    for (key, value) in damaged {
+        let previous = fields.insert(key.clone(), value.clone());
+        if serde_json::from_value::<T>(Value::Object(fields.clone())).is_err() {
+            match previous {
+                Some(previous) => fields.insert(key.clone(), previous),
+                None => fields.remove(key),
+            };
+        }
+    }
+    serde_json::from_value(Value::Object(fields)).unwrap_or(base)
+}
+
+/// Persist directory entries (renames) to disk.
+///
+/// Directories cannot be opened for syncing on Windows; failures are ignored
+/// since the rename itself has already succeeded.
+fn sync_dir(dir: &Path) {
+    if let Ok(handle) = File::open(dir) {
+        let _ = handle.sync_all();
+    }
+}
+
+/// Strip `redact_keys` from a payload, then drop null / empty values.
+fn sanitize_latest_value(value: &mut Value, redact_keys: &[String]) {
+    for key in redact_keys {
+        if key.contains('.') {
+            remove_path(value, &key.split('.').collect::<Vec<_>>());
+        } else {
+            remove_key_everywhere(value, key);
+        }
+    }
+    prune_empty(value);
+}
+
+fn remove_path(value: &mut Value, path: &[&str]) {
+    let Some((last, parents)) = path.split_last() else {
+        return;
+    };
+    let mut current = value;
+    for segment in parents {
+        let Some(next) = current.get_mut(*segment) else {
+            return;
+        };
+        current = next;
+    }
+    if let Value::Object(map) = current {
+        map.remove(*last);
+    }
+}
+
+fn remove_key_everywhere(value: &mut Value, key: &str) {
     match value {
-        Value::Object(map) => sanitize_object(map),
+        Value::Object(map) => {
+            map.remove(key);
+            for item in map.values_mut() {
+                remove_key_everywhere(item, key);
+            }
+        }
         Value::Array(items) => {
-            for item in items.iter_mut() {
-                sanitize_latest_value(item);
+            for item in items {
+                remove_key_everywhere(item, key);
             }
-            items.retain(|item| match item {
-                Value::Null => false,
-                Value::Object(obj) => !obj.is_empty(),
-                Value::Array(arr) => !arr.is_empty(),
-                _ => true,
-            });
         }
         _ => {}
     }
 }
 
-fn sanitize_object(map: &mut Map<String, Value>) {
-    if let Some(Value::Object(cost_map)) = map.get_mut("cost") {
-        for key in [
-            "input_tokens",
-            "output_tokens",
-            "total_tokens",
-            "cache_read_tokens",
-            "cache_write_tokens",
-        ] {
-            cost_map.remove(key);
-        }
-        let cost_keys: Vec<String> = cost_map.keys().cloned().collect();
-        for key in cost_keys {
-            if let Some(value) = cost_map.get_mut(&key) {
-                sanitize_latest_value(value);
-                if value.is_null()
-                    || matches!(value, Value::Object(obj) if obj.is_empty())
-                    || matches!(value, Value::Array(arr) if arr.is_empty())
-                {
-                    cost_map.remove(&key);
-                }
+fn is_empty_value(value: &Value) -> bool {
+    match value {
+        Value::Null => true,
+        Value::Object(map) => map.is_empty(),
+        Value::Array(items) => items.is_empty(),
+        _ => false,
+    }
+}
+
+fn prune_empty(value: &mut Value) {
+    match value {
+        Value::Object(map) => prune_object(map),
+        Value::Array(items) => {
+            for item in items.iter_mut() {
+                prune_empty(item);
             }
+            items.retain(|item| !is_empty_value(item));
         }
-        if cost_map.is_empty() {
-            map.remove("cost");
-        }
+        _ => {}
     }
+}
 
-    let keys: Vec<String> = map.keys().cloned().collect();
-    for key in keys {
-        if let Some(value) = map.get_mut(&key) {
-            sanitize_latest_value(value);
-            if value.is_null()
-                || matches!(value, Value::Object(obj) if obj.is_empty())
-                || matches!(value, Value::Array(arr) if arr.is_empty())
-            {
-                map.remove(&key);
+fn prune_object(map: &mut Map<String, Value>) {
+    for item in map.values_mut() {
+        prune_empty(item);
+    }
+    map.retain(|_, item| !is_empty_value(item));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::schema::default_redact_keys;
+    use serde_json::json;
+
+    #[test]
+    fn salvage_keeps_every_intact_history_field() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let mut snapshot = SessionSnapshot::new("salvaged");
+        snapshot.meta.resumed_from = Some("earlier".to_string());
+        snapshot.history.cost.total.total_cost_usd = 1.5;
+        snapshot.history.tokens = Some(TokenHistory {
+            input: 10,
+            ..TokenHistory::default()
+        });
+        snapshot.history.model_usage = vec![ModelUsageEntry {
+            id: "claude-opus-4".to_string(),
+            display_name: None,
+            last_used_at: None,
+        }];
+        snapshot.history.errors.count = 3;
+        snapshot.history.critical_alerted = true;
+        snapshot.history.model_switch.last_model_id = Some("claude-opus-4".to_string());
+        snapshot
+            .history
+            .hooks
+            .active
+            .insert("context_critical".to_string());
+        snapshot.transcript_state.processed_messages = 7;
+
+        let mut value = serde_json::to_value(&snapshot)?;
+        value["meta"]["created_at"] = json!(42);
+        value["history"]["errors"] = json!("oops");
+        let path = dir.path().join("salvaged.json");
+        fs::write(&path, value.to_string())?;
+
+        let salvaged = salvage_snapshot_file(&path).ok_or_else(|| anyhow!("not salvaged"))?;
+        assert_eq!(salvaged.meta.resumed_from.as_deref(), Some("earlier"));
+        assert!(salvaged.meta.created_at.is_some());
+        assert_eq!(salvaged.history.errors, ErrorStreak::default());
+        snapshot.history.errors = ErrorStreak::default();
+        assert_eq!(
+            serde_json::to_value(&salvaged.history)?,
+            serde_json::to_value(&snapshot.history)?
+        );
+        assert_eq!(salvaged.transcript_state, snapshot.transcript_state);
+        Ok(())
+    }
+
+    #[test]
+    fn sanitize_strips_default_cost_token_counters() {
+        let mut value = json!({
+            "session_id": "s",
+            "cost": {"total_cost_usd": 0.5, "input_tokens": 10, "output_tokens": 2},
+            "workspace": {"current_dir": "/tmp", "extra": null}
+        });
+        sanitize_latest_value(&mut value, &default_redact_keys());
+        assert_eq!(
+            value,
+            json!({
+                "session_id": "s",
+                "cost": {"total_cost_usd": 0.5},
+                "workspace": {"current_dir": "/tmp"}
+            })
+        );
+    }
+
+    #[test]
+    fn sanitize_supports_bare_keys_at_any_depth() {
+        let mut value = json!({
+            "cwd": "/home/me/project",
+            "workspace": {"cwd": "/home/me/project", "project_dir": "/home/me"},
+            "cost": {"input_tokens": 1}
+        });
+        sanitize_latest_value(
+            &mut value,
+            &["cwd".to_string(), "cost.input_tokens".to_string()],
+        );
+        assert_eq!(value, json!({"workspace": {"project_dir": "/home/me"}}));
+    }
+
+    #[test]
+    fn concurrent_field_updates_are_all_kept() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let manager = StorageManager::with_config(
+            StorageConfig {
+                storage_path: Some(dir.path().to_path_buf()),
+                ..StorageConfig::default()
+            },
+            Some("project".to_string()),
+        )?;
+
+        std::thread::scope(|scope| {
+            for round in 0..20_u32 {
+                let manager = &manager;
+                scope.spawn(move || {
+                    let streak = ErrorStreak {
+                        count: round + 1,
+                        last_error_uuid: None,
+                    };
+                    manager.update_error_streak("session", &streak)
+                });
+                scope.spawn(move || manager.update_critical_alert("session", true));
+                scope.spawn(move || {
+                    let state = ModelSwitchState {
+                        last_model_id: Some("opus".to_string()),
+                        ..ModelSwitchState::default()
+                    };
+                    manager.update_model_switch("session", &state)
+                });
             }
-        }
+        });
+
+        let history = manager
+            .get_snapshot("session")?
+            .map(|snapshot| snapshot.history)
+            .unwrap_or_default();
+        assert!(history.errors.count > 0);
+        assert!(history.critical_alerted);
+        assert_eq!(history.model_switch.last_model_id.as_deref(), Some("opus"));
+        // 每次写入使用自己的临时文件,写完不留残余
+        assert_eq!(fs::read_dir(&manager.paths.sessions_dir)?.count(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn temp_files_map_back_to_their_snapshot() {
+        let dir = Path::new("/sessions");
+        let first = temp_path_for(&dir.join("abc.json"));
+        let second = temp_path_for(&dir.join("abc.json"));
+        assert_ne!(first, second);
+        assert_eq!(temp_target(&first), Some(dir.join("abc.json")));
+        assert_eq!(
+            temp_target(&dir.join("abc.json.tmp")),
+            Some(dir.join("abc.json"))
+        );
+        assert_eq!(temp_target(&dir.join("abc.json")), None);
+        assert_eq!(temp_target(&dir.join("notes.tmp")), None);
     }
 }
@@ -0,0 +1,205 @@
+//! Incremental session metrics
+//!
+//! 会话快照整体重写代价不小,也只保留最新状态。每次快照更新产生的增量
+//! (新消息消耗的 token、成本增长、状态条件的成立与失效)以一行 JSON 追加到
+//! 会话目录下的 `<session>.metrics.jsonl`,只追加不重写。快照本身只在状态变化
+//! 或每隔一段时间重写;两次重写之间的计数以检查点事件记在日志里,读取快照时
+//! 从 `SessionMeta::metrics_offset` 之后找最新的检查点补齐。
+//! `ccsp storage stats` 统计日志规模,`ccsp usage --source metrics` 按事件时间
+//! 汇总 token 与成本,跨天的会话也能拆到各自的日期。
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::types::SessionCounters;
+
+/// File name suffix of per-session metrics logs
+pub const METRICS_SUFFIX: &str = ".metrics.jsonl";
+
+/// One change recorded for a session
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MetricEvent {
+    pub timestamp: DateTime<Utc>,
+    pub session_id: String,
+    #[serde(flatten)]
+    pub kind: MetricKind,
+}
+
+/// What changed
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum MetricKind {
+    /// Tokens of the assistant messages read since the previous update
+    Tokens {
+        input: u64,
+        output: u64,
+        cache_creation_input: u64,
+        cache_read_input: u64,
+        messages: u64,
+    },
+    /// Growth of the session's total cost
+    Cost { delta_usd: f64, total_usd: f64 },
+    /// A hook condition (`context_critical`, `error_detected`, ...) started or
+    /// stopped holding
+    Status { condition: String, active: bool },
+    /// Counters of the session at this point; newer than the snapshot file
+    /// until the next snapshot write
+    Checkpoint(Box<SessionCounters>),
+}
+
+/// Metrics log of `session_id` inside `sessions_dir`
+#[must_use]
+pub fn metrics_path(sessions_dir: &Path, session_id: &str) -> PathBuf {
+    sessions_dir.join(format!("{session_id}{METRICS_SUFFIX}"))
+}
+
+/// Read every event of a metrics log; unparsable lines are skipped.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be read.
+pub fn read_metrics(path: &Path) -> Result<Vec<MetricEvent>> {
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read metrics log: {}", path.display()))?;
+    Ok(content
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect())
+}
+
+/// Length of the log at `path` in bytes; 0 if it does not exist
+#[must_use]
+pub fn log_len(path: &Path) -> u64 {
+    fs::metadata(path).map_or(0, |meta| meta.len())
+}
+
+/// Latest counter checkpoint written at or after byte `offset` of the log.
+///
+/// A log shorter than `offset` was replaced since the snapshot was written,
+/// so none of its checkpoints apply.
+#[must_use]
+pub fn latest_checkpoint(path: &Path, offset: u64) -> Option<SessionCounters> {
+    let mut file = File::open(path).ok()?;
+    if file.metadata().ok()?.len() < offset {
+        return None;
+    }
+    file.seek(SeekFrom::Start(offset)).ok()?;
+    BufReader::new(file)
+        .lines()
+        .map_while(std::result::Result::ok)
+        .filter_map(|line| serde_json::from_str::<MetricEvent>(&line).ok())
+        .filter_map(|event| match event.kind {
+            MetricKind::Checkpoint(counters) => Some(*counters),
+            _ => None,
+        })
+        .last()
+}
+
+/// Append `events` to the log at `path`.
+///
+/// # Errors
+///
+/// Returns an error if an event cannot be serialized or the log cannot be written.
+pub fn append_events(path: &Path, events: &[MetricEvent]) -> Result<()> {
+    if events.is_empty() {
+        return Ok(());
+    }
+    let mut content = String::new();
+    for event in events {
+        content.push_str(&serde_json::to_string(event)?);
+        content.push('\n');
+    }
+    // 一次 write 写入所有行:O_APPEND 下并发的渲染进程不会交错出半行
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .and_then(|mut file| file.write_all(content.as_bytes()))
+        .with_context(|| format!("Failed to append metrics log: {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn events_round_trip_through_the_log() -> Result<()> {
+        let dir = tempdir()?;
+        let path = metrics_path(dir.path(), "s1");
+        let event = |kind| MetricEvent {
+            timestamp: Utc::now(),
+            session_id: "s1".to_string(),
+            kind,
+        };
+        let events = [
+            event(MetricKind::Tokens {
+                input: 10,
+                output: 200,
+                cache_creation_input: 0,
+                cache_read_input: 3000,
+                messages: 2,
+            }),
+            event(MetricKind::Cost {
+                delta_usd: 0.25,
+                total_usd: 1.5,
+            }),
+        ];
+        append_events(&path, &events)?;
+        append_events(&path, &[])?;
+        append_events(
+            &path,
+            &[event(MetricKind::Status {
+                condition: "context_critical".to_string(),
+                active: true,
+            })],
+        )?;
+        // 被截断的半行不影响其余事件
+        OpenOptions::new()
+            .append(true)
+            .open(&path)?
+            .write_all(b"{\"timestamp\":")?;
+
+        let read = read_metrics(&path)?;
+        assert_eq!(read.len(), 3);
+        assert_eq!(read[..2], events);
+        let line = fs::read_to_string(&path)?;
+        assert!(line.starts_with("{\"timestamp\""));
+        assert!(line.contains("\"type\":\"tokens\""));
+        Ok(())
+    }
+
+    #[test]
+    fn latest_checkpoint_only_looks_past_the_offset() -> Result<()> {
+        let dir = tempdir()?;
+        let path = metrics_path(dir.path(), "s1");
+        let checkpoint = |total| {
+            let mut counters = SessionCounters::default();
+            counters.cost.current.total_cost_usd = total;
+            MetricEvent {
+                timestamp: Utc::now(),
+                session_id: "s1".to_string(),
+                kind: MetricKind::Checkpoint(Box::new(counters)),
+            }
+        };
+        let total =
+            |counters: Option<SessionCounters>| counters.map(|c| c.cost.current.total_cost_usd);
+
+        assert_eq!(latest_checkpoint(&path, 0), None);
+        append_events(&path, &[checkpoint(1.0)])?;
+        let offset = log_len(&path);
+        assert_eq!(total(latest_checkpoint(&path, 0)), Some(1.0));
+        assert_eq!(latest_checkpoint(&path, offset), None);
+
+        append_events(&path, &[checkpoint(2.0), checkpoint(3.0)])?;
+        assert_eq!(total(latest_checkpoint(&path, offset)), Some(3.0));
+        // 日志被替换得比偏移还短时,其中的检查点不属于这份快照
+        assert_eq!(latest_checkpoint(&path, log_len(&path) + 1), None);
+        Ok(())
+    }
+}
@@ -1,58 +1,83 @@
 //! Storage system for statusline-pro
 //!
 //! Provides persistent storage for session snapshots and incremental metrics.
+//!
+//! 快照、指标日志、历史日志等落盘部分只在启用 `storage` 特性时编译;
+//! 组件用到的会话类型与 [`MemoryStorage`] 始终可用。
 
+#[cfg(any(feature = "storage", feature = "git"))]
+pub(crate) mod aggregate_cache;
+#[cfg(feature = "storage")]
+mod context;
+mod handle;
+#[cfg(feature = "storage")]
+pub mod history;
+#[cfg(feature = "storage")]
 mod manager;
+#[cfg(feature = "storage")]
+pub mod metrics;
+pub mod pricing;
+mod project_id;
 mod project_resolver;
+pub mod recorder;
 mod types;
+pub mod usage_report;
+pub mod warm_start;
 
-pub use manager::StorageManager;
+#[cfg(feature = "storage")]
+pub use context::StorageContext;
+#[cfg(feature = "storage")]
+pub use handle::DiskStorage;
+pub use handle::{MemoryStorage, StorageHandle};
+#[cfg(feature = "storage")]
+pub use manager::{
+    ProjectStats, ScrubReport, SnapshotAge, StorageManager, StorageStats, TempRecovery,
+};
+pub use project_id::{ProjectId, SymlinkPolicy};
 pub use project_resolver::ProjectResolver;
 pub use types::*;
 
+use crate::config::ModelProviderConfig;
+#[cfg(feature = "storage")]
 use crate::config::StorageConfig as SettingsConfig;
+#[cfg(feature = "storage")]
+use crate::error::{StatuslineError, StatuslineResult};
 use anyhow::Result;
+use std::collections::HashMap;
+use std::hash::BuildHasher;
 use std::path::PathBuf;
-use std::sync::{LazyLock, RwLock};
 use tokio::task;
 
-#[derive(Debug, Clone, Default)]
-struct StorageRuntimeState {
-    config: types::StorageConfig,
-    project_id: Option<String>,
-}
-
-static STORAGE_RUNTIME: LazyLock<RwLock<StorageRuntimeState>> =
-    LazyLock::new(|| RwLock::new(StorageRuntimeState::default()));
-
-fn runtime_config() -> types::StorageConfig {
-    STORAGE_RUNTIME
-        .read()
-        .unwrap_or_else(std::sync::PoisonError::into_inner)
-        .config
-        .clone()
-}
-
-fn runtime_project_id() -> Option<String> {
-    STORAGE_RUNTIME
-        .read()
-        .unwrap_or_else(std::sync::PoisonError::into_inner)
-        .project_id
-        .clone()
-}
-
-fn update_runtime_config(config: types::StorageConfig) {
-    if let Ok(mut state) = STORAGE_RUNTIME.write() {
-        state.config = config;
-    }
+/// User-level data directory (`<STATUSLINE_STORAGE_PATH or ~/.claude>/statusline-pro`)
+///
+/// Recordings, user mock scenarios and other non-project files live under here.
+#[must_use]
+pub fn user_data_dir() -> PathBuf {
+    std::env::var_os("STATUSLINE_STORAGE_PATH")
+        .map_or_else(
+            || crate::utils::home_dir_or_temp().join(".claude"),
+            PathBuf::from,
+        )
+        .join("statusline-pro")
 }
 
-fn update_runtime_project_id(project_id: Option<String>) {
-    if let Ok(mut state) = STORAGE_RUNTIME.write() {
-        state.project_id = project_id;
-    }
+/// Claude Code's transcript directory: `$CLAUDE_CONFIG_DIR/projects`,
+/// otherwise `~/.claude/projects`.
+///
+/// 与 statusline 自己的存储目录(`storage.storagePath` / `STATUSLINE_STORAGE_PATH`)
+/// 无关:改了存储目录,transcript 仍在 Claude Code 的配置目录下。
+#[must_use]
+pub fn claude_projects_dir() -> PathBuf {
+    std::env::var_os("CLAUDE_CONFIG_DIR")
+        .filter(|dir| !dir.is_empty())
+        .map_or_else(
+            || crate::utils::home_dir_or_temp().join(".claude"),
+            PathBuf::from,
+        )
+        .join("projects")
 }
 
+#[cfg(feature = "storage")]
 fn convert_settings(settings: &SettingsConfig) -> types::StorageConfig {
     let storage_path = std::env::var("STATUSLINE_STORAGE_PATH")
         .ok()
@@ -64,9 +89,17 @@ fn convert_settings(settings: &SettingsConfig) -> types::StorageConfig {
         enable_cost_persistence: settings.enable_cost_persistence,
         session_expiry_days: Some(settings.session_expiry_days),
         enable_startup_cleanup: settings.enable_startup_cleanup,
+        redact_keys: settings.redact_keys.clone(),
+        store_latest: settings.store_latest,
+        history_max_entries: if settings.history_log {
+            settings.history_max_entries
+        } else {
+            0
+        },
     }
 }
 
+#[cfg(feature = "storage")]
 /// Initialize the storage system with optional project ID and configuration settings
 ///
 /// # Errors
@@ -76,67 +109,127 @@ fn convert_settings(settings: &SettingsConfig) -> types::StorageConfig {
 pub async fn initialize_storage_with_settings(
     project_id: Option<String>,
     settings: &SettingsConfig,
-) -> Result<()> {
-    let storage_config = convert_settings(settings);
-    update_runtime_config(storage_config.clone());
-
-    if let Some(ref id) = project_id {
-        update_runtime_project_id(Some(id.clone()));
-    }
-
-    let mut manager = StorageManager::new()?;
-
-    if let Some(id) = project_id.clone() {
-        manager.set_project_id(&id);
-    }
-
-    manager.ensure_directories()?;
-
-    if storage_config.enable_startup_cleanup {
-        manager.cleanup_old_sessions().await?;
+) -> StatuslineResult<()> {
+    let mut context = StorageContext::new(settings);
+    context.set_project_id(
+        project_id.or_else(|| StorageContext::global().project_id().map(str::to_string)),
+    );
+    if let Some(id) = context.project_id() {
+        ProjectResolver::set_global_project_id(Some(id));
     }
+    StorageContext::set_global(context.clone());
 
-    Ok(())
+    context.initialize().await.map_err(StatuslineError::Storage)
 }
 
+#[cfg(feature = "storage")]
 /// Initialize the storage system using default settings
 ///
 /// # Errors
 ///
 /// Propagates errors from [`initialize_storage_with_settings`].
-pub async fn initialize_storage(project_id: Option<String>) -> Result<()> {
+pub async fn initialize_storage(project_id: Option<String>) -> StatuslineResult<()> {
     initialize_storage_with_settings(project_id, &SettingsConfig::default()).await
 }
 
-pub(crate) fn current_runtime_config() -> types::StorageConfig {
-    runtime_config()
+#[cfg(feature = "storage")]
+/// Update session snapshot from Claude Code input data.
+///
+/// # Errors
+///
+/// Returns an error when snapshot persistence fails or when storage manager
+/// operations encounter I/O errors.
+pub async fn update_session_snapshot(input_data: &serde_json::Value) -> StatuslineResult<()> {
+    StorageContext::global()
+        .update_session_snapshot(input_data)
+        .await
+        .map_err(StatuslineError::Storage)
 }
 
-pub(crate) fn current_runtime_project_id() -> Option<String> {
-    runtime_project_id()
+#[cfg(feature = "storage")]
+/// Re-sanitize stored snapshots with the given storage settings.
+///
+/// # Errors
+///
+/// Returns an error if a snapshot cannot be rewritten.
+pub async fn scrub_sessions(settings: &SettingsConfig, dry_run: bool) -> Result<ScrubReport> {
+    let config = convert_settings(settings);
+    task::spawn_blocking(move || StorageManager::scrub_sessions(&config, dry_run)).await?
 }
 
-pub(crate) fn set_runtime_project_id(project_id: Option<String>) {
-    update_runtime_project_id(project_id);
+#[cfg(feature = "storage")]
+/// History log of the session whose ID is or starts with `session`.
+///
+/// # Errors
+///
+/// Returns an error when several sessions match or the log cannot be read.
+pub async fn session_history_log(
+    settings: &SettingsConfig,
+    session: &str,
+) -> Result<Option<Vec<history::HistoryEntry>>> {
+    let config = convert_settings(settings);
+    let session = session.to_string();
+    task::spawn_blocking(move || {
+        StorageManager::find_history(&config, &session)?
+            .map(|path| history::read_history(&path))
+            .transpose()
+    })
+    .await?
 }
 
-/// Update session snapshot from Claude Code input data.
+#[cfg(feature = "storage")]
+/// Collect storage statistics with the given storage settings.
 ///
 /// # Errors
 ///
-/// Returns an error when snapshot persistence fails or when storage manager
-/// operations encounter I/O errors.
-pub async fn update_session_snapshot(input_data: &serde_json::Value) -> Result<()> {
-    let payload = input_data.clone();
-    task::spawn_blocking(move || {
-        let manager = StorageManager::new()?;
-        manager.update_snapshot_from_value(&payload)?;
-        Ok::<(), anyhow::Error>(())
+/// Returns an error if a repaired snapshot cannot be written back.
+pub async fn storage_stats(settings: &SettingsConfig, repair: bool) -> Result<StorageStats> {
+    let config = convert_settings(settings);
+    task::spawn_blocking(move || StorageManager::storage_stats(&config, repair)).await?
+}
+
+/// Build a usage report from snapshots or directly from Claude Code transcripts.
+///
+/// # Errors
+///
+/// Returns an error if the blocking scan task fails, or when snapshots or
+/// metrics are requested in a build without the `storage` feature.
+pub async fn usage_report<S: BuildHasher + Sync>(
+    settings: &crate::config::StorageConfig,
+    providers: &HashMap<String, ModelProviderConfig, S>,
+    source: usage_report::UsageSource,
+) -> Result<usage_report::UsageReport> {
+    #[cfg(feature = "storage")]
+    let config = convert_settings(settings);
+    #[cfg(not(feature = "storage"))]
+    let _ = settings;
+    let providers: HashMap<String, ModelProviderConfig> = providers
+        .iter()
+        .map(|(name, provider)| (name.clone(), provider.clone()))
+        .collect();
+    task::spawn_blocking(move || match source {
+        #[cfg(feature = "storage")]
+        usage_report::UsageSource::Snapshots => Ok(usage_report::report_from_snapshots(
+            &StorageManager::all_snapshot_files(&config),
+        )),
+        #[cfg(feature = "storage")]
+        usage_report::UsageSource::Metrics => Ok(usage_report::report_from_metrics(
+            &StorageManager::all_metrics_files(&config),
+        )),
+        #[cfg(not(feature = "storage"))]
+        usage_report::UsageSource::Snapshots | usage_report::UsageSource::Metrics => {
+            anyhow::bail!("built without the `storage` feature; use the transcripts source")
+        }
+        usage_report::UsageSource::Transcripts => Ok(usage_report::report_from_transcripts(
+            &claude_projects_dir(),
+            &providers,
+            &pricing::PricingCatalog::load(),
+        )),
     })
-    .await??;
-    Ok(())
+    .await?
 }
 
+#[cfg(feature = "storage")]
 /// Get session cost display (single session mode)
 /// Retrieve cost metrics for a given session.
 ///
@@ -155,6 +248,7 @@ pub async fn get_session_cost_display(session_id: &str) -> Result<f64> {
     Ok(snapshot.map_or(0.0, |snap| snap.history.cost.total.total_cost_usd))
 }
 
+#[cfg(feature = "storage")]
 /// Get conversation cost display (conversation mode)
 /// Retrieve conversation-level cost metrics.
 ///
@@ -166,6 +260,56 @@ pub async fn get_conversation_cost_display(session_id: &str) -> Result<f64> {
     get_session_cost_display(session_id).await
 }
 
+#[cfg(feature = "storage")]
+/// Retrieve the aggregated history of a session.
+///
+/// # Errors
+///
+/// Returns an error when snapshot data cannot be loaded or parsed from disk.
+pub async fn get_session_history(session_id: &str) -> Result<Option<SessionHistory>> {
+    StorageContext::global().session_history(session_id).await
+}
+
+#[cfg(feature = "storage")]
+/// Usage across all projects since `since`, for quotas.
+///
+/// # Errors
+///
+/// Returns an error if the blocking scan task fails.
+pub async fn usage_since(
+    since: chrono::DateTime<chrono::Utc>,
+    include_tokens: bool,
+) -> Result<usage_report::PeriodUsage> {
+    StorageContext::global()
+        .usage_since(since, include_tokens)
+        .await
+}
+
+#[cfg(feature = "storage")]
+/// Cost burn rate baseline of all stored sessions except `exclude_session`.
+///
+/// # Errors
+///
+/// Returns an error if the blocking scan task fails.
+pub async fn burn_rate_baseline(
+    exclude_session: &str,
+) -> Result<Option<usage_report::BurnRateBaseline>> {
+    StorageContext::global()
+        .burn_rate_baseline(exclude_session)
+        .await
+}
+
+#[cfg(feature = "storage")]
+/// Sessions of the current project updated within `window`, oldest first.
+///
+/// # Errors
+///
+/// Returns an error if the storage directories cannot be created.
+pub async fn get_active_sessions(window: std::time::Duration) -> Result<Vec<ActiveSession>> {
+    StorageContext::global().active_sessions(window).await
+}
+
+#[cfg(feature = "storage")]
 /// Retrieve cached token usage for a session.
 /// Retrieve token history for a given session.
 ///
@@ -182,3 +326,120 @@ pub async fn get_session_tokens(session_id: &str) -> Result<Option<TokenHistory>
 
     Ok(snapshot.and_then(|snap| snap.history.tokens))
 }
+
+#[cfg(feature = "storage")]
+/// Retrieve the persisted consecutive error streak for a session.
+///
+/// # Errors
+///
+/// Returns an error when snapshot data cannot be loaded or parsed from disk.
+pub async fn get_session_error_streak(session_id: &str) -> Result<Option<ErrorStreak>> {
+    let session_id = session_id.to_string();
+    let snapshot = task::spawn_blocking(move || {
+        let manager = StorageManager::new()?;
+        manager.get_snapshot(&session_id)
+    })
+    .await??;
+
+    Ok(snapshot.map(|snap| snap.history.errors))
+}
+
+#[cfg(feature = "storage")]
+/// Whether the critical context bell already rang for a session.
+///
+/// # Errors
+///
+/// Returns an error when snapshot data cannot be loaded or parsed from disk.
+pub async fn get_session_critical_alert(session_id: &str) -> Result<bool> {
+    let session_id = session_id.to_string();
+    let snapshot = task::spawn_blocking(move || {
+        let manager = StorageManager::new()?;
+        manager.get_snapshot(&session_id)
+    })
+    .await??;
+
+    Ok(snapshot.is_some_and(|snap| snap.history.critical_alerted))
+}
+
+#[cfg(feature = "storage")]
+/// Record whether the critical context bell rang for a session.
+///
+/// # Errors
+///
+/// Returns an error when the snapshot cannot be loaded or written.
+pub async fn update_session_critical_alert(session_id: &str, alerted: bool) -> Result<()> {
+    let session_id = session_id.to_string();
+    task::spawn_blocking(move || {
+        let manager = StorageManager::new()?;
+        manager.update_critical_alert(&session_id, alerted)
+    })
+    .await??;
+    Ok(())
+}
+
+#[cfg(feature = "storage")]
+/// Model switch tracking state of a session.
+///
+/// # Errors
+///
+/// Returns an error when snapshot data cannot be loaded or parsed from disk.
+pub async fn get_session_model_switch(session_id: &str) -> Result<ModelSwitchState> {
+    let session_id = session_id.to_string();
+    let snapshot = task::spawn_blocking(move || {
+        let manager = StorageManager::new()?;
+        manager.get_snapshot(&session_id)
+    })
+    .await??;
+
+    Ok(snapshot
+        .map(|snap| snap.history.model_switch)
+        .unwrap_or_default())
+}
+
+#[cfg(feature = "storage")]
+/// Persist the model switch tracking state of a session.
+///
+/// # Errors
+///
+/// Returns an error when the snapshot cannot be loaded or written.
+pub async fn update_session_model_switch(session_id: &str, state: ModelSwitchState) -> Result<()> {
+    let session_id = session_id.to_string();
+    task::spawn_blocking(move || {
+        let manager = StorageManager::new()?;
+        manager.update_model_switch(&session_id, &state)
+    })
+    .await??;
+    Ok(())
+}
+
+#[cfg(feature = "storage")]
+/// Persist the event hook state of a session.
+///
+/// # Errors
+///
+/// Returns an error when the snapshot cannot be loaded or written.
+pub async fn update_session_hook_state(session_id: &str, state: HookState) -> Result<()> {
+    let session_id = session_id.to_string();
+    task::spawn_blocking(move || {
+        let manager = StorageManager::new()?;
+        manager.update_hook_state(&session_id, &state)
+    })
+    .await??;
+    Ok(())
+}
+
+#[cfg(feature = "storage")]
+/// Persist the consecutive error streak for a session.
+///
+/// # Errors
+///
+/// Returns an error when the snapshot cannot be loaded or written.
+pub async fn update_session_error_streak(session_id: &str, streak: ErrorStreak) -> Result<()> {
+    let session_id = session_id.to_string();
+    task::spawn_blocking(move || {
+        let manager = StorageManager::new()?;
+        manager.update_error_streak(&session_id, &streak)
+    })
+    .await??;
+    Ok(())
+}
@@ -0,0 +1,261 @@
+//! Model price catalog
+//!
+//! 使用 `LiteLLM` 格式(`model_prices_and_context_window.json`)的模型价格表。
+//! `ccsp pricing update` 下载最新价格表并缓存到用户数据目录;从未更新过或缓存
+//! 损坏时回退到随程序打包的离线快照 `configs/pricing.json`。
+
+use std::collections::HashMap;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context, Result};
+use serde_json::Value;
+
+use crate::config::{Config, ModelPricingConfig};
+use crate::utils::provider_profiles::model_id_candidates;
+
+/// Offline snapshot shipped with the binary
+const BUNDLED_PRICING: &str = include_str!("../../configs/pricing.json");
+
+/// File name of the downloaded catalog under the user data dir
+const CACHE_FILE: &str = "pricing.json";
+
+/// Prices are stored per million tokens, like the provider pricing rules
+const UNIT_TOKENS: f64 = 1_000_000.0;
+
+/// Where a loaded catalog came from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CatalogSource {
+    Bundled,
+    Cached(PathBuf),
+}
+
+/// Model prices in USD, keyed by lowercase model name
+#[derive(Debug, Clone)]
+pub struct PricingCatalog {
+    prices: HashMap<String, ModelPricingConfig>,
+    pub source: CatalogSource,
+}
+
+impl PricingCatalog {
+    /// Catalog bundled with the binary.
+    #[must_use]
+    pub fn bundled() -> Self {
+        Self {
+            prices: parse_litellm(BUNDLED_PRICING).unwrap_or_default(),
+            source: CatalogSource::Bundled,
+        }
+    }
+
+    /// Downloaded catalog if present and valid, otherwise the bundled one.
+    #[must_use]
+    pub fn load() -> Self {
+        Self::load_from(&cache_path())
+    }
+
+    /// Like [`Self::load`], reading the cache from `path`.
+    #[must_use]
+    pub fn load_from(path: &Path) -> Self {
+        fs::read_to_string(path)
+            .ok()
+            .and_then(|content| parse_litellm(&content).ok())
+            .filter(|prices| !prices.is_empty())
+            .map_or_else(Self::bundled, |prices| Self {
+                prices,
+                source: CatalogSource::Cached(path.to_path_buf()),
+            })
+    }
+
+    /// Price of `model`, tolerating case, `provider/` prefixes and `[1m]` suffixes.
+    #[must_use]
+    pub fn lookup(&self, model: &str) -> Option<&ModelPricingConfig> {
+        model_id_candidates(model)
+            .iter()
+            .find_map(|candidate| self.prices.get(candidate))
+    }
+
+    /// Number of priced models
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.prices.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.prices.is_empty()
+    }
+}
+
+/// Location of the downloaded catalog
+#[must_use]
+pub fn cache_path() -> PathBuf {
+    super::user_data_dir().join(CACHE_FILE)
+}
+
+/// Parse a `LiteLLM` price list into per-million-token prices.
+///
+/// Entries without both input and output prices (embeddings, image models,
+/// the `sample_spec` placeholder) are skipped.
+///
+/// # Errors
+///
+/// Returns an error if `content` is not a JSON object.
+pub fn parse_litellm(content: &str) -> Result<HashMap<String, ModelPricingConfig>> {
+    let value: Value = serde_json::from_str(content).context("价格表不是合法的 JSON")?;
+    let Some(entries) = value.as_object() else {
+        bail!("价格表格式错误: 顶层应为对象");
+    };
+
+    let per_million = |entry: &Value, key: &str| {
+        entry
+            .get(key)
+            .and_then(Value::as_f64)
+            .map(|cost| cost * UNIT_TOKENS)
+    };
+
+    Ok(entries
+        .iter()
+        .filter_map(|(model, entry)| {
+            let pricing = ModelPricingConfig {
+                unit_tokens: UNIT_TOKENS,
+                input: per_million(entry, "input_cost_per_token")?,
+                output: per_million(entry, "output_cost_per_token")?,
+                cache_read: per_million(entry, "cache_read_input_token_cost"),
+                cache_write: per_million(entry, "cache_creation_input_token_cost"),
+            };
+            Some((model.to_ascii_lowercase(), pricing))
+        })
+        .collect())
+}
+
+/// Download the configured price list and replace the cached catalog.
+///
+/// Returns the number of priced models and the cache path.
+///
+/// # Errors
+///
+/// Returns an error when network access is disabled (`offline = true`), the
+/// download fails, the response holds no prices, or the cache cannot be written.
+pub fn update(config: &Config) -> Result<(usize, PathBuf)> {
+    if config.offline {
+        bail!("已启用离线模式(offline = true),不会下载价格表");
+    }
+
+    let content = download(&config.pricing.source_url, config.pricing.timeout_ms)?;
+    let prices = parse_litellm(&content)?;
+    if prices.is_empty() {
+        bail!("价格表中没有任何可用的模型价格");
+    }
+
+    let path = cache_path();
+    write_cache(&path, &content)?;
+    Ok((prices.len(), path))
+}
+
+#[cfg(feature = "http")]
+fn download(url: &str, timeout_ms: u64) -> Result<String> {
+    let response = ureq::get(url)
+        .timeout(std::time::Duration::from_millis(timeout_ms))
+        .set("User-Agent", "claude-code-statusline-pro")
+        .call()
+        .with_context(|| format!("下载价格表失败: {url}"))?;
+    response.into_string().context("读取价格表响应失败")
+}
+
+#[cfg(not(feature = "http"))]
+fn download(_url: &str, _timeout_ms: u64) -> Result<String> {
+    bail!("当前构建未启用 http 特性,无法下载价格表")
+}
+
+fn write_cache(path: &Path, content: &str) -> Result<()> {
+    let dir = path.parent().unwrap_or_else(|| Path::new("."));
+    fs::create_dir_all(dir)
+        .with_context(|| format!("Failed to create directory: {}", dir.display()))?;
+
+    let mut file = tempfile::NamedTempFile::new_in(dir)?;
+    file.write_all(content.as_bytes())?;
+    file.as_file().sync_all()?;
+    file.persist(path)
+        .with_context(|| format!("Failed to write pricing cache: {}", path.display()))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn litellm_entries_convert_to_per_million_prices() -> Result<()> {
+        let prices = parse_litellm(
+            r#"{
+                "sample_spec": {"input_cost_per_token": "see docs"},
+                "Claude-Test": {
+                    "input_cost_per_token": 3e-6,
+                    "output_cost_per_token": 1.5e-5,
+                    "cache_read_input_token_cost": 3e-7
+                },
+                "text-embedding": {"input_cost_per_token": 1e-7}
+            }"#,
+        )?;
+
+        assert_eq!(prices.len(), 1);
+        let pricing = &prices["claude-test"];
+        assert!((pricing.input - 3.0).abs() < 1e-9);
+        assert!((pricing.output - 15.0).abs() < 1e-9);
+        assert!(pricing
+            .cache_read
+            .is_some_and(|price| (price - 0.3).abs() < 1e-9));
+        assert!(pricing.cache_write.is_none());
+        assert!(parse_litellm("[]").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn bundled_catalog_prices_claude_models() {
+        let catalog = PricingCatalog::bundled();
+        assert!(!catalog.is_empty());
+        assert!(catalog.lookup("claude-sonnet-4-20250514").is_some());
+        assert!(catalog.lookup("Claude-Sonnet-4-20250514[1m]").is_some());
+        assert!(catalog
+            .lookup("anthropic/claude-sonnet-4-20250514")
+            .is_some());
+        assert!(catalog.lookup("unknown-model").is_none());
+    }
+
+    #[test]
+    fn invalid_cache_falls_back_to_bundled_catalog() -> Result<()> {
+        let dir = tempdir()?;
+        let path = dir.path().join(CACHE_FILE);
+
+        assert_eq!(
+            PricingCatalog::load_from(&path).source,
+            CatalogSource::Bundled
+        );
+
+        fs::write(&path, "{ broken")?;
+        assert_eq!(
+            PricingCatalog::load_from(&path).source,
+            CatalogSource::Bundled
+        );
+
+        write_cache(
+            &path,
+            r#"{"my-model": {"input_cost_per_token": 1e-6, "output_cost_per_token": 2e-6}}"#,
+        )?;
+        let catalog = PricingCatalog::load_from(&path);
+        assert_eq!(catalog.source, CatalogSource::Cached(path));
+        assert_eq!(catalog.len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn update_is_refused_offline() {
+        let config = Config {
+            offline: true,
+            ..Config::default()
+        };
+        assert!(update(&config).is_err());
+    }
+}
@@ -0,0 +1,275 @@
+//! Project ID
+//!
+//! 项目 ID 是 `~/.claude/projects/<id>/` 下的目录名,配置与存储共用,必须与
+//! TypeScript 版本逐字节一致。路径到 ID 的转换分三步:
+//!
+//! 1. 解析:按 [`SymlinkPolicy`] 决定是否 canonicalize(解析符号链接与 `..`);
+//!    路径不存在时保持原样
+//! 2. 去掉 Windows 的 verbatim 前缀 `\\?\` 及其后的反斜杠:`\\?\C:\x` → `C:\x`,
+//!    `\\?\UNC\srv\share` → `UNC\srv\share`
+//! 3. 编码:`C:\` 或 `C:/` 开头时写成 `C--`(盘符大小写不变),其后只替换同一种
+//!    分隔符;否则分隔符与 `:` 都替换为 `-`。连续的 `-` 合并,末尾的 `-` 去掉
+//!    (`/a/b/` 与 `/a/b` 相同)
+//!
+//! 这些规则(包括盘符不统一大小写、UNC 前缀保留为 `UNC-`)与 TypeScript 版本
+//! 逐字节一致,`tests/fixtures/project_ids.json` 记录了两边共同的结果;已有用户的
+//! 目录名由它决定,不能单方面"修正"。
+//!
+//! ```text
+//! /Users/name/project          -> -Users-name-project
+//! C:\Users\name\project        -> C--Users-name-project
+//! \\server\share\project       -> -server-share-project
+//! \\?\UNC\server\share\project -> UNC-server-share-project
+//! ```
+
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+/// Verbatim prefixes stripped before encoding; the doubled form comes from
+/// paths that went through JSON escaping once too often
+const VERBATIM_PREFIXES: [&str; 2] = [r"\\\\?\", r"\\?\"];
+
+static TRANSCRIPT_PROJECT: LazyLock<Option<Regex>> =
+    LazyLock::new(|| Regex::new(r"[/\\]projects[/\\]([^/\\]+)[/\\]").ok());
+
+/// Whether symbolic links in a project path are resolved before hashing
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SymlinkPolicy {
+    /// Canonicalize the path, so every link to a project shares its ID
+    #[default]
+    Resolve,
+    /// Keep links as written; only relative paths are made absolute
+    Preserve,
+}
+
+/// Directory name identifying a project under `~/.claude/projects`
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct ProjectId(String);
+
+impl ProjectId {
+    /// ID of the project at `path`, resolving symbolic links.
+    ///
+    /// An empty path stands for the current directory.
+    #[must_use]
+    pub fn from_path(path: &str) -> Self {
+        Self::from_path_with(path, SymlinkPolicy::Resolve)
+    }
+
+    /// ID of the project at `path` under the given symlink policy
+    #[must_use]
+    pub fn from_path_with(path: &str, policy: SymlinkPolicy) -> Self {
+        let resolved = resolve(path, policy);
+        Self(encode(strip_verbatim_prefix(&resolved)))
+    }
+
+    /// Project folder of a Claude Code transcript
+    /// (`~/.claude/projects/<id>/<session>.jsonl`)
+    #[must_use]
+    pub fn from_transcript(transcript_path: &str) -> Option<Self> {
+        TRANSCRIPT_PROJECT
+            .as_ref()?
+            .captures(transcript_path)?
+            .get(1)
+            .map(|id| Self(id.as_str().to_string()))
+    }
+
+    /// An already encoded ID, e.g. from `--project-id` or a directory listing
+    #[must_use]
+    pub fn from_raw(id: impl Into<String>) -> Self {
+        Self(id.into())
+    }
+
+    #[must_use]
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    #[must_use]
+    pub fn into_string(self) -> String {
+        self.0
+    }
+}
+
+impl fmt::Display for ProjectId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl AsRef<str> for ProjectId {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl AsRef<Path> for ProjectId {
+    fn as_ref(&self) -> &Path {
+        Path::new(&self.0)
+    }
+}
+
+impl From<ProjectId> for String {
+    fn from(id: ProjectId) -> Self {
+        id.0
+    }
+}
+
+impl PartialEq<str> for ProjectId {
+    fn eq(&self, other: &str) -> bool {
+        self.0 == other
+    }
+}
+
+impl PartialEq<&str> for ProjectId {
+    fn eq(&self, other: &&str) -> bool {
+        self.0 == *other
+    }
+}
+
+fn resolve(path: &str, policy: SymlinkPolicy) -> String {
+    let path = if path.is_empty() { "." } else { path };
+    let resolved: Option<PathBuf> = match policy {
+        SymlinkPolicy::Resolve => Path::new(path).canonicalize().ok(),
+        SymlinkPolicy::Preserve => std::path::absolute(path).ok(),
+    };
+    resolved.map_or_else(
+        || path.to_string(),
+        |resolved| resolved.to_string_lossy().into_owned(),
+    )
+}
+
+/// `\\?\C:\x` → `C:\x`, `\\?\UNC\srv\share` → `UNC\srv\share`
+fn strip_verbatim_prefix(path: &str) -> &str {
+    VERBATIM_PREFIXES
+        .iter()
+        .find_map(|prefix| path.strip_prefix(prefix))
+        .map_or(path, |rest| rest.trim_start_matches('\\'))
+}
+
+/// `C:\x` or `C:/x` split into `C--` and the rest with its separator
+fn split_drive(path: &str) -> Option<(&str, &str, char)> {
+    let bytes = path.as_bytes();
+    if bytes.len() < 3 || !bytes[0].is_ascii_alphabetic() || bytes[1] != b':' {
+        return None;
+    }
+    let separator = char::from(bytes[2]);
+    matches!(separator, '\\' | '/').then(|| (&path[..1], &path[3..], separator))
+}
+
+fn encode(path: &str) -> String {
+    let Some((drive, rest, separator)) = split_drive(path) else {
+        let encoded = path.replace(['\\', '/', ':'], "-");
+        return collapse_dashes(encoded.trim_end_matches('-'));
+    };
+    // 只替换盘符后的那一种分隔符,`C:\a/b` 中的 `/` 原样保留
+    let encoded = format!("{drive}--{}", rest.replace(separator, "-"));
+    let encoded = encoded.trim_end_matches('-');
+    if encoded.len() < 3 {
+        return collapse_dashes(encoded);
+    }
+    let (prefix, rest) = encoded.split_at(3);
+    let rest = collapse_dashes(rest);
+    format!("{prefix}{}", rest.trim_start_matches('-'))
+        .trim_end_matches('-')
+        .to_string()
+}
+
+fn collapse_dashes(input: &str) -> String {
+    let mut collapsed = String::with_capacity(input.len());
+    for ch in input.chars() {
+        if ch != '-' || !collapsed.ends_with('-') {
+            collapsed.push(ch);
+        }
+    }
+    collapsed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn id(path: &str) -> String {
+        ProjectId::from_path(path).into_string()
+    }
+
+    #[test]
+    fn unix_paths_normalize_separators_and_trailing_slashes() {
+        assert_eq!(id("/Users/example//project"), "-Users-example-project");
+        assert_eq!(id("/Users/example/project/"), "-Users-example-project");
+        assert_eq!(
+            id("/Users/example/my.project_v2"),
+            "-Users-example-my.project_v2"
+        );
+        // 根目录编码为空 ID,与 TypeScript 版本相同
+        assert_eq!(id("/"), "");
+    }
+
+    #[test]
+    fn drive_letters_keep_their_case() {
+        assert_eq!(id(r"E:\Users\example\project"), "E--Users-example-project");
+        assert_eq!(id(r"e:\Users\example\project\"), "e--Users-example-project");
+        assert_eq!(id("c:/Users/example/project"), "c--Users-example-project");
+        assert_eq!(id(r"C:\Users/mixed\seps"), "C--Users/mixed-seps");
+        assert_eq!(id(r"C:\"), "C");
+        assert_eq!(
+            id(r"E:\\Users\\example\\project"),
+            "E--Users-example-project"
+        );
+    }
+
+    #[test]
+    fn verbatim_prefixes_are_stripped() {
+        assert_eq!(
+            id(r"\\?\C:\Users\example\project"),
+            "C--Users-example-project"
+        );
+        assert_eq!(
+            id(r"\\\\?\\C:\\Users\\example\\project"),
+            "C--Users-example-project"
+        );
+        assert_eq!(id(r"\\server\share\project"), "-server-share-project");
+        assert_eq!(
+            id(r"\\?\UNC\server\share\project"),
+            "UNC-server-share-project"
+        );
+    }
+
+    #[test]
+    fn transcript_paths_yield_their_project_folder() {
+        let transcript = "/home/u/.claude/projects/-home-u-app/abc.jsonl";
+        assert_eq!(
+            ProjectId::from_transcript(transcript),
+            Some(ProjectId::from_raw("-home-u-app"))
+        );
+        let windows = r"C:\Users\u\.claude\projects\C--Users-u-app\abc.jsonl";
+        assert_eq!(
+            ProjectId::from_transcript(windows)
+                .as_ref()
+                .map(ProjectId::as_str),
+            Some("C--Users-u-app")
+        );
+        assert_eq!(ProjectId::from_transcript("/tmp/abc.jsonl"), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn symlink_policy_decides_which_path_is_hashed() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let target = dir.path().join("real");
+        let link = dir.path().join("link");
+        std::fs::create_dir(&target)?;
+        std::os::unix::fs::symlink(&target, &link)?;
+        let link = link.to_string_lossy();
+
+        let resolved = ProjectId::from_path_with(&link, SymlinkPolicy::Resolve);
+        let preserved = ProjectId::from_path_with(&link, SymlinkPolicy::Preserve);
+        assert!(resolved.as_str().ends_with("-real"), "{resolved}");
+        assert!(preserved.as_str().ends_with("-link"), "{preserved}");
+        Ok(())
+    }
+}
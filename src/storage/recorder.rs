@@ -0,0 +1,177 @@
+//! Raw stdin payload recorder
+//!
+//! `ccsp record` 把 Claude Code 实际发送的 stdin JSON 逐行追加到
+//! `~/.claude/statusline-pro/recordings/<date>.jsonl`,`ccsp replay` 再把这些
+//! 记录喂回生成器,用来精确复现渲染问题,也方便附在 issue 里。
+
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// A single recorded stdin payload
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecordedPayload {
+    /// RFC 3339 timestamp of when the payload was captured
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub recorded_at: Option<String>,
+    /// Parsed JSON payload (absent when stdin was not valid JSON)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub payload: Option<Value>,
+    /// Raw stdin text, kept only when it could not be parsed as JSON
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub raw: Option<String>,
+}
+
+impl RecordedPayload {
+    /// Build a record from raw stdin text, timestamped now
+    #[must_use]
+    pub fn capture(raw: &str) -> Self {
+        let recorded_at = Some(Utc::now().to_rfc3339());
+        match serde_json::from_str::<Value>(raw) {
+            Ok(payload) => Self {
+                recorded_at,
+                payload: Some(payload),
+                raw: None,
+            },
+            Err(_) => Self {
+                recorded_at,
+                payload: None,
+                raw: Some(raw.to_string()),
+            },
+        }
+    }
+}
+
+/// Directory holding recordings (`STATUSLINE_STORAGE_PATH` aware)
+#[must_use]
+pub fn recordings_dir() -> PathBuf {
+    super::user_data_dir().join("recordings")
+}
+
+/// Default recording file for today
+#[must_use]
+pub fn default_recording_path() -> PathBuf {
+    recordings_dir().join(format!("{}.jsonl", Utc::now().format("%Y-%m-%d")))
+}
+
+/// Append a record to `target` (or today's default file), returning the path written.
+///
+/// # Errors
+///
+/// Returns an error if the recording directory or file cannot be written.
+pub fn append_recording(record: &RecordedPayload, target: Option<&Path>) -> Result<PathBuf> {
+    let path = target.map_or_else(default_recording_path, Path::to_path_buf);
+    if let Some(parent) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create directory: {}", parent.display()))?;
+    }
+
+    let mut line = serde_json::to_string(record)?;
+    line.push('\n');
+
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .with_context(|| format!("Failed to open recording file: {}", path.display()))?;
+    file.write_all(line.as_bytes())
+        .with_context(|| format!("Failed to append recording: {}", path.display()))?;
+
+    Ok(path)
+}
+
+/// Read every record from a JSONL recording.
+///
+/// Lines that are bare payloads (no `payload` / `raw` wrapper) are accepted too,
+/// so hand-written captures can be replayed. Blank lines are skipped.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be read or a line is not valid JSON.
+pub fn read_recording(path: &Path) -> Result<Vec<RecordedPayload>> {
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read recording: {}", path.display()))?;
+
+    content
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(idx, line)| {
+            let value: Value = serde_json::from_str(line)
+                .with_context(|| format!("Invalid JSON on line {}", idx + 1))?;
+            Ok(parse_record(value))
+        })
+        .collect()
+}
+
+fn parse_record(value: Value) -> RecordedPayload {
+    let is_wrapped = value
+        .as_object()
+        .is_some_and(|obj| obj.contains_key("payload") || obj.contains_key("raw"));
+
+    if is_wrapped {
+        if let Ok(record) = serde_json::from_value::<RecordedPayload>(value.clone()) {
+            return record;
+        }
+    }
+
+    RecordedPayload {
+        recorded_at: None,
+        payload: Some(value),
+        raw: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use tempfile::tempdir;
+
+    #[test]
+    fn capture_keeps_raw_text_for_invalid_json() {
+        let record = RecordedPayload::capture("not json");
+        assert!(record.payload.is_none());
+        assert_eq!(record.raw.as_deref(), Some("not json"));
+        assert!(record.recorded_at.is_some());
+    }
+
+    #[test]
+    fn append_and_read_round_trip() -> Result<()> {
+        let dir = tempdir()?;
+        let path = dir.path().join("nested").join("capture.jsonl");
+
+        append_recording(
+            &RecordedPayload::capture(r#"{"session_id":"a"}"#),
+            Some(&path),
+        )?;
+        append_recording(&RecordedPayload::capture("oops"), Some(&path))?;
+
+        let records = read_recording(&path)?;
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].payload, Some(json!({"session_id": "a"})));
+        assert_eq!(records[1].raw.as_deref(), Some("oops"));
+        Ok(())
+    }
+
+    #[test]
+    fn bare_payload_lines_are_accepted() -> Result<()> {
+        let dir = tempdir()?;
+        let path = dir.path().join("manual.jsonl");
+        fs::write(&path, "{\"session_id\":\"b\",\"cwd\":\"/tmp\"}\n\n")?;
+
+        let records = read_recording(&path)?;
+        assert_eq!(records.len(), 1);
+        assert_eq!(
+            records[0].payload,
+            Some(json!({"session_id": "b", "cwd": "/tmp"}))
+        );
+        assert!(records[0].recorded_at.is_none());
+        Ok(())
+    }
+}
@@ -2,11 +2,14 @@
 //!
 //! Defines the snapshot structures that persist Claude Code session data.
 
+use std::collections::{BTreeMap, BTreeSet};
+
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
 /// Storage configuration mirroring the TypeScript settings
 #[derive(Debug, Clone)]
+#[allow(clippy::struct_excessive_bools)]
 pub struct StorageConfig {
     /// Enable conversation-level cost tracking
     pub enable_conversation_tracking: bool,
@@ -18,6 +21,12 @@ pub struct StorageConfig {
     pub session_expiry_days: Option<u32>,
     /// Whether cleanup should run on startup
     pub enable_startup_cleanup: bool,
+    /// Keys stripped from the stored `latest` payload
+    pub redact_keys: Vec<String>,
+    /// Whether `latest` is stored at all
+    pub store_latest: bool,
+    /// Entries kept per session history log; 0 disables the log
+    pub history_max_entries: usize,
 }
 
 impl Default for StorageConfig {
@@ -28,6 +37,9 @@ impl Default for StorageConfig {
             storage_path: None,
             session_expiry_days: Some(30),
             enable_startup_cleanup: true,
+            redact_keys: crate::config::schema::default_redact_keys(),
+            store_latest: true,
+            history_max_entries: 0,
         }
     }
 }
@@ -68,12 +80,61 @@ impl SessionSnapshot {
                 project_path: None,
                 created_at: Some(Utc::now()),
                 last_update_time: Some(Utc::now()),
+                resumed_from: None,
+                metrics_offset: 0,
             },
             latest: serde_json::Value::Null,
             history: SessionHistory::default(),
             transcript_state: TranscriptState::default(),
         }
     }
+
+    /// The counters updated on every render
+    #[must_use]
+    pub fn counters(&self) -> SessionCounters {
+        SessionCounters {
+            updated_at: self.meta.last_update_time,
+            cost: self.history.cost.clone(),
+            tokens: self.history.tokens.clone(),
+            model_usage: self.history.model_usage.clone(),
+            transcript_state: self.transcript_state.clone(),
+        }
+    }
+
+    /// Replace the counters with a checkpoint from the metrics log
+    pub fn apply_counters(&mut self, counters: SessionCounters) {
+        self.meta.last_update_time = counters.updated_at.or(self.meta.last_update_time);
+        self.history.cost = counters.cost;
+        self.history.tokens = counters.tokens;
+        self.history.model_usage = counters.model_usage;
+        self.transcript_state = counters.transcript_state;
+    }
+}
+
+/// Counters of a snapshot that change on every render: cost, tokens, models
+/// and the transcript position.
+///
+/// 快照只在状态变化或每隔一段时间整体重写;两次重写之间,这些计数以检查点的
+/// 形式追加到指标日志,读取快照时用日志中最新的检查点补齐。
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
+pub struct SessionCounters {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub cost: CostHistory,
+    #[serde(default)]
+    pub tokens: Option<TokenHistory>,
+    #[serde(default)]
+    pub model_usage: Vec<ModelUsageEntry>,
+    #[serde(default)]
+    pub transcript_state: TranscriptState,
+}
+
+/// A session of the current project whose snapshot was updated recently.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ActiveSession {
+    pub session_id: String,
+    pub created_at: Option<DateTime<Utc>>,
 }
 
 /// Metadata describing a stored session.
@@ -86,6 +147,13 @@ pub struct SessionMeta {
     pub created_at: Option<DateTime<Utc>>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub last_update_time: Option<DateTime<Utc>>,
+    /// Session this one was resumed from; its history was carried over
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resumed_from: Option<String>,
+    /// Bytes of the session's metrics log already reflected in this snapshot;
+    /// counter checkpoints past it are replayed on load
+    #[serde(default)]
+    pub metrics_offset: u64,
 }
 
 impl Default for SessionMeta {
@@ -95,6 +163,8 @@ impl Default for SessionMeta {
             project_path: None,
             created_at: Some(Utc::now()),
             last_update_time: Some(Utc::now()),
+            resumed_from: None,
+            metrics_offset: 0,
         }
     }
 }
@@ -108,10 +178,54 @@ pub struct SessionHistory {
     pub tokens: Option<TokenHistory>,
     #[serde(default)]
     pub model_usage: Vec<ModelUsageEntry>,
+    #[serde(default)]
+    pub errors: ErrorStreak,
+    /// The critical context bell already rang; re-armed once usage drops below critical
+    #[serde(default)]
+    pub critical_alerted: bool,
+    #[serde(default)]
+    pub model_switch: ModelSwitchState,
+    #[serde(default)]
+    pub hooks: HookState,
+}
+
+/// Session event hooks: which conditions currently hold and when each event
+/// last ran its command.
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq)]
+pub struct HookState {
+    /// Events whose condition held at the last render
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub active: BTreeSet<String>,
+    /// Last time each event fired, for debouncing
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub last_fired: BTreeMap<String, DateTime<Utc>>,
+}
+
+/// Last model seen in a session and the pending switch notice, if any.
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq)]
+pub struct ModelSwitchState {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_model_id: Option<String>,
+    /// Model the session most recently switched away from
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub previous_model_id: Option<String>,
+    /// Renders left to show the switch notice
+    #[serde(default)]
+    pub remaining_renders: u32,
+}
+
+/// Consecutive assistant errors observed in the transcript.
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq)]
+pub struct ErrorStreak {
+    #[serde(default)]
+    pub count: u32,
+    /// UUID of the most recent error entry already counted
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_error_uuid: Option<String>,
 }
 
 /// Aggregated cost data broken into buckets.
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
 pub struct CostHistory {
     #[serde(default)]
     pub current: CostMetrics,
@@ -163,7 +277,7 @@ impl CostHistory {
 }
 
 /// Cost metrics captured from Claude Code.
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
 pub struct CostMetrics {
     #[serde(default)]
     pub total_cost_usd: f64,
@@ -206,7 +320,7 @@ impl CostMetrics {
 }
 
 /// Token usage extracted from transcript updates.
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
 pub struct TokenHistory {
     #[serde(default)]
     pub input: u64,
@@ -225,7 +339,7 @@ pub struct TokenHistory {
 }
 
 /// Track which models have been observed during this session.
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
 pub struct ModelUsageEntry {
     pub id: String,
     #[serde(default, skip_serializing_if = "Option::is_none")]
@@ -235,7 +349,7 @@ pub struct ModelUsageEntry {
 }
 
 /// Internal transcript processing state.
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
 pub struct TranscriptState {
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub transcript_path: Option<String>,
@@ -0,0 +1,769 @@
+//! Usage reports
+//!
+//! 汇总历史 token / 成本用量,三种数据源:
+//! - `snapshots`: statusline-pro 自己保存的会话快照(只覆盖安装之后的会话)
+//! - `metrics`: 会话的增量指标日志,按事件时间累加 token 与成本
+//! - `transcripts`: 直接扫描 Claude Code 的 `projects/**/*.jsonl`,
+//!   包含安装前的历史。同一条响应会在恢复 / 分叉的 transcript 中重复出现,
+//!   按 `message.id` + `requestId` 去重。
+//!
+//! `usage_since` 按同样的两种数据源统计某个时间点以来的用量,供 `quota` 组件使用;
+//! `burn_rate_baseline` 统计历史会话的成本速率,供 `usage` 组件识别异常会话。
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use anyhow::{bail, Result};
+use chrono::{DateTime, Local, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+#[cfg(feature = "storage")]
+use super::manager::read_current_snapshot;
+#[cfg(feature = "storage")]
+use super::metrics::{self, MetricKind};
+use super::pricing::PricingCatalog;
+use super::types::CostMetrics;
+#[cfg(feature = "storage")]
+use super::types::SessionSnapshot;
+use crate::config::ModelProviderConfig;
+use crate::utils::provider_profiles::{
+    provider_pricing, provider_pricing_currency, DEFAULT_CURRENCY,
+};
+
+/// Where usage reports read their data from
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum UsageSource {
+    /// statusline-pro session snapshots
+    #[default]
+    Snapshots,
+    /// Per-session incremental metrics logs
+    Metrics,
+    /// Claude Code transcript files
+    Transcripts,
+}
+
+impl FromStr for UsageSource {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> Result<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "snapshots" | "snapshot" => Ok(Self::Snapshots),
+            "metrics" => Ok(Self::Metrics),
+            "transcripts" | "transcript" | "jsonl" => Ok(Self::Transcripts),
+            other => bail!("未知的用量数据源: {other} (可选 snapshots / metrics / transcripts)"),
+        }
+    }
+}
+
+/// Aggregated token counts and cost
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct UsageTotals {
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cache_creation_tokens: u64,
+    pub cache_read_tokens: u64,
+    /// Cost in USD of the priced entries
+    pub cost_usd: f64,
+    /// Assistant messages (transcripts, metrics) or sessions (snapshots) counted
+    pub entries: u64,
+    /// Messages with tokens but no known price
+    pub unpriced: u64,
+}
+
+impl UsageTotals {
+    #[must_use]
+    pub const fn total_tokens(&self) -> u64 {
+        self.input_tokens + self.output_tokens + self.cache_creation_tokens + self.cache_read_tokens
+    }
+
+    fn add(&mut self, other: &Self) {
+        self.input_tokens += other.input_tokens;
+        self.output_tokens += other.output_tokens;
+        self.cache_creation_tokens += other.cache_creation_tokens;
+        self.cache_read_tokens += other.cache_read_tokens;
+        self.cost_usd += other.cost_usd;
+        self.entries += other.entries;
+        self.unpriced += other.unpriced;
+    }
+}
+
+/// Usage grouped by local date and by model
+#[derive(Debug, Clone, Default)]
+pub struct UsageReport {
+    pub source: UsageSource,
+    pub by_day: BTreeMap<NaiveDate, UsageTotals>,
+    /// Per-model totals; snapshots carry no per-model split, so this stays empty for them
+    pub by_model: BTreeMap<String, UsageTotals>,
+    pub total: UsageTotals,
+    /// Files read
+    pub files: usize,
+    /// Transcript entries skipped as duplicates of an already counted response
+    pub duplicates: u64,
+}
+
+impl UsageReport {
+    fn record(&mut self, day: Option<NaiveDate>, model: Option<&str>, usage: &UsageTotals) {
+        if let Some(day) = day {
+            self.by_day.entry(day).or_default().add(usage);
+        }
+        if let Some(model) = model {
+            self.by_model
+                .entry(model.to_string())
+                .or_default()
+                .add(usage);
+        }
+        self.total.add(usage);
+    }
+}
+
+/// Usage accumulated since a point in time, for weekly quotas
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct PeriodUsage {
+    /// Session time (`cost.total_duration_ms`) of sessions active in the period
+    pub session_hours: f64,
+    /// Tokens of the transcript responses in the period (input, output and cache)
+    pub tokens: u64,
+}
+
+/// Sessions shorter than this have too noisy a burn rate to count
+pub const MIN_BURN_RATE_DURATION_MS: u64 = 10 * 60 * 1000;
+
+/// Mean and spread of the cost burn rate (USD per hour) of stored sessions
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct BurnRateBaseline {
+    pub mean: f64,
+    /// Population standard deviation
+    pub std_dev: f64,
+    /// Sessions the baseline was computed from
+    pub sessions: usize,
+}
+
+impl BurnRateBaseline {
+    /// Baseline of the given burn rates, `None` when there are none
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn from_rates(rates: &[f64]) -> Option<Self> {
+        if rates.is_empty() {
+            return None;
+        }
+        let count = rates.len() as f64;
+        let mean = rates.iter().sum::<f64>() / count;
+        let variance = rates.iter().map(|rate| (rate - mean).powi(2)).sum::<f64>() / count;
+        Some(Self {
+            mean,
+            std_dev: variance.sqrt(),
+            sessions: rates.len(),
+        })
+    }
+
+    /// Standard deviations `rate` lies above the mean; `None` without spread
+    #[must_use]
+    pub fn z_score(&self, rate: f64) -> Option<f64> {
+        (self.std_dev > f64::EPSILON).then(|| (rate - self.mean) / self.std_dev)
+    }
+}
+
+/// Cost burn rate in USD per hour, `None` for sessions shorter than
+/// [`MIN_BURN_RATE_DURATION_MS`] or without cost.
+#[must_use]
+#[allow(clippy::cast_precision_loss)]
+pub fn burn_rate(cost_usd: f64, duration_ms: u64) -> Option<f64> {
+    (duration_ms >= MIN_BURN_RATE_DURATION_MS && cost_usd > 0.0)
+        .then(|| cost_usd / (duration_ms as f64 / 3_600_000.0))
+}
+
+/// Burn rate baseline of session cost totals
+#[must_use]
+pub fn burn_rate_baseline<'a>(
+    costs: impl IntoIterator<Item = &'a CostMetrics>,
+) -> Option<BurnRateBaseline> {
+    let rates: Vec<f64> = costs
+        .into_iter()
+        .filter_map(|cost| burn_rate(cost.total_cost_usd, cost.total_duration_ms))
+        .collect();
+    BurnRateBaseline::from_rates(&rates)
+}
+
+#[cfg(feature = "storage")]
+/// Burn rate baseline of the stored sessions other than `exclude_session`
+#[must_use]
+pub fn baseline_from_snapshots(
+    snapshot_files: &[PathBuf],
+    exclude_session: &str,
+) -> Option<BurnRateBaseline> {
+    let snapshots = load_snapshots(snapshot_files);
+    burn_rate_baseline(
+        latest_sessions(&snapshots)
+            .filter(|snapshot| snapshot.meta.session_id != exclude_session)
+            // 当前会话恢复自的旧会话也属于当前会话
+            .filter(|snapshot| snapshot.meta.resumed_from.as_deref() != Some(exclude_session))
+            .map(|snapshot| &snapshot.history.cost.total),
+    )
+}
+
+#[cfg(feature = "storage")]
+/// Aggregate the cost totals of the given session snapshots.
+///
+/// 快照里的 token 只是最近一条消息的用量,无法累加,因此只统计成本。
+/// 恢复的会话已把前一个会话的总成本并入自身,被恢复的旧快照不再重复计入。
+#[must_use]
+pub fn report_from_snapshots(snapshot_files: &[PathBuf]) -> UsageReport {
+    let mut report = UsageReport {
+        source: UsageSource::Snapshots,
+        ..UsageReport::default()
+    };
+
+    let snapshots = load_snapshots(snapshot_files);
+    report.files = snapshots.len();
+
+    for snapshot in latest_sessions(&snapshots) {
+        let usage = UsageTotals {
+            cost_usd: snapshot.history.cost.total.total_cost_usd,
+            entries: 1,
+            ..UsageTotals::default()
+        };
+        let day = snapshot
+            .meta
+            .created_at
+            .or(snapshot.meta.last_update_time)
+            .map(local_date);
+        report.record(day, None, &usage);
+    }
+
+    report
+}
+
+#[cfg(feature = "storage")]
+/// Aggregate the token and cost deltas of the given metrics logs.
+///
+/// 每个增量按自己的时间戳计入日期;成本增量没有模型信息,`by_model` 为空。
+#[must_use]
+pub fn report_from_metrics(metrics_files: &[PathBuf]) -> UsageReport {
+    let mut report = UsageReport {
+        source: UsageSource::Metrics,
+        ..UsageReport::default()
+    };
+
+    for path in metrics_files {
+        let Ok(events) = metrics::read_metrics(path) else {
+            continue;
+        };
+        report.files += 1;
+        for event in events {
+            let usage = match event.kind {
+                MetricKind::Tokens {
+                    input,
+                    output,
+                    cache_creation_input,
+                    cache_read_input,
+                    messages,
+                } => UsageTotals {
+                    input_tokens: input,
+                    output_tokens: output,
+                    cache_creation_tokens: cache_creation_input,
+                    cache_read_tokens: cache_read_input,
+                    entries: messages,
+                    ..UsageTotals::default()
+                },
+                MetricKind::Cost { delta_usd, .. } => UsageTotals {
+                    cost_usd: delta_usd,
+                    ..UsageTotals::default()
+                },
+                MetricKind::Status { .. } | MetricKind::Checkpoint(_) => continue,
+            };
+            report.record(Some(local_date(event.timestamp)), None, &usage);
+        }
+    }
+
+    report
+}
+
+#[cfg(feature = "storage")]
+/// Session hours of the snapshots updated at or after `since`.
+///
+/// 会话时长只有累计值,跨越 `since` 的会话整体计入。
+#[must_use]
+#[allow(clippy::cast_precision_loss)]
+pub fn period_from_snapshots(snapshot_files: &[PathBuf], since: DateTime<Utc>) -> PeriodUsage {
+    let snapshots = load_snapshots(snapshot_files);
+    let duration_ms: u64 = latest_sessions(&snapshots)
+        .filter(|snapshot| {
+            snapshot
+                .meta
+                .last_update_time
+                .or(snapshot.meta.created_at)
+                .is_some_and(|updated| updated >= since)
+        })
+        .map(|snapshot| snapshot.history.cost.total.total_duration_ms)
+        .sum();
+    PeriodUsage {
+        session_hours: duration_ms as f64 / 3_600_000.0,
+        tokens: 0,
+    }
+}
+
+#[cfg(feature = "storage")]
+fn load_snapshots(snapshot_files: &[PathBuf]) -> Vec<SessionSnapshot> {
+    snapshot_files
+        .iter()
+        .filter_map(|path| read_current_snapshot(path))
+        .collect()
+}
+
+#[cfg(feature = "storage")]
+/// Snapshots not continued by a resumed session (whose totals already include them)
+fn latest_sessions(snapshots: &[SessionSnapshot]) -> impl Iterator<Item = &SessionSnapshot> {
+    let resumed: HashSet<&str> = snapshots
+        .iter()
+        .filter_map(|snapshot| snapshot.meta.resumed_from.as_deref())
+        .collect();
+    snapshots
+        .iter()
+        .filter(move |snapshot| !resumed.contains(snapshot.meta.session_id.as_str()))
+}
+
+/// Tokens of the transcript responses at or after `since`, deduplicated like
+/// [`report_from_transcripts`].
+///
+/// 只读取 `since` 之后修改过的 transcript 文件。
+#[must_use]
+pub fn transcript_tokens_since(projects_dir: &Path, since: DateTime<Utc>) -> u64 {
+    let mut files = Vec::new();
+    collect_jsonl_files(projects_dir, &mut files);
+    files.retain(|path| {
+        fs::metadata(path)
+            .and_then(|metadata| metadata.modified())
+            .map_or(true, |modified| DateTime::<Utc>::from(modified) >= since)
+    });
+    files.sort();
+
+    let mut seen = HashSet::new();
+    let mut tokens = 0;
+    for path in files {
+        let Ok(file) = File::open(&path) else {
+            continue;
+        };
+        for line in BufReader::new(file).lines().map_while(std::io::Result::ok) {
+            let Ok(entry) = serde_json::from_str::<Value>(line.trim()) else {
+                continue;
+            };
+            if entry_time(&entry).is_none_or(|time| time < since) {
+                continue;
+            }
+            let Some((_, usage)) = assistant_usage(&entry) else {
+                continue;
+            };
+            if dedup_key(&entry).is_some_and(|key| !seen.insert(key)) {
+                continue;
+            }
+            tokens += usage.total_tokens();
+        }
+    }
+    tokens
+}
+
+/// Scan Claude Code transcripts (`projects_dir/**/*.jsonl`) directly.
+///
+/// Cost comes from the entry's own `costUSD` when present, otherwise from the
+/// configured provider pricing when it is quoted in USD, then from the price
+/// `catalog`; anything else counts as unpriced.
+#[must_use]
+pub fn report_from_transcripts<S: std::hash::BuildHasher>(
+    projects_dir: &Path,
+    providers: &HashMap<String, ModelProviderConfig, S>,
+    catalog: &PricingCatalog,
+) -> UsageReport {
+    let mut report = UsageReport {
+        source: UsageSource::Transcripts,
+        ..UsageReport::default()
+    };
+    let mut seen = HashSet::new();
+
+    let mut files = Vec::new();
+    collect_jsonl_files(projects_dir, &mut files);
+    files.sort();
+
+    for path in files {
+        let Ok(file) = File::open(&path) else {
+            continue;
+        };
+        report.files += 1;
+
+        for line in BufReader::new(file).lines().map_while(std::io::Result::ok) {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            let Ok(entry) = serde_json::from_str::<Value>(line) else {
+                continue;
+            };
+            let Some((model, mut usage)) = assistant_usage(&entry) else {
+                continue;
+            };
+
+            if let Some(key) = dedup_key(&entry) {
+                if !seen.insert(key) {
+                    report.duplicates += 1;
+                    continue;
+                }
+            }
+
+            match entry_cost(&entry, model.as_deref(), &usage, providers, catalog) {
+                Some(cost) => usage.cost_usd = cost,
+                None => usage.unpriced = 1,
+            }
+
+            let day = entry_time(&entry).map(local_date);
+            report.record(day, model.as_deref(), &usage);
+        }
+    }
+
+    report
+}
+
+fn entry_time(entry: &Value) -> Option<DateTime<Utc>> {
+    let timestamp = entry.get("timestamp")?.as_str()?;
+    DateTime::parse_from_rfc3339(timestamp)
+        .ok()
+        .map(|timestamp| timestamp.with_timezone(&Utc))
+}
+
+fn local_date(timestamp: DateTime<Utc>) -> NaiveDate {
+    timestamp.with_timezone(&Local).date_naive()
+}
+
+fn collect_jsonl_files(dir: &Path, files: &mut Vec<PathBuf>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let Ok(file_type) = entry.file_type() else {
+            continue;
+        };
+        if file_type.is_dir() {
+            collect_jsonl_files(&path, files);
+        } else if path.extension().is_some_and(|ext| ext == "jsonl") {
+            files.push(path);
+        }
+    }
+}
+
+/// `message.id` + `requestId`, the pair ccusage uses to identify a response
+fn dedup_key(entry: &Value) -> Option<String> {
+    let message_id = entry.get("message")?.get("id")?.as_str()?;
+    let request_id = entry
+        .get("requestId")
+        .and_then(Value::as_str)
+        .unwrap_or_default();
+    Some(format!("{message_id}:{request_id}"))
+}
+
+fn assistant_usage(entry: &Value) -> Option<(Option<String>, UsageTotals)> {
+    if entry.get("type").and_then(Value::as_str) != Some("assistant") {
+        return None;
+    }
+    let message = entry.get("message")?;
+    let usage = message.get("usage")?;
+    let field = |key: &str| usage.get(key).and_then(Value::as_u64).unwrap_or(0);
+
+    let totals = UsageTotals {
+        input_tokens: field("input_tokens"),
+        output_tokens: field("output_tokens"),
+        cache_creation_tokens: field("cache_creation_input_tokens"),
+        cache_read_tokens: field("cache_read_input_tokens"),
+        entries: 1,
+        ..UsageTotals::default()
+    };
+    let model = message
+        .get("model")
+        .and_then(Value::as_str)
+        .filter(|model| !model.is_empty() && *model != "<synthetic>")
+        .map(str::to_string);
+    Some((model, totals))
+}
+
+#[allow(clippy::cast_precision_loss)]
+fn entry_cost<S: std::hash::BuildHasher>(
+    entry: &Value,
+    model: Option<&str>,
+    usage: &UsageTotals,
+    providers: &HashMap<String, ModelProviderConfig, S>,
+    catalog: &PricingCatalog,
+) -> Option<f64> {
+    if let Some(cost) = entry.get("costUSD").and_then(Value::as_f64) {
+        return Some(cost);
+    }
+
+    let model = model?;
+    let model_names = [model.to_string()];
+    let pricing = match provider_pricing(providers, &model_names, None) {
+        Some(pricing) => {
+            let currency = provider_pricing_currency(providers, &model_names, None);
+            if currency.is_some_and(|currency| !currency.eq_ignore_ascii_case(DEFAULT_CURRENCY)) {
+                return None;
+            }
+            pricing
+        }
+        None => catalog.lookup(model)?.clone(),
+    };
+    if pricing.unit_tokens <= 0.0 {
+        return None;
+    }
+    let cache_read_price = pricing.cache_read.unwrap_or(pricing.input);
+    let cache_write_price = pricing.cache_write.unwrap_or(pricing.input);
+    let raw = (usage.input_tokens as f64).mul_add(
+        pricing.input,
+        (usage.output_tokens as f64).mul_add(
+            pricing.output,
+            (usage.cache_read_tokens as f64).mul_add(
+                cache_read_price,
+                usage.cache_creation_tokens as f64 * cache_write_price,
+            ),
+        ),
+    );
+    Some(raw / pricing.unit_tokens)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use tempfile::tempdir;
+
+    fn assistant(message_id: &str, request_id: &str, model: &str, input: u64) -> String {
+        json!({
+            "type": "assistant",
+            "requestId": request_id,
+            "timestamp": "2025-03-01T12:00:00Z",
+            "message": {
+                "id": message_id,
+                "model": model,
+                "usage": {"input_tokens": input, "output_tokens": 10}
+            }
+        })
+        .to_string()
+    }
+
+    #[test]
+    fn transcripts_are_deduplicated_by_message_and_request_id() -> Result<()> {
+        let dir = tempdir()?;
+        let project = dir.path().join("-home-me-app");
+        fs::create_dir_all(project.join("nested"))?;
+        fs::write(
+            project.join("a.jsonl"),
+            [
+                assistant("msg-1", "req-1", "claude-sonnet-4-20250514", 1_000_000),
+                json!({"type": "user", "message": {"content": "hi"}}).to_string(),
+                assistant("msg-2", "req-2", "deepseek-chat", 100),
+            ]
+            .join("\n"),
+        )?;
+        // 恢复会话时旧响应被复制进新 transcript
+        fs::write(
+            project.join("nested").join("b.jsonl"),
+            assistant("msg-1", "req-1", "claude-sonnet-4-20250514", 1_000_000),
+        )?;
+
+        let mut providers = crate::utils::provider_profiles::default_model_providers();
+        providers.insert(
+            "sonnet".to_string(),
+            serde_json::from_value(json!({
+                "models": ["claude-sonnet-4*"],
+                "currency": "USD",
+                "pricing": {"claude-sonnet-4*": {"input": 3.0, "output": 15.0}}
+            }))?,
+        );
+        let report = report_from_transcripts(dir.path(), &providers, &PricingCatalog::bundled());
+
+        assert_eq!(report.files, 2);
+        assert_eq!(report.duplicates, 1);
+        assert_eq!(report.total.entries, 2);
+        assert_eq!(report.total.input_tokens, 1_000_100);
+        assert_eq!(report.by_day.len(), 1);
+
+        let sonnet = &report.by_model["claude-sonnet-4-20250514"];
+        assert!((sonnet.cost_usd - 3.000_15).abs() < 1e-9, "{sonnet:?}");
+        // DeepSeek 按人民币计价,不能混进美元总额
+        assert_eq!(report.by_model["deepseek-chat"].unpriced, 1);
+        assert!((report.total.cost_usd - sonnet.cost_usd).abs() < f64::EPSILON);
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "storage")]
+    fn snapshot_report_skips_sessions_continued_by_a_resume() -> Result<()> {
+        let dir = tempdir()?;
+        let mut old = SessionSnapshot::new("old");
+        old.history.cost.total.total_cost_usd = 1.0;
+        let mut resumed = SessionSnapshot::new("new");
+        resumed.meta.resumed_from = Some("old".to_string());
+        resumed.history.cost.total.total_cost_usd = 1.5;
+        let mut other = SessionSnapshot::new("other");
+        other.history.cost.total.total_cost_usd = 0.25;
+
+        let mut files = Vec::new();
+        for snapshot in [&old, &resumed, &other] {
+            let path = dir
+                .path()
+                .join(format!("{}.json", snapshot.meta.session_id));
+            fs::write(&path, serde_json::to_string(snapshot)?)?;
+            files.push(path);
+        }
+
+        let report = report_from_snapshots(&files);
+        assert_eq!(report.files, 3);
+        assert_eq!(report.total.entries, 2);
+        assert!((report.total.cost_usd - 1.75).abs() < f64::EPSILON);
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "storage")]
+    fn metrics_report_splits_a_session_across_days() -> Result<()> {
+        use super::metrics::MetricEvent;
+
+        let dir = tempdir()?;
+        let path = metrics::metrics_path(dir.path(), "s1");
+        let first = Local::now() - chrono::Duration::days(1);
+        let second = Local::now();
+        let event = |timestamp: DateTime<Local>, kind| MetricEvent {
+            timestamp: timestamp.with_timezone(&Utc),
+            session_id: "s1".to_string(),
+            kind,
+        };
+        let tokens = |input| MetricKind::Tokens {
+            input,
+            output: 5,
+            cache_creation_input: 0,
+            cache_read_input: 0,
+            messages: 1,
+        };
+        metrics::append_events(
+            &path,
+            &[
+                event(first, tokens(100)),
+                event(
+                    first,
+                    MetricKind::Cost {
+                        delta_usd: 0.5,
+                        total_usd: 0.5,
+                    },
+                ),
+                event(
+                    second,
+                    MetricKind::Status {
+                        condition: "context_critical".to_string(),
+                        active: true,
+                    },
+                ),
+                event(second, tokens(40)),
+            ],
+        )?;
+
+        let report = report_from_metrics(&[path]);
+        assert_eq!(report.source, UsageSource::Metrics);
+        assert_eq!(report.files, 1);
+        assert_eq!(report.by_day.len(), 2);
+        assert_eq!(report.by_day[&first.date_naive()].input_tokens, 100);
+        assert_eq!(report.by_day[&second.date_naive()].input_tokens, 40);
+        assert_eq!(report.total.entries, 2);
+        assert_eq!(report.total.output_tokens, 10);
+        assert!((report.total.cost_usd - 0.5).abs() < f64::EPSILON);
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "storage")]
+    fn period_usage_counts_only_recent_sessions_and_responses() -> Result<()> {
+        let dir = tempdir()?;
+        let since = DateTime::parse_from_rfc3339("2025-03-03T00:00:00Z")?.with_timezone(&Utc);
+
+        let mut recent = SessionSnapshot::new("recent");
+        recent.history.cost.total.total_duration_ms = 5_400_000;
+        let mut stale = SessionSnapshot::new("stale");
+        stale.meta.last_update_time = Some(since - chrono::Duration::hours(1));
+        stale.history.cost.total.total_duration_ms = 3_600_000;
+        let mut files = Vec::new();
+        for snapshot in [&recent, &stale] {
+            let path = dir
+                .path()
+                .join(format!("{}.json", snapshot.meta.session_id));
+            fs::write(&path, serde_json::to_string(snapshot)?)?;
+            files.push(path);
+        }
+        let usage = period_from_snapshots(&files, since);
+        assert!((usage.session_hours - 1.5).abs() < f64::EPSILON);
+
+        let with_time = |message_id: &str, timestamp: &str| {
+            assistant(message_id, "req", "claude-sonnet-4", 100)
+                .replace("2025-03-01T12:00:00Z", timestamp)
+        };
+        let project = dir.path().join("-home-me-app");
+        fs::create_dir_all(&project)?;
+        fs::write(
+            project.join("a.jsonl"),
+            [
+                with_time("old", "2025-03-02T23:59:00Z"),
+                with_time("new", "2025-03-03T08:00:00Z"),
+                with_time("new", "2025-03-03T08:00:00Z"),
+            ]
+            .join("\n"),
+        )?;
+        assert_eq!(transcript_tokens_since(dir.path(), since), 110);
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "storage")]
+    fn burn_rate_baseline_ignores_short_sessions_and_current_session() -> Result<()> {
+        let dir = tempdir()?;
+        let mut files = Vec::new();
+        // 每小时 $1、$2、$3;短会话和当前会话不计入
+        for (id, cost, minutes) in [
+            ("a", 1.0, 60),
+            ("b", 1.0, 30),
+            ("c", 3.0, 60),
+            ("short", 50.0, 1),
+            ("current", 40.0, 60),
+        ] {
+            let mut snapshot = SessionSnapshot::new(id);
+            snapshot.history.cost.total.total_cost_usd = cost;
+            snapshot.history.cost.total.total_duration_ms = minutes * 60_000;
+            let path = dir.path().join(format!("{id}.json"));
+            fs::write(&path, serde_json::to_string(&snapshot)?)?;
+            files.push(path);
+        }
+
+        let baseline = baseline_from_snapshots(&files, "current")
+            .ok_or_else(|| anyhow::anyhow!("baseline expected"))?;
+        assert_eq!(baseline.sessions, 3);
+        assert!((baseline.mean - 2.0).abs() < 1e-9);
+        assert!((baseline.std_dev - (2.0_f64 / 3.0).sqrt()).abs() < 1e-9);
+        assert!(baseline.z_score(40.0).is_some_and(|z| z > 3.0));
+
+        assert_eq!(BurnRateBaseline::from_rates(&[]), None);
+        assert_eq!(
+            BurnRateBaseline::from_rates(&[1.0, 1.0]).and_then(|b| b.z_score(5.0)),
+            None
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn usage_source_parses_aliases() -> Result<()> {
+        assert_eq!(
+            "Transcripts".parse::<UsageSource>()?,
+            UsageSource::Transcripts
+        );
+        assert_eq!("jsonl".parse::<UsageSource>()?, UsageSource::Transcripts);
+        assert_eq!("snapshots".parse::<UsageSource>()?, UsageSource::Snapshots);
+        assert_eq!("metrics".parse::<UsageSource>()?, UsageSource::Metrics);
+        assert!("web".parse::<UsageSource>().is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,113 @@
+//! Warm-start outputs
+//!
+//! 每次渲染后,把按时渲染完成的组件输出连同渲染时间写入会话目录下的
+//! `<session>.warm`(JSON,组件名 → 输出)。每次调用都是新进程,生成器的组件缓存
+//! 是空的;某个组件在 `performance.max_total_ms` 内没能渲染完(冷启动时 git 状态、
+//! transcript 解析都可能很慢),就用这里记录的该组件上一次输出补上这一段,其余
+//! 组件照常显示新结果。渲染时间早于 `performance.warm_start_max_age_secs` 的记录
+//! 不再使用;一直超出预算的组件不会刷新自己的渲染时间,过期后就不再显示。
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::components::ComponentOutput;
+
+/// File extension of per-session warm-start outputs
+pub const WARM_START_EXTENSION: &str = "warm";
+
+/// Last output of one component and when it was rendered
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WarmOutput {
+    pub rendered_at: DateTime<Utc>,
+    pub output: ComponentOutput,
+}
+
+/// Warm-start outputs of `session_id` inside `sessions_dir`
+#[must_use]
+pub fn warm_start_path(sessions_dir: &Path, session_id: &str) -> PathBuf {
+    sessions_dir.join(format!("{session_id}.{WARM_START_EXTENSION}"))
+}
+
+/// The stored outputs rendered within `max_age` of `now`; a missing or
+/// unreadable file yields none.
+#[must_use]
+pub fn read_outputs(
+    path: &Path,
+    max_age: Duration,
+    now: DateTime<Utc>,
+) -> BTreeMap<String, WarmOutput> {
+    let Some(mut outputs) = fs::read_to_string(path)
+        .ok()
+        .and_then(|content| serde_json::from_str::<BTreeMap<String, WarmOutput>>(&content).ok())
+    else {
+        return BTreeMap::new();
+    };
+    outputs.retain(|_, warm| {
+        (now - warm.rendered_at)
+            .to_std()
+            .map_or(true, |age| age <= max_age)
+    });
+    outputs
+}
+
+/// Replace the stored outputs.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be written.
+pub fn write_outputs(path: &Path, outputs: &BTreeMap<String, WarmOutput>) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create {}", parent.display()))?;
+    }
+    let content =
+        serde_json::to_string(outputs).context("Failed to serialize warm-start outputs")?;
+    // 先写临时文件再 rename,并发的读取方不会读到半个文件
+    let tmp_path =
+        path.with_extension(format!("{WARM_START_EXTENSION}.{}.tmp", std::process::id()));
+    fs::write(&tmp_path, content)
+        .and_then(|()| fs::rename(&tmp_path, path))
+        .with_context(|| format!("Failed to write warm-start outputs: {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn outputs_expire_after_max_age() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = warm_start_path(dir.path(), "s1");
+        let now = Utc::now();
+        let max_age = Duration::from_secs(300);
+        assert!(read_outputs(&path, max_age, now).is_empty());
+
+        let warm = |text: &str, age_secs| WarmOutput {
+            rendered_at: now - chrono::Duration::seconds(age_secs),
+            output: ComponentOutput::preformatted(text),
+        };
+        write_outputs(
+            &path,
+            &BTreeMap::from([
+                ("branch".to_string(), warm("\x1b[32mmain\x1b[0m", 10)),
+                ("usage".to_string(), warm("$1.20", 301)),
+            ]),
+        )?;
+        let outputs = read_outputs(&path, max_age, now);
+        let texts: Vec<_> = outputs
+            .iter()
+            .map(|(name, warm)| (name.as_str(), warm.output.text.as_str()))
+            .collect();
+        assert_eq!(texts, [("branch", "\x1b[32mmain\x1b[0m")]);
+
+        fs::write(&path, "not json")?;
+        assert!(read_outputs(&path, max_age, now).is_empty());
+        Ok(())
+    }
+}
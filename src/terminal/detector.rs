@@ -5,9 +5,71 @@
 
 use std::env;
 
-use crate::components::{ColorSupport, TerminalCapabilities};
+use crate::components::{ColorSupport, GraphicsProtocol, TerminalCapabilities};
 use crate::config::AutoDetect;
 
+/// Color-related environment variables consulted by the color policy
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ColorEnv {
+    /// Value of `NO_COLOR`
+    pub no_color: Option<String>,
+    /// Value of `CLICOLOR_FORCE`
+    pub clicolor_force: Option<String>,
+}
+
+impl ColorEnv {
+    /// Read the color variables from the process environment
+    #[must_use]
+    pub fn from_env() -> Self {
+        Self {
+            no_color: env::var("NO_COLOR").ok(),
+            clicolor_force: env::var("CLICOLOR_FORCE").ok(),
+        }
+    }
+
+    /// `NO_COLOR` only applies when set to a non-empty string
+    fn no_color_requested(&self) -> bool {
+        self.no_color
+            .as_deref()
+            .is_some_and(|value| !value.is_empty())
+    }
+
+    /// `CLICOLOR_FORCE` applies when set to anything other than empty or `0`
+    fn clicolor_forced(&self) -> bool {
+        self.clicolor_force
+            .as_deref()
+            .is_some_and(|value| !value.is_empty() && value != "0")
+    }
+}
+
+/// Which rule decided the final color level
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorDecisionSource {
+    /// `CLICOLOR_FORCE` forced colors on
+    ClicolorForce,
+    /// `NO_COLOR` disabled colors
+    NoColor,
+    /// Explicit configuration or CLI flags
+    Config,
+    /// Terminal environment detection
+    Detection,
+}
+
+/// Result of the color policy
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ColorDecision {
+    /// Resolved color level
+    pub support: ColorSupport,
+    /// Rule that produced the level
+    pub source: ColorDecisionSource,
+}
+
+impl ColorDecision {
+    const fn new(support: ColorSupport, source: ColorDecisionSource) -> Self {
+        Self { support, source }
+    }
+}
+
 /// Terminal detector for capability detection
 pub struct TerminalDetector;
 
@@ -18,6 +80,24 @@ impl TerminalDetector {
         Self
     }
 
+    /// Terminal width in columns: `COLUMNS` first, then the controlling tty.
+    ///
+    /// The statusline's stdout is a pipe, so the tty query may fail; callers
+    /// pick their own fallback.
+    #[must_use]
+    pub fn detect_columns() -> Option<usize> {
+        env::var("COLUMNS")
+            .ok()
+            .and_then(|value| value.trim().parse::<usize>().ok())
+            .filter(|columns| *columns > 0)
+            .or_else(|| {
+                crossterm::terminal::size()
+                    .ok()
+                    .map(|(columns, _)| usize::from(columns))
+                    .filter(|columns| *columns > 0)
+            })
+    }
+
     /// Detect terminal capabilities
     #[must_use]
     pub fn detect(
@@ -35,15 +115,18 @@ impl TerminalDetector {
                 color_support: ColorSupport::None,
                 supports_emoji: false,
                 supports_nerd_font: false,
+                graphics: GraphicsProtocol::None,
+                supports_progress: false,
             };
         }
 
         // Detect individual capabilities
-        let color_support = if force_nerd_font || force_emoji {
-            ColorSupport::TrueColor // If we're forcing special fonts, assume full color support
-        } else {
-            Self::detect_color_support(enable_colors)
-        };
+        let color_decision = Self::resolve_color_policy(
+            &ColorEnv::from_env(),
+            enable_colors,
+            force_nerd_font || force_emoji,
+        );
+        let color_support = color_decision.support;
 
         let supports_emoji = if force_emoji {
             true
@@ -63,39 +146,75 @@ impl TerminalDetector {
         if std::env::var("DEBUG").is_ok() {
             eprintln!("[调试] 终端能力检测结果:");
             eprintln!("  - color_support: {color_support:?}");
+            eprintln!("  - color_decision: {:?}", color_decision.source);
             eprintln!("  - supports_emoji: {supports_emoji}");
             eprintln!("  - supports_nerd_font: {supports_nerd_font}");
             eprintln!("  - TERM_PROGRAM: {:?}", std::env::var("TERM_PROGRAM"));
             eprintln!("  - TERM: {:?}", std::env::var("TERM"));
             eprintln!("  - COLORTERM: {:?}", std::env::var("COLORTERM"));
+            eprintln!("  - NO_COLOR: {:?}", std::env::var("NO_COLOR"));
+            eprintln!("  - CLICOLOR_FORCE: {:?}", std::env::var("CLICOLOR_FORCE"));
         }
 
         TerminalCapabilities {
             color_support,
             supports_emoji,
             supports_nerd_font,
+            graphics: GraphicsProtocol::None,
+            supports_progress: false,
         }
     }
 
-    /// Detect color support level
-    fn detect_color_support(enable_colors: &AutoDetect) -> ColorSupport {
+    /// Resolve the color level using an ordered policy.
+    ///
+    /// Priority (highest first):
+    /// 1. `CLICOLOR_FORCE` set to a non-zero value forces colors on
+    /// 2. `NO_COLOR` set to a non-empty value turns colors off (<https://no-color.org/>)
+    /// 3. Explicit config (`enable_colors = true/false`, or forced Nerd Font/emoji
+    ///    which implies full color)
+    /// 4. Terminal environment detection
+    #[must_use]
+    pub fn resolve_color_policy(
+        env: &ColorEnv,
+        enable_colors: &AutoDetect,
+        force_full_color: bool,
+    ) -> ColorDecision {
+        if env.clicolor_forced() {
+            let support = match enable_colors {
+                AutoDetect::Bool(true) => ColorSupport::TrueColor,
+                _ if force_full_color => ColorSupport::TrueColor,
+                _ => Self::detect_color_level(),
+            };
+            return ColorDecision::new(support, ColorDecisionSource::ClicolorForce);
+        }
+
+        if env.no_color_requested() {
+            return ColorDecision::new(ColorSupport::None, ColorDecisionSource::NoColor);
+        }
+
+        if force_full_color {
+            return ColorDecision::new(ColorSupport::TrueColor, ColorDecisionSource::Config);
+        }
+
         match enable_colors {
-            AutoDetect::Bool(false) => ColorSupport::None,
-            AutoDetect::Bool(true) => ColorSupport::TrueColor, // Explicit enable assumes full support
+            AutoDetect::Bool(false) => {
+                ColorDecision::new(ColorSupport::None, ColorDecisionSource::Config)
+            }
+            // Explicit enable assumes full support
+            AutoDetect::Bool(true) => {
+                ColorDecision::new(ColorSupport::TrueColor, ColorDecisionSource::Config)
+            }
             AutoDetect::Auto(_) => {
-                // Auto-detect based on environment
-                Self::detect_color_level()
+                ColorDecision::new(Self::detect_color_level(), ColorDecisionSource::Detection)
             }
         }
     }
 
     /// Detect the actual color support level from environment
+    ///
+    /// `NO_COLOR` / `CLICOLOR_FORCE` are handled by [`Self::resolve_color_policy`],
+    /// so this only looks at terminal identification variables.
     fn detect_color_level() -> ColorSupport {
-        // Check NO_COLOR env var first (https://no-color.org/)
-        if env::var("NO_COLOR").is_ok() {
-            return ColorSupport::None;
-        }
-
         // Check COLORTERM for truecolor support
         if let Ok(colorterm) = env::var("COLORTERM") {
             if colorterm == "truecolor" || colorterm == "24bit" {
@@ -399,6 +518,83 @@ mod tests {
         assert!(caps.supports_nerd_font);
     }
 
+    fn color_env(no_color: Option<&str>, clicolor_force: Option<&str>) -> ColorEnv {
+        ColorEnv {
+            no_color: no_color.map(str::to_string),
+            clicolor_force: clicolor_force.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_color_policy_clicolor_force_beats_everything() {
+        for no_color in [None, Some("1")] {
+            for enable in [AutoDetect::Bool(false), AutoDetect::Bool(true)] {
+                let decision = TerminalDetector::resolve_color_policy(
+                    &color_env(no_color, Some("1")),
+                    &enable,
+                    false,
+                );
+                assert_eq!(decision.source, ColorDecisionSource::ClicolorForce);
+                assert!(decision.support.has_colors());
+            }
+        }
+    }
+
+    #[test]
+    fn test_color_policy_no_color_beats_config() {
+        for enable in [AutoDetect::Bool(true), AutoDetect::Auto("auto".to_string())] {
+            for force_full_color in [false, true] {
+                let decision = TerminalDetector::resolve_color_policy(
+                    &color_env(Some("1"), None),
+                    &enable,
+                    force_full_color,
+                );
+                assert_eq!(decision.support, ColorSupport::None);
+                assert_eq!(decision.source, ColorDecisionSource::NoColor);
+            }
+        }
+    }
+
+    #[test]
+    fn test_color_policy_ignores_empty_and_zero_values() {
+        let decision = TerminalDetector::resolve_color_policy(
+            &color_env(Some(""), Some("0")),
+            &AutoDetect::Bool(true),
+            false,
+        );
+        assert_eq!(decision.support, ColorSupport::TrueColor);
+        assert_eq!(decision.source, ColorDecisionSource::Config);
+
+        let decision = TerminalDetector::resolve_color_policy(
+            &color_env(None, Some("")),
+            &AutoDetect::Bool(false),
+            false,
+        );
+        assert_eq!(decision.support, ColorSupport::None);
+        assert_eq!(decision.source, ColorDecisionSource::Config);
+    }
+
+    #[test]
+    fn test_color_policy_config_beats_detection() {
+        let env = ColorEnv::default();
+
+        let decision =
+            TerminalDetector::resolve_color_policy(&env, &AutoDetect::Bool(false), false);
+        assert_eq!(decision.support, ColorSupport::None);
+        assert_eq!(decision.source, ColorDecisionSource::Config);
+
+        let decision = TerminalDetector::resolve_color_policy(&env, &AutoDetect::Bool(false), true);
+        assert_eq!(decision.support, ColorSupport::TrueColor);
+        assert_eq!(decision.source, ColorDecisionSource::Config);
+
+        let decision = TerminalDetector::resolve_color_policy(
+            &env,
+            &AutoDetect::Auto("auto".to_string()),
+            false,
+        );
+        assert_eq!(decision.source, ColorDecisionSource::Detection);
+    }
+
     #[test]
     fn test_color_support_methods() {
         assert!(!ColorSupport::None.has_colors());
@@ -0,0 +1,303 @@
+//! Inline image icons
+//!
+//! 支持 kitty 图形协议或 iTerm2 内联图片协议的终端(kitty、Ghostty、iTerm2、
+//! WezTerm)上,组件可以用 `image_icon` 指定一个小 PNG(模型 logo、项目头像)
+//! 代替字形图标。图片固定占 2×1 个单元格;终端不支持、文件缺失或不是 PNG 时
+//! 回退到普通图标。`terminal.graphics_protocol` 可强制指定或关闭协议。
+//!
+//! 编码后的图片按路径缓存,文件修改时间或大小变化才重新读取。kitty 图片带上由
+//! 路径和修改时间算出的 `i=` 编号:同一进程里只在第一次渲染时传输数据,之后只按
+//! 编号放置;每次调用都是新进程时,同一编号的重复传输也只会替换终端里的旧图片。
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::env;
+use std::fmt::Write;
+use std::hash::{Hash, Hasher};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock, PoisonError};
+use std::time::SystemTime;
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+
+use crate::components::GraphicsProtocol;
+use crate::utils::expand_home;
+
+/// Columns an image icon occupies
+const ICON_COLUMNS: u8 = 2;
+
+/// Larger files are not embedded on every refresh
+const MAX_ICON_BYTES: u64 = 64 * 1024;
+
+/// Base64 bytes per kitty escape sequence
+const KITTY_CHUNK: usize = 4096;
+
+const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+/// Encoded icons by path, for the lifetime of the process
+static ICON_CACHE: OnceLock<Mutex<HashMap<PathBuf, CachedIcon>>> = OnceLock::new();
+
+/// Base64 data of one version of an icon file
+struct CachedIcon {
+    modified: SystemTime,
+    len: u64,
+    /// kitty image id (`i=`)
+    id: u32,
+    payload: String,
+    /// The kitty image was already sent to the terminal
+    transmitted: bool,
+}
+
+impl CachedIcon {
+    /// Sequence drawing this icon; after the first kitty transmission only
+    /// a placement of the stored image
+    fn sequence(&mut self, protocol: GraphicsProtocol) -> Option<String> {
+        if protocol == GraphicsProtocol::Kitty && self.transmitted {
+            return Some(kitty_placement(self.id));
+        }
+        let sequence = encode_icon(&self.payload, self.len, protocol, self.id)?;
+        self.transmitted |= protocol == GraphicsProtocol::Kitty;
+        Some(sequence)
+    }
+}
+
+/// Protocol forced by `terminal.graphics_protocol`; `None` means auto-detect.
+#[must_use]
+pub fn protocol_from_config(value: &str) -> Option<GraphicsProtocol> {
+    match value.trim().to_ascii_lowercase().as_str() {
+        "kitty" => Some(GraphicsProtocol::Kitty),
+        "iterm2" | "iterm" => Some(GraphicsProtocol::Iterm2),
+        "none" | "off" | "false" => Some(GraphicsProtocol::None),
+        _ => None,
+    }
+}
+
+/// Protocol of the terminal described by `TERM`, `TERM_PROGRAM`,
+/// `LC_TERMINAL` and whether `KITTY_WINDOW_ID` is set.
+#[must_use]
+pub fn protocol_for_terminal(
+    term: Option<&str>,
+    term_program: Option<&str>,
+    lc_terminal: Option<&str>,
+    kitty_window: bool,
+) -> GraphicsProtocol {
+    let term_program = term_program.unwrap_or_default();
+    if kitty_window
+        || matches!(term, Some("xterm-kitty" | "xterm-ghostty"))
+        || term_program.eq_ignore_ascii_case("ghostty")
+    {
+        return GraphicsProtocol::Kitty;
+    }
+    if matches!(term_program, "iTerm.app" | "WezTerm") || lc_terminal == Some("iTerm2") {
+        return GraphicsProtocol::Iterm2;
+    }
+    GraphicsProtocol::None
+}
+
+/// Resolve the protocol from config, falling back to the environment.
+#[must_use]
+pub fn detect_protocol(config_value: &str) -> GraphicsProtocol {
+    protocol_from_config(config_value).unwrap_or_else(|| {
+        protocol_for_terminal(
+            env::var("TERM").ok().as_deref(),
+            env::var("TERM_PROGRAM").ok().as_deref(),
+            env::var("LC_TERMINAL").ok().as_deref(),
+            env::var_os("KITTY_WINDOW_ID").is_some(),
+        )
+    })
+}
+
+/// kitty image id of one version of the file at `path`; never 0
+fn image_id(path: &Path, modified: SystemTime, len: u64) -> u32 {
+    let mut hasher = DefaultHasher::new();
+    (path, modified, len).hash(&mut hasher);
+    u32::try_from(hasher.finish() & u64::from(u32::MAX)).map_or(1, |id| id.max(1))
+}
+
+/// kitty placement of the already transmitted image `id`
+fn kitty_placement(id: u32) -> String {
+    format!("\x1b_Ga=p,i={id},c={ICON_COLUMNS},r=1,q=2\x1b\\")
+}
+
+/// Escape sequence transmitting and drawing the base64 `payload` of a
+/// `png_len`-byte PNG as an icon; kitty stores it as image `id`.
+#[must_use]
+pub fn encode_icon(
+    payload: &str,
+    png_len: u64,
+    protocol: GraphicsProtocol,
+    id: u32,
+) -> Option<String> {
+    match protocol {
+        GraphicsProtocol::None => None,
+        GraphicsProtocol::Iterm2 => Some(format!(
+            "\x1b]1337;File=inline=1;size={png_len};width={ICON_COLUMNS};height=1;preserveAspectRatio=1:{payload}\x07"
+        )),
+        GraphicsProtocol::Kitty => {
+            // 超过 4096 字节的数据要分块发送,m=1 表示后面还有
+            let chunks: Vec<&[u8]> = payload.as_bytes().chunks(KITTY_CHUNK).collect();
+            let mut sequence = String::with_capacity(payload.len() + chunks.len() * 16);
+            for (idx, chunk) in chunks.iter().enumerate() {
+                let more = u8::from(idx + 1 < chunks.len());
+                let control = if idx == 0 {
+                    format!("f=100,a=T,i={id},c={ICON_COLUMNS},r=1,q=2,m={more}")
+                } else {
+                    format!("m={more}")
+                };
+                let _ = write!(
+                    sequence,
+                    "\x1b_G{control};{}\x1b\\",
+                    String::from_utf8_lossy(chunk)
+                );
+            }
+            Some(sequence)
+        }
+    }
+}
+
+/// Icon sequence for the PNG at `path`, or `None` when the terminal has no
+/// image protocol or the file is unusable.
+#[must_use]
+pub fn inline_image(path: &str, protocol: GraphicsProtocol) -> Option<String> {
+    if protocol == GraphicsProtocol::None || path.trim().is_empty() {
+        return None;
+    }
+    let path = PathBuf::from(expand_home(path.trim()));
+    let metadata = std::fs::metadata(&path).ok()?;
+    let modified = metadata.modified().ok()?;
+    let len = metadata.len();
+
+    let mut cache = ICON_CACHE
+        .get_or_init(|| Mutex::new(HashMap::new()))
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner);
+    if let Some(cached) = cache
+        .get_mut(&path)
+        .filter(|cached| cached.modified == modified && cached.len == len)
+    {
+        return cached.sequence(protocol);
+    }
+
+    let png = read_png(&path)?;
+    let len = u64::try_from(png.len()).ok()?;
+    let mut icon = CachedIcon {
+        modified,
+        len,
+        id: image_id(&path, modified, len),
+        payload: STANDARD.encode(&png),
+        transmitted: false,
+    };
+    let sequence = icon.sequence(protocol);
+    cache.insert(path, icon);
+    sequence
+}
+
+/// Contents of the PNG at `path`, `None` when missing, too large or not a PNG
+fn read_png(path: &Path) -> Option<Vec<u8>> {
+    let file = std::fs::File::open(path).ok()?;
+    let mut png = Vec::new();
+    file.take(MAX_ICON_BYTES + 1).read_to_end(&mut png).ok()?;
+    let fits = u64::try_from(png.len()).is_ok_and(|len| len <= MAX_ICON_BYTES);
+    (fits && png.starts_with(PNG_SIGNATURE)).then_some(png)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    #[test]
+    fn protocol_detected_from_terminal_identity() {
+        use GraphicsProtocol::{Iterm2, Kitty};
+        assert_eq!(
+            protocol_for_terminal(Some("xterm-kitty"), None, None, false),
+            Kitty
+        );
+        assert_eq!(protocol_for_terminal(None, None, None, true), Kitty);
+        assert_eq!(
+            protocol_for_terminal(None, Some("WezTerm"), None, false),
+            Iterm2
+        );
+        assert_eq!(
+            protocol_for_terminal(Some("screen"), Some("tmux"), Some("iTerm2"), false),
+            Iterm2
+        );
+        assert_eq!(
+            protocol_for_terminal(Some("xterm-256color"), Some("vscode"), None, false),
+            GraphicsProtocol::None
+        );
+        assert_eq!(protocol_from_config("off"), Some(GraphicsProtocol::None));
+        assert_eq!(protocol_from_config("auto"), None);
+    }
+
+    #[test]
+    fn encodes_icons_per_protocol() -> Result<(), String> {
+        let payload = STANDARD.encode([PNG_SIGNATURE, b"data"].concat());
+        let iterm =
+            encode_icon(&payload, 12, GraphicsProtocol::Iterm2, 7).ok_or("no iterm2 sequence")?;
+        assert!(iterm.starts_with("\x1b]1337;File=inline=1;size=12;width=2;height=1;"));
+        assert!(iterm.ends_with('\x07'));
+
+        let kitty =
+            encode_icon(&payload, 12, GraphicsProtocol::Kitty, 7).ok_or("no kitty sequence")?;
+        assert!(kitty.starts_with("\x1b_Gf=100,a=T,i=7,c=2,r=1,q=2,m=0;"));
+
+        let large = STANDARD.encode(vec![0u8; 6000]);
+        let kitty =
+            encode_icon(&large, 6000, GraphicsProtocol::Kitty, 7).ok_or("no kitty sequence")?;
+        assert_eq!(kitty.matches("\x1b_G").count(), 2);
+        assert!(kitty.contains("\x1b_Gm=0;"));
+        assert!(encode_icon(&payload, 12, GraphicsProtocol::None, 7).is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn kitty_icons_are_transmitted_once_per_file_version() -> Result<(), Box<dyn std::error::Error>>
+    {
+        let dir = tempfile::tempdir()?;
+        let icon = dir.path().join("logo.png");
+        std::fs::write(&icon, [PNG_SIGNATURE, b"v1"].concat())?;
+        let path = icon.to_string_lossy().into_owned();
+
+        let first = inline_image(&path, GraphicsProtocol::Kitty).ok_or("no kitty sequence")?;
+        assert!(first.starts_with("\x1b_Gf=100,a=T,i="));
+        let id = first
+            .split(',')
+            .find_map(|param| param.strip_prefix("i="))
+            .ok_or("no image id")?
+            .to_string();
+        let placement = format!("\x1b_Ga=p,i={id},c=2,r=1,q=2\x1b\\");
+        assert_eq!(
+            inline_image(&path, GraphicsProtocol::Kitty),
+            Some(placement)
+        );
+        // iTerm2 没有图片编号,每次都发送数据(来自缓存)
+        let iterm = inline_image(&path, GraphicsProtocol::Iterm2).ok_or("no iterm2 sequence")?;
+        assert!(iterm.contains(&STANDARD.encode([PNG_SIGNATURE, b"v1"].concat())));
+
+        // 文件变化后重新读取并以新编号传输
+        std::fs::write(&icon, [PNG_SIGNATURE, b"v2-longer"].concat())?;
+        let updated = inline_image(&path, GraphicsProtocol::Kitty).ok_or("no kitty sequence")?;
+        assert!(updated.starts_with("\x1b_Gf=100,a=T,i="));
+        assert!(!updated.contains(&format!("i={id},")));
+        Ok(())
+    }
+
+    #[test]
+    fn only_png_files_are_embedded() -> Result<(), Box<dyn std::error::Error>> {
+        let mut png = tempfile::NamedTempFile::new()?;
+        png.write_all(PNG_SIGNATURE)?;
+        let mut text = tempfile::NamedTempFile::new()?;
+        text.write_all(b"not an image")?;
+
+        let path = png.path().to_string_lossy().into_owned();
+        assert!(inline_image(&path, GraphicsProtocol::Kitty).is_some());
+        assert!(inline_image(&path, GraphicsProtocol::None).is_none());
+        let path = text.path().to_string_lossy().into_owned();
+        assert!(inline_image(&path, GraphicsProtocol::Kitty).is_none());
+        assert!(inline_image("/nonexistent/icon.png", GraphicsProtocol::Kitty).is_none());
+        Ok(())
+    }
+}
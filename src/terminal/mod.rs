@@ -1,5 +1,8 @@
 //! Terminal detection and rendering module
 
 pub mod detector;
+pub mod graphics;
+pub mod progress;
+pub mod title;
 
-pub use detector::TerminalDetector;
+pub use detector::{ColorDecision, ColorDecisionSource, ColorEnv, TerminalDetector};
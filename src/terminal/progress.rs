@@ -0,0 +1,100 @@
+//! Terminal progress reports (`OSC 9;4`)
+//!
+//! `ConEmu` 引入、Windows Terminal / Ghostty / `WezTerm` 跟进的 `ESC ] 9 ; 4 ; st ; pr BEL`
+//! 序列会在任务栏图标或标签页上显示进度。开启 `terminal.progress_osc` 后,
+//! 上下文用量除了文本进度条之外也以这种方式报告,状态随 token 组件的严重程度
+//! 变为警告(黄)或错误(红)。
+
+use std::env;
+
+use crate::config::{AutoDetect, Severity};
+
+/// Whether the terminal described by the environment understands `OSC 9;4`.
+#[must_use]
+pub fn progress_capable_terminal(
+    wt_session: bool,
+    conemu: bool,
+    term: Option<&str>,
+    term_program: Option<&str>,
+) -> bool {
+    wt_session
+        || conemu
+        || term == Some("xterm-ghostty")
+        || term_program
+            .is_some_and(|program| program.eq_ignore_ascii_case("ghostty") || program == "WezTerm")
+}
+
+/// Resolve `terminal.progress_osc` against the environment.
+#[must_use]
+pub fn detect_progress_support(setting: &AutoDetect) -> bool {
+    setting.is_enabled(progress_capable_terminal(
+        env::var_os("WT_SESSION").is_some(),
+        env::var_os("ConEmuPID").is_some(),
+        env::var("TERM").ok().as_deref(),
+        env::var("TERM_PROGRAM").ok().as_deref(),
+    ))
+}
+
+/// Progress report for `percent` (0–100); `None` clears the indicator.
+#[must_use]
+pub fn osc_progress(percent: Option<f64>, severity: Severity) -> String {
+    let Some(percent) = percent else {
+        return "\x1b]9;4;0;0\x07".to_string();
+    };
+    let state = match severity {
+        Severity::Normal => 1,
+        Severity::Critical => 2,
+        Severity::Warning => 4,
+    };
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    let percent = percent.clamp(0.0, 100.0).round() as u8;
+    format!("\x1b]9;4;{state};{percent}\x07")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn capable_terminals_detected() {
+        assert!(progress_capable_terminal(true, false, None, None));
+        assert!(progress_capable_terminal(false, true, None, None));
+        assert!(progress_capable_terminal(
+            false,
+            false,
+            Some("xterm-ghostty"),
+            None
+        ));
+        assert!(progress_capable_terminal(
+            false,
+            false,
+            None,
+            Some("WezTerm")
+        ));
+        assert!(!progress_capable_terminal(
+            false,
+            false,
+            Some("xterm-256color"),
+            Some("iTerm.app")
+        ));
+        assert!(detect_progress_support(&AutoDetect::Bool(true)));
+        assert!(!detect_progress_support(&AutoDetect::Bool(false)));
+    }
+
+    #[test]
+    fn progress_state_follows_severity() {
+        assert_eq!(
+            osc_progress(Some(45.4), Severity::Normal),
+            "\x1b]9;4;1;45\x07"
+        );
+        assert_eq!(
+            osc_progress(Some(88.0), Severity::Warning),
+            "\x1b]9;4;4;88\x07"
+        );
+        assert_eq!(
+            osc_progress(Some(120.0), Severity::Critical),
+            "\x1b]9;4;2;100\x07"
+        );
+        assert_eq!(osc_progress(None, Severity::Normal), "\x1b]9;4;0;0\x07");
+    }
+}
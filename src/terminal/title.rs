@@ -0,0 +1,74 @@
+//! Window / tab title output
+//!
+//! `terminal.set_title = true` 时在状态栏输出前附加 OSC 2 标题序列,内容是
+//! `title_components` 中各组件的纯文本摘要(如 `my-app · Opus 4.1 · 45.0%`),
+//! 面板很小时也能在窗口或标签页标题里看到关键信息。
+
+use crate::components::ComponentOutput;
+use crate::utils::strip_sgr;
+
+/// Separator between summarized components
+const TITLE_SEPARATOR: &str = " · ";
+
+/// Plain-text summary of `names`, in order, skipping components that did not
+/// render. The tokens segment is reduced to its percentage.
+#[must_use]
+pub fn title_summary(components: &[ComponentOutput], names: &[String]) -> String {
+    names
+        .iter()
+        .filter_map(|name| {
+            let component = components
+                .iter()
+                .find(|component| component.component_name.as_deref() == Some(name.as_str()))?;
+            let text = strip_sgr(&component.text);
+            let text = if name == "tokens" {
+                text.split_whitespace()
+                    .find(|word| word.ends_with('%'))
+                    .map_or_else(|| text.trim().to_string(), str::to_string)
+            } else {
+                text.trim().to_string()
+            };
+            Some(text).filter(|text| !text.is_empty())
+        })
+        .collect::<Vec<_>>()
+        .join(TITLE_SEPARATOR)
+}
+
+/// OSC 2 sequence setting the window title to `title`, with control
+/// characters removed so the title cannot terminate the sequence early.
+#[must_use]
+pub fn osc_title(title: &str) -> String {
+    let clean: String = title.chars().filter(|ch| !ch.is_control()).collect();
+    format!("\x1b]2;{clean}\x07")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn named(name: &str, text: &str) -> ComponentOutput {
+        ComponentOutput::new(text).with_component_name(name)
+    }
+
+    #[test]
+    fn summary_follows_configured_order() {
+        let components = vec![
+            named("model", "Opus 4.1"),
+            named("project", "my-app"),
+            named("tokens", "[\x1b[32m██\x1b[0m░░] 45.0% (90.0k/200k)"),
+        ];
+        let names: Vec<String> = ["project", "model", "tokens", "branch"]
+            .into_iter()
+            .map(String::from)
+            .collect();
+        assert_eq!(
+            title_summary(&components, &names),
+            "my-app · Opus 4.1 · 45.0%"
+        );
+    }
+
+    #[test]
+    fn title_sequence_drops_control_characters() {
+        assert_eq!(osc_title("a\x07b\x1b]c"), "\x1b]2;ab]c\x07");
+    }
+}
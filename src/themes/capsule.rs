@@ -5,7 +5,11 @@
 
 use anyhow::Result;
 
-use super::{ansi_bg, ansi_fg, colorize_segment, reapply_colors, ThemeRenderer, ANSI_RESET};
+use super::contrast::readable_foreground;
+use super::{
+    colorize_segment, group_runs, join_parts, push_separator, run_content, SegmentAnsi,
+    ThemeRenderer, ANSI_RESET,
+};
 use crate::components::{ComponentOutput, RenderContext};
 
 pub struct CapsuleThemeRenderer;
@@ -69,25 +73,16 @@ impl CapsuleThemeRenderer {
             ));
 
             if !part.is_empty() {
-                parts.push(part);
+                parts.push((part, component.separator.as_deref()));
             }
         }
 
-        parts.join(&colored_separator)
-    }
-
-    fn compose_content(component: &ComponentOutput) -> String {
-        let mut content = String::new();
-        if let Some(ref icon) = component.icon {
-            if !icon.is_empty() {
-                content.push_str(icon);
-                if !component.text.is_empty() {
-                    content.push(' ');
-                }
-            }
-        }
-        content.push_str(&component.text);
-        content
+        join_parts(
+            &parts,
+            &colored_separator,
+            &style.separator_color,
+            supports_colors,
+        )
     }
 
     fn should_preserve_internal_colors(component: &ComponentOutput) -> bool {
@@ -95,56 +90,20 @@ impl CapsuleThemeRenderer {
         text.contains('█')
             || text.contains('░')
             || text.contains('▓')
+            || component.component_name.as_deref() == Some("status")
             || ["Ready", "Thinking", "Error", "Tool", "Complete"]
                 .iter()
                 .any(|word| text.contains(word))
     }
 
-    fn render_capsule(
-        content: &str,
-        color: &str,
-        preserve_internal: bool,
-        fg_color: &str,
-    ) -> String {
-        let mut segment = String::new();
-
-        if let Some(fg) = ansi_fg(color).as_ref() {
-            segment.push_str(fg);
-        }
-        segment.push(Self::LEFT_CAP);
-        segment.push_str(ANSI_RESET);
-
-        let bg_seq = ansi_bg(color);
-        let fg_seq = ansi_fg(fg_color);
-
-        if let Some(bg) = bg_seq.as_ref() {
-            segment.push_str(bg);
-        }
-        if let Some(fg) = fg_seq.as_ref() {
-            segment.push_str(fg);
-        }
-        segment.push(' ');
-        if preserve_internal {
-            if let (Some(bg), Some(fg)) = (bg_seq.as_ref(), fg_seq.as_ref()) {
-                segment.push_str(&reapply_colors(content, bg, fg));
-            } else if let Some(bg) = bg_seq.as_ref() {
-                segment.push_str(&reapply_colors(content, bg, ""));
-            } else {
-                segment.push_str(content);
-            }
-        } else {
-            segment.push_str(content);
-        }
-        segment.push(' ');
-        segment.push_str(ANSI_RESET);
-
-        if let Some(fg) = ansi_fg(color).as_ref() {
-            segment.push_str(fg);
-        }
-        segment.push(Self::RIGHT_CAP);
-        segment.push_str(ANSI_RESET);
-
-        segment
+    fn push_capsule(out: &mut String, content: &str, ansi: &SegmentAnsi, preserve_internal: bool) {
+        out.push_str(&ansi.edge);
+        out.push(Self::LEFT_CAP);
+        out.push_str(ANSI_RESET);
+        ansi.push_body(out, content, preserve_internal);
+        out.push_str(&ansi.edge);
+        out.push(Self::RIGHT_CAP);
+        out.push_str(ANSI_RESET);
     }
 }
 
@@ -179,25 +138,37 @@ impl ThemeRenderer for CapsuleThemeRenderer {
         // Get foreground color from theme config
         let fg_color = &context.config.themes.capsule.fg;
 
-        let mut rendered = Vec::with_capacity(components.len());
+        let mut rendered = String::new();
         let mut color_iter = colors.iter();
 
-        for component in components {
-            let rendered_content = Self::compose_content(component);
+        for (idx, run) in group_runs(components).into_iter().enumerate() {
+            // 分组内每个组件各占一个颜色,整个胶囊使用第一个组件的颜色
             let color = color_iter
-                .next()
-                .cloned()
-                .unwrap_or_else(|| "bright_blue".to_string());
-            let preserve = Self::should_preserve_internal_colors(component);
-            rendered.push(Self::render_capsule(
-                &rendered_content,
-                &color,
+                .by_ref()
+                .take(run.len())
+                .fold(None, |first: Option<&String>, color| first.or(Some(color)))
+                .map_or("bright_blue", String::as_str);
+
+            if idx > 0 {
+                push_separator(
+                    &mut rendered,
+                    run.first().and_then(|c| c.separator.as_deref()),
+                    " ",
+                    &context.config.style.separator_color,
+                    supports_colors,
+                );
+            }
+            let preserve = run.iter().any(Self::should_preserve_internal_colors);
+            let fg = readable_foreground(fg_color, color, &context.config.style);
+            Self::push_capsule(
+                &mut rendered,
+                &run_content(run),
+                &SegmentAnsi::new(color, &fg),
                 preserve,
-                fg_color,
-            ));
+            );
         }
 
-        Ok(rendered.join(" "))
+        Ok(rendered)
     }
 
     fn name(&self) -> &'static str {
@@ -217,6 +188,7 @@ mod tests {
     use crate::components::{ColorSupport, TerminalCapabilities};
     use crate::config::{AutoDetect, Config};
     use crate::core::InputData;
+    use crate::themes::ansi_bg;
     use std::error::Error;
     use std::sync::Arc;
 
@@ -230,6 +202,7 @@ mod tests {
             input: Arc::new(InputData::default()),
             config: Arc::new(config),
             preview_mode: false,
+            transcript: None,
             terminal: TerminalCapabilities {
                 color_support: if colors {
                     ColorSupport::TrueColor
@@ -238,7 +211,9 @@ mod tests {
                 },
                 supports_emoji: true,
                 supports_nerd_font: nerd_font,
+                ..TerminalCapabilities::default()
             },
+            storage: None,
         }
     }
 
@@ -259,6 +234,30 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_capsule_group_shares_one_capsule() -> TestResult {
+        let theme = CapsuleThemeRenderer::new();
+        let ctx = create_test_context(true, true);
+
+        let grouped = |text: &str| {
+            let mut output = ComponentOutput::new(text.to_string());
+            output.group = Some(0);
+            output
+        };
+        let components = vec![
+            ComponentOutput::new("Project".to_string()),
+            grouped("45%"),
+            grouped("$1.20"),
+        ];
+
+        let colors = vec!["blue".to_string(), "yellow".to_string(), "red".to_string()];
+        let result = theme.render(&components, &colors, &ctx)?;
+        assert_eq!(result.matches('\u{e0b6}').count(), 2);
+        assert!(result.contains("45%  $1.20"));
+        assert!(!result.contains(&ansi_bg("red").unwrap_or_default()));
+        Ok(())
+    }
+
     #[test]
     fn test_capsule_theme_without_colors() -> TestResult {
         let theme = CapsuleThemeRenderer::new();
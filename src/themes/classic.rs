@@ -4,7 +4,7 @@
 
 use anyhow::Result;
 
-use super::{colorize_segment, ThemeRenderer};
+use super::{colorize_segment, join_parts, ThemeRenderer};
 use crate::components::{ComponentOutput, RenderContext};
 
 /// Classic theme renderer
@@ -95,12 +95,17 @@ impl ThemeRenderer for ClassicThemeRenderer {
             ));
 
             if !part.is_empty() {
-                parts.push(part);
+                parts.push((part, component.separator.as_deref()));
             }
         }
 
         // Join with separator
-        Ok(parts.join(&colored_separator))
+        Ok(join_parts(
+            &parts,
+            &colored_separator,
+            &style.separator_color,
+            supports_colors,
+        ))
     }
 
     fn name(&self) -> &'static str {
@@ -133,10 +138,12 @@ mod tests {
             input: Arc::new(InputData::default()),
             config: Arc::new(config),
             preview_mode: false,
+            transcript: None,
             terminal: TerminalCapabilities {
                 color_support: ColorSupport::None,
                 ..Default::default()
             },
+            storage: None,
         }
     }
 
@@ -172,6 +179,25 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_classic_theme_order_separator_overrides() -> TestResult {
+        let theme = ClassicThemeRenderer::new();
+        let ctx = create_test_context();
+
+        let mut grouped = ComponentOutput::new("model".to_string());
+        grouped.separator = Some(" ║ ".to_string());
+        let components = vec![
+            ComponentOutput::new("Project".to_string()),
+            ComponentOutput::new("main".to_string()),
+            grouped,
+            ComponentOutput::new("12%".to_string()),
+        ];
+
+        let result = theme.render(&components, &[], &ctx)?;
+        assert_eq!(result, "Project | main ║ model | 12%");
+        Ok(())
+    }
+
     #[test]
     fn test_classic_theme_hidden_components() -> TestResult {
         let theme = ClassicThemeRenderer::new();
@@ -200,10 +226,12 @@ mod tests {
             input: Arc::new(InputData::default()),
             config: Arc::new(config),
             preview_mode: false,
+            transcript: None,
             terminal: TerminalCapabilities {
                 color_support: ColorSupport::None,
                 ..Default::default()
             },
+            storage: None,
         };
 
         let components = vec![
@@ -0,0 +1,144 @@
+//! Foreground / background contrast
+//!
+//! Powerline 与胶囊主题用同一个前景色(`themes.*.fg`)绘制所有段落,自定义的前景色
+//! 或段落背景色可能让文字几乎看不见(如白字配浅黄底)。按 WCAG 的相对亮度计算对比度,
+//! 低于 `style.min_contrast` 时把前景色逐步推向黑色或白色,直到可读;
+//! 默认关闭(`style.auto_contrast = false`),保持配置的颜色不变。
+
+use std::sync::LazyLock;
+
+use super::{clamp_component, resolve_color};
+use crate::config::StyleConfig;
+
+type Rgb = (u8, u8, u8);
+
+const BLACK: Rgb = (0, 0, 0);
+const WHITE: Rgb = (255, 255, 255);
+
+/// Linearized sRGB channel of every 8-bit value; `powf` dominates contrast
+/// checks otherwise
+static LINEAR_CHANNELS: LazyLock<[f64; 256]> = LazyLock::new(|| {
+    let mut table = [0.0; 256];
+    for (value, linear) in (0..=u8::MAX).zip(table.iter_mut()) {
+        let channel = f64::from(value) / 255.0;
+        *linear = if channel <= 0.039_28 {
+            channel / 12.92
+        } else {
+            ((channel + 0.055) / 1.055).powf(2.4)
+        };
+    }
+    table
+});
+
+/// Linearized sRGB channel
+fn linear_channel(value: u8) -> f64 {
+    LINEAR_CHANNELS[usize::from(value)]
+}
+
+/// WCAG relative luminance, 0 (black) to 1 (white)
+#[must_use]
+pub fn relative_luminance((r, g, b): Rgb) -> f64 {
+    0.2126f64.mul_add(
+        linear_channel(r),
+        0.7152f64.mul_add(linear_channel(g), 0.0722 * linear_channel(b)),
+    )
+}
+
+/// WCAG contrast ratio between two colors, 1 to 21
+#[must_use]
+pub fn contrast_ratio(a: Rgb, b: Rgb) -> f64 {
+    let (la, lb) = (relative_luminance(a), relative_luminance(b));
+    (la.max(lb) + 0.05) / (la.min(lb) + 0.05)
+}
+
+/// `from` moved `amount` (0–1) of the way to `to`
+fn mix(from: Rgb, to: Rgb, amount: f32) -> Rgb {
+    let lerp = |from: u8, to: u8| {
+        clamp_component((f32::from(to) - f32::from(from)).mul_add(amount, f32::from(from)))
+    };
+    (lerp(from.0, to.0), lerp(from.1, to.1), lerp(from.2, to.2))
+}
+
+/// `fg`, or a lightened / darkened hex color when `fg` is unreadable on `bg`.
+///
+/// Colors that cannot be resolved to RGB (terminal default, unknown names)
+/// are returned unchanged.
+#[must_use]
+pub fn readable_foreground(fg: &str, bg: &str, style: &StyleConfig) -> String {
+    if !style.auto_contrast {
+        return fg.to_string();
+    }
+    let (Some(fg_rgb), Some(bg_rgb)) = (resolve_color(fg), resolve_color(bg)) else {
+        return fg.to_string();
+    };
+    if contrast_ratio(fg_rgb, bg_rgb) >= style.min_contrast {
+        return fg.to_string();
+    }
+
+    // 朝对比度更高的一端调整,尽量少改动原色
+    let target = if contrast_ratio(BLACK, bg_rgb) >= contrast_ratio(WHITE, bg_rgb) {
+        BLACK
+    } else {
+        WHITE
+    };
+    let (r, g, b) = (1..=10u8)
+        .map(|step| mix(fg_rgb, target, f32::from(step) / 10.0))
+        .find(|color| contrast_ratio(*color, bg_rgb) >= style.min_contrast)
+        .unwrap_or(target);
+    format!("#{r:02x}{g:02x}{b:02x}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn contrast_ratio_matches_wcag_bounds() {
+        assert!((contrast_ratio(BLACK, WHITE) - 21.0).abs() < 0.01);
+        assert!((contrast_ratio(WHITE, WHITE) - 1.0).abs() < f64::EPSILON);
+        assert!(contrast_ratio((0, 0, 255), WHITE) > contrast_ratio((255, 255, 0), WHITE));
+    }
+
+    #[test]
+    fn unreadable_foreground_is_adjusted() -> Result<(), String> {
+        let style = StyleConfig {
+            auto_contrast: true,
+            ..StyleConfig::default()
+        };
+
+        // 默认配色下白字蓝底足够清晰,保持原样
+        assert_eq!(readable_foreground("white", "blue", &style), "white");
+
+        let adjusted = readable_foreground("white", "yellow", &style);
+        let adjusted_rgb = resolve_color(&adjusted).ok_or("adjusted color is not hex")?;
+        let bg = resolve_color("yellow").ok_or("unknown color")?;
+        assert!(contrast_ratio(adjusted_rgb, bg) >= style.min_contrast);
+        // 浅色背景上应变暗
+        assert!(relative_luminance(adjusted_rgb) < relative_luminance(bg));
+
+        let strict = StyleConfig {
+            min_contrast: 4.5,
+            ..style
+        };
+        let adjusted = readable_foreground("#303030", "#202020", &strict);
+        let adjusted_rgb = resolve_color(&adjusted).ok_or("adjusted color is not hex")?;
+        assert!(contrast_ratio(adjusted_rgb, (0x20, 0x20, 0x20)) >= 4.5);
+        Ok(())
+    }
+
+    #[test]
+    fn adjustment_is_off_by_default() {
+        let style = StyleConfig::default();
+        assert!(!style.auto_contrast);
+        assert_eq!(readable_foreground("white", "yellow", &style), "white");
+        // 无法解析的颜色不做调整
+        let enabled = StyleConfig {
+            auto_contrast: true,
+            ..style
+        };
+        assert_eq!(
+            readable_foreground("default", "yellow", &enabled),
+            "default"
+        );
+    }
+}
@@ -0,0 +1,138 @@
+//! Frame / border rendering
+//!
+//! `style.frame.mode = "line"` 用一个圆角框包住整个状态栏(含多行扩展),
+//! `"rows"` 给每一行单独加框,`row_colors` 可以为每行指定边框颜色。
+//! 主要用于演示和录屏,让状态栏与终端输出在视觉上分开。
+
+use super::{ansi_fg_with_support, ANSI_RESET};
+use crate::components::ColorSupport;
+use crate::config::FrameConfig;
+use crate::utils::visible_width;
+
+/// Corners and edges: top-left, top-right, bottom-left, bottom-right,
+/// horizontal, vertical
+type BorderChars = [char; 6];
+
+fn border_chars(border: &str) -> BorderChars {
+    match border.trim().to_ascii_lowercase().as_str() {
+        "square" | "single" => ['┌', '┐', '└', '┘', '─', '│'],
+        "double" => ['╔', '╗', '╚', '╝', '═', '║'],
+        "heavy" | "bold" => ['┏', '┓', '┗', '┛', '━', '┃'],
+        _ => ['╭', '╮', '╰', '╯', '─', '│'],
+    }
+}
+
+/// `rows` boxed together, each padded to the widest row.
+fn boxed(rows: &[String], chars: BorderChars, color: Option<&str>) -> Vec<String> {
+    let [top_left, top_right, bottom_left, bottom_right, horizontal, vertical] = chars;
+    let paint = |text: String| match color {
+        Some(seq) => format!("{seq}{text}{ANSI_RESET}"),
+        None => text,
+    };
+    let width = rows.iter().map(|row| visible_width(row)).max().unwrap_or(0);
+    let edge = horizontal.to_string().repeat(width + 2);
+
+    let mut framed = Vec::with_capacity(rows.len() + 2);
+    framed.push(paint(format!("{top_left}{edge}{top_right}")));
+    for row in rows {
+        let padding = " ".repeat(width - visible_width(row));
+        framed.push(format!(
+            "{} {row}{padding} {}",
+            paint(vertical.to_string()),
+            paint(vertical.to_string())
+        ));
+    }
+    framed.push(paint(format!("{bottom_left}{edge}{bottom_right}")));
+    framed
+}
+
+/// Wrap rendered `lines` in the configured frame. `color_support` is `None`
+/// when colors are disabled.
+#[must_use]
+pub fn frame_lines(
+    lines: Vec<String>,
+    frame: &FrameConfig,
+    color_support: Option<ColorSupport>,
+) -> Vec<String> {
+    let mode = frame.mode.trim().to_ascii_lowercase();
+    if lines.is_empty() || !matches!(mode.as_str(), "line" | "rows") {
+        return lines;
+    }
+
+    let chars = border_chars(&frame.border);
+    let color_seq = |color: &str| {
+        color_support
+            .and_then(|support| ansi_fg_with_support(color, support))
+            .filter(|seq| !seq.is_empty())
+    };
+
+    if mode == "line" {
+        return boxed(&lines, chars, color_seq(&frame.color).as_deref());
+    }
+    lines
+        .iter()
+        .enumerate()
+        .flat_map(|(idx, line)| {
+            let color = frame.row_colors.get(idx).unwrap_or(&frame.color);
+            boxed(
+                std::slice::from_ref(line),
+                chars,
+                color_seq(color).as_deref(),
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame(mode: &str) -> FrameConfig {
+        FrameConfig {
+            mode: mode.to_string(),
+            ..FrameConfig::default()
+        }
+    }
+
+    #[test]
+    fn line_mode_boxes_all_rows_to_common_width() {
+        let lines = vec!["项目 main".to_string(), "\x1b[31mok\x1b[0m".to_string()];
+        let framed = frame_lines(lines, &frame("line"), None);
+        assert_eq!(
+            framed,
+            [
+                "╭───────────╮",
+                "│ 项目 main │",
+                "│ \x1b[31mok\x1b[0m        │",
+                "╰───────────╯",
+            ]
+        );
+    }
+
+    #[test]
+    fn rows_mode_uses_per_row_colors() {
+        let config = FrameConfig {
+            border: "square".to_string(),
+            row_colors: vec!["#ff0000".to_string()],
+            ..frame("rows")
+        };
+        let framed = frame_lines(
+            vec!["a".to_string(), "bc".to_string()],
+            &config,
+            Some(ColorSupport::TrueColor),
+        );
+        assert_eq!(framed.len(), 6);
+        assert!(framed[0].starts_with("\x1b[38;2;255;0;0m┌───┐"));
+        assert!(framed[3].contains("┌────┐"));
+        assert!(!framed[3].contains("255;0;0"));
+    }
+
+    #[test]
+    fn frame_disabled_by_default() {
+        let lines = vec!["a".to_string()];
+        assert_eq!(
+            frame_lines(lines.clone(), &FrameConfig::default(), None),
+            lines
+        );
+    }
+}
@@ -0,0 +1,118 @@
+//! Cross-segment background gradient
+//!
+//! `style.background_gradient` 让 Powerline / 胶囊主题的段落背景从 `start` 平滑过渡到
+//! `end`。插值按整行渲染宽度计算:每个段落取其中点在整行中的位置,
+//! 因此宽段落和窄段落相邻时颜色变化仍然均匀。
+
+use super::{clamp_component, resolve_color};
+use crate::components::ComponentOutput;
+use crate::config::BackgroundGradientConfig;
+use crate::utils::visible_width;
+
+/// Columns a segment adds around its content: padding and separator
+const SEGMENT_CHROME: usize = 3;
+
+/// Columns `component` takes on a powerline / capsule line
+fn segment_width(component: &ComponentOutput) -> usize {
+    let icon = component.icon.as_deref().unwrap_or_default();
+    let icon_width = match visible_width(icon) {
+        0 => 0,
+        width => width + 1,
+    };
+    icon_width + visible_width(&component.text) + SEGMENT_CHROME
+}
+
+/// Color `t` (0–1) of the way from `start` to `end`, as `#rrggbb`
+fn interpolate(start: (u8, u8, u8), end: (u8, u8, u8), t: f32) -> String {
+    let lerp = |from: u8, to: u8| {
+        clamp_component((f32::from(to) - f32::from(from)).mul_add(t, f32::from(from)))
+    };
+    format!(
+        "#{:02x}{:02x}{:02x}",
+        lerp(start.0, end.0),
+        lerp(start.1, end.1),
+        lerp(start.2, end.2)
+    )
+}
+
+/// Replace segment background `colors` (one per named component, as produced
+/// by the generator) with the configured gradient.
+pub fn apply_gradient(
+    colors: &mut [String],
+    components: &[ComponentOutput],
+    gradient: &BackgroundGradientConfig,
+) {
+    if !gradient.enabled {
+        return;
+    }
+    let (Some(start), Some(end)) = (resolve_color(&gradient.start), resolve_color(&gradient.end))
+    else {
+        return;
+    };
+
+    let widths: Vec<usize> = components
+        .iter()
+        .filter(|component| component.component_name.is_some())
+        .map(segment_width)
+        .collect();
+    let total = widths.iter().sum::<usize>().max(1);
+
+    let mut offset = 0;
+    for (color, width) in colors.iter_mut().zip(widths) {
+        let center = offset * 2 + width;
+        offset += width;
+        #[allow(clippy::cast_precision_loss)]
+        let t = center as f32 / (total * 2) as f32;
+        *color = interpolate(start, end, t);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn segment(name: &str, text: &str) -> ComponentOutput {
+        ComponentOutput::preformatted(text).with_component_name(name)
+    }
+
+    #[test]
+    fn gradient_follows_rendered_width() {
+        let gradient = BackgroundGradientConfig {
+            enabled: true,
+            start: "#000000".to_string(),
+            end: "#ffffff".to_string(),
+        };
+        // 宽度 3+3、11+3、3+3,总计 26 列
+        let components = vec![
+            segment("a", "abc"),
+            segment("b", "\x1b[31mabcdefghijk\x1b[0m"),
+            segment("c", "xyz"),
+        ];
+        let mut colors = vec!["blue".to_string(); 3];
+        apply_gradient(&mut colors, &components, &gradient);
+
+        // 中点分别位于 3/26、13/26、23/26
+        assert_eq!(colors, ["#1d1d1d", "#808080", "#e2e2e2"]);
+    }
+
+    #[test]
+    fn disabled_or_unknown_colors_keep_palette() {
+        let components = vec![segment("a", "abc")];
+        let mut colors = vec!["blue".to_string()];
+
+        apply_gradient(
+            &mut colors,
+            &components,
+            &BackgroundGradientConfig::default(),
+        );
+        assert_eq!(colors, ["blue"]);
+
+        let gradient = BackgroundGradientConfig {
+            enabled: true,
+            start: "default".to_string(),
+            ..BackgroundGradientConfig::default()
+        };
+        apply_gradient(&mut colors, &components, &gradient);
+        assert_eq!(colors, ["blue"]);
+    }
+}
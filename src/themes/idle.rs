@@ -0,0 +1,116 @@
+//! Idle session styling
+//!
+//! transcript 超过 `style.idle.threshold_minutes` 没有写入时视为空闲:整条状态行
+//! 变暗,或在行首显示 `⏸ idle 12m`,在一堆终端标签里一眼看出哪些会话还在干活。
+
+use std::time::{Duration, SystemTime};
+
+use super::ANSI_RESET;
+use crate::components::ComponentOutput;
+use crate::config::IdleConfig;
+use crate::utils::ansi;
+
+/// SGR faint
+const ANSI_DIM: &str = "\x1b[2m";
+
+/// How long the session has been idle, if at least the configured threshold.
+#[must_use]
+pub fn idle_duration(
+    config: &IdleConfig,
+    last_activity: Option<SystemTime>,
+    now: SystemTime,
+) -> Option<Duration> {
+    if config.threshold_minutes == 0 {
+        return None;
+    }
+    let idle = now.duration_since(last_activity?).ok()?;
+    (idle >= Duration::from_secs(config.threshold_minutes.saturating_mul(60))).then_some(idle)
+}
+
+/// Compact idle time: `12m`, `2h5m`, `3d`
+#[must_use]
+pub fn format_idle(idle: Duration) -> String {
+    let minutes = idle.as_secs() / 60;
+    let (days, hours, minutes) = (minutes / 1440, minutes / 60 % 24, minutes % 60);
+    if days > 0 {
+        format!("{days}d")
+    } else if hours > 0 && minutes > 0 {
+        format!("{hours}h{minutes}m")
+    } else if hours > 0 {
+        format!("{hours}h")
+    } else {
+        format!("{minutes}m")
+    }
+}
+
+/// The `⏸ idle 12m` segment shown at the start of the line.
+#[must_use]
+pub fn idle_segment(config: &IdleConfig, idle: Duration) -> ComponentOutput {
+    let mut output = ComponentOutput::new(format!("{} {}", config.text, format_idle(idle)))
+        .with_icon(config.icon.clone())
+        .with_icon_color(&config.color)
+        .with_text_color(&config.color);
+    output.set_component_name("idle");
+    output
+}
+
+/// Render `line` faint.
+#[must_use]
+pub fn dim_line(line: &str) -> String {
+    if line.is_empty() {
+        return String::new();
+    }
+    // 组件内部的 reset 会清掉 faint,之后重新设置
+    let body = ansi::reapply_after_resets(line, ANSI_DIM);
+    format!("{ANSI_DIM}{body}{ANSI_RESET}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(threshold_minutes: u64) -> IdleConfig {
+        IdleConfig {
+            threshold_minutes,
+            ..IdleConfig::default()
+        }
+    }
+
+    #[test]
+    fn idle_only_past_threshold() {
+        let now = SystemTime::UNIX_EPOCH + Duration::from_secs(10_000);
+        let ago = |minutes: u64| Some(now - Duration::from_secs(minutes * 60));
+
+        assert_eq!(idle_duration(&config(10), ago(9), now), None);
+        assert_eq!(
+            idle_duration(&config(10), ago(12), now),
+            Some(Duration::from_secs(720))
+        );
+        assert_eq!(idle_duration(&config(0), ago(120), now), None);
+        assert_eq!(idle_duration(&config(10), None, now), None);
+    }
+
+    #[test]
+    fn formats_idle_time_and_segment() {
+        let minutes = |value: u64| Duration::from_secs(value * 60);
+        assert_eq!(format_idle(minutes(12)), "12m");
+        assert_eq!(format_idle(minutes(125)), "2h5m");
+        assert_eq!(format_idle(minutes(120)), "2h");
+        assert_eq!(format_idle(minutes(3 * 1440 + 5)), "3d");
+
+        let segment = idle_segment(&config(10), minutes(12));
+        assert_eq!(segment.text, "idle 12m");
+        assert_eq!(segment.icon.as_deref(), Some("⏸"));
+        assert_eq!(segment.component_name.as_deref(), Some("idle"));
+    }
+
+    #[test]
+    fn dims_across_resets() {
+        let line = format!("a{ANSI_RESET}b");
+        assert_eq!(
+            dim_line(&line),
+            format!("{ANSI_DIM}a{ANSI_RESET}{ANSI_DIM}b{ANSI_RESET}")
+        );
+        assert_eq!(dim_line(""), "");
+    }
+}
@@ -2,14 +2,24 @@
 //!
 //! Provides different visual themes for the statusline.
 
+use std::borrow::Cow;
+
 use anyhow::Result;
 use crossterm::style::{Color, Stylize};
 
 use crate::components::{ColorSupport, ComponentOutput, RenderContext};
+use crate::utils::ansi;
 
 pub mod capsule;
 pub mod classic;
+pub mod contrast;
+pub mod frame;
+pub mod gradient;
+pub mod idle;
+pub mod palette;
 pub mod powerline;
+pub mod severity;
+pub mod text_safety;
 
 pub use capsule::CapsuleThemeRenderer;
 pub use classic::ClassicThemeRenderer;
@@ -47,7 +57,133 @@ pub(crate) fn colorize_segment(
     )
 }
 
-pub(crate) const ANSI_RESET: &str = "\x1b[0m";
+pub(crate) const ANSI_RESET: &str = ansi::RESET;
+
+/// Gap between the members of a component group inside one segment
+pub(crate) const GROUP_GAP: &str = "  ";
+
+/// Split outputs into runs drawn as one segment: consecutive members of the
+/// same `components.groups` entry, unless an explicit separator splits them.
+pub(crate) fn group_runs(components: &[ComponentOutput]) -> Vec<&[ComponentOutput]> {
+    let mut runs = Vec::new();
+    let mut start = 0;
+    for idx in 1..=components.len() {
+        let continues = components.get(idx).is_some_and(|component| {
+            component.separator.is_none()
+                && component.group.is_some()
+                && component.group == components[idx - 1].group
+        });
+        if !continues {
+            runs.push(&components[start..idx]);
+            start = idx;
+        }
+    }
+    runs
+}
+
+/// Join rendered parts with `separator`, except where a part carries its own
+/// separator from `components.order` (`sep:│`, `space`).
+pub(crate) fn join_parts(
+    parts: &[(String, Option<&str>)],
+    separator: &str,
+    separator_color: &str,
+    supports_colors: bool,
+) -> String {
+    let mut joined = String::new();
+    for (idx, (part, own_separator)) in parts.iter().enumerate() {
+        if idx > 0 {
+            push_separator(
+                &mut joined,
+                *own_separator,
+                separator,
+                separator_color,
+                supports_colors,
+            );
+        }
+        joined.push_str(part);
+    }
+    joined
+}
+
+/// Append the separator placed before a part: its own separator from
+/// `components.order`, colored, or the theme `separator`.
+pub(crate) fn push_separator(
+    out: &mut String,
+    own_separator: Option<&str>,
+    separator: &str,
+    separator_color: &str,
+    supports_colors: bool,
+) {
+    match own_separator {
+        Some(own) => out.push_str(&colorize_segment(
+            own,
+            Some(separator_color),
+            supports_colors,
+        )),
+        None => out.push_str(separator),
+    }
+}
+
+/// Icon and text of a component as drawn inside a segment
+pub(crate) fn push_content(out: &mut String, component: &ComponentOutput) {
+    if let Some(icon) = component.icon.as_deref().filter(|icon| !icon.is_empty()) {
+        out.push_str(icon);
+        if !component.text.is_empty() {
+            out.push(' ');
+        }
+    }
+    out.push_str(&component.text);
+}
+
+/// Contents of a group run, members separated by [`GROUP_GAP`]
+pub(crate) fn run_content(run: &[ComponentOutput]) -> String {
+    let mut content = String::new();
+    for (idx, component) in run.iter().enumerate() {
+        if idx > 0 {
+            content.push_str(GROUP_GAP);
+        }
+        push_content(&mut content, component);
+    }
+    content
+}
+
+/// ANSI sequences of one segment color, resolved once per render instead of
+/// once per use; unresolvable colors yield empty sequences
+pub(crate) struct SegmentAnsi {
+    /// Segment color as background, for the body
+    pub bg: String,
+    /// Text color on the body
+    pub text: String,
+    /// Segment color as foreground, for caps and arrows
+    pub edge: String,
+}
+
+impl SegmentAnsi {
+    pub(crate) fn new(color: &str, text_color: &str) -> Self {
+        Self {
+            bg: ansi_bg(color).unwrap_or_default(),
+            text: ansi_fg(text_color).unwrap_or_default(),
+            edge: ansi_fg(color).unwrap_or_default(),
+        }
+    }
+
+    /// Append ` content ` on the segment background, followed by a reset.
+    ///
+    /// With `preserve_internal` the content keeps its own colors: every reset
+    /// inside it is followed by the segment colors again.
+    pub(crate) fn push_body(&self, out: &mut String, content: &str, preserve_internal: bool) {
+        out.push_str(&self.bg);
+        out.push_str(&self.text);
+        out.push(' ');
+        if preserve_internal && !self.bg.is_empty() {
+            push_reapplied(out, content, &self.bg, &self.text);
+        } else {
+            out.push_str(content);
+        }
+        out.push(' ');
+        out.push_str(ANSI_RESET);
+    }
+}
 
 /// Generate foreground ANSI escape sequence based on color support level
 pub(crate) fn ansi_fg_with_support(color: &str, color_support: ColorSupport) -> Option<String> {
@@ -212,22 +348,39 @@ fn rgb_to_ansi16(r: u8, g: u8, b: u8) -> u8 {
     }
 }
 
-/// Reapply both background and foreground colors after `ANSI_RESET` sequences
-pub(crate) fn reapply_colors(content: &str, bg_seq: &str, fg_seq: &str) -> String {
-    if !content.contains(ANSI_RESET) {
-        return content.to_string();
+/// Append `content`, reapplying the background and foreground colors after
+/// each reset inside it
+pub(crate) fn push_reapplied(out: &mut String, content: &str, bg_seq: &str, fg_seq: &str) {
+    if !ansi::contains_reset(content) {
+        out.push_str(content);
+        return;
     }
 
-    let color_seq = format!("{bg_seq}{fg_seq}");
-    let mut processed = content.replace(ANSI_RESET, &(String::from(ANSI_RESET) + &color_seq));
-    if !processed.starts_with(&color_seq) {
-        processed = format!("{color_seq}{processed}");
+    let starts_colored = content
+        .strip_prefix(bg_seq)
+        .is_some_and(|rest| rest.starts_with(fg_seq));
+    if !starts_colored {
+        out.push_str(bg_seq);
+        out.push_str(fg_seq);
+    }
+    out.push_str(&ansi::reapply_after_resets(
+        content,
+        &format!("{bg_seq}{fg_seq}"),
+    ));
+}
+
+/// Trimmed, lowercase color name; 配置中的颜色名几乎都已是小写,只在需要时分配
+fn normalize_color_name(name: &str) -> Cow<'_, str> {
+    let trimmed = name.trim();
+    if trimmed.chars().any(char::is_uppercase) {
+        Cow::Owned(trimmed.to_lowercase())
+    } else {
+        Cow::Borrowed(trimmed)
     }
-    processed
 }
 
 fn resolve_color(name: &str) -> Option<(u8, u8, u8)> {
-    let normalized = name.trim().to_lowercase();
+    let normalized = normalize_color_name(name);
     if normalized.is_empty() {
         return None;
     }
@@ -238,7 +391,7 @@ fn resolve_color(name: &str) -> Option<(u8, u8, u8)> {
 
     if let Some(hex) = normalized.strip_prefix('#').or_else(|| {
         if normalized.len() == 6 && normalized.chars().all(|c| c.is_ascii_hexdigit()) {
-            Some(normalized.as_str())
+            Some(normalized.as_ref())
         } else {
             None
         }
@@ -254,7 +407,7 @@ fn resolve_color(name: &str) -> Option<(u8, u8, u8)> {
         }
     }
 
-    let nord = match normalized.as_str() {
+    let nord = match normalized.as_ref() {
         "black" => (46, 52, 64),
         "gray" | "grey" => (120, 128, 146),
         "white" => (236, 239, 244),
@@ -283,7 +436,7 @@ fn resolve_color(name: &str) -> Option<(u8, u8, u8)> {
 }
 
 fn parse_color(name: &str) -> Option<Color> {
-    match name.trim().to_lowercase().as_str() {
+    match normalize_color_name(name).as_ref() {
         "black" => Some(Color::Black),
         "red" => Some(Color::Red),
         "green" => Some(Color::Green),
@@ -300,7 +453,8 @@ fn parse_color(name: &str) -> Option<Color> {
         "bright_blue" => Some(Color::DarkBlue),
         "bright_magenta" | "bright_purple" => Some(Color::DarkMagenta),
         "bright_cyan" => Some(Color::DarkCyan),
-        _ => None,
+        // 十六进制颜色(如色盲友好调色板)按 RGB 输出
+        other => resolve_color(other).map(|(r, g, b)| Color::Rgb { r, g, b }),
     }
 }
 
@@ -0,0 +1,106 @@
+//! Semantic color roles
+//!
+//! 组件用 `ok` / `warn` / `danger` / `info` 四个语义角色表达状态,而不是直接写
+//! "green" / "red"。角色按 `palette.accessibility` 选择的预设映射到具体颜色,
+//! 红绿色弱用户可以切换到基于 Okabe-Ito 配色的 `deuteranopia` / `protanopia`
+//! 预设;`palette.ok` 等字段可以单独覆盖某个角色。配置中任何颜色字段都可以写角色名。
+
+use crate::config::PaletteConfig;
+
+/// Semantic color role
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorRole {
+    /// Healthy / done
+    Ok,
+    /// Needs attention soon
+    Warn,
+    /// Failed / over a limit
+    Danger,
+    /// Neutral activity
+    Info,
+}
+
+impl ColorRole {
+    /// Role named `name` (`ok`, `warn`, `danger`, `info`), case-insensitive
+    #[must_use]
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "ok" => Some(Self::Ok),
+            "warn" => Some(Self::Warn),
+            "danger" => Some(Self::Danger),
+            "info" => Some(Self::Info),
+            _ => None,
+        }
+    }
+}
+
+/// Role colors of a built-in accessibility preset: ok, warn, danger, info
+fn preset(accessibility: &str) -> [&'static str; 4] {
+    match accessibility.trim().to_ascii_lowercase().as_str() {
+        // 红绿色弱:蓝 / 黄 / 朱红,靠明度和蓝黄轴区分
+        "deuteranopia" | "deuteranomaly" => ["#0072b2", "#f0e442", "#d55e00", "#56b4e9"],
+        // 红色弱视者看红色偏暗,危险色改用更亮的橙色
+        "protanopia" | "protanomaly" => ["#56b4e9", "#f0e442", "#e69f00", "#0072b2"],
+        _ => ["green", "yellow", "red", "cyan"],
+    }
+}
+
+impl PaletteConfig {
+    /// Color of `role` under the configured preset and overrides
+    #[must_use]
+    pub fn role_color(&self, role: ColorRole) -> String {
+        let [ok, warn, danger, info] = preset(&self.accessibility);
+        let (custom, fallback) = match role {
+            ColorRole::Ok => (&self.ok, ok),
+            ColorRole::Warn => (&self.warn, warn),
+            ColorRole::Danger => (&self.danger, danger),
+            ColorRole::Info => (&self.info, info),
+        };
+        custom
+            .as_deref()
+            .map(str::trim)
+            .filter(|color| !color.is_empty() && ColorRole::from_name(color).is_none())
+            .unwrap_or(fallback)
+            .to_string()
+    }
+
+    /// `color` with a role name replaced by its palette color; other colors
+    /// are returned unchanged.
+    #[must_use]
+    pub fn resolve(&self, color: &str) -> String {
+        ColorRole::from_name(color).map_or_else(|| color.to_string(), |role| self.role_color(role))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_palette_keeps_classic_colors() {
+        let palette = PaletteConfig::default();
+        assert_eq!(palette.resolve("ok"), "green");
+        assert_eq!(palette.resolve("Danger"), "red");
+        assert_eq!(palette.resolve("magenta"), "magenta");
+        assert_eq!(palette.resolve("#123456"), "#123456");
+    }
+
+    #[test]
+    fn accessibility_presets_and_overrides() {
+        let mut palette = PaletteConfig {
+            accessibility: "deuteranopia".to_string(),
+            ..PaletteConfig::default()
+        };
+        assert_eq!(palette.resolve("ok"), "#0072b2");
+        assert_eq!(palette.resolve("danger"), "#d55e00");
+
+        palette.accessibility = "protanopia".to_string();
+        palette.warn = Some("bright_yellow".to_string());
+        assert_eq!(palette.resolve("danger"), "#e69f00");
+        assert_eq!(palette.resolve("warn"), "bright_yellow");
+
+        // 角色不能指向另一个角色,回退到预设
+        palette.info = Some("ok".to_string());
+        assert_eq!(palette.resolve("info"), "#0072b2");
+    }
+}
@@ -5,12 +5,27 @@
 
 use anyhow::Result;
 
-use super::{ansi_bg, ansi_fg, colorize_segment, reapply_colors, ThemeRenderer, ANSI_RESET};
+use super::contrast::readable_foreground;
+use super::{
+    colorize_segment, group_runs, join_parts, push_separator, run_content, SegmentAnsi,
+    ThemeRenderer, ANSI_RESET,
+};
 use crate::components::{ComponentOutput, RenderContext};
 
 /// Powerline theme renderer
 pub struct PowerlineThemeRenderer;
 
+/// A component ready to be drawn as a powerline segment
+struct PreparedSegment {
+    content: String,
+    /// Sequences of the background color; `None` for fake components drawn
+    /// inline
+    ansi: Option<SegmentAnsi>,
+    preserve_internal: bool,
+    /// Explicit separator from `components.order` placed before the segment
+    separator: Option<String>,
+}
+
 impl PowerlineThemeRenderer {
     const POWERLINE_SEPARATOR: char = '\u{e0b0}';
     const POWERLINE_START: char = '\u{e0d7}';
@@ -70,25 +85,16 @@ impl PowerlineThemeRenderer {
             ));
 
             if !part.is_empty() {
-                parts.push(part);
+                parts.push((part, component.separator.as_deref()));
             }
         }
 
-        parts.join(&colored_separator)
-    }
-
-    fn compose_content(component: &ComponentOutput) -> String {
-        let mut content = String::new();
-        if let Some(ref icon) = component.icon {
-            if !icon.is_empty() {
-                content.push_str(icon);
-                if !component.text.is_empty() {
-                    content.push(' ');
-                }
-            }
-        }
-        content.push_str(&component.text);
-        content
+        join_parts(
+            &parts,
+            &colored_separator,
+            &style.separator_color,
+            supports_colors,
+        )
     }
 
     fn is_fake_component(component: &ComponentOutput) -> bool {
@@ -104,69 +110,45 @@ impl PowerlineThemeRenderer {
         text.contains('█')
             || text.contains('░')
             || text.contains('▓')
+            || component.component_name.as_deref() == Some("status")
             || ["Ready", "Thinking", "Error", "Tool", "Complete"]
                 .iter()
                 .any(|word| text.contains(word))
     }
 
-    fn next_visible_color(
-        segments: &[(String, Option<String>, bool)],
+    /// Background of the next colored segment; `None` when an explicit
+    /// separator comes first, so the arrow ends on the terminal background.
+    fn next_visible_ansi(
+        segments: &[PreparedSegment],
         current_index: usize,
-    ) -> Option<String> {
+    ) -> Option<&SegmentAnsi> {
         segments
             .iter()
             .skip(current_index + 1)
-            .find_map(|(_, color, _)| color.clone())
+            .find(|segment| segment.ansi.is_some() || segment.separator.is_some())
+            .filter(|segment| segment.separator.is_none())
+            .and_then(|segment| segment.ansi.as_ref())
     }
 
-    fn render_segment(
-        content: &str,
-        bg_color: &str,
-        next_bg: Option<&str>,
-        preserve_internal: bool,
-        fg_color: &str,
-    ) -> String {
-        let mut segment = String::new();
-
-        let bg_seq = ansi_bg(bg_color);
-        let fg_seq = ansi_fg(fg_color);
-
-        if let Some(bg) = bg_seq.as_ref() {
-            segment.push_str(bg);
-        }
-        if let Some(fg) = fg_seq.as_ref() {
-            segment.push_str(fg);
-        }
-
-        segment.push(' ');
-        if preserve_internal {
-            if let (Some(bg), Some(fg)) = (bg_seq.as_ref(), fg_seq.as_ref()) {
-                segment.push_str(&reapply_colors(content, bg, fg));
-            } else if let Some(bg) = bg_seq.as_ref() {
-                segment.push_str(&reapply_colors(content, bg, ""));
-            } else {
-                segment.push_str(content);
-            }
-        } else {
-            segment.push_str(content);
-        }
-        segment.push(' ');
+    fn push_start_symbol(out: &mut String, ansi: &SegmentAnsi) {
+        out.push_str(&ansi.edge);
+        out.push(Self::POWERLINE_START);
+        out.push_str(ANSI_RESET);
+    }
 
-        segment.push_str(ANSI_RESET);
-        if let Some(next) = next_bg {
-            if let Some(bg) = ansi_bg(next).as_ref() {
-                segment.push_str(bg);
-            }
-            if let Some(fg) = ansi_fg(bg_color).as_ref() {
-                segment.push_str(fg);
-            }
-        } else if let Some(fg) = ansi_fg(bg_color).as_ref() {
-            segment.push_str(fg);
+    fn push_segment(
+        out: &mut String,
+        segment: &PreparedSegment,
+        ansi: &SegmentAnsi,
+        next: Option<&SegmentAnsi>,
+    ) {
+        ansi.push_body(out, &segment.content, segment.preserve_internal);
+        if let Some(next) = next {
+            out.push_str(&next.bg);
         }
-        segment.push(Self::POWERLINE_SEPARATOR);
-        segment.push_str(ANSI_RESET);
-
-        segment
+        out.push_str(&ansi.edge);
+        out.push(Self::POWERLINE_SEPARATOR);
+        out.push_str(ANSI_RESET);
     }
 }
 
@@ -198,59 +180,64 @@ impl ThemeRenderer for PowerlineThemeRenderer {
             ));
         }
 
+        // Get foreground color from theme config
+        let fg_color = &context.config.themes.powerline.fg;
+
         let mut prepared = Vec::with_capacity(components.len());
         let mut color_iter = colors.iter();
 
-        for component in components {
-            let is_fake = Self::is_fake_component(component);
-            let color = if is_fake {
-                None
-            } else {
-                Some(
-                    color_iter
-                        .next()
-                        .cloned()
-                        .unwrap_or_else(|| "blue".to_string()),
-                )
-            };
-
-            prepared.push((
-                Self::compose_content(component),
-                color,
-                Self::should_preserve_internal_colors(component),
-            ));
+        for run in group_runs(components) {
+            // 分组内每个组件各占一个颜色,整段使用第一个组件的颜色
+            let members = run.iter().filter(|c| !Self::is_fake_component(c)).count();
+            let ansi = (members > 0).then(|| {
+                let color = color_iter
+                    .by_ref()
+                    .take(members)
+                    .fold(None, |first: Option<&String>, color| first.or(Some(color)))
+                    .map_or("blue", String::as_str);
+                let fg = readable_foreground(fg_color, color, &context.config.style);
+                SegmentAnsi::new(color, &fg)
+            });
+
+            prepared.push(PreparedSegment {
+                content: run_content(run),
+                ansi,
+                preserve_internal: run.iter().any(Self::should_preserve_internal_colors),
+                separator: run.first().and_then(|c| c.separator.clone()),
+            });
         }
 
-        // Get foreground color from theme config
-        let fg_color = &context.config.themes.powerline.fg;
-
         // Prepend start symbol (powerline reverse triangle)
-        let mut rendered = String::new();
-        if let Some((_, Some(color), _)) = prepared.iter().find(|(_, color, _)| color.is_some()) {
-            if let Some(fg) = ansi_fg(color).as_ref() {
-                rendered.push_str(fg);
-            }
-            rendered.push(Self::POWERLINE_START);
-            rendered.push_str(ANSI_RESET);
+        let capacity = prepared
+            .iter()
+            .map(|segment| segment.content.len() + 64)
+            .sum();
+        let mut rendered = String::with_capacity(capacity);
+        if let Some(ansi) = prepared.iter().find_map(|segment| segment.ansi.as_ref()) {
+            Self::push_start_symbol(&mut rendered, ansi);
         }
 
-        for idx in 0..prepared.len() {
-            let (ref segment_content, ref color_opt, preserve_internal) = prepared[idx];
-            if color_opt.is_none() {
-                rendered.push_str(segment_content);
-                continue;
+        for (idx, segment) in prepared.iter().enumerate() {
+            // 显式分隔符把状态栏切成几组,每组重新以起始三角开头
+            if let Some(separator) = segment.separator.as_deref().filter(|_| idx > 0) {
+                push_separator(
+                    &mut rendered,
+                    Some(separator),
+                    "",
+                    &context.config.style.separator_color,
+                    supports_colors,
+                );
+                if let Some(ansi) = segment.ansi.as_ref() {
+                    Self::push_start_symbol(&mut rendered, ansi);
+                }
             }
 
-            if let Some(color) = color_opt.as_deref() {
-                let next_color = Self::next_visible_color(&prepared, idx);
-                rendered.push_str(&Self::render_segment(
-                    segment_content,
-                    color,
-                    next_color.as_deref(),
-                    preserve_internal,
-                    fg_color,
-                ));
-            }
+            let Some(ansi) = segment.ansi.as_ref() else {
+                rendered.push_str(&segment.content);
+                continue;
+            };
+            let next = Self::next_visible_ansi(&prepared, idx);
+            Self::push_segment(&mut rendered, segment, ansi, next);
         }
 
         Ok(rendered)
@@ -286,6 +273,7 @@ mod tests {
             input: Arc::new(InputData::default()),
             config: Arc::new(config),
             preview_mode: false,
+            transcript: None,
             terminal: TerminalCapabilities {
                 color_support: if colors {
                     ColorSupport::TrueColor
@@ -294,7 +282,9 @@ mod tests {
                 },
                 supports_emoji: true,
                 supports_nerd_font: nerd_font,
+                ..TerminalCapabilities::default()
             },
+            storage: None,
         }
     }
 
@@ -315,6 +305,29 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_powerline_reapplies_background_inside_progress_bars() -> TestResult {
+        let theme = PowerlineThemeRenderer::new();
+        let ctx = create_test_context(true, true);
+
+        let components = vec![ComponentOutput::preformatted(
+            "\x1b[32m██\x1b[0m░░ 50%".to_string(),
+        )];
+        let colors = vec!["blue".to_string()];
+        let result = theme.render(&components, &colors, &ctx)?;
+
+        let fg = readable_foreground(&ctx.config.themes.powerline.fg, "blue", &ctx.config.style);
+        let ansi = SegmentAnsi::new("blue", &fg);
+        let body = format!("{}{} ", ansi.bg, ansi.text);
+        let reapplied = format!(
+            "\x1b[32m██{ANSI_RESET}{}{}░░ 50% {ANSI_RESET}",
+            ansi.bg, ansi.text
+        );
+        assert!(result.contains(&format!("{body}{}{}{reapplied}", ansi.bg, ansi.text)));
+        assert!(result.ends_with(&format!("{}\u{e0b0}{ANSI_RESET}", ansi.edge)));
+        Ok(())
+    }
+
     #[test]
     fn test_powerline_theme_without_colors() -> TestResult {
         let theme = PowerlineThemeRenderer::new();
@@ -0,0 +1,135 @@
+//! Severity-driven line styling
+//!
+//! 组件通过 `ComponentOutput::severity` 报告自身状态(错误、上下文将满、超预算),
+//! `style.severity_rules` 按整行最严重的状态改变分隔符颜色和背景色调,
+//! 段落很多时也能一眼看到问题。
+
+use super::{ansi_bg_with_support, resolve_color, ANSI_RESET};
+use crate::components::{ColorSupport, ComponentOutput};
+use crate::config::{Severity, SeverityRuleConfig};
+use crate::utils::ansi;
+
+/// Share of the tint blended into segment backgrounds
+const TINT_RATIO: f32 = 0.5;
+
+/// The rule to apply for `components`, if any.
+///
+/// 每条规则只看 `components` 列出的组件(为空时看全部),取其中最严重的状态与
+/// `level` 比较;多条满足时取 `level` 最高的,同级取先声明的。
+#[must_use]
+pub fn select_rule<'a>(
+    rules: &'a [SeverityRuleConfig],
+    components: &[ComponentOutput],
+) -> Option<&'a SeverityRuleConfig> {
+    rules
+        .iter()
+        .filter(|rule| rule.level > Severity::Normal)
+        .filter(|rule| {
+            let worst = components
+                .iter()
+                .filter(|component| {
+                    rule.components.is_empty()
+                        || component
+                            .component_name
+                            .as_ref()
+                            .is_some_and(|name| rule.components.contains(name))
+                })
+                .map(|component| component.severity)
+                .max()
+                .unwrap_or_default();
+            worst >= rule.level
+        })
+        .fold(None, |best: Option<&SeverityRuleConfig>, rule| match best {
+            Some(best) if best.level >= rule.level => Some(best),
+            _ => Some(rule),
+        })
+}
+
+/// Blend `tint` into a segment background color, as a `#rrggbb` string.
+#[must_use]
+pub fn tint_color(color: &str, tint: &str) -> String {
+    let (Some(base), Some(tint)) = (resolve_color(color), resolve_color(tint)) else {
+        return color.to_string();
+    };
+    let mix = |from: u8, to: u8| {
+        let value = (f32::from(to) - f32::from(from)).mul_add(TINT_RATIO, f32::from(from));
+        super::clamp_component(value)
+    };
+    format!(
+        "#{:02x}{:02x}{:02x}",
+        mix(base.0, tint.0),
+        mix(base.1, tint.1),
+        mix(base.2, tint.2)
+    )
+}
+
+/// Paint the whole line on a `tint` background (classic theme).
+#[must_use]
+pub fn tint_line(line: &str, tint: &str, color_support: ColorSupport) -> String {
+    let Some(bg) = ansi_bg_with_support(tint, color_support).filter(|bg| !bg.is_empty()) else {
+        return line.to_string();
+    };
+    // 组件内部的 reset 会清掉背景,之后重新设置
+    let body = ansi::reapply_after_resets(line, &bg);
+    format!("{bg}{body}{ANSI_RESET}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn output(name: &str, severity: Severity) -> ComponentOutput {
+        let mut output = ComponentOutput::new(name).with_severity(severity);
+        output.set_component_name(name);
+        output
+    }
+
+    fn rule(level: Severity, components: &[&str], tint: &str) -> SeverityRuleConfig {
+        SeverityRuleConfig {
+            level,
+            components: components.iter().map(|name| (*name).to_string()).collect(),
+            separator_color: None,
+            tint: Some(tint.to_string()),
+        }
+    }
+
+    #[test]
+    fn most_severe_matching_rule_wins() {
+        let rules = [
+            rule(Severity::Warning, &[], "yellow"),
+            rule(Severity::Critical, &["status"], "red"),
+            rule(Severity::Critical, &[], "magenta"),
+        ];
+
+        let calm = [output("tokens", Severity::Normal)];
+        assert!(select_rule(&rules, &calm).is_none());
+
+        let warning = [output("tokens", Severity::Warning)];
+        let selected = select_rule(&rules, &warning).and_then(|rule| rule.tint.as_deref());
+        assert_eq!(selected, Some("yellow"));
+
+        // status 规则只看 status 组件;tokens 的 critical 落到通用规则
+        let critical_tokens = [
+            output("status", Severity::Warning),
+            output("tokens", Severity::Critical),
+        ];
+        let selected = select_rule(&rules, &critical_tokens).and_then(|rule| rule.tint.as_deref());
+        assert_eq!(selected, Some("magenta"));
+
+        let critical_status = [output("status", Severity::Critical)];
+        let selected = select_rule(&rules, &critical_status).and_then(|rule| rule.tint.as_deref());
+        assert_eq!(selected, Some("red"));
+    }
+
+    #[test]
+    fn tint_blends_colors_and_wraps_line() {
+        assert_eq!(tint_color("#000000", "#ff0000"), "#800000");
+        assert_eq!(tint_color("not-a-color", "red"), "not-a-color");
+
+        let line = format!("a{ANSI_RESET}b");
+        let tinted = tint_line(&line, "#102030", ColorSupport::TrueColor);
+        let bg = "\x1b[48;2;16;32;48m";
+        assert_eq!(tinted, format!("{bg}a{ANSI_RESET}{bg}b{ANSI_RESET}"));
+        assert_eq!(tint_line(&line, "red", ColorSupport::None), line);
+    }
+}
@@ -0,0 +1,123 @@
+//! RTL and wide-character safe segment text
+//!
+//! 希伯来语、阿拉伯语等从右到左的项目名/分支名会被终端的双向算法和相邻的
+//! 分隔符、数字重新排序,把 Powerline 段边界搅乱。`style.bidi_isolation`
+//! 把含 RTL 字符的段落包进 FSI … PDI 隔离区,并去掉名字里自带的方向控制符。
+//! `style.wide_char_padding` 在以双宽字符结尾的段落后补一个空格,避免部分终端
+//! 把紧随其后的 Powerline 箭头画进宽字符的第二列。
+
+use unicode_segmentation::UnicodeSegmentation;
+
+use super::Theme;
+use crate::components::ComponentOutput;
+use crate::config::StyleConfig;
+use crate::utils::{ansi, display_width};
+
+/// FIRST STRONG ISOLATE
+const FSI: char = '\u{2068}';
+/// POP DIRECTIONAL ISOLATE
+const PDI: char = '\u{2069}';
+
+/// Whether `ch` belongs to a right-to-left script
+const fn is_rtl(ch: char) -> bool {
+    matches!(
+        ch,
+        '\u{0590}'..='\u{08ff}'
+            | '\u{fb1d}'..='\u{fdff}'
+            | '\u{fe70}'..='\u{feff}'
+            | '\u{10800}'..='\u{10fff}'
+            | '\u{1e800}'..='\u{1efff}'
+    )
+}
+
+/// Explicit directional formatting characters (embeddings, overrides,
+/// isolates and marks)
+const fn is_bidi_control(ch: char) -> bool {
+    matches!(
+        ch,
+        '\u{061c}' | '\u{200e}' | '\u{200f}' | '\u{202a}'..='\u{202e}' | '\u{2066}'..='\u{2069}'
+    )
+}
+
+/// `text` without directional controls, wrapped in an isolate when it
+/// contains right-to-left characters.
+#[must_use]
+pub fn isolate_bidi(text: &str) -> String {
+    let cleaned: String = text.chars().filter(|ch| !is_bidi_control(*ch)).collect();
+    if cleaned.chars().any(is_rtl) {
+        format!("{FSI}{cleaned}{PDI}")
+    } else {
+        cleaned
+    }
+}
+
+/// Whether the last visible grapheme of `text` is double-width
+#[must_use]
+pub fn ends_with_wide(text: &str) -> bool {
+    ansi::segments(text)
+        .filter_map(|segment| match segment {
+            ansi::Segment::Text(text) => Some(text),
+            ansi::Segment::Escape(_) => None,
+        })
+        .last()
+        .and_then(|text| text.graphemes(true).next_back())
+        .is_some_and(|grapheme| display_width(grapheme) > 1)
+}
+
+/// Apply the configured text safety to rendered segments.
+pub fn secure_segments(outputs: &mut [ComponentOutput], style: &StyleConfig, theme: &str) {
+    let pad_wide = style.wide_char_padding && Theme::from_name(theme) != Theme::Classic;
+    for output in outputs {
+        if style.bidi_isolation
+            && output
+                .text
+                .chars()
+                .any(|ch| is_rtl(ch) || is_bidi_control(ch))
+        {
+            output.text = isolate_bidi(&output.text);
+        }
+        if pad_wide && ends_with_wide(&output.text) {
+            output.text.push(' ');
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rtl_text_is_isolated() {
+        assert_eq!(isolate_bidi("main"), "main");
+        assert_eq!(isolate_bidi("פרויקט-2"), "\u{2068}פרויקט-2\u{2069}");
+        // 自带的方向覆盖符会被去掉,不再影响行内其他段落
+        assert_eq!(isolate_bidi("fix\u{202e}txt.exe"), "fixtxt.exe");
+        assert_eq!(isolate_bidi("\u{2067}مشروع"), "\u{2068}مشروع\u{2069}");
+    }
+
+    #[test]
+    fn wide_endings_detected_through_ansi() {
+        assert!(ends_with_wide("项目"));
+        assert!(ends_with_wide("项目\x1b[0m\x1b[39m"));
+        assert!(!ends_with_wide("项目 main"));
+        assert!(!ends_with_wide(""));
+    }
+
+    #[test]
+    fn segments_secured_per_style() {
+        let style = StyleConfig {
+            wide_char_padding: true,
+            ..StyleConfig::default()
+        };
+        let mut outputs = vec![ComponentOutput::new("אבג"), ComponentOutput::new("项目")];
+
+        secure_segments(&mut outputs, &style, "classic");
+        assert_eq!(outputs[0].text, "\u{2068}אבג\u{2069}");
+        assert_eq!(outputs[1].text, "项目");
+
+        secure_segments(&mut outputs, &style, "powerline");
+        assert_eq!(outputs[1].text, "项目 ");
+        // 已隔离的文本不会被重复包裹
+        assert_eq!(outputs[0].text, "\u{2068}אבג\u{2069}");
+    }
+}
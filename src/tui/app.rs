@@ -6,6 +6,7 @@ use anyhow::{anyhow, Context, Result};
 use ratatui::text::Line;
 use toml_edit::DocumentMut;
 
+use claude_code_statusline_pro::config::backup::backup_config;
 use claude_code_statusline_pro::config::{Config, ConfigLoader, MergeReport};
 
 use crate::tui::io;
@@ -217,7 +218,13 @@ impl App {
     pub async fn from_options(options: EditOptions) -> Result<Self> {
         let document = io::load_or_create(&options.path)?;
         let original = document.clone();
-        let mocks = preview::available_mocks();
+        let mut mocks = preview::available_mocks();
+        // `--mock path/to/payload.json` 不在场景列表里,追加进去才能被选中和轮换
+        if !mocks.contains(&options.mock_scenario)
+            && std::path::Path::new(&options.mock_scenario).is_file()
+        {
+            mocks.push(options.mock_scenario.clone());
+        }
         // --mock 写错名字时不能静默用 index 0(排序后可能不是 dev,
         // 会让用户看到一个和自己输入完全无关的预览场景)。先尝试匹配
         // 用户输入,匹配不到再回退到文档里承诺的默认 `dev`,同时
@@ -807,6 +814,8 @@ impl App {
             self.error(format!("校验失败,未保存: {err}"));
             return Ok(());
         }
+        backup_config(&self.options.path)
+            .with_context(|| format!("备份 {} 失败", self.options.path.display()))?;
         io::save(&self.options.path, &self.document)
             .with_context(|| format!("保存到 {} 失败", self.options.path.display()))?;
         self.original = self.document.clone();
@@ -39,8 +39,7 @@ pub async fn render(
     };
 
     let mut generator = StatuslineGenerator::new(config.clone(), options);
-    let mock_gen = MockDataGenerator::new();
-    let input = mock_gen.generate(mock).unwrap_or_default();
+    let input = MockDataGenerator::new().resolve(mock)?;
 
     let raw = generator.generate(input).await?;
     ansi_to_lines(&raw)
@@ -54,7 +53,7 @@ fn ansi_to_lines(raw: &str) -> Result<Vec<Line<'static>>> {
     Ok(text.lines.into_iter().collect())
 }
 
-/// 可用 mock 场景列表(排序)。
+/// 可用 mock 场景列表(排序),包含用户 `mocks/` 目录下的自定义场景。
 pub fn available_mocks() -> Vec<String> {
     let mut v: Vec<String> = MockDataGenerator::new()
         .available()
@@ -88,7 +87,12 @@ pub fn parse_config(toml_text: &str, inherited: &serde_json::Value) -> Result<Co
             toml_edit::de::from_str(toml_text).map_err(|err| anyhow!("{err}"))?;
         merge_json(&mut merged, overlay);
     }
-    serde_json::from_value(merged).map_err(|err| anyhow!("{err}"))
+    let config: Config = serde_json::from_value(merged).map_err(|err| anyhow!("{err}"))?;
+    // 组件表按需反序列化,编辑器保存前要逐个检查,不能等渲染时静默回退默认值
+    if let Some((name, err)) = config.components.section_errors().into_iter().next() {
+        return Err(anyhow!("components.{name}: {err}"));
+    }
+    Ok(config)
 }
 
 /// 返回点路径 `dotted`(形如 `components.project.enabled`)的"最终生效值"
@@ -192,6 +192,36 @@ pub static SECTIONS: &[Section] = &[
                 kind: FieldKind::Bool,
                 help: "项目名为空时是否显示。",
             },
+            Field {
+                label: "show_relative_dir",
+                path: "components.project.show_relative_dir",
+                kind: FieldKind::Bool,
+                help: "在项目根目录下的子目录工作时显示 repo/packages/web(monorepo)。",
+            },
+            Field {
+                label: "relative_depth",
+                path: "components.project.relative_depth",
+                kind: FieldKind::Int { min: 1, max: 6 },
+                help: "显示项目根目录下的几级目录。",
+            },
+            Field {
+                label: "max_length",
+                path: "components.project.max_length",
+                kind: FieldKind::Int { min: 0, max: 120 },
+                help: "项目文本最大宽度,0=不限;超长时保留末尾。",
+            },
+            Field {
+                label: "source",
+                path: "components.project.source",
+                kind: FieldKind::Enum(&["directory", "manifest", "remote"]),
+                help: "directory=目录名,manifest=读取 Cargo.toml/package.json/pyproject.toml 包名,remote=git 远程仓库名。",
+            },
+            Field {
+                label: "show_version",
+                path: "components.project.show_version",
+                kind: FieldKind::Bool,
+                help: "manifest 模式下追加版本号。",
+            },
         ],
     },
     // ============== 模型组件 ==============
@@ -241,6 +271,18 @@ pub static SECTIONS: &[Section] = &[
                 kind: FieldKind::Bool,
                 help: "显示完整模型名(Sonnet 4.5)而非缩写(S4.5)。",
             },
+            Field {
+                label: "show_context_window",
+                path: "components.model.show_context_window",
+                kind: FieldKind::Bool,
+                help: "在模型名后追加上下文窗口大小(·1M / ·200K)。",
+            },
+            Field {
+                label: "switch_notice_renders",
+                path: "components.model.switch_notice_renders",
+                kind: FieldKind::Int { min: 0, max: 100 },
+                help: "会话中途切换模型后显示 ↻ 提示的渲染次数,0 为关闭。",
+            },
         ],
     },
     // ============== 分支组件 ==============
@@ -351,12 +393,30 @@ pub static SECTIONS: &[Section] = &[
                 kind: FieldKind::Bool,
                 help: "显示百分比数值。",
             },
+            Field {
+                label: "show_numbers",
+                path: "components.tokens.show_numbers",
+                kind: FieldKind::Bool,
+                help: "显示 (used/total) 数量;关闭后只保留百分比/进度条。",
+            },
+            Field {
+                label: "unit",
+                path: "components.tokens.unit",
+                kind: FieldKind::Enum(&["k", "m", "raw", "auto"]),
+                help: "数量单位:k=45.2k,m=0.05M,raw=原始数字,auto=按大小选择。",
+            },
             Field {
                 label: "show_raw_numbers",
                 path: "components.tokens.show_raw_numbers",
                 kind: FieldKind::Bool,
                 help: "显示 (used/total) 原始数字。",
             },
+            Field {
+                label: "format",
+                path: "components.tokens.format",
+                kind: FieldKind::Text,
+                help: "文本模板,如 \"{percent}% of {window}\";不含占位符时使用默认布局。",
+            },
             Field {
                 label: "show_gradient",
                 path: "components.tokens.show_gradient",
@@ -369,6 +429,24 @@ pub static SECTIONS: &[Section] = &[
                 kind: FieldKind::Int { min: 4, max: 60 },
                 help: "进度条字符宽度。",
             },
+            Field {
+                label: "progress_style",
+                path: "components.tokens.progress_style",
+                kind: FieldKind::Enum(&["blocks", "braille", "dots", "line"]),
+                help: "进度条样式;braille/line 有半格精度,纯文本终端回退为 #/-。",
+            },
+            Field {
+                label: "critical_emphasis",
+                path: "components.tokens.critical_emphasis",
+                kind: FieldKind::Enum(&["none", "reverse", "bold"]),
+                help: "超过 critical 阈值时强调整段:reverse=反色,bold=红色加粗。",
+            },
+            Field {
+                label: "critical_bell",
+                path: "components.tokens.critical_bell",
+                kind: FieldKind::Bool,
+                help: "每个会话首次进入临界区时响铃一次。",
+            },
             Field {
                 label: "warning (%)",
                 path: "components.tokens.thresholds.warning",
@@ -497,6 +575,48 @@ pub static SECTIONS: &[Section] = &[
                 kind: FieldKind::Bool,
                 help: "显示最近的错误片段。",
             },
+            Field {
+                label: "show_error_count",
+                path: "components.status.show_error_count",
+                kind: FieldKind::Bool,
+                help: "连续多轮失败时显示 Error ×N。",
+            },
+            Field {
+                label: "error_escalation_threshold",
+                path: "components.status.error_escalation_threshold",
+                kind: FieldKind::Int { min: 2, max: 20 },
+                help: "连续错误达到该次数后改用 colors.error_critical。",
+            },
+            Field {
+                label: "details_inline_max_width",
+                path: "components.status.details_inline_max_width",
+                kind: FieldKind::Int { min: 0, max: 200 },
+                help: "错误详情超过该宽度时换到下方单独显示,0 为始终内联。",
+            },
+            Field {
+                label: "details_max_lines",
+                path: "components.status.details_max_lines",
+                kind: FieldKind::Int { min: 1, max: 10 },
+                help: "错误详情换行显示时的最大行数。",
+            },
+            Field {
+                label: "show_tool_elapsed",
+                path: "components.status.show_tool_elapsed",
+                kind: FieldKind::Bool,
+                help: "工具运行中显示已耗时,如 Tool (Bash · 12s)。",
+            },
+            Field {
+                label: "tool_warning_seconds",
+                path: "components.status.tool_warning_seconds",
+                kind: FieldKind::Int { min: 0, max: 3600 },
+                help: "工具运行超过该秒数后改用警告色,0 为不提示。",
+            },
+            Field {
+                label: "waiting_after_seconds",
+                path: "components.status.waiting_after_seconds",
+                kind: FieldKind::Int { min: 0, max: 3600 },
+                help: "工具超过该秒数仍无结果时显示 Waiting(等待权限确认),0 为关闭。",
+            },
         ],
     },
     // ============== 主题 ==============
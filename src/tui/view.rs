@@ -710,6 +710,9 @@ fn render_merge_report(frame: &mut Frame, area: Rect, app: &App) {
                     ConfigSourceType::User => "用户级",
                     ConfigSourceType::Project => "项目级",
                     ConfigSourceType::Custom => "自定义",
+                    ConfigSourceType::Component => "组件文件",
+                    ConfigSourceType::ProjectPattern => "目录规则",
+                    ConfigSourceType::Repository => "仓库级",
                 };
                 let path_str = layer
                     .path
@@ -0,0 +1,474 @@
+//! ANSI-aware string helpers
+//!
+//! 组件与主题会在文本里嵌入 SGR(颜色、粗体)、OSC(超链接、标题)以及 APC 图片
+//! (kitty 图形协议)序列。这里把文本拆成转义序列与可见文本两类片段:宽度按可见
+//! 字形计算,内联图片按它占的列数计算;截取、截断既不切开序列也不切开字形,截断处
+//! 补上 reset 与链接结束符;主题在嵌入的 reset 之后重新
+//! 套用背景色、强调样式也走这里,而不是各自查找 `ESC[0m`。
+
+use unicode_segmentation::UnicodeSegmentation;
+
+use super::width::{display_width, grapheme_width};
+use crate::config::SegmentAlign;
+
+/// SGR reset
+pub const RESET: &str = "\x1b[0m";
+
+/// OSC 8 sequence closing the current hyperlink
+const LINK_CLOSE: &str = "\x1b]8;;\x1b\\";
+
+/// Piece of a string carrying escape sequences
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Segment<'a> {
+    /// Visible text between escape sequences
+    Text(&'a str),
+    /// One complete escape sequence (CSI, OSC, APC/DCS/PM/SOS or two-byte
+    /// `ESC x`); a kitty image sent in several chunks is one segment
+    Escape(&'a str),
+}
+
+/// Iterator over the [`Segment`]s of a string
+#[derive(Debug, Clone)]
+pub struct Segments<'a> {
+    rest: &'a str,
+}
+
+impl<'a> Iterator for Segments<'a> {
+    type Item = Segment<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.rest.is_empty() {
+            return None;
+        }
+        let (segment, len) = if self.rest.starts_with('\x1b') {
+            let len = image_chunks_len(self.rest, escape_len(self.rest));
+            (Segment::Escape(&self.rest[..len]), len)
+        } else {
+            let len = self.rest.find('\x1b').unwrap_or(self.rest.len());
+            (Segment::Text(&self.rest[..len]), len)
+        };
+        self.rest = &self.rest[len..];
+        Some(segment)
+    }
+}
+
+/// Split `text` into escape sequences and visible text.
+#[must_use]
+pub const fn segments(text: &str) -> Segments<'_> {
+    Segments { rest: text }
+}
+
+/// Whether `sequence` is an SGR reset (`ESC[0m` or `ESC[m`)
+#[must_use]
+pub fn is_reset(sequence: &str) -> bool {
+    matches!(sequence, "\x1b[0m" | "\x1b[m")
+}
+
+/// `text` without escape sequences.
+#[must_use]
+pub fn strip_sgr(text: &str) -> String {
+    if !text.contains('\x1b') {
+        return text.to_string();
+    }
+    segments(text)
+        .filter_map(|segment| match segment {
+            Segment::Text(text) => Some(text),
+            Segment::Escape(_) => None,
+        })
+        .collect()
+}
+
+/// Return the number of terminal columns `text` occupies, ignoring escape
+/// sequences.
+#[must_use]
+pub fn visible_width(text: &str) -> usize {
+    segments(text)
+        .map(|segment| match segment {
+            Segment::Text(text) => display_width(text),
+            Segment::Escape(sequence) => escape_width(sequence),
+        })
+        .sum()
+}
+
+/// Pad `text` with spaces to at least `min_width` visible columns.
+#[must_use]
+pub fn pad_to_width(text: &str, min_width: usize, align: SegmentAlign) -> String {
+    let missing = min_width.saturating_sub(visible_width(text));
+    if missing == 0 {
+        return text.to_string();
+    }
+    let (left, right) = match align {
+        SegmentAlign::Left => (0, missing),
+        SegmentAlign::Center => (missing / 2, missing - missing / 2),
+        SegmentAlign::Right => (missing, 0),
+    };
+    format!("{}{text}{}", " ".repeat(left), " ".repeat(right))
+}
+
+/// Visible columns `start..end` of `text`.
+///
+/// Escape sequences before the end are kept so the slice carries the styles
+/// in effect; a wide cluster straddling either edge is dropped. Styles and
+/// hyperlinks left open at a cut are closed.
+#[must_use]
+pub fn slice(text: &str, start: usize, end: usize) -> String {
+    cut(text, start, end, usize::MAX)
+}
+
+/// Cut a rendered line to at most `max_width` columns and `max_bytes` bytes;
+/// 0 disables either limit.
+///
+/// Grapheme clusters are never split. When the line is cut, colors and an
+/// open OSC 8 hyperlink are closed so they do not leak into the terminal; the
+/// closing sequences count towards `max_bytes`.
+#[must_use]
+pub fn truncate_rendered(line: &str, max_width: usize, max_bytes: usize) -> String {
+    let max_width = if max_width == 0 {
+        usize::MAX
+    } else {
+        max_width
+    };
+    let max_bytes = if max_bytes == 0 {
+        usize::MAX
+    } else {
+        max_bytes
+    };
+    if line.len() <= max_bytes && visible_width(line) <= max_width {
+        return line.to_string();
+    }
+    cut(line, 0, max_width, max_bytes)
+}
+
+/// Re-emit `sequence` after every reset inside `text`, so a style wrapped
+/// around the whole text survives the resets of embedded colors.
+#[must_use]
+pub fn reapply_after_resets(text: &str, sequence: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for segment in segments(text) {
+        match segment {
+            Segment::Escape(escape) if is_reset(escape) => {
+                out.push_str(escape);
+                out.push_str(sequence);
+            }
+            Segment::Escape(piece) | Segment::Text(piece) => out.push_str(piece),
+        }
+    }
+    out
+}
+
+/// Whether `text` contains an SGR reset
+#[must_use]
+pub fn contains_reset(text: &str) -> bool {
+    text.contains('\x1b')
+        && segments(text)
+            .any(|segment| matches!(segment, Segment::Escape(escape) if is_reset(escape)))
+}
+
+/// Columns `start..end` of `line`, at most `max_bytes` long including the
+/// closing sequences
+fn cut(line: &str, start: usize, end: usize, max_bytes: usize) -> String {
+    let mut result = String::with_capacity(line.len().min(max_bytes));
+    let mut column = 0;
+    let mut styled = false;
+    let mut link_open = false;
+    let mut truncated = false;
+    let closing_len = |styled: bool, link_open: bool| {
+        usize::from(styled) * RESET.len() + usize::from(link_open) * LINK_CLOSE.len()
+    };
+
+    'outer: for segment in segments(line) {
+        match segment {
+            Segment::Escape(sequence) if escape_width(sequence) > 0 => {
+                // 内联图片与字形一样占列,放不下就整体丢弃
+                let width = escape_width(sequence);
+                if column + width > end
+                    || result.len() + sequence.len() + closing_len(styled, link_open) > max_bytes
+                {
+                    truncated = true;
+                    break;
+                }
+                if column >= start {
+                    result.push_str(sequence);
+                }
+                column += width;
+            }
+            Segment::Escape(sequence) => {
+                let (next_styled, next_link) = sequence.strip_prefix("\x1b]8;").map_or_else(
+                    || {
+                        let styled =
+                            !is_reset(sequence) && (styled || sequence.starts_with("\x1b["));
+                        (styled, link_open)
+                    },
+                    |body| (styled, opens_link(body)),
+                );
+                if result.len() + sequence.len() + closing_len(next_styled, next_link) > max_bytes {
+                    truncated = true;
+                    break;
+                }
+                result.push_str(sequence);
+                (styled, link_open) = (next_styled, next_link);
+            }
+            Segment::Text(text) => {
+                for grapheme in text.graphemes(true) {
+                    let width = grapheme_width(grapheme);
+                    if column + width > end
+                        || result.len() + grapheme.len() + closing_len(styled, link_open)
+                            > max_bytes
+                    {
+                        truncated = true;
+                        break 'outer;
+                    }
+                    if column >= start {
+                        result.push_str(grapheme);
+                    }
+                    column += width;
+                }
+            }
+        }
+    }
+
+    if truncated {
+        if link_open {
+            result.push_str(LINK_CLOSE);
+        }
+        if styled {
+            result.push_str(RESET);
+        }
+    }
+    result
+}
+
+/// Whether the body of an OSC 8 sequence (`params;url` plus its terminator)
+/// starts a link; an empty url ends the current one
+fn opens_link(body: &str) -> bool {
+    let body = body
+        .strip_suffix("\x1b\\")
+        .or_else(|| body.strip_suffix('\x07'))
+        .unwrap_or(body);
+    body.split_once(';').is_some_and(|(_, url)| !url.is_empty())
+}
+
+/// Byte length of the escape sequence at the start of `text`; an unterminated
+/// sequence runs to the end
+fn escape_len(text: &str) -> usize {
+    let bytes = text.as_bytes();
+    match bytes.get(1) {
+        // CSI:参数与中间字节之后以 0x40–0x7E 结束
+        Some(b'[') => bytes[2..]
+            .iter()
+            .position(|byte| (0x40..=0x7e).contains(byte))
+            .map_or(text.len(), |end| end + 3),
+        // OSC:以 BEL 或 ST(ESC \)结束
+        Some(b']') => match (text.find('\x07'), text[1..].find("\x1b\\")) {
+            (Some(bel), Some(st)) if st + 1 < bel => st + 3,
+            (Some(bel), _) => bel + 1,
+            (None, Some(st)) => st + 3,
+            (None, None) => text.len(),
+        },
+        // APC / DCS / PM / SOS:以 ST 结束,kitty 图片的 base64 数据都在里面
+        Some(b'_' | b'P' | b'^' | b'X') => text[1..].find("\x1b\\").map_or(text.len(), |st| st + 3),
+        Some(_) => 1 + text[1..].chars().next().map_or(0, char::len_utf8),
+        None => 1,
+    }
+}
+
+/// Byte length of a kitty image starting with the `first_len`-byte escape
+/// at the start of `text`: chunks marked `m=1` continue in the next APC
+fn image_chunks_len(text: &str, first_len: usize) -> usize {
+    let mut len = first_len;
+    let mut chunk = &text[..first_len];
+    while kitty_control(chunk).is_some_and(|control| control.split(',').any(|key| key == "m=1"))
+        && text[len..].starts_with("\x1b_G")
+    {
+        let next = escape_len(&text[len..]);
+        chunk = &text[len..len + next];
+        len += next;
+    }
+    len
+}
+
+/// Control data (`key=value,…`) of a kitty graphics APC
+fn kitty_control(sequence: &str) -> Option<&str> {
+    let body = sequence.strip_prefix("\x1b_G")?;
+    Some(body.split_once(';').map_or(body, |(control, _)| control))
+}
+
+/// Columns an inline image escape occupies: `c=` of a kitty image shown
+/// with `a=T` or placed with `a=p`, or `width=` of an inline iTerm2 file; 0
+/// for other escapes
+fn escape_width(sequence: &str) -> usize {
+    kitty_columns(sequence)
+        .or_else(|| iterm_columns(sequence))
+        .and_then(|columns| columns.parse().ok())
+        .unwrap_or(0)
+}
+
+/// `c=` of a kitty image APC that is displayed and moves the cursor
+fn kitty_columns(sequence: &str) -> Option<&str> {
+    let control = kitty_control(sequence)?;
+    let value = |key| param(control, ',', key);
+    // a=T(传输并显示)与 a=p(按编号放置)才会显示;C=1 不移动光标,不占列
+    (matches!(value("a"), Some("T" | "p")) && value("C") != Some("1"))
+        .then(|| value("c"))
+        .flatten()
+}
+
+/// `width=` of an inline iTerm2 file OSC
+fn iterm_columns(sequence: &str) -> Option<&str> {
+    let body = sequence.strip_prefix("\x1b]1337;File=")?;
+    let args = body.split_once(':').map_or(body, |(args, _)| args);
+    (param(args, ';', "inline") == Some("1"))
+        .then(|| param(args, ';', "width"))
+        .flatten()
+}
+
+/// Value of `key` in a `key=value` list split by `separator`
+fn param<'a>(params: &'a str, separator: char, key: &str) -> Option<&'a str> {
+    params
+        .split(separator)
+        .find_map(|param| param.split_once('=').filter(|(name, _)| *name == key))
+        .map(|(_, value)| value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn segments_separate_escapes_from_text() {
+        let parts: Vec<Segment<'_>> =
+            segments("\x1b[31mred\x1b]8;;u\x1b\\link\x1b]0;t\x07\x1b[m").collect();
+        assert_eq!(
+            parts,
+            [
+                Segment::Escape("\x1b[31m"),
+                Segment::Text("red"),
+                Segment::Escape("\x1b]8;;u\x1b\\"),
+                Segment::Text("link"),
+                Segment::Escape("\x1b]0;t\x07"),
+                Segment::Escape("\x1b[m"),
+            ]
+        );
+        assert_eq!(segments("").count(), 0);
+        // 未结束的序列吞到末尾,不会被当成可见文本
+        assert_eq!(visible_width("ok\x1b[38;5"), 2);
+    }
+
+    #[test]
+    fn visible_width_skips_sgr_sequences() {
+        assert_eq!(visible_width("\x1b[38;2;1;2;3m██\x1b[0m 45%"), 6);
+        assert_eq!(visible_width("\x1b[1m功能\x1b[22m"), 4);
+    }
+
+    #[test]
+    fn visible_width_skips_hyperlinks() {
+        let link = "\x1b]8;;https://linear.app/t/ENG-42\x1b\\ENG-42\x1b]8;;\x1b\\";
+        assert_eq!(strip_sgr(link), "ENG-42");
+        assert_eq!(visible_width(link), 6);
+        assert_eq!(strip_sgr("\x1b]0;title\x07main"), "main");
+    }
+
+    #[test]
+    fn padding_follows_alignment() {
+        assert_eq!(pad_to_width("4.3%", 6, SegmentAlign::Right), "  4.3%");
+        assert_eq!(pad_to_width("4.3%", 6, SegmentAlign::Left), "4.3%  ");
+        assert_eq!(pad_to_width("$1", 5, SegmentAlign::Center), " $1  ");
+        assert_eq!(pad_to_width("功能", 5, SegmentAlign::Right), " 功能");
+        assert_eq!(pad_to_width("too wide", 3, SegmentAlign::Right), "too wide");
+        assert_eq!(
+            pad_to_width("\x1b[32mok\x1b[0m", 4, SegmentAlign::Left),
+            "\x1b[32mok\x1b[0m  "
+        );
+    }
+
+    #[test]
+    fn slices_keep_styles_in_effect() {
+        let line = "\x1b[32mab功能\x1b[0mcd";
+        assert_eq!(slice(line, 0, 2), "\x1b[32mab\x1b[0m");
+        assert_eq!(slice(line, 2, 6), "\x1b[32m功能\x1b[0m");
+        // 跨边界的宽字符整体丢弃
+        assert_eq!(slice(line, 3, 8), "\x1b[32m能\x1b[0mcd");
+        assert_eq!(slice(line, 0, 100), line);
+    }
+
+    #[test]
+    fn rendered_lines_cut_by_width_keep_ansi_balanced() {
+        let line = format!("\x1b[32m{}\x1b[0m | 功能", "x".repeat(10_000));
+        let cut = truncate_rendered(&line, 80, 0);
+        assert_eq!(visible_width(&cut), 80);
+        assert!(cut.starts_with("\x1b[32mxxx"));
+        assert!(cut.ends_with("x\x1b[0m"));
+
+        // 宽字符不会被切开
+        let cut = truncate_rendered("ab功能", 3, 0);
+        assert_eq!(cut, "ab");
+        // 未超限时原样返回
+        assert_eq!(truncate_rendered(&line, 0, 0), line);
+        assert_eq!(truncate_rendered("main", 10, 10), "main");
+    }
+
+    #[test]
+    fn rendered_lines_cut_by_bytes_include_closing_sequences() {
+        let wide = format!("\x1b[1;31m{}\x1b[0m", "项目".repeat(5_000));
+        let cut = truncate_rendered(&wide, 0, 100);
+        assert!(cut.len() <= 100, "{}", cut.len());
+        assert!(cut.ends_with("\x1b[0m"));
+        assert_eq!(visible_width(&cut) % 2, 0);
+
+        let link = "\x1b]8;;https://linear.app/t/ENG-42\x1b\\ENG-42\x1b]8;;\x1b\\ done";
+        let cut = truncate_rendered(link, 3, 0);
+        assert_eq!(strip_sgr(&cut), "ENG");
+        assert!(cut.ends_with("ENG\x1b]8;;\x1b\\"));
+        // 已关闭的链接不再重复关闭
+        let cut = truncate_rendered(link, 7, 0);
+        assert!(cut.ends_with("ENG-42\x1b]8;;\x1b\\ "), "{cut:?}");
+    }
+
+    #[test]
+    fn graphics_icons_are_one_escape_of_their_columns() {
+        let kitty = "\x1b_Gf=100,a=T,c=2,r=1,q=2,m=1;iVBORw0K\x1b\\\x1b_Gm=0;GgoAAA==\x1b\\";
+        assert_eq!(
+            segments(kitty).collect::<Vec<_>>(),
+            [Segment::Escape(kitty)]
+        );
+        assert_eq!(visible_width(kitty), 2);
+        assert_eq!(strip_sgr(&format!("{kitty} main")), " main");
+        let iterm =
+            "\x1b]1337;File=inline=1;size=12;width=2;height=1;preserveAspectRatio=1:aGk=\x07";
+        assert_eq!(visible_width(iterm), 2);
+        // 其它 DCS / 未结束的 APC 不占列,也不会被当成可见文本
+        assert_eq!(visible_width("\x1bPq#0;2;0;0;0\x1b\\ok"), 2);
+        assert_eq!(visible_width("ok\x1b_Ga=T,c=2;iVBOR"), 4);
+        assert_eq!(visible_width("\x1b_Ga=t,i=7;AAAA\x1b\\"), 0);
+        assert_eq!(visible_width("\x1b_Ga=p,i=7,c=2,r=1,q=2\x1b\\"), 2);
+    }
+
+    #[test]
+    fn rendered_lines_never_cut_inside_graphics_icons() {
+        let icon = "\x1b_Gf=100,a=T,c=2,r=1,q=2,m=0;iVBORw0KGgoAAAANSUhEUgAA\x1b\\";
+        let line = format!("\x1b[32m{icon} main\x1b[0m");
+        assert_eq!(visible_width(&line), 7);
+        assert_eq!(
+            truncate_rendered(&line, 4, 0),
+            format!("\x1b[32m{icon} m\x1b[0m")
+        );
+        // 图标放不下时整体丢弃,而不是露出半截 base64
+        assert_eq!(truncate_rendered(&line, 1, 0), "\x1b[32m\x1b[0m");
+        assert_eq!(truncate_rendered(&line, 0, 20), "\x1b[32m\x1b[0m");
+        assert_eq!(slice(&line, 2, 7), "\x1b[32m main\x1b[0m");
+    }
+
+    #[test]
+    fn styles_reapplied_after_every_reset() {
+        assert_eq!(
+            reapply_after_resets("a\x1b[0mb\x1b[mc", "\x1b[2m"),
+            "a\x1b[0m\x1b[2mb\x1b[m\x1b[2mc"
+        );
+        assert_eq!(
+            reapply_after_resets("\x1b[31mplain", "\x1b[2m"),
+            "\x1b[31mplain"
+        );
+        assert!(contains_reset("x\x1b[m"));
+        assert!(!contains_reset("\x1b[31mx"));
+    }
+}
@@ -0,0 +1,112 @@
+//! ASCII transliteration for `terminal.ascii_only`
+//!
+//! `--force-text` 只替换图标,进度条、分隔符、徽标等字符仍可能是多字节的。
+//! 严格 ASCII 模式在输出前把每个非 ASCII 字符换成 ASCII 对应物:常见符号按表
+//! 替换,零宽字符直接删除,其余字符变为 `?`。ANSI 转义序列本身是 ASCII,不受影响。
+
+use unicode_width::UnicodeWidthChar;
+
+/// Characters and their ASCII replacement
+const REPLACEMENTS: &[(&str, &str)] = &[
+    // 标点与箭头
+    ("\u{a0}\u{2002}\u{2003}\u{2009}\u{3000}", " "),
+    ("·•∙。", "."),
+    ("…", "..."),
+    ("‘’′", "'"),
+    ("“”″", "\""),
+    ("→⟶➜", "->"),
+    ("←", "<-"),
+    ("↑▲△⬆", "^"),
+    ("↓▼▽⬇", "v"),
+    ("↻↺⟳", "~"),
+    ("×✕✗✘❌", "x"),
+    ("⚠❗‼", "!"),
+    ("⏸", "||"),
+    ("，", ","),
+    ("：", ":"),
+    // 制表符与分隔符
+    ("│┃║▏▕⡇", "|"),
+    ("–—−─━═░⣀\u{2800}", "-"),
+    ("└┌┐┘├┤┬┴┼╰╭╮╯┏┓┗┛┣┫┳┻╋╔╗╚╝╠╣╦╩╬✓✔✅", "+"),
+    // 进度条与状态点
+    ("█▉▊▋▌▍▎▓▒⣿⣶⣤", "#"),
+    ("●◉■◼◆◈◇★☆⭐", "*"),
+    ("○◯□◻◐◑◒◓", "o"),
+    // Powerline / 胶囊主题分隔符
+    ("\u{e0b0}\u{e0b1}\u{e0bc}", ">"),
+    ("\u{e0b2}\u{e0b3}\u{e0be}", "<"),
+    ("\u{e0b4}\u{e0b5}）", ")"),
+    ("\u{e0b6}\u{e0b7}（", "("),
+    ("\u{e0d7}", ""),
+    // 货币
+    ("€", "EUR"),
+    ("£", "GBP"),
+    ("¥￥", "Y"),
+];
+
+/// ASCII replacement of a single character
+fn replacement(ch: char) -> Option<String> {
+    // 带圈数字 ①..⑳
+    if let Some(offset) = u32::from(ch)
+        .checked_sub(0x2460)
+        .filter(|offset| *offset < 20)
+    {
+        return Some((offset + 1).to_string());
+    }
+    REPLACEMENTS
+        .iter()
+        .find(|(chars, _)| chars.contains(ch))
+        .map(|(_, ascii)| (*ascii).to_string())
+}
+
+/// Whether `ch` has no visible width on its own (ZWJ, variation selectors,
+/// combining marks, skin tone modifiers)
+fn is_invisible(ch: char) -> bool {
+    matches!(ch, '\u{1f3fb}'..='\u{1f3ff}') || ch.width().unwrap_or(0) == 0
+}
+
+/// `text` with every non-ASCII character replaced by an ASCII equivalent.
+#[must_use]
+pub fn to_ascii(text: &str) -> String {
+    if text.is_ascii() {
+        return text.to_string();
+    }
+    let mut ascii = String::with_capacity(text.len());
+    for ch in text.chars() {
+        if ch.is_ascii() {
+            ascii.push(ch);
+        } else if let Some(replacement) = replacement(ch) {
+            ascii.push_str(&replacement);
+        } else if !is_invisible(ch) {
+            ascii.push('?');
+        }
+    }
+    ascii
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn replaces_glyphs_with_ascii() {
+        assert_eq!(to_ascii("plain | text"), "plain | text");
+        assert_eq!(to_ascii("████░░ 60%"), "####-- 60%");
+        assert_eq!(to_ascii("⣿⣿⡇⣀ · €1.20"), "##|- . EUR1.20");
+        assert_eq!(to_ascii("↻ Opus→Sonnet ②"), "~ Opus->Sonnet 2");
+        assert_eq!(to_ascii("\u{e0b6}a\u{e0b4}\u{e0b0}"), "(a)>");
+        // 各种边框样式的角都变成 `+`
+        assert_eq!(to_ascii("╭─╮┏━┓╔═╗"), "+-++-++-+");
+        assert_eq!(to_ascii("╰─╯┗━┛╚═╝"), "+-++-++-+");
+    }
+
+    #[test]
+    fn drops_invisible_and_masks_unknown() {
+        // ZWJ 组合的 emoji 只留下可见字符对应的 `?`
+        assert_eq!(to_ascii("👨\u{200d}👩 ok"), "?? ok");
+        assert_eq!(to_ascii("⚡\u{fe0f}"), "?");
+        assert_eq!(to_ascii("e\u{301}"), "e");
+        assert_eq!(to_ascii("项目"), "??");
+        assert!(to_ascii("\x1b[31m⚠ x\x1b[0m").is_ascii());
+    }
+}
@@ -0,0 +1,120 @@
+//! Localization of built-in strings
+//!
+//! 内置字符串以英文原文作为键(gettext 风格):按 `language` 先查用户在
+//! `[translations.<lang>]` 中提供的译文,再查内置表,都没有时原样返回。
+//! `zh-CN`、`zh_CN.UTF-8` 之类的写法会依次尝试完整标签和主语言 `zh`。
+
+use crate::config::Config;
+
+/// Built-in Chinese translations
+const ZH: &[(&str, &str)] = &[
+    // 状态组件
+    ("Ready", "就绪"),
+    ("Thinking", "思考中"),
+    ("Tool", "工具"),
+    ("Error", "错误"),
+    ("Waiting", "等待中"),
+    ("Warning", "警告"),
+    ("Max Tokens", "输出上限"),
+    ("Token limit reached", "已达到 token 上限"),
+    ("403 quota insufficient", "403 额度不足"),
+    ("Filter error", "内容过滤错误"),
+    // 分支组件
+    ("no-git", "无 Git"),
+];
+
+/// Built-in table of a primary language subtag; English needs none
+fn builtin(language: &str) -> &'static [(&'static str, &'static str)] {
+    match language {
+        "zh" => ZH,
+        _ => &[],
+    }
+}
+
+/// Language tags to try, most specific first: `zh_CN.UTF-8` → `zh-cn`, `zh`
+fn language_candidates(language: &str) -> Vec<String> {
+    let tag = language
+        .split('.')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .replace('_', "-")
+        .to_ascii_lowercase();
+    let primary = tag.split('-').next().unwrap_or_default().to_string();
+    let mut candidates = vec![tag.clone()];
+    if primary != tag {
+        candidates.push(primary);
+    }
+    candidates.retain(|candidate| !candidate.is_empty());
+    candidates
+}
+
+/// `text` in the configured language.
+#[must_use]
+pub fn translate<'a>(config: &'a Config, text: &'a str) -> &'a str {
+    let candidates = language_candidates(&config.language);
+
+    let user = candidates.iter().find_map(|language| {
+        config
+            .translations
+            .iter()
+            .find(|(key, _)| key.replace('_', "-").eq_ignore_ascii_case(language))
+            .and_then(|(_, table)| table.get(text))
+    });
+    if let Some(translated) = user {
+        return translated;
+    }
+
+    candidates
+        .iter()
+        .find_map(|language| {
+            builtin(language)
+                .iter()
+                .find(|(source, _)| *source == text)
+                .map(|(_, translated)| *translated)
+        })
+        .unwrap_or(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn config(language: &str) -> Config {
+        Config {
+            language: language.to_string(),
+            ..Config::default()
+        }
+    }
+
+    #[test]
+    fn builtin_tables_follow_language() {
+        assert_eq!(translate(&config("en"), "Ready"), "Ready");
+        assert_eq!(translate(&config("zh"), "Ready"), "就绪");
+        assert_eq!(translate(&config("zh_CN.UTF-8"), "no-git"), "无 Git");
+        // 没有译文的字符串原样返回
+        assert_eq!(translate(&config("zh"), "feature/x"), "feature/x");
+        assert_eq!(translate(&config("fr"), "Ready"), "Ready");
+    }
+
+    #[test]
+    fn user_translations_take_precedence() {
+        let mut config = config("ja-JP");
+        config.translations.insert(
+            "ja".to_string(),
+            HashMap::from([("Ready".to_string(), "準備完了".to_string())]),
+        );
+        config.translations.insert(
+            "zh".to_string(),
+            HashMap::from([("Ready".to_string(), "空闲".to_string())]),
+        );
+
+        assert_eq!(translate(&config, "Ready"), "準備完了");
+        assert_eq!(translate(&config, "Thinking"), "Thinking");
+
+        config.language = "zh".to_string();
+        assert_eq!(translate(&config, "Ready"), "空闲");
+        assert_eq!(translate(&config, "Thinking"), "思考中");
+    }
+}
@@ -1,10 +1,25 @@
 //! 实用工具模块
 //!
-//! 包含跨平台 home 目录解析和模型 ID 解析等辅助函数。
+//! 包含跨平台 home 目录解析、模型 ID 解析、数字格式化、内置字符串翻译、ASCII 转写、终端控制字符清理、ANSI 序列处理和终端显示宽度计算等辅助函数。
 
+pub mod ansi;
+pub mod ascii;
 pub mod effort;
+pub mod git_remote;
+pub mod i18n;
 pub mod model_parser;
+pub mod number_format;
+pub mod platform;
+pub mod project_manifest;
 pub mod provider_profiles;
+pub mod rev_lines;
+pub mod sanitize;
+pub mod secrets;
+pub mod width;
+
+pub use ansi::{pad_to_width, strip_sgr, truncate_rendered, visible_width};
+pub use rev_lines::RevLines;
+pub use width::{display_width, truncate_to_width, truncate_with_ellipsis, wrap_to_width};
 
 use std::env;
 use std::path::PathBuf;
@@ -44,6 +59,25 @@ pub fn home_dir() -> Option<PathBuf> {
     dirs::home_dir()
 }
 
+/// 用户主目录;无法确定时(静态链接的 musl 二进制跑在没有 `HOME`、
+/// `/etc/passwd` 也不含当前用户的精简容器里)退回系统临时目录,
+/// 避免把 `.claude/` 写进当前工作目录。
+#[must_use]
+pub fn home_dir_or_temp() -> PathBuf {
+    home_dir().unwrap_or_else(env::temp_dir)
+}
+
+/// 展开路径开头的 `~` 为用户主目录;其它路径原样返回。
+#[must_use]
+pub fn expand_home(path: &str) -> String {
+    match (path.strip_prefix("~"), home_dir()) {
+        (Some(rest), Some(home)) if rest.is_empty() || rest.starts_with(['/', '\\']) => {
+            format!("{}{rest}", home.display())
+        }
+        _ => path.to_string(),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,245 @@
+//! Locale-aware number formatting
+//!
+//! 只处理状态行需要的几件事:千位分隔符、小数点符号、舍入方式和紧凑写法
+//! (`1.2k`)。`locale` 按语言前缀匹配(`de-DE`、`de_AT` 都算 `de`),空字符串或
+//! 未知 locale 保持原样输出。
+
+/// Digit grouping and decimal mark of a locale
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NumberLocale {
+    pub group_separator: Option<char>,
+    pub decimal_separator: char,
+}
+
+impl NumberLocale {
+    /// No grouping, `.` decimal mark (the historical output)
+    pub const PLAIN: Self = Self {
+        group_separator: None,
+        decimal_separator: '.',
+    };
+
+    /// Resolve a locale tag such as `en-US`, `de`, `fr_FR`.
+    #[must_use]
+    pub fn from_tag(tag: &str) -> Self {
+        let language = tag
+            .trim()
+            .split(['-', '_'])
+            .next()
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+
+        let (group, decimal) = match language.as_str() {
+            "en" | "zh" | "ja" | "ko" => (',', '.'),
+            "de" | "es" | "it" | "nl" | "pt" | "id" | "tr" => ('.', ','),
+            "fr" | "ru" | "pl" | "cs" | "sv" | "fi" | "nb" | "uk" => (' ', ','),
+            "ch" | "rm" => ('\'', '.'),
+            _ => return Self::PLAIN,
+        };
+        Self {
+            group_separator: Some(group),
+            decimal_separator: decimal,
+        }
+    }
+
+    /// Format `value` with `precision` decimals.
+    #[must_use]
+    pub fn format(&self, value: f64, precision: usize) -> String {
+        let raw = format!("{value:.precision$}");
+        if *self == Self::PLAIN {
+            return raw;
+        }
+
+        let (sign, unsigned) = raw
+            .strip_prefix('-')
+            .map_or(("", raw.as_str()), |rest| ("-", rest));
+        let (integer, fraction) = unsigned
+            .split_once('.')
+            .map_or((unsigned, None), |(integer, fraction)| {
+                (integer, Some(fraction))
+            });
+
+        let mut out = String::with_capacity(raw.len() + integer.len() / 3);
+        out.push_str(sign);
+        for (idx, digit) in integer.chars().enumerate() {
+            if idx > 0 && (integer.len() - idx) % 3 == 0 {
+                if let Some(separator) = self.group_separator {
+                    out.push(separator);
+                }
+            }
+            out.push(digit);
+        }
+        if let Some(fraction) = fraction {
+            out.push(self.decimal_separator);
+            out.push_str(fraction);
+        }
+        out
+    }
+}
+
+impl Default for NumberLocale {
+    fn default() -> Self {
+        Self::PLAIN
+    }
+}
+
+/// How values are rounded to the displayed precision
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RoundingMode {
+    #[default]
+    Nearest,
+    Ceil,
+    Floor,
+}
+
+impl RoundingMode {
+    /// Parse a config value; unknown values fall back to [`Self::Nearest`].
+    #[must_use]
+    pub fn from_config(value: &str) -> Self {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "ceil" | "up" => Self::Ceil,
+            "floor" | "down" => Self::Floor,
+            _ => Self::Nearest,
+        }
+    }
+
+    /// Round `value` to `precision` decimals.
+    #[must_use]
+    pub fn round(self, value: f64, precision: usize) -> f64 {
+        if !value.is_finite() {
+            return value;
+        }
+
+        let factor = 10_f64.powi(i32::try_from(precision).unwrap_or(i32::MAX));
+        let scaled = value * factor;
+        // 1.23 * 100 = 123.00000000000001,不能因为浮点误差多进一位
+        let nearest = scaled.round();
+        let rounded = if self == Self::Nearest || (scaled - nearest).abs() < 1e-9 {
+            nearest
+        } else if self == Self::Ceil {
+            scaled.ceil()
+        } else {
+            scaled.floor()
+        };
+        rounded / factor
+    }
+}
+
+/// Options for [`format_amount`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AmountFormat {
+    pub precision: usize,
+    pub rounding: RoundingMode,
+    /// Abbreviate thousands and above (`1.2k`, `3.4M`)
+    pub compact: bool,
+    pub locale: NumberLocale,
+}
+
+/// Decimals shown for compact (`k`/`M`/`B`) amounts
+const COMPACT_PRECISION: usize = 1;
+
+const COMPACT_UNITS: [(f64, &str); 3] = [(1e3, "k"), (1e6, "M"), (1e9, "B")];
+
+/// Columns of a fixed-width percentage without the `%` sign (`100.0`)
+pub const PERCENT_WIDTH: usize = 5;
+
+/// Columns of a fixed-width amount with `precision` decimals: two integer
+/// digits, the decimal mark and the decimals (`99.99`)
+#[must_use]
+pub const fn amount_width(precision: usize) -> usize {
+    if precision == 0 {
+        2
+    } else {
+        precision + 3
+    }
+}
+
+/// Format an amount with rounding, optional compaction and locale separators.
+#[must_use]
+pub fn format_amount(value: f64, format: &AmountFormat) -> String {
+    if format.compact {
+        // 舍入后进位到 1000 时升一级单位:999_999 显示为 1.0M 而不是 1000.0k
+        let mut scaled: Option<(f64, &str)> = None;
+        for (unit, suffix) in COMPACT_UNITS {
+            let carried = scaled.is_some_and(|(rounded, _)| rounded.abs() >= 1e3);
+            if value.abs() < unit && !carried {
+                break;
+            }
+            scaled = Some((
+                format.rounding.round(value / unit, COMPACT_PRECISION),
+                suffix,
+            ));
+        }
+        if let Some((rounded, suffix)) = scaled {
+            return format!(
+                "{}{suffix}",
+                format.locale.format(rounded, COMPACT_PRECISION)
+            );
+        }
+    }
+
+    let rounded = format.rounding.round(value, format.precision);
+    format.locale.format(rounded, format.precision)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fixed_widths_fit_typical_values() {
+        assert_eq!(format!("{:>PERCENT_WIDTH$.1}%", 4.3), "  4.3%");
+        assert_eq!(format!("{:>PERCENT_WIDTH$.1}%", 100.0), "100.0%");
+        assert_eq!(format!("${:>1$}", "0.32", amount_width(2)), "$ 0.32");
+        assert_eq!(amount_width(0), 2);
+    }
+
+    #[test]
+    fn formats_grouping_and_decimal_mark_per_locale() {
+        assert_eq!(NumberLocale::PLAIN.format(1234.5, 2), "1234.50");
+        assert_eq!(
+            NumberLocale::from_tag("en-US").format(1_234_567.891, 2),
+            "1,234,567.89"
+        );
+        assert_eq!(
+            NumberLocale::from_tag("de_DE").format(1234.5, 2),
+            "1.234,50"
+        );
+        assert_eq!(NumberLocale::from_tag("fr").format(-1234.5, 1), "-1 234,5");
+        assert_eq!(NumberLocale::from_tag("de").format(999.0, 0), "999");
+        assert_eq!(NumberLocale::from_tag("xx"), NumberLocale::PLAIN);
+        assert_eq!(NumberLocale::from_tag(""), NumberLocale::PLAIN);
+    }
+
+    #[test]
+    fn rounding_modes_respect_precision() {
+        assert!((RoundingMode::Ceil.round(1.231, 2) - 1.24).abs() < 1e-9);
+        assert!((RoundingMode::Ceil.round(1.23, 2) - 1.23).abs() < 1e-9);
+        assert!((RoundingMode::Floor.round(1.239, 2) - 1.23).abs() < 1e-9);
+        assert!((RoundingMode::Floor.round(-1.231, 2) + 1.24).abs() < 1e-9);
+        assert_eq!(RoundingMode::from_config("CEIL"), RoundingMode::Ceil);
+        assert_eq!(RoundingMode::from_config("bogus"), RoundingMode::Nearest);
+    }
+
+    #[test]
+    fn compact_amounts_abbreviate_large_values() {
+        let compact = AmountFormat {
+            precision: 2,
+            compact: true,
+            ..AmountFormat::default()
+        };
+        assert_eq!(format_amount(12.345, &compact), "12.35");
+        assert_eq!(format_amount(1234.0, &compact), "1.2k");
+        assert_eq!(format_amount(2_500_000.0, &compact), "2.5M");
+        assert_eq!(format_amount(999_999.0, &compact), "1.0M");
+        assert_eq!(format_amount(999.0, &compact), "999.00");
+
+        let floor = AmountFormat {
+            rounding: RoundingMode::Floor,
+            locale: NumberLocale::from_tag("de"),
+            ..compact
+        };
+        assert_eq!(format_amount(1299.0, &floor), "1,2k");
+        assert_eq!(format_amount(999_999.0, &floor), "999,9k");
+        assert_eq!(format_amount(12.349, &floor), "12,34");
+    }
+}
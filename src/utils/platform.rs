@@ -0,0 +1,78 @@
+//! Build and runtime platform information
+//!
+//! 发布的 Linux 二进制是完全静态链接的 musl 程序(libgit2 源码内置,不依赖 OpenSSL),
+//! 可以直接放进 Alpine / scratch 容器或 ARM 路由器。可选能力在这里集中探测:
+//! 编译时关闭的特性、运行时不可用的系统钥匙串都只让对应功能降级,不影响渲染。
+//! `doctor` 命令会打印这些信息。
+
+/// Optional cargo features and whether this build includes them
+pub const FEATURES: &[(&str, bool)] = &[
+    ("git", cfg!(feature = "git")),
+    ("storage", cfg!(feature = "storage")),
+    ("http", cfg!(feature = "http")),
+    ("keyring", cfg!(feature = "keyring")),
+];
+
+/// Whether the C runtime is statically linked (musl release builds)
+#[must_use]
+pub const fn is_static_build() -> bool {
+    cfg!(target_feature = "crt-static")
+}
+
+/// Target triple components, e.g. `linux-aarch64 (musl)`
+#[must_use]
+pub fn target_description() -> String {
+    let env = if cfg!(target_env = "musl") {
+        " (musl)"
+    } else if cfg!(target_env = "gnu") {
+        " (gnu)"
+    } else if cfg!(target_env = "msvc") {
+        " (msvc)"
+    } else {
+        ""
+    };
+    format!("{}-{}{env}", std::env::consts::OS, std::env::consts::ARCH)
+}
+
+/// Whether the system keyring can be used.
+///
+/// 容器里的 seccomp 策略常禁止 keyutils 系统调用,macOS / Windows 的钥匙串也可能
+/// 因无会话而不可用;此时 `keyring:` 引用解析失败,对应 Widget 不显示。
+///
+/// # Errors
+///
+/// Returns the reason when the keyring is missing from the build or the
+/// platform store cannot be reached.
+#[cfg(feature = "keyring")]
+pub fn keyring_status() -> Result<(), String> {
+    let entry = keyring::Entry::new("claude-code-statusline-pro", "availability-probe")
+        .map_err(|err| err.to_string())?;
+    match entry.get_password() {
+        Ok(_) | Err(keyring::Error::NoEntry) => Ok(()),
+        Err(err) => Err(err.to_string()),
+    }
+}
+
+/// Whether the system keyring can be used.
+///
+/// # Errors
+///
+/// Always fails: this build does not include the `keyring` feature.
+#[cfg(not(feature = "keyring"))]
+pub fn keyring_status() -> Result<(), String> {
+    Err("built without the `keyring` feature".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn build_information_is_consistent() {
+        assert_eq!(FEATURES.len(), 4);
+        assert!(FEATURES
+            .iter()
+            .any(|(name, enabled)| *name == "git" && *enabled == cfg!(feature = "git")));
+        assert!(target_description().starts_with(std::env::consts::OS));
+    }
+}
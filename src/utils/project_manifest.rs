@@ -0,0 +1,166 @@
+//! Project metadata from package manifests
+//!
+//! 按 `Cargo.toml` → `package.json` → `pyproject.toml` 的顺序读取项目目录下的
+//! 清单文件,取出规范的包名和版本。结果按清单路径和修改时间缓存在进程内,
+//! 长时间运行的场景(TUI 预览)不会在每次渲染时重复解析。
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{LazyLock, Mutex};
+use std::time::SystemTime;
+
+use serde_json::Value;
+use toml_edit::{DocumentMut, Item};
+
+/// Manifest files checked, in priority order
+const MANIFESTS: [&str; 3] = ["Cargo.toml", "package.json", "pyproject.toml"];
+
+/// Name and version declared by a manifest
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProjectManifest {
+    pub name: String,
+    pub version: Option<String>,
+}
+
+type CacheEntry = (Option<SystemTime>, Option<ProjectManifest>);
+
+static CACHE: LazyLock<Mutex<HashMap<PathBuf, CacheEntry>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Read the first manifest in `dir` that declares a package name.
+///
+/// A workspace-only `Cargo.toml` (no `[package]`) or a `package.json` without
+/// `name` is skipped in favour of the next manifest.
+#[must_use]
+pub fn read_project_manifest(dir: &Path) -> Option<ProjectManifest> {
+    MANIFESTS.iter().find_map(|file| {
+        let path = dir.join(file);
+        let modified = fs::metadata(&path).ok()?.modified().ok();
+        if let Some((_, manifest)) = cached(&path, modified) {
+            return manifest;
+        }
+
+        let manifest = fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| parse_manifest(file, &content));
+        if let Ok(mut cache) = CACHE.lock() {
+            cache.insert(path, (modified, manifest.clone()));
+        }
+        manifest
+    })
+}
+
+/// Cached entry for `path` if the file has not changed since it was parsed
+fn cached(path: &Path, modified: Option<SystemTime>) -> Option<CacheEntry> {
+    let cache = CACHE.lock().ok()?;
+    cache
+        .get(path)
+        .filter(|(cached_modified, _)| modified.is_some() && *cached_modified == modified)
+        .cloned()
+}
+
+fn parse_manifest(file: &str, content: &str) -> Option<ProjectManifest> {
+    if file == "package.json" {
+        let value: Value = serde_json::from_str(content).ok()?;
+        let field = |key: &str| {
+            value
+                .get(key)
+                .and_then(Value::as_str)
+                .map(str::trim)
+                .filter(|text| !text.is_empty())
+                .map(str::to_string)
+        };
+        return Some(ProjectManifest {
+            name: field("name")?,
+            version: field("version"),
+        });
+    }
+
+    let document: DocumentMut = content.parse().ok()?;
+    let tables: &[&[&str]] = if file == "Cargo.toml" {
+        &[&["package"]]
+    } else {
+        &[&["project"], &["tool", "poetry"]]
+    };
+    tables.iter().find_map(|path| {
+        let table = path
+            .iter()
+            .try_fold(document.as_item(), |item, key| item.get(key))?;
+        // `version.workspace = true` 之类的非字符串值视为未声明
+        let field = |key: &str| {
+            table
+                .get(key)
+                .and_then(Item::as_str)
+                .map(str::trim)
+                .filter(|text| !text.is_empty())
+                .map(str::to_string)
+        };
+        Some(ProjectManifest {
+            name: field("name")?,
+            version: field("version"),
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn parses_each_manifest_kind() {
+        assert_eq!(
+            parse_manifest(
+                "Cargo.toml",
+                "[package]\nname = \"ccsp\"\nversion.workspace = true\n"
+            ),
+            Some(ProjectManifest {
+                name: "ccsp".to_string(),
+                version: None,
+            })
+        );
+        assert_eq!(
+            parse_manifest("Cargo.toml", "[workspace]\nmembers = []\n"),
+            None
+        );
+        assert_eq!(
+            parse_manifest(
+                "package.json",
+                r#"{"name": "@acme/web", "version": "1.2.0"}"#
+            ),
+            Some(ProjectManifest {
+                name: "@acme/web".to_string(),
+                version: Some("1.2.0".to_string()),
+            })
+        );
+        assert_eq!(
+            parse_manifest(
+                "pyproject.toml",
+                "[tool.poetry]\nname = \"api\"\nversion = \"0.3.1\"\n"
+            ),
+            Some(ProjectManifest {
+                name: "api".to_string(),
+                version: Some("0.3.1".to_string()),
+            })
+        );
+    }
+
+    #[test]
+    fn skips_manifests_without_a_name() -> anyhow::Result<()> {
+        let dir = tempdir()?;
+        fs::write(dir.path().join("Cargo.toml"), "[workspace]\n")?;
+        fs::write(
+            dir.path().join("package.json"),
+            r#"{"name": "site", "version": "2.0.0"}"#,
+        )?;
+
+        let manifest = read_project_manifest(dir.path());
+        assert_eq!(
+            manifest.map(|manifest| manifest.name),
+            Some("site".to_string())
+        );
+        assert_eq!(read_project_manifest(&dir.path().join("missing")), None);
+        Ok(())
+    }
+}
@@ -848,6 +848,53 @@ pub fn match_model_currency_rules<S: BuildHasher>(
     })
 }
 
+/// Canonical ISO code for common currency aliases and symbols.
+///
+/// `¥` 同时用于人民币和日元,无法判断时原样保留。
+#[must_use]
+pub fn normalize_currency_code(currency: &str) -> String {
+    let normalized = currency.trim().to_ascii_uppercase();
+    match normalized.as_str() {
+        "US$" | "$" => DEFAULT_CURRENCY.to_string(),
+        "RMB" | "CN¥" | "￥" => "CNY".to_string(),
+        "JP¥" => "JPY".to_string(),
+        "€" => "EUR".to_string(),
+        "£" => "GBP".to_string(),
+        _ => normalized,
+    }
+}
+
+/// Convert `amount` between currencies.
+///
+/// `rates` maps a currency code to its units per 1 USD (`EUR = 0.92`); USD is
+/// always 1. Returns `None` when either side has no usable rate.
+#[must_use]
+pub fn convert_currency<S: BuildHasher>(
+    amount: f64,
+    from: &str,
+    to: &str,
+    rates: &HashMap<String, f64, S>,
+) -> Option<f64> {
+    let from = normalize_currency_code(from);
+    let to = normalize_currency_code(to);
+    if from == to {
+        return Some(amount);
+    }
+
+    let rate = |code: &str| {
+        if code == DEFAULT_CURRENCY {
+            return Some(1.0);
+        }
+        rates
+            .iter()
+            .find(|(key, _)| normalize_currency_code(key) == code)
+            .map(|(_, rate)| *rate)
+            .filter(|rate| rate.is_finite() && *rate > 0.0)
+    };
+
+    Some(amount / rate(&from)? * rate(&to)?)
+}
+
 #[must_use]
 pub fn builtin_endpoint_currency(endpoint: &str) -> Option<&'static str> {
     BUILTIN_ENDPOINT_CURRENCY_RULES
@@ -1113,7 +1160,7 @@ impl PrefixPricingMatch<'_> {
     }
 }
 
-fn model_id_candidates(model_id: &str) -> Vec<String> {
+pub(crate) fn model_id_candidates(model_id: &str) -> Vec<String> {
     let normalized = model_id.trim().to_ascii_lowercase();
     let mut candidates = vec![normalized.clone()];
 
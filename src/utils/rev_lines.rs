@@ -0,0 +1,167 @@
+//! Reverse line reader
+//!
+//! Reads a file from the end in fixed-size chunks and yields its lines newest
+//! first, so finding the last few entries of a 200MB transcript only touches
+//! its tail instead of loading the whole file.
+
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::Path;
+
+/// Bytes read per backwards seek
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// Iterator over the lines of a reader, last line first.
+///
+/// Lines are returned without their terminator (`\n` or `\r\n`); invalid
+/// UTF-8 is replaced lossily. A trailing newline does not produce an empty
+/// final line, matching [`str::lines`].
+pub struct RevLines<R> {
+    reader: R,
+    /// Offset of the first byte already read; everything before it is unread
+    position: u64,
+    /// Read bytes not yet returned as lines
+    pending: Vec<u8>,
+    chunk_size: usize,
+    /// The file's final newline has been dropped (or there was none)
+    trailing_checked: bool,
+    done: bool,
+}
+
+impl RevLines<File> {
+    /// Open `path` for reverse line reading.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be opened or its length queried.
+    pub fn open(path: &Path) -> io::Result<Self> {
+        Self::new(File::open(path)?)
+    }
+}
+
+impl<R: Read + Seek> RevLines<R> {
+    /// Wrap a seekable reader.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if seeking to the end fails.
+    pub fn new(reader: R) -> io::Result<Self> {
+        Self::with_chunk_size(reader, CHUNK_SIZE)
+    }
+
+    fn with_chunk_size(mut reader: R, chunk_size: usize) -> io::Result<Self> {
+        let position = reader.seek(SeekFrom::End(0))?;
+        Ok(Self {
+            reader,
+            position,
+            pending: Vec::new(),
+            chunk_size: chunk_size.max(1),
+            trailing_checked: false,
+            done: position == 0,
+        })
+    }
+
+    /// Prepend the previous chunk to `pending`. Returns `false` at the start of the file.
+    fn read_chunk(&mut self) -> io::Result<bool> {
+        if self.position == 0 {
+            return Ok(false);
+        }
+
+        let len = usize::try_from(self.position)
+            .map_or(self.chunk_size, |remaining| remaining.min(self.chunk_size));
+        self.position -= len as u64;
+        self.reader.seek(SeekFrom::Start(self.position))?;
+
+        let mut chunk = vec![0; len];
+        self.reader.read_exact(&mut chunk)?;
+        chunk.extend_from_slice(&self.pending);
+        self.pending = chunk;
+        Ok(true)
+    }
+
+    fn take_line(bytes: &[u8]) -> String {
+        let bytes = bytes.strip_suffix(b"\r").unwrap_or(bytes);
+        String::from_utf8_lossy(bytes).into_owned()
+    }
+}
+
+impl<R: Read + Seek> Iterator for RevLines<R> {
+    type Item = io::Result<String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        loop {
+            if !self.trailing_checked && !self.pending.is_empty() {
+                // 与 str::lines 一致:文件末尾的换行不产生空行
+                if self.pending.last() == Some(&b'\n') {
+                    self.pending.pop();
+                }
+                self.trailing_checked = true;
+            }
+
+            if self.trailing_checked {
+                if let Some(idx) = self.pending.iter().rposition(|byte| *byte == b'\n') {
+                    let line = Self::take_line(&self.pending[idx + 1..]);
+                    self.pending.truncate(idx);
+                    return Some(Ok(line));
+                }
+            }
+
+            match self.read_chunk() {
+                Ok(true) => {}
+                Ok(false) => {
+                    // 读到文件开头:剩下的就是第一行
+                    self.done = true;
+                    let line = Self::take_line(&self.pending);
+                    self.pending.clear();
+                    return Some(Ok(line));
+                }
+                Err(err) => {
+                    self.done = true;
+                    return Some(Err(err));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    fn collect(content: &str, chunk_size: usize) -> io::Result<Vec<String>> {
+        RevLines::with_chunk_size(Cursor::new(content.as_bytes().to_vec()), chunk_size)?.collect()
+    }
+
+    #[test]
+    fn yields_lines_newest_first_across_chunk_boundaries() -> io::Result<()> {
+        let content = "first\nsecond line\r\n\nthird\n";
+        let expected: Vec<&str> = content.lines().rev().collect();
+        for chunk_size in [1, 2, 3, 7, 64] {
+            assert_eq!(
+                collect(content, chunk_size)?,
+                expected,
+                "chunk={chunk_size}"
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn handles_missing_trailing_newline_and_empty_input() -> io::Result<()> {
+        assert_eq!(collect("a\nb", 2)?, ["b", "a"]);
+        assert!(collect("", 4)?.is_empty());
+        assert_eq!(collect("\n", 4)?, [""]);
+        Ok(())
+    }
+
+    #[test]
+    fn multibyte_characters_survive_chunk_splits() -> io::Result<()> {
+        assert_eq!(collect("功能\n测试\n", 1)?, ["测试", "功能"]);
+        Ok(())
+    }
+}
@@ -0,0 +1,57 @@
+//! Terminal-safe text
+//!
+//! 分支名、模型显示名、项目目录、transcript 里的错误详情都来自外部数据,原样
+//! 写进终端时,精心构造的分支名可以注入转义序列(改标题、伪造超链接、清屏)。
+//! 组件输出在构造时经过这里:换行与制表符变成空格,ESC 换成可见的 `␛`,其余
+//! C0/C1 控制字符直接去掉。
+
+use std::borrow::Cow;
+
+/// Visible stand-in for ESC (U+241B SYMBOL FOR ESCAPE)
+const ESCAPE_SYMBOL: char = '\u{241b}';
+
+/// Whether `ch` would be interpreted by the terminal instead of printed
+const fn is_terminal_control(ch: char) -> bool {
+    matches!(ch, '\u{0}'..='\u{1f}' | '\u{7f}'..='\u{9f}')
+}
+
+/// `text` with control characters neutralized; borrowed when already clean.
+#[must_use]
+pub fn sanitize_terminal_text(text: &str) -> Cow<'_, str> {
+    if !text.chars().any(is_terminal_control) {
+        return Cow::Borrowed(text);
+    }
+    Cow::Owned(
+        text.chars()
+            .filter_map(|ch| match ch {
+                '\n' | '\r' | '\t' => Some(' '),
+                '\x1b' => Some(ESCAPE_SYMBOL),
+                ch if is_terminal_control(ch) => None,
+                ch => Some(ch),
+            })
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn controls_are_neutralized() {
+        assert!(matches!(
+            sanitize_terminal_text("feature/项目-1"),
+            Cow::Borrowed("feature/项目-1")
+        ));
+        assert_eq!(
+            sanitize_terminal_text("main\x1b]0;pwned\x07"),
+            "main\u{241b}]0;pwned"
+        );
+        assert_eq!(
+            sanitize_terminal_text("Bash failed:\n\tline 2\r"),
+            "Bash failed:  line 2 "
+        );
+        // C1 控制字符(如单字节 CSI)与 DEL 一并去掉
+        assert_eq!(sanitize_terminal_text("a\u{9b}31mb\u{7f}"), "a31mb");
+    }
+}
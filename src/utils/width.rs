@@ -0,0 +1,198 @@
+//! Terminal display width helpers
+//!
+//! Measures text in terminal columns instead of bytes or chars so CJK names,
+//! emoji sequences and combining marks line up the same way across every
+//! renderer. Width is computed per grapheme cluster; clusters that use emoji
+//! presentation (VS16, ZWJ sequences, skin tones, flags) always occupy two
+//! columns, matching what modern terminals draw.
+
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+const ZERO_WIDTH_JOINER: char = '\u{200d}';
+const EMOJI_PRESENTATION_SELECTOR: char = '\u{fe0f}';
+
+/// Return the number of terminal columns `text` occupies.
+#[must_use]
+pub fn display_width(text: &str) -> usize {
+    text.graphemes(true).map(grapheme_width).sum()
+}
+
+/// Truncate `text` so that it fits in `max_width` columns.
+///
+/// Grapheme clusters are never split; a wide cluster that would straddle the
+/// limit is dropped entirely.
+#[must_use]
+pub fn truncate_to_width(text: &str, max_width: usize) -> String {
+    let mut result = String::with_capacity(text.len().min(max_width * 4));
+    let mut used = 0;
+
+    for grapheme in text.graphemes(true) {
+        let width = grapheme_width(grapheme);
+        if used + width > max_width {
+            break;
+        }
+        used += width;
+        result.push_str(grapheme);
+    }
+
+    result
+}
+
+/// Truncate `text` to `max_width` columns, appending `ellipsis` when cut.
+///
+/// The ellipsis counts towards the limit, so the returned string never exceeds
+/// `max_width` columns (unless the ellipsis alone is wider than the limit).
+#[must_use]
+pub fn truncate_with_ellipsis(text: &str, max_width: usize, ellipsis: &str) -> String {
+    if display_width(text) <= max_width {
+        return text.to_string();
+    }
+
+    let budget = max_width.saturating_sub(display_width(ellipsis));
+    let mut truncated = truncate_to_width(text, budget);
+    truncated.push_str(ellipsis);
+    truncated
+}
+
+/// Word-wrap `text` into lines of at most `max_width` columns.
+///
+/// Breaks on whitespace where possible; words wider than a line are split on
+/// grapheme boundaries. A `max_width` of 0 returns the text as a single line.
+#[must_use]
+pub fn wrap_to_width(text: &str, max_width: usize) -> Vec<String> {
+    if max_width == 0 {
+        return vec![text.to_string()];
+    }
+
+    let mut lines = Vec::new();
+    let mut current = String::new();
+    let mut used = 0;
+
+    for word in text.split_whitespace() {
+        let word_width = display_width(word);
+        let gap = usize::from(used > 0);
+        if used + gap + word_width <= max_width {
+            if gap == 1 {
+                current.push(' ');
+            }
+            current.push_str(word);
+            used += gap + word_width;
+            continue;
+        }
+
+        if used > 0 {
+            lines.push(std::mem::take(&mut current));
+            used = 0;
+        }
+
+        if word_width <= max_width {
+            current.push_str(word);
+            used = word_width;
+            continue;
+        }
+
+        for grapheme in word.graphemes(true) {
+            let width = grapheme_width(grapheme);
+            if used + width > max_width && used > 0 {
+                lines.push(std::mem::take(&mut current));
+                used = 0;
+            }
+            current.push_str(grapheme);
+            used += width;
+        }
+    }
+
+    if used > 0 {
+        lines.push(current);
+    }
+    lines
+}
+
+/// Columns of one grapheme cluster
+pub(super) fn grapheme_width(grapheme: &str) -> usize {
+    if is_emoji_sequence(grapheme) {
+        return 2;
+    }
+    UnicodeWidthStr::width(grapheme)
+}
+
+fn is_emoji_sequence(grapheme: &str) -> bool {
+    if grapheme.chars().next().is_some_and(is_regional_indicator) {
+        return true;
+    }
+
+    grapheme.chars().any(|ch| {
+        matches!(ch, ZERO_WIDTH_JOINER | EMOJI_PRESENTATION_SELECTOR) || is_emoji_modifier(ch)
+    })
+}
+
+const fn is_regional_indicator(ch: char) -> bool {
+    matches!(ch, '\u{1f1e6}'..='\u{1f1ff}')
+}
+
+const fn is_emoji_modifier(ch: char) -> bool {
+    matches!(ch, '\u{1f3fb}'..='\u{1f3ff}')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ascii_width_matches_length() {
+        assert_eq!(display_width("main"), 4);
+        assert_eq!(display_width(""), 0);
+    }
+
+    #[test]
+    fn cjk_characters_are_double_width() {
+        assert_eq!(display_width("功能"), 4);
+        assert_eq!(display_width("项目-a"), 6);
+    }
+
+    #[test]
+    fn emoji_sequences_count_as_one_wide_cluster() {
+        assert_eq!(display_width("🚀"), 2);
+        assert_eq!(display_width("❤\u{fe0f}"), 2);
+        assert_eq!(display_width("👨\u{200d}👩\u{200d}👧"), 2);
+        assert_eq!(display_width("👍🏽"), 2);
+        assert_eq!(display_width("🇨🇳"), 2);
+    }
+
+    #[test]
+    fn combining_marks_do_not_add_width() {
+        assert_eq!(display_width("e\u{301}"), 1);
+    }
+
+    #[test]
+    fn truncation_never_splits_wide_clusters() {
+        assert_eq!(truncate_to_width("功能测试", 5), "功能");
+        assert_eq!(
+            truncate_to_width("a👨\u{200d}👩\u{200d}👧b", 3),
+            "a👨\u{200d}👩\u{200d}👧"
+        );
+        assert_eq!(truncate_to_width("abc", 10), "abc");
+    }
+
+    #[test]
+    fn ellipsis_counts_towards_limit() {
+        assert_eq!(
+            truncate_with_ellipsis("very-long-branch", 6, "..."),
+            "ver..."
+        );
+        assert_eq!(truncate_with_ellipsis("功能/测试", 7, "..."), "功能...");
+        assert_eq!(truncate_with_ellipsis("short", 6, "..."), "short");
+    }
+
+    #[test]
+    fn wrapping_breaks_on_words_and_splits_long_ones() {
+        assert_eq!(
+            wrap_to_width("connection reset by peer", 10),
+            ["connection", "reset by", "peer"]
+        );
+        assert_eq!(wrap_to_width("abcdefghij", 4), ["abcd", "efgh", "ij"]);
+        assert_eq!(wrap_to_width("功能 测试用例", 5), ["功能", "测试", "用例"]);
+        assert!(wrap_to_width("   ", 5).is_empty());
+    }
+}
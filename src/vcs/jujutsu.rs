@@ -0,0 +1,153 @@
+//! Jujutsu provider
+//!
+//! 工作区根目录由 `.jj` 标记。状态通过 `jj log -r @ --ignore-working-copy` 读取:
+//! 不对工作区做快照,因此不会在每次渲染时产生新的 operation,代价是脏状态反映的是
+//! 上一次 jj 命令时的工作区。显示 `@` 上的书签,其次是父提交上的书签(在 `main`
+//! 之上新建的修改),都没有时显示变更 ID 的最短唯一前缀。`jj` 超过
+//! `branch.performance.git_timeout` 仍未结束时被终止,本次渲染回退到 git。
+
+use std::io::Read;
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+use anyhow::{bail, Context, Result};
+
+use super::{VcsProvider, VcsStatus};
+
+/// Tab separated: change id, bookmarks, parent bookmarks, empty, conflict
+const LOG_TEMPLATE: &str = r#"change_id.shortest(8) ++ "\t" ++ local_bookmarks.map(|b| b.name()).join(",") ++ "\t" ++ parents.map(|p| p.local_bookmarks().map(|b| b.name()).join(",")).join(",") ++ "\t" ++ if(empty, "empty", "changed") ++ "\t" ++ if(conflict, "conflict", "")"#;
+
+/// Interval at which a running `jj` is checked against its timeout
+const POLL_INTERVAL: Duration = Duration::from_millis(5);
+
+/// Reads `.jj` workspaces through the `jj` executable
+#[derive(Debug, Clone, Copy)]
+pub struct JujutsuProvider {
+    /// Time `jj log` may take before it is killed
+    timeout: Duration,
+}
+
+impl JujutsuProvider {
+    #[must_use]
+    pub const fn new(timeout: Duration) -> Self {
+        Self { timeout }
+    }
+}
+
+impl Default for JujutsuProvider {
+    fn default() -> Self {
+        Self::new(Duration::from_secs(1))
+    }
+}
+
+/// Status from the output of [`LOG_TEMPLATE`]
+fn parse_log(output: &str) -> Option<VcsStatus> {
+    let mut fields = output.lines().next()?.split('\t');
+    let change_id = fields.next().filter(|id| !id.is_empty())?;
+    let first = |list: Option<&str>| {
+        list.and_then(|list| list.split(',').find(|name| !name.is_empty()))
+            .map(str::to_string)
+    };
+    let bookmark = first(fields.next());
+    let parent_bookmark = first(fields.next());
+    let changed = fields.next() == Some("changed");
+    let conflict = fields.next() == Some("conflict");
+    Some(VcsStatus {
+        branch: bookmark
+            .or(parent_bookmark)
+            .unwrap_or_else(|| change_id.to_string()),
+        dirty: Some(changed || conflict),
+        ..VcsStatus::default()
+    })
+}
+
+/// Everything left in an exited process's output pipe
+fn read_pipe(pipe: Option<impl Read>) -> String {
+    let mut text = String::new();
+    if let Some(mut pipe) = pipe {
+        let _ = pipe.read_to_string(&mut text);
+    }
+    text
+}
+
+impl VcsProvider for JujutsuProvider {
+    fn name(&self) -> &'static str {
+        "jj"
+    }
+
+    fn marker(&self) -> &'static str {
+        ".jj"
+    }
+
+    fn status(&self, root: &Path) -> Result<VcsStatus> {
+        let mut child = Command::new("jj")
+            .args([
+                "log",
+                "-r",
+                "@",
+                "--no-graph",
+                "--ignore-working-copy",
+                "--color",
+                "never",
+                "-T",
+                LOG_TEMPLATE,
+            ])
+            .current_dir(root)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .context("Failed to run `jj log`")?;
+
+        // 输出只有一行,不会在管道缓冲区写满时阻塞
+        let deadline = Instant::now() + self.timeout;
+        let status = loop {
+            if let Some(status) = child.try_wait().context("Failed to wait for `jj log`")? {
+                break status;
+            }
+            if Instant::now() >= deadline {
+                let _ = child.kill();
+                let _ = child.wait();
+                bail!("`jj log` timed out after {}ms", self.timeout.as_millis());
+            }
+            std::thread::sleep(POLL_INTERVAL);
+        };
+        if !status.success() {
+            bail!("`jj log` failed: {}", read_pipe(child.stderr.take()).trim());
+        }
+        parse_log(&read_pipe(child.stdout.take())).context("Unexpected `jj log` output")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn log_output_is_parsed() {
+        let status = parse_log("kxqpmvtn\tfeature\tmain\tchanged\t\n");
+        assert_eq!(
+            status,
+            Some(VcsStatus {
+                branch: "feature".to_string(),
+                dirty: Some(true),
+                ..VcsStatus::default()
+            })
+        );
+
+        let status = parse_log("kxqpmvtn\t\tmain,release\tempty\t");
+        assert_eq!(
+            status.map(|status| (status.branch, status.dirty)),
+            Some(("main".to_string(), Some(false)))
+        );
+
+        let status = parse_log("kxqp\t\t\tempty\tconflict");
+        assert_eq!(
+            status.map(|status| (status.branch, status.dirty)),
+            Some(("kxqp".to_string(), Some(true)))
+        );
+
+        assert_eq!(parse_log(""), None);
+    }
+}
@@ -0,0 +1,112 @@
+//! Mercurial / Sapling provider
+//!
+//! 只读取仓库目录里的小文件,不启动 `hg` / `sl`:活动书签
+//! (`bookmarks.current`)优先,其次是命名分支(`branch`),都没有时显示
+//! `dirstate` 记录的父提交短哈希。脏状态需要扫描工作区,这里不提供。
+
+use std::fmt::Write as _;
+use std::fs;
+use std::path::Path;
+
+use anyhow::Result;
+
+use super::{VcsProvider, VcsStatus};
+
+/// Length of the abbreviated parent hash
+const SHORT_HASH_LEN: usize = 12;
+
+/// Reads `.hg` (Mercurial) or `.sl` (Sapling) working copies
+#[derive(Debug, Clone, Copy)]
+pub struct MercurialProvider {
+    name: &'static str,
+    marker: &'static str,
+}
+
+impl MercurialProvider {
+    #[must_use]
+    pub const fn mercurial() -> Self {
+        Self {
+            name: "hg",
+            marker: ".hg",
+        }
+    }
+
+    #[must_use]
+    pub const fn sapling() -> Self {
+        Self {
+            name: "sl",
+            marker: ".sl",
+        }
+    }
+}
+
+fn read_trimmed(path: &Path) -> Option<String> {
+    fs::read_to_string(path)
+        .ok()
+        .map(|content| content.trim().to_string())
+        .filter(|content| !content.is_empty())
+}
+
+/// Hex of the first parent recorded at the start of `dirstate`
+fn parent_hash(dirstate: &Path) -> Option<String> {
+    let bytes = fs::read(dirstate).ok()?;
+    let parent = bytes.get(..SHORT_HASH_LEN / 2)?;
+    if parent.iter().all(|byte| *byte == 0) {
+        return None;
+    }
+    let mut hex = String::with_capacity(SHORT_HASH_LEN);
+    for byte in parent {
+        let _ = write!(hex, "{byte:02x}");
+    }
+    Some(hex)
+}
+
+impl VcsProvider for MercurialProvider {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn marker(&self) -> &'static str {
+        self.marker
+    }
+
+    fn status(&self, root: &Path) -> Result<VcsStatus> {
+        let dir = root.join(self.marker);
+        let branch = read_trimmed(&dir.join("bookmarks.current"))
+            .or_else(|| read_trimmed(&dir.join("branch")))
+            .or_else(|| parent_hash(&dir.join("dirstate")))
+            .unwrap_or_else(|| "default".to_string());
+        Ok(VcsStatus {
+            branch,
+            ..VcsStatus::default()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bookmark_then_branch_then_parent() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let hg = dir.path().join(".hg");
+        fs::create_dir(&hg)?;
+        let provider = MercurialProvider::mercurial();
+        assert_eq!(provider.status(dir.path())?.branch, "default");
+
+        let mut dirstate = vec![0xab, 0xcd, 0x01, 0x23, 0x45, 0x67];
+        dirstate.resize(40, 0);
+        fs::write(hg.join("dirstate"), dirstate)?;
+        assert_eq!(provider.status(dir.path())?.branch, "abcd01234567");
+
+        fs::write(hg.join("branch"), "stable\n")?;
+        assert_eq!(provider.status(dir.path())?.branch, "stable");
+
+        fs::write(hg.join("bookmarks.current"), "feature-x")?;
+        let status = provider.status(dir.path())?;
+        assert_eq!(status.branch, "feature-x");
+        assert_eq!(status.dirty, None);
+        Ok(())
+    }
+}
@@ -0,0 +1,119 @@
+//! Version control backends besides git
+//!
+//! 分支组件默认通过 libgit2 读取 git 仓库。Jujutsu、Mercurial、Sapling 等后端实现
+//! [`VcsProvider`],提供分支(书签)与脏状态;渲染时从当前目录向上查找,离目录最近
+//! 的仓库根决定使用哪个后端。与 git 同目录共存的仓库(`jj git init --colocate`)
+//! 优先使用非 git 后端,因为此时 git 的 `HEAD` 总是处于分离状态。
+//!
+//! `components.branch.vcs` 按名称启用内置后端,库使用者也可以实现该 trait 并通过
+//! [`BranchComponent::with_vcs_providers`](crate::components::BranchComponent::with_vcs_providers)
+//! 注入自己的后端。
+
+pub mod jujutsu;
+pub mod mercurial;
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+
+pub use jujutsu::JujutsuProvider;
+pub use mercurial::MercurialProvider;
+
+/// Branch and working copy state reported by a backend
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct VcsStatus {
+    /// Branch, bookmark or change id to display
+    pub branch: String,
+    /// `None` when the backend cannot tell without an expensive scan
+    pub dirty: Option<bool>,
+    pub ahead: usize,
+    pub behind: usize,
+}
+
+/// A version control backend able to describe a working copy
+pub trait VcsProvider: Send + Sync {
+    /// Name used in `components.branch.vcs`
+    fn name(&self) -> &'static str;
+
+    /// Root of the working copy managed by this backend that `dir` belongs
+    /// to, if any
+    fn find_root(&self, dir: &Path) -> Option<PathBuf> {
+        dir.ancestors()
+            .find(|ancestor| ancestor.join(self.marker()).is_dir())
+            .map(Path::to_path_buf)
+    }
+
+    /// Directory marking a working copy root (`.jj`, `.hg`)
+    fn marker(&self) -> &'static str;
+
+    /// Read the state of the working copy at `root`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error when the state cannot be read.
+    fn status(&self, root: &Path) -> Result<VcsStatus>;
+}
+
+/// Names of the built-in providers
+pub const BUILTIN_PROVIDERS: [&str; 3] = ["jj", "hg", "sl"];
+
+/// Built-in provider called `name`; `timeout` bounds the commands it runs
+#[must_use]
+pub fn builtin_provider(name: &str, timeout: Duration) -> Option<Arc<dyn VcsProvider>> {
+    match name.trim().to_ascii_lowercase().as_str() {
+        "jj" | "jujutsu" => Some(Arc::new(JujutsuProvider::new(timeout))),
+        "hg" | "mercurial" => Some(Arc::new(MercurialProvider::mercurial())),
+        "sl" | "sapling" => Some(Arc::new(MercurialProvider::sapling())),
+        _ => None,
+    }
+}
+
+/// Provider managing `dir` together with its root, when that root is at
+/// least as close to `dir` as the enclosing git repository
+#[must_use]
+pub fn select_provider(
+    providers: &[Arc<dyn VcsProvider>],
+    dir: &Path,
+) -> Option<(Arc<dyn VcsProvider>, PathBuf)> {
+    let git_root = dir
+        .ancestors()
+        .find(|ancestor| ancestor.join(".git").exists());
+    providers
+        .iter()
+        .filter_map(|provider| Some((provider.clone(), provider.find_root(dir)?)))
+        .filter(|(_, root)| git_root.is_none_or(|git_root| root.starts_with(git_root)))
+        .max_by_key(|(_, root)| root.components().count())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nearest_working_copy_wins() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let providers: Vec<Arc<dyn VcsProvider>> = BUILTIN_PROVIDERS
+            .iter()
+            .filter_map(|name| builtin_provider(name, Duration::from_secs(1)))
+            .collect();
+
+        // jj 与 git 同目录共存:jj 优先
+        std::fs::create_dir_all(dir.path().join("colocated/.jj"))?;
+        std::fs::create_dir_all(dir.path().join("colocated/.git"))?;
+        let (provider, root) = select_provider(&providers, &dir.path().join("colocated"))
+            .ok_or_else(|| anyhow::anyhow!("colocated jj not selected"))?;
+        assert_eq!(provider.name(), "jj");
+        assert_eq!(root, dir.path().join("colocated"));
+
+        // hg 仓库内嵌套的 git 仓库:git 更近,不使用 hg
+        std::fs::create_dir_all(dir.path().join("hg/.hg"))?;
+        std::fs::create_dir_all(dir.path().join("hg/vendor/lib/.git"))?;
+        assert!(select_provider(&providers, &dir.path().join("hg/vendor/lib")).is_none());
+        let (provider, _) = select_provider(&providers, &dir.path().join("hg/vendor"))
+            .ok_or_else(|| anyhow::anyhow!("hg not selected"))?;
+        assert_eq!(provider.name(), "hg");
+        Ok(())
+    }
+}
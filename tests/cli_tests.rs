@@ -44,3 +44,424 @@ fn cli_config_init_force_creates_files() {
     let components_dir = config_path.parent().unwrap().join("components");
     assert!(components_dir.exists(), "components directory missing");
 }
+
+#[test]
+#[allow(deprecated)]
+fn cli_config_init_diff_writes_nothing() {
+    let temp_home = tempdir().expect("create temp home");
+    let project_dir = temp_home.path().join("workspace");
+    fs::create_dir_all(&project_dir).expect("create project dir");
+
+    Command::cargo_bin("claude-code-statusline-pro")
+        .expect("binary available")
+        .env("HOME", temp_home.path())
+        .args(["config", "init", project_dir.to_str().unwrap(), "--diff"])
+        .assert()
+        .failure();
+
+    Command::cargo_bin("claude-code-statusline-pro")
+        .expect("binary available")
+        .env("HOME", temp_home.path())
+        .args(["config", "init", project_dir.to_str().unwrap()])
+        .args(["--with-components", "--diff"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("(新文件)"));
+
+    let hashed = ProjectResolver::hash_global_path(project_dir.to_str().unwrap());
+    let config_dir = temp_home
+        .path()
+        .join(".claude")
+        .join("projects")
+        .join(hashed)
+        .join("statusline-pro");
+    assert!(
+        !config_dir.join("config.toml").exists(),
+        "--diff wrote the config"
+    );
+    assert!(
+        !config_dir.join("components").exists(),
+        "--diff wrote component files"
+    );
+}
+
+#[test]
+#[allow(deprecated)]
+fn cli_completions_cover_subcommands() {
+    let mut cmd = Command::cargo_bin("claude-code-statusline-pro").expect("binary available");
+    cmd.arg("completions")
+        .arg("bash")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("_ccsp()"))
+        .stdout(predicate::str::contains("preview"))
+        .stdout(predicate::str::contains("config"));
+
+    let mut cmd = Command::cargo_bin("claude-code-statusline-pro").expect("binary available");
+    cmd.arg("completions")
+        .arg("zsh")
+        .arg("--bin-name")
+        .arg("claude-code-statusline-pro")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "#compdef claude-code-statusline-pro",
+        ));
+}
+
+#[test]
+#[allow(deprecated)]
+fn cli_open_stdin_times_out_instead_of_hanging() {
+    use std::process::{Command as StdCommand, Stdio};
+    use std::time::{Duration, Instant};
+
+    let temp_home = tempdir().expect("create temp home");
+    // stdin 保持打开但不写入任何内容,模拟未正确管道输入的调用
+    let mut child = StdCommand::new(assert_cmd::cargo::cargo_bin("claude-code-statusline-pro"))
+        .env("HOME", temp_home.path())
+        .args(["--stdin-timeout", "200", "--no-colors"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("spawn binary");
+    let _stdin = child.stdin.take();
+
+    let started = Instant::now();
+    let output = child.wait_with_output().expect("wait for binary");
+    assert!(output.status.success());
+    assert!(started.elapsed() < Duration::from_secs(10));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("未从 stdin 收到输入"));
+}
+
+#[test]
+fn cli_record_open_stdin_times_out_instead_of_hanging() {
+    use std::process::{Command as StdCommand, Stdio};
+    use std::time::{Duration, Instant};
+
+    let temp_home = tempdir().expect("create temp home");
+    let recording = temp_home.path().join("recording.jsonl");
+    let mut child = StdCommand::new(assert_cmd::cargo::cargo_bin("claude-code-statusline-pro"))
+        .env("HOME", temp_home.path())
+        .args([
+            "--stdin-timeout",
+            "200",
+            "--no-colors",
+            "record",
+            "--output",
+        ])
+        .arg(&recording)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("spawn binary");
+    let _stdin = child.stdin.take();
+
+    let started = Instant::now();
+    let output = child.wait_with_output().expect("wait for binary");
+    assert!(output.status.success());
+    assert!(started.elapsed() < Duration::from_secs(10));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("未从 stdin 收到输入"));
+    assert!(!recording.exists());
+}
+
+#[cfg(unix)]
+#[test]
+fn cli_exits_within_budget_while_component_still_runs() {
+    use std::os::unix::fs::PermissionsExt;
+    use std::process::{Command as StdCommand, Stdio};
+    use std::time::{Duration, Instant};
+
+    let temp_home = tempdir().expect("create temp home");
+    // 分支组件在 .jj 工作区里调用 jj;这个 jj 要 10 秒才返回
+    let bin_dir = temp_home.path().join("bin");
+    fs::create_dir_all(&bin_dir).expect("create bin dir");
+    let jj = bin_dir.join("jj");
+    fs::write(&jj, "#!/bin/sh\nsleep 10\n").expect("write fake jj");
+    fs::set_permissions(&jj, fs::Permissions::from_mode(0o755)).expect("chmod fake jj");
+    let workspace = temp_home.path().join("slow-workspace");
+    fs::create_dir_all(workspace.join(".jj")).expect("create jj workspace");
+    let config = temp_home.path().join("config.toml");
+    fs::write(
+        &config,
+        "preset = \"PB\"\n[components]\nrender_timeout_ms = 0\n[performance]\nmax_total_ms = 300\n",
+    )
+    .expect("write config");
+
+    let path = std::env::var_os("PATH").unwrap_or_default();
+    let mut paths = vec![bin_dir];
+    paths.extend(std::env::split_paths(&path));
+    let mut child = StdCommand::new(assert_cmd::cargo::cargo_bin("claude-code-statusline-pro"))
+        .env("HOME", temp_home.path())
+        .env("PATH", std::env::join_paths(paths).expect("join PATH"))
+        .env_remove("STATUSLINE_STORAGE_PATH")
+        .args(["--no-colors", "--config"])
+        .arg(&config)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("spawn binary");
+    let payload = format!(
+        r#"{{"session_id":"budget-1","cwd":"{}","model":{{"id":"claude-opus-4"}}}}"#,
+        workspace.display()
+    );
+    {
+        use std::io::Write;
+        let mut stdin = child.stdin.take().expect("stdin piped");
+        stdin.write_all(payload.as_bytes()).expect("write payload");
+    }
+
+    let started = Instant::now();
+    let output = child.wait_with_output().expect("wait for binary");
+    assert!(output.status.success());
+    assert!(
+        started.elapsed() < Duration::from_secs(5),
+        "exited after {:?}",
+        started.elapsed()
+    );
+    assert!(String::from_utf8_lossy(&output.stdout).contains("slow-workspace"));
+}
+
+#[cfg(feature = "storage")]
+#[test]
+#[allow(deprecated)]
+fn cli_history_lists_rendered_lines() {
+    let temp_home = tempdir().expect("create temp home");
+    let config_dir = temp_home.path().join(".claude").join("statusline-pro");
+    fs::create_dir_all(&config_dir).expect("create config dir");
+    fs::write(
+        config_dir.join("config.toml"),
+        "preset = \"PMS\"\n[storage]\nhistoryLog = true\n",
+    )
+    .expect("write config");
+
+    for model in ["claude-opus-4", "claude-opus-4", "claude-sonnet-4"] {
+        let payload = format!(
+            r#"{{"session_id":"history-session-1","cwd":"/tmp","model":{{"id":"{model}"}}}}"#
+        );
+        Command::cargo_bin("claude-code-statusline-pro")
+            .expect("binary available")
+            .env("HOME", temp_home.path())
+            .env_remove("STATUSLINE_STORAGE_PATH")
+            .arg("--no-colors")
+            .write_stdin(payload)
+            .assert()
+            .success();
+    }
+
+    Command::cargo_bin("claude-code-statusline-pro")
+        .expect("binary available")
+        .env("HOME", temp_home.path())
+        .env_remove("STATUSLINE_STORAGE_PATH")
+        .args(["history", "history-sess"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("(2 条)"))
+        .stdout(predicate::str::contains("claude-sonnet-4"));
+}
+
+#[cfg(not(feature = "storage"))]
+#[test]
+#[allow(deprecated)]
+fn cli_history_is_compiled_out() {
+    Command::cargo_bin("claude-code-statusline-pro")
+        .expect("binary available")
+        .arg("--help")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("usage"))
+        .stdout(predicate::str::contains("history").not())
+        .stdout(predicate::str::contains("sessions").not());
+
+    Command::cargo_bin("claude-code-statusline-pro")
+        .expect("binary available")
+        .args(["history", "history-sess"])
+        .assert()
+        .failure();
+}
+
+#[test]
+#[allow(deprecated)]
+fn cli_usage_reads_transcripts_from_claude_config_dir() {
+    let temp_home = tempdir().expect("create temp home");
+    let claude_dir = temp_home.path().join("claude-config");
+    let project_dir = claude_dir.join("projects").join("-tmp-demo");
+    fs::create_dir_all(&project_dir).expect("create transcript dir");
+    fs::write(
+        project_dir.join("session.jsonl"),
+        r#"{"type":"assistant","timestamp":"2026-01-02T03:04:05Z","message":{"model":"claude-opus-4","usage":{"input_tokens":1200,"output_tokens":34}}}"#,
+    )
+    .expect("write transcript");
+
+    // 存储目录另设,transcript 仍从 Claude Code 的配置目录读取
+    Command::cargo_bin("claude-code-statusline-pro")
+        .expect("binary available")
+        .env("HOME", temp_home.path())
+        .env("CLAUDE_CONFIG_DIR", &claude_dir)
+        .env("STATUSLINE_STORAGE_PATH", temp_home.path().join("storage"))
+        .args(["usage", "--source", "transcripts"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("文件 1"))
+        .stdout(predicate::str::contains("没有可统计的数据").not());
+}
+
+#[test]
+#[allow(deprecated)]
+fn cli_hash_prints_project_id() {
+    Command::cargo_bin("claude-code-statusline-pro")
+        .expect("binary available")
+        .args(["hash", r"c:\Users\alice\project\"])
+        .assert()
+        .success()
+        .stdout("c--Users-alice-project\n");
+
+    let temp_dir = tempdir().expect("create temp dir");
+    let target = temp_dir.path().join("real-project");
+    fs::create_dir_all(&target).expect("create project dir");
+    Command::cargo_bin("claude-code-statusline-pro")
+        .expect("binary available")
+        .arg("hash")
+        .arg(&target)
+        .assert()
+        .success()
+        .stdout(format!(
+            "{}\n",
+            ProjectResolver::hash_path(target.to_str().expect("utf-8 path"))
+        ));
+}
+
+#[test]
+#[allow(deprecated)]
+fn cli_rejects_unknown_preset_entries() {
+    let temp_home = tempdir().expect("create temp home");
+    Command::cargo_bin("claude-code-statusline-pro")
+        .expect("binary available")
+        .env("HOME", temp_home.path())
+        .args(["--mock", "dev", "--preset", "project,weather"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("weather"))
+        .stderr(predicate::str::contains("P=project"));
+
+    Command::cargo_bin("claude-code-statusline-pro")
+        .expect("binary available")
+        .env("HOME", temp_home.path())
+        .args(["--mock", "dev", "--no-colors", "--preset", "model,project"])
+        .assert()
+        .success()
+        .stdout(predicate::str::is_empty().not());
+}
+
+#[test]
+#[allow(deprecated)]
+fn cli_components_override_order_for_one_invocation() {
+    let temp_home = tempdir().expect("create temp home");
+    let config_dir = temp_home.path().join(".claude").join("statusline-pro");
+    fs::create_dir_all(&config_dir).expect("create config dir");
+    let config_path = config_dir.join("config.toml");
+    fs::write(&config_path, "preset = \"PMBT\"\n").expect("write config");
+    let payload = r#"{"session_id":"components-1","cwd":"/tmp/components-demo","model":{"id":"claude-opus-4"}}"#;
+
+    let run = |args: &[&str]| {
+        Command::cargo_bin("claude-code-statusline-pro")
+            .expect("binary available")
+            .env("HOME", temp_home.path())
+            .env_remove("STATUSLINE_STORAGE_PATH")
+            .args(["--no-colors", "--no-icons", "--no-emoji"])
+            .args(args)
+            .write_stdin(payload)
+            .assert()
+    };
+    run(&["--components", "project"])
+        .success()
+        .stdout(predicate::str::contains("components-demo"))
+        .stdout(predicate::str::contains("opus").not());
+    run(&[])
+        .success()
+        .stdout(predicate::str::contains("components-demo"))
+        .stdout(predicate::str::contains("Opus").or(predicate::str::contains("opus")));
+    assert_eq!(
+        fs::read_to_string(&config_path).expect("read config"),
+        "preset = \"PMBT\"\n"
+    );
+
+    run(&["--components", "project,weather"])
+        .failure()
+        .stderr(predicate::str::contains("weather"));
+    run(&["--components", "project", "--preset", "PM"]).failure();
+}
+
+#[test]
+#[allow(deprecated)]
+fn cli_render_selects_config_profile() {
+    let temp_home = tempdir().expect("create temp home");
+    let config_dir = temp_home.path().join(".claude").join("statusline-pro");
+    fs::create_dir_all(&config_dir).expect("create config dir");
+    fs::write(
+        config_dir.join("config.toml"),
+        "preset = \"PM\"\n\n[profiles.minimal]\npreset = \"P\"\n",
+    )
+    .expect("write config");
+    let payload =
+        r#"{"session_id":"profile-1","cwd":"/tmp/profile-demo","model":{"id":"claude-opus-4"}}"#;
+
+    let run = |args: &[&str]| {
+        Command::cargo_bin("claude-code-statusline-pro")
+            .expect("binary available")
+            .env("HOME", temp_home.path())
+            .env_remove("STATUSLINE_STORAGE_PATH")
+            .args(["--no-colors", "--no-icons", "--no-emoji"])
+            .args(args)
+            .write_stdin(payload)
+            .assert()
+    };
+    run(&["render"])
+        .success()
+        .stdout(predicate::str::contains("profile-demo"))
+        .stdout(predicate::str::contains("Opus").or(predicate::str::contains("opus")));
+    run(&["render", "--profile", "minimal"])
+        .success()
+        .stdout(predicate::str::contains("profile-demo"))
+        .stdout(predicate::str::contains("Opus").not())
+        .stdout(predicate::str::contains("opus").not());
+    run(&["render", "--profile", "wide"])
+        .failure()
+        .stderr(predicate::str::contains("minimal"));
+}
+
+#[test]
+#[allow(deprecated)]
+fn cli_render_profile_preset_overrides_template_order() {
+    let temp_home = tempdir().expect("create temp home");
+    let config_dir = temp_home.path().join(".claude").join("statusline-pro");
+    fs::create_dir_all(&config_dir).expect("create config dir");
+    fs::copy(
+        concat!(env!("CARGO_MANIFEST_DIR"), "/configs/config.template.toml"),
+        config_dir.join("config.toml"),
+    )
+    .expect("copy template");
+    let payload = r#"{"session_id":"profile-2","cwd":"/tmp/profile-demo","model":{"id":"claude-opus-4"},"cost":{"total_cost_usd":1.23}}"#;
+
+    let run = |args: &[&str]| {
+        Command::cargo_bin("claude-code-statusline-pro")
+            .expect("binary available")
+            .env("HOME", temp_home.path())
+            .env_remove("STATUSLINE_STORAGE_PATH")
+            .env_remove("CLAUDE_CONFIG_DIR")
+            .args(["--no-colors", "--no-icons", "--no-emoji"])
+            .args(args)
+            .write_stdin(payload)
+            .assert()
+    };
+    // 模板写了完整的 components.order,配置档只换 preset 也要生效
+    run(&["render"])
+        .success()
+        .stdout(predicate::str::contains("$1.23"));
+    run(&["render", "--profile", "minimal"])
+        .success()
+        .stdout(predicate::str::contains("profile-demo"))
+        .stdout(predicate::str::contains("$1.23").not());
+}
@@ -2,7 +2,11 @@
 
 use anyhow::Result;
 use claude_code_statusline_pro::{
-    config::{Config, ConfigLoader},
+    components::{
+        Component, ComponentFactory, ComponentOutput, ComponentRegistry, RenderContext,
+        BUILTIN_COMPONENTS,
+    },
+    config::{AutoDetect, BaseComponentConfig, Config, ConfigLoader},
     core::{
         generator::{GeneratorOptions, StatuslineGenerator},
         CostInfo, InputData, ModelInfo,
@@ -309,6 +313,33 @@ async fn test_special_characters_in_branch_name() -> Result<()> {
     Ok(())
 }
 
+#[tokio::test]
+async fn test_control_sequences_in_input_are_neutralized() -> Result<()> {
+    let input = InputData {
+        cwd: Some("/tmp/evil\x1b]0;pwned\x07\nproject".to_string()),
+        model: Some(ModelInfo {
+            id: Some("claude-opus-4".to_string()),
+            display_name: Some("Opus\x1b[2J".to_string()),
+        }),
+        ..Default::default()
+    };
+
+    let mut config = Config::default();
+    config.style.enable_colors = AutoDetect::Bool(false);
+    config.preset = Some("PM".to_string());
+    let mut generator = StatuslineGenerator::new(config, GeneratorOptions::default());
+
+    let result = generator.generate(input).await?;
+    assert!(
+        result.contains("evil\u{241b}]0;pwned project"),
+        "{result:?}"
+    );
+    assert!(!result.contains("\x1b]0;"), "{result:?}");
+    assert!(!result.contains('\x07'), "{result:?}");
+    assert!(!result.contains("\x1b[2J"), "{result:?}");
+    Ok(())
+}
+
 #[tokio::test]
 async fn test_unicode_in_branch_name() -> Result<()> {
     let input = InputData {
@@ -536,3 +567,115 @@ async fn test_all_presets() -> Result<()> {
 
     Ok(())
 }
+
+struct HostnameComponent;
+
+#[async_trait::async_trait]
+impl Component for HostnameComponent {
+    fn name(&self) -> &'static str {
+        "hostname"
+    }
+
+    fn is_enabled(&self, _ctx: &RenderContext) -> bool {
+        true
+    }
+
+    async fn render(&self, _ctx: &RenderContext) -> ComponentOutput {
+        ComponentOutput::new("build-box")
+    }
+
+    fn base_config(&self, _ctx: &RenderContext) -> Option<&BaseComponentConfig> {
+        None
+    }
+}
+
+struct HostnameFactory;
+
+impl ComponentFactory for HostnameFactory {
+    fn create(&self, _config: &Config) -> Box<dyn Component> {
+        Box::new(HostnameComponent)
+    }
+
+    fn name(&self) -> &'static str {
+        "hostname"
+    }
+}
+
+#[tokio::test]
+async fn test_registered_custom_component_follows_order() -> Result<()> {
+    let mut config = Config {
+        theme: "classic".to_string(),
+        ..Config::default()
+    };
+    config.style.enable_colors = AutoDetect::Bool(false);
+    config.components.order = vec!["hostname".to_string(), "model".to_string()];
+
+    let mut registry = ComponentRegistry::default();
+    registry.register(HostnameFactory);
+    let options = GeneratorOptions {
+        preview_mode: true,
+        ..GeneratorOptions::default()
+    };
+    let mut generator = StatuslineGenerator::with_registry(config, options, registry);
+    assert!(generator.registry().contains("hostname"));
+
+    let input = InputData {
+        model: Some(ModelInfo {
+            id: Some("claude-sonnet-4".to_string()),
+            display_name: Some("Sonnet 4".to_string()),
+        }),
+        ..Default::default()
+    };
+    let result = generator.generate(input).await?;
+    let host = result.find("build-box");
+    let model = result.find("claude-sonnet-4");
+    assert!(
+        matches!((host, model), (Some(host), Some(model)) if host < model),
+        "result={result}"
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_ascii_only_output_is_pure_ascii() -> Result<()> {
+    let input = InputData {
+        session_id: Some("ascii-session".to_string()),
+        model: Some(ModelInfo {
+            id: Some("claude-opus-4-1".to_string()),
+            display_name: Some("Opus 4.1 · 思考".to_string()),
+        }),
+        git_branch: Some("feature/ünïcode-分支".to_string()),
+        cwd: Some("/tmp/项目".to_string()),
+        extra: serde_json::json!({
+            "__mock__": {
+                "tokensUsage": {
+                    "context_used": 150_000u64
+                }
+            }
+        }),
+        ..Default::default()
+    };
+
+    for theme in ["classic", "powerline", "capsule"] {
+        let mut config = Config {
+            theme: theme.to_string(),
+            ..Config::default()
+        };
+        config.terminal.ascii_only = true;
+        config.style.enable_colors = AutoDetect::Bool(true);
+        config.components.order = BUILTIN_COMPONENTS.iter().map(ToString::to_string).collect();
+        config.components.tokens.progress_style = "braille".to_string();
+        config.components.usage.currency = "CNY".to_string();
+
+        let options = GeneratorOptions {
+            preview_mode: true,
+            update_throttling: false,
+            ..GeneratorOptions::default()
+        };
+        let mut generator = StatuslineGenerator::new(config, options);
+        let result = generator.generate(input.clone()).await?;
+        assert!(!result.is_empty(), "theme={theme}");
+        assert!(result.is_ascii(), "theme={theme} result={result:?}");
+    }
+    Ok(())
+}
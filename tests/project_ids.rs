@@ -0,0 +1,65 @@
+//! Project ID compatibility corpus for `tests/fixtures/project_ids.json`.
+//!
+//! 项目 ID 决定 `~/.claude/projects/<id>/` 下的配置与会话快照,TypeScript 版本与
+//! 本实现共用这些目录。fixture 中的 ID 是两边必须一致的结果:修改路径编码时
+//! 这里失败,意味着已有用户的配置和会话会"消失",不要直接改 fixture。
+//!
+//! 语料中的路径在测试机器上都不存在,canonicalize 失败后按原样编码,
+//! 因此在任何平台上结果相同。
+
+use std::path::Path;
+
+use anyhow::Result;
+use claude_code_statusline_pro::storage::{ProjectId, ProjectResolver};
+use pretty_assertions::assert_eq;
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize)]
+struct Case {
+    platform: String,
+    path: String,
+    id: String,
+}
+
+fn corpus() -> Result<Vec<Case>> {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/project_ids.json");
+    Ok(serde_json::from_str(&std::fs::read_to_string(path)?)?)
+}
+
+#[test]
+fn project_ids_match_the_shared_corpus() -> Result<()> {
+    let cases = corpus()?;
+    for platform in ["macos", "linux", "windows", "unc", "unicode"] {
+        assert!(
+            cases.iter().any(|case| case.platform == platform),
+            "corpus has no {platform} paths"
+        );
+    }
+
+    let mismatches: Vec<String> = cases
+        .iter()
+        .filter_map(|case| {
+            let actual = ProjectId::from_path(&case.path);
+            (actual != case.id.as_str()).then(|| {
+                format!(
+                    "[{}] {:?}: expected {:?}, got {:?}",
+                    case.platform,
+                    case.path,
+                    case.id,
+                    actual.as_str()
+                )
+            })
+        })
+        .collect();
+    assert_eq!(mismatches, Vec::<String>::new());
+    Ok(())
+}
+
+#[test]
+fn legacy_resolver_helpers_agree_with_project_id() -> Result<()> {
+    for case in corpus()? {
+        assert_eq!(ProjectResolver::hash_path(&case.path), case.id);
+        assert_eq!(ProjectResolver::hash_global_path(&case.path), case.id);
+    }
+    Ok(())
+}
@@ -0,0 +1,56 @@
+//! Golden tests for `tests/fixtures/render/*`.
+//!
+//! After an intentional rendering change, regenerate the goldens with
+//! `cargo run -- --render-fixtures` and review the diff.
+
+use std::path::Path;
+
+use anyhow::Result;
+use claude_code_statusline_pro::core::fixtures;
+use pretty_assertions::assert_eq;
+
+#[tokio::test]
+async fn render_fixtures_match_goldens() -> Result<()> {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR")).join(fixtures::DEFAULT_FIXTURES_DIR);
+    let dirs = fixtures::discover(&root)?;
+    assert!(!dirs.is_empty(), "no fixtures found in {}", root.display());
+
+    // NO_COLOR / CLICOLOR_FORCE 优先级高于配置,对应的 golden 无法复现时跳过
+    let env_set = |name: &str| std::env::var_os(name).is_some_and(|value| !value.is_empty());
+    let compare_ansi = !env_set("NO_COLOR");
+    let compare_plain = !env_set("CLICOLOR_FORCE");
+    if !compare_ansi {
+        eprintln!("NO_COLOR is set, skipping ANSI golden comparison");
+    }
+    if !compare_plain {
+        eprintln!("CLICOLOR_FORCE is set, skipping plain golden comparison");
+    }
+
+    for dir in &dirs {
+        let fixture = fixtures::load(dir)?;
+        let output = fixtures::render(&fixture).await?;
+        let Some(expected) = fixtures::read_goldens(dir) else {
+            panic!(
+                "fixture '{}' has no goldens, run `cargo run -- --render-fixtures`",
+                fixture.name
+            );
+        };
+
+        if compare_plain {
+            assert_eq!(
+                expected.plain, output.plain,
+                "plain output of fixture '{}' changed",
+                fixture.name
+            );
+        }
+        if compare_ansi {
+            assert_eq!(
+                expected.ansi, output.ansi,
+                "ANSI output of fixture '{}' changed",
+                fixture.name
+            );
+        }
+    }
+
+    Ok(())
+}
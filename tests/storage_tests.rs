@@ -1,10 +1,14 @@
+#![cfg(feature = "storage")]
+
 use claude_code_statusline_pro::{
+    components::{Component, ModelComponent, RenderContext, TerminalCapabilities, TokensComponent},
     config::Config,
+    core::{InputData, ModelInfo},
     storage::{self, ProjectResolver, StorageManager},
 };
 use std::fs;
 use std::io::Write;
-use std::sync::OnceLock;
+use std::sync::{Arc, OnceLock};
 use tempfile::tempdir;
 use tokio::sync::Mutex;
 
@@ -187,3 +191,536 @@ async fn test_snapshot_updates_tokens_from_transcript() -> anyhow::Result<()> {
     drop(temp_dir);
     Ok(())
 }
+
+#[tokio::test]
+async fn test_error_streak_survives_snapshot_updates() -> anyhow::Result<()> {
+    let _guard = storage_test_mutex().lock().await;
+    let project_id = "streak-project";
+    let temp_dir = init_with_temp_storage(project_id).await?;
+
+    let session_id = "streak-session";
+    assert_eq!(storage::get_session_error_streak(session_id).await?, None);
+
+    let streak = storage::ErrorStreak {
+        count: 3,
+        last_error_uuid: Some("err-3".to_string()),
+    };
+    storage::update_session_error_streak(session_id, streak.clone()).await?;
+
+    let input = serde_json::json!({
+        "session_id": session_id,
+        "cost": { "total_cost_usd": 0.2 }
+    });
+    storage::update_session_snapshot(&input).await?;
+
+    assert_eq!(
+        storage::get_session_error_streak(session_id).await?,
+        Some(streak)
+    );
+    assert!((storage::get_session_cost_display(session_id).await? - 0.2).abs() < f64::EPSILON);
+
+    std::env::remove_var("STATUSLINE_STORAGE_PATH");
+    reset_project_resolver();
+    drop(temp_dir);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_critical_bell_rings_once_per_crossing() -> anyhow::Result<()> {
+    let _guard = storage_test_mutex().lock().await;
+    let temp_dir = init_with_temp_storage("bell-project").await?;
+
+    let mut config = Config::default();
+    config.components.tokens.critical_bell = true;
+    let component = TokensComponent::new((*config.components.tokens).clone());
+    let config = Arc::new(config);
+    let render = |context_used: u64| {
+        let mut input = InputData {
+            session_id: Some("bell-session".to_string()),
+            ..InputData::default()
+        };
+        input.extra = serde_json::json!({
+            "__mock__": { "tokensUsage": { "context_used": context_used } }
+        });
+        let ctx = RenderContext {
+            input: Arc::new(input),
+            config: Arc::clone(&config),
+            terminal: TerminalCapabilities::default(),
+            preview_mode: false,
+            transcript: None,
+            storage: None,
+        };
+        let component = &component;
+        async move { component.render(&ctx).await.text }
+    };
+
+    assert!(render(195_000).await.starts_with('\x07'));
+    assert!(!render(196_000).await.contains('\x07'));
+    assert!(!render(100_000).await.contains('\x07'));
+    assert!(render(197_000).await.starts_with('\x07'));
+
+    std::env::remove_var("STATUSLINE_STORAGE_PATH");
+    reset_project_resolver();
+    drop(temp_dir);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_model_switch_notice_persists_across_renders() -> anyhow::Result<()> {
+    let _guard = storage_test_mutex().lock().await;
+    let temp_dir = init_with_temp_storage("switch-project").await?;
+
+    let mut config = Config::default();
+    config.components.model.switch_notice_renders = 2;
+    let component = ModelComponent::new((*config.components.model).clone());
+    let config = Arc::new(config);
+    let render = |model_id: &str| {
+        let input = InputData {
+            session_id: Some("switch-session".to_string()),
+            model: Some(ModelInfo {
+                id: Some(model_id.to_string()),
+                display_name: None,
+            }),
+            ..InputData::default()
+        };
+        let ctx = RenderContext {
+            input: Arc::new(input),
+            config: Arc::clone(&config),
+            terminal: TerminalCapabilities::default(),
+            preview_mode: false,
+            transcript: None,
+            storage: None,
+        };
+        let component = &component;
+        async move { component.render(&ctx).await.text }
+    };
+
+    assert_eq!(render("claude-opus-4-1-20250805").await, "O4.1");
+    assert_eq!(
+        render("claude-sonnet-4-5-20250929").await,
+        "S4.5 ↻ Opus→Sonnet"
+    );
+    assert_eq!(
+        render("claude-sonnet-4-5-20250929").await,
+        "S4.5 ↻ Opus→Sonnet"
+    );
+    assert_eq!(render("claude-sonnet-4-5-20250929").await, "S4.5");
+
+    std::env::remove_var("STATUSLINE_STORAGE_PATH");
+    reset_project_resolver();
+    drop(temp_dir);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_resumed_session_continues_previous_history() -> anyhow::Result<()> {
+    let _guard = storage_test_mutex().lock().await;
+    let project_id = "resume-project";
+    let temp_dir = init_with_temp_storage(project_id).await?;
+
+    let transcript_path = temp_dir.path().join("resume.jsonl");
+    fs::write(
+        &transcript_path,
+        concat!(
+            r#"{"type":"assistant","uuid":"msg-1","message":{"usage":{"input_tokens":10,"output_tokens":5}}}"#,
+            "\n"
+        ),
+    )?;
+
+    storage::update_session_snapshot(&serde_json::json!({
+        "session_id": "resume-old",
+        "transcript_path": transcript_path,
+        "cost": { "total_cost_usd": 1.5 }
+    }))
+    .await?;
+
+    // 恢复后 session id 变了,但仍写入同一个 transcript,费用从零开始计
+    storage::update_session_snapshot(&serde_json::json!({
+        "session_id": "resume-new",
+        "transcript_path": transcript_path,
+        "cost": { "total_cost_usd": 0.25 }
+    }))
+    .await?;
+
+    let total_cost = storage::get_session_cost_display("resume-new").await?;
+    assert!(
+        (total_cost - 1.75).abs() < f64::EPSILON,
+        "total={total_cost}"
+    );
+
+    let snapshot = StorageManager::new()?
+        .get_snapshot("resume-new")?
+        .expect("resumed snapshot should exist");
+    assert_eq!(snapshot.meta.resumed_from.as_deref(), Some("resume-old"));
+    assert_eq!(
+        snapshot
+            .history
+            .tokens
+            .as_ref()
+            .and_then(|tokens| tokens.last_message_uuid.as_deref()),
+        Some("msg-1")
+    );
+
+    std::env::remove_var("STATUSLINE_STORAGE_PATH");
+    reset_project_resolver();
+    drop(temp_dir);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_resume_links_new_transcript_by_parent_uuid() -> anyhow::Result<()> {
+    let _guard = storage_test_mutex().lock().await;
+    let project_id = "resume-parent-project";
+    let temp_dir = init_with_temp_storage(project_id).await?;
+
+    let old_transcript = temp_dir.path().join("old.jsonl");
+    fs::write(
+        &old_transcript,
+        concat!(
+            r#"{"type":"assistant","uuid":"old-last","message":{"usage":{"input_tokens":1,"output_tokens":1}}}"#,
+            "\n"
+        ),
+    )?;
+    storage::update_session_snapshot(&serde_json::json!({
+        "session_id": "parent-old",
+        "transcript_path": old_transcript,
+        "cost": { "total_cost_usd": 2.0 }
+    }))
+    .await?;
+
+    let unrelated = temp_dir.path().join("unrelated.jsonl");
+    fs::write(&unrelated, "{\"type\":\"user\",\"parentUuid\":null}\n")?;
+    storage::update_session_snapshot(&serde_json::json!({
+        "session_id": "parent-unrelated",
+        "transcript_path": unrelated,
+        "cost": { "total_cost_usd": 0.1 }
+    }))
+    .await?;
+    let unrelated_cost = storage::get_session_cost_display("parent-unrelated").await?;
+    assert!((unrelated_cost - 0.1).abs() < f64::EPSILON);
+
+    let new_transcript = temp_dir.path().join("new.jsonl");
+    fs::write(
+        &new_transcript,
+        "{\"type\":\"user\",\"uuid\":\"new-1\",\"parentUuid\":\"old-last\"}\n",
+    )?;
+    storage::update_session_snapshot(&serde_json::json!({
+        "session_id": "parent-new",
+        "transcript_path": new_transcript,
+        "cost": { "total_cost_usd": 0.5 }
+    }))
+    .await?;
+
+    let total_cost = storage::get_session_cost_display("parent-new").await?;
+    assert!(
+        (total_cost - 2.5).abs() < f64::EPSILON,
+        "total={total_cost}"
+    );
+
+    std::env::remove_var("STATUSLINE_STORAGE_PATH");
+    reset_project_resolver();
+    drop(temp_dir);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_scrub_reapplies_latest_policy_to_existing_snapshots() -> anyhow::Result<()> {
+    let _guard = storage_test_mutex().lock().await;
+    let project_id = "scrub-project";
+    let temp_dir = init_with_temp_storage(project_id).await?;
+
+    storage::update_session_snapshot(&serde_json::json!({
+        "session_id": "scrub-session",
+        "cwd": "/home/me/secret-project",
+        "cost": { "total_cost_usd": 0.3, "input_tokens": 10 }
+    }))
+    .await?;
+
+    let manager = StorageManager::new()?;
+    let snapshot = manager
+        .get_snapshot("scrub-session")?
+        .expect("snapshot should exist");
+    assert_eq!(
+        snapshot.latest,
+        serde_json::json!({
+            "session_id": "scrub-session",
+            "cwd": "/home/me/secret-project",
+            "cost": { "total_cost_usd": 0.3 }
+        })
+    );
+
+    let mut settings = Config::default().storage;
+    settings.redact_keys.push("cwd".to_string());
+    let report = storage::scrub_sessions(&settings, true).await?;
+    assert_eq!(report.rewritten.len(), 1);
+    let unchanged = manager.get_snapshot("scrub-session")?.expect("snapshot");
+    assert!(
+        unchanged.latest.get("cwd").is_some(),
+        "dry-run must not write"
+    );
+
+    storage::scrub_sessions(&settings, false).await?;
+    let scrubbed = manager.get_snapshot("scrub-session")?.expect("snapshot");
+    assert!(scrubbed.latest.get("cwd").is_none());
+
+    settings.store_latest = false;
+    storage::scrub_sessions(&settings, false).await?;
+    let emptied = manager.get_snapshot("scrub-session")?.expect("snapshot");
+    assert!(emptied.latest.is_null());
+    assert!((emptied.history.cost.total.total_cost_usd - 0.3).abs() < f64::EPSILON);
+
+    let report = storage::scrub_sessions(&settings, false).await?;
+    assert!(report.rewritten.is_empty());
+
+    std::env::remove_var("STATUSLINE_STORAGE_PATH");
+    reset_project_resolver();
+    drop(temp_dir);
+    Ok(())
+}
+
+/// Backdate `path` past the age at which temp files count as abandoned
+fn make_old(path: &std::path::Path) -> anyhow::Result<()> {
+    let hour_ago = std::time::SystemTime::now() - std::time::Duration::from_secs(3600);
+    fs::File::options()
+        .write(true)
+        .open(path)?
+        .set_modified(hour_ago)?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_startup_recovers_interrupted_snapshot_writes() -> anyhow::Result<()> {
+    let _guard = storage_test_mutex().lock().await;
+    let project_id = "recovery-project";
+    let temp_dir = init_with_temp_storage(project_id).await?;
+
+    let sessions_dir = temp_dir
+        .path()
+        .join("projects")
+        .join(project_id)
+        .join("statusline-pro")
+        .join("sessions");
+
+    // 写入完成但 rename 前中断:临时文件完整,正式文件缺失
+    let mut pending = storage::SessionSnapshot::new("interrupted");
+    pending.history.cost.total.total_cost_usd = 1.25;
+    fs::write(
+        sessions_dir.join("interrupted.json.tmp"),
+        serde_json::to_string(&pending)?,
+    )?;
+    // 写到一半断电:临时文件被截断
+    fs::write(sessions_dir.join("torn.json.tmp"), "{\"meta\": {\"sess")?;
+    // 另一个进程刚写下、还没来得及 rename 的临时文件
+    fs::write(
+        sessions_dir.join("in-flight.json.tmp"),
+        serde_json::to_string(&storage::SessionSnapshot::new("in-flight"))?,
+    )?;
+    make_old(&sessions_dir.join("interrupted.json.tmp"))?;
+    make_old(&sessions_dir.join("torn.json.tmp"))?;
+
+    let config = Config::default();
+    storage::initialize_storage_with_settings(Some(project_id.to_string()), &config.storage)
+        .await?;
+
+    assert!(!sessions_dir.join("interrupted.json.tmp").exists());
+    assert!(!sessions_dir.join("torn.json.tmp").exists());
+    assert!(sessions_dir.join("in-flight.json.tmp").exists());
+    assert!(!sessions_dir.join("in-flight.json").exists());
+    let total_cost = storage::get_session_cost_display("interrupted").await?;
+    assert!(
+        (total_cost - 1.25).abs() < f64::EPSILON,
+        "total={total_cost}"
+    );
+
+    // 正式快照更新时,旧的临时文件直接丢弃
+    let mut stale = storage::SessionSnapshot::new("interrupted");
+    stale.meta.last_update_time = Some(chrono::DateTime::<chrono::Utc>::UNIX_EPOCH);
+    fs::write(
+        sessions_dir.join("interrupted.json.tmp"),
+        serde_json::to_string(&stale)?,
+    )?;
+    make_old(&sessions_dir.join("interrupted.json.tmp"))?;
+    let recovery = StorageManager::new()?.recover_temp_files()?;
+    assert_eq!(
+        recovery,
+        storage::TempRecovery {
+            completed: 0,
+            removed: 1
+        }
+    );
+    let total_cost = storage::get_session_cost_display("interrupted").await?;
+    assert!((total_cost - 1.25).abs() < f64::EPSILON);
+
+    std::env::remove_var("STATUSLINE_STORAGE_PATH");
+    reset_project_resolver();
+    drop(temp_dir);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_storage_stats_counts_and_repairs_snapshots() -> anyhow::Result<()> {
+    let _guard = storage_test_mutex().lock().await;
+    let project_id = "stats-project";
+    let temp_dir = init_with_temp_storage(project_id).await?;
+
+    storage::update_session_snapshot(&serde_json::json!({
+        "session_id": "stats-ok",
+        "cost": { "total_cost_usd": 0.4 }
+    }))
+    .await?;
+
+    let sessions_dir = temp_dir
+        .path()
+        .join("projects")
+        .join(project_id)
+        .join("statusline-pro")
+        .join("sessions");
+    // history.cost 类型错误,其余字段完好
+    fs::write(
+        sessions_dir.join("stats-damaged.json"),
+        r#"{"meta":{"session_id":"stats-damaged"},"history":{"cost":"oops","errors":{"count":2}}}"#,
+    )?;
+    fs::write(sessions_dir.join("stats-garbage.json"), "not json")?;
+
+    let settings = Config::default().storage;
+    let stats = storage::storage_stats(&settings, false).await?;
+    let project = stats
+        .projects
+        .iter()
+        .find(|project| project.project_id == project_id)
+        .expect("project should be listed");
+    assert_eq!(project.sessions, 3);
+    assert!(project.bytes > 0);
+    assert_eq!(project.parse_failures.len(), 2);
+    assert!(project.oldest.is_some() && project.newest.is_some());
+    assert!(project.repaired.is_empty());
+
+    let stats = storage::storage_stats(&settings, true).await?;
+    let project = stats
+        .projects
+        .iter()
+        .find(|project| project.project_id == project_id)
+        .expect("project should be listed");
+    assert_eq!(project.repaired.len(), 1);
+    assert_eq!(project.unrecoverable.len(), 1);
+
+    assert_eq!(
+        storage::get_session_error_streak("stats-damaged")
+            .await?
+            .map(|streak| streak.count),
+        Some(2)
+    );
+    let stats = storage::storage_stats(&settings, false).await?;
+    assert_eq!(stats.total_parse_failures(), 1);
+
+    std::env::remove_var("STATUSLINE_STORAGE_PATH");
+    reset_project_resolver();
+    drop(temp_dir);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_snapshot_updates_append_metrics_log() -> anyhow::Result<()> {
+    use claude_code_statusline_pro::storage::metrics::{self, MetricKind};
+    use claude_code_statusline_pro::storage::{usage_report::UsageSource, HookState};
+
+    let _guard = storage_test_mutex().lock().await;
+    let project_id = "metrics-project";
+    let temp_dir = init_with_temp_storage(project_id).await?;
+    let sessions_dir = temp_dir
+        .path()
+        .join("projects")
+        .join(project_id)
+        .join("statusline-pro")
+        .join("sessions");
+    let transcript_path = temp_dir.path().join("metrics-session.jsonl");
+    let assistant = |uuid: &str, input: u64| {
+        format!(
+            r#"{{"type":"assistant","uuid":"{uuid}","message":{{"usage":{{"input_tokens":{input},"output_tokens":5}}}}}}"#
+        )
+    };
+    fs::write(
+        &transcript_path,
+        format!("{}\n{}\n", assistant("m1", 10), assistant("m2", 20)),
+    )?;
+    let input = |cost: f64| {
+        serde_json::json!({
+            "session_id": "metrics-session",
+            "transcript_path": transcript_path,
+            "cost": { "total_cost_usd": cost }
+        })
+    };
+
+    storage::update_session_snapshot(&input(0.5)).await?;
+    // 没有新消息、成本不变:不产生事件
+    storage::update_session_snapshot(&input(0.5)).await?;
+    let mut file = fs::OpenOptions::new().append(true).open(&transcript_path)?;
+    writeln!(file, "{}", assistant("m3", 40))?;
+    storage::update_session_snapshot(&input(0.75)).await?;
+    // 间隔内的更新只追加检查点,快照文件不重写,读取时仍能看到最新计数
+    let stored = fs::read_to_string(sessions_dir.join("metrics-session.json"))?;
+    let stored: serde_json::Value = serde_json::from_str(&stored)?;
+    assert_eq!(
+        stored["history"]["cost"]["total"]["total_cost_usd"].as_f64(),
+        Some(0.5)
+    );
+    let history = storage::get_session_history("metrics-session")
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("missing session history"))?;
+    assert!((history.cost.total.total_cost_usd - 0.75).abs() < 1e-9);
+    assert_eq!(history.tokens.map(|tokens| tokens.input), Some(40));
+
+    storage::update_session_hook_state(
+        "metrics-session",
+        HookState {
+            active: ["context_critical".to_string()].into(),
+            ..HookState::default()
+        },
+    )
+    .await?;
+
+    let events = metrics::read_metrics(&metrics::metrics_path(&sessions_dir, "metrics-session"))?;
+    let kinds: Vec<MetricKind> = events.into_iter().map(|event| event.kind).collect();
+    assert_eq!(kinds.len(), 6);
+    assert!(matches!(
+        kinds[0],
+        MetricKind::Tokens {
+            input: 30,
+            output: 10,
+            messages: 2,
+            ..
+        }
+    ));
+    assert!(matches!(
+        kinds[3],
+        MetricKind::Cost { delta_usd, total_usd } if (delta_usd - 0.25).abs() < 1e-9 && (total_usd - 0.75).abs() < 1e-9
+    ));
+    assert!(
+        matches!(&kinds[4], MetricKind::Checkpoint(counters) if (counters.cost.total.total_cost_usd - 0.75).abs() < 1e-9)
+    );
+    assert_eq!(
+        kinds[5],
+        MetricKind::Status {
+            condition: "context_critical".to_string(),
+            active: true
+        }
+    );
+
+    let settings = Config::default().storage;
+    let stats = storage::storage_stats(&settings, false).await?;
+    assert_eq!(stats.total_metrics_events(), 6);
+    let report = storage::usage_report(
+        &settings,
+        &std::collections::HashMap::new(),
+        UsageSource::Metrics,
+    )
+    .await?;
+    assert_eq!(report.total.input_tokens, 70);
+    assert_eq!(report.total.entries, 3);
+    assert!((report.total.cost_usd - 0.75).abs() < 1e-9);
+
+    std::env::remove_var("STATUSLINE_STORAGE_PATH");
+    reset_project_resolver();
+    drop(temp_dir);
+    Ok(())
+}